@@ -0,0 +1,288 @@
+//! Authority kinds
+
+use crate::{
+    error::TokenError,
+    instruction::{ethereum_authorization_message, MAX_SIGNERS},
+    state::{
+        Account, EthereumAddress, EthereumOwner, Multisig, MultisigProposal, WeightedMultisig,
+    },
+};
+use solana_program::{
+    account_info::AccountInfo,
+    clock::{Clock, UnixTimestamp},
+    entrypoint::ProgramResult,
+    program_error::ProgramError,
+    program_option::COption,
+    program_pack::Pack,
+    pubkey::Pubkey,
+    secp256k1_program,
+    sysvar::{self, Sysvar},
+};
+use std::{convert::TryInto, mem::size_of};
+
+/// The kind of authority an account holds, resolved from the account's owner and data length.
+/// Every owner, delegate and authority the program checks is validated through its kind.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum AuthorityKind {
+    /// A key that must sign the transaction itself. Program derived addresses signing through
+    /// `invoke_signed` are of this kind.
+    Signer,
+    /// A multisignature account, which needs `m` of its `n` signers to sign the transaction.
+    Multisig,
+    /// A weighted multisignature account, which needs the weights of its signers that sign the
+    /// transaction to total at least its threshold.
+    WeightedMultisig,
+    /// An Ethereum owner, which needs a secp256k1 signature from its address over the
+    /// instruction's authorization message.
+    EthereumOwner,
+    /// A token account's session key, which must sign the transaction itself and may only act
+    /// for the account's owner within the session's scope.
+    SessionKey,
+}
+impl AuthorityKind {
+    /// Resolves the kind of authority held by an account
+    pub fn resolve(program_id: &Pubkey, account_info: &AccountInfo) -> Self {
+        if program_id != account_info.owner {
+            return Self::Signer;
+        }
+        let data_len = account_info.data_len();
+        if data_len == Multisig::get_packed_len() {
+            Self::Multisig
+        } else if data_len == WeightedMultisig::get_packed_len() {
+            Self::WeightedMultisig
+        } else if data_len == EthereumOwner::get_packed_len() {
+            Self::EthereumOwner
+        } else {
+            Self::Signer
+        }
+    }
+
+    /// Resolves the kind of authority held over a token account by `account_info`, which is either
+    /// the account's session key or an authority of the kind it holds itself
+    pub fn resolve_for_account(
+        program_id: &Pubkey,
+        account: &Account,
+        account_info: &AccountInfo,
+    ) -> Self {
+        if account_info.key != &account.owner
+            && account.session_key == COption::Some(*account_info.key)
+        {
+            return Self::SessionKey;
+        }
+        Self::resolve(program_id, account_info)
+    }
+
+    /// Validates that the authority held by `account_info` approves the current instruction,
+    /// given the signer accounts that follow it and the proposal carrying the instruction, if any
+    pub fn validate(
+        self,
+        program_id: &Pubkey,
+        account_info: &AccountInfo,
+        signers: &[AccountInfo],
+        proposal: Option<&MultisigProposal>,
+    ) -> ProgramResult {
+        match self {
+            Self::Signer | Self::SessionKey => {
+                if !account_info.is_signer {
+                    return Err(ProgramError::MissingRequiredSignature);
+                }
+                Ok(())
+            }
+            Self::Multisig => validate_multisig(account_info, signers, proposal),
+            Self::WeightedMultisig => validate_weighted_multisig(account_info, signers),
+            Self::EthereumOwner => validate_ethereum_owner(program_id, account_info, signers),
+        }
+    }
+}
+
+/// An operation a session key performs for a token account's owner
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SessionUse<'a> {
+    /// The `SESSION_SCOPE_*` bit of the operation
+    pub scope: u8,
+    /// The account receiving the tokens, if the operation is a transfer
+    pub destination: Option<&'a Pubkey>,
+    /// The amount of tokens the operation moves
+    pub amount: u64,
+    /// Whether the amount is spent from the session's remaining amount
+    pub debit: bool,
+}
+impl SessionUse<'_> {
+    /// Checks the operation against the scope of the account's session, which must not have
+    /// expired, and spends the amount from the session's remaining amount if debited
+    pub fn apply(&self, account: &mut Account, unix_timestamp: UnixTimestamp) -> ProgramResult {
+        if unix_timestamp >= account.session_expiry {
+            return Err(TokenError::SessionKeyExpired.into());
+        }
+        if account.session_scope & self.scope == 0 {
+            return Err(TokenError::SessionScopeExceeded.into());
+        }
+        let session_destinations = account.session_destinations();
+        if !session_destinations.is_empty()
+            && !self
+                .destination
+                .is_some_and(|destination| session_destinations.contains(destination))
+        {
+            return Err(TokenError::SessionScopeExceeded.into());
+        }
+        if account.session_remaining_amount < self.amount {
+            return Err(TokenError::SessionScopeExceeded.into());
+        }
+        if self.debit {
+            account.session_remaining_amount = account
+                .session_remaining_amount
+                .checked_sub(self.amount)
+                .ok_or(TokenError::Overflow)?;
+        }
+        Ok(())
+    }
+}
+
+/// Matches the signer accounts against a multisignature's signer keys, each key at most once
+fn match_signers(
+    keys: &[Pubkey],
+    signers: &[AccountInfo],
+) -> Result<[bool; MAX_SIGNERS], ProgramError> {
+    let mut matched = [false; MAX_SIGNERS];
+    for signer in signers.iter() {
+        for (position, key) in keys.iter().enumerate() {
+            if key == signer.key && !matched[position] {
+                if !signer.is_signer {
+                    return Err(ProgramError::MissingRequiredSignature);
+                }
+                matched[position] = true;
+            }
+        }
+    }
+    Ok(matched)
+}
+
+fn validate_multisig(
+    multisig_info: &AccountInfo,
+    signers: &[AccountInfo],
+    proposal: Option<&MultisigProposal>,
+) -> ProgramResult {
+    // The approvals of a proposal under execution stand in for its multisignature's signers
+    if proposal.is_some_and(|proposal| proposal.multisig == *multisig_info.key) {
+        return Ok(());
+    }
+    let multisig = Multisig::unpack(&multisig_info.data.borrow())?;
+    let matched = match_signers(&multisig.signers[0..multisig.n as usize], signers)?;
+    let num_signers = matched.iter().filter(|matched| **matched).count();
+    if num_signers < multisig.m as usize {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    Ok(())
+}
+
+fn validate_weighted_multisig(
+    multisig_info: &AccountInfo,
+    signers: &[AccountInfo],
+) -> ProgramResult {
+    let multisig = WeightedMultisig::unpack(&multisig_info.data.borrow())?;
+    let matched = match_signers(&multisig.signers[0..multisig.n as usize], signers)?;
+    let weight: u16 = matched
+        .iter()
+        .zip(multisig.weights.iter())
+        .filter(|(matched, _)| **matched)
+        .map(|(_, weight)| *weight as u16)
+        .sum();
+    if weight < multisig.threshold {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    Ok(())
+}
+
+/// A secp256k1 program instruction preceding the current one must have verified the owner
+/// address's signature over the current instruction's authorization message, whose `valid_until`
+/// timestamp must not have passed. The processor advances the owner's nonce once the instruction
+/// succeeds.
+fn validate_ethereum_owner(
+    program_id: &Pubkey,
+    owner_account_info: &AccountInfo,
+    signers: &[AccountInfo],
+) -> ProgramResult {
+    let ethereum_owner = EthereumOwner::unpack(&owner_account_info.data.borrow())?;
+    let instructions_info = signers
+        .iter()
+        .find(|account_info| sysvar::instructions::check_id(account_info.key))
+        .ok_or(ProgramError::NotEnoughAccountKeys)?;
+
+    let current_index = sysvar::instructions::load_current_index_checked(instructions_info)?;
+    let current_instruction = sysvar::instructions::load_instruction_at_checked(
+        current_index as usize,
+        instructions_info,
+    )?;
+    if current_instruction.program_id != *program_id {
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    let mut valid_until = None;
+    for index in 0..current_index {
+        let instruction =
+            sysvar::instructions::load_instruction_at_checked(index as usize, instructions_info)?;
+        if instruction.program_id != secp256k1_program::id() {
+            continue;
+        }
+        valid_until = secp256k1_signed_messages(&instruction.data, index, &ethereum_owner.address)
+            .find_map(|message| {
+                // The authorization message ends with its `valid_until` timestamp
+                let valid_until_start = message.len().checked_sub(size_of::<UnixTimestamp>())?;
+                let valid_until =
+                    UnixTimestamp::from_le_bytes(message[valid_until_start..].try_into().ok()?);
+                let expected = ethereum_authorization_message(
+                    &current_instruction,
+                    ethereum_owner.nonce,
+                    valid_until,
+                );
+                if message == expected.as_slice() {
+                    Some(valid_until)
+                } else {
+                    None
+                }
+            });
+        if valid_until.is_some() {
+            break;
+        }
+    }
+    let valid_until = valid_until.ok_or(TokenError::MissingAuthorizationSignature)?;
+    if Clock::get()?.unix_timestamp > valid_until {
+        return Err(TokenError::AuthorizationNotValid.into());
+    }
+
+    Ok(())
+}
+
+/// Returns the messages signed by `address` that the secp256k1 program instruction at `index`
+/// verifies in its own data
+fn secp256k1_signed_messages<'a>(
+    data: &'a [u8],
+    index: u16,
+    address: &'a EthereumAddress,
+) -> impl Iterator<Item = &'a [u8]> {
+    // Each signature is described by eleven bytes of offsets following a one byte header:
+    // signature offset (u16) and instruction index (u8), Ethereum address offset (u16) and
+    // instruction index (u8), message offset (u16), size (u16) and instruction index (u8).
+    const HEADER_LEN: usize = 1;
+    const OFFSETS_LEN: usize = 11;
+    let num_signatures = data.first().copied().unwrap_or(0) as usize;
+    (0..num_signatures).filter_map(move |i| {
+        let start = HEADER_LEN + i * OFFSETS_LEN;
+        let offsets = data.get(start..start + OFFSETS_LEN)?;
+        let read = |at: usize| u16::from_le_bytes([offsets[at], offsets[at + 1]]) as usize;
+        let own_index = index as usize;
+        if offsets[2] as usize != own_index
+            || offsets[5] as usize != own_index
+            || offsets[10] as usize != own_index
+        {
+            return None;
+        }
+        let address_offset = read(3);
+        let message_offset = read(6);
+        let message_size = read(8);
+        if data.get(address_offset..address_offset + 20) != Some(&address[..]) {
+            return None;
+        }
+        data.get(message_offset..message_offset + message_size)
+    })
+}
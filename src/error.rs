@@ -64,6 +64,138 @@ pub enum TokenError {
     /// Mint decimals mismatch between the client and mint
     #[error("The provided decimals value different from the Mint decimals")]
     MintDecimalsMismatch,
+    /// Minting has been paused by the mint pauser
+    #[error("Minting is paused")]
+    MintingPaused,
+    /// This token mint has no pauser.
+    #[error("This token mint cannot pause minting")]
+    MintCannotPause,
+    /// Minter allowance exceeded by the requested amount
+    #[error("Minter allowance exceeded")]
+    MinterAllowanceExceeded,
+    /// No authority change has been proposed for the requested authority type.
+    #[error("No pending authority")]
+    NoPendingAuthority,
+    /// Mint-level authority changes must be queued and wait out the Mint's authority timelock.
+    #[error("Authority change is timelocked")]
+    AuthorityChangeTimelocked,
+    /// Another authority change is already queued for this mint.
+    #[error("An authority change is already queued")]
+    AuthorityChangeQueued,
+    /// The authority timelock may not be decreased.
+    #[error("Authority timelock may only be increased")]
+    InvalidAuthorityTimelock,
+    /// The account has no guardians registered.
+    #[error("Account has no guardians")]
+    NoGuardians,
+    /// The delegate's approval has expired.
+    #[error("Delegate approval expired")]
+    DelegateExpired,
+    /// The amount exceeds the delegate's per-transfer cap.
+    #[error("Delegate transfer cap exceeded")]
+    DelegateTransferCapExceeded,
+    /// The allowance was granted on a different token account.
+    #[error("Allowance account mismatch")]
+    AllowanceMismatch,
+    /// The allowance was granted by a previous owner of the token account.
+    #[error("Allowance was granted by a previous account owner")]
+    AllowanceOwnerChanged,
+    /// The account's current delegation differs from the expected one.
+    #[error("Unexpected delegation")]
+    UnexpectedDelegation,
+    /// The authorization's nonce does not match the owner's current nonce.
+    #[error("Invalid authorization nonce")]
+    InvalidNonce,
+    /// The current time is outside the authorization's validity window.
+    #[error("Authorization is outside its validity window")]
+    AuthorizationNotValid,
+    /// No signature by the owner over the authorization was verified.
+    #[error("Missing owner signature over the authorization")]
+    MissingAuthorizationSignature,
+    /// The session key has expired.
+    #[error("Session key expired")]
+    SessionKeyExpired,
+    /// The operation is outside the session key's scope.
+    #[error("Operation outside the session key's scope")]
+    SessionScopeExceeded,
+    /// The weight threshold is zero or exceeds the signers' total weight.
+    #[error("Invalid weight threshold")]
+    InvalidWeightThreshold,
+    /// The key is already one of the multisignature's signers.
+    #[error("Duplicate multisignature signer")]
+    DuplicateSigner,
+    /// The key is not one of the multisignature's signers.
+    #[error("Not a multisignature signer")]
+    SignerNotFound,
+    /// The proposal is not pending.
+    #[error("Proposal is not pending")]
+    ProposalNotPending,
+    /// The proposal has expired.
+    #[error("Proposal expired")]
+    ProposalExpired,
+    /// The signer has already approved the proposal.
+    #[error("Proposal already approved by the signer")]
+    AlreadyApproved,
+    /// An account does not match the one the proposal names.
+    #[error("Account does not match the proposal")]
+    ProposalAccountMismatch,
+    /// The multisignature requires more signers than it has.
+    #[error("Multisignature threshold exceeds its signers")]
+    ThresholdExceedsSigners,
+    /// The multisignature is listed among its own signers.
+    #[error("Multisignature cannot be its own signer")]
+    MultisigSelfSigner,
+    /// The account has an outstanding hold.
+    #[error("Account has an outstanding hold")]
+    HoldExists,
+    /// The account has no hold.
+    #[error("Account has no hold")]
+    HoldNotFound,
+    /// The hold has expired.
+    #[error("Hold expired")]
+    HoldExpired,
+    /// The outflow exceeds the account's spending limit.
+    #[error("Spending limit exceeded")]
+    SpendingLimitExceeded,
+    /// The destination is not on the source account's allowlist.
+    #[error("Destination not allowed")]
+    DestinationNotAllowed,
+    /// The allowlist holds the maximum number of destinations.
+    #[error("Destination allowlist is full")]
+    AllowlistFull,
+    /// The destination is already on the allowlist.
+    #[error("Destination already allowed")]
+    DestinationAlreadyAllowed,
+    /// The destination is not on the allowlist.
+    #[error("Destination not found on the allowlist")]
+    DestinationNotFound,
+    /// The allowlist does not belong to the account.
+    #[error("Allowlist does not match the account")]
+    AllowlistMismatch,
+    /// The destination would exceed the mint's maximum balance.
+    #[error("Maximum balance exceeded")]
+    MaxBalanceExceeded,
+    /// Trading of the mint has not started.
+    #[error("Trading has not started")]
+    TradingNotStarted,
+    /// The account's transfer cooldown has not elapsed.
+    #[error("Transfer cooldown has not elapsed")]
+    TransferCooldown,
+    /// An owner lacks a valid credential for the mint.
+    #[error("Owner lacks a valid credential")]
+    CredentialRequired,
+    /// The mint has no compliance authority.
+    #[error("Mint has no compliance authority")]
+    MintCannotIssueCredentials,
+    /// The amount is below the mint's minimum transfer amount.
+    #[error("Transfer amount below the mint's minimum")]
+    BelowMinimumTransfer,
+    /// The amount is below the smallest incoming transfer the destination accepts.
+    #[error("Transfer amount below the destination's minimum")]
+    BelowMinimumIncoming,
+    /// The account is too small to hold the extension data.
+    #[error("Account too small for extension data")]
+    ExtensionRequired,
 }
 impl From<TokenError> for ProgramError {
     fn from(e: TokenError) -> Self {
@@ -1,12 +1,22 @@
 //! Instruction types
 
-use crate::{check_program_account, error::TokenError};
+use crate::{
+    check_program_account,
+    error::TokenError,
+    get_allowance_address, get_credential_address, get_ethereum_owner_address, get_nonce_address,
+    state::{
+        EthereumAddress, TimelockedAuthority, MAX_PROPOSAL_ACCOUNTS, MAX_PROPOSAL_DATA_LEN,
+        MAX_SESSION_DESTINATIONS,
+    },
+};
+use num_enum::TryFromPrimitive;
 use solana_program::{
+    clock::UnixTimestamp,
     instruction::{AccountMeta, Instruction},
     program_error::ProgramError,
     program_option::COption,
     pubkey::Pubkey,
-    sysvar,
+    system_program, sysvar,
 };
 use std::convert::TryInto;
 use std::mem::size_of;
@@ -46,7 +56,8 @@ pub enum TokenInstruction {
     /// with the native mint then the token balance of the initialized account
     /// will be equal to the amount of SOL in the account. If this account is
     /// associated with another mint, that mint must be initialized before this
-    /// command can succeed.
+    /// command can succeed. If that mint carries the extension, so must the
+    /// account.
     ///
     /// The `InitializeAccount` instruction requires no signers and MUST be
     /// included within the same Transaction as the system program's
@@ -64,7 +75,8 @@ pub enum TokenInstruction {
     /// Transfers tokens from one account to another either directly or via a
     /// delegate.  If this account is associated with the native mint then equal
     /// amounts of SOL and Tokens will be transferred to the destination
-    /// account.
+    /// account. Tokens reserved by a hold cannot be transferred, nor more than
+    /// the account's spending limit allows.
     ///
     /// Accounts expected by this instruction:
     ///
@@ -78,6 +90,30 @@ pub enum TokenInstruction {
     ///   1. `[writable]` The destination account.
     ///   2. `[]` The source account's multisignature owner/delegate.
     ///   3. ..3+M `[signer]` M signer accounts.
+    ///
+    ///   * Allowance
+    ///   0. `[writable]` The source account.
+    ///   1. `[writable]` The destination account.
+    ///   2. `[writable]` The allowance.
+    ///   3. `[signer]` The allowance's spender.
+    ///   4. ..4+M `[signer]` M signer accounts, if the spender is a
+    ///      multisignature.
+    ///
+    /// An expired delegate approval is cleared and no longer lets the
+    /// delegate act. The time is read from the Clock sysvar if it follows the
+    /// authority accounts, or from the runtime otherwise.
+    ///
+    /// The account's session key may sign in place of the owner, within the
+    /// session's scope. The Clock sysvar must then follow the authority
+    /// accounts.
+    ///
+    /// If the accounts carry the extension and the tokens are not native, the
+    /// mint must follow the authority accounts, along with the Clock sysvar
+    /// while the mint has launch controls. If the source account has a
+    /// destination allowlist, the allowlist and the Clock sysvar must follow
+    /// them too. If the mint has a compliance authority, the source and
+    /// destination owners' credentials and the Clock sysvar must follow the
+    /// authority accounts.
     Transfer {
         /// The amount of tokens to transfer.
         amount: u64,
@@ -116,12 +152,26 @@ pub enum TokenInstruction {
     ///   1. `[writable]` The account to mint tokens to.
     ///   2. `[]` The mint's multisignature mint-tokens authority.
     ///   3. ..3+M `[signer]` M signer accounts.
+    ///
+    ///   * Minter
+    ///   0. `[writable]` The mint.
+    ///   1. `[writable]` The account to mint tokens to.
+    ///   2. `[writable]` The minter.
+    ///   3. `[signer]` The minter's authority.
+    ///   4. ..4+M `[signer]` M signer accounts, if the minter's authority is a
+    ///      multisignature.
+    ///
+    /// If the mint has a compliance authority, the destination owner's
+    /// credential and the Clock sysvar must follow the authority accounts.
     MintTo {
         /// The amount of new tokens to mint.
         amount: u64,
     },
     /// Burns tokens by removing them from an account.  `Burn` does not support
     /// accounts associated with the native mint, use `CloseAccount` instead.
+    /// If the mint has a burner, it may also burn from accounts owned by the
+    /// mint authority. Tokens reserved by a hold cannot be burned, nor more
+    /// than the account's spending limit allows.
     ///
     /// Accounts expected by this instruction:
     ///
@@ -135,6 +185,22 @@ pub enum TokenInstruction {
     ///   1. `[writable]` The token mint.
     ///   2. `[]` The account's multisignature owner/delegate.
     ///   3. ..3+M `[signer]` M signer accounts.
+    ///
+    ///   * Allowance
+    ///   0. `[writable]` The account to burn from.
+    ///   1. `[writable]` The token mint.
+    ///   2. `[writable]` The allowance.
+    ///   3. `[signer]` The allowance's spender.
+    ///   4. ..4+M `[signer]` M signer accounts, if the spender is a
+    ///      multisignature.
+    ///
+    /// An expired delegate approval is cleared and no longer lets the
+    /// delegate act. The time is read from the Clock sysvar if it follows the
+    /// authority accounts, or from the runtime otherwise.
+    ///
+    /// The account's session key may sign in place of the owner, within the
+    /// session's scope. The Clock sysvar must then follow the authority
+    /// accounts.
     Burn {
         /// The amount of tokens to burn.
         amount: u64,
@@ -153,255 +219,4122 @@ pub enum TokenInstruction {
         /// The new account's owner/multisignature.
         owner: Pubkey,
     },
-}
-impl TokenInstruction {
-    /// Unpacks a byte buffer into a [TokenInstruction](enum.TokenInstruction.html).
-    pub fn unpack(input: &[u8]) -> Result<Self, ProgramError> {
-        use TokenError::InvalidInstruction;
-
-        let (&tag, rest) = input.split_first().ok_or(InvalidInstruction)?;
-        Ok(match tag {
-            0 => {
-                let (&decimals, rest) = rest.split_first().ok_or(InvalidInstruction)?;
-                let (mint_authority, rest) = Self::unpack_pubkey(rest)?;
-                let (freeze_authority, _rest) = Self::unpack_pubkey_option(rest)?;
-                Self::InitializeMint {
-                    mint_authority,
-                    freeze_authority,
-                    decimals,
-                }
-            }
-            1 => Self::InitializeAccount,            
-            3 | 4 | 7 | 8 => {
-                let amount = rest
-                    .get(..8)
-                    .and_then(|slice| slice.try_into().ok())
-                    .map(u64::from_le_bytes)
-                    .ok_or(InvalidInstruction)?;
-                match tag {
-                    3 => Self::Transfer { amount },
-                    4 => Self::Approve { amount },
-                    7 => Self::MintTo { amount },
-                    8 => Self::Burn { amount },
-                    _ => unreachable!(),
-                }
-            }         
-            16 => {
-                let (owner, _rest) = Self::unpack_pubkey(rest)?;
-                Self::InitializeAccount2 { owner }
-            }
-
-            _ => return Err(TokenError::InvalidInstruction.into()),
-        })
-    }
-
-    /// Packs a [TokenInstruction](enum.TokenInstruction.html) into a byte buffer.
-    pub fn pack(&self) -> Vec<u8> {
-        let mut buf = Vec::with_capacity(size_of::<Self>());
-        match self {
-            &Self::InitializeMint {
-                ref mint_authority,
-                ref freeze_authority,
-                decimals,
-            } => {
-                buf.push(0);
-                buf.push(decimals);
-                buf.extend_from_slice(mint_authority.as_ref());
-                Self::pack_pubkey_option(freeze_authority, &mut buf);
-            }
-            Self::InitializeAccount => buf.push(1),            
-            &Self::Transfer { amount } => {
-                buf.push(3);
-                buf.extend_from_slice(&amount.to_le_bytes());
-            }
-            &Self::Approve { amount } => {
-                buf.push(4);
-                buf.extend_from_slice(&amount.to_le_bytes());
-            }
-            &Self::MintTo { amount } => {
-                buf.push(7);
-                buf.extend_from_slice(&amount.to_le_bytes());
-            }
-            &Self::Burn { amount } => {
-                buf.push(8);
-                buf.extend_from_slice(&amount.to_le_bytes());
-            }            
-            &Self::InitializeAccount2 { owner } => {
-                buf.push(16);
-                buf.extend_from_slice(owner.as_ref());
-            }
-        };
-        buf
-    }
-
-    fn unpack_pubkey(input: &[u8]) -> Result<(Pubkey, &[u8]), ProgramError> {
-        if input.len() >= 32 {
-            let (key, rest) = input.split_at(32);
-            let pk = Pubkey::new(key);
-            Ok((pk, rest))
-        } else {
-            Err(TokenError::InvalidInstruction.into())
-        }
-    }
-
-    fn unpack_pubkey_option(input: &[u8]) -> Result<(COption<Pubkey>, &[u8]), ProgramError> {
-        match input.split_first() {
-            Option::Some((&0, rest)) => Ok((COption::None, rest)),
-            Option::Some((&1, rest)) if rest.len() >= 32 => {
-                let (key, rest) = rest.split_at(32);
-                let pk = Pubkey::new(key);
-                Ok((COption::Some(pk), rest))
-            }
-            _ => Err(TokenError::InvalidInstruction.into()),
-        }
-    }
-
-    fn pack_pubkey_option(value: &COption<Pubkey>, buf: &mut Vec<u8>) {
-        match *value {
-            COption::Some(ref key) => {
-                buf.push(1);
-                buf.extend_from_slice(&key.to_bytes());
-            }
-            COption::None => buf.push(0),
-        }
-    }
-}
-
-/// Specifies the authority type for SetAuthority instructions
-#[repr(u8)]
-#[derive(Clone, Debug, PartialEq)]
-pub enum AuthorityType {
-    /// Authority to mint new tokens
-    MintTokens,
-    /// Authority to freeze any account associated with the Mint
-    FreezeAccount,
-    /// Owner of a given token account
-    AccountOwner,
-    /// Authority to close a token account
-    CloseAccount,
-}
-
-impl AuthorityType {
-    fn into(&self) -> u8 {
-        match self {
-            AuthorityType::MintTokens => 0,
-            AuthorityType::FreezeAccount => 1,
-            AuthorityType::AccountOwner => 2,
-            AuthorityType::CloseAccount => 3,
-        }
-    }
-
-    fn from(index: u8) -> Result<Self, ProgramError> {
-        match index {
-            0 => Ok(AuthorityType::MintTokens),
-            1 => Ok(AuthorityType::FreezeAccount),
-            2 => Ok(AuthorityType::AccountOwner),
-            3 => Ok(AuthorityType::CloseAccount),
-            _ => Err(TokenError::InvalidInstruction.into()),
-        }
-    }
-}
-
-/// Creates a `InitializeMint` instruction.
-pub fn initialize_mint(
-    token_program_id: &Pubkey,
-    mint_pubkey: &Pubkey,
-    mint_authority_pubkey: &Pubkey,
-    freeze_authority_pubkey: Option<&Pubkey>,
-    decimals: u8,
-) -> Result<Instruction, ProgramError> {
-    check_program_account(token_program_id)?;
-    let freeze_authority = freeze_authority_pubkey.cloned().into();
-    let data = TokenInstruction::InitializeMint {
-        mint_authority: *mint_authority_pubkey,
-        freeze_authority,
-        decimals,
-    }
-    .pack();
-
-    let accounts = vec![
-        AccountMeta::new(*mint_pubkey, false),
-        AccountMeta::new_readonly(sysvar::rent::id(), false),
-    ];
-
-    Ok(Instruction {
-        program_id: *token_program_id,
-        accounts,
-        data,
-    })
-}
-
-/// Creates a `InitializeAccount` instruction.
-pub fn initialize_account(
-    token_program_id: &Pubkey,
-    account_pubkey: &Pubkey,
-    mint_pubkey: &Pubkey,
-    owner_pubkey: &Pubkey,
-) -> Result<Instruction, ProgramError> {
-    check_program_account(token_program_id)?;
-    let data = TokenInstruction::InitializeAccount.pack();
-
-    let accounts = vec![
-        AccountMeta::new(*account_pubkey, false),
-        AccountMeta::new_readonly(*mint_pubkey, false),
-        AccountMeta::new_readonly(*owner_pubkey, false),
-        AccountMeta::new_readonly(sysvar::rent::id(), false),
-    ];
-
-    Ok(Instruction {
-        program_id: *token_program_id,
-        accounts,
-        data,
-    })
-}
-
-/// Creates a `InitializeAccount2` instruction.
-pub fn initialize_account2(
-    token_program_id: &Pubkey,
-    account_pubkey: &Pubkey,
-    mint_pubkey: &Pubkey,
-    owner_pubkey: &Pubkey,
-) -> Result<Instruction, ProgramError> {
-    check_program_account(token_program_id)?;
-    let data = TokenInstruction::InitializeAccount2 {
-        owner: *owner_pubkey,
-    }
-    .pack();
-
-    let accounts = vec![
-        AccountMeta::new(*account_pubkey, false),
-        AccountMeta::new_readonly(*mint_pubkey, false),
-        AccountMeta::new_readonly(sysvar::rent::id(), false),
-    ];
-
-    Ok(Instruction {
-        program_id: *token_program_id,
-        accounts,
-        data,
-    })
-}
-
-/// Creates a `InitializeMultisig` instruction.
-pub fn initialize_multisig(
-    token_program_id: &Pubkey,
-    multisig_pubkey: &Pubkey,
-    signer_pubkeys: &[&Pubkey],
-    m: u8,
-) -> Result<Instruction, ProgramError> {
-    check_program_account(token_program_id)?;
-    if !is_valid_signer_index(m as usize)
+    /// Initializes a new minter, granting its authority an allowance of tokens
+    /// it may mint.  Minters are managed by the mint's minting authority.
+    ///
+    /// The `InitializeMinter` instruction MUST be included within the same
+    /// Transaction as the system program's `CreateAccount` instruction that
+    /// creates the account being initialized.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   * Single authority
+    ///   0. `[writable]` The minter to initialize.
+    ///   1. `[]` The mint.
+    ///   2. `[]` Rent sysvar
+    ///   3. `[signer]` The mint's minting authority.
+    ///
+    ///   * Multisignature authority
+    ///   0. `[writable]` The minter to initialize.
+    ///   1. `[]` The mint.
+    ///   2. `[]` Rent sysvar
+    ///   3. `[]` The mint's multisignature minting authority.
+    ///   4. ..4+M `[signer]` M signer accounts.
+    InitializeMinter {
+        /// The authority/multisignature permitted to mint against the allowance.
+        authority: Pubkey,
+        /// The amount of tokens the minter may mint.
+        allowance: u64,
+    },
+    /// Replaces the remaining allowance of a minter.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   * Single authority
+    ///   0. `[writable]` The minter.
+    ///   1. `[]` The mint.
+    ///   2. `[signer]` The mint's minting authority.
+    ///
+    ///   * Multisignature authority
+    ///   0. `[writable]` The minter.
+    ///   1. `[]` The mint.
+    ///   2. `[]` The mint's multisignature minting authority.
+    ///   3. ..3+M `[signer]` M signer accounts.
+    SetMinterAllowance {
+        /// The new allowance of the minter.
+        allowance: u64,
+    },
+    /// Revokes a minter by closing its account and transferring its lamports
+    /// to the destination account.  Other minters of the mint are unaffected.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   * Single authority
+    ///   0. `[writable]` The minter to revoke.
+    ///   1. `[]` The mint.
+    ///   2. `[writable]` The destination account.
+    ///   3. `[signer]` The mint's minting authority.
+    ///
+    ///   * Multisignature authority
+    ///   0. `[writable]` The minter to revoke.
+    ///   1. `[]` The mint.
+    ///   2. `[writable]` The destination account.
+    ///   3. `[]` The mint's multisignature minting authority.
+    ///   4. ..4+M `[signer]` M signer accounts.
+    RevokeMinter,
+    /// Assigns or clears a role of a mint.  Roles are managed by the mint's
+    /// minting authority.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   * Single authority
+    ///   0. `[writable]` The mint.
+    ///   1. `[signer]` The mint's minting authority.
+    ///
+    ///   * Multisignature authority
+    ///   0. `[writable]` The mint.
+    ///   1. `[]` The mint's multisignature minting authority.
+    ///   2. ..2+M `[signer]` M signer accounts.
+    SetMintRole {
+        /// The role to assign.
+        role: MintRole,
+        /// The new authority holding the role.
+        new_authority: COption<Pubkey>,
+    },
+    /// Pauses minting of a mint using the mint's pauser.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   * Single authority
+    ///   0. `[writable]` The mint.
+    ///   1. `[signer]` The mint's pauser.
+    ///
+    ///   * Multisignature authority
+    ///   0. `[writable]` The mint.
+    ///   1. `[]` The mint's multisignature pauser.
+    ///   2. ..2+M `[signer]` M signer accounts.
+    PauseMinting,
+    /// Resumes minting of a paused mint using the mint's pauser.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   * Single authority
+    ///   0. `[writable]` The mint.
+    ///   1. `[signer]` The mint's pauser.
+    ///
+    ///   * Multisignature authority
+    ///   0. `[writable]` The mint.
+    ///   1. `[]` The mint's multisignature pauser.
+    ///   2. ..2+M `[signer]` M signer accounts.
+    ResumeMinting,
+    /// Proposes a new authority for a mint or account.  The authority is not
+    /// changed until the proposed authority accepts it with `AcceptAuthority`.
+    /// A new proposal replaces any pending proposal of the same authority type.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   * Single authority
+    ///   0. `[writable]` The mint or account to change the authority of.
+    ///   1. `[signer]` The current authority of the mint or account.
+    ///
+    ///   * Multisignature authority
+    ///   0. `[writable]` The mint or account to change the authority of.
+    ///   1. `[]` The mint's or account's current multisignature authority.
+    ///   2. ..2+M `[signer]` M signer accounts
+    ProposeAuthority {
+        /// The type of authority to propose.
+        authority_type: AuthorityType,
+        /// The proposed authority.
+        new_authority: Pubkey,
+    },
+    /// Accepts a pending authority proposal, replacing the current authority of
+    /// a mint or account with the proposed one.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   * Single authority
+    ///   0. `[writable]` The mint or account to change the authority of.
+    ///   1. `[signer]` The proposed authority.
+    ///
+    ///   * Multisignature authority
+    ///   0. `[writable]` The mint or account to change the authority of.
+    ///   1. `[]` The proposed multisignature authority.
+    ///   2. ..2+M `[signer]` M signer accounts
+    AcceptAuthority {
+        /// The type of authority to accept.
+        authority_type: AuthorityType,
+    },
+    /// Cancels a pending authority proposal of a mint or account.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   * Single authority
+    ///   0. `[writable]` The mint or account.
+    ///   1. `[signer]` The current authority of the mint or account.
+    ///
+    ///   * Multisignature authority
+    ///   0. `[writable]` The mint or account.
+    ///   1. `[]` The mint's or account's current multisignature authority.
+    ///   2. ..2+M `[signer]` M signer accounts
+    CancelAuthorityProposal {
+        /// The type of authority to cancel the proposal of.
+        authority_type: AuthorityType,
+    },
+    /// Sets the minimum delay between queueing and executing a mint-level
+    /// authority change. Once set, mint-level authorities may only be changed
+    /// through `QueueAuthorityChange`, and the delay may only be increased.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   * Single authority
+    ///   0. `[writable]` The mint.
+    ///   1. `[signer]` The mint's minting authority.
+    ///
+    ///   * Multisignature authority
+    ///   0. `[writable]` The mint.
+    ///   1. `[]` The mint's multisignature minting authority.
+    ///   2. ..2+M `[signer]` M signer accounts.
+    SetAuthorityTimelock {
+        /// The delay, in seconds.
+        delay: u64,
+    },
+    /// Queues a change of a mint-level authority, executable once the mint's
+    /// authority timelock has elapsed. Only one change may be queued at a time.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   * Single authority
+    ///   0. `[writable]` The mint.
+    ///   1. `[]` Clock sysvar.
+    ///   2. `[signer]` The current authority of the mint. Mint role changes
+    ///      are queued by the minting authority.
+    ///
+    ///   * Multisignature authority
+    ///   0. `[writable]` The mint.
+    ///   1. `[]` Clock sysvar.
+    ///   2. `[]` The mint's current multisignature authority.
+    ///   3. ..3+M `[signer]` M signer accounts.
+    QueueAuthorityChange {
+        /// The mint-level authority to change.
+        authority: TimelockedAuthority,
+        /// The new authority
+        new_authority: COption<Pubkey>,
+    },
+    /// Executes a queued mint-level authority change whose timelock has
+    /// elapsed. Requires no signers.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` The mint.
+    ///   1. `[]` Clock sysvar.
+    ExecuteAuthorityChange,
+    /// Cancels a queued mint-level authority change.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   * Single authority
+    ///   0. `[writable]` The mint.
+    ///   1. `[signer]` The current authority that queued the change.
+    ///
+    ///   * Multisignature authority
+    ///   0. `[writable]` The mint.
+    ///   1. `[]` The current multisignature authority that queued the change.
+    ///   2. ..2+M `[signer]` M signer accounts.
+    CancelAuthorityChange,
+    /// Registers or removes the guardians of an account, and sets the delay
+    /// during which the owner may veto a recovery. Any pending recovery is
+    /// cancelled.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   * Single owner
+    ///   0. `[writable]` The account.
+    ///   1. `[signer]` The account's owner.
+    ///
+    ///   * Multisignature owner
+    ///   0. `[writable]` The account.
+    ///   1. `[]` The account's multisignature owner.
+    ///   2. ..2+M `[signer]` M signer accounts.
+    SetGuardians {
+        /// The guardian authority, usually a multisignature account holding the
+        /// guardian set and threshold.
+        guardians: COption<Pubkey>,
+        /// The veto delay, in seconds.
+        recovery_delay: u64,
+    },
+    /// Initiates a recovery of an account's ownership by its guardians. The
+    /// new owner is installed by `CompleteRecovery` once the account's
+    /// recovery delay has elapsed, unless vetoed by the owner.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   * Single guardian
+    ///   0. `[writable]` The account.
+    ///   1. `[]` Clock sysvar.
+    ///   2. `[signer]` The account's guardian.
+    ///
+    ///   * Multisignature guardians
+    ///   0. `[writable]` The account.
+    ///   1. `[]` Clock sysvar.
+    ///   2. `[]` The account's multisignature guardians.
+    ///   3. ..3+M `[signer]` M guardian signer accounts.
+    InitiateRecovery {
+        /// The new owner of the account.
+        new_owner: Pubkey,
+    },
+    /// Completes a recovery whose veto delay has elapsed. Requires no signers.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` The account.
+    ///   1. `[]` Clock sysvar.
+    CompleteRecovery,
+    /// Vetoes a pending recovery of an account.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   * Single owner
+    ///   0. `[writable]` The account.
+    ///   1. `[signer]` The account's owner.
+    ///
+    ///   * Multisignature owner
+    ///   0. `[writable]` The account.
+    ///   1. `[]` The account's multisignature owner.
+    ///   2. ..2+M `[signer]` M signer accounts.
+    VetoRecovery,
+    /// Approves a delegate until an expiry, optionally capping the amount the
+    /// delegate may move in a single transfer or burn.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   * Single owner
+    ///   0. `[writable]` The source account.
+    ///   1. `[]` The delegate.
+    ///   2. `[signer]` The source account owner.
+    ///
+    ///   * Multisignature owner
+    ///   0. `[writable]` The source account.
+    ///   1. `[]` The delegate.
+    ///   2. `[]` The source account's multisignature owner.
+    ///   3. ..3+M `[signer]` M signer accounts
+    ApproveWithLimits {
+        /// The amount of tokens the delegate is approved for.
+        amount: u64,
+        /// Unix timestamp at which the approval expires, or zero if it never
+        /// expires.
+        expiry: UnixTimestamp,
+        /// The maximum amount per transfer or burn.
+        max_per_transfer: COption<u64>,
+    },
+    /// Sets the amount a spender may transfer or burn from a token account,
+    /// creating the spender's allowance account if it doesn't exist yet. The
+    /// allowance lives at the program derived address returned by
+    /// `get_allowance_address`, so a token account may have any number of
+    /// spenders. The allowance lapses once the token account changes owner.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   * Single owner
+    ///   0. `[writable]` The allowance.
+    ///   1. `[]` The token account.
+    ///   2. `[]` The spender.
+    ///   3. `[writable, signer]` The payer funding the allowance account.
+    ///   4. `[]` System program.
+    ///   5. `[]` Rent sysvar.
+    ///   6. `[signer]` The token account's owner.
+    ///
+    ///   * Multisignature owner
+    ///   0. `[writable]` The allowance.
+    ///   1. `[]` The token account.
+    ///   2. `[]` The spender.
+    ///   3. `[writable, signer]` The payer funding the allowance account.
+    ///   4. `[]` System program.
+    ///   5. `[]` Rent sysvar.
+    ///   6. `[]` The token account's multisignature owner.
+    ///   7. ..7+M `[signer]` M signer accounts.
+    SetAllowance {
+        /// The amount of tokens the spender is approved for.
+        amount: u64,
+    },
+    /// Closes an allowance account, transferring its SOL to the destination.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   * Single owner
+    ///   0. `[writable]` The allowance.
+    ///   1. `[]` The token account.
+    ///   2. `[writable]` The destination account.
+    ///   3. `[signer]` The token account's owner.
+    ///
+    ///   * Multisignature owner
+    ///   0. `[writable]` The allowance.
+    ///   1. `[]` The token account.
+    ///   2. `[writable]` The destination account.
+    ///   3. `[]` The token account's multisignature owner.
+    ///   4. ..4+M `[signer]` M signer accounts.
+    CloseAllowance,
+    /// Approves a delegate like `Approve`, but only if the account's current
+    /// delegate and delegated amount match the expected ones. Guards against a
+    /// delegate spending the old approval before the new one lands.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   * Single owner
+    ///   0. `[writable]` The source account.
+    ///   1. `[]` The delegate.
+    ///   2. `[signer]` The source account owner.
+    ///
+    ///   * Multisignature owner
+    ///   0. `[writable]` The source account.
+    ///   1. `[]` The delegate.
+    ///   2. `[]` The source account's multisignature owner.
+    ///   3. ..3+M `[signer]` M signer accounts
+    ApproveIfCurrent {
+        /// The expected current delegate.
+        expected_delegate: COption<Pubkey>,
+        /// The expected current delegated amount.
+        expected_amount: u64,
+        /// The amount of tokens the delegate is approved for.
+        amount: u64,
+    },
+    /// Increases the amount approved for the account's current delegate, or
+    /// approves the delegate if the account has none.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   * Single owner
+    ///   0. `[writable]` The source account.
+    ///   1. `[]` The delegate.
+    ///   2. `[signer]` The source account owner.
+    ///
+    ///   * Multisignature owner
+    ///   0. `[writable]` The source account.
+    ///   1. `[]` The delegate.
+    ///   2. `[]` The source account's multisignature owner.
+    ///   3. ..3+M `[signer]` M signer accounts
+    IncreaseAllowance {
+        /// The amount of tokens to add to the delegated amount.
+        amount: u64,
+    },
+    /// Decreases the amount approved for the account's current delegate. The
+    /// delegate is removed once the delegated amount reaches zero.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   * Single owner
+    ///   0. `[writable]` The source account.
+    ///   1. `[]` The delegate.
+    ///   2. `[signer]` The source account owner.
+    ///
+    ///   * Multisignature owner
+    ///   0. `[writable]` The source account.
+    ///   1. `[]` The delegate.
+    ///   2. `[]` The source account's multisignature owner.
+    ///   3. ..3+M `[signer]` M signer accounts
+    DecreaseAllowance {
+        /// The amount of tokens to remove from the delegated amount.
+        amount: u64,
+    },
+    /// Creates and initializes the nonce protecting an owner's off-chain
+    /// signed authorizations. The nonce lives at the program derived address
+    /// returned by `get_nonce_address` and starts at zero.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` The nonce.
+    ///   1. `[]` The owner.
+    ///   2. `[writable, signer]` The payer funding the nonce account.
+    ///   3. `[]` System program.
+    ///   4. `[]` Rent sysvar.
+    InitializeNonce,
+    /// Approves a delegate on behalf of the source account's owner, who signed
+    /// the `authorization_message` of this instruction off-chain. The
+    /// signature must be verified by an ed25519 program instruction earlier
+    /// in the same transaction, holding the public key, signature and message
+    /// in its own instruction data. Requires no signers.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` The source account.
+    ///   1. `[]` The delegate.
+    ///   2. `[writable]` The owner's nonce.
+    ///   3. `[]` Instructions sysvar.
+    ///   4. `[]` Clock sysvar.
+    Permit {
+        /// The amount of tokens the delegate is approved for.
+        amount: u64,
+        /// The owner's current nonce.
+        nonce: u64,
+        /// The authorization is not valid before this time.
+        valid_after: UnixTimestamp,
+        /// The authorization is not valid after this time.
+        valid_until: UnixTimestamp,
+    },
+    /// Transfers tokens on behalf of the source account's owner, who signed
+    /// the `authorization_message` of this instruction off-chain. The
+    /// signature is verified as for `Permit`. Requires no signers. The mint
+    /// and other accounts a `Transfer` needs follow the listed accounts.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` The source account.
+    ///   1. `[writable]` The destination account.
+    ///   2. `[writable]` The owner's nonce.
+    ///   3. `[]` Instructions sysvar.
+    ///   4. `[]` Clock sysvar.
+    TransferWithAuthorization {
+        /// The amount of tokens to transfer.
+        amount: u64,
+        /// The owner's current nonce.
+        nonce: u64,
+        /// The authorization is not valid before this time.
+        valid_after: UnixTimestamp,
+        /// The authorization is not valid after this time.
+        valid_until: UnixTimestamp,
+    },
+    /// Registers a temporary session key that may transfer or burn for the
+    /// owner, replacing any existing session. The session is limited to the
+    /// operations in its scope bitmap, to up to `MAX_SESSION_DESTINATIONS`
+    /// destinations if any are given, and to a total amount, and expires at
+    /// the given time.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   * Single owner
+    ///   0. `[writable]` The token account.
+    ///   1. `[signer]` The token account's owner.
+    ///
+    ///   * Multisignature owner
+    ///   0. `[writable]` The token account.
+    ///   1. `[]` The token account's multisignature owner.
+    ///   2. ..2+M `[signer]` M signer accounts.
+    CreateSession {
+        /// The session key.
+        session_key: Pubkey,
+        /// The unix timestamp at which the session expires.
+        expiry: UnixTimestamp,
+        /// Bitmap of `SESSION_SCOPE_*` operations the session key may perform.
+        scope: u8,
+        /// The destinations the session key may transfer to, or any if empty.
+        destinations: Vec<Pubkey>,
+        /// The total amount the session key may transfer or burn.
+        max_amount: u64,
+    },
+    /// Revokes the account's session key.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   * Single owner
+    ///   0. `[writable]` The token account.
+    ///   1. `[signer]` The token account's owner.
+    ///
+    ///   * Multisignature owner
+    ///   0. `[writable]` The token account.
+    ///   1. `[]` The token account's multisignature owner.
+    ///   2. ..2+M `[signer]` M signer accounts.
+    RevokeSession,
+    /// Creates and initializes the owner whose authority is proven by an
+    /// Ethereum address's secp256k1 signatures. The owner lives at the program
+    /// derived address returned by `get_ethereum_owner_address`, and may be
+    /// named as the owner of token accounts.
+    ///
+    /// Wherever such an owner is expected, it is passed writable and unsigned,
+    /// with the Instructions sysvar among the signer accounts that follow it.
+    /// A secp256k1 program instruction earlier in the same transaction must
+    /// verify the address's signature over the `ethereum_authorization_message`
+    /// of the instruction, holding the address, signature and message in its
+    /// own instruction data. The authorization lapses after the `valid_until`
+    /// timestamp it carries, and each one advances the owner's nonce.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` The Ethereum owner.
+    ///   1. `[writable, signer]` The payer funding the owner account.
+    ///   2. `[]` System program.
+    ///   3. `[]` Rent sysvar.
+    InitializeEthereumOwner {
+        /// The Ethereum address.
+        address: EthereumAddress,
+    },
+    /// Initializes a weighted multisignature account with N provided signers,
+    /// each carrying a weight. The account is accepted wherever a
+    /// multisignature authority is, and approves an instruction once the
+    /// weights of the signers that signed it total at least the threshold.
+    ///
+    /// The `InitializeWeightedMultisig` instruction requires no signers and
+    /// MUST be included within the same Transaction as the system program's
+    /// `CreateAccount` instruction that creates the account being initialized.
+    /// Otherwise another party can acquire ownership of the uninitialized
+    /// account.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` The weighted multisignature account to initialize.
+    ///   1. `[]` Rent sysvar
+    ///   2. ..2+N. `[]` The signer accounts, must equal to N where 1 <= N <=
+    ///      11.
+    InitializeWeightedMultisig {
+        /// The total weight of signatures required to approve an instruction.
+        threshold: u16,
+        /// The weight of each signer, in the order of the signer accounts.
+        weights: Vec<u8>,
+    },
+    /// Adds a signer to a multisignature, approved by the multisignature
+    /// itself.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` The multisignature account.
+    ///   1. ..1+M `[signer]` M signer accounts.
+    AddSigner {
+        /// The signer to add.
+        signer: Pubkey,
+    },
+    /// Removes a signer from a multisignature, approved by the multisignature
+    /// itself. The remaining signers must still meet the threshold `M`.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` The multisignature account.
+    ///   1. ..1+M `[signer]` M signer accounts.
+    RemoveSigner {
+        /// The signer to remove.
+        signer: Pubkey,
+    },
+    /// Sets the number of signers a multisignature requires, approved by the
+    /// multisignature under its current threshold.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` The multisignature account.
+    ///   1. ..1+M `[signer]` M signer accounts.
+    SetThreshold {
+        /// The number of signers required, at most the number of signers.
+        m: u8,
+    },
+    /// Proposes an instruction whose authority is a multisignature, to be
+    /// approved by its signers in separate transactions and executed by
+    /// `ExecuteProposal` once approvals reach the threshold `M`. The proposer,
+    /// one of the signers, gives the first approval.
+    ///
+    /// The proposed instruction names the multisignature as its authority,
+    /// followed by no signer accounts.
+    ///
+    /// The `CreateProposal` instruction requires no signers and MUST be
+    /// included within the same Transaction as the system program's
+    /// `CreateAccount` instruction that creates the account being initialized.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` The proposal account to initialize.
+    ///   1. `[]` The multisignature.
+    ///   2. `[signer]` The proposer.
+    ///   3. `[]` Rent sysvar
+    ///   4. ..4+N `[]` The N accounts the proposed instruction takes, in order.
+    CreateProposal {
+        /// Unix timestamp after which the proposal can no longer be approved
+        /// or executed.
+        expiry: UnixTimestamp,
+        /// The serialized `TokenInstruction` to execute.
+        instruction_data: Vec<u8>,
+    },
+    /// Approves a pending proposal as one of its multisignature's signers.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` The proposal.
+    ///   1. `[]` The proposal's multisignature.
+    ///   2. `[signer]` The approving signer.
+    ///   3. `[]` Clock sysvar.
+    ApproveProposal,
+    /// Executes a pending proposal's instruction once the approvals of the
+    /// multisignature's current signers reach its threshold `M`. Anyone may
+    /// execute an approved proposal.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` The proposal.
+    ///   1. `[]` The proposal's multisignature.
+    ///   2. `[]` Clock sysvar.
+    ///   3. ..3+N `[]` The N accounts the proposed instruction takes, in order,
+    ///      writable where the instruction writes to them.
+    ExecuteProposal,
+    /// Cancels a pending proposal.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   * Proposer
+    ///   0. `[writable]` The proposal.
+    ///   1. `[signer]` The proposer.
+    ///
+    ///   * Multisignature
+    ///   0. `[writable]` The proposal.
+    ///   1. `[]` The proposal's multisignature.
+    ///   2. ..2+M `[signer]` M signer accounts.
+    CancelProposal,
+    /// Places a hold on part of an account's balance, reserving it for a
+    /// capturer until the hold expires. Held tokens cannot be transferred or
+    /// burned. An account has at most one hold at a time.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   * Single owner
+    ///   0. `[writable]` The account to hold funds in.
+    ///   1. `[signer]` The account's owner.
+    ///
+    ///   * Multisignature owner
+    ///   0. `[writable]` The account to hold funds in.
+    ///   1. `[]` The account's multisignature owner.
+    ///   2. ..2+M `[signer]` M signer accounts.
+    PlaceHold {
+        /// The authority permitted to capture the held funds.
+        capturer: Pubkey,
+        /// The amount of tokens to hold.
+        amount: u64,
+        /// Unix timestamp at which the hold expires.
+        expiry: UnixTimestamp,
+    },
+    /// Captures up to the held amount into a destination account, before the
+    /// hold expires. Any remainder stays held until the hold expires, and the
+    /// hold is cleared once nothing remains held. The mint and other accounts a
+    /// `Transfer` needs follow the capturer accounts.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   * Single capturer
+    ///   0. `[writable]` The account holding the funds.
+    ///   1. `[writable]` The destination account.
+    ///   2. `[]` Clock sysvar.
+    ///   3. `[signer]` The hold's capturer.
+    ///
+    ///   * Multisignature capturer
+    ///   0. `[writable]` The account holding the funds.
+    ///   1. `[writable]` The destination account.
+    ///   2. `[]` Clock sysvar.
+    ///   3. `[]` The hold's multisignature capturer.
+    ///   4. ..4+M `[signer]` M signer accounts.
+    CaptureHold {
+        /// The amount of tokens to capture.
+        amount: u64,
+    },
+    /// Releases an account's hold. Anyone may release an expired hold; the
+    /// capturer may release it at any time.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   * Expired hold
+    ///   0. `[writable]` The account holding the funds.
+    ///   1. `[]` Clock sysvar.
+    ///
+    ///   * Single capturer
+    ///   0. `[writable]` The account holding the funds.
+    ///   1. `[]` Clock sysvar.
+    ///   2. `[signer]` The hold's capturer.
+    ///
+    ///   * Multisignature capturer
+    ///   0. `[writable]` The account holding the funds.
+    ///   1. `[]` Clock sysvar.
+    ///   2. `[]` The hold's multisignature capturer.
+    ///   3. ..3+M `[signer]` M signer accounts.
+    ReleaseHold,
+    /// Restricts an account to receiving tokens, using the Mint's
+    /// freeze_authority (if set). The account can no longer send or burn
+    /// tokens. `ThawAccount` lifts the restriction.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   * Single owner
+    ///   0. `[writable]` The account to restrict.
+    ///   1. `[]` The token mint.
+    ///   2. `[signer]` The mint freeze authority.
+    ///
+    ///   * Multisignature owner
+    ///   0. `[writable]` The account to restrict.
+    ///   1. `[]` The token mint.
+    ///   2. `[]` The mint's multisignature freeze authority.
+    ///   3. ..3+M `[signer]` M signer accounts.
+    SetReceiveOnly,
+    /// Restricts an account to sending and burning tokens, using the Mint's
+    /// freeze_authority (if set). The account can no longer receive tokens.
+    /// `ThawAccount` lifts the restriction.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   * Single owner
+    ///   0. `[writable]` The account to restrict.
+    ///   1. `[]` The token mint.
+    ///   2. `[signer]` The mint freeze authority.
+    ///
+    ///   * Multisignature owner
+    ///   0. `[writable]` The account to restrict.
+    ///   1. `[]` The token mint.
+    ///   2. `[]` The mint's multisignature freeze authority.
+    ///   3. ..3+M `[signer]` M signer accounts.
+    SetSendOnly,
+    /// Freezes an account like `FreezeAccount`, recording a reason code and
+    /// an optional auto-thaw timestamp. Once the timestamp passes, the account
+    /// behaves as thawed in every instruction, without a `ThawAccount`.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   * Single owner
+    ///   0. `[writable]` The account to freeze.
+    ///   1. `[]` The token mint.
+    ///   2. `[signer]` The mint freeze authority.
+    ///
+    ///   * Multisignature owner
+    ///   0. `[writable]` The account to freeze.
+    ///   1. `[]` The token mint.
+    ///   2. `[]` The mint's multisignature freeze authority.
+    ///   3. ..3+M `[signer]` M signer accounts.
+    FreezeAccountWithReason {
+        /// The reason code for the freeze.
+        reason: u8,
+        /// Unix timestamp from which the account behaves as thawed, or zero
+        /// to freeze until a `ThawAccount`.
+        auto_thaw_timestamp: UnixTimestamp,
+    },
+    /// Freezes several accounts of a mint at once, validating the freeze
+    /// authority a single time. Accounts already frozen are left unchanged, and
+    /// their indices among the N accounts are set as the return data, one
+    /// byte each.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   * Single owner
+    ///   0. `[]` The token mint.
+    ///   1. `[signer]` The mint freeze authority.
+    ///   2. ..2+N `[writable]` The N accounts to freeze.
+    ///
+    ///   * Multisignature owner
+    ///   0. `[]` The token mint.
+    ///   1. `[]` The mint's multisignature freeze authority.
+    ///   2. ..2+N `[writable]` The N accounts to freeze.
+    ///   3. ..3+M `[signer]` M signer accounts, following the N accounts.
+    FreezeAccounts {
+        /// The number of accounts N to freeze.
+        count: u8,
+    },
+    /// Thaws several accounts of a mint at once, validating the freeze
+    /// authority a single time. Accounts already thawed are left unchanged, and
+    /// their indices among the N accounts are set as the return data, one
+    /// byte each.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   * Single owner
+    ///   0. `[]` The token mint.
+    ///   1. `[signer]` The mint freeze authority.
+    ///   2. ..2+N `[writable]` The N accounts to thaw.
+    ///
+    ///   * Multisignature owner
+    ///   0. `[]` The token mint.
+    ///   1. `[]` The mint's multisignature freeze authority.
+    ///   2. ..2+N `[writable]` The N accounts to thaw.
+    ///   3. ..3+M `[signer]` M signer accounts, following the N accounts.
+    ThawAccounts {
+        /// The number of accounts N to thaw.
+        count: u8,
+    },
+    /// Sets the most an account may send or burn within a rolling window.
+    /// Lowering the limit or lengthening the window takes effect at once; any
+    /// other change takes effect only after `SPENDING_LIMIT_INCREASE_DELAY`.
+    /// To lift the limit, raise it to `u64::MAX`.
+    ///
+    /// Transfers and burns from an account with a spending limit must include
+    /// the Clock sysvar among their accounts.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   * Single owner
+    ///   0. `[writable]` The account.
+    ///   1. `[]` Clock sysvar.
+    ///   2. `[signer]` The account's owner.
+    ///
+    ///   * Multisignature owner
+    ///   0. `[writable]` The account.
+    ///   1. `[]` Clock sysvar.
+    ///   2. `[]` The account's multisignature owner.
+    ///   3. ..3+M `[signer]` M signer accounts.
+    SetSpendingLimit {
+        /// The most the account may send or burn per window.
+        amount: u64,
+        /// The length of the window, in seconds.
+        window: u64,
+    },
+    /// Initializes a destination allowlist for an account. Once initialized,
+    /// the account may only transfer to destinations on the allowlist.
+    ///
+    /// The `InitializeDestinationAllowlist` instruction requires no signers
+    /// for the allowlist and MUST be included within the same Transaction as
+    /// the system program's `CreateAccount` instruction that creates the
+    /// allowlist being initialized.  Otherwise another party can acquire
+    /// ownership of the uninitialized allowlist.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   * Single owner
+    ///   0. `[writable]` The allowlist to initialize.
+    ///   1. `[writable]` The account.
+    ///   2. `[]` Rent sysvar.
+    ///   3. `[signer]` The account's owner.
+    ///
+    ///   * Multisignature owner
+    ///   0. `[writable]` The allowlist to initialize.
+    ///   1. `[writable]` The account.
+    ///   2. `[]` Rent sysvar.
+    ///   3. `[]` The account's multisignature owner.
+    ///   4. ..4+M `[signer]` M signer accounts.
+    InitializeDestinationAllowlist,
+    /// Adds a destination to an account's allowlist. The destination is
+    /// either a token account or an owner, whose token accounts are then all
+    /// allowed. It may receive transfers only after
+    /// `DESTINATION_ALLOWLIST_DELAY`.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   * Single owner
+    ///   0. `[writable]` The allowlist.
+    ///   1. `[]` The account.
+    ///   2. `[]` Clock sysvar.
+    ///   3. `[signer]` The account's owner.
+    ///
+    ///   * Multisignature owner
+    ///   0. `[writable]` The allowlist.
+    ///   1. `[]` The account.
+    ///   2. `[]` Clock sysvar.
+    ///   3. `[]` The account's multisignature owner.
+    ///   4. ..4+M `[signer]` M signer accounts.
+    AddAllowedDestination {
+        /// The token account or owner to allow.
+        destination: Pubkey,
+    },
+    /// Removes a destination from an account's allowlist, with immediate
+    /// effect.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   * Single owner
+    ///   0. `[writable]` The allowlist.
+    ///   1. `[]` The account.
+    ///   2. `[signer]` The account's owner.
+    ///
+    ///   * Multisignature owner
+    ///   0. `[writable]` The allowlist.
+    ///   1. `[]` The account.
+    ///   2. `[]` The account's multisignature owner.
+    ///   3. ..3+M `[signer]` M signer accounts.
+    RemoveAllowedDestination {
+        /// The token account or owner to remove.
+        destination: Pubkey,
+    },
+    /// Sets the most any account of the mint may hold, unless exempted by the
+    /// mint authority. Set to `None` to remove it.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   * Single authority
+    ///   0. `[writable]` The mint.
+    ///   1. `[signer]` The mint's minting authority.
+    ///
+    ///   * Multisignature authority
+    ///   0. `[writable]` The mint.
+    ///   1. `[]` The mint's multisignature minting authority.
+    ///   2. ..2+M `[signer]` M signer accounts.
+    SetMaxBalance {
+        /// The maximum balance, or `None` to remove it.
+        max_balance: COption<u64>,
+    },
+    /// Exempts an account, such as an issuer or treasury account, from the
+    /// mint's maximum balance.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   * Single authority
+    ///   0. `[writable]` The account.
+    ///   1. `[]` The token mint.
+    ///   2. `[signer]` The mint's minting authority.
+    ///
+    ///   * Multisignature authority
+    ///   0. `[writable]` The account.
+    ///   1. `[]` The token mint.
+    ///   2. `[]` The mint's multisignature minting authority.
+    ///   3. ..3+M `[signer]` M signer accounts.
+    AddMaxBalanceExemption,
+    /// Subjects an exempted account to the mint's maximum balance again.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   * Single authority
+    ///   0. `[writable]` The account.
+    ///   1. `[]` The token mint.
+    ///   2. `[signer]` The mint's minting authority.
+    ///
+    ///   * Multisignature authority
+    ///   0. `[writable]` The account.
+    ///   1. `[]` The token mint.
+    ///   2. `[]` The mint's multisignature minting authority.
+    ///   3. ..3+M `[signer]` M signer accounts.
+    RemoveMaxBalanceExemption,
+    /// Sets the mint's launch controls: until the trading start, only
+    /// accounts owned by the mint authority may transfer, and other accounts
+    /// may make at most one outgoing transfer per cooldown. Set both to zero
+    /// to remove them.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   * Single authority
+    ///   0. `[writable]` The mint.
+    ///   1. `[signer]` The mint's minting authority.
+    ///
+    ///   * Multisignature authority
+    ///   0. `[writable]` The mint.
+    ///   1. `[]` The mint's multisignature minting authority.
+    ///   2. ..2+M `[signer]` M signer accounts.
+    SetLaunchControls {
+        /// Unix timestamp from which all accounts may transfer, or zero.
+        trading_start_timestamp: UnixTimestamp,
+        /// Minimum delay, in seconds, between an account's outgoing
+        /// transfers, or zero.
+        transfer_cooldown: u64,
+    },
+    /// Issues or renews an owner's credential for a mint with a compliance
+    /// authority, creating the credential account if it doesn't exist yet.
+    /// The credential lives at the program derived address returned by
+    /// `get_credential_address`.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   * Single authority
+    ///   0. `[writable]` The credential.
+    ///   1. `[]` The token mint.
+    ///   2. `[]` The verified owner.
+    ///   3. `[writable, signer]` The payer funding the credential account.
+    ///   4. `[]` System program.
+    ///   5. `[]` Rent sysvar.
+    ///   6. `[signer]` The mint's compliance authority.
+    ///
+    ///   * Multisignature authority
+    ///   0. `[writable]` The credential.
+    ///   1. `[]` The token mint.
+    ///   2. `[]` The verified owner.
+    ///   3. `[writable, signer]` The payer funding the credential account.
+    ///   4. `[]` System program.
+    ///   5. `[]` Rent sysvar.
+    ///   6. `[]` The mint's multisignature compliance authority.
+    ///   7. ..7+M `[signer]` M signer accounts.
+    IssueCredential {
+        /// Unix timestamp at which the credential expires.
+        expiry: UnixTimestamp,
+        /// The owner's jurisdiction code.
+        jurisdiction: u16,
+    },
+    /// Revokes a credential with immediate effect, transferring the
+    /// credential account's SOL to the destination.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   * Single authority
+    ///   0. `[writable]` The credential.
+    ///   1. `[]` The token mint.
+    ///   2. `[writable]` The destination account.
+    ///   3. `[signer]` The mint's compliance authority.
+    ///
+    ///   * Multisignature authority
+    ///   0. `[writable]` The credential.
+    ///   1. `[]` The token mint.
+    ///   2. `[writable]` The destination account.
+    ///   3. `[]` The mint's multisignature compliance authority.
+    ///   4. ..4+M `[signer]` M signer accounts.
+    RevokeCredential,
+    /// Sets the smallest amount a transfer of the mint's tokens may move,
+    /// unless it empties the source account. Set to zero to remove it.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   * Single authority
+    ///   0. `[writable]` The mint.
+    ///   1. `[signer]` The mint's minting authority.
+    ///
+    ///   * Multisignature authority
+    ///   0. `[writable]` The mint.
+    ///   1. `[]` The mint's multisignature minting authority.
+    ///   2. ..2+M `[signer]` M signer accounts.
+    SetMinTransferAmount {
+        /// The minimum transfer amount, or zero for none.
+        amount: u64,
+    },
+    /// Sets the smallest incoming transfer the account accepts, protecting it
+    /// from dust. Set to zero to accept any amount.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   * Single owner
+    ///   0. `[writable]` The account.
+    ///   1. `[signer]` The account's owner.
+    ///
+    ///   * Multisignature owner
+    ///   0. `[writable]` The account.
+    ///   1. `[]` The account's multisignature owner.
+    ///   2. ..2+M `[signer]` M signer accounts.
+    SetMinIncomingAmount {
+        /// The minimum incoming amount, or zero for none.
+        amount: u64,
+    },
+}
+impl TokenInstruction {
+    /// Unpacks a byte buffer into a [TokenInstruction](enum.TokenInstruction.html).
+    pub fn unpack(input: &[u8]) -> Result<Self, ProgramError> {
+        use TokenError::InvalidInstruction;
+
+        let (&tag, rest) = input.split_first().ok_or(InvalidInstruction)?;
+        Ok(match tag {
+            0 => {
+                let (&decimals, rest) = rest.split_first().ok_or(InvalidInstruction)?;
+                let (mint_authority, rest) = Self::unpack_pubkey(rest)?;
+                let (freeze_authority, _rest) = Self::unpack_pubkey_option(rest)?;
+                Self::InitializeMint {
+                    mint_authority,
+                    freeze_authority,
+                    decimals,
+                }
+            }
+            1 => Self::InitializeAccount,            
+            3 | 4 | 7 | 8 => {
+                let amount = rest
+                    .get(..8)
+                    .and_then(|slice| slice.try_into().ok())
+                    .map(u64::from_le_bytes)
+                    .ok_or(InvalidInstruction)?;
+                match tag {
+                    3 => Self::Transfer { amount },
+                    4 => Self::Approve { amount },
+                    7 => Self::MintTo { amount },
+                    8 => Self::Burn { amount },
+                    _ => unreachable!(),
+                }
+            }         
+            16 => {
+                let (owner, _rest) = Self::unpack_pubkey(rest)?;
+                Self::InitializeAccount2 { owner }
+            }
+            17 => {
+                let (authority, rest) = Self::unpack_pubkey(rest)?;
+                let (allowance, _rest) = Self::unpack_u64(rest)?;
+                Self::InitializeMinter {
+                    authority,
+                    allowance,
+                }
+            }
+            18 => {
+                let (allowance, _rest) = Self::unpack_u64(rest)?;
+                Self::SetMinterAllowance { allowance }
+            }
+            19 => Self::RevokeMinter,
+            20 => {
+                let (role, rest) = rest.split_first().ok_or(InvalidInstruction)?;
+                let role = MintRole::from(*role)?;
+                let (new_authority, _rest) = Self::unpack_pubkey_option(rest)?;
+                Self::SetMintRole {
+                    role,
+                    new_authority,
+                }
+            }
+            21 => Self::PauseMinting,
+            22 => Self::ResumeMinting,
+            23..=25 => {
+                let (authority_type, rest) = rest.split_first().ok_or(InvalidInstruction)?;
+                let authority_type = AuthorityType::from(*authority_type)?;
+                match tag {
+                    23 => {
+                        let (new_authority, _rest) = Self::unpack_pubkey(rest)?;
+                        Self::ProposeAuthority {
+                            authority_type,
+                            new_authority,
+                        }
+                    }
+                    24 => Self::AcceptAuthority { authority_type },
+                    25 => Self::CancelAuthorityProposal { authority_type },
+                    _ => unreachable!(),
+                }
+            }
+            26 => {
+                let (delay, _rest) = Self::unpack_u64(rest)?;
+                Self::SetAuthorityTimelock { delay }
+            }
+            27 => {
+                let (&authority, rest) = rest.split_first().ok_or(InvalidInstruction)?;
+                let authority = match TimelockedAuthority::try_from_primitive(authority) {
+                    Ok(TimelockedAuthority::None) | Err(_) => return Err(InvalidInstruction.into()),
+                    Ok(authority) => authority,
+                };
+                let (new_authority, _rest) = Self::unpack_pubkey_option(rest)?;
+                Self::QueueAuthorityChange {
+                    authority,
+                    new_authority,
+                }
+            }
+            28 => Self::ExecuteAuthorityChange,
+            29 => Self::CancelAuthorityChange,
+            30 => {
+                let (guardians, rest) = Self::unpack_pubkey_option(rest)?;
+                let (recovery_delay, _rest) = Self::unpack_u64(rest)?;
+                Self::SetGuardians {
+                    guardians,
+                    recovery_delay,
+                }
+            }
+            31 => {
+                let (new_owner, _rest) = Self::unpack_pubkey(rest)?;
+                Self::InitiateRecovery { new_owner }
+            }
+            32 => Self::CompleteRecovery,
+            33 => Self::VetoRecovery,
+            34 => {
+                let (amount, rest) = Self::unpack_u64(rest)?;
+                let (expiry, rest) = Self::unpack_u64(rest)?;
+                let (max_per_transfer, _rest) = Self::unpack_u64_option(rest)?;
+                Self::ApproveWithLimits {
+                    amount,
+                    expiry: expiry as UnixTimestamp,
+                    max_per_transfer,
+                }
+            }
+            35 => {
+                let (amount, _rest) = Self::unpack_u64(rest)?;
+                Self::SetAllowance { amount }
+            }
+            36 => Self::CloseAllowance,
+            37 => {
+                let (expected_delegate, rest) = Self::unpack_pubkey_option(rest)?;
+                let (expected_amount, rest) = Self::unpack_u64(rest)?;
+                let (amount, _rest) = Self::unpack_u64(rest)?;
+                Self::ApproveIfCurrent {
+                    expected_delegate,
+                    expected_amount,
+                    amount,
+                }
+            }
+            38 | 39 => {
+                let (amount, _rest) = Self::unpack_u64(rest)?;
+                match tag {
+                    38 => Self::IncreaseAllowance { amount },
+                    39 => Self::DecreaseAllowance { amount },
+                    _ => unreachable!(),
+                }
+            }
+            40 => Self::InitializeNonce,
+            41 | 42 => {
+                let (amount, rest) = Self::unpack_u64(rest)?;
+                let (nonce, rest) = Self::unpack_u64(rest)?;
+                let (valid_after, rest) = Self::unpack_u64(rest)?;
+                let (valid_until, _rest) = Self::unpack_u64(rest)?;
+                let valid_after = valid_after as UnixTimestamp;
+                let valid_until = valid_until as UnixTimestamp;
+                match tag {
+                    41 => Self::Permit {
+                        amount,
+                        nonce,
+                        valid_after,
+                        valid_until,
+                    },
+                    42 => Self::TransferWithAuthorization {
+                        amount,
+                        nonce,
+                        valid_after,
+                        valid_until,
+                    },
+                    _ => unreachable!(),
+                }
+            }
+            43 => {
+                let (session_key, rest) = Self::unpack_pubkey(rest)?;
+                let (expiry, rest) = Self::unpack_u64(rest)?;
+                let (&scope, rest) = rest.split_first().ok_or(InvalidInstruction)?;
+                let (&num_destinations, mut rest) = rest.split_first().ok_or(InvalidInstruction)?;
+                if num_destinations as usize > MAX_SESSION_DESTINATIONS {
+                    return Err(InvalidInstruction.into());
+                }
+                let mut destinations = Vec::with_capacity(num_destinations as usize);
+                for _ in 0..num_destinations {
+                    let (destination, next) = Self::unpack_pubkey(rest)?;
+                    destinations.push(destination);
+                    rest = next;
+                }
+                let (max_amount, _rest) = Self::unpack_u64(rest)?;
+                Self::CreateSession {
+                    session_key,
+                    expiry: expiry as UnixTimestamp,
+                    scope,
+                    destinations,
+                    max_amount,
+                }
+            }
+            44 => Self::RevokeSession,
+            45 => {
+                let address = rest
+                    .get(..20)
+                    .and_then(|slice| slice.try_into().ok())
+                    .ok_or(InvalidInstruction)?;
+                Self::InitializeEthereumOwner { address }
+            }
+            46 => {
+                let threshold = rest
+                    .get(..2)
+                    .and_then(|slice| slice.try_into().ok())
+                    .map(u16::from_le_bytes)
+                    .ok_or(InvalidInstruction)?;
+                Self::InitializeWeightedMultisig {
+                    threshold,
+                    weights: rest[2..].to_vec(),
+                }
+            }
+            47 | 48 => {
+                let (signer, _rest) = Self::unpack_pubkey(rest)?;
+                match tag {
+                    47 => Self::AddSigner { signer },
+                    48 => Self::RemoveSigner { signer },
+                    _ => unreachable!(),
+                }
+            }
+            49 => {
+                let (&m, _rest) = rest.split_first().ok_or(InvalidInstruction)?;
+                Self::SetThreshold { m }
+            }
+            50 => {
+                let (expiry, rest) = Self::unpack_u64(rest)?;
+                Self::CreateProposal {
+                    expiry: expiry as UnixTimestamp,
+                    instruction_data: rest.to_vec(),
+                }
+            }
+            51 => Self::ApproveProposal,
+            52 => Self::ExecuteProposal,
+            53 => Self::CancelProposal,
+            54 => {
+                let (capturer, rest) = Self::unpack_pubkey(rest)?;
+                let (amount, rest) = Self::unpack_u64(rest)?;
+                let (expiry, _rest) = Self::unpack_u64(rest)?;
+                Self::PlaceHold {
+                    capturer,
+                    amount,
+                    expiry: expiry as UnixTimestamp,
+                }
+            }
+            55 => {
+                let (amount, _rest) = Self::unpack_u64(rest)?;
+                Self::CaptureHold { amount }
+            }
+            56 => Self::ReleaseHold,
+            57 => Self::SetReceiveOnly,
+            58 => Self::SetSendOnly,
+            59 => {
+                let (&reason, rest) = rest.split_first().ok_or(InvalidInstruction)?;
+                let (auto_thaw_timestamp, _rest) = Self::unpack_u64(rest)?;
+                Self::FreezeAccountWithReason {
+                    reason,
+                    auto_thaw_timestamp: auto_thaw_timestamp as UnixTimestamp,
+                }
+            }
+            60 | 61 => {
+                let (&count, _rest) = rest.split_first().ok_or(InvalidInstruction)?;
+                match tag {
+                    60 => Self::FreezeAccounts { count },
+                    61 => Self::ThawAccounts { count },
+                    _ => unreachable!(),
+                }
+            }
+            62 => {
+                let (amount, rest) = Self::unpack_u64(rest)?;
+                let (window, _rest) = Self::unpack_u64(rest)?;
+                Self::SetSpendingLimit { amount, window }
+            }
+            63 => Self::InitializeDestinationAllowlist,
+            64 | 65 => {
+                let (destination, _rest) = Self::unpack_pubkey(rest)?;
+                match tag {
+                    64 => Self::AddAllowedDestination { destination },
+                    65 => Self::RemoveAllowedDestination { destination },
+                    _ => unreachable!(),
+                }
+            }
+            66 => {
+                let (max_balance, _rest) = Self::unpack_u64_option(rest)?;
+                Self::SetMaxBalance { max_balance }
+            }
+            67 => Self::AddMaxBalanceExemption,
+            68 => Self::RemoveMaxBalanceExemption,
+            69 => {
+                let (trading_start_timestamp, rest) = Self::unpack_u64(rest)?;
+                let (transfer_cooldown, _rest) = Self::unpack_u64(rest)?;
+                Self::SetLaunchControls {
+                    trading_start_timestamp: trading_start_timestamp as UnixTimestamp,
+                    transfer_cooldown,
+                }
+            }
+            70 => {
+                let (expiry, rest) = Self::unpack_u64(rest)?;
+                let (jurisdiction, _rest) = Self::unpack_u16(rest)?;
+                Self::IssueCredential {
+                    expiry: expiry as UnixTimestamp,
+                    jurisdiction,
+                }
+            }
+            71 => Self::RevokeCredential,
+            72 | 73 => {
+                let (amount, _rest) = Self::unpack_u64(rest)?;
+                match tag {
+                    72 => Self::SetMinTransferAmount { amount },
+                    73 => Self::SetMinIncomingAmount { amount },
+                    _ => unreachable!(),
+                }
+            }
+
+            _ => return Err(TokenError::InvalidInstruction.into()),
+        })
+    }
+
+    /// Packs a [TokenInstruction](enum.TokenInstruction.html) into a byte buffer.
+    pub fn pack(&self) -> Vec<u8> {
+        let mut buf = Vec::with_capacity(size_of::<Self>());
+        match self {
+            &Self::InitializeMint {
+                ref mint_authority,
+                ref freeze_authority,
+                decimals,
+            } => {
+                buf.push(0);
+                buf.push(decimals);
+                buf.extend_from_slice(mint_authority.as_ref());
+                Self::pack_pubkey_option(freeze_authority, &mut buf);
+            }
+            Self::InitializeAccount => buf.push(1),            
+            &Self::Transfer { amount } => {
+                buf.push(3);
+                buf.extend_from_slice(&amount.to_le_bytes());
+            }
+            &Self::Approve { amount } => {
+                buf.push(4);
+                buf.extend_from_slice(&amount.to_le_bytes());
+            }
+            &Self::MintTo { amount } => {
+                buf.push(7);
+                buf.extend_from_slice(&amount.to_le_bytes());
+            }
+            &Self::Burn { amount } => {
+                buf.push(8);
+                buf.extend_from_slice(&amount.to_le_bytes());
+            }            
+            &Self::InitializeAccount2 { owner } => {
+                buf.push(16);
+                buf.extend_from_slice(owner.as_ref());
+            }
+            &Self::InitializeMinter {
+                ref authority,
+                allowance,
+            } => {
+                buf.push(17);
+                buf.extend_from_slice(authority.as_ref());
+                buf.extend_from_slice(&allowance.to_le_bytes());
+            }
+            &Self::SetMinterAllowance { allowance } => {
+                buf.push(18);
+                buf.extend_from_slice(&allowance.to_le_bytes());
+            }
+            Self::RevokeMinter => buf.push(19),
+            Self::SetMintRole {
+                role,
+                ref new_authority,
+            } => {
+                buf.push(20);
+                buf.push(role.into());
+                Self::pack_pubkey_option(new_authority, &mut buf);
+            }
+            Self::PauseMinting => buf.push(21),
+            Self::ResumeMinting => buf.push(22),
+            Self::ProposeAuthority {
+                authority_type,
+                ref new_authority,
+            } => {
+                buf.push(23);
+                buf.push(authority_type.into());
+                buf.extend_from_slice(new_authority.as_ref());
+            }
+            Self::AcceptAuthority { authority_type } => {
+                buf.push(24);
+                buf.push(authority_type.into());
+            }
+            Self::CancelAuthorityProposal { authority_type } => {
+                buf.push(25);
+                buf.push(authority_type.into());
+            }
+            &Self::SetAuthorityTimelock { delay } => {
+                buf.push(26);
+                buf.extend_from_slice(&delay.to_le_bytes());
+            }
+            &Self::QueueAuthorityChange {
+                authority,
+                ref new_authority,
+            } => {
+                buf.push(27);
+                buf.push(authority as u8);
+                Self::pack_pubkey_option(new_authority, &mut buf);
+            }
+            Self::ExecuteAuthorityChange => buf.push(28),
+            Self::CancelAuthorityChange => buf.push(29),
+            &Self::SetGuardians {
+                ref guardians,
+                recovery_delay,
+            } => {
+                buf.push(30);
+                Self::pack_pubkey_option(guardians, &mut buf);
+                buf.extend_from_slice(&recovery_delay.to_le_bytes());
+            }
+            Self::InitiateRecovery { new_owner } => {
+                buf.push(31);
+                buf.extend_from_slice(new_owner.as_ref());
+            }
+            Self::CompleteRecovery => buf.push(32),
+            Self::VetoRecovery => buf.push(33),
+            &Self::ApproveWithLimits {
+                amount,
+                expiry,
+                ref max_per_transfer,
+            } => {
+                buf.push(34);
+                buf.extend_from_slice(&amount.to_le_bytes());
+                buf.extend_from_slice(&expiry.to_le_bytes());
+                Self::pack_u64_option(max_per_transfer, &mut buf);
+            }
+            &Self::SetAllowance { amount } => {
+                buf.push(35);
+                buf.extend_from_slice(&amount.to_le_bytes());
+            }
+            Self::CloseAllowance => buf.push(36),
+            &Self::ApproveIfCurrent {
+                ref expected_delegate,
+                expected_amount,
+                amount,
+            } => {
+                buf.push(37);
+                Self::pack_pubkey_option(expected_delegate, &mut buf);
+                buf.extend_from_slice(&expected_amount.to_le_bytes());
+                buf.extend_from_slice(&amount.to_le_bytes());
+            }
+            &Self::IncreaseAllowance { amount } => {
+                buf.push(38);
+                buf.extend_from_slice(&amount.to_le_bytes());
+            }
+            &Self::DecreaseAllowance { amount } => {
+                buf.push(39);
+                buf.extend_from_slice(&amount.to_le_bytes());
+            }
+            Self::InitializeNonce => buf.push(40),
+            &Self::Permit {
+                amount,
+                nonce,
+                valid_after,
+                valid_until,
+            } => {
+                buf.push(41);
+                buf.extend_from_slice(&amount.to_le_bytes());
+                buf.extend_from_slice(&nonce.to_le_bytes());
+                buf.extend_from_slice(&valid_after.to_le_bytes());
+                buf.extend_from_slice(&valid_until.to_le_bytes());
+            }
+            &Self::TransferWithAuthorization {
+                amount,
+                nonce,
+                valid_after,
+                valid_until,
+            } => {
+                buf.push(42);
+                buf.extend_from_slice(&amount.to_le_bytes());
+                buf.extend_from_slice(&nonce.to_le_bytes());
+                buf.extend_from_slice(&valid_after.to_le_bytes());
+                buf.extend_from_slice(&valid_until.to_le_bytes());
+            }
+            &Self::CreateSession {
+                ref session_key,
+                expiry,
+                scope,
+                ref destinations,
+                max_amount,
+            } => {
+                buf.push(43);
+                buf.extend_from_slice(session_key.as_ref());
+                buf.extend_from_slice(&expiry.to_le_bytes());
+                buf.push(scope);
+                buf.push(destinations.len() as u8);
+                for destination in destinations {
+                    buf.extend_from_slice(destination.as_ref());
+                }
+                buf.extend_from_slice(&max_amount.to_le_bytes());
+            }
+            Self::RevokeSession => buf.push(44),
+            Self::InitializeEthereumOwner { ref address } => {
+                buf.push(45);
+                buf.extend_from_slice(address);
+            }
+            Self::InitializeWeightedMultisig {
+                threshold,
+                ref weights,
+            } => {
+                buf.push(46);
+                buf.extend_from_slice(&threshold.to_le_bytes());
+                buf.extend_from_slice(weights);
+            }
+            Self::AddSigner { ref signer } => {
+                buf.push(47);
+                buf.extend_from_slice(signer.as_ref());
+            }
+            Self::RemoveSigner { ref signer } => {
+                buf.push(48);
+                buf.extend_from_slice(signer.as_ref());
+            }
+            &Self::SetThreshold { m } => {
+                buf.push(49);
+                buf.push(m);
+            }
+            Self::CreateProposal {
+                expiry,
+                ref instruction_data,
+            } => {
+                buf.push(50);
+                buf.extend_from_slice(&expiry.to_le_bytes());
+                buf.extend_from_slice(instruction_data);
+            }
+            Self::ApproveProposal => buf.push(51),
+            Self::ExecuteProposal => buf.push(52),
+            Self::CancelProposal => buf.push(53),
+            &Self::PlaceHold {
+                ref capturer,
+                amount,
+                expiry,
+            } => {
+                buf.push(54);
+                buf.extend_from_slice(capturer.as_ref());
+                buf.extend_from_slice(&amount.to_le_bytes());
+                buf.extend_from_slice(&expiry.to_le_bytes());
+            }
+            &Self::CaptureHold { amount } => {
+                buf.push(55);
+                buf.extend_from_slice(&amount.to_le_bytes());
+            }
+            Self::ReleaseHold => buf.push(56),
+            Self::SetReceiveOnly => buf.push(57),
+            Self::SetSendOnly => buf.push(58),
+            &Self::FreezeAccountWithReason {
+                reason,
+                auto_thaw_timestamp,
+            } => {
+                buf.push(59);
+                buf.push(reason);
+                buf.extend_from_slice(&auto_thaw_timestamp.to_le_bytes());
+            }
+            &Self::FreezeAccounts { count } => {
+                buf.push(60);
+                buf.push(count);
+            }
+            &Self::ThawAccounts { count } => {
+                buf.push(61);
+                buf.push(count);
+            }
+            &Self::SetSpendingLimit { amount, window } => {
+                buf.push(62);
+                buf.extend_from_slice(&amount.to_le_bytes());
+                buf.extend_from_slice(&window.to_le_bytes());
+            }
+            Self::InitializeDestinationAllowlist => buf.push(63),
+            Self::AddAllowedDestination { destination } => {
+                buf.push(64);
+                buf.extend_from_slice(destination.as_ref());
+            }
+            Self::RemoveAllowedDestination { destination } => {
+                buf.push(65);
+                buf.extend_from_slice(destination.as_ref());
+            }
+            Self::SetMaxBalance { max_balance } => {
+                buf.push(66);
+                Self::pack_u64_option(max_balance, &mut buf);
+            }
+            Self::AddMaxBalanceExemption => buf.push(67),
+            Self::RemoveMaxBalanceExemption => buf.push(68),
+            &Self::SetLaunchControls {
+                trading_start_timestamp,
+                transfer_cooldown,
+            } => {
+                buf.push(69);
+                buf.extend_from_slice(&trading_start_timestamp.to_le_bytes());
+                buf.extend_from_slice(&transfer_cooldown.to_le_bytes());
+            }
+            &Self::IssueCredential {
+                expiry,
+                jurisdiction,
+            } => {
+                buf.push(70);
+                buf.extend_from_slice(&expiry.to_le_bytes());
+                buf.extend_from_slice(&jurisdiction.to_le_bytes());
+            }
+            Self::RevokeCredential => buf.push(71),
+            &Self::SetMinTransferAmount { amount } => {
+                buf.push(72);
+                buf.extend_from_slice(&amount.to_le_bytes());
+            }
+            &Self::SetMinIncomingAmount { amount } => {
+                buf.push(73);
+                buf.extend_from_slice(&amount.to_le_bytes());
+            }
+        };
+        buf
+    }
+
+    fn unpack_pubkey(input: &[u8]) -> Result<(Pubkey, &[u8]), ProgramError> {
+        if input.len() >= 32 {
+            let (key, rest) = input.split_at(32);
+            let pk = Pubkey::new(key);
+            Ok((pk, rest))
+        } else {
+            Err(TokenError::InvalidInstruction.into())
+        }
+    }
+
+    fn unpack_u64(input: &[u8]) -> Result<(u64, &[u8]), ProgramError> {
+        if input.len() >= 8 {
+            let (amount, rest) = input.split_at(8);
+            let amount = amount
+                .try_into()
+                .ok()
+                .map(u64::from_le_bytes)
+                .ok_or(TokenError::InvalidInstruction)?;
+            Ok((amount, rest))
+        } else {
+            Err(TokenError::InvalidInstruction.into())
+        }
+    }
+
+    fn unpack_u16(input: &[u8]) -> Result<(u16, &[u8]), ProgramError> {
+        if input.len() >= 2 {
+            let (value, rest) = input.split_at(2);
+            let value = value
+                .try_into()
+                .ok()
+                .map(u16::from_le_bytes)
+                .ok_or(TokenError::InvalidInstruction)?;
+            Ok((value, rest))
+        } else {
+            Err(TokenError::InvalidInstruction.into())
+        }
+    }
+
+    fn unpack_pubkey_option(input: &[u8]) -> Result<(COption<Pubkey>, &[u8]), ProgramError> {
+        match input.split_first() {
+            Option::Some((&0, rest)) => Ok((COption::None, rest)),
+            Option::Some((&1, rest)) if rest.len() >= 32 => {
+                let (key, rest) = rest.split_at(32);
+                let pk = Pubkey::new(key);
+                Ok((COption::Some(pk), rest))
+            }
+            _ => Err(TokenError::InvalidInstruction.into()),
+        }
+    }
+
+    fn pack_pubkey_option(value: &COption<Pubkey>, buf: &mut Vec<u8>) {
+        match *value {
+            COption::Some(ref key) => {
+                buf.push(1);
+                buf.extend_from_slice(&key.to_bytes());
+            }
+            COption::None => buf.push(0),
+        }
+    }
+
+    fn unpack_u64_option(input: &[u8]) -> Result<(COption<u64>, &[u8]), ProgramError> {
+        match input.split_first() {
+            Option::Some((&0, rest)) => Ok((COption::None, rest)),
+            Option::Some((&1, rest)) => {
+                let (value, rest) = Self::unpack_u64(rest)?;
+                Ok((COption::Some(value), rest))
+            }
+            _ => Err(TokenError::InvalidInstruction.into()),
+        }
+    }
+
+    fn pack_u64_option(value: &COption<u64>, buf: &mut Vec<u8>) {
+        match *value {
+            COption::Some(value) => {
+                buf.push(1);
+                buf.extend_from_slice(&value.to_le_bytes());
+            }
+            COption::None => buf.push(0),
+        }
+    }
+}
+
+/// Specifies the authority type for SetAuthority instructions
+#[repr(u8)]
+#[derive(Clone, Debug, PartialEq)]
+pub enum AuthorityType {
+    /// Authority to mint new tokens
+    MintTokens,
+    /// Authority to freeze any account associated with the Mint
+    FreezeAccount,
+    /// Owner of a given token account
+    AccountOwner,
+    /// Authority to close a token account
+    CloseAccount,
+}
+
+impl AuthorityType {
+    fn into(&self) -> u8 {
+        match self {
+            AuthorityType::MintTokens => 0,
+            AuthorityType::FreezeAccount => 1,
+            AuthorityType::AccountOwner => 2,
+            AuthorityType::CloseAccount => 3,
+        }
+    }
+
+    fn from(index: u8) -> Result<Self, ProgramError> {
+        match index {
+            0 => Ok(AuthorityType::MintTokens),
+            1 => Ok(AuthorityType::FreezeAccount),
+            2 => Ok(AuthorityType::AccountOwner),
+            3 => Ok(AuthorityType::CloseAccount),
+            _ => Err(TokenError::InvalidInstruction.into()),
+        }
+    }
+}
+
+/// Specifies the mint role for SetMintRole instructions
+#[repr(u8)]
+#[derive(Clone, Debug, PartialEq)]
+pub enum MintRole {
+    /// Authority to burn the Mint's tokens from accounts owned by the mint authority
+    Burner,
+    /// Authority to pause and resume minting
+    Pauser,
+    /// Authority to freeze and thaw any account associated with the Mint
+    Freezer,
+    /// Authority to issue and revoke the credentials holders need to send and receive tokens
+    ComplianceAuthority,
+}
+
+impl MintRole {
+    fn into(&self) -> u8 {
+        match self {
+            MintRole::Burner => 0,
+            MintRole::Pauser => 1,
+            MintRole::Freezer => 2,
+            MintRole::ComplianceAuthority => 3,
+        }
+    }
+
+    fn from(index: u8) -> Result<Self, ProgramError> {
+        match index {
+            0 => Ok(MintRole::Burner),
+            1 => Ok(MintRole::Pauser),
+            2 => Ok(MintRole::Freezer),
+            3 => Ok(MintRole::ComplianceAuthority),
+            _ => Err(TokenError::InvalidInstruction.into()),
+        }
+    }
+}
+
+/// Creates a `InitializeMint` instruction.
+pub fn initialize_mint(
+    token_program_id: &Pubkey,
+    mint_pubkey: &Pubkey,
+    mint_authority_pubkey: &Pubkey,
+    freeze_authority_pubkey: Option<&Pubkey>,
+    decimals: u8,
+) -> Result<Instruction, ProgramError> {
+    check_program_account(token_program_id)?;
+    let freeze_authority = freeze_authority_pubkey.cloned().into();
+    let data = TokenInstruction::InitializeMint {
+        mint_authority: *mint_authority_pubkey,
+        freeze_authority,
+        decimals,
+    }
+    .pack();
+
+    let accounts = vec![
+        AccountMeta::new(*mint_pubkey, false),
+        AccountMeta::new_readonly(sysvar::rent::id(), false),
+    ];
+
+    Ok(Instruction {
+        program_id: *token_program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a `InitializeAccount` instruction.
+pub fn initialize_account(
+    token_program_id: &Pubkey,
+    account_pubkey: &Pubkey,
+    mint_pubkey: &Pubkey,
+    owner_pubkey: &Pubkey,
+) -> Result<Instruction, ProgramError> {
+    check_program_account(token_program_id)?;
+    let data = TokenInstruction::InitializeAccount.pack();
+
+    let accounts = vec![
+        AccountMeta::new(*account_pubkey, false),
+        AccountMeta::new_readonly(*mint_pubkey, false),
+        AccountMeta::new_readonly(*owner_pubkey, false),
+        AccountMeta::new_readonly(sysvar::rent::id(), false),
+    ];
+
+    Ok(Instruction {
+        program_id: *token_program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a `InitializeAccount2` instruction.
+pub fn initialize_account2(
+    token_program_id: &Pubkey,
+    account_pubkey: &Pubkey,
+    mint_pubkey: &Pubkey,
+    owner_pubkey: &Pubkey,
+) -> Result<Instruction, ProgramError> {
+    check_program_account(token_program_id)?;
+    let data = TokenInstruction::InitializeAccount2 {
+        owner: *owner_pubkey,
+    }
+    .pack();
+
+    let accounts = vec![
+        AccountMeta::new(*account_pubkey, false),
+        AccountMeta::new_readonly(*mint_pubkey, false),
+        AccountMeta::new_readonly(sysvar::rent::id(), false),
+    ];
+
+    Ok(Instruction {
+        program_id: *token_program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a `InitializeMultisig` instruction.
+pub fn initialize_multisig(
+    token_program_id: &Pubkey,
+    multisig_pubkey: &Pubkey,
+    signer_pubkeys: &[&Pubkey],
+    m: u8,
+) -> Result<Instruction, ProgramError> {
+    check_program_account(token_program_id)?;
+    if !is_valid_signer_index(m as usize)
         || !is_valid_signer_index(signer_pubkeys.len())
         || m as usize > signer_pubkeys.len()
     {
         return Err(ProgramError::MissingRequiredSignature);
     }
-    let data = TokenInstruction::InitializeMultisig { m }.pack();
+    let data = TokenInstruction::InitializeMultisig { m }.pack();
+
+    let mut accounts = Vec::with_capacity(1 + 1 + signer_pubkeys.len());
+    accounts.push(AccountMeta::new(*multisig_pubkey, false));
+    accounts.push(AccountMeta::new_readonly(sysvar::rent::id(), false));
+    for signer_pubkey in signer_pubkeys.iter() {
+        accounts.push(AccountMeta::new_readonly(**signer_pubkey, false));
+    }
+
+    Ok(Instruction {
+        program_id: *token_program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a `InitializeWeightedMultisig` instruction.
+pub fn initialize_weighted_multisig(
+    token_program_id: &Pubkey,
+    multisig_pubkey: &Pubkey,
+    signer_pubkeys: &[&Pubkey],
+    weights: &[u8],
+    threshold: u16,
+) -> Result<Instruction, ProgramError> {
+    check_program_account(token_program_id)?;
+    let total_weight: u16 = weights.iter().map(|weight| *weight as u16).sum();
+    if !is_valid_signer_index(signer_pubkeys.len())
+        || weights.len() != signer_pubkeys.len()
+        || threshold == 0
+        || threshold > total_weight
+    {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    let data = TokenInstruction::InitializeWeightedMultisig {
+        threshold,
+        weights: weights.to_vec(),
+    }
+    .pack();
+
+    let mut accounts = Vec::with_capacity(1 + 1 + signer_pubkeys.len());
+    accounts.push(AccountMeta::new(*multisig_pubkey, false));
+    accounts.push(AccountMeta::new_readonly(sysvar::rent::id(), false));
+    for signer_pubkey in signer_pubkeys.iter() {
+        accounts.push(AccountMeta::new_readonly(**signer_pubkey, false));
+    }
+
+    Ok(Instruction {
+        program_id: *token_program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates an `AddSigner` instruction.
+pub fn add_signer(
+    token_program_id: &Pubkey,
+    multisig_pubkey: &Pubkey,
+    new_signer_pubkey: &Pubkey,
+    signer_pubkeys: &[&Pubkey],
+) -> Result<Instruction, ProgramError> {
+    check_program_account(token_program_id)?;
+    let data = TokenInstruction::AddSigner {
+        signer: *new_signer_pubkey,
+    }
+    .pack();
+    Ok(multisig_update_instruction(
+        token_program_id,
+        multisig_pubkey,
+        signer_pubkeys,
+        data,
+    ))
+}
+
+/// Creates a `RemoveSigner` instruction.
+pub fn remove_signer(
+    token_program_id: &Pubkey,
+    multisig_pubkey: &Pubkey,
+    removed_signer_pubkey: &Pubkey,
+    signer_pubkeys: &[&Pubkey],
+) -> Result<Instruction, ProgramError> {
+    check_program_account(token_program_id)?;
+    let data = TokenInstruction::RemoveSigner {
+        signer: *removed_signer_pubkey,
+    }
+    .pack();
+    Ok(multisig_update_instruction(
+        token_program_id,
+        multisig_pubkey,
+        signer_pubkeys,
+        data,
+    ))
+}
+
+/// Creates a `SetThreshold` instruction.
+pub fn set_threshold(
+    token_program_id: &Pubkey,
+    multisig_pubkey: &Pubkey,
+    m: u8,
+    signer_pubkeys: &[&Pubkey],
+) -> Result<Instruction, ProgramError> {
+    check_program_account(token_program_id)?;
+    if !is_valid_signer_index(m as usize) {
+        return Err(ProgramError::InvalidArgument);
+    }
+    let data = TokenInstruction::SetThreshold { m }.pack();
+    Ok(multisig_update_instruction(
+        token_program_id,
+        multisig_pubkey,
+        signer_pubkeys,
+        data,
+    ))
+}
+
+fn multisig_update_instruction(
+    token_program_id: &Pubkey,
+    multisig_pubkey: &Pubkey,
+    signer_pubkeys: &[&Pubkey],
+    data: Vec<u8>,
+) -> Instruction {
+    let mut accounts = Vec::with_capacity(1 + signer_pubkeys.len());
+    accounts.push(AccountMeta::new(*multisig_pubkey, false));
+    for signer_pubkey in signer_pubkeys.iter() {
+        accounts.push(AccountMeta::new_readonly(**signer_pubkey, true));
+    }
+
+    Instruction {
+        program_id: *token_program_id,
+        accounts,
+        data,
+    }
+}
+
+/// Creates a `CreateProposal` instruction proposing `instruction`, which names
+/// the multisignature as its authority.
+pub fn create_proposal(
+    token_program_id: &Pubkey,
+    proposal_pubkey: &Pubkey,
+    multisig_pubkey: &Pubkey,
+    proposer_pubkey: &Pubkey,
+    instruction: &Instruction,
+    expiry: UnixTimestamp,
+) -> Result<Instruction, ProgramError> {
+    check_program_account(token_program_id)?;
+    if instruction.program_id != *token_program_id
+        || instruction.data.len() > MAX_PROPOSAL_DATA_LEN
+        || instruction.accounts.len() > MAX_PROPOSAL_ACCOUNTS
+    {
+        return Err(ProgramError::InvalidArgument);
+    }
+    let data = TokenInstruction::CreateProposal {
+        expiry,
+        instruction_data: instruction.data.clone(),
+    }
+    .pack();
+
+    let mut accounts = Vec::with_capacity(4 + instruction.accounts.len());
+    accounts.push(AccountMeta::new(*proposal_pubkey, false));
+    accounts.push(AccountMeta::new_readonly(*multisig_pubkey, false));
+    accounts.push(AccountMeta::new_readonly(*proposer_pubkey, true));
+    accounts.push(AccountMeta::new_readonly(sysvar::rent::id(), false));
+    for account_meta in instruction.accounts.iter() {
+        accounts.push(AccountMeta::new_readonly(account_meta.pubkey, false));
+    }
+
+    Ok(Instruction {
+        program_id: *token_program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates an `ApproveProposal` instruction.
+pub fn approve_proposal(
+    token_program_id: &Pubkey,
+    proposal_pubkey: &Pubkey,
+    multisig_pubkey: &Pubkey,
+    signer_pubkey: &Pubkey,
+) -> Result<Instruction, ProgramError> {
+    check_program_account(token_program_id)?;
+    let data = TokenInstruction::ApproveProposal.pack();
+
+    let accounts = vec![
+        AccountMeta::new(*proposal_pubkey, false),
+        AccountMeta::new_readonly(*multisig_pubkey, false),
+        AccountMeta::new_readonly(*signer_pubkey, true),
+        AccountMeta::new_readonly(sysvar::clock::id(), false),
+    ];
+
+    Ok(Instruction {
+        program_id: *token_program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates an `ExecuteProposal` instruction for a proposal of `instruction`.
+pub fn execute_proposal(
+    token_program_id: &Pubkey,
+    proposal_pubkey: &Pubkey,
+    multisig_pubkey: &Pubkey,
+    instruction: &Instruction,
+) -> Result<Instruction, ProgramError> {
+    check_program_account(token_program_id)?;
+    let data = TokenInstruction::ExecuteProposal.pack();
+
+    let mut accounts = Vec::with_capacity(3 + instruction.accounts.len());
+    accounts.push(AccountMeta::new(*proposal_pubkey, false));
+    accounts.push(AccountMeta::new_readonly(*multisig_pubkey, false));
+    accounts.push(AccountMeta::new_readonly(sysvar::clock::id(), false));
+    for account_meta in instruction.accounts.iter() {
+        accounts.push(AccountMeta {
+            pubkey: account_meta.pubkey,
+            is_signer: false,
+            is_writable: account_meta.is_writable,
+        });
+    }
+
+    Ok(Instruction {
+        program_id: *token_program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a `CancelProposal` instruction.
+pub fn cancel_proposal(
+    token_program_id: &Pubkey,
+    proposal_pubkey: &Pubkey,
+    authority_pubkey: &Pubkey,
+    signer_pubkeys: &[&Pubkey],
+) -> Result<Instruction, ProgramError> {
+    check_program_account(token_program_id)?;
+    let data = TokenInstruction::CancelProposal.pack();
+
+    let mut accounts = Vec::with_capacity(2 + signer_pubkeys.len());
+    accounts.push(AccountMeta::new(*proposal_pubkey, false));
+    accounts.push(AccountMeta::new_readonly(
+        *authority_pubkey,
+        signer_pubkeys.is_empty(),
+    ));
+    for signer_pubkey in signer_pubkeys.iter() {
+        accounts.push(AccountMeta::new_readonly(**signer_pubkey, true));
+    }
+
+    Ok(Instruction {
+        program_id: *token_program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a `PlaceHold` instruction.
+pub fn place_hold(
+    token_program_id: &Pubkey,
+    account_pubkey: &Pubkey,
+    owner_pubkey: &Pubkey,
+    signer_pubkeys: &[&Pubkey],
+    capturer_pubkey: &Pubkey,
+    amount: u64,
+    expiry: UnixTimestamp,
+) -> Result<Instruction, ProgramError> {
+    check_program_account(token_program_id)?;
+    let data = TokenInstruction::PlaceHold {
+        capturer: *capturer_pubkey,
+        amount,
+        expiry,
+    }
+    .pack();
+
+    let mut accounts = Vec::with_capacity(2 + signer_pubkeys.len());
+    accounts.push(AccountMeta::new(*account_pubkey, false));
+    accounts.push(AccountMeta::new_readonly(
+        *owner_pubkey,
+        signer_pubkeys.is_empty(),
+    ));
+    for signer_pubkey in signer_pubkeys.iter() {
+        accounts.push(AccountMeta::new_readonly(**signer_pubkey, true));
+    }
+
+    Ok(Instruction {
+        program_id: *token_program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a `CaptureHold` instruction.
+pub fn capture_hold(
+    token_program_id: &Pubkey,
+    account_pubkey: &Pubkey,
+    destination_pubkey: &Pubkey,
+    capturer_pubkey: &Pubkey,
+    signer_pubkeys: &[&Pubkey],
+    amount: u64,
+) -> Result<Instruction, ProgramError> {
+    check_program_account(token_program_id)?;
+    let data = TokenInstruction::CaptureHold { amount }.pack();
+
+    let mut accounts = Vec::with_capacity(4 + signer_pubkeys.len());
+    accounts.push(AccountMeta::new(*account_pubkey, false));
+    accounts.push(AccountMeta::new(*destination_pubkey, false));
+    accounts.push(AccountMeta::new_readonly(sysvar::clock::id(), false));
+    accounts.push(AccountMeta::new_readonly(
+        *capturer_pubkey,
+        signer_pubkeys.is_empty(),
+    ));
+    for signer_pubkey in signer_pubkeys.iter() {
+        accounts.push(AccountMeta::new_readonly(**signer_pubkey, true));
+    }
+
+    Ok(Instruction {
+        program_id: *token_program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a `ReleaseHold` instruction. Pass no capturer to release an
+/// expired hold.
+pub fn release_hold(
+    token_program_id: &Pubkey,
+    account_pubkey: &Pubkey,
+    capturer_pubkey: Option<&Pubkey>,
+    signer_pubkeys: &[&Pubkey],
+) -> Result<Instruction, ProgramError> {
+    check_program_account(token_program_id)?;
+    let data = TokenInstruction::ReleaseHold.pack();
+
+    let mut accounts = Vec::with_capacity(3 + signer_pubkeys.len());
+    accounts.push(AccountMeta::new(*account_pubkey, false));
+    accounts.push(AccountMeta::new_readonly(sysvar::clock::id(), false));
+    if let Some(capturer_pubkey) = capturer_pubkey {
+        accounts.push(AccountMeta::new_readonly(
+            *capturer_pubkey,
+            signer_pubkeys.is_empty(),
+        ));
+        for signer_pubkey in signer_pubkeys.iter() {
+            accounts.push(AccountMeta::new_readonly(**signer_pubkey, true));
+        }
+    }
+
+    Ok(Instruction {
+        program_id: *token_program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a `Transfer` instruction.
+pub fn transfer(
+    token_program_id: &Pubkey,
+    source_pubkey: &Pubkey,
+    destination_pubkey: &Pubkey,
+    authority_pubkey: &Pubkey,
+    signer_pubkeys: &[&Pubkey],
+    amount: u64,
+) -> Result<Instruction, ProgramError> {
+    check_program_account(token_program_id)?;
+    let data = TokenInstruction::Transfer { amount }.pack();
+
+    let mut accounts = Vec::with_capacity(3 + signer_pubkeys.len());
+    accounts.push(AccountMeta::new(*source_pubkey, false));
+    accounts.push(AccountMeta::new(*destination_pubkey, false));
+    accounts.push(AccountMeta::new_readonly(
+        *authority_pubkey,
+        signer_pubkeys.is_empty(),
+    ));
+    for signer_pubkey in signer_pubkeys.iter() {
+        accounts.push(AccountMeta::new_readonly(**signer_pubkey, true));
+    }
+
+    Ok(Instruction {
+        program_id: *token_program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates an `Approve` instruction.
+pub fn approve(
+    token_program_id: &Pubkey,
+    source_pubkey: &Pubkey,
+    delegate_pubkey: &Pubkey,
+    owner_pubkey: &Pubkey,
+    signer_pubkeys: &[&Pubkey],
+    amount: u64,
+) -> Result<Instruction, ProgramError> {
+    check_program_account(token_program_id)?;
+    let data = TokenInstruction::Approve { amount }.pack();
+
+    let mut accounts = Vec::with_capacity(3 + signer_pubkeys.len());
+    accounts.push(AccountMeta::new(*source_pubkey, false));
+    accounts.push(AccountMeta::new_readonly(*delegate_pubkey, false));
+    accounts.push(AccountMeta::new_readonly(
+        *owner_pubkey,
+        signer_pubkeys.is_empty(),
+    ));
+    for signer_pubkey in signer_pubkeys.iter() {
+        accounts.push(AccountMeta::new_readonly(**signer_pubkey, true));
+    }
+
+    Ok(Instruction {
+        program_id: *token_program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates an `ApproveIfCurrent` instruction.
+#[allow(clippy::too_many_arguments)]
+pub fn approve_if_current(
+    token_program_id: &Pubkey,
+    source_pubkey: &Pubkey,
+    delegate_pubkey: &Pubkey,
+    owner_pubkey: &Pubkey,
+    signer_pubkeys: &[&Pubkey],
+    expected_delegate_pubkey: Option<&Pubkey>,
+    expected_amount: u64,
+    amount: u64,
+) -> Result<Instruction, ProgramError> {
+    check_program_account(token_program_id)?;
+    let expected_delegate = expected_delegate_pubkey.cloned().into();
+    let data = TokenInstruction::ApproveIfCurrent {
+        expected_delegate,
+        expected_amount,
+        amount,
+    }
+    .pack();
+
+    let mut accounts = Vec::with_capacity(3 + signer_pubkeys.len());
+    accounts.push(AccountMeta::new(*source_pubkey, false));
+    accounts.push(AccountMeta::new_readonly(*delegate_pubkey, false));
+    accounts.push(AccountMeta::new_readonly(
+        *owner_pubkey,
+        signer_pubkeys.is_empty(),
+    ));
+    for signer_pubkey in signer_pubkeys.iter() {
+        accounts.push(AccountMeta::new_readonly(**signer_pubkey, true));
+    }
+
+    Ok(Instruction {
+        program_id: *token_program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates an `IncreaseAllowance` instruction.
+pub fn increase_allowance(
+    token_program_id: &Pubkey,
+    source_pubkey: &Pubkey,
+    delegate_pubkey: &Pubkey,
+    owner_pubkey: &Pubkey,
+    signer_pubkeys: &[&Pubkey],
+    amount: u64,
+) -> Result<Instruction, ProgramError> {
+    check_program_account(token_program_id)?;
+    let data = TokenInstruction::IncreaseAllowance { amount }.pack();
+
+    let mut accounts = Vec::with_capacity(3 + signer_pubkeys.len());
+    accounts.push(AccountMeta::new(*source_pubkey, false));
+    accounts.push(AccountMeta::new_readonly(*delegate_pubkey, false));
+    accounts.push(AccountMeta::new_readonly(
+        *owner_pubkey,
+        signer_pubkeys.is_empty(),
+    ));
+    for signer_pubkey in signer_pubkeys.iter() {
+        accounts.push(AccountMeta::new_readonly(**signer_pubkey, true));
+    }
+
+    Ok(Instruction {
+        program_id: *token_program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a `DecreaseAllowance` instruction.
+pub fn decrease_allowance(
+    token_program_id: &Pubkey,
+    source_pubkey: &Pubkey,
+    delegate_pubkey: &Pubkey,
+    owner_pubkey: &Pubkey,
+    signer_pubkeys: &[&Pubkey],
+    amount: u64,
+) -> Result<Instruction, ProgramError> {
+    check_program_account(token_program_id)?;
+    let data = TokenInstruction::DecreaseAllowance { amount }.pack();
+
+    let mut accounts = Vec::with_capacity(3 + signer_pubkeys.len());
+    accounts.push(AccountMeta::new(*source_pubkey, false));
+    accounts.push(AccountMeta::new_readonly(*delegate_pubkey, false));
+    accounts.push(AccountMeta::new_readonly(
+        *owner_pubkey,
+        signer_pubkeys.is_empty(),
+    ));
+    for signer_pubkey in signer_pubkeys.iter() {
+        accounts.push(AccountMeta::new_readonly(**signer_pubkey, true));
+    }
+
+    Ok(Instruction {
+        program_id: *token_program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a `Revoke` instruction.
+pub fn revoke(
+    token_program_id: &Pubkey,
+    source_pubkey: &Pubkey,
+    owner_pubkey: &Pubkey,
+    signer_pubkeys: &[&Pubkey],
+) -> Result<Instruction, ProgramError> {
+    check_program_account(token_program_id)?;
+    let data = TokenInstruction::Revoke.pack();
+
+    let mut accounts = Vec::with_capacity(2 + signer_pubkeys.len());
+    accounts.push(AccountMeta::new(*source_pubkey, false));
+    accounts.push(AccountMeta::new_readonly(
+        *owner_pubkey,
+        signer_pubkeys.is_empty(),
+    ));
+    for signer_pubkey in signer_pubkeys.iter() {
+        accounts.push(AccountMeta::new_readonly(**signer_pubkey, true));
+    }
+
+    Ok(Instruction {
+        program_id: *token_program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a `SetAuthority` instruction.
+pub fn set_authority(
+    token_program_id: &Pubkey,
+    owned_pubkey: &Pubkey,
+    new_authority_pubkey: Option<&Pubkey>,
+    authority_type: AuthorityType,
+    owner_pubkey: &Pubkey,
+    signer_pubkeys: &[&Pubkey],
+) -> Result<Instruction, ProgramError> {
+    check_program_account(token_program_id)?;
+    let new_authority = new_authority_pubkey.cloned().into();
+    let data = TokenInstruction::SetAuthority {
+        authority_type,
+        new_authority,
+    }
+    .pack();
+
+    let mut accounts = Vec::with_capacity(3 + signer_pubkeys.len());
+    accounts.push(AccountMeta::new(*owned_pubkey, false));
+    accounts.push(AccountMeta::new_readonly(
+        *owner_pubkey,
+        signer_pubkeys.is_empty(),
+    ));
+    for signer_pubkey in signer_pubkeys.iter() {
+        accounts.push(AccountMeta::new_readonly(**signer_pubkey, true));
+    }
+
+    Ok(Instruction {
+        program_id: *token_program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a `ProposeAuthority` instruction.
+pub fn propose_authority(
+    token_program_id: &Pubkey,
+    owned_pubkey: &Pubkey,
+    new_authority_pubkey: &Pubkey,
+    authority_type: AuthorityType,
+    owner_pubkey: &Pubkey,
+    signer_pubkeys: &[&Pubkey],
+) -> Result<Instruction, ProgramError> {
+    check_program_account(token_program_id)?;
+    let data = TokenInstruction::ProposeAuthority {
+        authority_type,
+        new_authority: *new_authority_pubkey,
+    }
+    .pack();
+
+    let mut accounts = Vec::with_capacity(2 + signer_pubkeys.len());
+    accounts.push(AccountMeta::new(*owned_pubkey, false));
+    accounts.push(AccountMeta::new_readonly(
+        *owner_pubkey,
+        signer_pubkeys.is_empty(),
+    ));
+    for signer_pubkey in signer_pubkeys.iter() {
+        accounts.push(AccountMeta::new_readonly(**signer_pubkey, true));
+    }
+
+    Ok(Instruction {
+        program_id: *token_program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates an `AcceptAuthority` instruction.
+pub fn accept_authority(
+    token_program_id: &Pubkey,
+    owned_pubkey: &Pubkey,
+    authority_type: AuthorityType,
+    new_authority_pubkey: &Pubkey,
+    signer_pubkeys: &[&Pubkey],
+) -> Result<Instruction, ProgramError> {
+    check_program_account(token_program_id)?;
+    let data = TokenInstruction::AcceptAuthority { authority_type }.pack();
+
+    let mut accounts = Vec::with_capacity(2 + signer_pubkeys.len());
+    accounts.push(AccountMeta::new(*owned_pubkey, false));
+    accounts.push(AccountMeta::new_readonly(
+        *new_authority_pubkey,
+        signer_pubkeys.is_empty(),
+    ));
+    for signer_pubkey in signer_pubkeys.iter() {
+        accounts.push(AccountMeta::new_readonly(**signer_pubkey, true));
+    }
+
+    Ok(Instruction {
+        program_id: *token_program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a `CancelAuthorityProposal` instruction.
+pub fn cancel_authority_proposal(
+    token_program_id: &Pubkey,
+    owned_pubkey: &Pubkey,
+    authority_type: AuthorityType,
+    owner_pubkey: &Pubkey,
+    signer_pubkeys: &[&Pubkey],
+) -> Result<Instruction, ProgramError> {
+    check_program_account(token_program_id)?;
+    let data = TokenInstruction::CancelAuthorityProposal { authority_type }.pack();
+
+    let mut accounts = Vec::with_capacity(2 + signer_pubkeys.len());
+    accounts.push(AccountMeta::new(*owned_pubkey, false));
+    accounts.push(AccountMeta::new_readonly(
+        *owner_pubkey,
+        signer_pubkeys.is_empty(),
+    ));
+    for signer_pubkey in signer_pubkeys.iter() {
+        accounts.push(AccountMeta::new_readonly(**signer_pubkey, true));
+    }
+
+    Ok(Instruction {
+        program_id: *token_program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a `SetAuthorityTimelock` instruction.
+pub fn set_authority_timelock(
+    token_program_id: &Pubkey,
+    mint_pubkey: &Pubkey,
+    owner_pubkey: &Pubkey,
+    signer_pubkeys: &[&Pubkey],
+    delay: u64,
+) -> Result<Instruction, ProgramError> {
+    check_program_account(token_program_id)?;
+    let data = TokenInstruction::SetAuthorityTimelock { delay }.pack();
+
+    let mut accounts = Vec::with_capacity(2 + signer_pubkeys.len());
+    accounts.push(AccountMeta::new(*mint_pubkey, false));
+    accounts.push(AccountMeta::new_readonly(
+        *owner_pubkey,
+        signer_pubkeys.is_empty(),
+    ));
+    for signer_pubkey in signer_pubkeys.iter() {
+        accounts.push(AccountMeta::new_readonly(**signer_pubkey, true));
+    }
+
+    Ok(Instruction {
+        program_id: *token_program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a `QueueAuthorityChange` instruction.
+pub fn queue_authority_change(
+    token_program_id: &Pubkey,
+    mint_pubkey: &Pubkey,
+    new_authority_pubkey: Option<&Pubkey>,
+    authority: TimelockedAuthority,
+    owner_pubkey: &Pubkey,
+    signer_pubkeys: &[&Pubkey],
+) -> Result<Instruction, ProgramError> {
+    check_program_account(token_program_id)?;
+    let new_authority = new_authority_pubkey.cloned().into();
+    let data = TokenInstruction::QueueAuthorityChange {
+        authority,
+        new_authority,
+    }
+    .pack();
+
+    let mut accounts = Vec::with_capacity(3 + signer_pubkeys.len());
+    accounts.push(AccountMeta::new(*mint_pubkey, false));
+    accounts.push(AccountMeta::new_readonly(sysvar::clock::id(), false));
+    accounts.push(AccountMeta::new_readonly(
+        *owner_pubkey,
+        signer_pubkeys.is_empty(),
+    ));
+    for signer_pubkey in signer_pubkeys.iter() {
+        accounts.push(AccountMeta::new_readonly(**signer_pubkey, true));
+    }
+
+    Ok(Instruction {
+        program_id: *token_program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates an `ExecuteAuthorityChange` instruction.
+pub fn execute_authority_change(
+    token_program_id: &Pubkey,
+    mint_pubkey: &Pubkey,
+) -> Result<Instruction, ProgramError> {
+    check_program_account(token_program_id)?;
+    let data = TokenInstruction::ExecuteAuthorityChange.pack();
+
+    let accounts = vec![
+        AccountMeta::new(*mint_pubkey, false),
+        AccountMeta::new_readonly(sysvar::clock::id(), false),
+    ];
+
+    Ok(Instruction {
+        program_id: *token_program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a `CancelAuthorityChange` instruction.
+pub fn cancel_authority_change(
+    token_program_id: &Pubkey,
+    mint_pubkey: &Pubkey,
+    owner_pubkey: &Pubkey,
+    signer_pubkeys: &[&Pubkey],
+) -> Result<Instruction, ProgramError> {
+    check_program_account(token_program_id)?;
+    let data = TokenInstruction::CancelAuthorityChange.pack();
+
+    let mut accounts = Vec::with_capacity(2 + signer_pubkeys.len());
+    accounts.push(AccountMeta::new(*mint_pubkey, false));
+    accounts.push(AccountMeta::new_readonly(
+        *owner_pubkey,
+        signer_pubkeys.is_empty(),
+    ));
+    for signer_pubkey in signer_pubkeys.iter() {
+        accounts.push(AccountMeta::new_readonly(**signer_pubkey, true));
+    }
+
+    Ok(Instruction {
+        program_id: *token_program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a `SetGuardians` instruction.
+pub fn set_guardians(
+    token_program_id: &Pubkey,
+    account_pubkey: &Pubkey,
+    guardians_pubkey: Option<&Pubkey>,
+    recovery_delay: u64,
+    owner_pubkey: &Pubkey,
+    signer_pubkeys: &[&Pubkey],
+) -> Result<Instruction, ProgramError> {
+    check_program_account(token_program_id)?;
+    let guardians = guardians_pubkey.cloned().into();
+    let data = TokenInstruction::SetGuardians {
+        guardians,
+        recovery_delay,
+    }
+    .pack();
+
+    let mut accounts = Vec::with_capacity(2 + signer_pubkeys.len());
+    accounts.push(AccountMeta::new(*account_pubkey, false));
+    accounts.push(AccountMeta::new_readonly(
+        *owner_pubkey,
+        signer_pubkeys.is_empty(),
+    ));
+    for signer_pubkey in signer_pubkeys.iter() {
+        accounts.push(AccountMeta::new_readonly(**signer_pubkey, true));
+    }
+
+    Ok(Instruction {
+        program_id: *token_program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates an `InitiateRecovery` instruction.
+pub fn initiate_recovery(
+    token_program_id: &Pubkey,
+    account_pubkey: &Pubkey,
+    new_owner_pubkey: &Pubkey,
+    guardians_pubkey: &Pubkey,
+    signer_pubkeys: &[&Pubkey],
+) -> Result<Instruction, ProgramError> {
+    check_program_account(token_program_id)?;
+    let data = TokenInstruction::InitiateRecovery {
+        new_owner: *new_owner_pubkey,
+    }
+    .pack();
+
+    let mut accounts = Vec::with_capacity(3 + signer_pubkeys.len());
+    accounts.push(AccountMeta::new(*account_pubkey, false));
+    accounts.push(AccountMeta::new_readonly(sysvar::clock::id(), false));
+    accounts.push(AccountMeta::new_readonly(
+        *guardians_pubkey,
+        signer_pubkeys.is_empty(),
+    ));
+    for signer_pubkey in signer_pubkeys.iter() {
+        accounts.push(AccountMeta::new_readonly(**signer_pubkey, true));
+    }
+
+    Ok(Instruction {
+        program_id: *token_program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a `CompleteRecovery` instruction.
+pub fn complete_recovery(
+    token_program_id: &Pubkey,
+    account_pubkey: &Pubkey,
+) -> Result<Instruction, ProgramError> {
+    check_program_account(token_program_id)?;
+    let data = TokenInstruction::CompleteRecovery.pack();
+
+    let accounts = vec![
+        AccountMeta::new(*account_pubkey, false),
+        AccountMeta::new_readonly(sysvar::clock::id(), false),
+    ];
+
+    Ok(Instruction {
+        program_id: *token_program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a `VetoRecovery` instruction.
+pub fn veto_recovery(
+    token_program_id: &Pubkey,
+    account_pubkey: &Pubkey,
+    owner_pubkey: &Pubkey,
+    signer_pubkeys: &[&Pubkey],
+) -> Result<Instruction, ProgramError> {
+    check_program_account(token_program_id)?;
+    let data = TokenInstruction::VetoRecovery.pack();
+
+    let mut accounts = Vec::with_capacity(2 + signer_pubkeys.len());
+    accounts.push(AccountMeta::new(*account_pubkey, false));
+    accounts.push(AccountMeta::new_readonly(
+        *owner_pubkey,
+        signer_pubkeys.is_empty(),
+    ));
+    for signer_pubkey in signer_pubkeys.iter() {
+        accounts.push(AccountMeta::new_readonly(**signer_pubkey, true));
+    }
+
+    Ok(Instruction {
+        program_id: *token_program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates an `ApproveWithLimits` instruction.
+#[allow(clippy::too_many_arguments)]
+pub fn approve_with_limits(
+    token_program_id: &Pubkey,
+    source_pubkey: &Pubkey,
+    delegate_pubkey: &Pubkey,
+    owner_pubkey: &Pubkey,
+    signer_pubkeys: &[&Pubkey],
+    amount: u64,
+    expiry: UnixTimestamp,
+    max_per_transfer: Option<u64>,
+) -> Result<Instruction, ProgramError> {
+    check_program_account(token_program_id)?;
+    let data = TokenInstruction::ApproveWithLimits {
+        amount,
+        expiry,
+        max_per_transfer: max_per_transfer.into(),
+    }
+    .pack();
+
+    let mut accounts = Vec::with_capacity(3 + signer_pubkeys.len());
+    accounts.push(AccountMeta::new(*source_pubkey, false));
+    accounts.push(AccountMeta::new_readonly(*delegate_pubkey, false));
+    accounts.push(AccountMeta::new_readonly(
+        *owner_pubkey,
+        signer_pubkeys.is_empty(),
+    ));
+    for signer_pubkey in signer_pubkeys.iter() {
+        accounts.push(AccountMeta::new_readonly(**signer_pubkey, true));
+    }
+
+    Ok(Instruction {
+        program_id: *token_program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a `SetAllowance` instruction.
+pub fn set_allowance(
+    token_program_id: &Pubkey,
+    account_pubkey: &Pubkey,
+    spender_pubkey: &Pubkey,
+    payer_pubkey: &Pubkey,
+    owner_pubkey: &Pubkey,
+    signer_pubkeys: &[&Pubkey],
+    amount: u64,
+) -> Result<Instruction, ProgramError> {
+    check_program_account(token_program_id)?;
+    let data = TokenInstruction::SetAllowance { amount }.pack();
+
+    let mut accounts = Vec::with_capacity(7 + signer_pubkeys.len());
+    accounts.push(AccountMeta::new(
+        get_allowance_address(account_pubkey, spender_pubkey),
+        false,
+    ));
+    accounts.push(AccountMeta::new_readonly(*account_pubkey, false));
+    accounts.push(AccountMeta::new_readonly(*spender_pubkey, false));
+    accounts.push(AccountMeta::new(*payer_pubkey, true));
+    accounts.push(AccountMeta::new_readonly(system_program::id(), false));
+    accounts.push(AccountMeta::new_readonly(sysvar::rent::id(), false));
+    accounts.push(AccountMeta::new_readonly(
+        *owner_pubkey,
+        signer_pubkeys.is_empty(),
+    ));
+    for signer_pubkey in signer_pubkeys.iter() {
+        accounts.push(AccountMeta::new_readonly(**signer_pubkey, true));
+    }
+
+    Ok(Instruction {
+        program_id: *token_program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a `CloseAllowance` instruction.
+pub fn close_allowance(
+    token_program_id: &Pubkey,
+    account_pubkey: &Pubkey,
+    spender_pubkey: &Pubkey,
+    destination_pubkey: &Pubkey,
+    owner_pubkey: &Pubkey,
+    signer_pubkeys: &[&Pubkey],
+) -> Result<Instruction, ProgramError> {
+    check_program_account(token_program_id)?;
+    let data = TokenInstruction::CloseAllowance.pack();
+
+    let mut accounts = Vec::with_capacity(4 + signer_pubkeys.len());
+    accounts.push(AccountMeta::new(
+        get_allowance_address(account_pubkey, spender_pubkey),
+        false,
+    ));
+    accounts.push(AccountMeta::new_readonly(*account_pubkey, false));
+    accounts.push(AccountMeta::new(*destination_pubkey, false));
+    accounts.push(AccountMeta::new_readonly(
+        *owner_pubkey,
+        signer_pubkeys.is_empty(),
+    ));
+    for signer_pubkey in signer_pubkeys.iter() {
+        accounts.push(AccountMeta::new_readonly(**signer_pubkey, true));
+    }
+
+    Ok(Instruction {
+        program_id: *token_program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a `Transfer` instruction that spends a spender's allowance.
+pub fn transfer_with_allowance(
+    token_program_id: &Pubkey,
+    source_pubkey: &Pubkey,
+    destination_pubkey: &Pubkey,
+    spender_pubkey: &Pubkey,
+    signer_pubkeys: &[&Pubkey],
+    amount: u64,
+) -> Result<Instruction, ProgramError> {
+    check_program_account(token_program_id)?;
+    let data = TokenInstruction::Transfer { amount }.pack();
+
+    let mut accounts = Vec::with_capacity(4 + signer_pubkeys.len());
+    accounts.push(AccountMeta::new(*source_pubkey, false));
+    accounts.push(AccountMeta::new(*destination_pubkey, false));
+    accounts.push(AccountMeta::new(
+        get_allowance_address(source_pubkey, spender_pubkey),
+        false,
+    ));
+    accounts.push(AccountMeta::new_readonly(
+        *spender_pubkey,
+        signer_pubkeys.is_empty(),
+    ));
+    for signer_pubkey in signer_pubkeys.iter() {
+        accounts.push(AccountMeta::new_readonly(**signer_pubkey, true));
+    }
+
+    Ok(Instruction {
+        program_id: *token_program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a `Burn` instruction that spends a spender's allowance.
+pub fn burn_with_allowance(
+    token_program_id: &Pubkey,
+    account_pubkey: &Pubkey,
+    mint_pubkey: &Pubkey,
+    spender_pubkey: &Pubkey,
+    signer_pubkeys: &[&Pubkey],
+    amount: u64,
+) -> Result<Instruction, ProgramError> {
+    check_program_account(token_program_id)?;
+    let data = TokenInstruction::Burn { amount }.pack();
+
+    let mut accounts = Vec::with_capacity(4 + signer_pubkeys.len());
+    accounts.push(AccountMeta::new(*account_pubkey, false));
+    accounts.push(AccountMeta::new(*mint_pubkey, false));
+    accounts.push(AccountMeta::new(
+        get_allowance_address(account_pubkey, spender_pubkey),
+        false,
+    ));
+    accounts.push(AccountMeta::new_readonly(
+        *spender_pubkey,
+        signer_pubkeys.is_empty(),
+    ));
+    for signer_pubkey in signer_pubkeys.iter() {
+        accounts.push(AccountMeta::new_readonly(**signer_pubkey, true));
+    }
+
+    Ok(Instruction {
+        program_id: *token_program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates an `InitializeNonce` instruction.
+pub fn initialize_nonce(
+    token_program_id: &Pubkey,
+    owner_pubkey: &Pubkey,
+    payer_pubkey: &Pubkey,
+) -> Result<Instruction, ProgramError> {
+    check_program_account(token_program_id)?;
+    let data = TokenInstruction::InitializeNonce.pack();
+
+    let accounts = vec![
+        AccountMeta::new(get_nonce_address(owner_pubkey), false),
+        AccountMeta::new_readonly(*owner_pubkey, false),
+        AccountMeta::new(*payer_pubkey, true),
+        AccountMeta::new_readonly(system_program::id(), false),
+        AccountMeta::new_readonly(sysvar::rent::id(), false),
+    ];
+
+    Ok(Instruction {
+        program_id: *token_program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a `Permit` instruction.
+#[allow(clippy::too_many_arguments)]
+pub fn permit(
+    token_program_id: &Pubkey,
+    source_pubkey: &Pubkey,
+    delegate_pubkey: &Pubkey,
+    owner_pubkey: &Pubkey,
+    amount: u64,
+    nonce: u64,
+    valid_after: UnixTimestamp,
+    valid_until: UnixTimestamp,
+) -> Result<Instruction, ProgramError> {
+    check_program_account(token_program_id)?;
+    let data = TokenInstruction::Permit {
+        amount,
+        nonce,
+        valid_after,
+        valid_until,
+    }
+    .pack();
+
+    let accounts = vec![
+        AccountMeta::new(*source_pubkey, false),
+        AccountMeta::new_readonly(*delegate_pubkey, false),
+        AccountMeta::new(get_nonce_address(owner_pubkey), false),
+        AccountMeta::new_readonly(sysvar::instructions::id(), false),
+        AccountMeta::new_readonly(sysvar::clock::id(), false),
+    ];
+
+    Ok(Instruction {
+        program_id: *token_program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a `TransferWithAuthorization` instruction.
+#[allow(clippy::too_many_arguments)]
+pub fn transfer_with_authorization(
+    token_program_id: &Pubkey,
+    source_pubkey: &Pubkey,
+    destination_pubkey: &Pubkey,
+    owner_pubkey: &Pubkey,
+    amount: u64,
+    nonce: u64,
+    valid_after: UnixTimestamp,
+    valid_until: UnixTimestamp,
+) -> Result<Instruction, ProgramError> {
+    check_program_account(token_program_id)?;
+    let data = TokenInstruction::TransferWithAuthorization {
+        amount,
+        nonce,
+        valid_after,
+        valid_until,
+    }
+    .pack();
+
+    let accounts = vec![
+        AccountMeta::new(*source_pubkey, false),
+        AccountMeta::new(*destination_pubkey, false),
+        AccountMeta::new(get_nonce_address(owner_pubkey), false),
+        AccountMeta::new_readonly(sysvar::instructions::id(), false),
+        AccountMeta::new_readonly(sysvar::clock::id(), false),
+    ];
+
+    Ok(Instruction {
+        program_id: *token_program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Returns the message a token account's owner signs off-chain to authorize a
+/// `Permit` or `TransferWithAuthorization` instruction: the token program id,
+/// the packed instruction data, the source account and the delegate or
+/// destination account.
+pub fn authorization_message(
+    token_program_id: &Pubkey,
+    instruction: &TokenInstruction,
+    source_pubkey: &Pubkey,
+    counterparty_pubkey: &Pubkey,
+) -> Vec<u8> {
+    let mut message = token_program_id.to_bytes().to_vec();
+    message.extend_from_slice(&instruction.pack());
+    message.extend_from_slice(source_pubkey.as_ref());
+    message.extend_from_slice(counterparty_pubkey.as_ref());
+    message
+}
+
+/// Creates a `CreateSession` instruction.
+#[allow(clippy::too_many_arguments)]
+pub fn create_session(
+    token_program_id: &Pubkey,
+    account_pubkey: &Pubkey,
+    owner_pubkey: &Pubkey,
+    signer_pubkeys: &[&Pubkey],
+    session_key: &Pubkey,
+    expiry: UnixTimestamp,
+    scope: u8,
+    destinations: &[&Pubkey],
+    max_amount: u64,
+) -> Result<Instruction, ProgramError> {
+    check_program_account(token_program_id)?;
+    if destinations.len() > MAX_SESSION_DESTINATIONS {
+        return Err(ProgramError::InvalidArgument);
+    }
+    let data = TokenInstruction::CreateSession {
+        session_key: *session_key,
+        expiry,
+        scope,
+        destinations: destinations
+            .iter()
+            .map(|destination| **destination)
+            .collect(),
+        max_amount,
+    }
+    .pack();
+
+    let mut accounts = Vec::with_capacity(2 + signer_pubkeys.len());
+    accounts.push(AccountMeta::new(*account_pubkey, false));
+    accounts.push(AccountMeta::new_readonly(
+        *owner_pubkey,
+        signer_pubkeys.is_empty(),
+    ));
+    for signer_pubkey in signer_pubkeys.iter() {
+        accounts.push(AccountMeta::new_readonly(**signer_pubkey, true));
+    }
+
+    Ok(Instruction {
+        program_id: *token_program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a `RevokeSession` instruction.
+pub fn revoke_session(
+    token_program_id: &Pubkey,
+    account_pubkey: &Pubkey,
+    owner_pubkey: &Pubkey,
+    signer_pubkeys: &[&Pubkey],
+) -> Result<Instruction, ProgramError> {
+    check_program_account(token_program_id)?;
+    let data = TokenInstruction::RevokeSession.pack();
+
+    let mut accounts = Vec::with_capacity(2 + signer_pubkeys.len());
+    accounts.push(AccountMeta::new(*account_pubkey, false));
+    accounts.push(AccountMeta::new_readonly(
+        *owner_pubkey,
+        signer_pubkeys.is_empty(),
+    ));
+    for signer_pubkey in signer_pubkeys.iter() {
+        accounts.push(AccountMeta::new_readonly(**signer_pubkey, true));
+    }
+
+    Ok(Instruction {
+        program_id: *token_program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates an `InitializeEthereumOwner` instruction.
+pub fn initialize_ethereum_owner(
+    token_program_id: &Pubkey,
+    address: &EthereumAddress,
+    payer_pubkey: &Pubkey,
+) -> Result<Instruction, ProgramError> {
+    check_program_account(token_program_id)?;
+    let data = TokenInstruction::InitializeEthereumOwner { address: *address }.pack();
+
+    let accounts = vec![
+        AccountMeta::new(get_ethereum_owner_address(address), false),
+        AccountMeta::new(*payer_pubkey, true),
+        AccountMeta::new_readonly(system_program::id(), false),
+        AccountMeta::new_readonly(sysvar::rent::id(), false),
+    ];
+
+    Ok(Instruction {
+        program_id: *token_program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Returns the message an Ethereum owner signs with secp256k1 to authorize an
+/// instruction until the `valid_until` timestamp: the program id, the owner's
+/// current nonce, the instruction data, the keys of all the instruction's
+/// accounts and `valid_until`, prefixed as an EIP-191 signed message.
+pub fn ethereum_authorization_message(
+    instruction: &Instruction,
+    nonce: u64,
+    valid_until: UnixTimestamp,
+) -> Vec<u8> {
+    let mut body = instruction.program_id.to_bytes().to_vec();
+    body.extend_from_slice(&nonce.to_le_bytes());
+    body.extend_from_slice(&instruction.data);
+    for account in instruction.accounts.iter() {
+        body.extend_from_slice(account.pubkey.as_ref());
+    }
+    body.extend_from_slice(&valid_until.to_le_bytes());
+
+    let mut message = format!("\x19Ethereum Signed Message:\n{}", body.len()).into_bytes();
+    message.extend_from_slice(&body);
+    message
+}
+
+/// Creates a `MintTo` instruction.
+pub fn mint_to(
+    token_program_id: &Pubkey,
+    mint_pubkey: &Pubkey,
+    account_pubkey: &Pubkey,
+    owner_pubkey: &Pubkey,
+    signer_pubkeys: &[&Pubkey],
+    amount: u64,
+) -> Result<Instruction, ProgramError> {
+    check_program_account(token_program_id)?;
+    let data = TokenInstruction::MintTo { amount }.pack();
+
+    let mut accounts = Vec::with_capacity(3 + signer_pubkeys.len());
+    accounts.push(AccountMeta::new(*mint_pubkey, false));
+    accounts.push(AccountMeta::new(*account_pubkey, false));
+    accounts.push(AccountMeta::new_readonly(
+        *owner_pubkey,
+        signer_pubkeys.is_empty(),
+    ));
+    for signer_pubkey in signer_pubkeys.iter() {
+        accounts.push(AccountMeta::new_readonly(**signer_pubkey, true));
+    }
+
+    Ok(Instruction {
+        program_id: *token_program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a `MintTo` instruction that mints against a minter's allowance.
+pub fn mint_to_with_minter(
+    token_program_id: &Pubkey,
+    mint_pubkey: &Pubkey,
+    account_pubkey: &Pubkey,
+    minter_pubkey: &Pubkey,
+    minter_authority_pubkey: &Pubkey,
+    signer_pubkeys: &[&Pubkey],
+    amount: u64,
+) -> Result<Instruction, ProgramError> {
+    check_program_account(token_program_id)?;
+    let data = TokenInstruction::MintTo { amount }.pack();
+
+    let mut accounts = Vec::with_capacity(4 + signer_pubkeys.len());
+    accounts.push(AccountMeta::new(*mint_pubkey, false));
+    accounts.push(AccountMeta::new(*account_pubkey, false));
+    accounts.push(AccountMeta::new(*minter_pubkey, false));
+    accounts.push(AccountMeta::new_readonly(
+        *minter_authority_pubkey,
+        signer_pubkeys.is_empty(),
+    ));
+    for signer_pubkey in signer_pubkeys.iter() {
+        accounts.push(AccountMeta::new_readonly(**signer_pubkey, true));
+    }
+
+    Ok(Instruction {
+        program_id: *token_program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a `Burn` instruction.
+pub fn burn(
+    token_program_id: &Pubkey,
+    account_pubkey: &Pubkey,
+    mint_pubkey: &Pubkey,
+    authority_pubkey: &Pubkey,
+    signer_pubkeys: &[&Pubkey],
+    amount: u64,
+) -> Result<Instruction, ProgramError> {
+    check_program_account(token_program_id)?;
+    let data = TokenInstruction::Burn { amount }.pack();
+
+    let mut accounts = Vec::with_capacity(3 + signer_pubkeys.len());
+    accounts.push(AccountMeta::new(*account_pubkey, false));
+    accounts.push(AccountMeta::new(*mint_pubkey, false));
+    accounts.push(AccountMeta::new_readonly(
+        *authority_pubkey,
+        signer_pubkeys.is_empty(),
+    ));
+    for signer_pubkey in signer_pubkeys.iter() {
+        accounts.push(AccountMeta::new_readonly(**signer_pubkey, true));
+    }
+
+    Ok(Instruction {
+        program_id: *token_program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a `CloseAccount` instruction.
+pub fn close_account(
+    token_program_id: &Pubkey,
+    account_pubkey: &Pubkey,
+    destination_pubkey: &Pubkey,
+    owner_pubkey: &Pubkey,
+    signer_pubkeys: &[&Pubkey],
+) -> Result<Instruction, ProgramError> {
+    check_program_account(token_program_id)?;
+    let data = TokenInstruction::CloseAccount.pack();
+
+    let mut accounts = Vec::with_capacity(3 + signer_pubkeys.len());
+    accounts.push(AccountMeta::new(*account_pubkey, false));
+    accounts.push(AccountMeta::new(*destination_pubkey, false));
+    accounts.push(AccountMeta::new_readonly(
+        *owner_pubkey,
+        signer_pubkeys.is_empty(),
+    ));
+    for signer_pubkey in signer_pubkeys.iter() {
+        accounts.push(AccountMeta::new_readonly(**signer_pubkey, true));
+    }
+
+    Ok(Instruction {
+        program_id: *token_program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a `FreezeAccount` instruction.
+pub fn freeze_account(
+    token_program_id: &Pubkey,
+    account_pubkey: &Pubkey,
+    mint_pubkey: &Pubkey,
+    owner_pubkey: &Pubkey,
+    signer_pubkeys: &[&Pubkey],
+) -> Result<Instruction, ProgramError> {
+    check_program_account(token_program_id)?;
+    let data = TokenInstruction::FreezeAccount.pack();
+
+    let mut accounts = Vec::with_capacity(3 + signer_pubkeys.len());
+    accounts.push(AccountMeta::new(*account_pubkey, false));
+    accounts.push(AccountMeta::new_readonly(*mint_pubkey, false));
+    accounts.push(AccountMeta::new_readonly(
+        *owner_pubkey,
+        signer_pubkeys.is_empty(),
+    ));
+    for signer_pubkey in signer_pubkeys.iter() {
+        accounts.push(AccountMeta::new_readonly(**signer_pubkey, true));
+    }
+
+    Ok(Instruction {
+        program_id: *token_program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a `ThawAccount` instruction.
+pub fn thaw_account(
+    token_program_id: &Pubkey,
+    account_pubkey: &Pubkey,
+    mint_pubkey: &Pubkey,
+    owner_pubkey: &Pubkey,
+    signer_pubkeys: &[&Pubkey],
+) -> Result<Instruction, ProgramError> {
+    check_program_account(token_program_id)?;
+    let data = TokenInstruction::ThawAccount.pack();
+
+    let mut accounts = Vec::with_capacity(3 + signer_pubkeys.len());
+    accounts.push(AccountMeta::new(*account_pubkey, false));
+    accounts.push(AccountMeta::new_readonly(*mint_pubkey, false));
+    accounts.push(AccountMeta::new_readonly(
+        *owner_pubkey,
+        signer_pubkeys.is_empty(),
+    ));
+    for signer_pubkey in signer_pubkeys.iter() {
+        accounts.push(AccountMeta::new_readonly(**signer_pubkey, true));
+    }
+
+    Ok(Instruction {
+        program_id: *token_program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a `SetReceiveOnly` instruction.
+pub fn set_receive_only(
+    token_program_id: &Pubkey,
+    account_pubkey: &Pubkey,
+    mint_pubkey: &Pubkey,
+    owner_pubkey: &Pubkey,
+    signer_pubkeys: &[&Pubkey],
+) -> Result<Instruction, ProgramError> {
+    check_program_account(token_program_id)?;
+    let data = TokenInstruction::SetReceiveOnly.pack();
+
+    let mut accounts = Vec::with_capacity(3 + signer_pubkeys.len());
+    accounts.push(AccountMeta::new(*account_pubkey, false));
+    accounts.push(AccountMeta::new_readonly(*mint_pubkey, false));
+    accounts.push(AccountMeta::new_readonly(
+        *owner_pubkey,
+        signer_pubkeys.is_empty(),
+    ));
+    for signer_pubkey in signer_pubkeys.iter() {
+        accounts.push(AccountMeta::new_readonly(**signer_pubkey, true));
+    }
+
+    Ok(Instruction {
+        program_id: *token_program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a `SetSendOnly` instruction.
+pub fn set_send_only(
+    token_program_id: &Pubkey,
+    account_pubkey: &Pubkey,
+    mint_pubkey: &Pubkey,
+    owner_pubkey: &Pubkey,
+    signer_pubkeys: &[&Pubkey],
+) -> Result<Instruction, ProgramError> {
+    check_program_account(token_program_id)?;
+    let data = TokenInstruction::SetSendOnly.pack();
+
+    let mut accounts = Vec::with_capacity(3 + signer_pubkeys.len());
+    accounts.push(AccountMeta::new(*account_pubkey, false));
+    accounts.push(AccountMeta::new_readonly(*mint_pubkey, false));
+    accounts.push(AccountMeta::new_readonly(
+        *owner_pubkey,
+        signer_pubkeys.is_empty(),
+    ));
+    for signer_pubkey in signer_pubkeys.iter() {
+        accounts.push(AccountMeta::new_readonly(**signer_pubkey, true));
+    }
+
+    Ok(Instruction {
+        program_id: *token_program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a `FreezeAccountWithReason` instruction.
+pub fn freeze_account_with_reason(
+    token_program_id: &Pubkey,
+    account_pubkey: &Pubkey,
+    mint_pubkey: &Pubkey,
+    owner_pubkey: &Pubkey,
+    signer_pubkeys: &[&Pubkey],
+    reason: u8,
+    auto_thaw_timestamp: UnixTimestamp,
+) -> Result<Instruction, ProgramError> {
+    check_program_account(token_program_id)?;
+    let data = TokenInstruction::FreezeAccountWithReason {
+        reason,
+        auto_thaw_timestamp,
+    }
+    .pack();
+
+    let mut accounts = Vec::with_capacity(3 + signer_pubkeys.len());
+    accounts.push(AccountMeta::new(*account_pubkey, false));
+    accounts.push(AccountMeta::new_readonly(*mint_pubkey, false));
+    accounts.push(AccountMeta::new_readonly(
+        *owner_pubkey,
+        signer_pubkeys.is_empty(),
+    ));
+    for signer_pubkey in signer_pubkeys.iter() {
+        accounts.push(AccountMeta::new_readonly(**signer_pubkey, true));
+    }
+
+    Ok(Instruction {
+        program_id: *token_program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a `FreezeAccounts` instruction.
+pub fn freeze_accounts(
+    token_program_id: &Pubkey,
+    account_pubkeys: &[&Pubkey],
+    mint_pubkey: &Pubkey,
+    owner_pubkey: &Pubkey,
+    signer_pubkeys: &[&Pubkey],
+) -> Result<Instruction, ProgramError> {
+    check_program_account(token_program_id)?;
+    let count = account_pubkeys
+        .len()
+        .try_into()
+        .map_err(|_| ProgramError::InvalidArgument)?;
+    let data = TokenInstruction::FreezeAccounts { count }.pack();
+
+    let mut accounts = Vec::with_capacity(2 + account_pubkeys.len() + signer_pubkeys.len());
+    accounts.push(AccountMeta::new_readonly(*mint_pubkey, false));
+    accounts.push(AccountMeta::new_readonly(
+        *owner_pubkey,
+        signer_pubkeys.is_empty(),
+    ));
+    for account_pubkey in account_pubkeys.iter() {
+        accounts.push(AccountMeta::new(**account_pubkey, false));
+    }
+    for signer_pubkey in signer_pubkeys.iter() {
+        accounts.push(AccountMeta::new_readonly(**signer_pubkey, true));
+    }
+
+    Ok(Instruction {
+        program_id: *token_program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a `ThawAccounts` instruction.
+pub fn thaw_accounts(
+    token_program_id: &Pubkey,
+    account_pubkeys: &[&Pubkey],
+    mint_pubkey: &Pubkey,
+    owner_pubkey: &Pubkey,
+    signer_pubkeys: &[&Pubkey],
+) -> Result<Instruction, ProgramError> {
+    check_program_account(token_program_id)?;
+    let count = account_pubkeys
+        .len()
+        .try_into()
+        .map_err(|_| ProgramError::InvalidArgument)?;
+    let data = TokenInstruction::ThawAccounts { count }.pack();
+
+    let mut accounts = Vec::with_capacity(2 + account_pubkeys.len() + signer_pubkeys.len());
+    accounts.push(AccountMeta::new_readonly(*mint_pubkey, false));
+    accounts.push(AccountMeta::new_readonly(
+        *owner_pubkey,
+        signer_pubkeys.is_empty(),
+    ));
+    for account_pubkey in account_pubkeys.iter() {
+        accounts.push(AccountMeta::new(**account_pubkey, false));
+    }
+    for signer_pubkey in signer_pubkeys.iter() {
+        accounts.push(AccountMeta::new_readonly(**signer_pubkey, true));
+    }
+
+    Ok(Instruction {
+        program_id: *token_program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a `SetSpendingLimit` instruction.
+pub fn set_spending_limit(
+    token_program_id: &Pubkey,
+    account_pubkey: &Pubkey,
+    owner_pubkey: &Pubkey,
+    signer_pubkeys: &[&Pubkey],
+    amount: u64,
+    window: u64,
+) -> Result<Instruction, ProgramError> {
+    check_program_account(token_program_id)?;
+    let data = TokenInstruction::SetSpendingLimit { amount, window }.pack();
+
+    let mut accounts = Vec::with_capacity(3 + signer_pubkeys.len());
+    accounts.push(AccountMeta::new(*account_pubkey, false));
+    accounts.push(AccountMeta::new_readonly(sysvar::clock::id(), false));
+    accounts.push(AccountMeta::new_readonly(
+        *owner_pubkey,
+        signer_pubkeys.is_empty(),
+    ));
+    for signer_pubkey in signer_pubkeys.iter() {
+        accounts.push(AccountMeta::new_readonly(**signer_pubkey, true));
+    }
+
+    Ok(Instruction {
+        program_id: *token_program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates an `InitializeDestinationAllowlist` instruction.
+pub fn initialize_destination_allowlist(
+    token_program_id: &Pubkey,
+    allowlist_pubkey: &Pubkey,
+    account_pubkey: &Pubkey,
+    owner_pubkey: &Pubkey,
+    signer_pubkeys: &[&Pubkey],
+) -> Result<Instruction, ProgramError> {
+    check_program_account(token_program_id)?;
+    let data = TokenInstruction::InitializeDestinationAllowlist.pack();
+
+    let mut accounts = Vec::with_capacity(4 + signer_pubkeys.len());
+    accounts.push(AccountMeta::new(*allowlist_pubkey, false));
+    accounts.push(AccountMeta::new(*account_pubkey, false));
+    accounts.push(AccountMeta::new_readonly(sysvar::rent::id(), false));
+    accounts.push(AccountMeta::new_readonly(
+        *owner_pubkey,
+        signer_pubkeys.is_empty(),
+    ));
+    for signer_pubkey in signer_pubkeys.iter() {
+        accounts.push(AccountMeta::new_readonly(**signer_pubkey, true));
+    }
+
+    Ok(Instruction {
+        program_id: *token_program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates an `AddAllowedDestination` instruction.
+pub fn add_allowed_destination(
+    token_program_id: &Pubkey,
+    allowlist_pubkey: &Pubkey,
+    account_pubkey: &Pubkey,
+    owner_pubkey: &Pubkey,
+    signer_pubkeys: &[&Pubkey],
+    destination: &Pubkey,
+) -> Result<Instruction, ProgramError> {
+    check_program_account(token_program_id)?;
+    let data = TokenInstruction::AddAllowedDestination {
+        destination: *destination,
+    }
+    .pack();
+
+    let mut accounts = Vec::with_capacity(4 + signer_pubkeys.len());
+    accounts.push(AccountMeta::new(*allowlist_pubkey, false));
+    accounts.push(AccountMeta::new_readonly(*account_pubkey, false));
+    accounts.push(AccountMeta::new_readonly(sysvar::clock::id(), false));
+    accounts.push(AccountMeta::new_readonly(
+        *owner_pubkey,
+        signer_pubkeys.is_empty(),
+    ));
+    for signer_pubkey in signer_pubkeys.iter() {
+        accounts.push(AccountMeta::new_readonly(**signer_pubkey, true));
+    }
+
+    Ok(Instruction {
+        program_id: *token_program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a `RemoveAllowedDestination` instruction.
+pub fn remove_allowed_destination(
+    token_program_id: &Pubkey,
+    allowlist_pubkey: &Pubkey,
+    account_pubkey: &Pubkey,
+    owner_pubkey: &Pubkey,
+    signer_pubkeys: &[&Pubkey],
+    destination: &Pubkey,
+) -> Result<Instruction, ProgramError> {
+    check_program_account(token_program_id)?;
+    let data = TokenInstruction::RemoveAllowedDestination {
+        destination: *destination,
+    }
+    .pack();
+
+    let mut accounts = Vec::with_capacity(3 + signer_pubkeys.len());
+    accounts.push(AccountMeta::new(*allowlist_pubkey, false));
+    accounts.push(AccountMeta::new_readonly(*account_pubkey, false));
+    accounts.push(AccountMeta::new_readonly(
+        *owner_pubkey,
+        signer_pubkeys.is_empty(),
+    ));
+    for signer_pubkey in signer_pubkeys.iter() {
+        accounts.push(AccountMeta::new_readonly(**signer_pubkey, true));
+    }
+
+    Ok(Instruction {
+        program_id: *token_program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a `SetMaxBalance` instruction.
+pub fn set_max_balance(
+    token_program_id: &Pubkey,
+    mint_pubkey: &Pubkey,
+    owner_pubkey: &Pubkey,
+    signer_pubkeys: &[&Pubkey],
+    max_balance: Option<u64>,
+) -> Result<Instruction, ProgramError> {
+    check_program_account(token_program_id)?;
+    let data = TokenInstruction::SetMaxBalance {
+        max_balance: max_balance.into(),
+    }
+    .pack();
+
+    let mut accounts = Vec::with_capacity(2 + signer_pubkeys.len());
+    accounts.push(AccountMeta::new(*mint_pubkey, false));
+    accounts.push(AccountMeta::new_readonly(
+        *owner_pubkey,
+        signer_pubkeys.is_empty(),
+    ));
+    for signer_pubkey in signer_pubkeys.iter() {
+        accounts.push(AccountMeta::new_readonly(**signer_pubkey, true));
+    }
+
+    Ok(Instruction {
+        program_id: *token_program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates an `AddMaxBalanceExemption` instruction.
+pub fn add_max_balance_exemption(
+    token_program_id: &Pubkey,
+    account_pubkey: &Pubkey,
+    mint_pubkey: &Pubkey,
+    owner_pubkey: &Pubkey,
+    signer_pubkeys: &[&Pubkey],
+) -> Result<Instruction, ProgramError> {
+    check_program_account(token_program_id)?;
+    let data = TokenInstruction::AddMaxBalanceExemption.pack();
+
+    let mut accounts = Vec::with_capacity(3 + signer_pubkeys.len());
+    accounts.push(AccountMeta::new(*account_pubkey, false));
+    accounts.push(AccountMeta::new_readonly(*mint_pubkey, false));
+    accounts.push(AccountMeta::new_readonly(
+        *owner_pubkey,
+        signer_pubkeys.is_empty(),
+    ));
+    for signer_pubkey in signer_pubkeys.iter() {
+        accounts.push(AccountMeta::new_readonly(**signer_pubkey, true));
+    }
+
+    Ok(Instruction {
+        program_id: *token_program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a `RemoveMaxBalanceExemption` instruction.
+pub fn remove_max_balance_exemption(
+    token_program_id: &Pubkey,
+    account_pubkey: &Pubkey,
+    mint_pubkey: &Pubkey,
+    owner_pubkey: &Pubkey,
+    signer_pubkeys: &[&Pubkey],
+) -> Result<Instruction, ProgramError> {
+    check_program_account(token_program_id)?;
+    let data = TokenInstruction::RemoveMaxBalanceExemption.pack();
+
+    let mut accounts = Vec::with_capacity(3 + signer_pubkeys.len());
+    accounts.push(AccountMeta::new(*account_pubkey, false));
+    accounts.push(AccountMeta::new_readonly(*mint_pubkey, false));
+    accounts.push(AccountMeta::new_readonly(
+        *owner_pubkey,
+        signer_pubkeys.is_empty(),
+    ));
+    for signer_pubkey in signer_pubkeys.iter() {
+        accounts.push(AccountMeta::new_readonly(**signer_pubkey, true));
+    }
+
+    Ok(Instruction {
+        program_id: *token_program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a `SetLaunchControls` instruction.
+pub fn set_launch_controls(
+    token_program_id: &Pubkey,
+    mint_pubkey: &Pubkey,
+    owner_pubkey: &Pubkey,
+    signer_pubkeys: &[&Pubkey],
+    trading_start_timestamp: UnixTimestamp,
+    transfer_cooldown: u64,
+) -> Result<Instruction, ProgramError> {
+    check_program_account(token_program_id)?;
+    let data = TokenInstruction::SetLaunchControls {
+        trading_start_timestamp,
+        transfer_cooldown,
+    }
+    .pack();
+
+    let mut accounts = Vec::with_capacity(2 + signer_pubkeys.len());
+    accounts.push(AccountMeta::new(*mint_pubkey, false));
+    accounts.push(AccountMeta::new_readonly(
+        *owner_pubkey,
+        signer_pubkeys.is_empty(),
+    ));
+    for signer_pubkey in signer_pubkeys.iter() {
+        accounts.push(AccountMeta::new_readonly(**signer_pubkey, true));
+    }
+
+    Ok(Instruction {
+        program_id: *token_program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates an `IssueCredential` instruction.
+#[allow(clippy::too_many_arguments)]
+pub fn issue_credential(
+    token_program_id: &Pubkey,
+    mint_pubkey: &Pubkey,
+    holder_pubkey: &Pubkey,
+    payer_pubkey: &Pubkey,
+    authority_pubkey: &Pubkey,
+    signer_pubkeys: &[&Pubkey],
+    expiry: UnixTimestamp,
+    jurisdiction: u16,
+) -> Result<Instruction, ProgramError> {
+    check_program_account(token_program_id)?;
+    let data = TokenInstruction::IssueCredential {
+        expiry,
+        jurisdiction,
+    }
+    .pack();
+
+    let mut accounts = Vec::with_capacity(7 + signer_pubkeys.len());
+    accounts.push(AccountMeta::new(
+        get_credential_address(mint_pubkey, holder_pubkey),
+        false,
+    ));
+    accounts.push(AccountMeta::new_readonly(*mint_pubkey, false));
+    accounts.push(AccountMeta::new_readonly(*holder_pubkey, false));
+    accounts.push(AccountMeta::new(*payer_pubkey, true));
+    accounts.push(AccountMeta::new_readonly(system_program::id(), false));
+    accounts.push(AccountMeta::new_readonly(sysvar::rent::id(), false));
+    accounts.push(AccountMeta::new_readonly(
+        *authority_pubkey,
+        signer_pubkeys.is_empty(),
+    ));
+    for signer_pubkey in signer_pubkeys.iter() {
+        accounts.push(AccountMeta::new_readonly(**signer_pubkey, true));
+    }
+
+    Ok(Instruction {
+        program_id: *token_program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a `RevokeCredential` instruction.
+pub fn revoke_credential(
+    token_program_id: &Pubkey,
+    mint_pubkey: &Pubkey,
+    holder_pubkey: &Pubkey,
+    destination_pubkey: &Pubkey,
+    authority_pubkey: &Pubkey,
+    signer_pubkeys: &[&Pubkey],
+) -> Result<Instruction, ProgramError> {
+    check_program_account(token_program_id)?;
+    let data = TokenInstruction::RevokeCredential.pack();
+
+    let mut accounts = Vec::with_capacity(4 + signer_pubkeys.len());
+    accounts.push(AccountMeta::new(
+        get_credential_address(mint_pubkey, holder_pubkey),
+        false,
+    ));
+    accounts.push(AccountMeta::new_readonly(*mint_pubkey, false));
+    accounts.push(AccountMeta::new(*destination_pubkey, false));
+    accounts.push(AccountMeta::new_readonly(
+        *authority_pubkey,
+        signer_pubkeys.is_empty(),
+    ));
+    for signer_pubkey in signer_pubkeys.iter() {
+        accounts.push(AccountMeta::new_readonly(**signer_pubkey, true));
+    }
+
+    Ok(Instruction {
+        program_id: *token_program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a `SetMinTransferAmount` instruction.
+pub fn set_min_transfer_amount(
+    token_program_id: &Pubkey,
+    mint_pubkey: &Pubkey,
+    owner_pubkey: &Pubkey,
+    signer_pubkeys: &[&Pubkey],
+    amount: u64,
+) -> Result<Instruction, ProgramError> {
+    check_program_account(token_program_id)?;
+    let data = TokenInstruction::SetMinTransferAmount { amount }.pack();
+
+    let mut accounts = Vec::with_capacity(2 + signer_pubkeys.len());
+    accounts.push(AccountMeta::new(*mint_pubkey, false));
+    accounts.push(AccountMeta::new_readonly(
+        *owner_pubkey,
+        signer_pubkeys.is_empty(),
+    ));
+    for signer_pubkey in signer_pubkeys.iter() {
+        accounts.push(AccountMeta::new_readonly(**signer_pubkey, true));
+    }
+
+    Ok(Instruction {
+        program_id: *token_program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a `SetMinIncomingAmount` instruction.
+pub fn set_min_incoming_amount(
+    token_program_id: &Pubkey,
+    account_pubkey: &Pubkey,
+    owner_pubkey: &Pubkey,
+    signer_pubkeys: &[&Pubkey],
+    amount: u64,
+) -> Result<Instruction, ProgramError> {
+    check_program_account(token_program_id)?;
+    let data = TokenInstruction::SetMinIncomingAmount { amount }.pack();
+
+    let mut accounts = Vec::with_capacity(2 + signer_pubkeys.len());
+    accounts.push(AccountMeta::new(*account_pubkey, false));
+    accounts.push(AccountMeta::new_readonly(
+        *owner_pubkey,
+        signer_pubkeys.is_empty(),
+    ));
+    for signer_pubkey in signer_pubkeys.iter() {
+        accounts.push(AccountMeta::new_readonly(**signer_pubkey, true));
+    }
+
+    Ok(Instruction {
+        program_id: *token_program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a `TransferChecked` instruction.
+#[allow(clippy::too_many_arguments)]
+pub fn transfer_checked(
+    token_program_id: &Pubkey,
+    source_pubkey: &Pubkey,
+    mint_pubkey: &Pubkey,
+    destination_pubkey: &Pubkey,
+    authority_pubkey: &Pubkey,
+    signer_pubkeys: &[&Pubkey],
+    amount: u64,
+    decimals: u8,
+) -> Result<Instruction, ProgramError> {
+    check_program_account(token_program_id)?;
+    let data = TokenInstruction::TransferChecked { amount, decimals }.pack();
+
+    let mut accounts = Vec::with_capacity(4 + signer_pubkeys.len());
+    accounts.push(AccountMeta::new(*source_pubkey, false));
+    accounts.push(AccountMeta::new_readonly(*mint_pubkey, false));
+    accounts.push(AccountMeta::new(*destination_pubkey, false));
+    accounts.push(AccountMeta::new_readonly(
+        *authority_pubkey,
+        signer_pubkeys.is_empty(),
+    ));
+    for signer_pubkey in signer_pubkeys.iter() {
+        accounts.push(AccountMeta::new_readonly(**signer_pubkey, true));
+    }
+
+    Ok(Instruction {
+        program_id: *token_program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates an `ApproveChecked` instruction.
+#[allow(clippy::too_many_arguments)]
+pub fn approve_checked(
+    token_program_id: &Pubkey,
+    source_pubkey: &Pubkey,
+    mint_pubkey: &Pubkey,
+    delegate_pubkey: &Pubkey,
+    owner_pubkey: &Pubkey,
+    signer_pubkeys: &[&Pubkey],
+    amount: u64,
+    decimals: u8,
+) -> Result<Instruction, ProgramError> {
+    check_program_account(token_program_id)?;
+    let data = TokenInstruction::ApproveChecked { amount, decimals }.pack();
+
+    let mut accounts = Vec::with_capacity(4 + signer_pubkeys.len());
+    accounts.push(AccountMeta::new(*source_pubkey, false));
+    accounts.push(AccountMeta::new_readonly(*mint_pubkey, false));
+    accounts.push(AccountMeta::new_readonly(*delegate_pubkey, false));
+    accounts.push(AccountMeta::new_readonly(
+        *owner_pubkey,
+        signer_pubkeys.is_empty(),
+    ));
+    for signer_pubkey in signer_pubkeys.iter() {
+        accounts.push(AccountMeta::new_readonly(**signer_pubkey, true));
+    }
+
+    Ok(Instruction {
+        program_id: *token_program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a `MintToChecked` instruction.
+pub fn mint_to_checked(
+    token_program_id: &Pubkey,
+    mint_pubkey: &Pubkey,
+    account_pubkey: &Pubkey,
+    owner_pubkey: &Pubkey,
+    signer_pubkeys: &[&Pubkey],
+    amount: u64,
+    decimals: u8,
+) -> Result<Instruction, ProgramError> {
+    check_program_account(token_program_id)?;
+    let data = TokenInstruction::MintToChecked { amount, decimals }.pack();
+
+    let mut accounts = Vec::with_capacity(3 + signer_pubkeys.len());
+    accounts.push(AccountMeta::new(*mint_pubkey, false));
+    accounts.push(AccountMeta::new(*account_pubkey, false));
+    accounts.push(AccountMeta::new_readonly(
+        *owner_pubkey,
+        signer_pubkeys.is_empty(),
+    ));
+    for signer_pubkey in signer_pubkeys.iter() {
+        accounts.push(AccountMeta::new_readonly(**signer_pubkey, true));
+    }
+
+    Ok(Instruction {
+        program_id: *token_program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a `BurnChecked` instruction.
+pub fn burn_checked(
+    token_program_id: &Pubkey,
+    account_pubkey: &Pubkey,
+    mint_pubkey: &Pubkey,
+    authority_pubkey: &Pubkey,
+    signer_pubkeys: &[&Pubkey],
+    amount: u64,
+    decimals: u8,
+) -> Result<Instruction, ProgramError> {
+    check_program_account(token_program_id)?;
+    let data = TokenInstruction::BurnChecked { amount, decimals }.pack();
+
+    let mut accounts = Vec::with_capacity(3 + signer_pubkeys.len());
+    accounts.push(AccountMeta::new(*account_pubkey, false));
+    accounts.push(AccountMeta::new(*mint_pubkey, false));
+    accounts.push(AccountMeta::new_readonly(
+        *authority_pubkey,
+        signer_pubkeys.is_empty(),
+    ));
+    for signer_pubkey in signer_pubkeys.iter() {
+        accounts.push(AccountMeta::new_readonly(**signer_pubkey, true));
+    }
+
+    Ok(Instruction {
+        program_id: *token_program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a `InitializeMinter` instruction.
+pub fn initialize_minter(
+    token_program_id: &Pubkey,
+    minter_pubkey: &Pubkey,
+    mint_pubkey: &Pubkey,
+    minter_authority_pubkey: &Pubkey,
+    owner_pubkey: &Pubkey,
+    signer_pubkeys: &[&Pubkey],
+    allowance: u64,
+) -> Result<Instruction, ProgramError> {
+    check_program_account(token_program_id)?;
+    let data = TokenInstruction::InitializeMinter {
+        authority: *minter_authority_pubkey,
+        allowance,
+    }
+    .pack();
+
+    let mut accounts = Vec::with_capacity(4 + signer_pubkeys.len());
+    accounts.push(AccountMeta::new(*minter_pubkey, false));
+    accounts.push(AccountMeta::new_readonly(*mint_pubkey, false));
+    accounts.push(AccountMeta::new_readonly(sysvar::rent::id(), false));
+    accounts.push(AccountMeta::new_readonly(
+        *owner_pubkey,
+        signer_pubkeys.is_empty(),
+    ));
+    for signer_pubkey in signer_pubkeys.iter() {
+        accounts.push(AccountMeta::new_readonly(**signer_pubkey, true));
+    }
+
+    Ok(Instruction {
+        program_id: *token_program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a `SetMinterAllowance` instruction.
+pub fn set_minter_allowance(
+    token_program_id: &Pubkey,
+    minter_pubkey: &Pubkey,
+    mint_pubkey: &Pubkey,
+    owner_pubkey: &Pubkey,
+    signer_pubkeys: &[&Pubkey],
+    allowance: u64,
+) -> Result<Instruction, ProgramError> {
+    check_program_account(token_program_id)?;
+    let data = TokenInstruction::SetMinterAllowance { allowance }.pack();
 
-    let mut accounts = Vec::with_capacity(1 + 1 + signer_pubkeys.len());
-    accounts.push(AccountMeta::new(*multisig_pubkey, false));
-    accounts.push(AccountMeta::new_readonly(sysvar::rent::id(), false));
+    let mut accounts = Vec::with_capacity(3 + signer_pubkeys.len());
+    accounts.push(AccountMeta::new(*minter_pubkey, false));
+    accounts.push(AccountMeta::new_readonly(*mint_pubkey, false));
+    accounts.push(AccountMeta::new_readonly(
+        *owner_pubkey,
+        signer_pubkeys.is_empty(),
+    ));
     for signer_pubkey in signer_pubkeys.iter() {
-        accounts.push(AccountMeta::new_readonly(**signer_pubkey, false));
+        accounts.push(AccountMeta::new_readonly(**signer_pubkey, true));
     }
 
     Ok(Instruction {
@@ -411,23 +4344,24 @@ pub fn initialize_multisig(
     })
 }
 
-/// Creates a `Transfer` instruction.
-pub fn transfer(
+/// Creates a `RevokeMinter` instruction.
+pub fn revoke_minter(
     token_program_id: &Pubkey,
-    source_pubkey: &Pubkey,
+    minter_pubkey: &Pubkey,
+    mint_pubkey: &Pubkey,
     destination_pubkey: &Pubkey,
-    authority_pubkey: &Pubkey,
+    owner_pubkey: &Pubkey,
     signer_pubkeys: &[&Pubkey],
-    amount: u64,
 ) -> Result<Instruction, ProgramError> {
     check_program_account(token_program_id)?;
-    let data = TokenInstruction::Transfer { amount }.pack();
+    let data = TokenInstruction::RevokeMinter.pack();
 
-    let mut accounts = Vec::with_capacity(3 + signer_pubkeys.len());
-    accounts.push(AccountMeta::new(*source_pubkey, false));
+    let mut accounts = Vec::with_capacity(4 + signer_pubkeys.len());
+    accounts.push(AccountMeta::new(*minter_pubkey, false));
+    accounts.push(AccountMeta::new_readonly(*mint_pubkey, false));
     accounts.push(AccountMeta::new(*destination_pubkey, false));
     accounts.push(AccountMeta::new_readonly(
-        *authority_pubkey,
+        *owner_pubkey,
         signer_pubkeys.is_empty(),
     ));
     for signer_pubkey in signer_pubkeys.iter() {
@@ -441,21 +4375,25 @@ pub fn transfer(
     })
 }
 
-/// Creates an `Approve` instruction.
-pub fn approve(
+/// Creates a `SetMintRole` instruction.
+pub fn set_mint_role(
     token_program_id: &Pubkey,
-    source_pubkey: &Pubkey,
-    delegate_pubkey: &Pubkey,
+    mint_pubkey: &Pubkey,
+    new_authority_pubkey: Option<&Pubkey>,
+    role: MintRole,
     owner_pubkey: &Pubkey,
     signer_pubkeys: &[&Pubkey],
-    amount: u64,
 ) -> Result<Instruction, ProgramError> {
     check_program_account(token_program_id)?;
-    let data = TokenInstruction::Approve { amount }.pack();
+    let new_authority = new_authority_pubkey.cloned().into();
+    let data = TokenInstruction::SetMintRole {
+        role,
+        new_authority,
+    }
+    .pack();
 
-    let mut accounts = Vec::with_capacity(3 + signer_pubkeys.len());
-    accounts.push(AccountMeta::new(*source_pubkey, false));
-    accounts.push(AccountMeta::new_readonly(*delegate_pubkey, false));
+    let mut accounts = Vec::with_capacity(2 + signer_pubkeys.len());
+    accounts.push(AccountMeta::new(*mint_pubkey, false));
     accounts.push(AccountMeta::new_readonly(
         *owner_pubkey,
         signer_pubkeys.is_empty(),
@@ -471,18 +4409,18 @@ pub fn approve(
     })
 }
 
-/// Creates a `Revoke` instruction.
-pub fn revoke(
+/// Creates a `PauseMinting` instruction.
+pub fn pause_minting(
     token_program_id: &Pubkey,
-    source_pubkey: &Pubkey,
+    mint_pubkey: &Pubkey,
     owner_pubkey: &Pubkey,
     signer_pubkeys: &[&Pubkey],
 ) -> Result<Instruction, ProgramError> {
     check_program_account(token_program_id)?;
-    let data = TokenInstruction::Revoke.pack();
+    let data = TokenInstruction::PauseMinting.pack();
 
     let mut accounts = Vec::with_capacity(2 + signer_pubkeys.len());
-    accounts.push(AccountMeta::new(*source_pubkey, false));
+    accounts.push(AccountMeta::new(*mint_pubkey, false));
     accounts.push(AccountMeta::new_readonly(
         *owner_pubkey,
         signer_pubkeys.is_empty(),
@@ -498,25 +4436,18 @@ pub fn revoke(
     })
 }
 
-/// Creates a `SetAuthority` instruction.
-pub fn set_authority(
+/// Creates a `ResumeMinting` instruction.
+pub fn resume_minting(
     token_program_id: &Pubkey,
-    owned_pubkey: &Pubkey,
-    new_authority_pubkey: Option<&Pubkey>,
-    authority_type: AuthorityType,
+    mint_pubkey: &Pubkey,
     owner_pubkey: &Pubkey,
     signer_pubkeys: &[&Pubkey],
 ) -> Result<Instruction, ProgramError> {
     check_program_account(token_program_id)?;
-    let new_authority = new_authority_pubkey.cloned().into();
-    let data = TokenInstruction::SetAuthority {
-        authority_type,
-        new_authority,
-    }
-    .pack();
+    let data = TokenInstruction::ResumeMinting.pack();
 
-    let mut accounts = Vec::with_capacity(3 + signer_pubkeys.len());
-    accounts.push(AccountMeta::new(*owned_pubkey, false));
+    let mut accounts = Vec::with_capacity(2 + signer_pubkeys.len());
+    accounts.push(AccountMeta::new(*mint_pubkey, false));
     accounts.push(AccountMeta::new_readonly(
         *owner_pubkey,
         signer_pubkeys.is_empty(),
@@ -532,449 +4463,729 @@ pub fn set_authority(
     })
 }
 
-/// Creates a `MintTo` instruction.
-pub fn mint_to(
-    token_program_id: &Pubkey,
-    mint_pubkey: &Pubkey,
-    account_pubkey: &Pubkey,
-    owner_pubkey: &Pubkey,
-    signer_pubkeys: &[&Pubkey],
-    amount: u64,
-) -> Result<Instruction, ProgramError> {
-    check_program_account(token_program_id)?;
-    let data = TokenInstruction::MintTo { amount }.pack();
+/// Utility function that checks index is between MIN_SIGNERS and MAX_SIGNERS
+pub fn is_valid_signer_index(index: usize) -> bool {
+    (MIN_SIGNERS..=MAX_SIGNERS).contains(&index)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_instruction_packing() {
+        let check = TokenInstruction::InitializeMint {
+            decimals: 2,
+            mint_authority: Pubkey::new(&[1u8; 32]),
+            freeze_authority: COption::None,
+        };
+        let packed = check.pack();
+        let mut expect = Vec::from([0u8, 2]);
+        expect.extend_from_slice(&[1u8; 32]);
+        expect.extend_from_slice(&[0]);
+        assert_eq!(packed, expect);
+        let unpacked = TokenInstruction::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check);
+
+        let check = TokenInstruction::InitializeMint {
+            decimals: 2,
+            mint_authority: Pubkey::new(&[2u8; 32]),
+            freeze_authority: COption::Some(Pubkey::new(&[3u8; 32])),
+        };
+        let packed = check.pack();
+        let mut expect = vec![0u8, 2];
+        expect.extend_from_slice(&[2u8; 32]);
+        expect.extend_from_slice(&[1]);
+        expect.extend_from_slice(&[3u8; 32]);
+        assert_eq!(packed, expect);
+        let unpacked = TokenInstruction::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check);
+
+        let check = TokenInstruction::InitializeAccount;
+        let packed = check.pack();
+        let expect = Vec::from([1u8]);
+        assert_eq!(packed, expect);
+        let unpacked = TokenInstruction::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check);
+
+        let check = TokenInstruction::InitializeMultisig { m: 1 };
+        let packed = check.pack();
+        let expect = Vec::from([2u8, 1]);
+        assert_eq!(packed, expect);
+        let unpacked = TokenInstruction::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check);
+
+        let check = TokenInstruction::Transfer { amount: 1 };
+        let packed = check.pack();
+        let expect = Vec::from([3u8, 1, 0, 0, 0, 0, 0, 0, 0]);
+        assert_eq!(packed, expect);
+        let unpacked = TokenInstruction::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check);
+
+        let check = TokenInstruction::Approve { amount: 1 };
+        let packed = check.pack();
+        let expect = Vec::from([4u8, 1, 0, 0, 0, 0, 0, 0, 0]);
+        assert_eq!(packed, expect);
+        let unpacked = TokenInstruction::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check);
+
+        let check = TokenInstruction::Revoke;
+        let packed = check.pack();
+        let expect = Vec::from([5u8]);
+        assert_eq!(packed, expect);
+        let unpacked = TokenInstruction::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check);
+
+        let check = TokenInstruction::SetAuthority {
+            authority_type: AuthorityType::FreezeAccount,
+            new_authority: COption::Some(Pubkey::new(&[4u8; 32])),
+        };
+        let packed = check.pack();
+        let mut expect = Vec::from([6u8, 1]);
+        expect.extend_from_slice(&[1]);
+        expect.extend_from_slice(&[4u8; 32]);
+        assert_eq!(packed, expect);
+        let unpacked = TokenInstruction::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check);
+
+        let check = TokenInstruction::MintTo { amount: 1 };
+        let packed = check.pack();
+        let expect = Vec::from([7u8, 1, 0, 0, 0, 0, 0, 0, 0]);
+        assert_eq!(packed, expect);
+        let unpacked = TokenInstruction::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check);
+
+        let check = TokenInstruction::Burn { amount: 1 };
+        let packed = check.pack();
+        let expect = Vec::from([8u8, 1, 0, 0, 0, 0, 0, 0, 0]);
+        assert_eq!(packed, expect);
+        let unpacked = TokenInstruction::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check);
+
+        let check = TokenInstruction::CloseAccount;
+        let packed = check.pack();
+        let expect = Vec::from([9u8]);
+        assert_eq!(packed, expect);
+        let unpacked = TokenInstruction::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check);
+
+        let check = TokenInstruction::FreezeAccount;
+        let packed = check.pack();
+        let expect = Vec::from([10u8]);
+        assert_eq!(packed, expect);
+        let unpacked = TokenInstruction::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check);
+
+        let check = TokenInstruction::ThawAccount;
+        let packed = check.pack();
+        let expect = Vec::from([11u8]);
+        assert_eq!(packed, expect);
+        let unpacked = TokenInstruction::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check);
+
+        let check = TokenInstruction::TransferChecked {
+            amount: 1,
+            decimals: 2,
+        };
+        let packed = check.pack();
+        let expect = Vec::from([12u8, 1, 0, 0, 0, 0, 0, 0, 0, 2]);
+        assert_eq!(packed, expect);
+        let unpacked = TokenInstruction::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check);
+
+        let check = TokenInstruction::ApproveChecked {
+            amount: 1,
+            decimals: 2,
+        };
+        let packed = check.pack();
+        let expect = Vec::from([13u8, 1, 0, 0, 0, 0, 0, 0, 0, 2]);
+        assert_eq!(packed, expect);
+        let unpacked = TokenInstruction::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check);
+
+        let check = TokenInstruction::MintToChecked {
+            amount: 1,
+            decimals: 2,
+        };
+        let packed = check.pack();
+        let expect = Vec::from([14u8, 1, 0, 0, 0, 0, 0, 0, 0, 2]);
+        assert_eq!(packed, expect);
+        let unpacked = TokenInstruction::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check);
+
+        let check = TokenInstruction::BurnChecked {
+            amount: 1,
+            decimals: 2,
+        };
+        let packed = check.pack();
+        let expect = Vec::from([15u8, 1, 0, 0, 0, 0, 0, 0, 0, 2]);
+        assert_eq!(packed, expect);
+        let unpacked = TokenInstruction::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check);
+
+        let check = TokenInstruction::InitializeAccount2 {
+            owner: Pubkey::new(&[2u8; 32]),
+        };
+        let packed = check.pack();
+        let mut expect = vec![16u8];
+        expect.extend_from_slice(&[2u8; 32]);
+        assert_eq!(packed, expect);
+        let unpacked = TokenInstruction::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check);
+
+        let check = TokenInstruction::InitializeMinter {
+            authority: Pubkey::new(&[2u8; 32]),
+            allowance: 1,
+        };
+        let packed = check.pack();
+        let mut expect = vec![17u8];
+        expect.extend_from_slice(&[2u8; 32]);
+        expect.extend_from_slice(&[1, 0, 0, 0, 0, 0, 0, 0]);
+        assert_eq!(packed, expect);
+        let unpacked = TokenInstruction::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check);
+
+        let check = TokenInstruction::SetMinterAllowance { allowance: 1 };
+        let packed = check.pack();
+        let expect = Vec::from([18u8, 1, 0, 0, 0, 0, 0, 0, 0]);
+        assert_eq!(packed, expect);
+        let unpacked = TokenInstruction::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check);
 
-    let mut accounts = Vec::with_capacity(3 + signer_pubkeys.len());
-    accounts.push(AccountMeta::new(*mint_pubkey, false));
-    accounts.push(AccountMeta::new(*account_pubkey, false));
-    accounts.push(AccountMeta::new_readonly(
-        *owner_pubkey,
-        signer_pubkeys.is_empty(),
-    ));
-    for signer_pubkey in signer_pubkeys.iter() {
-        accounts.push(AccountMeta::new_readonly(**signer_pubkey, true));
-    }
+        let check = TokenInstruction::RevokeMinter;
+        let packed = check.pack();
+        let expect = Vec::from([19u8]);
+        assert_eq!(packed, expect);
+        let unpacked = TokenInstruction::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check);
 
-    Ok(Instruction {
-        program_id: *token_program_id,
-        accounts,
-        data,
-    })
-}
+        let check = TokenInstruction::SetMintRole {
+            role: MintRole::Freezer,
+            new_authority: COption::Some(Pubkey::new(&[4u8; 32])),
+        };
+        let packed = check.pack();
+        let mut expect = Vec::from([20u8, 2]);
+        expect.extend_from_slice(&[1]);
+        expect.extend_from_slice(&[4u8; 32]);
+        assert_eq!(packed, expect);
+        let unpacked = TokenInstruction::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check);
 
-/// Creates a `Burn` instruction.
-pub fn burn(
-    token_program_id: &Pubkey,
-    account_pubkey: &Pubkey,
-    mint_pubkey: &Pubkey,
-    authority_pubkey: &Pubkey,
-    signer_pubkeys: &[&Pubkey],
-    amount: u64,
-) -> Result<Instruction, ProgramError> {
-    check_program_account(token_program_id)?;
-    let data = TokenInstruction::Burn { amount }.pack();
+        let check = TokenInstruction::PauseMinting;
+        let packed = check.pack();
+        let expect = Vec::from([21u8]);
+        assert_eq!(packed, expect);
+        let unpacked = TokenInstruction::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check);
 
-    let mut accounts = Vec::with_capacity(3 + signer_pubkeys.len());
-    accounts.push(AccountMeta::new(*account_pubkey, false));
-    accounts.push(AccountMeta::new(*mint_pubkey, false));
-    accounts.push(AccountMeta::new_readonly(
-        *authority_pubkey,
-        signer_pubkeys.is_empty(),
-    ));
-    for signer_pubkey in signer_pubkeys.iter() {
-        accounts.push(AccountMeta::new_readonly(**signer_pubkey, true));
-    }
+        let check = TokenInstruction::ResumeMinting;
+        let packed = check.pack();
+        let expect = Vec::from([22u8]);
+        assert_eq!(packed, expect);
+        let unpacked = TokenInstruction::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check);
 
-    Ok(Instruction {
-        program_id: *token_program_id,
-        accounts,
-        data,
-    })
-}
+        let check = TokenInstruction::ProposeAuthority {
+            authority_type: AuthorityType::AccountOwner,
+            new_authority: Pubkey::new(&[4u8; 32]),
+        };
+        let packed = check.pack();
+        let mut expect = Vec::from([23u8, 2]);
+        expect.extend_from_slice(&[4u8; 32]);
+        assert_eq!(packed, expect);
+        let unpacked = TokenInstruction::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check);
 
-/// Creates a `CloseAccount` instruction.
-pub fn close_account(
-    token_program_id: &Pubkey,
-    account_pubkey: &Pubkey,
-    destination_pubkey: &Pubkey,
-    owner_pubkey: &Pubkey,
-    signer_pubkeys: &[&Pubkey],
-) -> Result<Instruction, ProgramError> {
-    check_program_account(token_program_id)?;
-    let data = TokenInstruction::CloseAccount.pack();
+        let check = TokenInstruction::AcceptAuthority {
+            authority_type: AuthorityType::MintTokens,
+        };
+        let packed = check.pack();
+        let expect = Vec::from([24u8, 0]);
+        assert_eq!(packed, expect);
+        let unpacked = TokenInstruction::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check);
 
-    let mut accounts = Vec::with_capacity(3 + signer_pubkeys.len());
-    accounts.push(AccountMeta::new(*account_pubkey, false));
-    accounts.push(AccountMeta::new(*destination_pubkey, false));
-    accounts.push(AccountMeta::new_readonly(
-        *owner_pubkey,
-        signer_pubkeys.is_empty(),
-    ));
-    for signer_pubkey in signer_pubkeys.iter() {
-        accounts.push(AccountMeta::new_readonly(**signer_pubkey, true));
-    }
+        let check = TokenInstruction::CancelAuthorityProposal {
+            authority_type: AuthorityType::CloseAccount,
+        };
+        let packed = check.pack();
+        let expect = Vec::from([25u8, 3]);
+        assert_eq!(packed, expect);
+        let unpacked = TokenInstruction::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check);
 
-    Ok(Instruction {
-        program_id: *token_program_id,
-        accounts,
-        data,
-    })
-}
+        let check = TokenInstruction::SetAuthorityTimelock { delay: 86_400 };
+        let packed = check.pack();
+        let mut expect = Vec::from([26u8]);
+        expect.extend_from_slice(&86_400u64.to_le_bytes());
+        assert_eq!(packed, expect);
+        let unpacked = TokenInstruction::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check);
 
-/// Creates a `FreezeAccount` instruction.
-pub fn freeze_account(
-    token_program_id: &Pubkey,
-    account_pubkey: &Pubkey,
-    mint_pubkey: &Pubkey,
-    owner_pubkey: &Pubkey,
-    signer_pubkeys: &[&Pubkey],
-) -> Result<Instruction, ProgramError> {
-    check_program_account(token_program_id)?;
-    let data = TokenInstruction::FreezeAccount.pack();
+        let check = TokenInstruction::QueueAuthorityChange {
+            authority: TimelockedAuthority::Pauser,
+            new_authority: COption::Some(Pubkey::new(&[4u8; 32])),
+        };
+        let packed = check.pack();
+        let mut expect = Vec::from([27u8, 4]);
+        expect.extend_from_slice(&[1]);
+        expect.extend_from_slice(&[4u8; 32]);
+        assert_eq!(packed, expect);
+        let unpacked = TokenInstruction::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check);
+        assert_eq!(
+            TokenInstruction::unpack(&[27u8, 0, 0]),
+            Err(TokenError::InvalidInstruction.into())
+        );
 
-    let mut accounts = Vec::with_capacity(3 + signer_pubkeys.len());
-    accounts.push(AccountMeta::new(*account_pubkey, false));
-    accounts.push(AccountMeta::new_readonly(*mint_pubkey, false));
-    accounts.push(AccountMeta::new_readonly(
-        *owner_pubkey,
-        signer_pubkeys.is_empty(),
-    ));
-    for signer_pubkey in signer_pubkeys.iter() {
-        accounts.push(AccountMeta::new_readonly(**signer_pubkey, true));
-    }
+        let check = TokenInstruction::ExecuteAuthorityChange;
+        let packed = check.pack();
+        let expect = Vec::from([28u8]);
+        assert_eq!(packed, expect);
+        let unpacked = TokenInstruction::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check);
 
-    Ok(Instruction {
-        program_id: *token_program_id,
-        accounts,
-        data,
-    })
-}
+        let check = TokenInstruction::CancelAuthorityChange;
+        let packed = check.pack();
+        let expect = Vec::from([29u8]);
+        assert_eq!(packed, expect);
+        let unpacked = TokenInstruction::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check);
 
-/// Creates a `ThawAccount` instruction.
-pub fn thaw_account(
-    token_program_id: &Pubkey,
-    account_pubkey: &Pubkey,
-    mint_pubkey: &Pubkey,
-    owner_pubkey: &Pubkey,
-    signer_pubkeys: &[&Pubkey],
-) -> Result<Instruction, ProgramError> {
-    check_program_account(token_program_id)?;
-    let data = TokenInstruction::ThawAccount.pack();
+        let check = TokenInstruction::SetGuardians {
+            guardians: COption::Some(Pubkey::new(&[5u8; 32])),
+            recovery_delay: 604_800,
+        };
+        let packed = check.pack();
+        let mut expect = Vec::from([30u8, 1]);
+        expect.extend_from_slice(&[5u8; 32]);
+        expect.extend_from_slice(&604_800u64.to_le_bytes());
+        assert_eq!(packed, expect);
+        let unpacked = TokenInstruction::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check);
 
-    let mut accounts = Vec::with_capacity(3 + signer_pubkeys.len());
-    accounts.push(AccountMeta::new(*account_pubkey, false));
-    accounts.push(AccountMeta::new_readonly(*mint_pubkey, false));
-    accounts.push(AccountMeta::new_readonly(
-        *owner_pubkey,
-        signer_pubkeys.is_empty(),
-    ));
-    for signer_pubkey in signer_pubkeys.iter() {
-        accounts.push(AccountMeta::new_readonly(**signer_pubkey, true));
-    }
+        let check = TokenInstruction::InitiateRecovery {
+            new_owner: Pubkey::new(&[6u8; 32]),
+        };
+        let packed = check.pack();
+        let mut expect = Vec::from([31u8]);
+        expect.extend_from_slice(&[6u8; 32]);
+        assert_eq!(packed, expect);
+        let unpacked = TokenInstruction::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check);
 
-    Ok(Instruction {
-        program_id: *token_program_id,
-        accounts,
-        data,
-    })
-}
+        let check = TokenInstruction::CompleteRecovery;
+        let packed = check.pack();
+        let expect = Vec::from([32u8]);
+        assert_eq!(packed, expect);
+        let unpacked = TokenInstruction::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check);
 
-/// Creates a `TransferChecked` instruction.
-#[allow(clippy::too_many_arguments)]
-pub fn transfer_checked(
-    token_program_id: &Pubkey,
-    source_pubkey: &Pubkey,
-    mint_pubkey: &Pubkey,
-    destination_pubkey: &Pubkey,
-    authority_pubkey: &Pubkey,
-    signer_pubkeys: &[&Pubkey],
-    amount: u64,
-    decimals: u8,
-) -> Result<Instruction, ProgramError> {
-    check_program_account(token_program_id)?;
-    let data = TokenInstruction::TransferChecked { amount, decimals }.pack();
+        let check = TokenInstruction::VetoRecovery;
+        let packed = check.pack();
+        let expect = Vec::from([33u8]);
+        assert_eq!(packed, expect);
+        let unpacked = TokenInstruction::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check);
 
-    let mut accounts = Vec::with_capacity(4 + signer_pubkeys.len());
-    accounts.push(AccountMeta::new(*source_pubkey, false));
-    accounts.push(AccountMeta::new_readonly(*mint_pubkey, false));
-    accounts.push(AccountMeta::new(*destination_pubkey, false));
-    accounts.push(AccountMeta::new_readonly(
-        *authority_pubkey,
-        signer_pubkeys.is_empty(),
-    ));
-    for signer_pubkey in signer_pubkeys.iter() {
-        accounts.push(AccountMeta::new_readonly(**signer_pubkey, true));
-    }
+        let check = TokenInstruction::ApproveWithLimits {
+            amount: 1000,
+            expiry: 1_700_000_000,
+            max_per_transfer: COption::Some(100),
+        };
+        let packed = check.pack();
+        let mut expect = Vec::from([34u8]);
+        expect.extend_from_slice(&1000u64.to_le_bytes());
+        expect.extend_from_slice(&1_700_000_000i64.to_le_bytes());
+        expect.extend_from_slice(&[1]);
+        expect.extend_from_slice(&100u64.to_le_bytes());
+        assert_eq!(packed, expect);
+        let unpacked = TokenInstruction::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check);
 
-    Ok(Instruction {
-        program_id: *token_program_id,
-        accounts,
-        data,
-    })
-}
+        let check = TokenInstruction::ApproveWithLimits {
+            amount: 1,
+            expiry: 0,
+            max_per_transfer: COption::None,
+        };
+        let packed = check.pack();
+        let mut expect = Vec::from([34u8]);
+        expect.extend_from_slice(&1u64.to_le_bytes());
+        expect.extend_from_slice(&0i64.to_le_bytes());
+        expect.extend_from_slice(&[0]);
+        assert_eq!(packed, expect);
+        let unpacked = TokenInstruction::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check);
 
-/// Creates an `ApproveChecked` instruction.
-#[allow(clippy::too_many_arguments)]
-pub fn approve_checked(
-    token_program_id: &Pubkey,
-    source_pubkey: &Pubkey,
-    mint_pubkey: &Pubkey,
-    delegate_pubkey: &Pubkey,
-    owner_pubkey: &Pubkey,
-    signer_pubkeys: &[&Pubkey],
-    amount: u64,
-    decimals: u8,
-) -> Result<Instruction, ProgramError> {
-    check_program_account(token_program_id)?;
-    let data = TokenInstruction::ApproveChecked { amount, decimals }.pack();
+        let check = TokenInstruction::SetAllowance { amount: 250 };
+        let packed = check.pack();
+        let mut expect = Vec::from([35u8]);
+        expect.extend_from_slice(&250u64.to_le_bytes());
+        assert_eq!(packed, expect);
+        let unpacked = TokenInstruction::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check);
 
-    let mut accounts = Vec::with_capacity(4 + signer_pubkeys.len());
-    accounts.push(AccountMeta::new(*source_pubkey, false));
-    accounts.push(AccountMeta::new_readonly(*mint_pubkey, false));
-    accounts.push(AccountMeta::new_readonly(*delegate_pubkey, false));
-    accounts.push(AccountMeta::new_readonly(
-        *owner_pubkey,
-        signer_pubkeys.is_empty(),
-    ));
-    for signer_pubkey in signer_pubkeys.iter() {
-        accounts.push(AccountMeta::new_readonly(**signer_pubkey, true));
-    }
+        let check = TokenInstruction::CloseAllowance;
+        let packed = check.pack();
+        let expect = Vec::from([36u8]);
+        assert_eq!(packed, expect);
+        let unpacked = TokenInstruction::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check);
 
-    Ok(Instruction {
-        program_id: *token_program_id,
-        accounts,
-        data,
-    })
-}
+        let check = TokenInstruction::ApproveIfCurrent {
+            expected_delegate: COption::Some(Pubkey::new(&[3u8; 32])),
+            expected_amount: 10,
+            amount: 20,
+        };
+        let packed = check.pack();
+        let mut expect = Vec::from([37u8, 1]);
+        expect.extend_from_slice(&[3u8; 32]);
+        expect.extend_from_slice(&10u64.to_le_bytes());
+        expect.extend_from_slice(&20u64.to_le_bytes());
+        assert_eq!(packed, expect);
+        let unpacked = TokenInstruction::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check);
 
-/// Creates a `MintToChecked` instruction.
-pub fn mint_to_checked(
-    token_program_id: &Pubkey,
-    mint_pubkey: &Pubkey,
-    account_pubkey: &Pubkey,
-    owner_pubkey: &Pubkey,
-    signer_pubkeys: &[&Pubkey],
-    amount: u64,
-    decimals: u8,
-) -> Result<Instruction, ProgramError> {
-    check_program_account(token_program_id)?;
-    let data = TokenInstruction::MintToChecked { amount, decimals }.pack();
+        let check = TokenInstruction::IncreaseAllowance { amount: 5 };
+        let packed = check.pack();
+        let expect = Vec::from([38u8, 5, 0, 0, 0, 0, 0, 0, 0]);
+        assert_eq!(packed, expect);
+        let unpacked = TokenInstruction::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check);
 
-    let mut accounts = Vec::with_capacity(3 + signer_pubkeys.len());
-    accounts.push(AccountMeta::new(*mint_pubkey, false));
-    accounts.push(AccountMeta::new(*account_pubkey, false));
-    accounts.push(AccountMeta::new_readonly(
-        *owner_pubkey,
-        signer_pubkeys.is_empty(),
-    ));
-    for signer_pubkey in signer_pubkeys.iter() {
-        accounts.push(AccountMeta::new_readonly(**signer_pubkey, true));
-    }
+        let check = TokenInstruction::DecreaseAllowance { amount: 5 };
+        let packed = check.pack();
+        let expect = Vec::from([39u8, 5, 0, 0, 0, 0, 0, 0, 0]);
+        assert_eq!(packed, expect);
+        let unpacked = TokenInstruction::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check);
 
-    Ok(Instruction {
-        program_id: *token_program_id,
-        accounts,
-        data,
-    })
-}
+        let check = TokenInstruction::InitializeNonce;
+        let packed = check.pack();
+        let expect = Vec::from([40u8]);
+        assert_eq!(packed, expect);
+        let unpacked = TokenInstruction::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check);
 
-/// Creates a `BurnChecked` instruction.
-pub fn burn_checked(
-    token_program_id: &Pubkey,
-    account_pubkey: &Pubkey,
-    mint_pubkey: &Pubkey,
-    authority_pubkey: &Pubkey,
-    signer_pubkeys: &[&Pubkey],
-    amount: u64,
-    decimals: u8,
-) -> Result<Instruction, ProgramError> {
-    check_program_account(token_program_id)?;
-    let data = TokenInstruction::BurnChecked { amount, decimals }.pack();
+        let check = TokenInstruction::Permit {
+            amount: 1,
+            nonce: 2,
+            valid_after: 3,
+            valid_until: 4,
+        };
+        let packed = check.pack();
+        let mut expect = Vec::from([41u8]);
+        expect.extend_from_slice(&1u64.to_le_bytes());
+        expect.extend_from_slice(&2u64.to_le_bytes());
+        expect.extend_from_slice(&3i64.to_le_bytes());
+        expect.extend_from_slice(&4i64.to_le_bytes());
+        assert_eq!(packed, expect);
+        let unpacked = TokenInstruction::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check);
 
-    let mut accounts = Vec::with_capacity(3 + signer_pubkeys.len());
-    accounts.push(AccountMeta::new(*account_pubkey, false));
-    accounts.push(AccountMeta::new(*mint_pubkey, false));
-    accounts.push(AccountMeta::new_readonly(
-        *authority_pubkey,
-        signer_pubkeys.is_empty(),
-    ));
-    for signer_pubkey in signer_pubkeys.iter() {
-        accounts.push(AccountMeta::new_readonly(**signer_pubkey, true));
-    }
+        let check = TokenInstruction::TransferWithAuthorization {
+            amount: 1,
+            nonce: 2,
+            valid_after: 3,
+            valid_until: 4,
+        };
+        let packed = check.pack();
+        let mut expect = Vec::from([42u8]);
+        expect.extend_from_slice(&1u64.to_le_bytes());
+        expect.extend_from_slice(&2u64.to_le_bytes());
+        expect.extend_from_slice(&3i64.to_le_bytes());
+        expect.extend_from_slice(&4i64.to_le_bytes());
+        assert_eq!(packed, expect);
+        let unpacked = TokenInstruction::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check);
 
-    Ok(Instruction {
-        program_id: *token_program_id,
-        accounts,
-        data,
-    })
-}
+        let check = TokenInstruction::CreateSession {
+            session_key: Pubkey::new(&[2u8; 32]),
+            expiry: 3,
+            scope: 1,
+            destinations: vec![Pubkey::new(&[4u8; 32]), Pubkey::new(&[6u8; 32])],
+            max_amount: 5,
+        };
+        let packed = check.pack();
+        let mut expect = Vec::from([43u8]);
+        expect.extend_from_slice(&[2u8; 32]);
+        expect.extend_from_slice(&3i64.to_le_bytes());
+        expect.extend_from_slice(&[1, 2]);
+        expect.extend_from_slice(&[4u8; 32]);
+        expect.extend_from_slice(&[6u8; 32]);
+        expect.extend_from_slice(&5u64.to_le_bytes());
+        assert_eq!(packed, expect);
+        let unpacked = TokenInstruction::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check);
 
-/// Utility function that checks index is between MIN_SIGNERS and MAX_SIGNERS
-pub fn is_valid_signer_index(index: usize) -> bool {
-    (MIN_SIGNERS..=MAX_SIGNERS).contains(&index)
-}
+        let check = TokenInstruction::RevokeSession;
+        let packed = check.pack();
+        let expect = Vec::from([44u8]);
+        assert_eq!(packed, expect);
+        let unpacked = TokenInstruction::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check);
 
-#[cfg(test)]
-mod test {
-    use super::*;
+        let check = TokenInstruction::InitializeEthereumOwner { address: [5u8; 20] };
+        let packed = check.pack();
+        let mut expect = Vec::from([45u8]);
+        expect.extend_from_slice(&[5u8; 20]);
+        assert_eq!(packed, expect);
+        let unpacked = TokenInstruction::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check);
 
-    #[test]
-    fn test_instruction_packing() {
-        let check = TokenInstruction::InitializeMint {
-            decimals: 2,
-            mint_authority: Pubkey::new(&[1u8; 32]),
-            freeze_authority: COption::None,
+        let check = TokenInstruction::InitializeWeightedMultisig {
+            threshold: 3,
+            weights: vec![2, 1, 1],
         };
         let packed = check.pack();
-        let mut expect = Vec::from([0u8, 2]);
-        expect.extend_from_slice(&[1u8; 32]);
-        expect.extend_from_slice(&[0]);
+        let expect = Vec::from([46u8, 3, 0, 2, 1, 1]);
         assert_eq!(packed, expect);
         let unpacked = TokenInstruction::unpack(&expect).unwrap();
         assert_eq!(unpacked, check);
 
-        let check = TokenInstruction::InitializeMint {
-            decimals: 2,
-            mint_authority: Pubkey::new(&[2u8; 32]),
-            freeze_authority: COption::Some(Pubkey::new(&[3u8; 32])),
+        let check = TokenInstruction::AddSigner {
+            signer: Pubkey::new(&[6u8; 32]),
         };
         let packed = check.pack();
-        let mut expect = vec![0u8, 2];
-        expect.extend_from_slice(&[2u8; 32]);
-        expect.extend_from_slice(&[1]);
-        expect.extend_from_slice(&[3u8; 32]);
+        let mut expect = Vec::from([47u8]);
+        expect.extend_from_slice(&[6u8; 32]);
         assert_eq!(packed, expect);
         let unpacked = TokenInstruction::unpack(&expect).unwrap();
         assert_eq!(unpacked, check);
 
-        let check = TokenInstruction::InitializeAccount;
+        let check = TokenInstruction::RemoveSigner {
+            signer: Pubkey::new(&[7u8; 32]),
+        };
         let packed = check.pack();
-        let expect = Vec::from([1u8]);
+        let mut expect = Vec::from([48u8]);
+        expect.extend_from_slice(&[7u8; 32]);
         assert_eq!(packed, expect);
         let unpacked = TokenInstruction::unpack(&expect).unwrap();
         assert_eq!(unpacked, check);
 
-        let check = TokenInstruction::InitializeMultisig { m: 1 };
+        let check = TokenInstruction::SetThreshold { m: 2 };
         let packed = check.pack();
-        let expect = Vec::from([2u8, 1]);
+        let expect = Vec::from([49u8, 2]);
         assert_eq!(packed, expect);
         let unpacked = TokenInstruction::unpack(&expect).unwrap();
         assert_eq!(unpacked, check);
 
-        let check = TokenInstruction::Transfer { amount: 1 };
+        let check = TokenInstruction::CreateProposal {
+            expiry: 1_000,
+            instruction_data: vec![7, 1, 2],
+        };
         let packed = check.pack();
-        let expect = Vec::from([3u8, 1, 0, 0, 0, 0, 0, 0, 0]);
+        let mut expect = Vec::from([50u8]);
+        expect.extend_from_slice(&1_000i64.to_le_bytes());
+        expect.extend_from_slice(&[7, 1, 2]);
         assert_eq!(packed, expect);
         let unpacked = TokenInstruction::unpack(&expect).unwrap();
         assert_eq!(unpacked, check);
 
-        let check = TokenInstruction::Approve { amount: 1 };
+        let check = TokenInstruction::ApproveProposal;
         let packed = check.pack();
-        let expect = Vec::from([4u8, 1, 0, 0, 0, 0, 0, 0, 0]);
+        let expect = Vec::from([51u8]);
         assert_eq!(packed, expect);
         let unpacked = TokenInstruction::unpack(&expect).unwrap();
         assert_eq!(unpacked, check);
 
-        let check = TokenInstruction::Revoke;
+        let check = TokenInstruction::ExecuteProposal;
         let packed = check.pack();
-        let expect = Vec::from([5u8]);
+        let expect = Vec::from([52u8]);
         assert_eq!(packed, expect);
         let unpacked = TokenInstruction::unpack(&expect).unwrap();
         assert_eq!(unpacked, check);
 
-        let check = TokenInstruction::SetAuthority {
-            authority_type: AuthorityType::FreezeAccount,
-            new_authority: COption::Some(Pubkey::new(&[4u8; 32])),
+        let check = TokenInstruction::CancelProposal;
+        let packed = check.pack();
+        let expect = Vec::from([53u8]);
+        assert_eq!(packed, expect);
+        let unpacked = TokenInstruction::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check);
+
+        let check = TokenInstruction::PlaceHold {
+            capturer: Pubkey::new(&[4u8; 32]),
+            amount: 100,
+            expiry: 1_000,
         };
         let packed = check.pack();
-        let mut expect = Vec::from([6u8, 1]);
-        expect.extend_from_slice(&[1]);
+        let mut expect = Vec::from([54u8]);
         expect.extend_from_slice(&[4u8; 32]);
+        expect.extend_from_slice(&100u64.to_le_bytes());
+        expect.extend_from_slice(&1_000i64.to_le_bytes());
         assert_eq!(packed, expect);
         let unpacked = TokenInstruction::unpack(&expect).unwrap();
         assert_eq!(unpacked, check);
 
-        let check = TokenInstruction::MintTo { amount: 1 };
+        let check = TokenInstruction::CaptureHold { amount: 60 };
         let packed = check.pack();
-        let expect = Vec::from([7u8, 1, 0, 0, 0, 0, 0, 0, 0]);
+        let mut expect = Vec::from([55u8]);
+        expect.extend_from_slice(&60u64.to_le_bytes());
         assert_eq!(packed, expect);
         let unpacked = TokenInstruction::unpack(&expect).unwrap();
         assert_eq!(unpacked, check);
 
-        let check = TokenInstruction::Burn { amount: 1 };
+        let check = TokenInstruction::ReleaseHold;
         let packed = check.pack();
-        let expect = Vec::from([8u8, 1, 0, 0, 0, 0, 0, 0, 0]);
+        let expect = Vec::from([56u8]);
         assert_eq!(packed, expect);
         let unpacked = TokenInstruction::unpack(&expect).unwrap();
         assert_eq!(unpacked, check);
 
-        let check = TokenInstruction::CloseAccount;
+        let check = TokenInstruction::SetReceiveOnly;
         let packed = check.pack();
-        let expect = Vec::from([9u8]);
+        let expect = Vec::from([57u8]);
         assert_eq!(packed, expect);
         let unpacked = TokenInstruction::unpack(&expect).unwrap();
         assert_eq!(unpacked, check);
 
-        let check = TokenInstruction::FreezeAccount;
+        let check = TokenInstruction::SetSendOnly;
         let packed = check.pack();
-        let expect = Vec::from([10u8]);
+        let expect = Vec::from([58u8]);
         assert_eq!(packed, expect);
         let unpacked = TokenInstruction::unpack(&expect).unwrap();
         assert_eq!(unpacked, check);
 
-        let check = TokenInstruction::ThawAccount;
+        let check = TokenInstruction::FreezeAccountWithReason {
+            reason: 3,
+            auto_thaw_timestamp: 1_000,
+        };
         let packed = check.pack();
-        let expect = Vec::from([11u8]);
+        let mut expect = Vec::from([59u8, 3]);
+        expect.extend_from_slice(&1_000i64.to_le_bytes());
         assert_eq!(packed, expect);
         let unpacked = TokenInstruction::unpack(&expect).unwrap();
         assert_eq!(unpacked, check);
 
-        let check = TokenInstruction::TransferChecked {
-            amount: 1,
-            decimals: 2,
+        let check = TokenInstruction::FreezeAccounts { count: 3 };
+        let packed = check.pack();
+        let expect = Vec::from([60u8, 3]);
+        assert_eq!(packed, expect);
+        let unpacked = TokenInstruction::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check);
+
+        let check = TokenInstruction::ThawAccounts { count: 3 };
+        let packed = check.pack();
+        let expect = Vec::from([61u8, 3]);
+        assert_eq!(packed, expect);
+        let unpacked = TokenInstruction::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check);
+
+        let check = TokenInstruction::SetSpendingLimit {
+            amount: 500,
+            window: 3_600,
         };
         let packed = check.pack();
-        let expect = Vec::from([12u8, 1, 0, 0, 0, 0, 0, 0, 0, 2]);
+        let mut expect = Vec::from([62u8]);
+        expect.extend_from_slice(&500u64.to_le_bytes());
+        expect.extend_from_slice(&3_600u64.to_le_bytes());
         assert_eq!(packed, expect);
         let unpacked = TokenInstruction::unpack(&expect).unwrap();
         assert_eq!(unpacked, check);
 
-        let check = TokenInstruction::ApproveChecked {
-            amount: 1,
-            decimals: 2,
+        let check = TokenInstruction::InitializeDestinationAllowlist;
+        let packed = check.pack();
+        let expect = Vec::from([63u8]);
+        assert_eq!(packed, expect);
+        let unpacked = TokenInstruction::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check);
+
+        let check = TokenInstruction::AddAllowedDestination {
+            destination: Pubkey::new(&[4u8; 32]),
         };
         let packed = check.pack();
-        let expect = Vec::from([13u8, 1, 0, 0, 0, 0, 0, 0, 0, 2]);
+        let mut expect = Vec::from([64u8]);
+        expect.extend_from_slice(&[4u8; 32]);
         assert_eq!(packed, expect);
         let unpacked = TokenInstruction::unpack(&expect).unwrap();
         assert_eq!(unpacked, check);
 
-        let check = TokenInstruction::MintToChecked {
-            amount: 1,
-            decimals: 2,
+        let check = TokenInstruction::RemoveAllowedDestination {
+            destination: Pubkey::new(&[4u8; 32]),
         };
         let packed = check.pack();
-        let expect = Vec::from([14u8, 1, 0, 0, 0, 0, 0, 0, 0, 2]);
+        let mut expect = Vec::from([65u8]);
+        expect.extend_from_slice(&[4u8; 32]);
         assert_eq!(packed, expect);
         let unpacked = TokenInstruction::unpack(&expect).unwrap();
         assert_eq!(unpacked, check);
 
-        let check = TokenInstruction::BurnChecked {
-            amount: 1,
-            decimals: 2,
+        let check = TokenInstruction::SetMaxBalance {
+            max_balance: COption::Some(1_000),
         };
         let packed = check.pack();
-        let expect = Vec::from([15u8, 1, 0, 0, 0, 0, 0, 0, 0, 2]);
+        let mut expect = Vec::from([66u8, 1]);
+        expect.extend_from_slice(&1_000u64.to_le_bytes());
         assert_eq!(packed, expect);
         let unpacked = TokenInstruction::unpack(&expect).unwrap();
         assert_eq!(unpacked, check);
 
-        let check = TokenInstruction::InitializeAccount2 {
-            owner: Pubkey::new(&[2u8; 32]),
+        let check = TokenInstruction::SetMaxBalance {
+            max_balance: COption::None,
         };
         let packed = check.pack();
-        let mut expect = vec![16u8];
-        expect.extend_from_slice(&[2u8; 32]);
+        let expect = Vec::from([66u8, 0]);
+        assert_eq!(packed, expect);
+        let unpacked = TokenInstruction::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check);
+
+        let check = TokenInstruction::AddMaxBalanceExemption;
+        let packed = check.pack();
+        let expect = Vec::from([67u8]);
+        assert_eq!(packed, expect);
+        let unpacked = TokenInstruction::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check);
+
+        let check = TokenInstruction::RemoveMaxBalanceExemption;
+        let packed = check.pack();
+        let expect = Vec::from([68u8]);
+        assert_eq!(packed, expect);
+        let unpacked = TokenInstruction::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check);
+
+        let check = TokenInstruction::SetLaunchControls {
+            trading_start_timestamp: 1_700_000_000,
+            transfer_cooldown: 60,
+        };
+        let packed = check.pack();
+        let mut expect = Vec::from([69u8]);
+        expect.extend_from_slice(&1_700_000_000i64.to_le_bytes());
+        expect.extend_from_slice(&60u64.to_le_bytes());
+        assert_eq!(packed, expect);
+        let unpacked = TokenInstruction::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check);
+
+        let check = TokenInstruction::IssueCredential {
+            expiry: 1_800_000_000,
+            jurisdiction: 840,
+        };
+        let packed = check.pack();
+        let mut expect = Vec::from([70u8]);
+        expect.extend_from_slice(&1_800_000_000i64.to_le_bytes());
+        expect.extend_from_slice(&840u16.to_le_bytes());
+        assert_eq!(packed, expect);
+        let unpacked = TokenInstruction::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check);
+
+        let check = TokenInstruction::RevokeCredential;
+        let packed = check.pack();
+        let expect = Vec::from([71u8]);
+        assert_eq!(packed, expect);
+        let unpacked = TokenInstruction::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check);
+
+        let check = TokenInstruction::SetMinTransferAmount { amount: 1_000 };
+        let packed = check.pack();
+        let mut expect = Vec::from([72u8]);
+        expect.extend_from_slice(&1_000u64.to_le_bytes());
+        assert_eq!(packed, expect);
+        let unpacked = TokenInstruction::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check);
+
+        let check = TokenInstruction::SetMinIncomingAmount { amount: 50 };
+        let packed = check.pack();
+        let mut expect = Vec::from([73u8]);
+        expect.extend_from_slice(&50u64.to_le_bytes());
         assert_eq!(packed, expect);
         let unpacked = TokenInstruction::unpack(&expect).unwrap();
         assert_eq!(unpacked, check);
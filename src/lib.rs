@@ -1,14 +1,21 @@
 #![deny(missing_docs)]
-#![forbid(unsafe_code)]
+#![cfg_attr(not(test), forbid(unsafe_code))]
+#![cfg_attr(test, deny(unsafe_code))]
 
 //! An ERC20-like Token program for the Solana blockchain
 
+pub mod authority;
 pub mod error;
 pub mod instruction;
 pub mod native_mint;
 pub mod processor;
 pub mod state;
 
+/// The native program that verifies ed25519 signatures
+pub mod ed25519_program {
+    solana_program::declare_id!("Ed25519SigVerify111111111111111111111111111");
+}
+
 #[cfg(not(feature = "no-entrypoint"))]
 mod entrypoint;
 
@@ -36,3 +43,68 @@ pub fn check_program_account(spl_token_program_id: &Pubkey) -> ProgramResult {
     }
     Ok(())
 }
+
+/// Seed prefix of allowance addresses
+pub const ALLOWANCE_SEED: &[u8] = b"allowance";
+
+/// Derives the address of the allowance granted to a spender on a token account
+pub fn get_allowance_address(account: &Pubkey, spender: &Pubkey) -> Pubkey {
+    get_allowance_address_and_bump_seed(account, spender, &id()).0
+}
+
+pub(crate) fn get_allowance_address_and_bump_seed(
+    account: &Pubkey,
+    spender: &Pubkey,
+    program_id: &Pubkey,
+) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[ALLOWANCE_SEED, account.as_ref(), spender.as_ref()],
+        program_id,
+    )
+}
+
+/// Seed prefix of authorization nonce addresses
+pub const NONCE_SEED: &[u8] = b"nonce";
+
+/// Derives the address of the nonce protecting an owner's off-chain signed authorizations
+pub fn get_nonce_address(owner: &Pubkey) -> Pubkey {
+    get_nonce_address_and_bump_seed(owner, &id()).0
+}
+
+pub(crate) fn get_nonce_address_and_bump_seed(owner: &Pubkey, program_id: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[NONCE_SEED, owner.as_ref()], program_id)
+}
+
+/// Seed prefix of Ethereum owner addresses
+pub const ETHEREUM_OWNER_SEED: &[u8] = b"ethereum-owner";
+
+/// Derives the address of the owner whose authority is proven by an Ethereum address's signatures
+pub fn get_ethereum_owner_address(address: &state::EthereumAddress) -> Pubkey {
+    get_ethereum_owner_address_and_bump_seed(address, &id()).0
+}
+
+pub(crate) fn get_ethereum_owner_address_and_bump_seed(
+    address: &state::EthereumAddress,
+    program_id: &Pubkey,
+) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[ETHEREUM_OWNER_SEED, address], program_id)
+}
+
+/// Seed prefix of credential addresses
+pub const CREDENTIAL_SEED: &[u8] = b"credential";
+
+/// Derives the address of the credential issued to an owner for a mint
+pub fn get_credential_address(mint: &Pubkey, owner: &Pubkey) -> Pubkey {
+    get_credential_address_and_bump_seed(mint, owner, &id()).0
+}
+
+pub(crate) fn get_credential_address_and_bump_seed(
+    mint: &Pubkey,
+    owner: &Pubkey,
+    program_id: &Pubkey,
+) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[CREDENTIAL_SEED, mint.as_ref(), owner.as_ref()],
+        program_id,
+    )
+}
@@ -10,22 +10,41 @@ InitializeAccount2
 //! Program state processor
 
 use crate::{
+    authority::{AuthorityKind, SessionUse},
+    ed25519_program,
     error::TokenError,
-    instruction::{is_valid_signer_index, AuthorityType, TokenInstruction, MAX_SIGNERS},
-    state::{Account, AccountState, Mint},
+    get_allowance_address_and_bump_seed, get_credential_address_and_bump_seed,
+    get_ethereum_owner_address_and_bump_seed, get_nonce_address_and_bump_seed,
+    instruction::{
+        authorization_message, is_valid_signer_index, AuthorityType, MintRole, TokenInstruction,
+        MAX_SIGNERS,
+    },
+    state::{
+        Account, AccountState, Allowance, AuthorizationNonce, Credential, DestinationAllowlist,
+        EthereumAddress, EthereumOwner, Mint, Minter, Multisig, MultisigProposal, ProposalState,
+        TimelockedAuthority, WeightedMultisig, DESTINATION_ALLOWLIST_DELAY,
+        MAX_ALLOWED_DESTINATIONS, MAX_PROPOSAL_ACCOUNTS, MAX_PROPOSAL_DATA_LEN,
+        MAX_SESSION_DESTINATIONS, SESSION_SCOPE_BURN, SESSION_SCOPE_TRANSFER,
+        SPENDING_LIMIT_INCREASE_DELAY,
+    },
+    ALLOWANCE_SEED, CREDENTIAL_SEED, ETHEREUM_OWNER_SEED, NONCE_SEED,
 };
 use num_traits::FromPrimitive;
 use solana_program::{
     account_info::{next_account_info, AccountInfo},
+    clock::UnixTimestamp,
     decode_error::DecodeError,
     entrypoint::ProgramResult,
     msg,
+    program::{invoke, invoke_signed, set_return_data},
     program_error::{PrintProgramError, ProgramError},
     program_option::COption,
     program_pack::{IsInitialized, Pack},
     pubkey::Pubkey,
-    sysvar::{rent::Rent, Sysvar},
+    system_instruction,
+    sysvar::{self, clock::Clock, rent::Rent, Sysvar},
 };
+use std::convert::TryFrom;
 
 /// Program state handler.
 pub struct Processor {}
@@ -88,6 +107,11 @@ impl Processor {
         if *mint_info.key != crate::native_mint::id() {
             let _ = Mint::unpack(&mint_info.data.borrow_mut())
                 .map_err(|_| Into::<ProgramError>::into(TokenError::InvalidMint))?;
+            if mint_info.data_len() == Mint::EXTENDED_LEN
+                && new_account_info_data_len != Account::EXTENDED_LEN
+            {
+                return Err(TokenError::ExtensionRequired.into());
+            }
         }
 
         account.mint = *mint_info.key;
@@ -150,19 +174,368 @@ impl Processor {
         for (i, signer_info) in signer_infos.iter().enumerate() {
             multisig.signers[i] = *signer_info.key;
         }
+        multisig.verify_integrity(multisig_info.key)?;
+        multisig.is_initialized = true;
+
+        Multisig::pack(multisig, &mut multisig_info.data.borrow_mut())?;
+
+        Ok(())
+    }
+
+    /// Processes an [InitializeWeightedMultisig](enum.TokenInstruction.html) instruction.
+    pub fn process_initialize_weighted_multisig(
+        accounts: &[AccountInfo],
+        threshold: u16,
+        weights: &[u8],
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let multisig_info = next_account_info(account_info_iter)?;
+        let multisig_info_data_len = multisig_info.data_len();
+        let rent = &Rent::from_account_info(next_account_info(account_info_iter)?)?;
+
+        let mut multisig = WeightedMultisig::unpack_unchecked(&multisig_info.data.borrow())?;
+        if multisig.is_initialized {
+            return Err(TokenError::AlreadyInUse.into());
+        }
+
+        if !rent.is_exempt(multisig_info.lamports(), multisig_info_data_len) {
+            return Err(TokenError::NotRentExempt.into());
+        }
+
+        let signer_infos = account_info_iter.as_slice();
+        if !is_valid_signer_index(signer_infos.len()) || weights.len() != signer_infos.len() {
+            return Err(TokenError::InvalidNumberOfProvidedSigners.into());
+        }
+        let total_weight: u16 = weights.iter().map(|weight| *weight as u16).sum();
+        if threshold == 0 || threshold > total_weight {
+            return Err(TokenError::InvalidWeightThreshold.into());
+        }
+        multisig.threshold = threshold;
+        multisig.n = signer_infos.len() as u8;
+        for (i, (signer_info, weight)) in signer_infos.iter().zip(weights).enumerate() {
+            multisig.signers[i] = *signer_info.key;
+            multisig.weights[i] = *weight;
+        }
+        multisig.verify_integrity(multisig_info.key)?;
         multisig.is_initialized = true;
 
+        WeightedMultisig::pack(multisig, &mut multisig_info.data.borrow_mut())?;
+
+        Ok(())
+    }
+
+    /// Processes an [AddSigner](enum.TokenInstruction.html) instruction.
+    pub fn process_add_signer(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        signer: Pubkey,
+        proposal: Option<&MultisigProposal>,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let multisig_info = next_account_info(account_info_iter)?;
+
+        let mut multisig = Multisig::unpack(&multisig_info.data.borrow())?;
+        Self::validate_owner_with_proposal(
+            program_id,
+            multisig_info.key,
+            multisig_info,
+            account_info_iter.as_slice(),
+            proposal,
+        )?;
+
+        let n = multisig.n as usize;
+        if signer == *multisig_info.key {
+            return Err(TokenError::MultisigSelfSigner.into());
+        }
+        if multisig.signers[..n].contains(&signer) {
+            return Err(TokenError::DuplicateSigner.into());
+        }
+        if !is_valid_signer_index(n + 1) {
+            return Err(TokenError::InvalidNumberOfProvidedSigners.into());
+        }
+        multisig.signers[n] = signer;
+        multisig.n += 1;
+
+        Multisig::pack(multisig, &mut multisig_info.data.borrow_mut())?;
+
+        Ok(())
+    }
+
+    /// Processes a [RemoveSigner](enum.TokenInstruction.html) instruction.
+    pub fn process_remove_signer(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        signer: Pubkey,
+        proposal: Option<&MultisigProposal>,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let multisig_info = next_account_info(account_info_iter)?;
+
+        let mut multisig = Multisig::unpack(&multisig_info.data.borrow())?;
+        Self::validate_owner_with_proposal(
+            program_id,
+            multisig_info.key,
+            multisig_info,
+            account_info_iter.as_slice(),
+            proposal,
+        )?;
+
+        let n = multisig.n as usize;
+        let position = multisig.signers[..n]
+            .iter()
+            .position(|key| *key == signer)
+            .ok_or(TokenError::SignerNotFound)?;
+        if !is_valid_signer_index(n - 1) {
+            return Err(TokenError::InvalidNumberOfProvidedSigners.into());
+        }
+        if multisig.m as usize > n - 1 {
+            return Err(TokenError::InvalidNumberOfRequiredSigners.into());
+        }
+        // Keep the signers contiguous so that `signers[..n]` stays valid
+        multisig.signers.copy_within(position + 1..n, position);
+        multisig.signers[n - 1] = Pubkey::default();
+        multisig.n -= 1;
+
+        Multisig::pack(multisig, &mut multisig_info.data.borrow_mut())?;
+
+        Ok(())
+    }
+
+    /// Processes a [SetThreshold](enum.TokenInstruction.html) instruction.
+    pub fn process_set_threshold(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        m: u8,
+        proposal: Option<&MultisigProposal>,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let multisig_info = next_account_info(account_info_iter)?;
+
+        let mut multisig = Multisig::unpack(&multisig_info.data.borrow())?;
+        Self::validate_owner_with_proposal(
+            program_id,
+            multisig_info.key,
+            multisig_info,
+            account_info_iter.as_slice(),
+            proposal,
+        )?;
+
+        if !is_valid_signer_index(m as usize) || m > multisig.n {
+            return Err(TokenError::InvalidNumberOfRequiredSigners.into());
+        }
+        multisig.m = m;
+
         Multisig::pack(multisig, &mut multisig_info.data.borrow_mut())?;
 
         Ok(())
     }
 
+    /// Processes a [CreateProposal](enum.TokenInstruction.html) instruction.
+    pub fn process_create_proposal(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        expiry: UnixTimestamp,
+        instruction_data: &[u8],
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let proposal_info = next_account_info(account_info_iter)?;
+        let proposal_info_data_len = proposal_info.data_len();
+        let multisig_info = next_account_info(account_info_iter)?;
+        let proposer_info = next_account_info(account_info_iter)?;
+        let rent = &Rent::from_account_info(next_account_info(account_info_iter)?)?;
+        let target_infos = account_info_iter.as_slice();
+
+        let mut proposal = MultisigProposal::unpack_unchecked(&proposal_info.data.borrow())?;
+        if proposal.is_initialized() {
+            return Err(TokenError::AlreadyInUse.into());
+        }
+
+        if !rent.is_exempt(proposal_info.lamports(), proposal_info_data_len) {
+            return Err(TokenError::NotRentExempt.into());
+        }
+
+        if instruction_data.len() > MAX_PROPOSAL_DATA_LEN
+            || target_infos.len() > MAX_PROPOSAL_ACCOUNTS
+        {
+            return Err(ProgramError::InvalidArgument);
+        }
+        match TokenInstruction::unpack(instruction_data)? {
+            TokenInstruction::CreateProposal { .. }
+            | TokenInstruction::ApproveProposal
+            | TokenInstruction::ExecuteProposal
+            | TokenInstruction::CancelProposal => return Err(TokenError::InvalidInstruction.into()),
+            _ => {}
+        }
+
+        if program_id != multisig_info.owner {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+        let multisig = Multisig::unpack(&multisig_info.data.borrow())?;
+        if !multisig.signers[..multisig.n as usize].contains(proposer_info.key) {
+            return Err(TokenError::SignerNotFound.into());
+        }
+        if !proposer_info.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        proposal.multisig = *multisig_info.key;
+        proposal.proposer = *proposer_info.key;
+        proposal.expiry = expiry;
+        proposal.state = ProposalState::Pending;
+        proposal.approvals[0] = *proposer_info.key;
+        proposal.num_approvals = 1;
+        proposal.data[..instruction_data.len()].copy_from_slice(instruction_data);
+        proposal.data_len = instruction_data.len() as u16;
+        for (i, target_info) in target_infos.iter().enumerate() {
+            proposal.accounts[i] = *target_info.key;
+        }
+        proposal.num_accounts = target_infos.len() as u8;
+
+        MultisigProposal::pack(proposal, &mut proposal_info.data.borrow_mut())?;
+
+        Ok(())
+    }
+
+    /// Processes an [ApproveProposal](enum.TokenInstruction.html) instruction.
+    pub fn process_approve_proposal(accounts: &[AccountInfo]) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let proposal_info = next_account_info(account_info_iter)?;
+        let multisig_info = next_account_info(account_info_iter)?;
+        let signer_info = next_account_info(account_info_iter)?;
+        let clock = Clock::from_account_info(next_account_info(account_info_iter)?)?;
+
+        let mut proposal = MultisigProposal::unpack(&proposal_info.data.borrow())?;
+        let multisig = Self::pending_proposal_multisig(&proposal, multisig_info, &clock)?;
+        let signers = &multisig.signers[..multisig.n as usize];
+        if !signers.contains(signer_info.key) {
+            return Err(TokenError::SignerNotFound.into());
+        }
+        if !signer_info.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+        if proposal.approvals().contains(signer_info.key) {
+            return Err(TokenError::AlreadyApproved.into());
+        }
+
+        // Approvals of removed signers no longer count, and would otherwise crowd out new ones
+        let mut approvals = proposal
+            .approvals()
+            .iter()
+            .filter(|approval| signers.contains(approval))
+            .copied()
+            .collect::<Vec<_>>();
+        approvals.push(*signer_info.key);
+        proposal.approvals = [Pubkey::default(); MAX_SIGNERS];
+        proposal.approvals[..approvals.len()].copy_from_slice(&approvals);
+        proposal.num_approvals = approvals.len() as u8;
+
+        MultisigProposal::pack(proposal, &mut proposal_info.data.borrow_mut())?;
+
+        Ok(())
+    }
+
+    /// Processes an [ExecuteProposal](enum.TokenInstruction.html) instruction.
+    pub fn process_execute_proposal(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let proposal_info = next_account_info(account_info_iter)?;
+        let multisig_info = next_account_info(account_info_iter)?;
+        let clock = Clock::from_account_info(next_account_info(account_info_iter)?)?;
+        let target_infos = account_info_iter.as_slice();
+
+        let mut proposal = MultisigProposal::unpack(&proposal_info.data.borrow())?;
+        let multisig = Self::pending_proposal_multisig(&proposal, multisig_info, &clock)?;
+        let signers = &multisig.signers[..multisig.n as usize];
+        let num_approvals = proposal
+            .approvals()
+            .iter()
+            .filter(|approval| signers.contains(approval))
+            .count();
+        if num_approvals < multisig.m as usize {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+        if target_infos.len() != proposal.instruction_accounts().len()
+            || target_infos
+                .iter()
+                .zip(proposal.instruction_accounts())
+                .any(|(target_info, key)| target_info.key != key)
+        {
+            return Err(TokenError::ProposalAccountMismatch.into());
+        }
+
+        // The proposal's approvals stand in for the multisignature's signers in its instruction
+        proposal.state = ProposalState::Executing;
+        MultisigProposal::pack(proposal, &mut proposal_info.data.borrow_mut())?;
+        Self::process_instruction(
+            program_id,
+            target_infos,
+            proposal.instruction_data(),
+            Some(&proposal),
+        )?;
+
+        proposal.state = ProposalState::Executed;
+        MultisigProposal::pack(proposal, &mut proposal_info.data.borrow_mut())?;
+
+        Ok(())
+    }
+
+    /// Processes a [CancelProposal](enum.TokenInstruction.html) instruction.
+    pub fn process_cancel_proposal(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let proposal_info = next_account_info(account_info_iter)?;
+        let authority_info = next_account_info(account_info_iter)?;
+
+        let mut proposal = MultisigProposal::unpack(&proposal_info.data.borrow())?;
+        if proposal.state != ProposalState::Pending {
+            return Err(TokenError::ProposalNotPending.into());
+        }
+        if authority_info.key == &proposal.proposer {
+            if !authority_info.is_signer {
+                return Err(ProgramError::MissingRequiredSignature);
+            }
+        } else {
+            Self::validate_owner(
+                program_id,
+                &proposal.multisig,
+                authority_info,
+                account_info_iter.as_slice(),
+            )?;
+        }
+
+        proposal.state = ProposalState::Cancelled;
+
+        MultisigProposal::pack(proposal, &mut proposal_info.data.borrow_mut())?;
+
+        Ok(())
+    }
+
+    /// Checks that a proposal may still be approved or executed, returning its multisignature
+    fn pending_proposal_multisig(
+        proposal: &MultisigProposal,
+        multisig_info: &AccountInfo,
+        clock: &Clock,
+    ) -> Result<Multisig, ProgramError> {
+        if proposal.state != ProposalState::Pending {
+            return Err(TokenError::ProposalNotPending.into());
+        }
+        if clock.unix_timestamp > proposal.expiry {
+            return Err(TokenError::ProposalExpired.into());
+        }
+        if multisig_info.key != &proposal.multisig {
+            return Err(TokenError::ProposalAccountMismatch.into());
+        }
+        Multisig::unpack(&multisig_info.data.borrow())
+    }
+
     /// Processes a [Transfer](enum.TokenInstruction.html) instruction.
     pub fn process_transfer(
         program_id: &Pubkey,
         accounts: &[AccountInfo],
         amount: u64,
         expected_decimals: Option<u8>,
+        proposal: Option<&MultisigProposal>,
     ) -> ProgramResult {
         let account_info_iter = &mut accounts.iter();
 
@@ -180,10 +553,12 @@ impl Processor {
         let mut source_account = Account::unpack(&source_account_info.data.borrow())?;
         let mut dest_account = Account::unpack(&dest_account_info.data.borrow())?;
 
-        if source_account.is_frozen() || dest_account.is_frozen() {
+        Self::thaw_if_expired(&mut source_account, accounts)?;
+        Self::thaw_if_expired(&mut dest_account, accounts)?;
+        if !source_account.can_send() || !dest_account.can_receive() {
             return Err(TokenError::AccountFrozen.into());
         }
-        if source_account.amount < amount {
+        if source_account.available_amount() < amount {
             return Err(TokenError::InsufficientFunds.into());
         }
         if source_account.mint != dest_account.mint {
@@ -204,12 +579,28 @@ impl Processor {
         let self_transfer = source_account_info.key == dest_account_info.key;
 
         match source_account.delegate {
+            _ if Self::is_allowance(program_id, authority_info) => Self::spend_allowance(
+                program_id,
+                source_account_info.key,
+                &source_account.owner,
+                authority_info,
+                account_info_iter,
+                amount,
+                !self_transfer,
+                proposal,
+            )?,
             COption::Some(ref delegate) if authority_info.key == delegate => {
-                Self::validate_owner(
+                Self::validate_owner_with_proposal(
                     program_id,
                     delegate,
                     authority_info,
                     account_info_iter.as_slice(),
+                    proposal,
+                )?;
+                Self::check_delegate_limits(
+                    &mut source_account,
+                    amount,
+                    account_info_iter.as_slice(),
                 )?;
                 if source_account.delegated_amount < amount {
                     return Err(TokenError::InsufficientFunds.into());
@@ -220,16 +611,26 @@ impl Processor {
                         .checked_sub(amount)
                         .ok_or(TokenError::Overflow)?;
                     if source_account.delegated_amount == 0 {
-                        source_account.delegate = COption::None;
+                        Self::clear_delegation(&mut source_account);
                     }
                 }
             }
-            _ => Self::validate_owner(
-                program_id,
-                &source_account.owner,
-                authority_info,
-                account_info_iter.as_slice(),
-            )?,
+            _ => {
+                Self::validate_account_owner(
+                    program_id,
+                    &mut source_account,
+                    authority_info,
+                    account_info_iter.as_slice(),
+                    SessionUse {
+                        scope: SESSION_SCOPE_TRANSFER,
+                        destination: Some(dest_account_info.key),
+                        amount,
+                        debit: !self_transfer,
+                    },
+                    proposal,
+                )?;
+                Self::clear_expired_delegation(&mut source_account, account_info_iter.as_slice())?;
+            }
         };
 
         // This check MUST occur just before the amounts are manipulated
@@ -238,6 +639,69 @@ impl Processor {
             return Ok(());
         }
 
+        Self::complete_transfer(
+            program_id,
+            source_account_info,
+            dest_account_info,
+            source_account,
+            dest_account,
+            amount,
+            accounts,
+        )
+    }
+
+    /// Checks a transfer between two distinct, already authorized token accounts against the
+    /// source's destination allowlist and spending limit, the destination's minimum incoming
+    /// amount and the mint's restrictions, then moves the tokens
+    fn complete_transfer(
+        program_id: &Pubkey,
+        source_account_info: &AccountInfo,
+        dest_account_info: &AccountInfo,
+        mut source_account: Account,
+        dest_account: Account,
+        amount: u64,
+        accounts: &[AccountInfo],
+    ) -> ProgramResult {
+        Self::check_destination_allowed(
+            program_id,
+            &source_account,
+            dest_account_info,
+            &dest_account,
+            accounts,
+        )?;
+        Self::spend_within_limit(&mut source_account, amount, accounts)?;
+        if amount < dest_account.min_incoming_amount {
+            return Err(TokenError::BelowMinimumIncoming.into());
+        }
+        // Only extended mints carry restrictions, and their accounts are extended too
+        if source_account_info.data_len() == Account::EXTENDED_LEN {
+            Self::check_mint_restrictions(
+                program_id,
+                &mut source_account,
+                &dest_account,
+                amount,
+                accounts,
+            )?;
+        }
+
+        Self::move_tokens(
+            source_account_info,
+            dest_account_info,
+            source_account,
+            dest_account,
+            amount,
+        )
+    }
+
+    /// Moves `amount` tokens, and the matching lamports for native accounts, between two distinct
+    /// token accounts that have already been validated
+    fn move_tokens(
+        source_account_info: &AccountInfo,
+        dest_account_info: &AccountInfo,
+        mut source_account: Account,
+        mut dest_account: Account,
+        amount: u64,
+    ) -> ProgramResult {
         source_account.amount = source_account
             .amount
             .checked_sub(amount)
@@ -271,6 +735,9 @@ impl Processor {
         accounts: &[AccountInfo],
         amount: u64,
         expected_decimals: Option<u8>,
+        expiry: UnixTimestamp,
+        max_per_transfer: COption<u64>,
+        proposal: Option<&MultisigProposal>,
     ) -> ProgramResult {
         let account_info_iter = &mut accounts.iter();
 
@@ -286,6 +753,7 @@ impl Processor {
 
         let mut source_account = Account::unpack(&source_account_info.data.borrow())?;
 
+        Self::thaw_if_expired(&mut source_account, accounts)?;
         if source_account.is_frozen() {
             return Err(TokenError::AccountFrozen.into());
         }
@@ -301,75 +769,184 @@ impl Processor {
             }
         }
 
-        Self::validate_owner(
+        Self::validate_owner_with_proposal(
             program_id,
             &source_account.owner,
             owner_info,
             account_info_iter.as_slice(),
+            proposal,
         )?;
 
         source_account.delegate = COption::Some(*delegate_info.key);
         source_account.delegated_amount = amount;
+        source_account.delegate_expiry = expiry;
+        source_account.delegate_transfer_cap = max_per_transfer;
 
         Account::pack(source_account, &mut source_account_info.data.borrow_mut())?;
 
         Ok(())
     }
 
-    /// Processes an [Revoke](enum.TokenInstruction.html) instruction.
-    pub fn process_revoke(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    /// Processes an [ApproveIfCurrent](enum.TokenInstruction.html) instruction.
+    pub fn process_approve_if_current(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        expected_delegate: COption<Pubkey>,
+        expected_amount: u64,
+        amount: u64,
+        proposal: Option<&MultisigProposal>,
+    ) -> ProgramResult {
         let account_info_iter = &mut accounts.iter();
         let source_account_info = next_account_info(account_info_iter)?;
+        let delegate_info = next_account_info(account_info_iter)?;
+        let owner_info = next_account_info(account_info_iter)?;
 
         let mut source_account = Account::unpack(&source_account_info.data.borrow())?;
 
-        let owner_info = next_account_info(account_info_iter)?;
-
+        Self::thaw_if_expired(&mut source_account, accounts)?;
         if source_account.is_frozen() {
             return Err(TokenError::AccountFrozen.into());
         }
 
-        Self::validate_owner(
+        Self::validate_owner_with_proposal(
             program_id,
             &source_account.owner,
             owner_info,
             account_info_iter.as_slice(),
+            proposal,
         )?;
 
-        source_account.delegate = COption::None;
-        source_account.delegated_amount = 0;
+        if source_account.delegate != expected_delegate
+            || source_account.delegated_amount != expected_amount
+        {
+            return Err(TokenError::UnexpectedDelegation.into());
+        }
+
+        Self::clear_delegation(&mut source_account);
+        source_account.delegate = COption::Some(*delegate_info.key);
+        source_account.delegated_amount = amount;
 
         Account::pack(source_account, &mut source_account_info.data.borrow_mut())?;
 
         Ok(())
     }
 
-    /// Processes a [SetAuthority](enum.TokenInstruction.html) instruction.
-    pub fn process_set_authority(
+    /// Processes an [IncreaseAllowance](enum.TokenInstruction.html) or
+    /// [DecreaseAllowance](enum.TokenInstruction.html) instruction.
+    pub fn process_change_allowance(
         program_id: &Pubkey,
         accounts: &[AccountInfo],
-        authority_type: AuthorityType,
-        new_authority: COption<Pubkey>,
+        amount: u64,
+        increase: bool,
+        proposal: Option<&MultisigProposal>,
     ) -> ProgramResult {
         let account_info_iter = &mut accounts.iter();
-        let account_info = next_account_info(account_info_iter)?;
-        let authority_info = next_account_info(account_info_iter)?;
+        let source_account_info = next_account_info(account_info_iter)?;
+        let delegate_info = next_account_info(account_info_iter)?;
+        let owner_info = next_account_info(account_info_iter)?;
 
-        if account_info.data_len() == Account::get_packed_len() {
-            let mut account = Account::unpack(&account_info.data.borrow())?;
+        let mut source_account = Account::unpack(&source_account_info.data.borrow())?;
 
-            if account.is_frozen() {
-                return Err(TokenError::AccountFrozen.into());
+        Self::thaw_if_expired(&mut source_account, accounts)?;
+        if source_account.is_frozen() {
+            return Err(TokenError::AccountFrozen.into());
+        }
+
+        Self::validate_owner_with_proposal(
+            program_id,
+            &source_account.owner,
+            owner_info,
+            account_info_iter.as_slice(),
+            proposal,
+        )?;
+
+        match source_account.delegate {
+            COption::Some(delegate) if delegate == *delegate_info.key => {}
+            COption::None if increase => {
+                Self::clear_delegation(&mut source_account);
+                source_account.delegate = COption::Some(*delegate_info.key);
             }
+            _ => return Err(TokenError::UnexpectedDelegation.into()),
+        }
 
-            match authority_type {
-                AuthorityType::AccountOwner => {
-                    Self::validate_owner(
-                        program_id,
-                        &account.owner,
-                        authority_info,
-                        account_info_iter.as_slice(),
-                    )?;
+        source_account.delegated_amount = if increase {
+            source_account.delegated_amount.checked_add(amount)
+        } else {
+            source_account.delegated_amount.checked_sub(amount)
+        }
+        .ok_or(TokenError::Overflow)?;
+
+        if source_account.delegated_amount == 0 {
+            Self::clear_delegation(&mut source_account);
+        }
+
+        Account::pack(source_account, &mut source_account_info.data.borrow_mut())?;
+
+        Ok(())
+    }
+
+    /// Processes an [Revoke](enum.TokenInstruction.html) instruction.
+    pub fn process_revoke(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        proposal: Option<&MultisigProposal>,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let source_account_info = next_account_info(account_info_iter)?;
+
+        let mut source_account = Account::unpack(&source_account_info.data.borrow())?;
+
+        let owner_info = next_account_info(account_info_iter)?;
+
+        Self::thaw_if_expired(&mut source_account, accounts)?;
+        if source_account.is_frozen() {
+            return Err(TokenError::AccountFrozen.into());
+        }
+
+        Self::validate_owner_with_proposal(
+            program_id,
+            &source_account.owner,
+            owner_info,
+            account_info_iter.as_slice(),
+            proposal,
+        )?;
+
+        Self::clear_delegation(&mut source_account);
+
+        Account::pack(source_account, &mut source_account_info.data.borrow_mut())?;
+
+        Ok(())
+    }
+
+    /// Processes a [SetAuthority](enum.TokenInstruction.html) instruction.
+    pub fn process_set_authority(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        authority_type: AuthorityType,
+        new_authority: COption<Pubkey>,
+        proposal: Option<&MultisigProposal>,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let account_info = next_account_info(account_info_iter)?;
+        let authority_info = next_account_info(account_info_iter)?;
+
+        if Account::is_packed_len(account_info.data_len()) {
+            let mut account = Account::unpack(&account_info.data.borrow())?;
+
+            Self::thaw_if_expired(&mut account, accounts)?;
+            if account.is_frozen() {
+                return Err(TokenError::AccountFrozen.into());
+            }
+
+            match authority_type {
+                AuthorityType::AccountOwner => {
+                    Self::validate_owner_with_proposal(
+                        program_id,
+                        &account.owner,
+                        authority_info,
+                        account_info_iter.as_slice(),
+                        proposal,
+                    )?;
 
                     if let COption::Some(authority) = new_authority {
                         account.owner = authority;
@@ -377,26 +954,35 @@ impl Processor {
                         return Err(TokenError::InvalidInstruction.into());
                     }
 
-                    account.delegate = COption::None;
-                    account.delegated_amount = 0;
+                    Self::clear_delegation(&mut account);
+                    account.pending_owner = COption::None;
+                    account.pending_close_authority = COption::None;
+                    account.guardians = COption::None;
+                    Self::clear_recovery(&mut account);
+                    Self::clear_session(&mut account);
                 }
                 AuthorityType::CloseAccount => {
                     let authority = account.close_authority.unwrap_or(account.owner);
-                    Self::validate_owner(
+                    Self::validate_owner_with_proposal(
                         program_id,
                         &authority,
                         authority_info,
                         account_info_iter.as_slice(),
+                        proposal,
                     )?;
                     account.close_authority = new_authority;
+                    account.pending_close_authority = COption::None;
                 }
                 _ => {
                     return Err(TokenError::AuthorityTypeNotSupported.into());
                 }
             }
             Account::pack(account, &mut account_info.data.borrow_mut())?;
-        } else if account_info.data_len() == Mint::get_packed_len() {
+        } else if Mint::is_packed_len(account_info.data_len()) {
             let mut mint = Mint::unpack(&account_info.data.borrow())?;
+            if mint.authority_timelock > 0 {
+                return Err(TokenError::AuthorityChangeTimelocked.into());
+            }
             match authority_type {
                 AuthorityType::MintTokens => {
                     // Once a mint's supply is fixed, it cannot be undone by setting a new
@@ -404,13 +990,15 @@ impl Processor {
                     let mint_authority = mint
                         .mint_authority
                         .ok_or(Into::<ProgramError>::into(TokenError::FixedSupply))?;
-                    Self::validate_owner(
+                    Self::validate_owner_with_proposal(
                         program_id,
                         &mint_authority,
                         authority_info,
                         account_info_iter.as_slice(),
+                        proposal,
                     )?;
                     mint.mint_authority = new_authority;
+                    mint.pending_mint_authority = COption::None;
                 }
                 AuthorityType::FreezeAccount => {
                     // Once a mint's freeze authority is disabled, it cannot be re-enabled by
@@ -418,13 +1006,222 @@ impl Processor {
                     let freeze_authority = mint
                         .freeze_authority
                         .ok_or(Into::<ProgramError>::into(TokenError::MintCannotFreeze))?;
-                    Self::validate_owner(
+                    Self::validate_owner_with_proposal(
                         program_id,
                         &freeze_authority,
                         authority_info,
                         account_info_iter.as_slice(),
+                        proposal,
                     )?;
                     mint.freeze_authority = new_authority;
+                    mint.pending_freeze_authority = COption::None;
+                }
+                _ => {
+                    return Err(TokenError::AuthorityTypeNotSupported.into());
+                }
+            }
+            Mint::pack(mint, &mut account_info.data.borrow_mut())?;
+        } else {
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        Ok(())
+    }
+
+    /// Processes a [ProposeAuthority](enum.TokenInstruction.html) or a
+    /// [CancelAuthorityProposal](enum.TokenInstruction.html) instruction.
+    pub fn process_propose_authority(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        authority_type: AuthorityType,
+        new_authority: COption<Pubkey>,
+        proposal: Option<&MultisigProposal>,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let account_info = next_account_info(account_info_iter)?;
+        let authority_info = next_account_info(account_info_iter)?;
+
+        if Account::is_packed_len(account_info.data_len()) {
+            let mut account = Account::unpack(&account_info.data.borrow())?;
+
+            Self::thaw_if_expired(&mut account, accounts)?;
+            if account.is_frozen() {
+                return Err(TokenError::AccountFrozen.into());
+            }
+
+            match authority_type {
+                AuthorityType::AccountOwner => {
+                    Self::validate_owner_with_proposal(
+                        program_id,
+                        &account.owner,
+                        authority_info,
+                        account_info_iter.as_slice(),
+                        proposal,
+                    )?;
+                    Self::update_pending_authority(&mut account.pending_owner, new_authority)?;
+                }
+                AuthorityType::CloseAccount => {
+                    let authority = account.close_authority.unwrap_or(account.owner);
+                    Self::validate_owner_with_proposal(
+                        program_id,
+                        &authority,
+                        authority_info,
+                        account_info_iter.as_slice(),
+                        proposal,
+                    )?;
+                    Self::update_pending_authority(
+                        &mut account.pending_close_authority,
+                        new_authority,
+                    )?;
+                }
+                _ => {
+                    return Err(TokenError::AuthorityTypeNotSupported.into());
+                }
+            }
+            Account::pack(account, &mut account_info.data.borrow_mut())?;
+        } else if Mint::is_packed_len(account_info.data_len()) {
+            let mut mint = Mint::unpack(&account_info.data.borrow())?;
+            match authority_type {
+                AuthorityType::MintTokens => {
+                    let mint_authority = mint
+                        .mint_authority
+                        .ok_or(Into::<ProgramError>::into(TokenError::FixedSupply))?;
+                    Self::validate_owner_with_proposal(
+                        program_id,
+                        &mint_authority,
+                        authority_info,
+                        account_info_iter.as_slice(),
+                        proposal,
+                    )?;
+                    Self::update_pending_authority(
+                        &mut mint.pending_mint_authority,
+                        new_authority,
+                    )?;
+                }
+                AuthorityType::FreezeAccount => {
+                    let freeze_authority = mint
+                        .freeze_authority
+                        .ok_or(Into::<ProgramError>::into(TokenError::MintCannotFreeze))?;
+                    Self::validate_owner_with_proposal(
+                        program_id,
+                        &freeze_authority,
+                        authority_info,
+                        account_info_iter.as_slice(),
+                        proposal,
+                    )?;
+                    Self::update_pending_authority(
+                        &mut mint.pending_freeze_authority,
+                        new_authority,
+                    )?;
+                }
+                _ => {
+                    return Err(TokenError::AuthorityTypeNotSupported.into());
+                }
+            }
+            Mint::pack(mint, &mut account_info.data.borrow_mut())?;
+        } else {
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        Ok(())
+    }
+
+    /// Processes an [AcceptAuthority](enum.TokenInstruction.html) instruction.
+    pub fn process_accept_authority(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        authority_type: AuthorityType,
+        proposal: Option<&MultisigProposal>,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let account_info = next_account_info(account_info_iter)?;
+        let authority_info = next_account_info(account_info_iter)?;
+
+        if Account::is_packed_len(account_info.data_len()) {
+            let mut account = Account::unpack(&account_info.data.borrow())?;
+
+            Self::thaw_if_expired(&mut account, accounts)?;
+            if account.is_frozen() {
+                return Err(TokenError::AccountFrozen.into());
+            }
+
+            match authority_type {
+                AuthorityType::AccountOwner => {
+                    let pending_owner = account
+                        .pending_owner
+                        .ok_or(Into::<ProgramError>::into(TokenError::NoPendingAuthority))?;
+                    Self::validate_owner_with_proposal(
+                        program_id,
+                        &pending_owner,
+                        authority_info,
+                        account_info_iter.as_slice(),
+                        proposal,
+                    )?;
+
+                    account.owner = pending_owner;
+                    Self::clear_delegation(&mut account);
+                    account.pending_owner = COption::None;
+                    account.pending_close_authority = COption::None;
+                    account.guardians = COption::None;
+                    Self::clear_recovery(&mut account);
+                    Self::clear_session(&mut account);
+                }
+                AuthorityType::CloseAccount => {
+                    let pending_close_authority =
+                        account
+                            .pending_close_authority
+                            .ok_or(Into::<ProgramError>::into(TokenError::NoPendingAuthority))?;
+                    Self::validate_owner_with_proposal(
+                        program_id,
+                        &pending_close_authority,
+                        authority_info,
+                        account_info_iter.as_slice(),
+                        proposal,
+                    )?;
+
+                    account.close_authority = COption::Some(pending_close_authority);
+                    account.pending_close_authority = COption::None;
+                }
+                _ => {
+                    return Err(TokenError::AuthorityTypeNotSupported.into());
+                }
+            }
+            Account::pack(account, &mut account_info.data.borrow_mut())?;
+        } else if Mint::is_packed_len(account_info.data_len()) {
+            let mut mint = Mint::unpack(&account_info.data.borrow())?;
+            if mint.authority_timelock > 0 {
+                return Err(TokenError::AuthorityChangeTimelocked.into());
+            }
+            match authority_type {
+                AuthorityType::MintTokens => {
+                    let pending_mint_authority =
+                        mint.pending_mint_authority
+                            .ok_or(Into::<ProgramError>::into(TokenError::NoPendingAuthority))?;
+                    Self::validate_owner_with_proposal(
+                        program_id,
+                        &pending_mint_authority,
+                        authority_info,
+                        account_info_iter.as_slice(),
+                        proposal,
+                    )?;
+
+                    mint.mint_authority = COption::Some(pending_mint_authority);
+                    mint.pending_mint_authority = COption::None;
+                }
+                AuthorityType::FreezeAccount => {
+                    let pending_freeze_authority =
+                        mint.pending_freeze_authority
+                            .ok_or(Into::<ProgramError>::into(TokenError::NoPendingAuthority))?;
+                    Self::validate_owner_with_proposal(
+                        program_id,
+                        &pending_freeze_authority,
+                        authority_info,
+                        account_info_iter.as_slice(),
+                        proposal,
+                    )?;
+
+                    mint.freeze_authority = COption::Some(pending_freeze_authority);
+                    mint.pending_freeze_authority = COption::None;
                 }
                 _ => {
                     return Err(TokenError::AuthorityTypeNotSupported.into());
@@ -444,6 +1241,7 @@ impl Processor {
         accounts: &[AccountInfo],
         amount: u64,
         expected_decimals: Option<u8>,
+        proposal: Option<&MultisigProposal>,
     ) -> ProgramResult {
         let account_info_iter = &mut accounts.iter();
         let mint_info = next_account_info(account_info_iter)?;
@@ -451,7 +1249,8 @@ impl Processor {
         let owner_info = next_account_info(account_info_iter)?;
 
         let mut dest_account = Account::unpack(&dest_account_info.data.borrow())?;
-        if dest_account.is_frozen() {
+        Self::thaw_if_expired(&mut dest_account, accounts)?;
+        if !dest_account.can_receive() {
             return Err(TokenError::AccountFrozen.into());
         }
 
@@ -469,14 +1268,48 @@ impl Processor {
             }
         }
 
-        match mint.mint_authority {
-            COption::Some(mint_authority) => Self::validate_owner(
+        let mint_authority = mint
+            .mint_authority
+            .ok_or(Into::<ProgramError>::into(TokenError::FixedSupply))?;
+        if mint.minting_paused {
+            return Err(TokenError::MintingPaused.into());
+        }
+        Self::enforce_max_balance(&mint, &dest_account, amount)?;
+        Self::enforce_credentials(
+            program_id,
+            mint_info.key,
+            &mint,
+            &[&dest_account.owner],
+            accounts,
+        )?;
+
+        if owner_info.key != &mint_authority && Self::is_minter(program_id, owner_info) {
+            let mut minter = Minter::unpack(&owner_info.data.borrow())?;
+            if mint_info.key != &minter.mint {
+                return Err(TokenError::MintMismatch.into());
+            }
+            let minter_authority_info = next_account_info(account_info_iter)?;
+            Self::validate_owner_with_proposal(
+                program_id,
+                &minter.authority,
+                minter_authority_info,
+                account_info_iter.as_slice(),
+                proposal,
+            )?;
+
+            minter.allowance = minter
+                .allowance
+                .checked_sub(amount)
+                .ok_or(TokenError::MinterAllowanceExceeded)?;
+            Minter::pack(minter, &mut owner_info.data.borrow_mut())?;
+        } else {
+            Self::validate_owner_with_proposal(
                 program_id,
                 &mint_authority,
                 owner_info,
                 account_info_iter.as_slice(),
-            )?,
-            COption::None => return Err(TokenError::FixedSupply.into()),
+                proposal,
+            )?;
         }
 
         dest_account.amount = dest_account
@@ -501,6 +1334,7 @@ impl Processor {
         accounts: &[AccountInfo],
         amount: u64,
         expected_decimals: Option<u8>,
+        proposal: Option<&MultisigProposal>,
     ) -> ProgramResult {
         let account_info_iter = &mut accounts.iter();
 
@@ -511,13 +1345,14 @@ impl Processor {
         let mut source_account = Account::unpack(&source_account_info.data.borrow())?;
         let mut mint = Mint::unpack(&mint_info.data.borrow())?;
 
-        if source_account.is_frozen() {
+        Self::thaw_if_expired(&mut source_account, accounts)?;
+        if !source_account.can_send() {
             return Err(TokenError::AccountFrozen.into());
         }
         if source_account.is_native() {
             return Err(TokenError::NativeNotSupported.into());
         }
-        if source_account.amount < amount {
+        if source_account.available_amount() < amount {
             return Err(TokenError::InsufficientFunds.into());
         }
         if mint_info.key != &source_account.mint {
@@ -531,12 +1366,41 @@ impl Processor {
         }
 
         match source_account.delegate {
-            COption::Some(ref delegate) if authority_info.key == delegate => {
-                Self::validate_owner(
+            _ if Self::is_allowance(program_id, authority_info) => {
+                Self::spend_allowance(
                     program_id,
-                    delegate,
+                    source_account_info.key,
+                    &source_account.owner,
+                    authority_info,
+                    account_info_iter,
+                    amount,
+                    true,
+                    proposal,
+                )?;
+            }
+            _ if mint.burner == COption::Some(*authority_info.key)
+                && mint.mint_authority == COption::Some(source_account.owner) =>
+            {
+                Self::validate_owner_with_proposal(
+                    program_id,
+                    authority_info.key,
+                    authority_info,
+                    account_info_iter.as_slice(),
+                    proposal,
+                )?;
+            }
+            COption::Some(delegate) if authority_info.key == &delegate => {
+                Self::validate_owner_with_proposal(
+                    program_id,
+                    &delegate,
                     authority_info,
                     account_info_iter.as_slice(),
+                    proposal,
+                )?;
+                Self::check_delegate_limits(
+                    &mut source_account,
+                    amount,
+                    account_info_iter.as_slice(),
                 )?;
 
                 if source_account.delegated_amount < amount {
@@ -547,17 +1411,29 @@ impl Processor {
                     .checked_sub(amount)
                     .ok_or(TokenError::Overflow)?;
                 if source_account.delegated_amount == 0 {
-                    source_account.delegate = COption::None;
+                    Self::clear_delegation(&mut source_account);
                 }
             }
-            _ => Self::validate_owner(
-                program_id,
-                &source_account.owner,
-                authority_info,
-                account_info_iter.as_slice(),
-            )?,
+            _ => {
+                Self::validate_account_owner(
+                    program_id,
+                    &mut source_account,
+                    authority_info,
+                    account_info_iter.as_slice(),
+                    SessionUse {
+                        scope: SESSION_SCOPE_BURN,
+                        destination: None,
+                        amount,
+                        debit: true,
+                    },
+                    proposal,
+                )?;
+                Self::clear_expired_delegation(&mut source_account, account_info_iter.as_slice())?;
+            }
         }
 
+        Self::spend_within_limit(&mut source_account, amount, accounts)?;
+
         source_account.amount = source_account
             .amount
             .checked_sub(amount)
@@ -574,7 +1450,11 @@ impl Processor {
     }
 
     /// Processes a [CloseAccount](enum.TokenInstruction.html) instruction.
-    pub fn process_close_account(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    pub fn process_close_account(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        proposal: Option<&MultisigProposal>,
+    ) -> ProgramResult {
         let account_info_iter = &mut accounts.iter();
         let source_account_info = next_account_info(account_info_iter)?;
         let dest_account_info = next_account_info(account_info_iter)?;
@@ -584,15 +1464,19 @@ impl Processor {
         if !source_account.is_native() && source_account.amount != 0 {
             return Err(TokenError::NonNativeHasBalance.into());
         }
+        if source_account.has_hold() {
+            return Err(TokenError::HoldExists.into());
+        }
 
         let authority = source_account
             .close_authority
             .unwrap_or(source_account.owner);
-        Self::validate_owner(
+        Self::validate_owner_with_proposal(
             program_id,
             &authority,
             authority_info,
             account_info_iter.as_slice(),
+            proposal,
         )?;
 
         let dest_starting_lamports = dest_account_info.lamports();
@@ -608,12 +1492,18 @@ impl Processor {
         Ok(())
     }
 
-    /// Processes a [FreezeAccount](enum.TokenInstruction.html) or a
-    /// [ThawAccount](enum.TokenInstruction.html) instruction.
-    pub fn process_toggle_freeze_account(
+    /// Processes a [FreezeAccount](enum.TokenInstruction.html),
+    /// [ThawAccount](enum.TokenInstruction.html),
+    /// [SetReceiveOnly](enum.TokenInstruction.html),
+    /// [SetSendOnly](enum.TokenInstruction.html) or
+    /// [FreezeAccountWithReason](enum.TokenInstruction.html) instruction.
+    pub fn process_set_account_state(
         program_id: &Pubkey,
         accounts: &[AccountInfo],
-        freeze: bool,
+        state: AccountState,
+        freeze_reason: u8,
+        auto_thaw_timestamp: UnixTimestamp,
+        proposal: Option<&MultisigProposal>,
     ) -> ProgramResult {
         let account_info_iter = &mut accounts.iter();
         let source_account_info = next_account_info(account_info_iter)?;
@@ -621,7 +1511,8 @@ impl Processor {
         let authority_info = next_account_info(account_info_iter)?;
 
         let mut source_account = Account::unpack(&source_account_info.data.borrow())?;
-        if freeze && source_account.is_frozen() || !freeze && !source_account.is_frozen() {
+        Self::thaw_if_expired(&mut source_account, accounts)?;
+        if source_account.state == state {
             return Err(TokenError::InvalidState.into());
         }
         if source_account.is_native() {
@@ -632,5156 +1523,17752 @@ impl Processor {
         }
 
         let mint = Mint::unpack(&mint_info.data.borrow_mut())?;
-        match mint.freeze_authority {
-            COption::Some(authority) => Self::validate_owner(
-                program_id,
-                &authority,
-                authority_info,
-                account_info_iter.as_slice(),
-            ),
-            COption::None => Err(TokenError::MintCannotFreeze.into()),
-        }?;
+        Self::validate_freeze_authority(
+            program_id,
+            &mint,
+            authority_info,
+            account_info_iter.as_slice(),
+            proposal,
+        )?;
 
-        source_account.state = if freeze {
-            AccountState::Frozen
-        } else {
-            AccountState::Initialized
-        };
+        source_account.state = state;
+        source_account.freeze_reason = freeze_reason;
+        source_account.auto_thaw_timestamp = auto_thaw_timestamp;
 
         Account::pack(source_account, &mut source_account_info.data.borrow_mut())?;
 
         Ok(())
     }
 
-    /// Processes an [Instruction](enum.Instruction.html).
-    pub fn process(program_id: &Pubkey, accounts: &[AccountInfo], input: &[u8]) -> ProgramResult {
-        let instruction = TokenInstruction::unpack(input)?;
+    /// Processes a [FreezeAccounts](enum.TokenInstruction.html) or a
+    /// [ThawAccounts](enum.TokenInstruction.html) instruction.
+    pub fn process_set_accounts_state(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        state: AccountState,
+        count: u8,
+        proposal: Option<&MultisigProposal>,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let mint_info = next_account_info(account_info_iter)?;
+        let authority_info = next_account_info(account_info_iter)?;
 
-        match instruction {
-            TokenInstruction::InitializeMint {
-                decimals,
-                mint_authority,
-                freeze_authority,
-            } => {
-                msg!("Instruction: InitializeMint");
-                Self::process_initialize_mint(accounts, decimals, mint_authority, freeze_authority)
-            }
-            TokenInstruction::InitializeAccount => {
-                msg!("Instruction: InitializeAccount");
-                Self::process_initialize_account(accounts)
-            }
-            TokenInstruction::InitializeAccount2 { owner } => {
-                msg!("Instruction: InitializeAccount2");
-                Self::process_initialize_account2(accounts, owner)
-            }
-            TokenInstruction::InitializeMultisig { m } => {
-                msg!("Instruction: InitializeMultisig");
-                Self::process_initialize_multisig(accounts, m)
-            }
-            TokenInstruction::Transfer { amount } => {
-                msg!("Instruction: Transfer");
-                Self::process_transfer(program_id, accounts, amount, None)
-            }
-            TokenInstruction::Approve { amount } => {
-                msg!("Instruction: Approve");
-                Self::process_approve(program_id, accounts, amount, None)
-            }
-            TokenInstruction::Revoke => {
-                msg!("Instruction: Revoke");
-                Self::process_revoke(program_id, accounts)
-            }
-            TokenInstruction::SetAuthority {
-                authority_type,
-                new_authority,
-            } => {
-                msg!("Instruction: SetAuthority");
-                Self::process_set_authority(program_id, accounts, authority_type, new_authority)
-            }
-            TokenInstruction::MintTo { amount } => {
-                msg!("Instruction: MintTo");
-                Self::process_mint_to(program_id, accounts, amount, None)
-            }
-            TokenInstruction::Burn { amount } => {
-                msg!("Instruction: Burn");
-                Self::process_burn(program_id, accounts, amount, None)
-            }
-            TokenInstruction::CloseAccount => {
-                msg!("Instruction: CloseAccount");
-                Self::process_close_account(program_id, accounts)
-            }
-            TokenInstruction::FreezeAccount => {
-                msg!("Instruction: FreezeAccount");
-                Self::process_toggle_freeze_account(program_id, accounts, true)
-            }
-            TokenInstruction::ThawAccount => {
-                msg!("Instruction: ThawAccount");
-                Self::process_toggle_freeze_account(program_id, accounts, false)
-            }
-            TokenInstruction::TransferChecked { amount, decimals } => {
-                msg!("Instruction: TransferChecked");
-                Self::process_transfer(program_id, accounts, amount, Some(decimals))
-            }
-            TokenInstruction::ApproveChecked { amount, decimals } => {
-                msg!("Instruction: ApproveChecked");
-                Self::process_approve(program_id, accounts, amount, Some(decimals))
+        let remaining = account_info_iter.as_slice();
+        if remaining.len() < count as usize {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        }
+        let (token_account_infos, signers) = remaining.split_at(count as usize);
+
+        let mint = Mint::unpack(&mint_info.data.borrow())?;
+        Self::validate_freeze_authority(program_id, &mint, authority_info, signers, proposal)?;
+
+        let mut unchanged = Vec::new();
+        for (index, token_account_info) in token_account_infos.iter().enumerate() {
+            let mut token_account = Account::unpack(&token_account_info.data.borrow())?;
+            Self::thaw_if_expired(&mut token_account, accounts)?;
+            if token_account.is_native() {
+                return Err(TokenError::NativeNotSupported.into());
             }
-            TokenInstruction::MintToChecked { amount, decimals } => {
-                msg!("Instruction: MintToChecked");
-                Self::process_mint_to(program_id, accounts, amount, Some(decimals))
+            if mint_info.key != &token_account.mint {
+                return Err(TokenError::MintMismatch.into());
             }
-            TokenInstruction::BurnChecked { amount, decimals } => {
-                msg!("Instruction: BurnChecked");
-                Self::process_burn(program_id, accounts, amount, Some(decimals))
+            if token_account.state == state {
+                unchanged.push(index as u8);
+                continue;
             }
+
+            token_account.state = state;
+            token_account.freeze_reason = 0;
+            token_account.auto_thaw_timestamp = 0;
+
+            Account::pack(token_account, &mut token_account_info.data.borrow_mut())?;
         }
+        set_return_data(&unchanged);
+
+        Ok(())
     }
 
-    /// Validates owner(s) are present
-    pub fn validate_owner(
+    /// Processes an [InitializeMinter](enum.TokenInstruction.html) instruction.
+    pub fn process_initialize_minter(
         program_id: &Pubkey,
-        expected_owner: &Pubkey,
-        owner_account_info: &AccountInfo,
-        signers: &[AccountInfo],
+        accounts: &[AccountInfo],
+        authority: Pubkey,
+        allowance: u64,
+        proposal: Option<&MultisigProposal>,
     ) -> ProgramResult {
-        if expected_owner != owner_account_info.key {
-            return Err(TokenError::OwnerMismatch.into());
+        let account_info_iter = &mut accounts.iter();
+        let minter_info = next_account_info(account_info_iter)?;
+        let minter_info_data_len = minter_info.data_len();
+        let mint_info = next_account_info(account_info_iter)?;
+        let rent = &Rent::from_account_info(next_account_info(account_info_iter)?)?;
+        let owner_info = next_account_info(account_info_iter)?;
+
+        let mut minter = Minter::unpack_unchecked(&minter_info.data.borrow())?;
+        if minter.is_initialized {
+            return Err(TokenError::AlreadyInUse.into());
         }
-        if program_id == owner_account_info.owner
-            && owner_account_info.data_len() == Multisig::get_packed_len()
-        {
-            let multisig = Multisig::unpack(&owner_account_info.data.borrow())?;
-            let mut num_signers = 0;
-            let mut matched = [false; MAX_SIGNERS];
-            for signer in signers.iter() {
-                for (position, key) in multisig.signers[0..multisig.n as usize].iter().enumerate() {
-                    if key == signer.key && !matched[position] {
-                        if !signer.is_signer {
-                            return Err(ProgramError::MissingRequiredSignature);
-                        }
-                        matched[position] = true;
-                        num_signers += 1;
-                    }
-                }
-            }
-            if num_signers < multisig.m {
-                return Err(ProgramError::MissingRequiredSignature);
-            }
-            return Ok(());
-        } else if !owner_account_info.is_signer {
-            return Err(ProgramError::MissingRequiredSignature);
+
+        if !rent.is_exempt(minter_info.lamports(), minter_info_data_len) {
+            return Err(TokenError::NotRentExempt.into());
         }
-        Ok(())
-    }
-}
 
-impl PrintProgramError for TokenError {
-    fn print<E>(&self)
-    where
-        E: 'static + std::error::Error + DecodeError<E> + PrintProgramError + FromPrimitive,
-    {
-        match self {
-            TokenError::NotRentExempt => msg!("Error: Lamport balance below rent-exempt threshold"),
-            TokenError::InsufficientFunds => msg!("Error: insufficient funds"),
-            TokenError::InvalidMint => msg!("Error: Invalid Mint"),
-            TokenError::MintMismatch => msg!("Error: Account not associated with this Mint"),
-            TokenError::OwnerMismatch => msg!("Error: owner does not match"),
-            TokenError::FixedSupply => msg!("Error: the total supply of this token is fixed"),
-            TokenError::AlreadyInUse => msg!("Error: account or token already in use"),
-            TokenError::InvalidNumberOfProvidedSigners => {
-                msg!("Error: Invalid number of provided signers")
-            }
-            TokenError::InvalidNumberOfRequiredSigners => {
-                msg!("Error: Invalid number of required signers")
-            }
-            TokenError::UninitializedState => msg!("Error: State is uninitialized"),
-            TokenError::NativeNotSupported => {
-                msg!("Error: Instruction does not support native tokens")
-            }
-            TokenError::NonNativeHasBalance => {
-                msg!("Error: Non-native account can only be closed if its balance is zero")
-            }
-            TokenError::InvalidInstruction => msg!("Error: Invalid instruction"),
-            TokenError::InvalidState => msg!("Error: Invalid account state for operation"),
-            TokenError::Overflow => msg!("Error: Operation overflowed"),
-            TokenError::AuthorityTypeNotSupported => {
-                msg!("Error: Account does not support specified authority type")
-            }
-            TokenError::MintCannotFreeze => msg!("Error: This token mint cannot freeze accounts"),
-            TokenError::AccountFrozen => msg!("Error: Account is frozen"),
-            TokenError::MintDecimalsMismatch => {
-                msg!("Error: decimals different from the Mint decimals")
-            }
+        let mint = Mint::unpack(&mint_info.data.borrow())?;
+        match mint.mint_authority {
+            COption::Some(mint_authority) => Self::validate_owner_with_proposal(
+                program_id,
+                &mint_authority,
+                owner_info,
+                account_info_iter.as_slice(),
+                proposal,
+            )?,
+            COption::None => return Err(TokenError::FixedSupply.into()),
         }
-    }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use crate::instruction::*;
-    use solana_program::{
-        account_info::IntoAccountInfo, clock::Epoch, instruction::Instruction, sysvar::rent,
-    };
-    use solana_sdk::account::{
-        create_account_for_test, create_is_signer_account_infos, Account as SolanaAccount,
-    };
+        minter.mint = *mint_info.key;
+        minter.authority = authority;
+        minter.allowance = allowance;
+        minter.is_initialized = true;
 
-    fn do_process_instruction(
-        instruction: Instruction,
-        accounts: Vec<&mut SolanaAccount>,
-    ) -> ProgramResult {
-        let mut meta = instruction
-            .accounts
-            .iter()
-            .zip(accounts)
-            .map(|(account_meta, account)| (&account_meta.pubkey, account_meta.is_signer, account))
-            .collect::<Vec<_>>();
+        Minter::pack(minter, &mut minter_info.data.borrow_mut())?;
 
-        let account_infos = create_is_signer_account_infos(&mut meta);
-        Processor::process(&instruction.program_id, &account_infos, &instruction.data)
+        Ok(())
     }
 
-    fn do_process_instruction_dups(
-        instruction: Instruction,
-        account_infos: Vec<AccountInfo>,
+    /// Processes a [SetMinterAllowance](enum.TokenInstruction.html) instruction.
+    pub fn process_set_minter_allowance(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        allowance: u64,
+        proposal: Option<&MultisigProposal>,
     ) -> ProgramResult {
-        Processor::process(&instruction.program_id, &account_infos, &instruction.data)
-    }
+        let account_info_iter = &mut accounts.iter();
+        let minter_info = next_account_info(account_info_iter)?;
+        let mint_info = next_account_info(account_info_iter)?;
+        let owner_info = next_account_info(account_info_iter)?;
 
-    fn return_token_error_as_program_error() -> ProgramError {
-        TokenError::MintMismatch.into()
-    }
+        let mut minter = Minter::unpack(&minter_info.data.borrow())?;
+        if mint_info.key != &minter.mint {
+            return Err(TokenError::MintMismatch.into());
+        }
 
-    fn rent_sysvar() -> SolanaAccount {
-        create_account_for_test(&Rent::default())
-    }
+        let mint = Mint::unpack(&mint_info.data.borrow())?;
+        match mint.mint_authority {
+            COption::Some(mint_authority) => Self::validate_owner_with_proposal(
+                program_id,
+                &mint_authority,
+                owner_info,
+                account_info_iter.as_slice(),
+                proposal,
+            )?,
+            COption::None => return Err(TokenError::FixedSupply.into()),
+        }
 
-    fn mint_minimum_balance() -> u64 {
-        Rent::default().minimum_balance(Mint::get_packed_len())
-    }
+        minter.allowance = allowance;
 
-    fn account_minimum_balance() -> u64 {
-        Rent::default().minimum_balance(Account::get_packed_len())
-    }
+        Minter::pack(minter, &mut minter_info.data.borrow_mut())?;
 
-    fn multisig_minimum_balance() -> u64 {
-        Rent::default().minimum_balance(Multisig::get_packed_len())
+        Ok(())
     }
 
-    #[test]
-    fn test_print_error() {
-        let error = return_token_error_as_program_error();
-        error.print::<TokenError>();
-    }
+    /// Processes a [RevokeMinter](enum.TokenInstruction.html) instruction.
+    pub fn process_revoke_minter(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        proposal: Option<&MultisigProposal>,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let minter_info = next_account_info(account_info_iter)?;
+        let mint_info = next_account_info(account_info_iter)?;
+        let dest_account_info = next_account_info(account_info_iter)?;
+        let owner_info = next_account_info(account_info_iter)?;
 
-    #[test]
-    #[should_panic(expected = "Custom(3)")]
-    fn test_error_unwrap() {
-        Err::<(), ProgramError>(return_token_error_as_program_error()).unwrap();
-    }
+        let mut minter = Minter::unpack(&minter_info.data.borrow())?;
+        if mint_info.key != &minter.mint {
+            return Err(TokenError::MintMismatch.into());
+        }
 
-    #[test]
-    fn test_unique_account_sizes() {
-        assert_ne!(Mint::get_packed_len(), 0);
-        assert_ne!(Mint::get_packed_len(), Account::get_packed_len());
-        assert_ne!(Mint::get_packed_len(), Multisig::get_packed_len());
-        assert_ne!(Account::get_packed_len(), 0);
-        assert_ne!(Account::get_packed_len(), Multisig::get_packed_len());
-        assert_ne!(Multisig::get_packed_len(), 0);
+        // A fixed supply mint has no authority left to manage its minters, but
+        // its minters can no longer mint either
+        let mint = Mint::unpack(&mint_info.data.borrow())?;
+        match mint.mint_authority {
+            COption::Some(mint_authority) => Self::validate_owner_with_proposal(
+                program_id,
+                &mint_authority,
+                owner_info,
+                account_info_iter.as_slice(),
+                proposal,
+            )?,
+            COption::None => return Err(TokenError::FixedSupply.into()),
+        }
+
+        let dest_starting_lamports = dest_account_info.lamports();
+        **dest_account_info.lamports.borrow_mut() = dest_starting_lamports
+            .checked_add(minter_info.lamports())
+            .ok_or(TokenError::Overflow)?;
+
+        **minter_info.lamports.borrow_mut() = 0;
+        minter.allowance = 0;
+
+        Minter::pack(minter, &mut minter_info.data.borrow_mut())?;
+
+        Ok(())
     }
 
-    #[test]
-    fn test_pack_unpack() {
-        // Mint
-        let check = Mint {
-            mint_authority: COption::Some(Pubkey::new(&[1; 32])),
-            supply: 42,
-            decimals: 7,
-            is_initialized: true,
-            freeze_authority: COption::Some(Pubkey::new(&[2; 32])),
-        };
-        let mut packed = vec![0; Mint::get_packed_len() + 1];
-        assert_eq!(
-            Err(ProgramError::InvalidAccountData),
-            Mint::pack(check, &mut packed)
-        );
-        let mut packed = vec![0; Mint::get_packed_len() - 1];
-        assert_eq!(
-            Err(ProgramError::InvalidAccountData),
-            Mint::pack(check, &mut packed)
-        );
-        let mut packed = vec![0; Mint::get_packed_len()];
-        Mint::pack(check, &mut packed).unwrap();
-        let expect = vec![
-            1, 0, 0, 0, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1,
-            1, 1, 1, 1, 1, 1, 1, 42, 0, 0, 0, 0, 0, 0, 0, 7, 1, 1, 0, 0, 0, 2, 2, 2, 2, 2, 2, 2, 2,
-            2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2,
-        ];
-        assert_eq!(packed, expect);
-        let unpacked = Mint::unpack(&packed).unwrap();
-        assert_eq!(unpacked, check);
+    /// Processes a [SetMintRole](enum.TokenInstruction.html) instruction.
+    pub fn process_set_mint_role(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        role: MintRole,
+        new_authority: COption<Pubkey>,
+        proposal: Option<&MultisigProposal>,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let mint_info = next_account_info(account_info_iter)?;
+        let owner_info = next_account_info(account_info_iter)?;
 
-        // Account
-        let check = Account {
-            mint: Pubkey::new(&[1; 32]),
-            owner: Pubkey::new(&[2; 32]),
-            amount: 3,
-            delegate: COption::Some(Pubkey::new(&[4; 32])),
-            state: AccountState::Frozen,
-            is_native: COption::Some(5),
-            delegated_amount: 6,
-            close_authority: COption::Some(Pubkey::new(&[7; 32])),
-        };
-        let mut packed = vec![0; Account::get_packed_len() + 1];
-        assert_eq!(
-            Err(ProgramError::InvalidAccountData),
-            Account::pack(check, &mut packed)
-        );
-        let mut packed = vec![0; Account::get_packed_len() - 1];
-        assert_eq!(
-            Err(ProgramError::InvalidAccountData),
-            Account::pack(check, &mut packed)
-        );
-        let mut packed = vec![0; Account::get_packed_len()];
-        Account::pack(check, &mut packed).unwrap();
-        let expect = vec![
-            1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1,
-            1, 1, 1, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2,
-            2, 2, 2, 2, 2, 2, 3, 0, 0, 0, 0, 0, 0, 0, 1, 0, 0, 0, 4, 4, 4, 4, 4, 4, 4, 4, 4, 4, 4,
-            4, 4, 4, 4, 4, 4, 4, 4, 4, 4, 4, 4, 4, 4, 4, 4, 4, 4, 4, 4, 4, 2, 1, 0, 0, 0, 5, 0, 0,
-            0, 0, 0, 0, 0, 6, 0, 0, 0, 0, 0, 0, 0, 1, 0, 0, 0, 7, 7, 7, 7, 7, 7, 7, 7, 7, 7, 7, 7,
-            7, 7, 7, 7, 7, 7, 7, 7, 7, 7, 7, 7, 7, 7, 7, 7, 7, 7, 7, 7,
-        ];
-        assert_eq!(packed, expect);
-        let unpacked = Account::unpack(&packed).unwrap();
-        assert_eq!(unpacked, check);
+        let mut mint = Mint::unpack(&mint_info.data.borrow())?;
+        if mint.authority_timelock > 0 {
+            return Err(TokenError::AuthorityChangeTimelocked.into());
+        }
+        match mint.mint_authority {
+            COption::Some(mint_authority) => Self::validate_owner_with_proposal(
+                program_id,
+                &mint_authority,
+                owner_info,
+                account_info_iter.as_slice(),
+                proposal,
+            )?,
+            COption::None => return Err(TokenError::FixedSupply.into()),
+        }
 
-        // Multisig
-        let check = Multisig {
-            m: 1,
-            n: 2,
-            is_initialized: true,
-            signers: [Pubkey::new(&[3; 32]); MAX_SIGNERS],
-        };
-        let mut packed = vec![0; Multisig::get_packed_len() + 1];
-        assert_eq!(
-            Err(ProgramError::InvalidAccountData),
-            Multisig::pack(check, &mut packed)
-        );
-        let mut packed = vec![0; Multisig::get_packed_len() - 1];
-        assert_eq!(
-            Err(ProgramError::InvalidAccountData),
-            Multisig::pack(check, &mut packed)
-        );
-        let mut packed = vec![0; Multisig::get_packed_len()];
-        Multisig::pack(check, &mut packed).unwrap();
-        let expect = vec![
-            1, 2, 1, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3,
-            3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3,
-            3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3,
-            3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3,
-            3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3,
-            3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3,
-            3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3,
-            3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3,
-            3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3,
-            3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3,
-            3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3,
-            3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3,
-            3, 3, 3, 3, 3, 3, 3,
-        ];
-        assert_eq!(packed, expect);
-        let unpacked = Multisig::unpack(&packed).unwrap();
-        assert_eq!(unpacked, check);
-    }
+        match role {
+            MintRole::Burner => mint.burner = new_authority,
+            MintRole::Pauser => mint.pauser = new_authority,
+            MintRole::Freezer => mint.freezer = new_authority,
+            MintRole::ComplianceAuthority => mint.compliance_authority = new_authority,
+        }
 
-    #[test]
-    fn test_initialize_mint() {
-        let program_id = crate::id();
-        let owner_key = Pubkey::new_unique();
-        let mint_key = Pubkey::new_unique();
-        let mut mint_account = SolanaAccount::new(42, Mint::get_packed_len(), &program_id);
-        let mint2_key = Pubkey::new_unique();
-        let mut mint2_account =
-            SolanaAccount::new(mint_minimum_balance(), Mint::get_packed_len(), &program_id);
-        let mut rent_sysvar = rent_sysvar();
+        Mint::pack(mint, &mut mint_info.data.borrow_mut())?;
 
-        // mint is not rent exempt
-        assert_eq!(
-            Err(TokenError::NotRentExempt.into()),
-            do_process_instruction(
-                initialize_mint(&program_id, &mint_key, &owner_key, None, 2).unwrap(),
-                vec![&mut mint_account, &mut rent_sysvar]
-            )
-        );
+        Ok(())
+    }
 
-        mint_account.lamports = mint_minimum_balance();
+    /// Processes a [PauseMinting](enum.TokenInstruction.html) or a
+    /// [ResumeMinting](enum.TokenInstruction.html) instruction.
+    pub fn process_toggle_pause_minting(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        pause: bool,
+        proposal: Option<&MultisigProposal>,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let mint_info = next_account_info(account_info_iter)?;
+        let authority_info = next_account_info(account_info_iter)?;
 
-        // create new mint
-        do_process_instruction(
-            initialize_mint(&program_id, &mint_key, &owner_key, None, 2).unwrap(),
-            vec![&mut mint_account, &mut rent_sysvar],
-        )
-        .unwrap();
+        let mut mint = Mint::unpack(&mint_info.data.borrow())?;
+        if pause == mint.minting_paused {
+            return Err(TokenError::InvalidState.into());
+        }
 
-        // create twice
-        assert_eq!(
-            Err(TokenError::AlreadyInUse.into()),
-            do_process_instruction(
-                initialize_mint(&program_id, &mint_key, &owner_key, None, 2,).unwrap(),
-                vec![&mut mint_account, &mut rent_sysvar]
-            )
-        );
+        match mint.pauser {
+            COption::Some(pauser) => Self::validate_owner_with_proposal(
+                program_id,
+                &pauser,
+                authority_info,
+                account_info_iter.as_slice(),
+                proposal,
+            ),
+            COption::None => Err(TokenError::MintCannotPause.into()),
+        }?;
 
-        // create another mint that can freeze
-        do_process_instruction(
-            initialize_mint(&program_id, &mint2_key, &owner_key, Some(&owner_key), 2).unwrap(),
-            vec![&mut mint2_account, &mut rent_sysvar],
-        )
-        .unwrap();
-        let mint = Mint::unpack_unchecked(&mint2_account.data).unwrap();
-        assert_eq!(mint.freeze_authority, COption::Some(owner_key));
-    }
+        mint.minting_paused = pause;
 
-    #[test]
-    fn test_initialize_mint_account() {
-        let program_id = crate::id();
-        let account_key = Pubkey::new_unique();
-        let mut account_account = SolanaAccount::new(42, Account::get_packed_len(), &program_id);
-        let owner_key = Pubkey::new_unique();
-        let mut owner_account = SolanaAccount::default();
-        let mint_key = Pubkey::new_unique();
-        let mut mint_account =
-            SolanaAccount::new(mint_minimum_balance(), Mint::get_packed_len(), &program_id);
-        let mut rent_sysvar = rent_sysvar();
+        Mint::pack(mint, &mut mint_info.data.borrow_mut())?;
 
-        // account is not rent exempt
-        assert_eq!(
-            Err(TokenError::NotRentExempt.into()),
-            do_process_instruction(
-                initialize_account(&program_id, &account_key, &mint_key, &owner_key).unwrap(),
-                vec![
-                    &mut account_account,
-                    &mut mint_account,
-                    &mut owner_account,
-                    &mut rent_sysvar
-                ],
-            )
-        );
+        Ok(())
+    }
 
-        account_account.lamports = account_minimum_balance();
+    /// Processes a [SetMaxBalance](enum.TokenInstruction.html) instruction.
+    pub fn process_set_max_balance(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        max_balance: COption<u64>,
+        proposal: Option<&MultisigProposal>,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let mint_info = next_account_info(account_info_iter)?;
+        let owner_info = next_account_info(account_info_iter)?;
 
-        // mint is not valid (not initialized)
-        assert_eq!(
-            Err(TokenError::InvalidMint.into()),
-            do_process_instruction(
-                initialize_account(&program_id, &account_key, &mint_key, &owner_key).unwrap(),
-                vec![
-                    &mut account_account,
-                    &mut mint_account,
-                    &mut owner_account,
-                    &mut rent_sysvar
-                ],
-            )
-        );
+        let mut mint = Mint::unpack(&mint_info.data.borrow())?;
+        let mint_authority = mint
+            .mint_authority
+            .ok_or(Into::<ProgramError>::into(TokenError::FixedSupply))?;
+        Self::validate_owner_with_proposal(
+            program_id,
+            &mint_authority,
+            owner_info,
+            account_info_iter.as_slice(),
+            proposal,
+        )?;
 
-        // create mint
-        do_process_instruction(
-            initialize_mint(&program_id, &mint_key, &owner_key, None, 2).unwrap(),
-            vec![&mut mint_account, &mut rent_sysvar],
-        )
-        .unwrap();
+        mint.max_balance = max_balance;
 
-        // create account
-        do_process_instruction(
-            initialize_account(&program_id, &account_key, &mint_key, &owner_key).unwrap(),
-            vec![
-                &mut account_account,
-                &mut mint_account,
-                &mut owner_account,
-                &mut rent_sysvar,
-            ],
-        )
-        .unwrap();
+        Mint::pack(mint, &mut mint_info.data.borrow_mut())?;
 
-        // create twice
-        assert_eq!(
-            Err(TokenError::AlreadyInUse.into()),
-            do_process_instruction(
-                initialize_account(&program_id, &account_key, &mint_key, &owner_key).unwrap(),
-                vec![
-                    &mut account_account,
-                    &mut mint_account,
-                    &mut owner_account,
-                    &mut rent_sysvar
-                ],
-            )
-        );
+        Ok(())
     }
 
-    #[test]
-    fn test_transfer_dups() {
-        let program_id = crate::id();
-        let account1_key = Pubkey::new_unique();
-        let mut account1_account = SolanaAccount::new(
-            account_minimum_balance(),
-            Account::get_packed_len(),
-            &program_id,
-        );
-        let mut account1_info: AccountInfo = (&account1_key, true, &mut account1_account).into();
-        let account2_key = Pubkey::new_unique();
-        let mut account2_account = SolanaAccount::new(
-            account_minimum_balance(),
-            Account::get_packed_len(),
-            &program_id,
-        );
-        let mut account2_info: AccountInfo = (&account2_key, false, &mut account2_account).into();
-        let account3_key = Pubkey::new_unique();
-        let mut account3_account = SolanaAccount::new(
-            account_minimum_balance(),
-            Account::get_packed_len(),
-            &program_id,
-        );
-        let account3_info: AccountInfo = (&account3_key, false, &mut account3_account).into();
-        let account4_key = Pubkey::new_unique();
-        let mut account4_account = SolanaAccount::new(
-            account_minimum_balance(),
-            Account::get_packed_len(),
-            &program_id,
-        );
-        let account4_info: AccountInfo = (&account4_key, true, &mut account4_account).into();
-        let multisig_key = Pubkey::new_unique();
-        let mut multisig_account = SolanaAccount::new(
-            multisig_minimum_balance(),
-            Multisig::get_packed_len(),
-            &program_id,
-        );
-        let multisig_info: AccountInfo = (&multisig_key, true, &mut multisig_account).into();
-        let owner_key = Pubkey::new_unique();
-        let mut owner_account = SolanaAccount::default();
-        let owner_info: AccountInfo = (&owner_key, true, &mut owner_account).into();
-        let mint_key = Pubkey::new_unique();
-        let mut mint_account =
-            SolanaAccount::new(mint_minimum_balance(), Mint::get_packed_len(), &program_id);
-        let mint_info: AccountInfo = (&mint_key, false, &mut mint_account).into();
-        let rent_key = rent::id();
-        let mut rent_sysvar = rent_sysvar();
-        let rent_info: AccountInfo = (&rent_key, false, &mut rent_sysvar).into();
+    /// Processes a [SetLaunchControls](enum.TokenInstruction.html) instruction.
+    pub fn process_set_launch_controls(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        trading_start_timestamp: UnixTimestamp,
+        transfer_cooldown: u64,
+        proposal: Option<&MultisigProposal>,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let mint_info = next_account_info(account_info_iter)?;
+        let owner_info = next_account_info(account_info_iter)?;
 
-        // create mint
-        do_process_instruction_dups(
-            initialize_mint(&program_id, &mint_key, &owner_key, None, 2).unwrap(),
-            vec![mint_info.clone(), rent_info.clone()],
-        )
-        .unwrap();
+        let mut mint = Mint::unpack(&mint_info.data.borrow())?;
+        let mint_authority = mint
+            .mint_authority
+            .ok_or(Into::<ProgramError>::into(TokenError::FixedSupply))?;
+        Self::validate_owner_with_proposal(
+            program_id,
+            &mint_authority,
+            owner_info,
+            account_info_iter.as_slice(),
+            proposal,
+        )?;
 
-        // create account
-        do_process_instruction_dups(
-            initialize_account(&program_id, &account1_key, &mint_key, &account1_key).unwrap(),
-            vec![
-                account1_info.clone(),
-                mint_info.clone(),
-                account1_info.clone(),
-                rent_info.clone(),
-            ],
-        )
-        .unwrap();
+        mint.trading_start_timestamp = trading_start_timestamp;
+        mint.transfer_cooldown = transfer_cooldown;
 
-        // create another account
-        do_process_instruction_dups(
-            initialize_account(&program_id, &account2_key, &mint_key, &owner_key).unwrap(),
-            vec![
-                account2_info.clone(),
-                mint_info.clone(),
-                owner_info.clone(),
-                rent_info.clone(),
-            ],
-        )
-        .unwrap();
+        Mint::pack(mint, &mut mint_info.data.borrow_mut())?;
 
-        // mint to account
-        do_process_instruction_dups(
-            mint_to(&program_id, &mint_key, &account1_key, &owner_key, &[], 1000).unwrap(),
-            vec![mint_info.clone(), account1_info.clone(), owner_info.clone()],
-        )
-        .unwrap();
+        Ok(())
+    }
 
-        // source-owner transfer
-        do_process_instruction_dups(
-            transfer(
-                &program_id,
-                &account1_key,
-                &account2_key,
-                &account1_key,
-                &[],
-                500,
-            )
-            .unwrap(),
-            vec![
-                account1_info.clone(),
-                account2_info.clone(),
-                account1_info.clone(),
-            ],
-        )
-        .unwrap();
+    /// Processes a [SetMinTransferAmount](enum.TokenInstruction.html) instruction.
+    pub fn process_set_min_transfer_amount(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        amount: u64,
+        proposal: Option<&MultisigProposal>,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let mint_info = next_account_info(account_info_iter)?;
+        let owner_info = next_account_info(account_info_iter)?;
 
-        // source-owner TransferChecked
-        do_process_instruction_dups(
-            transfer_checked(
-                &program_id,
-                &account1_key,
-                &mint_key,
-                &account2_key,
-                &account1_key,
-                &[],
-                500,
-                2,
-            )
-            .unwrap(),
-            vec![
-                account1_info.clone(),
-                mint_info.clone(),
-                account2_info.clone(),
-                account1_info.clone(),
-            ],
-        )
-        .unwrap();
+        let mut mint = Mint::unpack(&mint_info.data.borrow())?;
+        let mint_authority = mint
+            .mint_authority
+            .ok_or(Into::<ProgramError>::into(TokenError::FixedSupply))?;
+        Self::validate_owner_with_proposal(
+            program_id,
+            &mint_authority,
+            owner_info,
+            account_info_iter.as_slice(),
+            proposal,
+        )?;
 
-        // source-delegate transfer
-        let mut account = Account::unpack_unchecked(&account1_info.data.borrow()).unwrap();
-        account.amount = 1000;
-        account.delegated_amount = 1000;
-        account.delegate = COption::Some(account1_key);
-        account.owner = owner_key;
-        Account::pack(account, &mut account1_info.data.borrow_mut()).unwrap();
+        mint.min_transfer_amount = amount;
 
-        do_process_instruction_dups(
-            transfer(
-                &program_id,
-                &account1_key,
-                &account2_key,
-                &account1_key,
-                &[],
-                500,
-            )
-            .unwrap(),
-            vec![
-                account1_info.clone(),
-                account2_info.clone(),
-                account1_info.clone(),
-            ],
-        )
-        .unwrap();
+        Mint::pack(mint, &mut mint_info.data.borrow_mut())?;
 
-        // source-delegate TransferChecked
-        do_process_instruction_dups(
-            transfer_checked(
-                &program_id,
-                &account1_key,
-                &mint_key,
-                &account2_key,
-                &account1_key,
-                &[],
-                500,
-                2,
-            )
-            .unwrap(),
-            vec![
-                account1_info.clone(),
-                mint_info.clone(),
-                account2_info.clone(),
-                account1_info.clone(),
-            ],
-        )
-        .unwrap();
+        Ok(())
+    }
 
-        // test destination-owner transfer
-        do_process_instruction_dups(
-            initialize_account(&program_id, &account3_key, &mint_key, &account2_key).unwrap(),
-            vec![
-                account3_info.clone(),
-                mint_info.clone(),
-                account2_info.clone(),
-                rent_info.clone(),
-            ],
-        )
-        .unwrap();
-        do_process_instruction_dups(
-            mint_to(&program_id, &mint_key, &account3_key, &owner_key, &[], 1000).unwrap(),
-            vec![mint_info.clone(), account3_info.clone(), owner_info.clone()],
-        )
-        .unwrap();
+    /// Processes a [SetMinIncomingAmount](enum.TokenInstruction.html) instruction.
+    pub fn process_set_min_incoming_amount(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        amount: u64,
+        proposal: Option<&MultisigProposal>,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let account_info = next_account_info(account_info_iter)?;
+        let owner_info = next_account_info(account_info_iter)?;
 
-        account1_info.is_signer = false;
-        account2_info.is_signer = true;
-        do_process_instruction_dups(
-            transfer(
-                &program_id,
-                &account3_key,
-                &account2_key,
-                &account2_key,
-                &[],
-                500,
-            )
-            .unwrap(),
-            vec![
-                account3_info.clone(),
-                account2_info.clone(),
-                account2_info.clone(),
-            ],
-        )
-        .unwrap();
+        let mut account = Account::unpack(&account_info.data.borrow())?;
+        Self::validate_owner_with_proposal(
+            program_id,
+            &account.owner,
+            owner_info,
+            account_info_iter.as_slice(),
+            proposal,
+        )?;
 
-        // destination-owner TransferChecked
-        do_process_instruction_dups(
-            transfer_checked(
-                &program_id,
-                &account3_key,
-                &mint_key,
-                &account2_key,
-                &account2_key,
-                &[],
-                500,
-                2,
-            )
-            .unwrap(),
-            vec![
-                account3_info.clone(),
-                mint_info.clone(),
-                account2_info.clone(),
-                account2_info.clone(),
-            ],
-        )
-        .unwrap();
+        account.min_incoming_amount = amount;
 
-        // test source-multisig signer
-        do_process_instruction_dups(
-            initialize_multisig(&program_id, &multisig_key, &[&account4_key], 1).unwrap(),
-            vec![
-                multisig_info.clone(),
-                rent_info.clone(),
-                account4_info.clone(),
-            ],
-        )
-        .unwrap();
+        Account::pack(account, &mut account_info.data.borrow_mut())?;
 
-        do_process_instruction_dups(
-            initialize_account(&program_id, &account4_key, &mint_key, &multisig_key).unwrap(),
-            vec![
-                account4_info.clone(),
-                mint_info.clone(),
-                multisig_info.clone(),
-                rent_info.clone(),
-            ],
-        )
-        .unwrap();
+        Ok(())
+    }
 
-        do_process_instruction_dups(
-            mint_to(&program_id, &mint_key, &account4_key, &owner_key, &[], 1000).unwrap(),
-            vec![mint_info.clone(), account4_info.clone(), owner_info.clone()],
-        )
-        .unwrap();
+    /// Processes an [AddMaxBalanceExemption](enum.TokenInstruction.html) or
+    /// [RemoveMaxBalanceExemption](enum.TokenInstruction.html) instruction.
+    pub fn process_toggle_max_balance_exemption(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        exempt: bool,
+        proposal: Option<&MultisigProposal>,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let account_info = next_account_info(account_info_iter)?;
+        let mint_info = next_account_info(account_info_iter)?;
+        let owner_info = next_account_info(account_info_iter)?;
 
-        // source-multisig-signer transfer
-        do_process_instruction_dups(
-            transfer(
-                &program_id,
-                &account4_key,
-                &account2_key,
-                &multisig_key,
-                &[&account4_key],
-                500,
-            )
-            .unwrap(),
-            vec![
-                account4_info.clone(),
-                account2_info.clone(),
-                multisig_info.clone(),
-                account4_info.clone(),
-            ],
-        )
-        .unwrap();
+        let mut account = Account::unpack(&account_info.data.borrow())?;
+        if mint_info.key != &account.mint {
+            return Err(TokenError::MintMismatch.into());
+        }
 
-        // source-multisig-signer TransferChecked
-        do_process_instruction_dups(
-            transfer_checked(
-                &program_id,
-                &account4_key,
-                &mint_key,
-                &account2_key,
-                &multisig_key,
-                &[&account4_key],
-                500,
-                2,
-            )
-            .unwrap(),
-            vec![
-                account4_info.clone(),
-                mint_info.clone(),
-                account2_info.clone(),
-                multisig_info.clone(),
-                account4_info.clone(),
-            ],
-        )
-        .unwrap();
-    }
+        let mint = Mint::unpack(&mint_info.data.borrow())?;
+        let mint_authority = mint
+            .mint_authority
+            .ok_or(Into::<ProgramError>::into(TokenError::FixedSupply))?;
+        Self::validate_owner_with_proposal(
+            program_id,
+            &mint_authority,
+            owner_info,
+            account_info_iter.as_slice(),
+            proposal,
+        )?;
 
-    #[test]
-    fn test_transfer() {
-        let program_id = crate::id();
-        let account_key = Pubkey::new_unique();
-        let mut account_account = SolanaAccount::new(
-            account_minimum_balance(),
-            Account::get_packed_len(),
-            &program_id,
-        );
-        let account2_key = Pubkey::new_unique();
-        let mut account2_account = SolanaAccount::new(
-            account_minimum_balance(),
-            Account::get_packed_len(),
-            &program_id,
-        );
-        let account3_key = Pubkey::new_unique();
-        let mut account3_account = SolanaAccount::new(
-            account_minimum_balance(),
-            Account::get_packed_len(),
-            &program_id,
-        );
-        let delegate_key = Pubkey::new_unique();
-        let mut delegate_account = SolanaAccount::default();
-        let mismatch_key = Pubkey::new_unique();
-        let mut mismatch_account = SolanaAccount::new(
-            account_minimum_balance(),
-            Account::get_packed_len(),
-            &program_id,
-        );
-        let owner_key = Pubkey::new_unique();
-        let mut owner_account = SolanaAccount::default();
-        let owner2_key = Pubkey::new_unique();
-        let mut owner2_account = SolanaAccount::default();
-        let mint_key = Pubkey::new_unique();
-        let mut mint_account =
-            SolanaAccount::new(mint_minimum_balance(), Mint::get_packed_len(), &program_id);
-        let mint2_key = Pubkey::new_unique();
-        let mut rent_sysvar = rent_sysvar();
+        account.max_balance_exempt = exempt;
 
-        // create mint
-        do_process_instruction(
-            initialize_mint(&program_id, &mint_key, &owner_key, None, 2).unwrap(),
-            vec![&mut mint_account, &mut rent_sysvar],
-        )
-        .unwrap();
+        Account::pack(account, &mut account_info.data.borrow_mut())?;
 
-        // create account
-        do_process_instruction(
-            initialize_account(&program_id, &account_key, &mint_key, &owner_key).unwrap(),
-            vec![
-                &mut account_account,
-                &mut mint_account,
-                &mut owner_account,
-                &mut rent_sysvar,
-            ],
-        )
-        .unwrap();
+        Ok(())
+    }
 
-        // create another account
-        do_process_instruction(
-            initialize_account(&program_id, &account2_key, &mint_key, &owner_key).unwrap(),
-            vec![
-                &mut account2_account,
-                &mut mint_account,
-                &mut owner_account,
-                &mut rent_sysvar,
-            ],
-        )
-        .unwrap();
+    /// Processes a [SetAuthorityTimelock](enum.TokenInstruction.html) instruction.
+    pub fn process_set_authority_timelock(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        delay: u64,
+        proposal: Option<&MultisigProposal>,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let mint_info = next_account_info(account_info_iter)?;
+        let owner_info = next_account_info(account_info_iter)?;
 
-        // create another account
-        do_process_instruction(
-            initialize_account(&program_id, &account3_key, &mint_key, &owner_key).unwrap(),
-            vec![
-                &mut account3_account,
-                &mut mint_account,
-                &mut owner_account,
-                &mut rent_sysvar,
-            ],
-        )
-        .unwrap();
+        let mut mint = Mint::unpack(&mint_info.data.borrow())?;
+        let mint_authority = mint
+            .mint_authority
+            .ok_or(Into::<ProgramError>::into(TokenError::FixedSupply))?;
+        Self::validate_owner_with_proposal(
+            program_id,
+            &mint_authority,
+            owner_info,
+            account_info_iter.as_slice(),
+            proposal,
+        )?;
 
-        // create mismatch account
-        do_process_instruction(
-            initialize_account(&program_id, &mismatch_key, &mint_key, &owner_key).unwrap(),
-            vec![
-                &mut mismatch_account,
-                &mut mint_account,
-                &mut owner_account,
-                &mut rent_sysvar,
-            ],
-        )
-        .unwrap();
-        let mut account = Account::unpack_unchecked(&mismatch_account.data).unwrap();
-        account.mint = mint2_key;
-        Account::pack(account, &mut mismatch_account.data).unwrap();
+        // Shortening the timelock would cut the notice given for changes queued afterwards
+        if delay < mint.authority_timelock {
+            return Err(TokenError::InvalidAuthorityTimelock.into());
+        }
+        mint.authority_timelock = delay;
 
-        // mint to account
-        do_process_instruction(
-            mint_to(&program_id, &mint_key, &account_key, &owner_key, &[], 1000).unwrap(),
-            vec![&mut mint_account, &mut account_account, &mut owner_account],
-        )
-        .unwrap();
+        Mint::pack(mint, &mut mint_info.data.borrow_mut())?;
 
-        // missing signer
-        let mut instruction = transfer(
-            &program_id,
-            &account_key,
-            &account2_key,
-            &owner_key,
-            &[],
-            1000,
-        )
-        .unwrap();
-        instruction.accounts[2].is_signer = false;
-        assert_eq!(
-            Err(ProgramError::MissingRequiredSignature),
-            do_process_instruction(
-                instruction,
-                vec![
-                    &mut account_account,
-                    &mut account2_account,
-                    &mut owner_account,
-                ],
-            )
-        );
+        Ok(())
+    }
 
-        // mismatch mint
-        assert_eq!(
-            Err(TokenError::MintMismatch.into()),
-            do_process_instruction(
-                transfer(
-                    &program_id,
-                    &account_key,
-                    &mismatch_key,
-                    &owner_key,
-                    &[],
-                    1000
-                )
-                .unwrap(),
-                vec![
-                    &mut account_account,
-                    &mut mismatch_account,
-                    &mut owner_account,
-                ],
-            )
-        );
+    /// Processes a [QueueAuthorityChange](enum.TokenInstruction.html) instruction.
+    pub fn process_queue_authority_change(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        authority: TimelockedAuthority,
+        new_authority: COption<Pubkey>,
+        proposal: Option<&MultisigProposal>,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let mint_info = next_account_info(account_info_iter)?;
+        let clock = Clock::from_account_info(next_account_info(account_info_iter)?)?;
+        let owner_info = next_account_info(account_info_iter)?;
 
-        // missing owner
-        assert_eq!(
-            Err(TokenError::OwnerMismatch.into()),
-            do_process_instruction(
-                transfer(
-                    &program_id,
-                    &account_key,
-                    &account2_key,
-                    &owner2_key,
-                    &[],
-                    1000
-                )
-                .unwrap(),
-                vec![
-                    &mut account_account,
-                    &mut account2_account,
-                    &mut owner2_account,
-                ],
-            )
-        );
-
-        // transfer
-        do_process_instruction(
-            transfer(
-                &program_id,
-                &account_key,
-                &account2_key,
-                &owner_key,
-                &[],
-                1000,
-            )
-            .unwrap(),
-            vec![
-                &mut account_account,
-                &mut account2_account,
-                &mut owner_account,
-            ],
-        )
-        .unwrap();
+        let mut mint = Mint::unpack(&mint_info.data.borrow())?;
+        if mint.queued_authority != TimelockedAuthority::None {
+            return Err(TokenError::AuthorityChangeQueued.into());
+        }
 
-        // insufficient funds
-        assert_eq!(
-            Err(TokenError::InsufficientFunds.into()),
-            do_process_instruction(
-                transfer(&program_id, &account_key, &account2_key, &owner_key, &[], 1).unwrap(),
-                vec![
-                    &mut account_account,
-                    &mut account2_account,
-                    &mut owner_account,
-                ],
-            )
-        );
+        let current_authority = Self::current_timelocked_authority(&mint, authority)?;
+        Self::validate_owner_with_proposal(
+            program_id,
+            &current_authority,
+            owner_info,
+            account_info_iter.as_slice(),
+            proposal,
+        )?;
 
-        // transfer half back
-        do_process_instruction(
-            transfer(
-                &program_id,
-                &account2_key,
-                &account_key,
-                &owner_key,
-                &[],
-                500,
-            )
-            .unwrap(),
-            vec![
-                &mut account2_account,
-                &mut account_account,
-                &mut owner_account,
-            ],
-        )
-        .unwrap();
+        let delay = i64::try_from(mint.authority_timelock).map_err(|_| TokenError::Overflow)?;
+        mint.queued_unlock_timestamp = clock
+            .unix_timestamp
+            .checked_add(delay)
+            .ok_or(TokenError::Overflow)?;
+        mint.queued_authority = authority;
+        mint.queued_new_authority = new_authority;
 
-        // incorrect decimals
-        assert_eq!(
-            Err(TokenError::MintDecimalsMismatch.into()),
-            do_process_instruction(
-                transfer_checked(
-                    &program_id,
-                    &account2_key,
-                    &mint_key,
-                    &account_key,
-                    &owner_key,
-                    &[],
-                    1,
-                    10 // <-- incorrect decimals
-                )
-                .unwrap(),
-                vec![
-                    &mut account2_account,
-                    &mut mint_account,
-                    &mut account_account,
-                    &mut owner_account,
-                ],
-            )
-        );
+        Mint::pack(mint, &mut mint_info.data.borrow_mut())?;
 
-        // incorrect mint
-        assert_eq!(
-            Err(TokenError::MintMismatch.into()),
-            do_process_instruction(
-                transfer_checked(
-                    &program_id,
-                    &account2_key,
-                    &account3_key, // <-- incorrect mint
-                    &account_key,
-                    &owner_key,
-                    &[],
-                    1,
-                    2
-                )
-                .unwrap(),
-                vec![
-                    &mut account2_account,
-                    &mut account3_account, // <-- incorrect mint
-                    &mut account_account,
-                    &mut owner_account,
-                ],
-            )
-        );
-        // transfer rest with explicit decimals
-        do_process_instruction(
-            transfer_checked(
-                &program_id,
-                &account2_key,
-                &mint_key,
-                &account_key,
-                &owner_key,
-                &[],
-                500,
-                2,
-            )
-            .unwrap(),
-            vec![
-                &mut account2_account,
-                &mut mint_account,
-                &mut account_account,
-                &mut owner_account,
-            ],
-        )
-        .unwrap();
+        Ok(())
+    }
 
-        // insufficient funds
-        assert_eq!(
-            Err(TokenError::InsufficientFunds.into()),
-            do_process_instruction(
-                transfer(&program_id, &account2_key, &account_key, &owner_key, &[], 1).unwrap(),
-                vec![
-                    &mut account2_account,
-                    &mut account_account,
-                    &mut owner_account,
-                ],
-            )
-        );
+    /// Processes an [ExecuteAuthorityChange](enum.TokenInstruction.html) instruction.
+    pub fn process_execute_authority_change(accounts: &[AccountInfo]) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let mint_info = next_account_info(account_info_iter)?;
+        let clock = Clock::from_account_info(next_account_info(account_info_iter)?)?;
 
-        // approve delegate
-        do_process_instruction(
-            approve(
-                &program_id,
-                &account_key,
-                &delegate_key,
-                &owner_key,
-                &[],
-                100,
-            )
-            .unwrap(),
-            vec![
-                &mut account_account,
-                &mut delegate_account,
-                &mut owner_account,
-            ],
-        )
-        .unwrap();
+        let mut mint = Mint::unpack(&mint_info.data.borrow())?;
+        if mint.queued_authority == TimelockedAuthority::None {
+            return Err(TokenError::NoPendingAuthority.into());
+        }
+        if clock.unix_timestamp < mint.queued_unlock_timestamp {
+            return Err(TokenError::AuthorityChangeTimelocked.into());
+        }
 
-        // transfer via delegate
-        do_process_instruction(
-            transfer(
-                &program_id,
-                &account_key,
-                &account2_key,
-                &delegate_key,
-                &[],
-                100,
-            )
-            .unwrap(),
-            vec![
-                &mut account_account,
-                &mut account2_account,
-                &mut delegate_account,
-            ],
-        )
-        .unwrap();
+        // A fixed supply or a disabled freeze authority can't be revived by a queued change
+        Self::current_timelocked_authority(&mint, mint.queued_authority)?;
 
-        // insufficient funds approved via delegate
-        assert_eq!(
-            Err(TokenError::OwnerMismatch.into()),
-            do_process_instruction(
-                transfer(
-                    &program_id,
-                    &account_key,
-                    &account2_key,
-                    &delegate_key,
-                    &[],
-                    100
-                )
-                .unwrap(),
-                vec![
-                    &mut account_account,
-                    &mut account2_account,
-                    &mut delegate_account,
-                ],
-            )
-        );
+        let new_authority = mint.queued_new_authority;
+        match mint.queued_authority {
+            TimelockedAuthority::MintTokens => {
+                mint.mint_authority = new_authority;
+                mint.pending_mint_authority = COption::None;
+            }
+            TimelockedAuthority::FreezeAccount => {
+                mint.freeze_authority = new_authority;
+                mint.pending_freeze_authority = COption::None;
+            }
+            TimelockedAuthority::Burner => mint.burner = new_authority,
+            TimelockedAuthority::Pauser => mint.pauser = new_authority,
+            TimelockedAuthority::Freezer => mint.freezer = new_authority,
+            TimelockedAuthority::ComplianceAuthority => mint.compliance_authority = new_authority,
+            TimelockedAuthority::None => unreachable!(),
+        }
+        Self::clear_queued_authority_change(&mut mint);
 
-        // transfer rest
-        do_process_instruction(
-            transfer(
-                &program_id,
-                &account_key,
-                &account2_key,
-                &owner_key,
-                &[],
-                900,
-            )
-            .unwrap(),
-            vec![
-                &mut account_account,
-                &mut account2_account,
-                &mut owner_account,
-            ],
-        )
-        .unwrap();
+        Mint::pack(mint, &mut mint_info.data.borrow_mut())?;
 
-        // approve delegate
-        do_process_instruction(
-            approve(
-                &program_id,
-                &account_key,
-                &delegate_key,
-                &owner_key,
-                &[],
-                100,
-            )
-            .unwrap(),
-            vec![
-                &mut account_account,
-                &mut delegate_account,
-                &mut owner_account,
-            ],
-        )
-        .unwrap();
+        Ok(())
+    }
 
-        // insufficient funds in source account via delegate
-        assert_eq!(
-            Err(TokenError::InsufficientFunds.into()),
-            do_process_instruction(
-                transfer(
-                    &program_id,
-                    &account_key,
-                    &account2_key,
-                    &delegate_key,
-                    &[],
-                    100
-                )
-                .unwrap(),
-                vec![
-                    &mut account_account,
-                    &mut account2_account,
-                    &mut delegate_account,
-                ],
-            )
-        );
+    /// Processes a [CancelAuthorityChange](enum.TokenInstruction.html) instruction.
+    pub fn process_cancel_authority_change(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        proposal: Option<&MultisigProposal>,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let mint_info = next_account_info(account_info_iter)?;
+        let owner_info = next_account_info(account_info_iter)?;
+
+        let mut mint = Mint::unpack(&mint_info.data.borrow())?;
+        if mint.queued_authority == TimelockedAuthority::None {
+            return Err(TokenError::NoPendingAuthority.into());
+        }
+
+        let current_authority = Self::current_timelocked_authority(&mint, mint.queued_authority)?;
+        Self::validate_owner_with_proposal(
+            program_id,
+            &current_authority,
+            owner_info,
+            account_info_iter.as_slice(),
+            proposal,
+        )?;
+        Self::clear_queued_authority_change(&mut mint);
+
+        Mint::pack(mint, &mut mint_info.data.borrow_mut())?;
+
+        Ok(())
     }
 
-    #[test]
-    fn test_self_transfer() {
-        let program_id = crate::id();
-        let account_key = Pubkey::new_unique();
-        let mut account_account = SolanaAccount::new(
-            account_minimum_balance(),
-            Account::get_packed_len(),
-            &program_id,
-        );
-        let account2_key = Pubkey::new_unique();
-        let mut account2_account = SolanaAccount::new(
-            account_minimum_balance(),
-            Account::get_packed_len(),
-            &program_id,
-        );
-        let account3_key = Pubkey::new_unique();
-        let mut account3_account = SolanaAccount::new(
-            account_minimum_balance(),
-            Account::get_packed_len(),
-            &program_id,
-        );
-        let delegate_key = Pubkey::new_unique();
-        let mut delegate_account = SolanaAccount::default();
-        let owner_key = Pubkey::new_unique();
-        let mut owner_account = SolanaAccount::default();
-        let owner2_key = Pubkey::new_unique();
-        let mut owner2_account = SolanaAccount::default();
-        let mint_key = Pubkey::new_unique();
-        let mut mint_account =
-            SolanaAccount::new(mint_minimum_balance(), Mint::get_packed_len(), &program_id);
-        let mut rent_sysvar = rent_sysvar();
+    /// Processes a [SetGuardians](enum.TokenInstruction.html) instruction.
+    pub fn process_set_guardians(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        guardians: COption<Pubkey>,
+        recovery_delay: u64,
+        proposal: Option<&MultisigProposal>,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let account_info = next_account_info(account_info_iter)?;
+        let owner_info = next_account_info(account_info_iter)?;
 
-        // create mint
-        do_process_instruction(
-            initialize_mint(&program_id, &mint_key, &owner_key, None, 2).unwrap(),
-            vec![&mut mint_account, &mut rent_sysvar],
-        )
-        .unwrap();
+        let mut account = Account::unpack(&account_info.data.borrow())?;
+        Self::thaw_if_expired(&mut account, accounts)?;
+        if account.is_frozen() {
+            return Err(TokenError::AccountFrozen.into());
+        }
 
-        // create account
-        do_process_instruction(
-            initialize_account(&program_id, &account_key, &mint_key, &owner_key).unwrap(),
-            vec![
-                &mut account_account,
-                &mut mint_account,
-                &mut owner_account,
-                &mut rent_sysvar,
-            ],
-        )
-        .unwrap();
+        Self::validate_owner_with_proposal(
+            program_id,
+            &account.owner,
+            owner_info,
+            account_info_iter.as_slice(),
+            proposal,
+        )?;
 
-        // create another account
-        do_process_instruction(
-            initialize_account(&program_id, &account2_key, &mint_key, &owner_key).unwrap(),
-            vec![
-                &mut account2_account,
-                &mut mint_account,
-                &mut owner_account,
-                &mut rent_sysvar,
-            ],
-        )
-        .unwrap();
+        account.guardians = guardians;
+        account.recovery_delay = recovery_delay;
+        Self::clear_recovery(&mut account);
 
-        // create another account
-        do_process_instruction(
-            initialize_account(&program_id, &account3_key, &mint_key, &owner_key).unwrap(),
-            vec![
-                &mut account3_account,
-                &mut mint_account,
-                &mut owner_account,
-                &mut rent_sysvar,
-            ],
-        )
-        .unwrap();
+        Account::pack(account, &mut account_info.data.borrow_mut())?;
 
-        // mint to account
-        do_process_instruction(
-            mint_to(&program_id, &mint_key, &account_key, &owner_key, &[], 1000).unwrap(),
-            vec![&mut mint_account, &mut account_account, &mut owner_account],
-        )
-        .unwrap();
+        Ok(())
+    }
 
-        let account_info = (&account_key, false, &mut account_account).into_account_info();
-        let account3_info = (&account3_key, false, &mut account3_account).into_account_info();
-        let delegate_info = (&delegate_key, true, &mut delegate_account).into_account_info();
-        let owner_info = (&owner_key, true, &mut owner_account).into_account_info();
-        let owner2_info = (&owner2_key, true, &mut owner2_account).into_account_info();
-        let mint_info = (&mint_key, false, &mut mint_account).into_account_info();
+    /// Processes an [InitiateRecovery](enum.TokenInstruction.html) instruction.
+    pub fn process_initiate_recovery(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        new_owner: Pubkey,
+        proposal: Option<&MultisigProposal>,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let account_info = next_account_info(account_info_iter)?;
+        let clock = Clock::from_account_info(next_account_info(account_info_iter)?)?;
+        let guardians_info = next_account_info(account_info_iter)?;
 
-        // transfer
-        let instruction = transfer(
-            &program_id,
-            account_info.key,
-            account_info.key,
-            owner_info.key,
-            &[],
-            1000,
-        )
-        .unwrap();
-        assert_eq!(
-            Ok(()),
-            Processor::process(
-                &instruction.program_id,
-                &[
-                    account_info.clone(),
-                    account_info.clone(),
-                    owner_info.clone(),
-                ],
-                &instruction.data,
-            )
-        );
-        // no balance change...
-        let account = Account::unpack_unchecked(&account_info.try_borrow_data().unwrap()).unwrap();
-        assert_eq!(account.amount, 1000);
+        let mut account = Account::unpack(&account_info.data.borrow())?;
+        Self::thaw_if_expired(&mut account, accounts)?;
+        if account.is_frozen() {
+            return Err(TokenError::AccountFrozen.into());
+        }
 
-        // transfer checked
-        let instruction = transfer_checked(
-            &program_id,
-            account_info.key,
-            mint_info.key,
-            account_info.key,
-            owner_info.key,
-            &[],
-            1000,
-            2,
-        )
-        .unwrap();
-        assert_eq!(
-            Ok(()),
-            Processor::process(
-                &instruction.program_id,
-                &[
-                    account_info.clone(),
-                    mint_info.clone(),
-                    account_info.clone(),
-                    owner_info.clone(),
-                ],
-                &instruction.data,
-            )
-        );
-        // no balance change...
-        let account = Account::unpack_unchecked(&account_info.try_borrow_data().unwrap()).unwrap();
-        assert_eq!(account.amount, 1000);
+        let guardians = account
+            .guardians
+            .ok_or(Into::<ProgramError>::into(TokenError::NoGuardians))?;
+        Self::validate_owner_with_proposal(
+            program_id,
+            &guardians,
+            guardians_info,
+            account_info_iter.as_slice(),
+            proposal,
+        )?;
 
-        // missing signer
-        let mut owner_no_sign_info = owner_info.clone();
-        let mut instruction = transfer(
-            &program_id,
-            account_info.key,
-            account_info.key,
-            owner_no_sign_info.key,
-            &[],
-            1000,
-        )
-        .unwrap();
-        instruction.accounts[2].is_signer = false;
-        owner_no_sign_info.is_signer = false;
-        assert_eq!(
-            Err(ProgramError::MissingRequiredSignature),
-            Processor::process(
-                &instruction.program_id,
-                &[
-                    account_info.clone(),
-                    account_info.clone(),
-                    owner_no_sign_info.clone(),
-                ],
-                &instruction.data,
-            )
-        );
+        let delay = i64::try_from(account.recovery_delay).map_err(|_| TokenError::Overflow)?;
+        account.recovery_unlock_timestamp = clock
+            .unix_timestamp
+            .checked_add(delay)
+            .ok_or(TokenError::Overflow)?;
+        account.recovery_owner = COption::Some(new_owner);
 
-        // missing signer checked
-        let mut instruction = transfer_checked(
-            &program_id,
-            account_info.key,
-            mint_info.key,
-            account_info.key,
-            owner_no_sign_info.key,
-            &[],
-            1000,
-            2,
-        )
-        .unwrap();
-        instruction.accounts[3].is_signer = false;
-        assert_eq!(
-            Err(ProgramError::MissingRequiredSignature),
-            Processor::process(
-                &instruction.program_id,
-                &[
-                    account_info.clone(),
-                    mint_info.clone(),
-                    account_info.clone(),
-                    owner_no_sign_info,
-                ],
-                &instruction.data,
-            )
-        );
+        Account::pack(account, &mut account_info.data.borrow_mut())?;
 
-        // missing owner
-        let instruction = transfer(
-            &program_id,
-            account_info.key,
-            account_info.key,
-            owner2_info.key,
-            &[],
-            1000,
-        )
-        .unwrap();
-        assert_eq!(
-            Err(TokenError::OwnerMismatch.into()),
-            Processor::process(
-                &instruction.program_id,
-                &[
-                    account_info.clone(),
-                    account_info.clone(),
-                    owner2_info.clone(),
-                ],
-                &instruction.data,
-            )
-        );
+        Ok(())
+    }
 
-        // missing owner checked
-        let instruction = transfer_checked(
-            &program_id,
-            account_info.key,
-            mint_info.key,
-            account_info.key,
-            owner2_info.key,
-            &[],
-            1000,
-            2,
-        )
-        .unwrap();
-        assert_eq!(
-            Err(TokenError::OwnerMismatch.into()),
-            Processor::process(
-                &instruction.program_id,
-                &[
-                    account_info.clone(),
-                    mint_info.clone(),
-                    account_info.clone(),
-                    owner2_info.clone(),
-                ],
-                &instruction.data,
-            )
-        );
+    /// Processes a [CompleteRecovery](enum.TokenInstruction.html) instruction.
+    pub fn process_complete_recovery(accounts: &[AccountInfo]) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let account_info = next_account_info(account_info_iter)?;
+        let clock = Clock::from_account_info(next_account_info(account_info_iter)?)?;
 
-        // insufficient funds
-        let instruction = transfer(
-            &program_id,
-            account_info.key,
-            account_info.key,
-            owner_info.key,
-            &[],
-            1001,
-        )
-        .unwrap();
-        assert_eq!(
-            Err(TokenError::InsufficientFunds.into()),
-            Processor::process(
-                &instruction.program_id,
-                &[
-                    account_info.clone(),
-                    account_info.clone(),
-                    owner_info.clone(),
-                ],
-                &instruction.data,
-            )
-        );
+        let mut account = Account::unpack(&account_info.data.borrow())?;
+        Self::thaw_if_expired(&mut account, accounts)?;
+        if account.is_frozen() {
+            return Err(TokenError::AccountFrozen.into());
+        }
 
-        // insufficient funds checked
-        let instruction = transfer_checked(
-            &program_id,
-            account_info.key,
-            mint_info.key,
-            account_info.key,
-            owner_info.key,
-            &[],
-            1001,
-            2,
-        )
-        .unwrap();
-        assert_eq!(
-            Err(TokenError::InsufficientFunds.into()),
-            Processor::process(
-                &instruction.program_id,
-                &[
-                    account_info.clone(),
-                    mint_info.clone(),
-                    account_info.clone(),
-                    owner_info.clone(),
-                ],
-                &instruction.data,
-            )
-        );
+        let recovery_owner = account
+            .recovery_owner
+            .ok_or(Into::<ProgramError>::into(TokenError::NoPendingAuthority))?;
+        if clock.unix_timestamp < account.recovery_unlock_timestamp {
+            return Err(TokenError::AuthorityChangeTimelocked.into());
+        }
 
-        // incorrect decimals
-        let instruction = transfer_checked(
-            &program_id,
-            account_info.key,
-            mint_info.key,
-            account_info.key,
-            owner_info.key,
-            &[],
-            1,
-            10, // <-- incorrect decimals
-        )
-        .unwrap();
-        assert_eq!(
-            Err(TokenError::MintDecimalsMismatch.into()),
-            Processor::process(
-                &instruction.program_id,
-                &[
-                    account_info.clone(),
-                    mint_info.clone(),
-                    account_info.clone(),
-                    owner_info.clone(),
-                ],
-                &instruction.data,
-            )
-        );
+        account.owner = recovery_owner;
+        Self::clear_delegation(&mut account);
+        account.pending_owner = COption::None;
+        account.pending_close_authority = COption::None;
+        Self::clear_recovery(&mut account);
+        Self::clear_session(&mut account);
 
-        // incorrect mint
-        let instruction = transfer_checked(
-            &program_id,
-            account_info.key,
-            account3_info.key, // <-- incorrect mint
-            account_info.key,
-            owner_info.key,
-            &[],
-            1,
-            2,
-        )
-        .unwrap();
-        assert_eq!(
-            Err(TokenError::MintMismatch.into()),
-            Processor::process(
-                &instruction.program_id,
-                &[
-                    account_info.clone(),
-                    account3_info.clone(), // <-- incorrect mint
-                    account_info.clone(),
-                    owner_info.clone(),
-                ],
-                &instruction.data,
-            )
-        );
+        Account::pack(account, &mut account_info.data.borrow_mut())?;
 
-        // approve delegate
-        let instruction = approve(
-            &program_id,
-            account_info.key,
-            delegate_info.key,
-            owner_info.key,
-            &[],
-            100,
-        )
-        .unwrap();
-        Processor::process(
-            &instruction.program_id,
-            &[
-                account_info.clone(),
-                delegate_info.clone(),
-                owner_info.clone(),
-            ],
-            &instruction.data,
-        )
-        .unwrap();
+        Ok(())
+    }
 
-        // delegate transfer
-        let instruction = transfer(
-            &program_id,
-            account_info.key,
-            account_info.key,
-            delegate_info.key,
-            &[],
-            100,
-        )
-        .unwrap();
-        assert_eq!(
-            Ok(()),
-            Processor::process(
-                &instruction.program_id,
-                &[
-                    account_info.clone(),
-                    account_info.clone(),
-                    delegate_info.clone(),
-                ],
-                &instruction.data,
-            )
+    /// Processes a [VetoRecovery](enum.TokenInstruction.html) instruction.
+    pub fn process_veto_recovery(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        proposal: Option<&MultisigProposal>,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let account_info = next_account_info(account_info_iter)?;
+        let owner_info = next_account_info(account_info_iter)?;
+
+        let mut account = Account::unpack(&account_info.data.borrow())?;
+        if account.recovery_owner.is_none() {
+            return Err(TokenError::NoPendingAuthority.into());
+        }
+
+        Self::validate_owner_with_proposal(
+            program_id,
+            &account.owner,
+            owner_info,
+            account_info_iter.as_slice(),
+            proposal,
+        )?;
+        Self::clear_recovery(&mut account);
+
+        Account::pack(account, &mut account_info.data.borrow_mut())?;
+
+        Ok(())
+    }
+
+    /// Processes a [SetAllowance](enum.TokenInstruction.html) instruction.
+    pub fn process_set_allowance(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        amount: u64,
+        proposal: Option<&MultisigProposal>,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let allowance_info = next_account_info(account_info_iter)?;
+        let source_account_info = next_account_info(account_info_iter)?;
+        let spender_info = next_account_info(account_info_iter)?;
+        let payer_info = next_account_info(account_info_iter)?;
+        let system_program_info = next_account_info(account_info_iter)?;
+        let rent = &Rent::from_account_info(next_account_info(account_info_iter)?)?;
+        let owner_info = next_account_info(account_info_iter)?;
+
+        let mut source_account = Account::unpack(&source_account_info.data.borrow())?;
+        Self::thaw_if_expired(&mut source_account, accounts)?;
+        if source_account.is_frozen() {
+            return Err(TokenError::AccountFrozen.into());
+        }
+
+        Self::validate_owner_with_proposal(
+            program_id,
+            &source_account.owner,
+            owner_info,
+            account_info_iter.as_slice(),
+            proposal,
+        )?;
+
+        let (allowance_address, bump_seed) = get_allowance_address_and_bump_seed(
+            source_account_info.key,
+            spender_info.key,
+            program_id,
         );
-        // no balance change...
-        let account = Account::unpack_unchecked(&account_info.try_borrow_data().unwrap()).unwrap();
-        assert_eq!(account.amount, 1000);
-        assert_eq!(account.delegated_amount, 100);
+        if allowance_address != *allowance_info.key {
+            return Err(ProgramError::InvalidSeeds);
+        }
 
-        // delegate transfer checked
-        let instruction = transfer_checked(
-            &program_id,
-            account_info.key,
-            mint_info.key,
-            account_info.key,
-            delegate_info.key,
-            &[],
-            100,
-            2,
-        )
-        .unwrap();
-        assert_eq!(
-            Ok(()),
-            Processor::process(
-                &instruction.program_id,
+        if allowance_info.data_len() == 0 {
+            Self::create_pda_account(
+                program_id,
+                payer_info,
+                allowance_info,
+                system_program_info,
+                rent,
+                Allowance::LEN,
                 &[
-                    account_info.clone(),
-                    mint_info.clone(),
-                    account_info.clone(),
-                    delegate_info.clone(),
+                    ALLOWANCE_SEED,
+                    source_account_info.key.as_ref(),
+                    spender_info.key.as_ref(),
+                    &[bump_seed],
                 ],
-                &instruction.data,
-            )
-        );
-        // no balance change...
-        let account = Account::unpack_unchecked(&account_info.try_borrow_data().unwrap()).unwrap();
-        assert_eq!(account.amount, 1000);
-        assert_eq!(account.delegated_amount, 100);
+            )?;
+        }
+        if !Self::is_allowance(program_id, allowance_info) {
+            return Err(ProgramError::IncorrectProgramId);
+        }
 
-        // delegate insufficient funds
-        let instruction = transfer(
-            &program_id,
-            account_info.key,
-            account_info.key,
-            delegate_info.key,
-            &[],
-            101,
-        )
-        .unwrap();
-        assert_eq!(
-            Err(TokenError::InsufficientFunds.into()),
-            Processor::process(
-                &instruction.program_id,
-                &[
-                    account_info.clone(),
-                    account_info.clone(),
-                    delegate_info.clone(),
-                ],
-                &instruction.data,
-            )
-        );
+        let mut allowance = Allowance::unpack_unchecked(&allowance_info.data.borrow())?;
+        if !allowance.is_initialized {
+            allowance.account = *source_account_info.key;
+            allowance.spender = *spender_info.key;
+            allowance.bump_seed = bump_seed;
+            allowance.is_initialized = true;
+        }
+        allowance.owner = source_account.owner;
+        allowance.amount = amount;
 
-        // delegate insufficient funds checked
-        let instruction = transfer_checked(
-            &program_id,
-            account_info.key,
-            mint_info.key,
-            account_info.key,
-            delegate_info.key,
-            &[],
-            101,
-            2,
-        )
-        .unwrap();
-        assert_eq!(
-            Err(TokenError::InsufficientFunds.into()),
-            Processor::process(
-                &instruction.program_id,
-                &[
-                    account_info.clone(),
-                    mint_info.clone(),
-                    account_info.clone(),
-                    delegate_info.clone(),
-                ],
-                &instruction.data,
-            )
-        );
+        Allowance::pack(allowance, &mut allowance_info.data.borrow_mut())?;
 
-        // owner transfer with delegate assigned
-        let instruction = transfer(
-            &program_id,
-            account_info.key,
-            account_info.key,
-            owner_info.key,
-            &[],
-            1000,
-        )
-        .unwrap();
-        assert_eq!(
-            Ok(()),
-            Processor::process(
-                &instruction.program_id,
-                &[
-                    account_info.clone(),
-                    account_info.clone(),
-                    owner_info.clone(),
-                ],
-                &instruction.data,
-            )
-        );
-        // no balance change...
-        let account = Account::unpack_unchecked(&account_info.try_borrow_data().unwrap()).unwrap();
-        assert_eq!(account.amount, 1000);
+        Ok(())
+    }
 
-        // owner transfer with delegate assigned checked
-        let instruction = transfer_checked(
-            &program_id,
-            account_info.key,
-            mint_info.key,
-            account_info.key,
-            owner_info.key,
-            &[],
-            1000,
-            2,
-        )
-        .unwrap();
-        assert_eq!(
-            Ok(()),
-            Processor::process(
-                &instruction.program_id,
-                &[
-                    account_info.clone(),
-                    mint_info.clone(),
-                    account_info.clone(),
-                    owner_info.clone(),
-                ],
-                &instruction.data,
-            )
-        );
-        // no balance change...
-        let account = Account::unpack_unchecked(&account_info.try_borrow_data().unwrap()).unwrap();
-        assert_eq!(account.amount, 1000);
+    /// Processes a [CloseAllowance](enum.TokenInstruction.html) instruction.
+    pub fn process_close_allowance(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        proposal: Option<&MultisigProposal>,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let allowance_info = next_account_info(account_info_iter)?;
+        let source_account_info = next_account_info(account_info_iter)?;
+        let dest_account_info = next_account_info(account_info_iter)?;
+        let owner_info = next_account_info(account_info_iter)?;
+
+        let allowance = Allowance::unpack(&allowance_info.data.borrow())?;
+        if source_account_info.key != &allowance.account {
+            return Err(TokenError::AllowanceMismatch.into());
+        }
+
+        let source_account = Account::unpack(&source_account_info.data.borrow())?;
+        Self::validate_owner_with_proposal(
+            program_id,
+            &source_account.owner,
+            owner_info,
+            account_info_iter.as_slice(),
+            proposal,
+        )?;
+
+        // The allowance is left uninitialized, so it can't be spent again within the transaction
+        allowance_info.data.borrow_mut().fill(0);
+
+        let dest_starting_lamports = dest_account_info.lamports();
+        **dest_account_info.lamports.borrow_mut() = dest_starting_lamports
+            .checked_add(allowance_info.lamports())
+            .ok_or(TokenError::Overflow)?;
+
+        **allowance_info.lamports.borrow_mut() = 0;
+
+        Ok(())
     }
 
-    #[test]
-    fn test_mintable_token_with_zero_supply() {
-        let program_id = crate::id();
-        let account_key = Pubkey::new_unique();
-        let mut account_account = SolanaAccount::new(
-            account_minimum_balance(),
-            Account::get_packed_len(),
-            &program_id,
-        );
-        let owner_key = Pubkey::new_unique();
-        let mut owner_account = SolanaAccount::default();
-        let mint_key = Pubkey::new_unique();
-        let mut mint_account =
-            SolanaAccount::new(mint_minimum_balance(), Mint::get_packed_len(), &program_id);
-        let mut rent_sysvar = rent_sysvar();
+    /// Processes an [IssueCredential](enum.TokenInstruction.html) instruction.
+    pub fn process_issue_credential(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        expiry: UnixTimestamp,
+        jurisdiction: u16,
+        proposal: Option<&MultisigProposal>,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let credential_info = next_account_info(account_info_iter)?;
+        let mint_info = next_account_info(account_info_iter)?;
+        let holder_info = next_account_info(account_info_iter)?;
+        let payer_info = next_account_info(account_info_iter)?;
+        let system_program_info = next_account_info(account_info_iter)?;
+        let rent = &Rent::from_account_info(next_account_info(account_info_iter)?)?;
+        let authority_info = next_account_info(account_info_iter)?;
 
-        // create mint-able token with zero supply
-        let decimals = 2;
-        do_process_instruction(
-            initialize_mint(&program_id, &mint_key, &owner_key, None, decimals).unwrap(),
-            vec![&mut mint_account, &mut rent_sysvar],
-        )
-        .unwrap();
-        let mint = Mint::unpack_unchecked(&mint_account.data).unwrap();
-        assert_eq!(
-            mint,
-            Mint {
-                mint_authority: COption::Some(owner_key),
-                supply: 0,
-                decimals,
-                is_initialized: true,
-                freeze_authority: COption::None,
-            }
-        );
+        let mint = Mint::unpack(&mint_info.data.borrow())?;
+        let compliance_authority = mint
+            .compliance_authority
+            .ok_or(TokenError::MintCannotIssueCredentials)?;
+        Self::validate_owner_with_proposal(
+            program_id,
+            &compliance_authority,
+            authority_info,
+            account_info_iter.as_slice(),
+            proposal,
+        )?;
 
-        // create account
-        do_process_instruction(
-            initialize_account(&program_id, &account_key, &mint_key, &owner_key).unwrap(),
-            vec![
-                &mut account_account,
-                &mut mint_account,
-                &mut owner_account,
-                &mut rent_sysvar,
-            ],
-        )
-        .unwrap();
+        let (credential_address, bump_seed) =
+            get_credential_address_and_bump_seed(mint_info.key, holder_info.key, program_id);
+        if credential_address != *credential_info.key {
+            return Err(ProgramError::InvalidSeeds);
+        }
 
-        // mint to
-        do_process_instruction(
-            mint_to(&program_id, &mint_key, &account_key, &owner_key, &[], 42).unwrap(),
-            vec![&mut mint_account, &mut account_account, &mut owner_account],
-        )
-        .unwrap();
-        let _ = Mint::unpack(&mint_account.data).unwrap();
-        let account = Account::unpack_unchecked(&account_account.data).unwrap();
-        assert_eq!(account.amount, 42);
+        if credential_info.data_len() == 0 {
+            Self::create_pda_account(
+                program_id,
+                payer_info,
+                credential_info,
+                system_program_info,
+                rent,
+                Credential::LEN,
+                &[
+                    CREDENTIAL_SEED,
+                    mint_info.key.as_ref(),
+                    holder_info.key.as_ref(),
+                    &[bump_seed],
+                ],
+            )?;
+        }
+        if !Self::is_credential(program_id, credential_info) {
+            return Err(ProgramError::IncorrectProgramId);
+        }
 
-        // mint to 2, with incorrect decimals
-        assert_eq!(
-            Err(TokenError::MintDecimalsMismatch.into()),
-            do_process_instruction(
-                mint_to_checked(
-                    &program_id,
-                    &mint_key,
-                    &account_key,
-                    &owner_key,
-                    &[],
-                    42,
-                    decimals + 1
-                )
-                .unwrap(),
-                vec![&mut mint_account, &mut account_account, &mut owner_account],
-            )
-        );
+        let mut credential = Credential::unpack_unchecked(&credential_info.data.borrow())?;
+        if !credential.is_initialized {
+            credential.mint = *mint_info.key;
+            credential.owner = *holder_info.key;
+            credential.bump_seed = bump_seed;
+            credential.is_initialized = true;
+        }
+        credential.expiry = expiry;
+        credential.jurisdiction = jurisdiction;
 
-        let _ = Mint::unpack(&mint_account.data).unwrap();
-        let account = Account::unpack_unchecked(&account_account.data).unwrap();
-        assert_eq!(account.amount, 42);
+        Credential::pack(credential, &mut credential_info.data.borrow_mut())?;
 
-        // mint to 2
-        do_process_instruction(
-            mint_to_checked(
-                &program_id,
-                &mint_key,
-                &account_key,
-                &owner_key,
-                &[],
-                42,
-                decimals,
-            )
-            .unwrap(),
-            vec![&mut mint_account, &mut account_account, &mut owner_account],
-        )
-        .unwrap();
-        let _ = Mint::unpack(&mint_account.data).unwrap();
-        let account = Account::unpack_unchecked(&account_account.data).unwrap();
-        assert_eq!(account.amount, 84);
+        Ok(())
     }
 
-    #[test]
-    fn test_approve_dups() {
-        let program_id = crate::id();
-        let account1_key = Pubkey::new_unique();
-        let mut account1_account = SolanaAccount::new(
-            account_minimum_balance(),
-            Account::get_packed_len(),
-            &program_id,
-        );
-        let account1_info: AccountInfo = (&account1_key, true, &mut account1_account).into();
-        let account2_key = Pubkey::new_unique();
-        let mut account2_account = SolanaAccount::new(
-            account_minimum_balance(),
-            Account::get_packed_len(),
-            &program_id,
-        );
-        let account2_info: AccountInfo = (&account2_key, false, &mut account2_account).into();
-        let account3_key = Pubkey::new_unique();
-        let mut account3_account = SolanaAccount::new(
-            account_minimum_balance(),
-            Account::get_packed_len(),
-            &program_id,
-        );
-        let account3_info: AccountInfo = (&account3_key, true, &mut account3_account).into();
-        let multisig_key = Pubkey::new_unique();
-        let mut multisig_account = SolanaAccount::new(
-            multisig_minimum_balance(),
-            Multisig::get_packed_len(),
-            &program_id,
-        );
-        let multisig_info: AccountInfo = (&multisig_key, true, &mut multisig_account).into();
-        let owner_key = Pubkey::new_unique();
-        let mut owner_account = SolanaAccount::default();
-        let owner_info: AccountInfo = (&owner_key, true, &mut owner_account).into();
-        let mint_key = Pubkey::new_unique();
-        let mut mint_account =
-            SolanaAccount::new(mint_minimum_balance(), Mint::get_packed_len(), &program_id);
-        let mint_info: AccountInfo = (&mint_key, false, &mut mint_account).into();
-        let rent_key = rent::id();
-        let mut rent_sysvar = rent_sysvar();
-        let rent_info: AccountInfo = (&rent_key, false, &mut rent_sysvar).into();
+    /// Processes a [RevokeCredential](enum.TokenInstruction.html) instruction.
+    pub fn process_revoke_credential(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        proposal: Option<&MultisigProposal>,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let credential_info = next_account_info(account_info_iter)?;
+        let mint_info = next_account_info(account_info_iter)?;
+        let dest_account_info = next_account_info(account_info_iter)?;
+        let authority_info = next_account_info(account_info_iter)?;
 
-        // create mint
-        do_process_instruction_dups(
-            initialize_mint(&program_id, &mint_key, &owner_key, None, 2).unwrap(),
-            vec![mint_info.clone(), rent_info.clone()],
-        )
-        .unwrap();
+        let credential = Credential::unpack(&credential_info.data.borrow())?;
+        if mint_info.key != &credential.mint {
+            return Err(TokenError::MintMismatch.into());
+        }
 
-        // create account
-        do_process_instruction_dups(
-            initialize_account(&program_id, &account1_key, &mint_key, &account1_key).unwrap(),
-            vec![
-                account1_info.clone(),
-                mint_info.clone(),
-                account1_info.clone(),
-                rent_info.clone(),
-            ],
-        )
-        .unwrap();
+        let mint = Mint::unpack(&mint_info.data.borrow())?;
+        let compliance_authority = mint
+            .compliance_authority
+            .ok_or(TokenError::MintCannotIssueCredentials)?;
+        Self::validate_owner_with_proposal(
+            program_id,
+            &compliance_authority,
+            authority_info,
+            account_info_iter.as_slice(),
+            proposal,
+        )?;
 
-        // create another account
-        do_process_instruction_dups(
-            initialize_account(&program_id, &account2_key, &mint_key, &owner_key).unwrap(),
-            vec![
-                account2_info.clone(),
-                mint_info.clone(),
-                owner_info.clone(),
-                rent_info.clone(),
-            ],
-        )
-        .unwrap();
+        // The credential is left uninitialized, so it no longer counts within the transaction
+        credential_info.data.borrow_mut().fill(0);
 
-        // mint to account
-        do_process_instruction_dups(
-            mint_to(&program_id, &mint_key, &account1_key, &owner_key, &[], 1000).unwrap(),
-            vec![mint_info.clone(), account1_info.clone(), owner_info.clone()],
-        )
-        .unwrap();
+        let dest_starting_lamports = dest_account_info.lamports();
+        **dest_account_info.lamports.borrow_mut() = dest_starting_lamports
+            .checked_add(credential_info.lamports())
+            .ok_or(TokenError::Overflow)?;
 
-        // source-owner approve
-        do_process_instruction_dups(
-            approve(
-                &program_id,
-                &account1_key,
-                &account2_key,
-                &account1_key,
-                &[],
-                500,
-            )
-            .unwrap(),
-            vec![
-                account1_info.clone(),
-                account2_info.clone(),
-                account1_info.clone(),
-            ],
-        )
-        .unwrap();
+        **credential_info.lamports.borrow_mut() = 0;
 
-        // source-owner approve_checked
-        do_process_instruction_dups(
-            approve_checked(
-                &program_id,
-                &account1_key,
-                &mint_key,
-                &account2_key,
-                &account1_key,
-                &[],
-                500,
-                2,
-            )
-            .unwrap(),
-            vec![
-                account1_info.clone(),
-                mint_info.clone(),
-                account2_info.clone(),
-                account1_info.clone(),
-            ],
-        )
-        .unwrap();
+        Ok(())
+    }
 
-        // source-owner revoke
-        do_process_instruction_dups(
-            revoke(&program_id, &account1_key, &account1_key, &[]).unwrap(),
-            vec![account1_info.clone(), account1_info.clone()],
-        )
-        .unwrap();
+    /// Processes a [CreateSession](enum.TokenInstruction.html) instruction.
+    #[allow(clippy::too_many_arguments)]
+    pub fn process_create_session(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        session_key: Pubkey,
+        expiry: UnixTimestamp,
+        scope: u8,
+        destinations: &[Pubkey],
+        max_amount: u64,
+        proposal: Option<&MultisigProposal>,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let account_info = next_account_info(account_info_iter)?;
+        let owner_info = next_account_info(account_info_iter)?;
 
-        // test source-multisig signer
-        do_process_instruction_dups(
-            initialize_multisig(&program_id, &multisig_key, &[&account3_key], 1).unwrap(),
-            vec![
-                multisig_info.clone(),
-                rent_info.clone(),
-                account3_info.clone(),
-            ],
-        )
-        .unwrap();
+        if scope & !(SESSION_SCOPE_TRANSFER | SESSION_SCOPE_BURN) != 0
+            || destinations.len() > MAX_SESSION_DESTINATIONS
+        {
+            return Err(ProgramError::InvalidArgument);
+        }
 
-        do_process_instruction_dups(
-            initialize_account(&program_id, &account3_key, &mint_key, &multisig_key).unwrap(),
-            vec![
-                account3_info.clone(),
-                mint_info.clone(),
-                multisig_info.clone(),
-                rent_info.clone(),
-            ],
-        )
-        .unwrap();
+        let mut account = Account::unpack(&account_info.data.borrow())?;
 
-        do_process_instruction_dups(
-            mint_to(&program_id, &mint_key, &account3_key, &owner_key, &[], 1000).unwrap(),
-            vec![mint_info.clone(), account3_info.clone(), owner_info.clone()],
-        )
-        .unwrap();
+        Self::thaw_if_expired(&mut account, accounts)?;
+        if account.is_frozen() {
+            return Err(TokenError::AccountFrozen.into());
+        }
 
-        // source-multisig-signer approve
-        do_process_instruction_dups(
-            approve(
-                &program_id,
-                &account3_key,
-                &account2_key,
-                &multisig_key,
-                &[&account3_key],
-                500,
-            )
-            .unwrap(),
-            vec![
-                account3_info.clone(),
-                account2_info.clone(),
-                multisig_info.clone(),
-                account3_info.clone(),
-            ],
-        )
-        .unwrap();
+        Self::validate_owner_with_proposal(
+            program_id,
+            &account.owner,
+            owner_info,
+            account_info_iter.as_slice(),
+            proposal,
+        )?;
 
-        // source-multisig-signer approve_checked
-        do_process_instruction_dups(
-            approve_checked(
-                &program_id,
-                &account3_key,
-                &mint_key,
-                &account2_key,
-                &multisig_key,
-                &[&account3_key],
-                500,
-                2,
-            )
-            .unwrap(),
-            vec![
-                account3_info.clone(),
-                mint_info.clone(),
-                account2_info.clone(),
-                multisig_info.clone(),
-                account3_info.clone(),
-            ],
-        )
-        .unwrap();
+        account.session_key = COption::Some(session_key);
+        account.session_expiry = expiry;
+        account.session_scope = scope;
+        account.num_session_destinations = destinations.len() as u8;
+        account.session_destinations = Default::default();
+        account.session_destinations[..destinations.len()].copy_from_slice(destinations);
+        account.session_remaining_amount = max_amount;
 
-        // source-owner multisig-signer
-        do_process_instruction_dups(
-            revoke(&program_id, &account3_key, &multisig_key, &[&account3_key]).unwrap(),
-            vec![
-                account3_info.clone(),
-                multisig_info.clone(),
-                account3_info.clone(),
-            ],
-        )
-        .unwrap();
+        Account::pack(account, &mut account_info.data.borrow_mut())?;
+
+        Ok(())
     }
 
-    #[test]
-    fn test_approve() {
-        let program_id = crate::id();
-        let account_key = Pubkey::new_unique();
-        let mut account_account = SolanaAccount::new(
-            account_minimum_balance(),
-            Account::get_packed_len(),
-            &program_id,
-        );
-        let account2_key = Pubkey::new_unique();
-        let mut account2_account = SolanaAccount::new(
-            account_minimum_balance(),
-            Account::get_packed_len(),
-            &program_id,
-        );
-        let delegate_key = Pubkey::new_unique();
-        let mut delegate_account = SolanaAccount::default();
-        let owner_key = Pubkey::new_unique();
-        let mut owner_account = SolanaAccount::default();
-        let owner2_key = Pubkey::new_unique();
-        let mut owner2_account = SolanaAccount::default();
-        let mint_key = Pubkey::new_unique();
-        let mut mint_account =
-            SolanaAccount::new(mint_minimum_balance(), Mint::get_packed_len(), &program_id);
-        let mut rent_sysvar = rent_sysvar();
+    /// Processes a [RevokeSession](enum.TokenInstruction.html) instruction.
+    pub fn process_revoke_session(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        proposal: Option<&MultisigProposal>,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let account_info = next_account_info(account_info_iter)?;
+        let owner_info = next_account_info(account_info_iter)?;
 
-        // create mint
-        do_process_instruction(
-            initialize_mint(&program_id, &mint_key, &owner_key, None, 2).unwrap(),
-            vec![&mut mint_account, &mut rent_sysvar],
-        )
-        .unwrap();
+        let mut account = Account::unpack(&account_info.data.borrow())?;
 
-        // create account
-        do_process_instruction(
-            initialize_account(&program_id, &account_key, &mint_key, &owner_key).unwrap(),
-            vec![
-                &mut account_account,
-                &mut mint_account,
-                &mut owner_account,
-                &mut rent_sysvar,
-            ],
-        )
-        .unwrap();
+        Self::validate_owner_with_proposal(
+            program_id,
+            &account.owner,
+            owner_info,
+            account_info_iter.as_slice(),
+            proposal,
+        )?;
 
-        // create another account
-        do_process_instruction(
-            initialize_account(&program_id, &account2_key, &mint_key, &owner_key).unwrap(),
-            vec![
-                &mut account2_account,
-                &mut mint_account,
-                &mut owner_account,
-                &mut rent_sysvar,
-            ],
-        )
-        .unwrap();
+        Self::clear_session(&mut account);
 
-        // mint to account
-        do_process_instruction(
-            mint_to(&program_id, &mint_key, &account_key, &owner_key, &[], 1000).unwrap(),
-            vec![&mut mint_account, &mut account_account, &mut owner_account],
-        )
-        .unwrap();
+        Account::pack(account, &mut account_info.data.borrow_mut())?;
 
-        // missing signer
-        let mut instruction = approve(
-            &program_id,
-            &account_key,
-            &delegate_key,
-            &owner_key,
-            &[],
-            100,
+        Ok(())
+    }
+
+    /// Processes a [SetSpendingLimit](enum.TokenInstruction.html) instruction.
+    pub fn process_set_spending_limit(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        amount: u64,
+        window: u64,
+        proposal: Option<&MultisigProposal>,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let account_info = next_account_info(account_info_iter)?;
+        let clock = Clock::from_account_info(next_account_info(account_info_iter)?)?;
+        let owner_info = next_account_info(account_info_iter)?;
+
+        if window == 0 {
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        let mut account = Account::unpack(&account_info.data.borrow())?;
+        Self::thaw_if_expired(&mut account, accounts)?;
+        if account.is_frozen() {
+            return Err(TokenError::AccountFrozen.into());
+        }
+
+        Self::validate_owner_with_proposal(
+            program_id,
+            &account.owner,
+            owner_info,
+            account_info_iter.as_slice(),
+            proposal,
+        )?;
+
+        Self::apply_pending_spending_limit(&mut account, clock.unix_timestamp);
+
+        // A tighter limit takes effect at once, a looser one only after the delay
+        let tightens = match account.spending_limit {
+            COption::Some(limit) => amount <= limit && window >= account.spending_window,
+            COption::None => true,
+        };
+        if tightens {
+            account.spending_limit = COption::Some(amount);
+            account.spending_window = window;
+            account.pending_spending_limit = COption::None;
+            account.pending_spending_window = 0;
+            account.spending_limit_unlock_timestamp = 0;
+        } else {
+            account.pending_spending_limit = COption::Some(amount);
+            account.pending_spending_window = window;
+            account.spending_limit_unlock_timestamp = clock
+                .unix_timestamp
+                .checked_add(SPENDING_LIMIT_INCREASE_DELAY)
+                .ok_or(TokenError::Overflow)?;
+        }
+
+        Account::pack(account, &mut account_info.data.borrow_mut())?;
+
+        Ok(())
+    }
+
+    /// Processes an [InitializeDestinationAllowlist](enum.TokenInstruction.html) instruction.
+    pub fn process_initialize_destination_allowlist(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        proposal: Option<&MultisigProposal>,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let allowlist_info = next_account_info(account_info_iter)?;
+        let allowlist_info_data_len = allowlist_info.data_len();
+        let account_info = next_account_info(account_info_iter)?;
+        let rent = &Rent::from_account_info(next_account_info(account_info_iter)?)?;
+        let owner_info = next_account_info(account_info_iter)?;
+
+        let mut allowlist = DestinationAllowlist::unpack_unchecked(&allowlist_info.data.borrow())?;
+        if allowlist.is_initialized {
+            return Err(TokenError::AlreadyInUse.into());
+        }
+
+        if !rent.is_exempt(allowlist_info.lamports(), allowlist_info_data_len) {
+            return Err(TokenError::NotRentExempt.into());
+        }
+
+        let mut account = Account::unpack(&account_info.data.borrow())?;
+        if account.destination_allowlist.is_some() {
+            return Err(TokenError::AlreadyInUse.into());
+        }
+
+        Self::validate_owner_with_proposal(
+            program_id,
+            &account.owner,
+            owner_info,
+            account_info_iter.as_slice(),
+            proposal,
+        )?;
+
+        allowlist.account = *account_info.key;
+        allowlist.is_initialized = true;
+        account.destination_allowlist = COption::Some(*allowlist_info.key);
+
+        DestinationAllowlist::pack(allowlist, &mut allowlist_info.data.borrow_mut())?;
+        Account::pack(account, &mut account_info.data.borrow_mut())?;
+
+        Ok(())
+    }
+
+    /// Processes an [AddAllowedDestination](enum.TokenInstruction.html) instruction.
+    pub fn process_add_allowed_destination(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        destination: Pubkey,
+        proposal: Option<&MultisigProposal>,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let allowlist_info = next_account_info(account_info_iter)?;
+        let account_info = next_account_info(account_info_iter)?;
+        let clock = Clock::from_account_info(next_account_info(account_info_iter)?)?;
+        let owner_info = next_account_info(account_info_iter)?;
+
+        let mut allowlist = DestinationAllowlist::unpack(&allowlist_info.data.borrow())?;
+        if allowlist.account != *account_info.key {
+            return Err(TokenError::AllowlistMismatch.into());
+        }
+
+        let account = Account::unpack(&account_info.data.borrow())?;
+        Self::validate_owner_with_proposal(
+            program_id,
+            &account.owner,
+            owner_info,
+            account_info_iter.as_slice(),
+            proposal,
+        )?;
+
+        let n = allowlist.n as usize;
+        if allowlist.destinations[..n].contains(&destination) {
+            return Err(TokenError::DestinationAlreadyAllowed.into());
+        }
+        if n == MAX_ALLOWED_DESTINATIONS {
+            return Err(TokenError::AllowlistFull.into());
+        }
+        allowlist.destinations[n] = destination;
+        allowlist.active_timestamps[n] = clock
+            .unix_timestamp
+            .checked_add(DESTINATION_ALLOWLIST_DELAY)
+            .ok_or(TokenError::Overflow)?;
+        allowlist.n += 1;
+
+        DestinationAllowlist::pack(allowlist, &mut allowlist_info.data.borrow_mut())?;
+
+        Ok(())
+    }
+
+    /// Processes a [RemoveAllowedDestination](enum.TokenInstruction.html) instruction.
+    pub fn process_remove_allowed_destination(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        destination: Pubkey,
+        proposal: Option<&MultisigProposal>,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let allowlist_info = next_account_info(account_info_iter)?;
+        let account_info = next_account_info(account_info_iter)?;
+        let owner_info = next_account_info(account_info_iter)?;
+
+        let mut allowlist = DestinationAllowlist::unpack(&allowlist_info.data.borrow())?;
+        if allowlist.account != *account_info.key {
+            return Err(TokenError::AllowlistMismatch.into());
+        }
+
+        let account = Account::unpack(&account_info.data.borrow())?;
+        Self::validate_owner_with_proposal(
+            program_id,
+            &account.owner,
+            owner_info,
+            account_info_iter.as_slice(),
+            proposal,
+        )?;
+
+        let n = allowlist.n as usize;
+        let position = allowlist.destinations[..n]
+            .iter()
+            .position(|allowed| *allowed == destination)
+            .ok_or(TokenError::DestinationNotFound)?;
+        allowlist
+            .destinations
+            .copy_within(position + 1..n, position);
+        allowlist
+            .active_timestamps
+            .copy_within(position + 1..n, position);
+        allowlist.destinations[n - 1] = Pubkey::default();
+        allowlist.active_timestamps[n - 1] = 0;
+        allowlist.n -= 1;
+
+        DestinationAllowlist::pack(allowlist, &mut allowlist_info.data.borrow_mut())?;
+
+        Ok(())
+    }
+
+    /// Processes a [PlaceHold](enum.TokenInstruction.html) instruction.
+    pub fn process_place_hold(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        capturer: Pubkey,
+        amount: u64,
+        expiry: UnixTimestamp,
+        proposal: Option<&MultisigProposal>,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let account_info = next_account_info(account_info_iter)?;
+        let owner_info = next_account_info(account_info_iter)?;
+
+        let mut account = Account::unpack(&account_info.data.borrow())?;
+
+        Self::thaw_if_expired(&mut account, accounts)?;
+        if account.is_frozen() {
+            return Err(TokenError::AccountFrozen.into());
+        }
+        if account.has_hold() {
+            return Err(TokenError::HoldExists.into());
+        }
+        if account.available_amount() < amount {
+            return Err(TokenError::InsufficientFunds.into());
+        }
+
+        Self::validate_owner_with_proposal(
+            program_id,
+            &account.owner,
+            owner_info,
+            account_info_iter.as_slice(),
+            proposal,
+        )?;
+
+        account.hold_capturer = COption::Some(capturer);
+        account.held_amount = amount;
+        account.hold_expiry = expiry;
+
+        Account::pack(account, &mut account_info.data.borrow_mut())?;
+
+        Ok(())
+    }
+
+    /// Processes a [CaptureHold](enum.TokenInstruction.html) instruction.
+    pub fn process_capture_hold(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        amount: u64,
+        proposal: Option<&MultisigProposal>,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let source_account_info = next_account_info(account_info_iter)?;
+        let dest_account_info = next_account_info(account_info_iter)?;
+        let clock = Clock::from_account_info(next_account_info(account_info_iter)?)?;
+        let capturer_info = next_account_info(account_info_iter)?;
+
+        let mut source_account = Account::unpack(&source_account_info.data.borrow())?;
+        let capturer = source_account
+            .hold_capturer
+            .ok_or(TokenError::HoldNotFound)?;
+        Self::validate_owner_with_proposal(
+            program_id,
+            &capturer,
+            capturer_info,
+            account_info_iter.as_slice(),
+            proposal,
+        )?;
+
+        if clock.unix_timestamp >= source_account.hold_expiry {
+            return Err(TokenError::HoldExpired.into());
+        }
+        if source_account.held_amount < amount {
+            return Err(TokenError::InsufficientFunds.into());
+        }
+
+        // Any remainder stays held until the hold expires
+        source_account.held_amount = source_account
+            .held_amount
+            .checked_sub(amount)
+            .ok_or(TokenError::Overflow)?;
+        if source_account.held_amount == 0 {
+            Self::clear_hold(&mut source_account);
+        }
+
+        Self::thaw_if_expired(&mut source_account, accounts)?;
+        if source_account_info.key == dest_account_info.key {
+            if source_account.is_frozen() {
+                return Err(TokenError::AccountFrozen.into());
+            }
+            Account::pack(source_account, &mut source_account_info.data.borrow_mut())?;
+            return Ok(());
+        }
+
+        let mut dest_account = Account::unpack(&dest_account_info.data.borrow())?;
+        Self::thaw_if_expired(&mut dest_account, accounts)?;
+        if !source_account.can_send() || !dest_account.can_receive() {
+            return Err(TokenError::AccountFrozen.into());
+        }
+        if source_account.mint != dest_account.mint {
+            return Err(TokenError::MintMismatch.into());
+        }
+
+        Self::complete_transfer(
+            program_id,
+            source_account_info,
+            dest_account_info,
+            source_account,
+            dest_account,
+            amount,
+            accounts,
         )
-        .unwrap();
-        instruction.accounts[2].is_signer = false;
-        assert_eq!(
-            Err(ProgramError::MissingRequiredSignature),
-            do_process_instruction(
-                instruction,
-                vec![
-                    &mut account_account,
-                    &mut delegate_account,
-                    &mut owner_account,
-                ],
-            )
-        );
+    }
 
-        // no owner
-        assert_eq!(
-            Err(TokenError::OwnerMismatch.into()),
-            do_process_instruction(
-                approve(
+    /// Processes a [ReleaseHold](enum.TokenInstruction.html) instruction.
+    pub fn process_release_hold(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        proposal: Option<&MultisigProposal>,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let account_info = next_account_info(account_info_iter)?;
+        let clock = Clock::from_account_info(next_account_info(account_info_iter)?)?;
+
+        let mut account = Account::unpack(&account_info.data.borrow())?;
+        let capturer = account.hold_capturer.ok_or(TokenError::HoldNotFound)?;
+
+        if clock.unix_timestamp < account.hold_expiry {
+            let capturer_info = next_account_info(account_info_iter)?;
+            Self::validate_owner_with_proposal(
+                program_id,
+                &capturer,
+                capturer_info,
+                account_info_iter.as_slice(),
+                proposal,
+            )?;
+        }
+
+        Self::clear_hold(&mut account);
+
+        Account::pack(account, &mut account_info.data.borrow_mut())?;
+
+        Ok(())
+    }
+
+    /// Processes an [InitializeNonce](enum.TokenInstruction.html) instruction.
+    pub fn process_initialize_nonce(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let nonce_info = next_account_info(account_info_iter)?;
+        let owner_info = next_account_info(account_info_iter)?;
+        let payer_info = next_account_info(account_info_iter)?;
+        let system_program_info = next_account_info(account_info_iter)?;
+        let rent = &Rent::from_account_info(next_account_info(account_info_iter)?)?;
+
+        let (nonce_address, bump_seed) =
+            get_nonce_address_and_bump_seed(owner_info.key, program_id);
+        if nonce_address != *nonce_info.key {
+            return Err(ProgramError::InvalidSeeds);
+        }
+
+        if nonce_info.data_len() == 0 {
+            Self::create_pda_account(
+                program_id,
+                payer_info,
+                nonce_info,
+                system_program_info,
+                rent,
+                AuthorizationNonce::LEN,
+                &[NONCE_SEED, owner_info.key.as_ref(), &[bump_seed]],
+            )?;
+        }
+        if program_id != nonce_info.owner {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+
+        let mut nonce = AuthorizationNonce::unpack_unchecked(&nonce_info.data.borrow())?;
+        if nonce.is_initialized {
+            return Err(TokenError::AlreadyInUse.into());
+        }
+
+        nonce.owner = *owner_info.key;
+        nonce.nonce = 0;
+        nonce.bump_seed = bump_seed;
+        nonce.is_initialized = true;
+
+        AuthorizationNonce::pack(nonce, &mut nonce_info.data.borrow_mut())?;
+
+        Ok(())
+    }
+
+    /// Processes a [Permit](enum.TokenInstruction.html) instruction.
+    pub fn process_permit(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        amount: u64,
+        nonce: u64,
+        valid_after: UnixTimestamp,
+        valid_until: UnixTimestamp,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let source_account_info = next_account_info(account_info_iter)?;
+        let delegate_info = next_account_info(account_info_iter)?;
+
+        let mut source_account = Account::unpack(&source_account_info.data.borrow())?;
+
+        Self::thaw_if_expired(&mut source_account, accounts)?;
+        if source_account.is_frozen() {
+            return Err(TokenError::AccountFrozen.into());
+        }
+
+        let message = authorization_message(
+            program_id,
+            &TokenInstruction::Permit {
+                amount,
+                nonce,
+                valid_after,
+                valid_until,
+            },
+            source_account_info.key,
+            delegate_info.key,
+        );
+        Self::consume_authorization(
+            program_id,
+            &source_account.owner,
+            &message,
+            nonce,
+            valid_after,
+            valid_until,
+            account_info_iter,
+        )?;
+
+        Self::clear_delegation(&mut source_account);
+        source_account.delegate = COption::Some(*delegate_info.key);
+        source_account.delegated_amount = amount;
+
+        Account::pack(source_account, &mut source_account_info.data.borrow_mut())?;
+
+        Ok(())
+    }
+
+    /// Processes a [TransferWithAuthorization](enum.TokenInstruction.html) instruction.
+    pub fn process_transfer_with_authorization(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        amount: u64,
+        nonce: u64,
+        valid_after: UnixTimestamp,
+        valid_until: UnixTimestamp,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let source_account_info = next_account_info(account_info_iter)?;
+        let dest_account_info = next_account_info(account_info_iter)?;
+
+        let mut source_account = Account::unpack(&source_account_info.data.borrow())?;
+        let mut dest_account = Account::unpack(&dest_account_info.data.borrow())?;
+
+        Self::thaw_if_expired(&mut source_account, accounts)?;
+        Self::thaw_if_expired(&mut dest_account, accounts)?;
+        if !source_account.can_send() || !dest_account.can_receive() {
+            return Err(TokenError::AccountFrozen.into());
+        }
+        if source_account.available_amount() < amount {
+            return Err(TokenError::InsufficientFunds.into());
+        }
+        if source_account.mint != dest_account.mint {
+            return Err(TokenError::MintMismatch.into());
+        }
+
+        let message = authorization_message(
+            program_id,
+            &TokenInstruction::TransferWithAuthorization {
+                amount,
+                nonce,
+                valid_after,
+                valid_until,
+            },
+            source_account_info.key,
+            dest_account_info.key,
+        );
+        Self::consume_authorization(
+            program_id,
+            &source_account.owner,
+            &message,
+            nonce,
+            valid_after,
+            valid_until,
+            account_info_iter,
+        )?;
+
+        if source_account_info.key == dest_account_info.key {
+            return Ok(());
+        }
+
+        Self::complete_transfer(
+            program_id,
+            source_account_info,
+            dest_account_info,
+            source_account,
+            dest_account,
+            amount,
+            accounts,
+        )
+    }
+
+    /// Checks an off-chain signed authorization by `owner` against its validity window, the
+    /// owner's nonce and the ed25519 signatures verified earlier in the transaction, then advances
+    /// the nonce. Consumes the nonce, Instructions sysvar and Clock sysvar accounts.
+    fn consume_authorization(
+        program_id: &Pubkey,
+        owner: &Pubkey,
+        message: &[u8],
+        nonce: u64,
+        valid_after: UnixTimestamp,
+        valid_until: UnixTimestamp,
+        account_info_iter: &mut std::slice::Iter<AccountInfo>,
+    ) -> ProgramResult {
+        let nonce_info = next_account_info(account_info_iter)?;
+        let instructions_info = next_account_info(account_info_iter)?;
+        let clock = Clock::from_account_info(next_account_info(account_info_iter)?)?;
+
+        if clock.unix_timestamp < valid_after || clock.unix_timestamp > valid_until {
+            return Err(TokenError::AuthorizationNotValid.into());
+        }
+
+        if program_id != nonce_info.owner {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+        let mut authorization_nonce = AuthorizationNonce::unpack(&nonce_info.data.borrow())?;
+        if authorization_nonce.owner != *owner {
+            return Err(TokenError::OwnerMismatch.into());
+        }
+        if authorization_nonce.nonce != nonce {
+            return Err(TokenError::InvalidNonce.into());
+        }
+
+        Self::verify_ed25519_signature(instructions_info, owner, message)?;
+
+        authorization_nonce.nonce = nonce.checked_add(1).ok_or(TokenError::Overflow)?;
+        AuthorizationNonce::pack(authorization_nonce, &mut nonce_info.data.borrow_mut())?;
+
+        Ok(())
+    }
+
+    /// Checks that an ed25519 program instruction preceding the current one verified a
+    /// signature by `signer` over `message`. Only signatures whose public key and message are
+    /// held in the ed25519 instruction's own data are considered.
+    fn verify_ed25519_signature(
+        instructions_info: &AccountInfo,
+        signer: &Pubkey,
+        message: &[u8],
+    ) -> ProgramResult {
+        if !sysvar::instructions::check_id(instructions_info.key) {
+            return Err(ProgramError::InvalidArgument);
+        }
+        let current_index = sysvar::instructions::load_current_index_checked(instructions_info)?;
+        for index in 0..current_index {
+            let instruction = sysvar::instructions::load_instruction_at_checked(
+                index as usize,
+                instructions_info,
+            )?;
+            if instruction.program_id == ed25519_program::id()
+                && Self::ed25519_instruction_verifies(&instruction.data, signer, message)
+            {
+                return Ok(());
+            }
+        }
+        Err(TokenError::MissingAuthorizationSignature.into())
+    }
+
+    /// Checks whether ed25519 program instruction data includes a signature by `signer` over
+    /// `message`
+    fn ed25519_instruction_verifies(data: &[u8], signer: &Pubkey, message: &[u8]) -> bool {
+        // Each signature is described by seven u16 offsets following a two byte header:
+        // signature offset and instruction index, public key offset and instruction index,
+        // message offset, size and instruction index. An instruction index of u16::MAX refers
+        // to the ed25519 instruction itself.
+        const HEADER_LEN: usize = 2;
+        const OFFSETS_LEN: usize = 14;
+        let num_signatures = data.first().copied().unwrap_or(0) as usize;
+        (0..num_signatures).any(|i| {
+            let start = HEADER_LEN + i * OFFSETS_LEN;
+            let offsets = match data.get(start..start + OFFSETS_LEN) {
+                Some(offsets) => offsets,
+                None => return false,
+            };
+            let read = |at: usize| u16::from_le_bytes([offsets[at], offsets[at + 1]]);
+            if read(2) != u16::MAX || read(6) != u16::MAX || read(12) != u16::MAX {
+                return false;
+            }
+            let public_key_offset = read(4) as usize;
+            let message_offset = read(8) as usize;
+            let message_size = read(10) as usize;
+            data.get(public_key_offset..public_key_offset + 32) == Some(signer.as_ref())
+                && data.get(message_offset..message_offset + message_size) == Some(message)
+        })
+    }
+
+    /// Processes an [InitializeEthereumOwner](enum.TokenInstruction.html) instruction.
+    pub fn process_initialize_ethereum_owner(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        address: EthereumAddress,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let owner_info = next_account_info(account_info_iter)?;
+        let payer_info = next_account_info(account_info_iter)?;
+        let system_program_info = next_account_info(account_info_iter)?;
+        let rent = &Rent::from_account_info(next_account_info(account_info_iter)?)?;
+
+        let (owner_address, bump_seed) =
+            get_ethereum_owner_address_and_bump_seed(&address, program_id);
+        if owner_address != *owner_info.key {
+            return Err(ProgramError::InvalidSeeds);
+        }
+
+        if owner_info.data_len() == 0 {
+            Self::create_pda_account(
+                program_id,
+                payer_info,
+                owner_info,
+                system_program_info,
+                rent,
+                EthereumOwner::LEN,
+                &[ETHEREUM_OWNER_SEED, &address, &[bump_seed]],
+            )?;
+        }
+        if program_id != owner_info.owner {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+
+        let mut ethereum_owner = EthereumOwner::unpack_unchecked(&owner_info.data.borrow())?;
+        if ethereum_owner.is_initialized {
+            return Err(TokenError::AlreadyInUse.into());
+        }
+
+        ethereum_owner.address = address;
+        ethereum_owner.nonce = 0;
+        ethereum_owner.bump_seed = bump_seed;
+        ethereum_owner.is_initialized = true;
+
+        EthereumOwner::pack(ethereum_owner, &mut owner_info.data.borrow_mut())?;
+
+        Ok(())
+    }
+
+    /// Spends `amount` of the allowance held by `allowance_info` on the source account, after
+    /// validating the spender from the accounts that follow it. Allowances granted by a previous
+    /// owner of the source account can't be spent.
+    #[allow(clippy::too_many_arguments)]
+    fn spend_allowance(
+        program_id: &Pubkey,
+        source_account_key: &Pubkey,
+        source_owner: &Pubkey,
+        allowance_info: &AccountInfo,
+        account_info_iter: &mut std::slice::Iter<AccountInfo>,
+        amount: u64,
+        debit: bool,
+        proposal: Option<&MultisigProposal>,
+    ) -> ProgramResult {
+        let mut allowance = Allowance::unpack(&allowance_info.data.borrow())?;
+        if source_account_key != &allowance.account {
+            return Err(TokenError::AllowanceMismatch.into());
+        }
+        if source_owner != &allowance.owner {
+            return Err(TokenError::AllowanceOwnerChanged.into());
+        }
+        let spender_info = next_account_info(account_info_iter)?;
+        Self::validate_owner_with_proposal(
+            program_id,
+            &allowance.spender,
+            spender_info,
+            account_info_iter.as_slice(),
+            proposal,
+        )?;
+
+        if allowance.amount < amount {
+            return Err(TokenError::InsufficientFunds.into());
+        }
+        if debit {
+            allowance.amount = allowance
+                .amount
+                .checked_sub(amount)
+                .ok_or(TokenError::Overflow)?;
+            Allowance::pack(allowance, &mut allowance_info.data.borrow_mut())?;
+        }
+
+        Ok(())
+    }
+
+    /// Processes an [Instruction](enum.Instruction.html).
+    pub fn process(program_id: &Pubkey, accounts: &[AccountInfo], input: &[u8]) -> ProgramResult {
+        Self::process_instruction(program_id, accounts, input, None)?;
+        Self::advance_ethereum_nonces(program_id, accounts)
+    }
+
+    /// Processes an instruction, approved by the multisignature of `proposal` if it is the
+    /// instruction of a proposal under execution
+    fn process_instruction(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        input: &[u8],
+        proposal: Option<&MultisigProposal>,
+    ) -> ProgramResult {
+        let instruction = TokenInstruction::unpack(input)?;
+
+        match instruction {
+            TokenInstruction::InitializeMint {
+                decimals,
+                mint_authority,
+                freeze_authority,
+            } => {
+                msg!("Instruction: InitializeMint");
+                Self::process_initialize_mint(accounts, decimals, mint_authority, freeze_authority)
+            }
+            TokenInstruction::InitializeAccount => {
+                msg!("Instruction: InitializeAccount");
+                Self::process_initialize_account(accounts)
+            }
+            TokenInstruction::InitializeAccount2 { owner } => {
+                msg!("Instruction: InitializeAccount2");
+                Self::process_initialize_account2(accounts, owner)
+            }
+            TokenInstruction::InitializeMultisig { m } => {
+                msg!("Instruction: InitializeMultisig");
+                Self::process_initialize_multisig(accounts, m)
+            }
+            TokenInstruction::Transfer { amount } => {
+                msg!("Instruction: Transfer");
+                Self::process_transfer(program_id, accounts, amount, None, proposal)
+            }
+            TokenInstruction::Approve { amount } => {
+                msg!("Instruction: Approve");
+                Self::process_approve(
+                    program_id,
+                    accounts,
+                    amount,
+                    None,
+                    0,
+                    COption::None,
+                    proposal,
+                )
+            }
+            TokenInstruction::Revoke => {
+                msg!("Instruction: Revoke");
+                Self::process_revoke(program_id, accounts, proposal)
+            }
+            TokenInstruction::SetAuthority {
+                authority_type,
+                new_authority,
+            } => {
+                msg!("Instruction: SetAuthority");
+                Self::process_set_authority(
+                    program_id,
+                    accounts,
+                    authority_type,
+                    new_authority,
+                    proposal,
+                )
+            }
+            TokenInstruction::MintTo { amount } => {
+                msg!("Instruction: MintTo");
+                Self::process_mint_to(program_id, accounts, amount, None, proposal)
+            }
+            TokenInstruction::Burn { amount } => {
+                msg!("Instruction: Burn");
+                Self::process_burn(program_id, accounts, amount, None, proposal)
+            }
+            TokenInstruction::CloseAccount => {
+                msg!("Instruction: CloseAccount");
+                Self::process_close_account(program_id, accounts, proposal)
+            }
+            TokenInstruction::FreezeAccount => {
+                msg!("Instruction: FreezeAccount");
+                Self::process_set_account_state(
+                    program_id,
+                    accounts,
+                    AccountState::Frozen,
+                    0,
+                    0,
+                    proposal,
+                )
+            }
+            TokenInstruction::ThawAccount => {
+                msg!("Instruction: ThawAccount");
+                Self::process_set_account_state(
+                    program_id,
+                    accounts,
+                    AccountState::Initialized,
+                    0,
+                    0,
+                    proposal,
+                )
+            }
+            TokenInstruction::TransferChecked { amount, decimals } => {
+                msg!("Instruction: TransferChecked");
+                Self::process_transfer(program_id, accounts, amount, Some(decimals), proposal)
+            }
+            TokenInstruction::ApproveChecked { amount, decimals } => {
+                msg!("Instruction: ApproveChecked");
+                Self::process_approve(
+                    program_id,
+                    accounts,
+                    amount,
+                    Some(decimals),
+                    0,
+                    COption::None,
+                    proposal,
+                )
+            }
+            TokenInstruction::MintToChecked { amount, decimals } => {
+                msg!("Instruction: MintToChecked");
+                Self::process_mint_to(program_id, accounts, amount, Some(decimals), proposal)
+            }
+            TokenInstruction::BurnChecked { amount, decimals } => {
+                msg!("Instruction: BurnChecked");
+                Self::process_burn(program_id, accounts, amount, Some(decimals), proposal)
+            }
+            TokenInstruction::InitializeMinter {
+                authority,
+                allowance,
+            } => {
+                msg!("Instruction: InitializeMinter");
+                Self::process_initialize_minter(
+                    program_id, accounts, authority, allowance, proposal,
+                )
+            }
+            TokenInstruction::SetMinterAllowance { allowance } => {
+                msg!("Instruction: SetMinterAllowance");
+                Self::process_set_minter_allowance(program_id, accounts, allowance, proposal)
+            }
+            TokenInstruction::RevokeMinter => {
+                msg!("Instruction: RevokeMinter");
+                Self::process_revoke_minter(program_id, accounts, proposal)
+            }
+            TokenInstruction::SetMintRole {
+                role,
+                new_authority,
+            } => {
+                msg!("Instruction: SetMintRole");
+                Self::process_set_mint_role(program_id, accounts, role, new_authority, proposal)
+            }
+            TokenInstruction::PauseMinting => {
+                msg!("Instruction: PauseMinting");
+                Self::process_toggle_pause_minting(program_id, accounts, true, proposal)
+            }
+            TokenInstruction::ResumeMinting => {
+                msg!("Instruction: ResumeMinting");
+                Self::process_toggle_pause_minting(program_id, accounts, false, proposal)
+            }
+            TokenInstruction::ProposeAuthority {
+                authority_type,
+                new_authority,
+            } => {
+                msg!("Instruction: ProposeAuthority");
+                Self::process_propose_authority(
+                    program_id,
+                    accounts,
+                    authority_type,
+                    COption::Some(new_authority),
+                    proposal,
+                )
+            }
+            TokenInstruction::AcceptAuthority { authority_type } => {
+                msg!("Instruction: AcceptAuthority");
+                Self::process_accept_authority(program_id, accounts, authority_type, proposal)
+            }
+            TokenInstruction::CancelAuthorityProposal { authority_type } => {
+                msg!("Instruction: CancelAuthorityProposal");
+                Self::process_propose_authority(
+                    program_id,
+                    accounts,
+                    authority_type,
+                    COption::None,
+                    proposal,
+                )
+            }
+            TokenInstruction::SetAuthorityTimelock { delay } => {
+                msg!("Instruction: SetAuthorityTimelock");
+                Self::process_set_authority_timelock(program_id, accounts, delay, proposal)
+            }
+            TokenInstruction::QueueAuthorityChange {
+                authority,
+                new_authority,
+            } => {
+                msg!("Instruction: QueueAuthorityChange");
+                Self::process_queue_authority_change(
+                    program_id,
+                    accounts,
+                    authority,
+                    new_authority,
+                    proposal,
+                )
+            }
+            TokenInstruction::ExecuteAuthorityChange => {
+                msg!("Instruction: ExecuteAuthorityChange");
+                Self::process_execute_authority_change(accounts)
+            }
+            TokenInstruction::CancelAuthorityChange => {
+                msg!("Instruction: CancelAuthorityChange");
+                Self::process_cancel_authority_change(program_id, accounts, proposal)
+            }
+            TokenInstruction::SetGuardians {
+                guardians,
+                recovery_delay,
+            } => {
+                msg!("Instruction: SetGuardians");
+                Self::process_set_guardians(
+                    program_id,
+                    accounts,
+                    guardians,
+                    recovery_delay,
+                    proposal,
+                )
+            }
+            TokenInstruction::InitiateRecovery { new_owner } => {
+                msg!("Instruction: InitiateRecovery");
+                Self::process_initiate_recovery(program_id, accounts, new_owner, proposal)
+            }
+            TokenInstruction::CompleteRecovery => {
+                msg!("Instruction: CompleteRecovery");
+                Self::process_complete_recovery(accounts)
+            }
+            TokenInstruction::VetoRecovery => {
+                msg!("Instruction: VetoRecovery");
+                Self::process_veto_recovery(program_id, accounts, proposal)
+            }
+            TokenInstruction::ApproveWithLimits {
+                amount,
+                expiry,
+                max_per_transfer,
+            } => {
+                msg!("Instruction: ApproveWithLimits");
+                Self::process_approve(
+                    program_id,
+                    accounts,
+                    amount,
+                    None,
+                    expiry,
+                    max_per_transfer,
+                    proposal,
+                )
+            }
+            TokenInstruction::SetAllowance { amount } => {
+                msg!("Instruction: SetAllowance");
+                Self::process_set_allowance(program_id, accounts, amount, proposal)
+            }
+            TokenInstruction::CloseAllowance => {
+                msg!("Instruction: CloseAllowance");
+                Self::process_close_allowance(program_id, accounts, proposal)
+            }
+            TokenInstruction::ApproveIfCurrent {
+                expected_delegate,
+                expected_amount,
+                amount,
+            } => {
+                msg!("Instruction: ApproveIfCurrent");
+                Self::process_approve_if_current(
+                    program_id,
+                    accounts,
+                    expected_delegate,
+                    expected_amount,
+                    amount,
+                    proposal,
+                )
+            }
+            TokenInstruction::IncreaseAllowance { amount } => {
+                msg!("Instruction: IncreaseAllowance");
+                Self::process_change_allowance(program_id, accounts, amount, true, proposal)
+            }
+            TokenInstruction::DecreaseAllowance { amount } => {
+                msg!("Instruction: DecreaseAllowance");
+                Self::process_change_allowance(program_id, accounts, amount, false, proposal)
+            }
+            TokenInstruction::CreateSession {
+                session_key,
+                expiry,
+                scope,
+                destinations,
+                max_amount,
+            } => {
+                msg!("Instruction: CreateSession");
+                Self::process_create_session(
+                    program_id,
+                    accounts,
+                    session_key,
+                    expiry,
+                    scope,
+                    &destinations,
+                    max_amount,
+                    proposal,
+                )
+            }
+            TokenInstruction::RevokeSession => {
+                msg!("Instruction: RevokeSession");
+                Self::process_revoke_session(program_id, accounts, proposal)
+            }
+            TokenInstruction::InitializeEthereumOwner { address } => {
+                msg!("Instruction: InitializeEthereumOwner");
+                Self::process_initialize_ethereum_owner(program_id, accounts, address)
+            }
+            TokenInstruction::InitializeWeightedMultisig { threshold, weights } => {
+                msg!("Instruction: InitializeWeightedMultisig");
+                Self::process_initialize_weighted_multisig(accounts, threshold, &weights)
+            }
+            TokenInstruction::AddSigner { signer } => {
+                msg!("Instruction: AddSigner");
+                Self::process_add_signer(program_id, accounts, signer, proposal)
+            }
+            TokenInstruction::RemoveSigner { signer } => {
+                msg!("Instruction: RemoveSigner");
+                Self::process_remove_signer(program_id, accounts, signer, proposal)
+            }
+            TokenInstruction::SetThreshold { m } => {
+                msg!("Instruction: SetThreshold");
+                Self::process_set_threshold(program_id, accounts, m, proposal)
+            }
+            TokenInstruction::CreateProposal {
+                expiry,
+                instruction_data,
+            } => {
+                msg!("Instruction: CreateProposal");
+                Self::process_create_proposal(program_id, accounts, expiry, &instruction_data)
+            }
+            TokenInstruction::ApproveProposal => {
+                msg!("Instruction: ApproveProposal");
+                Self::process_approve_proposal(accounts)
+            }
+            TokenInstruction::ExecuteProposal => {
+                msg!("Instruction: ExecuteProposal");
+                Self::process_execute_proposal(program_id, accounts)
+            }
+            TokenInstruction::CancelProposal => {
+                msg!("Instruction: CancelProposal");
+                Self::process_cancel_proposal(program_id, accounts)
+            }
+            TokenInstruction::PlaceHold {
+                capturer,
+                amount,
+                expiry,
+            } => {
+                msg!("Instruction: PlaceHold");
+                Self::process_place_hold(program_id, accounts, capturer, amount, expiry, proposal)
+            }
+            TokenInstruction::CaptureHold { amount } => {
+                msg!("Instruction: CaptureHold");
+                Self::process_capture_hold(program_id, accounts, amount, proposal)
+            }
+            TokenInstruction::ReleaseHold => {
+                msg!("Instruction: ReleaseHold");
+                Self::process_release_hold(program_id, accounts, proposal)
+            }
+            TokenInstruction::SetReceiveOnly => {
+                msg!("Instruction: SetReceiveOnly");
+                Self::process_set_account_state(
+                    program_id,
+                    accounts,
+                    AccountState::ReceiveOnly,
+                    0,
+                    0,
+                    proposal,
+                )
+            }
+            TokenInstruction::SetSendOnly => {
+                msg!("Instruction: SetSendOnly");
+                Self::process_set_account_state(
+                    program_id,
+                    accounts,
+                    AccountState::SendOnly,
+                    0,
+                    0,
+                    proposal,
+                )
+            }
+            TokenInstruction::FreezeAccountWithReason {
+                reason,
+                auto_thaw_timestamp,
+            } => {
+                msg!("Instruction: FreezeAccountWithReason");
+                Self::process_set_account_state(
+                    program_id,
+                    accounts,
+                    AccountState::Frozen,
+                    reason,
+                    auto_thaw_timestamp,
+                    proposal,
+                )
+            }
+            TokenInstruction::FreezeAccounts { count } => {
+                msg!("Instruction: FreezeAccounts");
+                Self::process_set_accounts_state(
+                    program_id,
+                    accounts,
+                    AccountState::Frozen,
+                    count,
+                    proposal,
+                )
+            }
+            TokenInstruction::ThawAccounts { count } => {
+                msg!("Instruction: ThawAccounts");
+                Self::process_set_accounts_state(
+                    program_id,
+                    accounts,
+                    AccountState::Initialized,
+                    count,
+                    proposal,
+                )
+            }
+            TokenInstruction::SetSpendingLimit { amount, window } => {
+                msg!("Instruction: SetSpendingLimit");
+                Self::process_set_spending_limit(program_id, accounts, amount, window, proposal)
+            }
+            TokenInstruction::InitializeDestinationAllowlist => {
+                msg!("Instruction: InitializeDestinationAllowlist");
+                Self::process_initialize_destination_allowlist(program_id, accounts, proposal)
+            }
+            TokenInstruction::AddAllowedDestination { destination } => {
+                msg!("Instruction: AddAllowedDestination");
+                Self::process_add_allowed_destination(program_id, accounts, destination, proposal)
+            }
+            TokenInstruction::RemoveAllowedDestination { destination } => {
+                msg!("Instruction: RemoveAllowedDestination");
+                Self::process_remove_allowed_destination(
+                    program_id,
+                    accounts,
+                    destination,
+                    proposal,
+                )
+            }
+            TokenInstruction::SetMaxBalance { max_balance } => {
+                msg!("Instruction: SetMaxBalance");
+                Self::process_set_max_balance(program_id, accounts, max_balance, proposal)
+            }
+            TokenInstruction::AddMaxBalanceExemption => {
+                msg!("Instruction: AddMaxBalanceExemption");
+                Self::process_toggle_max_balance_exemption(program_id, accounts, true, proposal)
+            }
+            TokenInstruction::RemoveMaxBalanceExemption => {
+                msg!("Instruction: RemoveMaxBalanceExemption");
+                Self::process_toggle_max_balance_exemption(program_id, accounts, false, proposal)
+            }
+            TokenInstruction::SetLaunchControls {
+                trading_start_timestamp,
+                transfer_cooldown,
+            } => {
+                msg!("Instruction: SetLaunchControls");
+                Self::process_set_launch_controls(
+                    program_id,
+                    accounts,
+                    trading_start_timestamp,
+                    transfer_cooldown,
+                    proposal,
+                )
+            }
+            TokenInstruction::IssueCredential {
+                expiry,
+                jurisdiction,
+            } => {
+                msg!("Instruction: IssueCredential");
+                Self::process_issue_credential(program_id, accounts, expiry, jurisdiction, proposal)
+            }
+            TokenInstruction::RevokeCredential => {
+                msg!("Instruction: RevokeCredential");
+                Self::process_revoke_credential(program_id, accounts, proposal)
+            }
+            TokenInstruction::SetMinTransferAmount { amount } => {
+                msg!("Instruction: SetMinTransferAmount");
+                Self::process_set_min_transfer_amount(program_id, accounts, amount, proposal)
+            }
+            TokenInstruction::SetMinIncomingAmount { amount } => {
+                msg!("Instruction: SetMinIncomingAmount");
+                Self::process_set_min_incoming_amount(program_id, accounts, amount, proposal)
+            }
+            TokenInstruction::InitializeNonce => {
+                msg!("Instruction: InitializeNonce");
+                Self::process_initialize_nonce(program_id, accounts)
+            }
+            TokenInstruction::Permit {
+                amount,
+                nonce,
+                valid_after,
+                valid_until,
+            } => {
+                msg!("Instruction: Permit");
+                Self::process_permit(
+                    program_id,
+                    accounts,
+                    amount,
+                    nonce,
+                    valid_after,
+                    valid_until,
+                )
+            }
+            TokenInstruction::TransferWithAuthorization {
+                amount,
+                nonce,
+                valid_after,
+                valid_until,
+            } => {
+                msg!("Instruction: TransferWithAuthorization");
+                Self::process_transfer_with_authorization(
+                    program_id,
+                    accounts,
+                    amount,
+                    nonce,
+                    valid_after,
+                    valid_until,
+                )
+            }
+        }
+    }
+
+    /// Advances the nonce of every Ethereum owner that authorized the instruction, once the
+    /// instruction has succeeded, so that its authorization signature cannot be replayed
+    fn advance_ethereum_nonces(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+        let mut advanced: Vec<&Pubkey> = Vec::new();
+        for account_info in accounts {
+            if advanced.contains(&account_info.key)
+                || AuthorityKind::resolve(program_id, account_info) != AuthorityKind::EthereumOwner
+                || AuthorityKind::EthereumOwner
+                    .validate(program_id, account_info, accounts, None)
+                    .is_err()
+            {
+                continue;
+            }
+            let mut ethereum_owner = EthereumOwner::unpack(&account_info.data.borrow())?;
+            ethereum_owner.nonce = ethereum_owner
+                .nonce
+                .checked_add(1)
+                .ok_or(TokenError::Overflow)?;
+            EthereumOwner::pack(ethereum_owner, &mut account_info.data.borrow_mut())?;
+            advanced.push(account_info.key);
+        }
+        Ok(())
+    }
+
+    /// Records a proposed authority, or cancels the pending proposal if no
+    /// authority is given
+    fn update_pending_authority(
+        pending_authority: &mut COption<Pubkey>,
+        new_authority: COption<Pubkey>,
+    ) -> ProgramResult {
+        if new_authority.is_none() && pending_authority.is_none() {
+            return Err(TokenError::NoPendingAuthority.into());
+        }
+        *pending_authority = new_authority;
+        Ok(())
+    }
+
+    /// Returns the authority currently in control of a timelocked mint-level authority. Mint roles
+    /// are controlled by the mint authority.
+    fn current_timelocked_authority(
+        mint: &Mint,
+        authority: TimelockedAuthority,
+    ) -> Result<Pubkey, ProgramError> {
+        match authority {
+            TimelockedAuthority::FreezeAccount => mint
+                .freeze_authority
+                .ok_or_else(|| TokenError::MintCannotFreeze.into()),
+            TimelockedAuthority::None => Err(ProgramError::InvalidArgument),
+            _ => mint
+                .mint_authority
+                .ok_or_else(|| TokenError::FixedSupply.into()),
+        }
+    }
+
+    /// Clears a mint's queued authority change
+    fn clear_queued_authority_change(mint: &mut Mint) {
+        mint.queued_authority = TimelockedAuthority::None;
+        mint.queued_new_authority = COption::None;
+        mint.queued_unlock_timestamp = 0;
+    }
+
+    /// Clears an account's pending recovery
+    fn clear_recovery(account: &mut Account) {
+        account.recovery_owner = COption::None;
+        account.recovery_unlock_timestamp = 0;
+    }
+
+    /// Clears an account's session key
+    fn clear_session(account: &mut Account) {
+        account.session_key = COption::None;
+        account.session_expiry = 0;
+        account.session_scope = 0;
+        account.num_session_destinations = 0;
+        account.session_destinations = Default::default();
+        account.session_remaining_amount = 0;
+    }
+
+    /// Validates the mint's freeze authority, or its freezer while the mint retains a freeze
+    /// authority
+    fn validate_freeze_authority(
+        program_id: &Pubkey,
+        mint: &Mint,
+        authority_info: &AccountInfo,
+        signers: &[AccountInfo],
+        proposal: Option<&MultisigProposal>,
+    ) -> ProgramResult {
+        match mint.freeze_authority {
+            COption::Some(authority) => {
+                // The freezer may only act while the mint retains its freeze authority
+                let authority = match mint.freezer {
+                    COption::Some(freezer) if authority_info.key == &freezer => freezer,
+                    _ => authority,
+                };
+                Self::validate_owner_with_proposal(
+                    program_id,
+                    &authority,
+                    authority_info,
+                    signers,
+                    proposal,
+                )
+            }
+            COption::None => Err(TokenError::MintCannotFreeze.into()),
+        }
+    }
+
+    /// Thaws a frozen account whose auto-thaw timestamp has passed, reading the Clock sysvar
+    /// account if it is among the instruction's accounts and the Clock sysvar directly otherwise
+    fn thaw_if_expired(account: &mut Account, accounts: &[AccountInfo]) -> ProgramResult {
+        if account.is_frozen() && account.auto_thaw_timestamp != 0 {
+            let clock = Self::current_clock(accounts)?;
+            if account.freeze_expired(clock.unix_timestamp) {
+                account.state = AccountState::Initialized;
+                account.freeze_reason = 0;
+                account.auto_thaw_timestamp = 0;
+            }
+        }
+        Ok(())
+    }
+
+    /// Checks the destination against the source account's allowlist, if it has one, using the
+    /// allowlist and Clock sysvar accounts among the instruction's accounts
+    fn check_destination_allowed(
+        program_id: &Pubkey,
+        source_account: &Account,
+        dest_account_info: &AccountInfo,
+        dest_account: &Account,
+        accounts: &[AccountInfo],
+    ) -> ProgramResult {
+        let allowlist_key = match source_account.destination_allowlist {
+            COption::Some(allowlist_key) => allowlist_key,
+            COption::None => return Ok(()),
+        };
+        let allowlist_info = accounts
+            .iter()
+            .find(|account_info| account_info.key == &allowlist_key)
+            .ok_or(ProgramError::NotEnoughAccountKeys)?;
+        if allowlist_info.owner != program_id {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+        let allowlist = DestinationAllowlist::unpack(&allowlist_info.data.borrow())?;
+        let clock = Self::find_clock(accounts)?.ok_or(ProgramError::NotEnoughAccountKeys)?;
+        if !allowlist.allows(
+            dest_account_info.key,
+            &dest_account.owner,
+            clock.unix_timestamp,
+        ) {
+            return Err(TokenError::DestinationNotAllowed.into());
+        }
+        Ok(())
+    }
+
+    /// Checks a transfer against the mint's minimum transfer amount, launch controls, credentials
+    /// and maximum balance, using the mint and Clock sysvar among the instruction's accounts. The
+    /// mint is required for every transfer of non-native tokens, so restrictions apply to all
+    /// accounts as soon as they are set.
+    fn check_mint_restrictions(
+        program_id: &Pubkey,
+        source_account: &mut Account,
+        dest_account: &Account,
+        amount: u64,
+        accounts: &[AccountInfo],
+    ) -> ProgramResult {
+        if dest_account.is_native() {
+            return Ok(());
+        }
+        let mint_info = accounts
+            .iter()
+            .find(|account_info| account_info.key == &dest_account.mint)
+            .ok_or(ProgramError::NotEnoughAccountKeys)?;
+        if mint_info.owner != program_id {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+        let mint = Mint::unpack(&mint_info.data.borrow())?;
+        if amount < mint.min_transfer_amount && amount != source_account.available_amount() {
+            return Err(TokenError::BelowMinimumTransfer.into());
+        }
+        Self::enforce_launch_controls(&mint, source_account, accounts)?;
+        Self::enforce_credentials(
+            program_id,
+            mint_info.key,
+            &mint,
+            &[&source_account.owner, &dest_account.owner],
+            accounts,
+        )?;
+        Self::enforce_max_balance(&mint, dest_account, amount)
+    }
+
+    /// Checks an outgoing transfer against the mint's trading start and transfer cooldown,
+    /// recording the transfer time. Accounts owned by the mint authority are exempt.
+    fn enforce_launch_controls(
+        mint: &Mint,
+        source_account: &mut Account,
+        accounts: &[AccountInfo],
+    ) -> ProgramResult {
+        if mint.trading_start_timestamp == 0 && mint.transfer_cooldown == 0 {
+            return Ok(());
+        }
+        if mint.mint_authority == COption::Some(source_account.owner) {
+            return Ok(());
+        }
+        let clock = Self::find_clock(accounts)?.ok_or(ProgramError::NotEnoughAccountKeys)?;
+        if clock.unix_timestamp < mint.trading_start_timestamp {
+            return Err(TokenError::TradingNotStarted.into());
+        }
+        if mint.transfer_cooldown != 0 {
+            let cooldown_end = source_account
+                .last_transfer_timestamp
+                .saturating_add(mint.transfer_cooldown as UnixTimestamp);
+            if source_account.last_transfer_timestamp != 0 && clock.unix_timestamp < cooldown_end {
+                return Err(TokenError::TransferCooldown.into());
+            }
+            source_account.last_transfer_timestamp = clock.unix_timestamp;
+        }
+        Ok(())
+    }
+
+    /// Checks that each owner holds a valid credential for the mint, if it has a compliance
+    /// authority, using the credentials and Clock sysvar among the instruction's accounts
+    fn enforce_credentials(
+        program_id: &Pubkey,
+        mint_key: &Pubkey,
+        mint: &Mint,
+        owners: &[&Pubkey],
+        accounts: &[AccountInfo],
+    ) -> ProgramResult {
+        if mint.compliance_authority.is_none() {
+            return Ok(());
+        }
+        let clock = Self::find_clock(accounts)?.ok_or(ProgramError::NotEnoughAccountKeys)?;
+        for owner in owners {
+            let has_credential = accounts
+                .iter()
+                .filter(|account_info| Self::is_credential(program_id, account_info))
+                .filter_map(|account_info| Credential::unpack(&account_info.data.borrow()).ok())
+                .any(|credential| {
+                    credential.mint == *mint_key
+                        && credential.owner == **owner
+                        && credential.is_valid(clock.unix_timestamp)
+                });
+            if !has_credential {
+                return Err(TokenError::CredentialRequired.into());
+            }
+        }
+        Ok(())
+    }
+
+    /// Checks the account's balance after receiving `amount` against the mint's maximum balance
+    fn enforce_max_balance(mint: &Mint, account: &Account, amount: u64) -> ProgramResult {
+        if let COption::Some(max_balance) = mint.max_balance {
+            let balance = account
+                .amount
+                .checked_add(amount)
+                .ok_or(TokenError::Overflow)?;
+            if !account.max_balance_exempt && balance > max_balance {
+                return Err(TokenError::MaxBalanceExceeded.into());
+            }
+        }
+        Ok(())
+    }
+
+    /// Counts `amount` against the account's spending limit, if it has one, using the Clock
+    /// sysvar among the instruction's accounts
+    fn spend_within_limit(
+        account: &mut Account,
+        amount: u64,
+        accounts: &[AccountInfo],
+    ) -> ProgramResult {
+        if account.spending_limit.is_none() {
+            return Ok(());
+        }
+        let clock = Self::find_clock(accounts)?.ok_or(ProgramError::NotEnoughAccountKeys)?;
+        Self::apply_pending_spending_limit(account, clock.unix_timestamp);
+
+        let window_end = account
+            .spending_window_start
+            .saturating_add(account.spending_window as UnixTimestamp);
+        if clock.unix_timestamp >= window_end {
+            account.spending_window_start = clock.unix_timestamp;
+            account.spending_window_spent = 0;
+        }
+        let spent = account
+            .spending_window_spent
+            .checked_add(amount)
+            .ok_or(TokenError::Overflow)?;
+        if let COption::Some(limit) = account.spending_limit {
+            if spent > limit {
+                return Err(TokenError::SpendingLimitExceeded.into());
+            }
+        }
+        account.spending_window_spent = spent;
+        Ok(())
+    }
+
+    /// Installs an account's pending spending limit once its unlock timestamp passes
+    fn apply_pending_spending_limit(account: &mut Account, unix_timestamp: UnixTimestamp) {
+        if account.pending_spending_limit.is_some()
+            && unix_timestamp >= account.spending_limit_unlock_timestamp
+        {
+            account.spending_limit = account.pending_spending_limit;
+            account.spending_window = account.pending_spending_window;
+            account.pending_spending_limit = COption::None;
+            account.pending_spending_window = 0;
+            account.spending_limit_unlock_timestamp = 0;
+        }
+    }
+
+    /// Clears an account's hold
+    fn clear_hold(account: &mut Account) {
+        account.hold_capturer = COption::None;
+        account.held_amount = 0;
+        account.hold_expiry = 0;
+    }
+
+    /// Clears an account's delegation
+    fn clear_delegation(account: &mut Account) {
+        account.delegate = COption::None;
+        account.delegated_amount = 0;
+        account.delegate_expiry = 0;
+        account.delegate_transfer_cap = COption::None;
+    }
+
+    /// Finds the Clock sysvar among trailing accounts
+    fn find_clock(accounts: &[AccountInfo]) -> Result<Option<Clock>, ProgramError> {
+        accounts
+            .iter()
+            .find(|account_info| sysvar::clock::check_id(account_info.key))
+            .map(Clock::from_account_info)
+            .transpose()
+    }
+
+    /// Reads the clock from the Clock sysvar account among `accounts`, or from the runtime if it
+    /// isn't passed
+    fn current_clock(accounts: &[AccountInfo]) -> Result<Clock, ProgramError> {
+        match Self::find_clock(accounts)? {
+            Some(clock) => Ok(clock),
+            None => Clock::get(),
+        }
+    }
+
+    /// Checks the expiry and per-transfer cap of a delegate moving `amount` tokens, clearing the
+    /// delegation if it has expired
+    fn check_delegate_limits(
+        account: &mut Account,
+        amount: u64,
+        remaining_accounts: &[AccountInfo],
+    ) -> ProgramResult {
+        Self::clear_expired_delegation(account, remaining_accounts)?;
+        if account.delegate.is_none() {
+            return Err(TokenError::DelegateExpired.into());
+        }
+        if let COption::Some(cap) = account.delegate_transfer_cap {
+            if amount > cap {
+                return Err(TokenError::DelegateTransferCapExceeded.into());
+            }
+        }
+        Ok(())
+    }
+
+    /// Clears an expired delegation, reading the time from the Clock sysvar if it is among the
+    /// trailing accounts or from the runtime otherwise
+    fn clear_expired_delegation(
+        account: &mut Account,
+        remaining_accounts: &[AccountInfo],
+    ) -> ProgramResult {
+        if account.delegate.is_none() || account.delegate_expiry == 0 {
+            return Ok(());
+        }
+        if Self::current_clock(remaining_accounts)?.unix_timestamp >= account.delegate_expiry {
+            Self::clear_delegation(account);
+        }
+        Ok(())
+    }
+
+    /// Checks whether an account is a minter owned by this program
+    fn is_minter(program_id: &Pubkey, account_info: &AccountInfo) -> bool {
+        program_id == account_info.owner && account_info.data_len() == Minter::get_packed_len()
+    }
+
+    /// Creates a program derived address account of `space` bytes owned by this program. An
+    /// address that already holds lamports can't be created with `create_account`, so it is
+    /// funded up to rent exemption, then allocated and assigned instead.
+    fn create_pda_account<'a>(
+        program_id: &Pubkey,
+        payer_info: &AccountInfo<'a>,
+        pda_info: &AccountInfo<'a>,
+        system_program_info: &AccountInfo<'a>,
+        rent: &Rent,
+        space: usize,
+        signer_seeds: &[&[u8]],
+    ) -> ProgramResult {
+        let required_lamports = rent.minimum_balance(space);
+        if pda_info.lamports() == 0 {
+            return invoke_signed(
+                &system_instruction::create_account(
+                    payer_info.key,
+                    pda_info.key,
+                    required_lamports,
+                    space as u64,
+                    program_id,
+                ),
+                &[
+                    payer_info.clone(),
+                    pda_info.clone(),
+                    system_program_info.clone(),
+                ],
+                &[signer_seeds],
+            );
+        }
+
+        let missing_lamports = required_lamports.saturating_sub(pda_info.lamports());
+        if missing_lamports > 0 {
+            invoke(
+                &system_instruction::transfer(payer_info.key, pda_info.key, missing_lamports),
+                &[
+                    payer_info.clone(),
+                    pda_info.clone(),
+                    system_program_info.clone(),
+                ],
+            )?;
+        }
+        invoke_signed(
+            &system_instruction::allocate(pda_info.key, space as u64),
+            &[pda_info.clone(), system_program_info.clone()],
+            &[signer_seeds],
+        )?;
+        invoke_signed(
+            &system_instruction::assign(pda_info.key, program_id),
+            &[pda_info.clone(), system_program_info.clone()],
+            &[signer_seeds],
+        )
+    }
+
+    /// Checks whether an account is an allowance owned by this program
+    fn is_allowance(program_id: &Pubkey, account_info: &AccountInfo) -> bool {
+        program_id == account_info.owner && account_info.data_len() == Allowance::get_packed_len()
+    }
+
+    /// Checks whether an account is a credential owned by this program
+    fn is_credential(program_id: &Pubkey, account_info: &AccountInfo) -> bool {
+        program_id == account_info.owner && account_info.data_len() == Credential::get_packed_len()
+    }
+
+    /// Validates owner(s) are present
+    pub fn validate_owner(
+        program_id: &Pubkey,
+        expected_owner: &Pubkey,
+        owner_account_info: &AccountInfo,
+        signers: &[AccountInfo],
+    ) -> ProgramResult {
+        Self::validate_owner_with_proposal(
+            program_id,
+            expected_owner,
+            owner_account_info,
+            signers,
+            None,
+        )
+    }
+
+    /// Validates owner(s) are present, accepting the approvals of `proposal` in place of the
+    /// signatures of its multisignature's signers when the instruction is the proposal's
+    pub fn validate_owner_with_proposal(
+        program_id: &Pubkey,
+        expected_owner: &Pubkey,
+        owner_account_info: &AccountInfo,
+        signers: &[AccountInfo],
+        proposal: Option<&MultisigProposal>,
+    ) -> ProgramResult {
+        if expected_owner != owner_account_info.key {
+            return Err(TokenError::OwnerMismatch.into());
+        }
+        AuthorityKind::resolve(program_id, owner_account_info).validate(
+            program_id,
+            owner_account_info,
+            signers,
+            proposal,
+        )
+    }
+
+    /// Validates that `owner_account_info` approves the current instruction for the owner of
+    /// `account`, either as the owner itself or as the account's session key acting within
+    /// `session_use`
+    pub fn validate_account_owner(
+        program_id: &Pubkey,
+        account: &mut Account,
+        owner_account_info: &AccountInfo,
+        signers: &[AccountInfo],
+        session_use: SessionUse,
+        proposal: Option<&MultisigProposal>,
+    ) -> ProgramResult {
+        match AuthorityKind::resolve_for_account(program_id, account, owner_account_info) {
+            AuthorityKind::SessionKey => {
+                AuthorityKind::SessionKey.validate(
+                    program_id,
+                    owner_account_info,
+                    signers,
+                    proposal,
+                )?;
+                let clock = Self::find_clock(signers)?.ok_or(ProgramError::NotEnoughAccountKeys)?;
+                session_use.apply(account, clock.unix_timestamp)
+            }
+            _ => Self::validate_owner_with_proposal(
+                program_id,
+                &account.owner,
+                owner_account_info,
+                signers,
+                proposal,
+            ),
+        }
+    }
+}
+
+impl PrintProgramError for TokenError {
+    fn print<E>(&self)
+    where
+        E: 'static + std::error::Error + DecodeError<E> + PrintProgramError + FromPrimitive,
+    {
+        match self {
+            TokenError::NotRentExempt => msg!("Error: Lamport balance below rent-exempt threshold"),
+            TokenError::InsufficientFunds => msg!("Error: insufficient funds"),
+            TokenError::InvalidMint => msg!("Error: Invalid Mint"),
+            TokenError::MintMismatch => msg!("Error: Account not associated with this Mint"),
+            TokenError::OwnerMismatch => msg!("Error: owner does not match"),
+            TokenError::FixedSupply => msg!("Error: the total supply of this token is fixed"),
+            TokenError::AlreadyInUse => msg!("Error: account or token already in use"),
+            TokenError::InvalidNumberOfProvidedSigners => {
+                msg!("Error: Invalid number of provided signers")
+            }
+            TokenError::InvalidNumberOfRequiredSigners => {
+                msg!("Error: Invalid number of required signers")
+            }
+            TokenError::UninitializedState => msg!("Error: State is uninitialized"),
+            TokenError::NativeNotSupported => {
+                msg!("Error: Instruction does not support native tokens")
+            }
+            TokenError::NonNativeHasBalance => {
+                msg!("Error: Non-native account can only be closed if its balance is zero")
+            }
+            TokenError::InvalidInstruction => msg!("Error: Invalid instruction"),
+            TokenError::InvalidState => msg!("Error: Invalid account state for operation"),
+            TokenError::Overflow => msg!("Error: Operation overflowed"),
+            TokenError::AuthorityTypeNotSupported => {
+                msg!("Error: Account does not support specified authority type")
+            }
+            TokenError::MintCannotFreeze => msg!("Error: This token mint cannot freeze accounts"),
+            TokenError::AccountFrozen => msg!("Error: Account is frozen"),
+            TokenError::MintDecimalsMismatch => {
+                msg!("Error: decimals different from the Mint decimals")
+            }
+            TokenError::MintingPaused => msg!("Error: Minting is paused"),
+            TokenError::MintCannotPause => msg!("Error: This token mint cannot pause minting"),
+            TokenError::MinterAllowanceExceeded => msg!("Error: Minter allowance exceeded"),
+            TokenError::NoPendingAuthority => msg!("Error: No pending authority"),
+            TokenError::AuthorityChangeTimelocked => msg!("Error: Authority change is timelocked"),
+            TokenError::AuthorityChangeQueued => {
+                msg!("Error: An authority change is already queued")
+            }
+            TokenError::InvalidAuthorityTimelock => {
+                msg!("Error: Authority timelock may only be increased")
+            }
+            TokenError::NoGuardians => msg!("Error: Account has no guardians"),
+            TokenError::DelegateExpired => msg!("Error: Delegate approval expired"),
+            TokenError::DelegateTransferCapExceeded => {
+                msg!("Error: Delegate transfer cap exceeded")
+            }
+            TokenError::AllowanceMismatch => msg!("Error: Allowance account mismatch"),
+            TokenError::AllowanceOwnerChanged => {
+                msg!("Error: Allowance was granted by a previous account owner")
+            }
+            TokenError::UnexpectedDelegation => msg!("Error: Unexpected delegation"),
+            TokenError::InvalidNonce => msg!("Error: Invalid authorization nonce"),
+            TokenError::AuthorizationNotValid => {
+                msg!("Error: Authorization is outside its validity window")
+            }
+            TokenError::MissingAuthorizationSignature => {
+                msg!("Error: Missing owner signature over the authorization")
+            }
+            TokenError::SessionKeyExpired => msg!("Error: Session key expired"),
+            TokenError::SessionScopeExceeded => {
+                msg!("Error: Operation outside the session key's scope")
+            }
+            TokenError::InvalidWeightThreshold => msg!("Error: Invalid weight threshold"),
+            TokenError::DuplicateSigner => msg!("Error: Duplicate multisignature signer"),
+            TokenError::SignerNotFound => msg!("Error: Not a multisignature signer"),
+            TokenError::ProposalNotPending => msg!("Error: Proposal is not pending"),
+            TokenError::ProposalExpired => msg!("Error: Proposal expired"),
+            TokenError::AlreadyApproved => msg!("Error: Proposal already approved by the signer"),
+            TokenError::ProposalAccountMismatch => {
+                msg!("Error: Account does not match the proposal")
+            }
+            TokenError::ThresholdExceedsSigners => {
+                msg!("Error: Multisignature threshold exceeds its signers")
+            }
+            TokenError::MultisigSelfSigner => {
+                msg!("Error: Multisignature cannot be its own signer")
+            }
+            TokenError::HoldExists => msg!("Error: Account has an outstanding hold"),
+            TokenError::HoldNotFound => msg!("Error: Account has no hold"),
+            TokenError::HoldExpired => msg!("Error: Hold expired"),
+            TokenError::SpendingLimitExceeded => msg!("Error: Spending limit exceeded"),
+            TokenError::DestinationNotAllowed => msg!("Error: Destination not allowed"),
+            TokenError::AllowlistFull => msg!("Error: Destination allowlist is full"),
+            TokenError::DestinationAlreadyAllowed => msg!("Error: Destination already allowed"),
+            TokenError::DestinationNotFound => {
+                msg!("Error: Destination not found on the allowlist")
+            }
+            TokenError::AllowlistMismatch => msg!("Error: Allowlist does not match the account"),
+            TokenError::MaxBalanceExceeded => msg!("Error: Maximum balance exceeded"),
+            TokenError::TradingNotStarted => msg!("Error: Trading has not started"),
+            TokenError::TransferCooldown => msg!("Error: Transfer cooldown has not elapsed"),
+            TokenError::CredentialRequired => msg!("Error: Owner lacks a valid credential"),
+            TokenError::MintCannotIssueCredentials => {
+                msg!("Error: Mint has no compliance authority")
+            }
+            TokenError::BelowMinimumTransfer => {
+                msg!("Error: Transfer amount below the mint's minimum")
+            }
+            TokenError::BelowMinimumIncoming => {
+                msg!("Error: Transfer amount below the destination's minimum")
+            }
+            TokenError::ExtensionRequired => {
+                msg!("Error: Account too small for extension data")
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::instruction::*;
+    use solana_program::{
+        account_info::IntoAccountInfo,
+        clock::Epoch,
+        instruction::{AccountMeta, Instruction},
+        message::Message,
+        secp256k1_program, system_program,
+        sysvar::rent,
+    };
+    use solana_sdk::account::{
+        create_account_for_test, create_is_signer_account_infos, Account as SolanaAccount,
+    };
+    use std::{
+        cell::{Cell, RefCell},
+        collections::HashMap,
+        sync::Once,
+    };
+
+    thread_local! {
+        static SYSCALL_UNIX_TIMESTAMP: Cell<Option<UnixTimestamp>> = const { Cell::new(None) };
+        static INVOKED_INSTRUCTIONS: RefCell<Vec<Instruction>> = const { RefCell::new(Vec::new()) };
+        static RETURN_DATA: RefCell<Vec<u8>> = const { RefCell::new(Vec::new()) };
+    }
+
+    struct SyscallStubs;
+    impl solana_program::program_stubs::SyscallStubs for SyscallStubs {
+        fn sol_invoke_signed(
+            &self,
+            instruction: &Instruction,
+            _account_infos: &[AccountInfo],
+            _signers_seeds: &[&[&[u8]]],
+        ) -> ProgramResult {
+            INVOKED_INSTRUCTIONS.with(|instructions| {
+                instructions.borrow_mut().push(instruction.clone());
+            });
+            Ok(())
+        }
+
+        fn sol_get_return_data(&self) -> Option<(Pubkey, Vec<u8>)> {
+            RETURN_DATA.with(|data| {
+                let data = data.borrow();
+                if data.is_empty() {
+                    None
+                } else {
+                    Some((crate::id(), data.clone()))
+                }
+            })
+        }
+
+        fn sol_set_return_data(&self, data: &[u8]) {
+            RETURN_DATA.with(|return_data| *return_data.borrow_mut() = data.to_vec());
+        }
+
+        #[allow(unsafe_code)]
+        fn sol_get_clock_sysvar(&self, var_addr: *mut u8) -> u64 {
+            match SYSCALL_UNIX_TIMESTAMP.with(Cell::get) {
+                Some(unix_timestamp) => {
+                    unsafe {
+                        *(var_addr as *mut _ as *mut Clock) = Clock {
+                            unix_timestamp,
+                            ..Clock::default()
+                        };
+                    }
+                    solana_program::entrypoint::SUCCESS
+                }
+                None => solana_program::program_error::UNSUPPORTED_SYSVAR,
+            }
+        }
+    }
+
+    fn install_syscall_stubs() {
+        static STUBS: Once = Once::new();
+        STUBS.call_once(|| {
+            solana_program::program_stubs::set_syscall_stubs(Box::new(SyscallStubs));
+        });
+    }
+
+    /// Sets the timestamp `Clock::get` returns on the calling test's thread
+    fn set_syscall_clock(unix_timestamp: UnixTimestamp) {
+        install_syscall_stubs();
+        SYSCALL_UNIX_TIMESTAMP.with(|timestamp| timestamp.set(Some(unix_timestamp)));
+    }
+
+    /// Takes the cross-program invocations made on the calling test's thread, which the stubs
+    /// record without running
+    fn take_invoked_instructions() -> Vec<Instruction> {
+        install_syscall_stubs();
+        INVOKED_INSTRUCTIONS.with(|instructions| instructions.take())
+    }
+
+    fn do_process_instruction(
+        instruction: Instruction,
+        accounts: Vec<&mut SolanaAccount>,
+    ) -> ProgramResult {
+        let mut meta = instruction
+            .accounts
+            .iter()
+            .zip(accounts)
+            .map(|(account_meta, account)| (&account_meta.pubkey, account_meta.is_signer, account))
+            .collect::<Vec<_>>();
+
+        let account_infos = create_is_signer_account_infos(&mut meta);
+        Processor::process(&instruction.program_id, &account_infos, &instruction.data)
+    }
+
+    fn do_process_instruction_dups(
+        instruction: Instruction,
+        account_infos: Vec<AccountInfo>,
+    ) -> ProgramResult {
+        Processor::process(&instruction.program_id, &account_infos, &instruction.data)
+    }
+
+    fn return_token_error_as_program_error() -> ProgramError {
+        TokenError::MintMismatch.into()
+    }
+
+    fn rent_sysvar() -> SolanaAccount {
+        create_account_for_test(&Rent::default())
+    }
+
+    fn clock_sysvar(unix_timestamp: UnixTimestamp) -> SolanaAccount {
+        create_account_for_test(&Clock {
+            unix_timestamp,
+            ..Clock::default()
+        })
+    }
+
+    fn mint_minimum_balance() -> u64 {
+        Rent::default().minimum_balance(Mint::get_packed_len())
+    }
+
+    fn account_minimum_balance() -> u64 {
+        Rent::default().minimum_balance(Account::get_packed_len())
+    }
+
+    fn extended_mint_minimum_balance() -> u64 {
+        Rent::default().minimum_balance(Mint::EXTENDED_LEN)
+    }
+
+    fn extended_account_minimum_balance() -> u64 {
+        Rent::default().minimum_balance(Account::EXTENDED_LEN)
+    }
+
+    fn multisig_minimum_balance() -> u64 {
+        Rent::default().minimum_balance(Multisig::get_packed_len())
+    }
+
+    fn minter_minimum_balance() -> u64 {
+        Rent::default().minimum_balance(Minter::get_packed_len())
+    }
+
+    fn allowance_minimum_balance() -> u64 {
+        Rent::default().minimum_balance(Allowance::get_packed_len())
+    }
+
+    fn nonce_minimum_balance() -> u64 {
+        Rent::default().minimum_balance(AuthorizationNonce::get_packed_len())
+    }
+
+    fn instructions_sysvar(instructions: &[Instruction], current_index: u16) -> SolanaAccount {
+        let mut data = Message::new(instructions, None).serialize_instructions();
+        data.extend_from_slice(&current_index.to_le_bytes());
+        let mut account = SolanaAccount::new(0, data.len(), &sysvar::id());
+        account.data = data;
+        account
+    }
+
+    // The signature itself is checked by the secp256k1 program, not the token program
+    fn secp256k1_instruction(address: &EthereumAddress, message: &[u8], index: u8) -> Instruction {
+        let address_offset = 12u16;
+        let signature_offset = 32u16;
+        let message_offset = 97u16;
+        let mut data = vec![1];
+        data.extend_from_slice(&signature_offset.to_le_bytes());
+        data.push(index);
+        data.extend_from_slice(&address_offset.to_le_bytes());
+        data.push(index);
+        data.extend_from_slice(&message_offset.to_le_bytes());
+        data.extend_from_slice(&(message.len() as u16).to_le_bytes());
+        data.push(index);
+        data.extend_from_slice(address);
+        data.extend_from_slice(&[0; 65]);
+        data.extend_from_slice(message);
+        Instruction {
+            program_id: secp256k1_program::id(),
+            accounts: vec![],
+            data,
+        }
+    }
+
+    // The signature itself is checked by the ed25519 program, not the token program
+    fn ed25519_instruction(signer: &Pubkey, message: &[u8]) -> Instruction {
+        let public_key_offset = 16u16;
+        let signature_offset = 48u16;
+        let message_offset = 112u16;
+        let mut data = vec![1, 0];
+        for offset in &[
+            signature_offset,
+            u16::MAX,
+            public_key_offset,
+            u16::MAX,
+            message_offset,
+            message.len() as u16,
+            u16::MAX,
+        ] {
+            data.extend_from_slice(&offset.to_le_bytes());
+        }
+        data.extend_from_slice(signer.as_ref());
+        data.extend_from_slice(&[0; 64]);
+        data.extend_from_slice(message);
+        Instruction {
+            program_id: crate::ed25519_program::id(),
+            accounts: vec![],
+            data,
+        }
+    }
+
+    fn do_process_instruction_with_store(
+        instruction: Instruction,
+        store: &mut HashMap<Pubkey, SolanaAccount>,
+    ) -> ProgramResult {
+        let mut accounts = instruction
+            .accounts
+            .iter()
+            .map(|account_meta| store.get(&account_meta.pubkey).cloned().unwrap_or_default())
+            .collect::<Vec<_>>();
+        do_process_instruction(instruction.clone(), accounts.iter_mut().collect())?;
+        for (account_meta, account) in instruction.accounts.iter().zip(accounts) {
+            store.insert(account_meta.pubkey, account);
+        }
+        Ok(())
+    }
+
+    fn with_mint(mut instruction: Instruction, mint_key: &Pubkey) -> Instruction {
+        instruction
+            .accounts
+            .push(AccountMeta::new_readonly(*mint_key, false));
+        instruction
+    }
+
+    enum TestAuthority {
+        Signer(Pubkey),
+        Multisig {
+            multisig: Pubkey,
+            signer: Pubkey,
+        },
+        WeightedMultisig {
+            multisig: Pubkey,
+            signer: Pubkey,
+        },
+        EthereumOwner {
+            owner: Pubkey,
+            address: EthereumAddress,
+        },
+    }
+
+    impl TestAuthority {
+        fn key(&self) -> Pubkey {
+            match self {
+                Self::Signer(key) => *key,
+                Self::Multisig { multisig, .. } | Self::WeightedMultisig { multisig, .. } => {
+                    *multisig
+                }
+                Self::EthereumOwner { owner, .. } => *owner,
+            }
+        }
+
+        fn signer_pubkeys(&self) -> Vec<&Pubkey> {
+            match self {
+                Self::Multisig { signer, .. } | Self::WeightedMultisig { signer, .. } => {
+                    vec![signer]
+                }
+                _ => vec![],
+            }
+        }
+    }
+
+    // Runs an instruction built for `authority`, first without and then with its approval
+    fn assert_authority_required(
+        authority: &TestAuthority,
+        mut instruction: Instruction,
+        store: &mut HashMap<Pubkey, SolanaAccount>,
+    ) {
+        match authority {
+            TestAuthority::Signer(key)
+            | TestAuthority::Multisig { signer: key, .. }
+            | TestAuthority::WeightedMultisig { signer: key, .. } => {
+                let mut unsigned = instruction.clone();
+                unsigned
+                    .accounts
+                    .iter_mut()
+                    .filter(|account_meta| account_meta.pubkey == *key)
+                    .for_each(|account_meta| account_meta.is_signer = false);
+                assert_eq!(
+                    Err(ProgramError::MissingRequiredSignature),
+                    do_process_instruction_with_store(unsigned, store)
+                );
+            }
+            TestAuthority::EthereumOwner { owner, address } => {
+                instruction
+                    .accounts
+                    .iter_mut()
+                    .filter(|account_meta| account_meta.pubkey == *owner)
+                    .for_each(|account_meta| *account_meta = AccountMeta::new(*owner, false));
+                instruction
+                    .accounts
+                    .push(AccountMeta::new_readonly(sysvar::instructions::id(), false));
+                store.insert(
+                    sysvar::instructions::id(),
+                    instructions_sysvar(std::slice::from_ref(&instruction), 0),
+                );
+                assert_eq!(
+                    Err(TokenError::MissingAuthorizationSignature.into()),
+                    do_process_instruction_with_store(instruction.clone(), store)
+                );
+                let nonce = EthereumOwner::unpack(&store[owner].data).unwrap().nonce;
+                let message = ethereum_authorization_message(&instruction, nonce, i64::MAX);
+                set_syscall_clock(0);
+                store.insert(
+                    sysvar::instructions::id(),
+                    instructions_sysvar(
+                        &[
+                            secp256k1_instruction(address, &message, 0),
+                            instruction.clone(),
+                        ],
+                        1,
+                    ),
+                );
+            }
+        }
+        do_process_instruction_with_store(instruction, store).unwrap();
+    }
+
+    #[test]
+    fn test_print_error() {
+        let error = return_token_error_as_program_error();
+        error.print::<TokenError>();
+    }
+
+    #[test]
+    #[should_panic(expected = "Custom(3)")]
+    fn test_error_unwrap() {
+        Err::<(), ProgramError>(return_token_error_as_program_error()).unwrap();
+    }
+
+    #[test]
+    fn test_unique_account_sizes() {
+        assert_ne!(Mint::get_packed_len(), 0);
+        assert_ne!(Mint::get_packed_len(), Account::get_packed_len());
+        assert_ne!(Mint::get_packed_len(), Multisig::get_packed_len());
+        assert_ne!(Account::get_packed_len(), 0);
+        assert_ne!(Account::get_packed_len(), Multisig::get_packed_len());
+        assert_ne!(Multisig::get_packed_len(), 0);
+        assert_ne!(Minter::get_packed_len(), 0);
+        assert_ne!(Minter::get_packed_len(), Mint::get_packed_len());
+        assert_ne!(Minter::get_packed_len(), Account::get_packed_len());
+        assert_ne!(Minter::get_packed_len(), Multisig::get_packed_len());
+        assert_ne!(Allowance::get_packed_len(), 0);
+        assert_ne!(Allowance::get_packed_len(), Mint::get_packed_len());
+        assert_ne!(Allowance::get_packed_len(), Account::get_packed_len());
+        assert_ne!(Allowance::get_packed_len(), Multisig::get_packed_len());
+        assert_ne!(Allowance::get_packed_len(), Minter::get_packed_len());
+        assert_ne!(AuthorizationNonce::get_packed_len(), 0);
+        assert_ne!(AuthorizationNonce::get_packed_len(), Mint::get_packed_len());
+        assert_ne!(
+            AuthorizationNonce::get_packed_len(),
+            Account::get_packed_len()
+        );
+        assert_ne!(
+            AuthorizationNonce::get_packed_len(),
+            Multisig::get_packed_len()
+        );
+        assert_ne!(
+            AuthorizationNonce::get_packed_len(),
+            Minter::get_packed_len()
+        );
+        assert_ne!(
+            AuthorizationNonce::get_packed_len(),
+            Allowance::get_packed_len()
+        );
+        assert_ne!(EthereumOwner::get_packed_len(), 0);
+        assert_ne!(EthereumOwner::get_packed_len(), Mint::get_packed_len());
+        assert_ne!(EthereumOwner::get_packed_len(), Account::get_packed_len());
+        assert_ne!(EthereumOwner::get_packed_len(), Multisig::get_packed_len());
+        assert_ne!(EthereumOwner::get_packed_len(), Minter::get_packed_len());
+        assert_ne!(EthereumOwner::get_packed_len(), Allowance::get_packed_len());
+        assert_ne!(
+            EthereumOwner::get_packed_len(),
+            AuthorizationNonce::get_packed_len()
+        );
+        assert_ne!(WeightedMultisig::get_packed_len(), 0);
+        assert_ne!(WeightedMultisig::get_packed_len(), Mint::get_packed_len());
+        assert_ne!(
+            WeightedMultisig::get_packed_len(),
+            Account::get_packed_len()
+        );
+        assert_ne!(
+            WeightedMultisig::get_packed_len(),
+            Multisig::get_packed_len()
+        );
+        assert_ne!(WeightedMultisig::get_packed_len(), Minter::get_packed_len());
+        assert_ne!(
+            WeightedMultisig::get_packed_len(),
+            Allowance::get_packed_len()
+        );
+        assert_ne!(
+            WeightedMultisig::get_packed_len(),
+            AuthorizationNonce::get_packed_len()
+        );
+        assert_ne!(
+            WeightedMultisig::get_packed_len(),
+            EthereumOwner::get_packed_len()
+        );
+        assert_ne!(MultisigProposal::get_packed_len(), 0);
+        assert_ne!(MultisigProposal::get_packed_len(), Mint::get_packed_len());
+        assert_ne!(
+            MultisigProposal::get_packed_len(),
+            Account::get_packed_len()
+        );
+        assert_ne!(
+            MultisigProposal::get_packed_len(),
+            Multisig::get_packed_len()
+        );
+        assert_ne!(MultisigProposal::get_packed_len(), Minter::get_packed_len());
+        assert_ne!(
+            MultisigProposal::get_packed_len(),
+            Allowance::get_packed_len()
+        );
+        assert_ne!(
+            MultisigProposal::get_packed_len(),
+            AuthorizationNonce::get_packed_len()
+        );
+        assert_ne!(
+            MultisigProposal::get_packed_len(),
+            EthereumOwner::get_packed_len()
+        );
+        assert_ne!(
+            MultisigProposal::get_packed_len(),
+            WeightedMultisig::get_packed_len()
+        );
+        assert_ne!(DestinationAllowlist::get_packed_len(), 0);
+        assert_ne!(
+            DestinationAllowlist::get_packed_len(),
+            Mint::get_packed_len()
+        );
+        assert_ne!(
+            DestinationAllowlist::get_packed_len(),
+            Account::get_packed_len()
+        );
+        assert_ne!(
+            DestinationAllowlist::get_packed_len(),
+            Multisig::get_packed_len()
+        );
+        assert_ne!(
+            DestinationAllowlist::get_packed_len(),
+            Minter::get_packed_len()
+        );
+        assert_ne!(
+            DestinationAllowlist::get_packed_len(),
+            Allowance::get_packed_len()
+        );
+        assert_ne!(
+            DestinationAllowlist::get_packed_len(),
+            AuthorizationNonce::get_packed_len()
+        );
+        assert_ne!(
+            DestinationAllowlist::get_packed_len(),
+            EthereumOwner::get_packed_len()
+        );
+        assert_ne!(
+            DestinationAllowlist::get_packed_len(),
+            WeightedMultisig::get_packed_len()
+        );
+        assert_ne!(
+            DestinationAllowlist::get_packed_len(),
+            MultisigProposal::get_packed_len()
+        );
+        assert_ne!(Credential::get_packed_len(), 0);
+        assert_ne!(Credential::get_packed_len(), Mint::get_packed_len());
+        assert_ne!(Credential::get_packed_len(), Account::get_packed_len());
+        assert_ne!(Credential::get_packed_len(), Multisig::get_packed_len());
+        assert_ne!(Credential::get_packed_len(), Minter::get_packed_len());
+        assert_ne!(Credential::get_packed_len(), Allowance::get_packed_len());
+        assert_ne!(
+            Credential::get_packed_len(),
+            AuthorizationNonce::get_packed_len()
+        );
+        assert_ne!(
+            Credential::get_packed_len(),
+            EthereumOwner::get_packed_len()
+        );
+        assert_ne!(
+            Credential::get_packed_len(),
+            WeightedMultisig::get_packed_len()
+        );
+        assert_ne!(
+            Credential::get_packed_len(),
+            MultisigProposal::get_packed_len()
+        );
+        assert_ne!(
+            Credential::get_packed_len(),
+            DestinationAllowlist::get_packed_len()
+        );
+        assert_ne!(Mint::EXTENDED_LEN, Account::EXTENDED_LEN);
+        assert_ne!(Mint::EXTENDED_LEN, Account::get_packed_len());
+        assert_ne!(Account::EXTENDED_LEN, Mint::get_packed_len());
+        for packed_len in &[
+            Multisig::get_packed_len(),
+            Minter::get_packed_len(),
+            Allowance::get_packed_len(),
+            AuthorizationNonce::get_packed_len(),
+            EthereumOwner::get_packed_len(),
+            WeightedMultisig::get_packed_len(),
+            MultisigProposal::get_packed_len(),
+            DestinationAllowlist::get_packed_len(),
+            Credential::get_packed_len(),
+        ] {
+            assert_ne!(*packed_len, Mint::EXTENDED_LEN);
+            assert_ne!(*packed_len, Account::EXTENDED_LEN);
+        }
+    }
+
+    #[test]
+    fn test_pack_unpack() {
+        // Mint
+        let check = Mint {
+            mint_authority: COption::Some(Pubkey::new(&[1; 32])),
+            supply: 42,
+            decimals: 7,
+            is_initialized: true,
+            freeze_authority: COption::Some(Pubkey::new(&[2; 32])),
+            burner: COption::Some(Pubkey::new(&[3; 32])),
+            pauser: COption::Some(Pubkey::new(&[4; 32])),
+            freezer: COption::Some(Pubkey::new(&[5; 32])),
+            minting_paused: true,
+            pending_mint_authority: COption::Some(Pubkey::new(&[6; 32])),
+            pending_freeze_authority: COption::None,
+            authority_timelock: 3600,
+            queued_authority: TimelockedAuthority::Pauser,
+            queued_new_authority: COption::Some(Pubkey::new(&[7; 32])),
+            queued_unlock_timestamp: 42,
+            max_balance: COption::Some(1_000),
+            trading_start_timestamp: 1_700_000_000,
+            transfer_cooldown: 60,
+            compliance_authority: COption::Some(Pubkey::new(&[8; 32])),
+            min_transfer_amount: 5,
+        };
+        let mut packed = vec![0; Mint::get_packed_len() + 1];
+        assert_eq!(
+            Err(ProgramError::InvalidAccountData),
+            Mint::pack(check, &mut packed)
+        );
+        let mut packed = vec![0; Mint::get_packed_len() - 1];
+        assert_eq!(
+            Err(ProgramError::InvalidAccountData),
+            Mint::pack(check, &mut packed)
+        );
+        let mut packed = vec![0; Mint::get_packed_len()];
+        assert_eq!(
+            Err(TokenError::ExtensionRequired.into()),
+            Mint::pack(check, &mut packed)
+        );
+        let mut packed = vec![0; Mint::EXTENDED_LEN];
+        Mint::pack(check, &mut packed).unwrap();
+        let mut expect = vec![
+            1, 0, 0, 0, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1,
+            1, 1, 1, 1, 1, 1, 1, 42, 0, 0, 0, 0, 0, 0, 0, 7, 1, 1, 0, 0, 0, 2, 2, 2, 2, 2, 2, 2, 2,
+            2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2,
+        ];
+        expect.extend_from_slice(&[1]);
+        expect.extend_from_slice(&[1, 0, 0, 0]);
+        expect.extend_from_slice(&[3; 32]);
+        expect.extend_from_slice(&[1, 0, 0, 0]);
+        expect.extend_from_slice(&[4; 32]);
+        expect.extend_from_slice(&[1, 0, 0, 0]);
+        expect.extend_from_slice(&[5; 32]);
+        expect.extend_from_slice(&[1]);
+        expect.extend_from_slice(&[1, 0, 0, 0]);
+        expect.extend_from_slice(&[6; 32]);
+        expect.extend_from_slice(&[0; 36]);
+        expect.extend_from_slice(&3600u64.to_le_bytes());
+        expect.extend_from_slice(&[4]);
+        expect.extend_from_slice(&[1, 0, 0, 0]);
+        expect.extend_from_slice(&[7; 32]);
+        expect.extend_from_slice(&42i64.to_le_bytes());
+        expect.extend_from_slice(&[1, 0, 0, 0]);
+        expect.extend_from_slice(&1_000u64.to_le_bytes());
+        expect.extend_from_slice(&1_700_000_000i64.to_le_bytes());
+        expect.extend_from_slice(&60u64.to_le_bytes());
+        expect.extend_from_slice(&[1, 0, 0, 0]);
+        expect.extend_from_slice(&[8; 32]);
+        expect.extend_from_slice(&5u64.to_le_bytes());
+        assert_eq!(packed, expect);
+        let unpacked = Mint::unpack(&packed).unwrap();
+        assert_eq!(unpacked, check);
+
+        // Mint without extension
+        let check = Mint {
+            mint_authority: check.mint_authority,
+            supply: check.supply,
+            decimals: check.decimals,
+            is_initialized: check.is_initialized,
+            freeze_authority: check.freeze_authority,
+            ..Mint::default()
+        };
+        let unpacked = Mint::unpack(&packed[..Mint::get_packed_len()]).unwrap();
+        assert_eq!(unpacked, check);
+        let mut packed = vec![0; Mint::get_packed_len()];
+        Mint::pack(check, &mut packed).unwrap();
+        assert_eq!(packed, expect[..Mint::get_packed_len()]);
+        let mut packed = expect;
+        packed[Mint::get_packed_len()] = 0;
+        let unpacked = Mint::unpack(&packed).unwrap();
+        assert_eq!(unpacked, check);
+        packed[Mint::get_packed_len()] = 2;
+        assert_eq!(Err(ProgramError::InvalidAccountData), Mint::unpack(&packed));
+
+        // Account
+        let check = Account {
+            mint: Pubkey::new(&[1; 32]),
+            owner: Pubkey::new(&[2; 32]),
+            amount: 3,
+            delegate: COption::Some(Pubkey::new(&[4; 32])),
+            state: AccountState::Frozen,
+            is_native: COption::Some(5),
+            delegated_amount: 6,
+            close_authority: COption::Some(Pubkey::new(&[7; 32])),
+            pending_owner: COption::Some(Pubkey::new(&[8; 32])),
+            pending_close_authority: COption::None,
+            guardians: COption::Some(Pubkey::new(&[9; 32])),
+            recovery_delay: 3600,
+            recovery_owner: COption::None,
+            recovery_unlock_timestamp: 42,
+            delegate_expiry: 1_700_000_000,
+            delegate_transfer_cap: COption::Some(500),
+            session_key: COption::Some(Pubkey::new(&[10; 32])),
+            session_expiry: 1_800_000_000,
+            session_scope: SESSION_SCOPE_TRANSFER,
+            num_session_destinations: 1,
+            session_destinations: [
+                Pubkey::new(&[14; 32]),
+                Pubkey::default(),
+                Pubkey::default(),
+                Pubkey::default(),
+            ],
+            session_remaining_amount: 700,
+            hold_capturer: COption::Some(Pubkey::new(&[11; 32])),
+            held_amount: 2,
+            hold_expiry: 1_900_000_000,
+            freeze_reason: 12,
+            auto_thaw_timestamp: 2_000_000_000,
+            spending_limit: COption::Some(100),
+            spending_window: 3600,
+            spending_window_start: 50,
+            spending_window_spent: 20,
+            pending_spending_limit: COption::None,
+            pending_spending_window: 0,
+            spending_limit_unlock_timestamp: 0,
+            destination_allowlist: COption::Some(Pubkey::new(&[13; 32])),
+            max_balance_exempt: false,
+            last_transfer_timestamp: 2_100_000_000,
+            min_incoming_amount: 7,
+        };
+        let mut packed = vec![0; Account::get_packed_len() + 1];
+        assert_eq!(
+            Err(ProgramError::InvalidAccountData),
+            Account::pack(check, &mut packed)
+        );
+        let mut packed = vec![0; Account::get_packed_len() - 1];
+        assert_eq!(
+            Err(ProgramError::InvalidAccountData),
+            Account::pack(check, &mut packed)
+        );
+        let mut packed = vec![0; Account::get_packed_len()];
+        assert_eq!(
+            Err(TokenError::ExtensionRequired.into()),
+            Account::pack(check, &mut packed)
+        );
+        let mut packed = vec![0; Account::EXTENDED_LEN];
+        Account::pack(check, &mut packed).unwrap();
+        let mut expect = vec![
+            1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1,
+            1, 1, 1, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2,
+            2, 2, 2, 2, 2, 2, 3, 0, 0, 0, 0, 0, 0, 0, 1, 0, 0, 0, 4, 4, 4, 4, 4, 4, 4, 4, 4, 4, 4,
+            4, 4, 4, 4, 4, 4, 4, 4, 4, 4, 4, 4, 4, 4, 4, 4, 4, 4, 4, 4, 4, 2, 1, 0, 0, 0, 5, 0, 0,
+            0, 0, 0, 0, 0, 6, 0, 0, 0, 0, 0, 0, 0, 1, 0, 0, 0, 7, 7, 7, 7, 7, 7, 7, 7, 7, 7, 7, 7,
+            7, 7, 7, 7, 7, 7, 7, 7, 7, 7, 7, 7, 7, 7, 7, 7, 7, 7, 7, 7,
+        ];
+        expect.extend_from_slice(&[1]);
+        expect.extend_from_slice(&[1, 0, 0, 0]);
+        expect.extend_from_slice(&[8; 32]);
+        expect.extend_from_slice(&[0; 36]);
+        expect.extend_from_slice(&[1, 0, 0, 0]);
+        expect.extend_from_slice(&[9; 32]);
+        expect.extend_from_slice(&3600u64.to_le_bytes());
+        expect.extend_from_slice(&[0; 36]);
+        expect.extend_from_slice(&42i64.to_le_bytes());
+        expect.extend_from_slice(&1_700_000_000i64.to_le_bytes());
+        expect.extend_from_slice(&[1, 0, 0, 0]);
+        expect.extend_from_slice(&500u64.to_le_bytes());
+        expect.extend_from_slice(&[1, 0, 0, 0]);
+        expect.extend_from_slice(&[10; 32]);
+        expect.extend_from_slice(&1_800_000_000i64.to_le_bytes());
+        expect.extend_from_slice(&[1]);
+        expect.extend_from_slice(&[1]);
+        expect.extend_from_slice(&[14; 32]);
+        expect.extend_from_slice(&[0; 96]);
+        expect.extend_from_slice(&700u64.to_le_bytes());
+        expect.extend_from_slice(&[1, 0, 0, 0]);
+        expect.extend_from_slice(&[11; 32]);
+        expect.extend_from_slice(&2u64.to_le_bytes());
+        expect.extend_from_slice(&1_900_000_000i64.to_le_bytes());
+        expect.extend_from_slice(&[12]);
+        expect.extend_from_slice(&2_000_000_000i64.to_le_bytes());
+        expect.extend_from_slice(&[1, 0, 0, 0]);
+        expect.extend_from_slice(&100u64.to_le_bytes());
+        expect.extend_from_slice(&3600u64.to_le_bytes());
+        expect.extend_from_slice(&50i64.to_le_bytes());
+        expect.extend_from_slice(&20u64.to_le_bytes());
+        expect.extend_from_slice(&[0; 12]);
+        expect.extend_from_slice(&0u64.to_le_bytes());
+        expect.extend_from_slice(&0i64.to_le_bytes());
+        expect.extend_from_slice(&[1, 0, 0, 0]);
+        expect.extend_from_slice(&[13; 32]);
+        expect.extend_from_slice(&[0]);
+        expect.extend_from_slice(&2_100_000_000i64.to_le_bytes());
+        expect.extend_from_slice(&7u64.to_le_bytes());
+        assert_eq!(packed, expect);
+        let unpacked = Account::unpack(&packed).unwrap();
+        assert_eq!(unpacked, check);
+
+        // Account without extension
+        let check = Account {
+            mint: check.mint,
+            owner: check.owner,
+            amount: check.amount,
+            delegate: check.delegate,
+            state: check.state,
+            is_native: check.is_native,
+            delegated_amount: check.delegated_amount,
+            close_authority: check.close_authority,
+            ..Account::default()
+        };
+        let unpacked = Account::unpack(&packed[..Account::get_packed_len()]).unwrap();
+        assert_eq!(unpacked, check);
+        let mut packed = vec![0; Account::get_packed_len()];
+        Account::pack(check, &mut packed).unwrap();
+        assert_eq!(packed, expect[..Account::get_packed_len()]);
+        let mut packed = expect;
+        packed[Account::get_packed_len()] = 0;
+        let unpacked = Account::unpack(&packed).unwrap();
+        assert_eq!(unpacked, check);
+        packed[Account::get_packed_len()] = 2;
+        assert_eq!(
+            Err(ProgramError::InvalidAccountData),
+            Account::unpack(&packed)
+        );
+
+        // Multisig
+        let check = Multisig {
+            m: 1,
+            n: 2,
+            is_initialized: true,
+            signers: [Pubkey::new(&[3; 32]); MAX_SIGNERS],
+        };
+        let mut packed = vec![0; Multisig::get_packed_len() + 1];
+        assert_eq!(
+            Err(ProgramError::InvalidAccountData),
+            Multisig::pack(check, &mut packed)
+        );
+        let mut packed = vec![0; Multisig::get_packed_len() - 1];
+        assert_eq!(
+            Err(ProgramError::InvalidAccountData),
+            Multisig::pack(check, &mut packed)
+        );
+        let mut packed = vec![0; Multisig::get_packed_len()];
+        Multisig::pack(check, &mut packed).unwrap();
+        let expect = vec![
+            1, 2, 1, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3,
+            3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3,
+            3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3,
+            3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3,
+            3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3,
+            3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3,
+            3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3,
+            3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3,
+            3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3,
+            3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3,
+            3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3,
+            3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3,
+            3, 3, 3, 3, 3, 3, 3,
+        ];
+        assert_eq!(packed, expect);
+        let unpacked = Multisig::unpack(&packed).unwrap();
+        assert_eq!(unpacked, check);
+
+        // Minter
+        let check = Minter {
+            mint: Pubkey::new(&[1; 32]),
+            authority: Pubkey::new(&[2; 32]),
+            allowance: 3,
+            is_initialized: true,
+        };
+        let mut packed = vec![0; Minter::get_packed_len() + 1];
+        assert_eq!(
+            Err(ProgramError::InvalidAccountData),
+            Minter::pack(check, &mut packed)
+        );
+        let mut packed = vec![0; Minter::get_packed_len() - 1];
+        assert_eq!(
+            Err(ProgramError::InvalidAccountData),
+            Minter::pack(check, &mut packed)
+        );
+        let mut packed = vec![0; Minter::get_packed_len()];
+        Minter::pack(check, &mut packed).unwrap();
+        let mut expect = vec![1; 32];
+        expect.extend_from_slice(&[2; 32]);
+        expect.extend_from_slice(&[3, 0, 0, 0, 0, 0, 0, 0, 1]);
+        assert_eq!(packed, expect);
+        let unpacked = Minter::unpack(&packed).unwrap();
+        assert_eq!(unpacked, check);
+
+        // Allowance
+        let check = Allowance {
+            account: Pubkey::new(&[1; 32]),
+            spender: Pubkey::new(&[2; 32]),
+            owner: Pubkey::new(&[4; 32]),
+            amount: 3,
+            is_initialized: true,
+            bump_seed: 254,
+        };
+        let mut packed = vec![0; Allowance::get_packed_len() + 1];
+        assert_eq!(
+            Err(ProgramError::InvalidAccountData),
+            Allowance::pack(check, &mut packed)
+        );
+        let mut packed = vec![0; Allowance::get_packed_len() - 1];
+        assert_eq!(
+            Err(ProgramError::InvalidAccountData),
+            Allowance::pack(check, &mut packed)
+        );
+        let mut packed = vec![0; Allowance::get_packed_len()];
+        Allowance::pack(check, &mut packed).unwrap();
+        let mut expect = vec![1; 32];
+        expect.extend_from_slice(&[2; 32]);
+        expect.extend_from_slice(&[4; 32]);
+        expect.extend_from_slice(&[3, 0, 0, 0, 0, 0, 0, 0, 1, 254]);
+        assert_eq!(packed, expect);
+        let unpacked = Allowance::unpack(&packed).unwrap();
+        assert_eq!(unpacked, check);
+
+        // AuthorizationNonce
+        let check = AuthorizationNonce {
+            owner: Pubkey::new(&[1; 32]),
+            nonce: 2,
+            is_initialized: true,
+            bump_seed: 254,
+        };
+        let mut packed = vec![0; AuthorizationNonce::get_packed_len() + 1];
+        assert_eq!(
+            Err(ProgramError::InvalidAccountData),
+            AuthorizationNonce::pack(check, &mut packed)
+        );
+        let mut packed = vec![0; AuthorizationNonce::get_packed_len() - 1];
+        assert_eq!(
+            Err(ProgramError::InvalidAccountData),
+            AuthorizationNonce::pack(check, &mut packed)
+        );
+        let mut packed = vec![0; AuthorizationNonce::get_packed_len()];
+        AuthorizationNonce::pack(check, &mut packed).unwrap();
+        let mut expect = vec![1; 32];
+        expect.extend_from_slice(&[2, 0, 0, 0, 0, 0, 0, 0, 1, 254]);
+        assert_eq!(packed, expect);
+        let unpacked = AuthorizationNonce::unpack(&packed).unwrap();
+        assert_eq!(unpacked, check);
+
+        // EthereumOwner
+        let check = EthereumOwner {
+            address: [1; 20],
+            nonce: 2,
+            is_initialized: true,
+            bump_seed: 254,
+        };
+        let mut packed = vec![0; EthereumOwner::get_packed_len() + 1];
+        assert_eq!(
+            Err(ProgramError::InvalidAccountData),
+            EthereumOwner::pack(check, &mut packed)
+        );
+        let mut packed = vec![0; EthereumOwner::get_packed_len() - 1];
+        assert_eq!(
+            Err(ProgramError::InvalidAccountData),
+            EthereumOwner::pack(check, &mut packed)
+        );
+        let mut packed = vec![0; EthereumOwner::get_packed_len()];
+        EthereumOwner::pack(check, &mut packed).unwrap();
+        let mut expect = vec![1; 20];
+        expect.extend_from_slice(&[2, 0, 0, 0, 0, 0, 0, 0, 1, 254]);
+        assert_eq!(packed, expect);
+        let unpacked = EthereumOwner::unpack(&packed).unwrap();
+        assert_eq!(unpacked, check);
+
+        // WeightedMultisig
+        let check = WeightedMultisig {
+            threshold: 3,
+            n: 2,
+            is_initialized: true,
+            signers: [Pubkey::new(&[4; 32]); MAX_SIGNERS],
+            weights: [5; MAX_SIGNERS],
+        };
+        let mut packed = vec![0; WeightedMultisig::get_packed_len() + 1];
+        assert_eq!(
+            Err(ProgramError::InvalidAccountData),
+            WeightedMultisig::pack(check, &mut packed)
+        );
+        let mut packed = vec![0; WeightedMultisig::get_packed_len() - 1];
+        assert_eq!(
+            Err(ProgramError::InvalidAccountData),
+            WeightedMultisig::pack(check, &mut packed)
+        );
+        let mut packed = vec![0; WeightedMultisig::get_packed_len()];
+        WeightedMultisig::pack(check, &mut packed).unwrap();
+        let mut expect = vec![3, 0, 2, 1];
+        expect.extend_from_slice(&[4; 32 * MAX_SIGNERS]);
+        expect.extend_from_slice(&[5; MAX_SIGNERS]);
+        assert_eq!(packed, expect);
+        let unpacked = WeightedMultisig::unpack(&packed).unwrap();
+        assert_eq!(unpacked, check);
+
+        // MultisigProposal
+        let check = MultisigProposal {
+            multisig: Pubkey::new(&[1; 32]),
+            proposer: Pubkey::new(&[2; 32]),
+            expiry: 3,
+            state: ProposalState::Pending,
+            num_approvals: 1,
+            approvals: [Pubkey::new(&[4; 32]); MAX_SIGNERS],
+            data_len: 5,
+            data: [6; MAX_PROPOSAL_DATA_LEN],
+            num_accounts: 2,
+            accounts: [Pubkey::new(&[7; 32]); MAX_PROPOSAL_ACCOUNTS],
+        };
+        let mut packed = vec![0; MultisigProposal::get_packed_len() + 1];
+        assert_eq!(
+            Err(ProgramError::InvalidAccountData),
+            MultisigProposal::pack(check, &mut packed)
+        );
+        let mut packed = vec![0; MultisigProposal::get_packed_len() - 1];
+        assert_eq!(
+            Err(ProgramError::InvalidAccountData),
+            MultisigProposal::pack(check, &mut packed)
+        );
+        let mut packed = vec![0; MultisigProposal::get_packed_len()];
+        MultisigProposal::pack(check, &mut packed).unwrap();
+        let mut expect = vec![1; 32];
+        expect.extend_from_slice(&[2; 32]);
+        expect.extend_from_slice(&[3, 0, 0, 0, 0, 0, 0, 0, 1, 1]);
+        expect.extend_from_slice(&[4; 32 * MAX_SIGNERS]);
+        expect.extend_from_slice(&[5, 0]);
+        expect.extend_from_slice(&[6; MAX_PROPOSAL_DATA_LEN]);
+        expect.extend_from_slice(&[2]);
+        expect.extend_from_slice(&[7; 32 * MAX_PROPOSAL_ACCOUNTS]);
+        assert_eq!(packed, expect);
+        let unpacked = MultisigProposal::unpack(&packed).unwrap();
+        assert_eq!(unpacked, check);
+
+        // DestinationAllowlist
+        let check = DestinationAllowlist {
+            account: Pubkey::new(&[1; 32]),
+            is_initialized: true,
+            n: 2,
+            destinations: [Pubkey::new(&[3; 32]); MAX_ALLOWED_DESTINATIONS],
+            active_timestamps: [4; MAX_ALLOWED_DESTINATIONS],
+        };
+        let mut packed = vec![0; DestinationAllowlist::get_packed_len() + 1];
+        assert_eq!(
+            Err(ProgramError::InvalidAccountData),
+            DestinationAllowlist::pack(check, &mut packed)
+        );
+        let mut packed = vec![0; DestinationAllowlist::get_packed_len() - 1];
+        assert_eq!(
+            Err(ProgramError::InvalidAccountData),
+            DestinationAllowlist::pack(check, &mut packed)
+        );
+        let mut packed = vec![0; DestinationAllowlist::get_packed_len()];
+        DestinationAllowlist::pack(check, &mut packed).unwrap();
+        let mut expect = vec![1; 32];
+        expect.extend_from_slice(&[1, 2]);
+        expect.extend_from_slice(&[3; 32 * MAX_ALLOWED_DESTINATIONS]);
+        for _ in 0..MAX_ALLOWED_DESTINATIONS {
+            expect.extend_from_slice(&[4, 0, 0, 0, 0, 0, 0, 0]);
+        }
+        assert_eq!(packed, expect);
+        let unpacked = DestinationAllowlist::unpack(&packed).unwrap();
+        assert_eq!(unpacked, check);
+
+        // Credential
+        let check = Credential {
+            mint: Pubkey::new(&[1; 32]),
+            owner: Pubkey::new(&[2; 32]),
+            expiry: 3,
+            jurisdiction: 840,
+            is_initialized: true,
+            bump_seed: 254,
+        };
+        let mut packed = vec![0; Credential::get_packed_len() + 1];
+        assert_eq!(
+            Err(ProgramError::InvalidAccountData),
+            Credential::pack(check, &mut packed)
+        );
+        let mut packed = vec![0; Credential::get_packed_len() - 1];
+        assert_eq!(
+            Err(ProgramError::InvalidAccountData),
+            Credential::pack(check, &mut packed)
+        );
+        let mut packed = vec![0; Credential::get_packed_len()];
+        Credential::pack(check, &mut packed).unwrap();
+        let mut expect = vec![1; 32];
+        expect.extend_from_slice(&[2; 32]);
+        expect.extend_from_slice(&[3, 0, 0, 0, 0, 0, 0, 0]);
+        expect.extend_from_slice(&840u16.to_le_bytes());
+        expect.extend_from_slice(&[1, 254]);
+        assert_eq!(packed, expect);
+        let unpacked = Credential::unpack(&packed).unwrap();
+        assert_eq!(unpacked, check);
+    }
+
+    #[test]
+    fn test_initialize_mint() {
+        let program_id = crate::id();
+        let owner_key = Pubkey::new_unique();
+        let mint_key = Pubkey::new_unique();
+        let mut mint_account = SolanaAccount::new(42, Mint::get_packed_len(), &program_id);
+        let mint2_key = Pubkey::new_unique();
+        let mut mint2_account =
+            SolanaAccount::new(mint_minimum_balance(), Mint::get_packed_len(), &program_id);
+        let mut rent_sysvar = rent_sysvar();
+
+        // mint is not rent exempt
+        assert_eq!(
+            Err(TokenError::NotRentExempt.into()),
+            do_process_instruction(
+                initialize_mint(&program_id, &mint_key, &owner_key, None, 2).unwrap(),
+                vec![&mut mint_account, &mut rent_sysvar]
+            )
+        );
+
+        mint_account.lamports = mint_minimum_balance();
+
+        // create new mint
+        do_process_instruction(
+            initialize_mint(&program_id, &mint_key, &owner_key, None, 2).unwrap(),
+            vec![&mut mint_account, &mut rent_sysvar],
+        )
+        .unwrap();
+
+        // create twice
+        assert_eq!(
+            Err(TokenError::AlreadyInUse.into()),
+            do_process_instruction(
+                initialize_mint(&program_id, &mint_key, &owner_key, None, 2,).unwrap(),
+                vec![&mut mint_account, &mut rent_sysvar]
+            )
+        );
+
+        // create another mint that can freeze
+        do_process_instruction(
+            initialize_mint(&program_id, &mint2_key, &owner_key, Some(&owner_key), 2).unwrap(),
+            vec![&mut mint2_account, &mut rent_sysvar],
+        )
+        .unwrap();
+        let mint = Mint::unpack_unchecked(&mint2_account.data).unwrap();
+        assert_eq!(mint.freeze_authority, COption::Some(owner_key));
+    }
+
+    #[test]
+    fn test_initialize_mint_account() {
+        let program_id = crate::id();
+        let account_key = Pubkey::new_unique();
+        let mut account_account = SolanaAccount::new(42, Account::get_packed_len(), &program_id);
+        let owner_key = Pubkey::new_unique();
+        let mut owner_account = SolanaAccount::default();
+        let mint_key = Pubkey::new_unique();
+        let mut mint_account =
+            SolanaAccount::new(mint_minimum_balance(), Mint::get_packed_len(), &program_id);
+        let mut rent_sysvar = rent_sysvar();
+
+        // account is not rent exempt
+        assert_eq!(
+            Err(TokenError::NotRentExempt.into()),
+            do_process_instruction(
+                initialize_account(&program_id, &account_key, &mint_key, &owner_key).unwrap(),
+                vec![
+                    &mut account_account,
+                    &mut mint_account,
+                    &mut owner_account,
+                    &mut rent_sysvar
+                ],
+            )
+        );
+
+        account_account.lamports = account_minimum_balance();
+
+        // mint is not valid (not initialized)
+        assert_eq!(
+            Err(TokenError::InvalidMint.into()),
+            do_process_instruction(
+                initialize_account(&program_id, &account_key, &mint_key, &owner_key).unwrap(),
+                vec![
+                    &mut account_account,
+                    &mut mint_account,
+                    &mut owner_account,
+                    &mut rent_sysvar
+                ],
+            )
+        );
+
+        // create mint
+        do_process_instruction(
+            initialize_mint(&program_id, &mint_key, &owner_key, None, 2).unwrap(),
+            vec![&mut mint_account, &mut rent_sysvar],
+        )
+        .unwrap();
+
+        // create account
+        do_process_instruction(
+            initialize_account(&program_id, &account_key, &mint_key, &owner_key).unwrap(),
+            vec![
+                &mut account_account,
+                &mut mint_account,
+                &mut owner_account,
+                &mut rent_sysvar,
+            ],
+        )
+        .unwrap();
+
+        // create twice
+        assert_eq!(
+            Err(TokenError::AlreadyInUse.into()),
+            do_process_instruction(
+                initialize_account(&program_id, &account_key, &mint_key, &owner_key).unwrap(),
+                vec![
+                    &mut account_account,
+                    &mut mint_account,
+                    &mut owner_account,
+                    &mut rent_sysvar
+                ],
+            )
+        );
+    }
+
+    #[test]
+    fn test_transfer_dups() {
+        let program_id = crate::id();
+        let account1_key = Pubkey::new_unique();
+        let mut account1_account = SolanaAccount::new(
+            account_minimum_balance(),
+            Account::get_packed_len(),
+            &program_id,
+        );
+        let mut account1_info: AccountInfo = (&account1_key, true, &mut account1_account).into();
+        let account2_key = Pubkey::new_unique();
+        let mut account2_account = SolanaAccount::new(
+            account_minimum_balance(),
+            Account::get_packed_len(),
+            &program_id,
+        );
+        let mut account2_info: AccountInfo = (&account2_key, false, &mut account2_account).into();
+        let account3_key = Pubkey::new_unique();
+        let mut account3_account = SolanaAccount::new(
+            account_minimum_balance(),
+            Account::get_packed_len(),
+            &program_id,
+        );
+        let account3_info: AccountInfo = (&account3_key, false, &mut account3_account).into();
+        let account4_key = Pubkey::new_unique();
+        let mut account4_account = SolanaAccount::new(
+            account_minimum_balance(),
+            Account::get_packed_len(),
+            &program_id,
+        );
+        let account4_info: AccountInfo = (&account4_key, true, &mut account4_account).into();
+        let multisig_key = Pubkey::new_unique();
+        let mut multisig_account = SolanaAccount::new(
+            multisig_minimum_balance(),
+            Multisig::get_packed_len(),
+            &program_id,
+        );
+        let multisig_info: AccountInfo = (&multisig_key, true, &mut multisig_account).into();
+        let owner_key = Pubkey::new_unique();
+        let mut owner_account = SolanaAccount::default();
+        let owner_info: AccountInfo = (&owner_key, true, &mut owner_account).into();
+        let mint_key = Pubkey::new_unique();
+        let mut mint_account =
+            SolanaAccount::new(mint_minimum_balance(), Mint::get_packed_len(), &program_id);
+        let mint_info: AccountInfo = (&mint_key, false, &mut mint_account).into();
+        let rent_key = rent::id();
+        let mut rent_sysvar = rent_sysvar();
+        let rent_info: AccountInfo = (&rent_key, false, &mut rent_sysvar).into();
+
+        // create mint
+        do_process_instruction_dups(
+            initialize_mint(&program_id, &mint_key, &owner_key, None, 2).unwrap(),
+            vec![mint_info.clone(), rent_info.clone()],
+        )
+        .unwrap();
+
+        // create account
+        do_process_instruction_dups(
+            initialize_account(&program_id, &account1_key, &mint_key, &account1_key).unwrap(),
+            vec![
+                account1_info.clone(),
+                mint_info.clone(),
+                account1_info.clone(),
+                rent_info.clone(),
+            ],
+        )
+        .unwrap();
+
+        // create another account
+        do_process_instruction_dups(
+            initialize_account(&program_id, &account2_key, &mint_key, &owner_key).unwrap(),
+            vec![
+                account2_info.clone(),
+                mint_info.clone(),
+                owner_info.clone(),
+                rent_info.clone(),
+            ],
+        )
+        .unwrap();
+
+        // mint to account
+        do_process_instruction_dups(
+            mint_to(&program_id, &mint_key, &account1_key, &owner_key, &[], 1000).unwrap(),
+            vec![mint_info.clone(), account1_info.clone(), owner_info.clone()],
+        )
+        .unwrap();
+
+        // source-owner transfer
+        do_process_instruction_dups(
+            transfer(
+                &program_id,
+                &account1_key,
+                &account2_key,
+                &account1_key,
+                &[],
+                500,
+            )
+            .unwrap(),
+            vec![
+                account1_info.clone(),
+                account2_info.clone(),
+                account1_info.clone(),
+            ],
+        )
+        .unwrap();
+
+        // source-owner TransferChecked
+        do_process_instruction_dups(
+            transfer_checked(
+                &program_id,
+                &account1_key,
+                &mint_key,
+                &account2_key,
+                &account1_key,
+                &[],
+                500,
+                2,
+            )
+            .unwrap(),
+            vec![
+                account1_info.clone(),
+                mint_info.clone(),
+                account2_info.clone(),
+                account1_info.clone(),
+            ],
+        )
+        .unwrap();
+
+        // source-delegate transfer
+        let mut account = Account::unpack_unchecked(&account1_info.data.borrow()).unwrap();
+        account.amount = 1000;
+        account.delegated_amount = 1000;
+        account.delegate = COption::Some(account1_key);
+        account.owner = owner_key;
+        Account::pack(account, &mut account1_info.data.borrow_mut()).unwrap();
+
+        do_process_instruction_dups(
+            transfer(
+                &program_id,
+                &account1_key,
+                &account2_key,
+                &account1_key,
+                &[],
+                500,
+            )
+            .unwrap(),
+            vec![
+                account1_info.clone(),
+                account2_info.clone(),
+                account1_info.clone(),
+            ],
+        )
+        .unwrap();
+
+        // source-delegate TransferChecked
+        do_process_instruction_dups(
+            transfer_checked(
+                &program_id,
+                &account1_key,
+                &mint_key,
+                &account2_key,
+                &account1_key,
+                &[],
+                500,
+                2,
+            )
+            .unwrap(),
+            vec![
+                account1_info.clone(),
+                mint_info.clone(),
+                account2_info.clone(),
+                account1_info.clone(),
+            ],
+        )
+        .unwrap();
+
+        // test destination-owner transfer
+        do_process_instruction_dups(
+            initialize_account(&program_id, &account3_key, &mint_key, &account2_key).unwrap(),
+            vec![
+                account3_info.clone(),
+                mint_info.clone(),
+                account2_info.clone(),
+                rent_info.clone(),
+            ],
+        )
+        .unwrap();
+        do_process_instruction_dups(
+            mint_to(&program_id, &mint_key, &account3_key, &owner_key, &[], 1000).unwrap(),
+            vec![mint_info.clone(), account3_info.clone(), owner_info.clone()],
+        )
+        .unwrap();
+
+        account1_info.is_signer = false;
+        account2_info.is_signer = true;
+        do_process_instruction_dups(
+            transfer(
+                &program_id,
+                &account3_key,
+                &account2_key,
+                &account2_key,
+                &[],
+                500,
+            )
+            .unwrap(),
+            vec![
+                account3_info.clone(),
+                account2_info.clone(),
+                account2_info.clone(),
+            ],
+        )
+        .unwrap();
+
+        // destination-owner TransferChecked
+        do_process_instruction_dups(
+            transfer_checked(
+                &program_id,
+                &account3_key,
+                &mint_key,
+                &account2_key,
+                &account2_key,
+                &[],
+                500,
+                2,
+            )
+            .unwrap(),
+            vec![
+                account3_info.clone(),
+                mint_info.clone(),
+                account2_info.clone(),
+                account2_info.clone(),
+            ],
+        )
+        .unwrap();
+
+        // test source-multisig signer
+        do_process_instruction_dups(
+            initialize_multisig(&program_id, &multisig_key, &[&account4_key], 1).unwrap(),
+            vec![
+                multisig_info.clone(),
+                rent_info.clone(),
+                account4_info.clone(),
+            ],
+        )
+        .unwrap();
+
+        do_process_instruction_dups(
+            initialize_account(&program_id, &account4_key, &mint_key, &multisig_key).unwrap(),
+            vec![
+                account4_info.clone(),
+                mint_info.clone(),
+                multisig_info.clone(),
+                rent_info.clone(),
+            ],
+        )
+        .unwrap();
+
+        do_process_instruction_dups(
+            mint_to(&program_id, &mint_key, &account4_key, &owner_key, &[], 1000).unwrap(),
+            vec![mint_info.clone(), account4_info.clone(), owner_info.clone()],
+        )
+        .unwrap();
+
+        // source-multisig-signer transfer
+        do_process_instruction_dups(
+            transfer(
+                &program_id,
+                &account4_key,
+                &account2_key,
+                &multisig_key,
+                &[&account4_key],
+                500,
+            )
+            .unwrap(),
+            vec![
+                account4_info.clone(),
+                account2_info.clone(),
+                multisig_info.clone(),
+                account4_info.clone(),
+            ],
+        )
+        .unwrap();
+
+        // source-multisig-signer TransferChecked
+        do_process_instruction_dups(
+            transfer_checked(
+                &program_id,
+                &account4_key,
+                &mint_key,
+                &account2_key,
+                &multisig_key,
+                &[&account4_key],
+                500,
+                2,
+            )
+            .unwrap(),
+            vec![
+                account4_info.clone(),
+                mint_info.clone(),
+                account2_info.clone(),
+                multisig_info.clone(),
+                account4_info.clone(),
+            ],
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn test_transfer() {
+        let program_id = crate::id();
+        let account_key = Pubkey::new_unique();
+        let mut account_account = SolanaAccount::new(
+            account_minimum_balance(),
+            Account::get_packed_len(),
+            &program_id,
+        );
+        let account2_key = Pubkey::new_unique();
+        let mut account2_account = SolanaAccount::new(
+            account_minimum_balance(),
+            Account::get_packed_len(),
+            &program_id,
+        );
+        let account3_key = Pubkey::new_unique();
+        let mut account3_account = SolanaAccount::new(
+            account_minimum_balance(),
+            Account::get_packed_len(),
+            &program_id,
+        );
+        let delegate_key = Pubkey::new_unique();
+        let mut delegate_account = SolanaAccount::default();
+        let mismatch_key = Pubkey::new_unique();
+        let mut mismatch_account = SolanaAccount::new(
+            account_minimum_balance(),
+            Account::get_packed_len(),
+            &program_id,
+        );
+        let owner_key = Pubkey::new_unique();
+        let mut owner_account = SolanaAccount::default();
+        let owner2_key = Pubkey::new_unique();
+        let mut owner2_account = SolanaAccount::default();
+        let mint_key = Pubkey::new_unique();
+        let mut mint_account =
+            SolanaAccount::new(mint_minimum_balance(), Mint::get_packed_len(), &program_id);
+        let mint2_key = Pubkey::new_unique();
+        let mut rent_sysvar = rent_sysvar();
+
+        // create mint
+        do_process_instruction(
+            initialize_mint(&program_id, &mint_key, &owner_key, None, 2).unwrap(),
+            vec![&mut mint_account, &mut rent_sysvar],
+        )
+        .unwrap();
+
+        // create account
+        do_process_instruction(
+            initialize_account(&program_id, &account_key, &mint_key, &owner_key).unwrap(),
+            vec![
+                &mut account_account,
+                &mut mint_account,
+                &mut owner_account,
+                &mut rent_sysvar,
+            ],
+        )
+        .unwrap();
+
+        // create another account
+        do_process_instruction(
+            initialize_account(&program_id, &account2_key, &mint_key, &owner_key).unwrap(),
+            vec![
+                &mut account2_account,
+                &mut mint_account,
+                &mut owner_account,
+                &mut rent_sysvar,
+            ],
+        )
+        .unwrap();
+
+        // create another account
+        do_process_instruction(
+            initialize_account(&program_id, &account3_key, &mint_key, &owner_key).unwrap(),
+            vec![
+                &mut account3_account,
+                &mut mint_account,
+                &mut owner_account,
+                &mut rent_sysvar,
+            ],
+        )
+        .unwrap();
+
+        // create mismatch account
+        do_process_instruction(
+            initialize_account(&program_id, &mismatch_key, &mint_key, &owner_key).unwrap(),
+            vec![
+                &mut mismatch_account,
+                &mut mint_account,
+                &mut owner_account,
+                &mut rent_sysvar,
+            ],
+        )
+        .unwrap();
+        let mut account = Account::unpack_unchecked(&mismatch_account.data).unwrap();
+        account.mint = mint2_key;
+        Account::pack(account, &mut mismatch_account.data).unwrap();
+
+        // mint to account
+        do_process_instruction(
+            mint_to(&program_id, &mint_key, &account_key, &owner_key, &[], 1000).unwrap(),
+            vec![&mut mint_account, &mut account_account, &mut owner_account],
+        )
+        .unwrap();
+
+        // missing signer
+        let mut instruction = transfer(
+            &program_id,
+            &account_key,
+            &account2_key,
+            &owner_key,
+            &[],
+            1000,
+        )
+        .unwrap();
+        instruction.accounts[2].is_signer = false;
+        assert_eq!(
+            Err(ProgramError::MissingRequiredSignature),
+            do_process_instruction(
+                instruction,
+                vec![
+                    &mut account_account,
+                    &mut account2_account,
+                    &mut owner_account,
+                ],
+            )
+        );
+
+        // mismatch mint
+        assert_eq!(
+            Err(TokenError::MintMismatch.into()),
+            do_process_instruction(
+                transfer(
+                    &program_id,
+                    &account_key,
+                    &mismatch_key,
+                    &owner_key,
+                    &[],
+                    1000
+                )
+                .unwrap(),
+                vec![
+                    &mut account_account,
+                    &mut mismatch_account,
+                    &mut owner_account,
+                ],
+            )
+        );
+
+        // missing owner
+        assert_eq!(
+            Err(TokenError::OwnerMismatch.into()),
+            do_process_instruction(
+                transfer(
+                    &program_id,
+                    &account_key,
+                    &account2_key,
+                    &owner2_key,
+                    &[],
+                    1000
+                )
+                .unwrap(),
+                vec![
+                    &mut account_account,
+                    &mut account2_account,
+                    &mut owner2_account,
+                ],
+            )
+        );
+
+        // transfer
+        do_process_instruction(
+            transfer(
+                &program_id,
+                &account_key,
+                &account2_key,
+                &owner_key,
+                &[],
+                1000,
+            )
+            .unwrap(),
+            vec![
+                &mut account_account,
+                &mut account2_account,
+                &mut owner_account,
+            ],
+        )
+        .unwrap();
+
+        // insufficient funds
+        assert_eq!(
+            Err(TokenError::InsufficientFunds.into()),
+            do_process_instruction(
+                transfer(&program_id, &account_key, &account2_key, &owner_key, &[], 1).unwrap(),
+                vec![
+                    &mut account_account,
+                    &mut account2_account,
+                    &mut owner_account,
+                ],
+            )
+        );
+
+        // transfer half back
+        do_process_instruction(
+            transfer(
+                &program_id,
+                &account2_key,
+                &account_key,
+                &owner_key,
+                &[],
+                500,
+            )
+            .unwrap(),
+            vec![
+                &mut account2_account,
+                &mut account_account,
+                &mut owner_account,
+            ],
+        )
+        .unwrap();
+
+        // incorrect decimals
+        assert_eq!(
+            Err(TokenError::MintDecimalsMismatch.into()),
+            do_process_instruction(
+                transfer_checked(
+                    &program_id,
+                    &account2_key,
+                    &mint_key,
+                    &account_key,
+                    &owner_key,
+                    &[],
+                    1,
+                    10 // <-- incorrect decimals
+                )
+                .unwrap(),
+                vec![
+                    &mut account2_account,
+                    &mut mint_account,
+                    &mut account_account,
+                    &mut owner_account,
+                ],
+            )
+        );
+
+        // incorrect mint
+        assert_eq!(
+            Err(TokenError::MintMismatch.into()),
+            do_process_instruction(
+                transfer_checked(
+                    &program_id,
+                    &account2_key,
+                    &account3_key, // <-- incorrect mint
+                    &account_key,
+                    &owner_key,
+                    &[],
+                    1,
+                    2
+                )
+                .unwrap(),
+                vec![
+                    &mut account2_account,
+                    &mut account3_account, // <-- incorrect mint
+                    &mut account_account,
+                    &mut owner_account,
+                ],
+            )
+        );
+        // transfer rest with explicit decimals
+        do_process_instruction(
+            transfer_checked(
+                &program_id,
+                &account2_key,
+                &mint_key,
+                &account_key,
+                &owner_key,
+                &[],
+                500,
+                2,
+            )
+            .unwrap(),
+            vec![
+                &mut account2_account,
+                &mut mint_account,
+                &mut account_account,
+                &mut owner_account,
+            ],
+        )
+        .unwrap();
+
+        // insufficient funds
+        assert_eq!(
+            Err(TokenError::InsufficientFunds.into()),
+            do_process_instruction(
+                transfer(&program_id, &account2_key, &account_key, &owner_key, &[], 1).unwrap(),
+                vec![
+                    &mut account2_account,
+                    &mut account_account,
+                    &mut owner_account,
+                ],
+            )
+        );
+
+        // approve delegate
+        do_process_instruction(
+            approve(
+                &program_id,
+                &account_key,
+                &delegate_key,
+                &owner_key,
+                &[],
+                100,
+            )
+            .unwrap(),
+            vec![
+                &mut account_account,
+                &mut delegate_account,
+                &mut owner_account,
+            ],
+        )
+        .unwrap();
+
+        // transfer via delegate
+        do_process_instruction(
+            transfer(
+                &program_id,
+                &account_key,
+                &account2_key,
+                &delegate_key,
+                &[],
+                100,
+            )
+            .unwrap(),
+            vec![
+                &mut account_account,
+                &mut account2_account,
+                &mut delegate_account,
+            ],
+        )
+        .unwrap();
+
+        // insufficient funds approved via delegate
+        assert_eq!(
+            Err(TokenError::OwnerMismatch.into()),
+            do_process_instruction(
+                transfer(
+                    &program_id,
+                    &account_key,
+                    &account2_key,
+                    &delegate_key,
+                    &[],
+                    100
+                )
+                .unwrap(),
+                vec![
+                    &mut account_account,
+                    &mut account2_account,
+                    &mut delegate_account,
+                ],
+            )
+        );
+
+        // transfer rest
+        do_process_instruction(
+            transfer(
+                &program_id,
+                &account_key,
+                &account2_key,
+                &owner_key,
+                &[],
+                900,
+            )
+            .unwrap(),
+            vec![
+                &mut account_account,
+                &mut account2_account,
+                &mut owner_account,
+            ],
+        )
+        .unwrap();
+
+        // approve delegate
+        do_process_instruction(
+            approve(
+                &program_id,
+                &account_key,
+                &delegate_key,
+                &owner_key,
+                &[],
+                100,
+            )
+            .unwrap(),
+            vec![
+                &mut account_account,
+                &mut delegate_account,
+                &mut owner_account,
+            ],
+        )
+        .unwrap();
+
+        // insufficient funds in source account via delegate
+        assert_eq!(
+            Err(TokenError::InsufficientFunds.into()),
+            do_process_instruction(
+                transfer(
+                    &program_id,
+                    &account_key,
+                    &account2_key,
+                    &delegate_key,
+                    &[],
+                    100
+                )
+                .unwrap(),
+                vec![
+                    &mut account_account,
+                    &mut account2_account,
+                    &mut delegate_account,
+                ],
+            )
+        );
+    }
+
+    #[test]
+    fn test_self_transfer() {
+        let program_id = crate::id();
+        let account_key = Pubkey::new_unique();
+        let mut account_account = SolanaAccount::new(
+            account_minimum_balance(),
+            Account::get_packed_len(),
+            &program_id,
+        );
+        let account2_key = Pubkey::new_unique();
+        let mut account2_account = SolanaAccount::new(
+            account_minimum_balance(),
+            Account::get_packed_len(),
+            &program_id,
+        );
+        let account3_key = Pubkey::new_unique();
+        let mut account3_account = SolanaAccount::new(
+            account_minimum_balance(),
+            Account::get_packed_len(),
+            &program_id,
+        );
+        let delegate_key = Pubkey::new_unique();
+        let mut delegate_account = SolanaAccount::default();
+        let owner_key = Pubkey::new_unique();
+        let mut owner_account = SolanaAccount::default();
+        let owner2_key = Pubkey::new_unique();
+        let mut owner2_account = SolanaAccount::default();
+        let mint_key = Pubkey::new_unique();
+        let mut mint_account =
+            SolanaAccount::new(mint_minimum_balance(), Mint::get_packed_len(), &program_id);
+        let mut rent_sysvar = rent_sysvar();
+
+        // create mint
+        do_process_instruction(
+            initialize_mint(&program_id, &mint_key, &owner_key, None, 2).unwrap(),
+            vec![&mut mint_account, &mut rent_sysvar],
+        )
+        .unwrap();
+
+        // create account
+        do_process_instruction(
+            initialize_account(&program_id, &account_key, &mint_key, &owner_key).unwrap(),
+            vec![
+                &mut account_account,
+                &mut mint_account,
+                &mut owner_account,
+                &mut rent_sysvar,
+            ],
+        )
+        .unwrap();
+
+        // create another account
+        do_process_instruction(
+            initialize_account(&program_id, &account2_key, &mint_key, &owner_key).unwrap(),
+            vec![
+                &mut account2_account,
+                &mut mint_account,
+                &mut owner_account,
+                &mut rent_sysvar,
+            ],
+        )
+        .unwrap();
+
+        // create another account
+        do_process_instruction(
+            initialize_account(&program_id, &account3_key, &mint_key, &owner_key).unwrap(),
+            vec![
+                &mut account3_account,
+                &mut mint_account,
+                &mut owner_account,
+                &mut rent_sysvar,
+            ],
+        )
+        .unwrap();
+
+        // mint to account
+        do_process_instruction(
+            mint_to(&program_id, &mint_key, &account_key, &owner_key, &[], 1000).unwrap(),
+            vec![&mut mint_account, &mut account_account, &mut owner_account],
+        )
+        .unwrap();
+
+        let account_info = (&account_key, false, &mut account_account).into_account_info();
+        let account3_info = (&account3_key, false, &mut account3_account).into_account_info();
+        let delegate_info = (&delegate_key, true, &mut delegate_account).into_account_info();
+        let owner_info = (&owner_key, true, &mut owner_account).into_account_info();
+        let owner2_info = (&owner2_key, true, &mut owner2_account).into_account_info();
+        let mint_info = (&mint_key, false, &mut mint_account).into_account_info();
+
+        // transfer
+        let instruction = transfer(
+            &program_id,
+            account_info.key,
+            account_info.key,
+            owner_info.key,
+            &[],
+            1000,
+        )
+        .unwrap();
+        assert_eq!(
+            Ok(()),
+            Processor::process(
+                &instruction.program_id,
+                &[
+                    account_info.clone(),
+                    account_info.clone(),
+                    owner_info.clone(),
+                ],
+                &instruction.data,
+            )
+        );
+        // no balance change...
+        let account = Account::unpack_unchecked(&account_info.try_borrow_data().unwrap()).unwrap();
+        assert_eq!(account.amount, 1000);
+
+        // transfer checked
+        let instruction = transfer_checked(
+            &program_id,
+            account_info.key,
+            mint_info.key,
+            account_info.key,
+            owner_info.key,
+            &[],
+            1000,
+            2,
+        )
+        .unwrap();
+        assert_eq!(
+            Ok(()),
+            Processor::process(
+                &instruction.program_id,
+                &[
+                    account_info.clone(),
+                    mint_info.clone(),
+                    account_info.clone(),
+                    owner_info.clone(),
+                ],
+                &instruction.data,
+            )
+        );
+        // no balance change...
+        let account = Account::unpack_unchecked(&account_info.try_borrow_data().unwrap()).unwrap();
+        assert_eq!(account.amount, 1000);
+
+        // missing signer
+        let mut owner_no_sign_info = owner_info.clone();
+        let mut instruction = transfer(
+            &program_id,
+            account_info.key,
+            account_info.key,
+            owner_no_sign_info.key,
+            &[],
+            1000,
+        )
+        .unwrap();
+        instruction.accounts[2].is_signer = false;
+        owner_no_sign_info.is_signer = false;
+        assert_eq!(
+            Err(ProgramError::MissingRequiredSignature),
+            Processor::process(
+                &instruction.program_id,
+                &[
+                    account_info.clone(),
+                    account_info.clone(),
+                    owner_no_sign_info.clone(),
+                ],
+                &instruction.data,
+            )
+        );
+
+        // missing signer checked
+        let mut instruction = transfer_checked(
+            &program_id,
+            account_info.key,
+            mint_info.key,
+            account_info.key,
+            owner_no_sign_info.key,
+            &[],
+            1000,
+            2,
+        )
+        .unwrap();
+        instruction.accounts[3].is_signer = false;
+        assert_eq!(
+            Err(ProgramError::MissingRequiredSignature),
+            Processor::process(
+                &instruction.program_id,
+                &[
+                    account_info.clone(),
+                    mint_info.clone(),
+                    account_info.clone(),
+                    owner_no_sign_info,
+                ],
+                &instruction.data,
+            )
+        );
+
+        // missing owner
+        let instruction = transfer(
+            &program_id,
+            account_info.key,
+            account_info.key,
+            owner2_info.key,
+            &[],
+            1000,
+        )
+        .unwrap();
+        assert_eq!(
+            Err(TokenError::OwnerMismatch.into()),
+            Processor::process(
+                &instruction.program_id,
+                &[
+                    account_info.clone(),
+                    account_info.clone(),
+                    owner2_info.clone(),
+                ],
+                &instruction.data,
+            )
+        );
+
+        // missing owner checked
+        let instruction = transfer_checked(
+            &program_id,
+            account_info.key,
+            mint_info.key,
+            account_info.key,
+            owner2_info.key,
+            &[],
+            1000,
+            2,
+        )
+        .unwrap();
+        assert_eq!(
+            Err(TokenError::OwnerMismatch.into()),
+            Processor::process(
+                &instruction.program_id,
+                &[
+                    account_info.clone(),
+                    mint_info.clone(),
+                    account_info.clone(),
+                    owner2_info.clone(),
+                ],
+                &instruction.data,
+            )
+        );
+
+        // insufficient funds
+        let instruction = transfer(
+            &program_id,
+            account_info.key,
+            account_info.key,
+            owner_info.key,
+            &[],
+            1001,
+        )
+        .unwrap();
+        assert_eq!(
+            Err(TokenError::InsufficientFunds.into()),
+            Processor::process(
+                &instruction.program_id,
+                &[
+                    account_info.clone(),
+                    account_info.clone(),
+                    owner_info.clone(),
+                ],
+                &instruction.data,
+            )
+        );
+
+        // insufficient funds checked
+        let instruction = transfer_checked(
+            &program_id,
+            account_info.key,
+            mint_info.key,
+            account_info.key,
+            owner_info.key,
+            &[],
+            1001,
+            2,
+        )
+        .unwrap();
+        assert_eq!(
+            Err(TokenError::InsufficientFunds.into()),
+            Processor::process(
+                &instruction.program_id,
+                &[
+                    account_info.clone(),
+                    mint_info.clone(),
+                    account_info.clone(),
+                    owner_info.clone(),
+                ],
+                &instruction.data,
+            )
+        );
+
+        // incorrect decimals
+        let instruction = transfer_checked(
+            &program_id,
+            account_info.key,
+            mint_info.key,
+            account_info.key,
+            owner_info.key,
+            &[],
+            1,
+            10, // <-- incorrect decimals
+        )
+        .unwrap();
+        assert_eq!(
+            Err(TokenError::MintDecimalsMismatch.into()),
+            Processor::process(
+                &instruction.program_id,
+                &[
+                    account_info.clone(),
+                    mint_info.clone(),
+                    account_info.clone(),
+                    owner_info.clone(),
+                ],
+                &instruction.data,
+            )
+        );
+
+        // incorrect mint
+        let instruction = transfer_checked(
+            &program_id,
+            account_info.key,
+            account3_info.key, // <-- incorrect mint
+            account_info.key,
+            owner_info.key,
+            &[],
+            1,
+            2,
+        )
+        .unwrap();
+        assert_eq!(
+            Err(TokenError::MintMismatch.into()),
+            Processor::process(
+                &instruction.program_id,
+                &[
+                    account_info.clone(),
+                    account3_info.clone(), // <-- incorrect mint
+                    account_info.clone(),
+                    owner_info.clone(),
+                ],
+                &instruction.data,
+            )
+        );
+
+        // approve delegate
+        let instruction = approve(
+            &program_id,
+            account_info.key,
+            delegate_info.key,
+            owner_info.key,
+            &[],
+            100,
+        )
+        .unwrap();
+        Processor::process(
+            &instruction.program_id,
+            &[
+                account_info.clone(),
+                delegate_info.clone(),
+                owner_info.clone(),
+            ],
+            &instruction.data,
+        )
+        .unwrap();
+
+        // delegate transfer
+        let instruction = transfer(
+            &program_id,
+            account_info.key,
+            account_info.key,
+            delegate_info.key,
+            &[],
+            100,
+        )
+        .unwrap();
+        assert_eq!(
+            Ok(()),
+            Processor::process(
+                &instruction.program_id,
+                &[
+                    account_info.clone(),
+                    account_info.clone(),
+                    delegate_info.clone(),
+                ],
+                &instruction.data,
+            )
+        );
+        // no balance change...
+        let account = Account::unpack_unchecked(&account_info.try_borrow_data().unwrap()).unwrap();
+        assert_eq!(account.amount, 1000);
+        assert_eq!(account.delegated_amount, 100);
+
+        // delegate transfer checked
+        let instruction = transfer_checked(
+            &program_id,
+            account_info.key,
+            mint_info.key,
+            account_info.key,
+            delegate_info.key,
+            &[],
+            100,
+            2,
+        )
+        .unwrap();
+        assert_eq!(
+            Ok(()),
+            Processor::process(
+                &instruction.program_id,
+                &[
+                    account_info.clone(),
+                    mint_info.clone(),
+                    account_info.clone(),
+                    delegate_info.clone(),
+                ],
+                &instruction.data,
+            )
+        );
+        // no balance change...
+        let account = Account::unpack_unchecked(&account_info.try_borrow_data().unwrap()).unwrap();
+        assert_eq!(account.amount, 1000);
+        assert_eq!(account.delegated_amount, 100);
+
+        // delegate insufficient funds
+        let instruction = transfer(
+            &program_id,
+            account_info.key,
+            account_info.key,
+            delegate_info.key,
+            &[],
+            101,
+        )
+        .unwrap();
+        assert_eq!(
+            Err(TokenError::InsufficientFunds.into()),
+            Processor::process(
+                &instruction.program_id,
+                &[
+                    account_info.clone(),
+                    account_info.clone(),
+                    delegate_info.clone(),
+                ],
+                &instruction.data,
+            )
+        );
+
+        // delegate insufficient funds checked
+        let instruction = transfer_checked(
+            &program_id,
+            account_info.key,
+            mint_info.key,
+            account_info.key,
+            delegate_info.key,
+            &[],
+            101,
+            2,
+        )
+        .unwrap();
+        assert_eq!(
+            Err(TokenError::InsufficientFunds.into()),
+            Processor::process(
+                &instruction.program_id,
+                &[
+                    account_info.clone(),
+                    mint_info.clone(),
+                    account_info.clone(),
+                    delegate_info.clone(),
+                ],
+                &instruction.data,
+            )
+        );
+
+        // owner transfer with delegate assigned
+        let instruction = transfer(
+            &program_id,
+            account_info.key,
+            account_info.key,
+            owner_info.key,
+            &[],
+            1000,
+        )
+        .unwrap();
+        assert_eq!(
+            Ok(()),
+            Processor::process(
+                &instruction.program_id,
+                &[
+                    account_info.clone(),
+                    account_info.clone(),
+                    owner_info.clone(),
+                ],
+                &instruction.data,
+            )
+        );
+        // no balance change...
+        let account = Account::unpack_unchecked(&account_info.try_borrow_data().unwrap()).unwrap();
+        assert_eq!(account.amount, 1000);
+
+        // owner transfer with delegate assigned checked
+        let instruction = transfer_checked(
+            &program_id,
+            account_info.key,
+            mint_info.key,
+            account_info.key,
+            owner_info.key,
+            &[],
+            1000,
+            2,
+        )
+        .unwrap();
+        assert_eq!(
+            Ok(()),
+            Processor::process(
+                &instruction.program_id,
+                &[
+                    account_info.clone(),
+                    mint_info.clone(),
+                    account_info.clone(),
+                    owner_info.clone(),
+                ],
+                &instruction.data,
+            )
+        );
+        // no balance change...
+        let account = Account::unpack_unchecked(&account_info.try_borrow_data().unwrap()).unwrap();
+        assert_eq!(account.amount, 1000);
+    }
+
+    #[test]
+    fn test_mintable_token_with_zero_supply() {
+        let program_id = crate::id();
+        let account_key = Pubkey::new_unique();
+        let mut account_account = SolanaAccount::new(
+            account_minimum_balance(),
+            Account::get_packed_len(),
+            &program_id,
+        );
+        let owner_key = Pubkey::new_unique();
+        let mut owner_account = SolanaAccount::default();
+        let mint_key = Pubkey::new_unique();
+        let mut mint_account =
+            SolanaAccount::new(mint_minimum_balance(), Mint::get_packed_len(), &program_id);
+        let mut rent_sysvar = rent_sysvar();
+
+        // create mint-able token with zero supply
+        let decimals = 2;
+        do_process_instruction(
+            initialize_mint(&program_id, &mint_key, &owner_key, None, decimals).unwrap(),
+            vec![&mut mint_account, &mut rent_sysvar],
+        )
+        .unwrap();
+        let mint = Mint::unpack_unchecked(&mint_account.data).unwrap();
+        assert_eq!(
+            mint,
+            Mint {
+                mint_authority: COption::Some(owner_key),
+                supply: 0,
+                decimals,
+                is_initialized: true,
+                freeze_authority: COption::None,
+                burner: COption::None,
+                pauser: COption::None,
+                freezer: COption::None,
+                minting_paused: false,
+                pending_mint_authority: COption::None,
+                pending_freeze_authority: COption::None,
+                authority_timelock: 0,
+                queued_authority: TimelockedAuthority::None,
+                queued_new_authority: COption::None,
+                queued_unlock_timestamp: 0,
+                max_balance: COption::None,
+                trading_start_timestamp: 0,
+                transfer_cooldown: 0,
+                compliance_authority: COption::None,
+                min_transfer_amount: 0,
+            }
+        );
+
+        // create account
+        do_process_instruction(
+            initialize_account(&program_id, &account_key, &mint_key, &owner_key).unwrap(),
+            vec![
+                &mut account_account,
+                &mut mint_account,
+                &mut owner_account,
+                &mut rent_sysvar,
+            ],
+        )
+        .unwrap();
+
+        // mint to
+        do_process_instruction(
+            mint_to(&program_id, &mint_key, &account_key, &owner_key, &[], 42).unwrap(),
+            vec![&mut mint_account, &mut account_account, &mut owner_account],
+        )
+        .unwrap();
+        let _ = Mint::unpack(&mint_account.data).unwrap();
+        let account = Account::unpack_unchecked(&account_account.data).unwrap();
+        assert_eq!(account.amount, 42);
+
+        // mint to 2, with incorrect decimals
+        assert_eq!(
+            Err(TokenError::MintDecimalsMismatch.into()),
+            do_process_instruction(
+                mint_to_checked(
+                    &program_id,
+                    &mint_key,
+                    &account_key,
+                    &owner_key,
+                    &[],
+                    42,
+                    decimals + 1
+                )
+                .unwrap(),
+                vec![&mut mint_account, &mut account_account, &mut owner_account],
+            )
+        );
+
+        let _ = Mint::unpack(&mint_account.data).unwrap();
+        let account = Account::unpack_unchecked(&account_account.data).unwrap();
+        assert_eq!(account.amount, 42);
+
+        // mint to 2
+        do_process_instruction(
+            mint_to_checked(
+                &program_id,
+                &mint_key,
+                &account_key,
+                &owner_key,
+                &[],
+                42,
+                decimals,
+            )
+            .unwrap(),
+            vec![&mut mint_account, &mut account_account, &mut owner_account],
+        )
+        .unwrap();
+        let _ = Mint::unpack(&mint_account.data).unwrap();
+        let account = Account::unpack_unchecked(&account_account.data).unwrap();
+        assert_eq!(account.amount, 84);
+    }
+
+    #[test]
+    fn test_approve_dups() {
+        let program_id = crate::id();
+        let account1_key = Pubkey::new_unique();
+        let mut account1_account = SolanaAccount::new(
+            account_minimum_balance(),
+            Account::get_packed_len(),
+            &program_id,
+        );
+        let account1_info: AccountInfo = (&account1_key, true, &mut account1_account).into();
+        let account2_key = Pubkey::new_unique();
+        let mut account2_account = SolanaAccount::new(
+            account_minimum_balance(),
+            Account::get_packed_len(),
+            &program_id,
+        );
+        let account2_info: AccountInfo = (&account2_key, false, &mut account2_account).into();
+        let account3_key = Pubkey::new_unique();
+        let mut account3_account = SolanaAccount::new(
+            account_minimum_balance(),
+            Account::get_packed_len(),
+            &program_id,
+        );
+        let account3_info: AccountInfo = (&account3_key, true, &mut account3_account).into();
+        let multisig_key = Pubkey::new_unique();
+        let mut multisig_account = SolanaAccount::new(
+            multisig_minimum_balance(),
+            Multisig::get_packed_len(),
+            &program_id,
+        );
+        let multisig_info: AccountInfo = (&multisig_key, true, &mut multisig_account).into();
+        let owner_key = Pubkey::new_unique();
+        let mut owner_account = SolanaAccount::default();
+        let owner_info: AccountInfo = (&owner_key, true, &mut owner_account).into();
+        let mint_key = Pubkey::new_unique();
+        let mut mint_account =
+            SolanaAccount::new(mint_minimum_balance(), Mint::get_packed_len(), &program_id);
+        let mint_info: AccountInfo = (&mint_key, false, &mut mint_account).into();
+        let rent_key = rent::id();
+        let mut rent_sysvar = rent_sysvar();
+        let rent_info: AccountInfo = (&rent_key, false, &mut rent_sysvar).into();
+
+        // create mint
+        do_process_instruction_dups(
+            initialize_mint(&program_id, &mint_key, &owner_key, None, 2).unwrap(),
+            vec![mint_info.clone(), rent_info.clone()],
+        )
+        .unwrap();
+
+        // create account
+        do_process_instruction_dups(
+            initialize_account(&program_id, &account1_key, &mint_key, &account1_key).unwrap(),
+            vec![
+                account1_info.clone(),
+                mint_info.clone(),
+                account1_info.clone(),
+                rent_info.clone(),
+            ],
+        )
+        .unwrap();
+
+        // create another account
+        do_process_instruction_dups(
+            initialize_account(&program_id, &account2_key, &mint_key, &owner_key).unwrap(),
+            vec![
+                account2_info.clone(),
+                mint_info.clone(),
+                owner_info.clone(),
+                rent_info.clone(),
+            ],
+        )
+        .unwrap();
+
+        // mint to account
+        do_process_instruction_dups(
+            mint_to(&program_id, &mint_key, &account1_key, &owner_key, &[], 1000).unwrap(),
+            vec![mint_info.clone(), account1_info.clone(), owner_info.clone()],
+        )
+        .unwrap();
+
+        // source-owner approve
+        do_process_instruction_dups(
+            approve(
+                &program_id,
+                &account1_key,
+                &account2_key,
+                &account1_key,
+                &[],
+                500,
+            )
+            .unwrap(),
+            vec![
+                account1_info.clone(),
+                account2_info.clone(),
+                account1_info.clone(),
+            ],
+        )
+        .unwrap();
+
+        // source-owner approve_checked
+        do_process_instruction_dups(
+            approve_checked(
+                &program_id,
+                &account1_key,
+                &mint_key,
+                &account2_key,
+                &account1_key,
+                &[],
+                500,
+                2,
+            )
+            .unwrap(),
+            vec![
+                account1_info.clone(),
+                mint_info.clone(),
+                account2_info.clone(),
+                account1_info.clone(),
+            ],
+        )
+        .unwrap();
+
+        // source-owner revoke
+        do_process_instruction_dups(
+            revoke(&program_id, &account1_key, &account1_key, &[]).unwrap(),
+            vec![account1_info.clone(), account1_info.clone()],
+        )
+        .unwrap();
+
+        // test source-multisig signer
+        do_process_instruction_dups(
+            initialize_multisig(&program_id, &multisig_key, &[&account3_key], 1).unwrap(),
+            vec![
+                multisig_info.clone(),
+                rent_info.clone(),
+                account3_info.clone(),
+            ],
+        )
+        .unwrap();
+
+        do_process_instruction_dups(
+            initialize_account(&program_id, &account3_key, &mint_key, &multisig_key).unwrap(),
+            vec![
+                account3_info.clone(),
+                mint_info.clone(),
+                multisig_info.clone(),
+                rent_info.clone(),
+            ],
+        )
+        .unwrap();
+
+        do_process_instruction_dups(
+            mint_to(&program_id, &mint_key, &account3_key, &owner_key, &[], 1000).unwrap(),
+            vec![mint_info.clone(), account3_info.clone(), owner_info.clone()],
+        )
+        .unwrap();
+
+        // source-multisig-signer approve
+        do_process_instruction_dups(
+            approve(
+                &program_id,
+                &account3_key,
+                &account2_key,
+                &multisig_key,
+                &[&account3_key],
+                500,
+            )
+            .unwrap(),
+            vec![
+                account3_info.clone(),
+                account2_info.clone(),
+                multisig_info.clone(),
+                account3_info.clone(),
+            ],
+        )
+        .unwrap();
+
+        // source-multisig-signer approve_checked
+        do_process_instruction_dups(
+            approve_checked(
+                &program_id,
+                &account3_key,
+                &mint_key,
+                &account2_key,
+                &multisig_key,
+                &[&account3_key],
+                500,
+                2,
+            )
+            .unwrap(),
+            vec![
+                account3_info.clone(),
+                mint_info.clone(),
+                account2_info.clone(),
+                multisig_info.clone(),
+                account3_info.clone(),
+            ],
+        )
+        .unwrap();
+
+        // source-owner multisig-signer
+        do_process_instruction_dups(
+            revoke(&program_id, &account3_key, &multisig_key, &[&account3_key]).unwrap(),
+            vec![
+                account3_info.clone(),
+                multisig_info.clone(),
+                account3_info.clone(),
+            ],
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn test_approve() {
+        let program_id = crate::id();
+        let account_key = Pubkey::new_unique();
+        let mut account_account = SolanaAccount::new(
+            account_minimum_balance(),
+            Account::get_packed_len(),
+            &program_id,
+        );
+        let account2_key = Pubkey::new_unique();
+        let mut account2_account = SolanaAccount::new(
+            account_minimum_balance(),
+            Account::get_packed_len(),
+            &program_id,
+        );
+        let delegate_key = Pubkey::new_unique();
+        let mut delegate_account = SolanaAccount::default();
+        let owner_key = Pubkey::new_unique();
+        let mut owner_account = SolanaAccount::default();
+        let owner2_key = Pubkey::new_unique();
+        let mut owner2_account = SolanaAccount::default();
+        let mint_key = Pubkey::new_unique();
+        let mut mint_account =
+            SolanaAccount::new(mint_minimum_balance(), Mint::get_packed_len(), &program_id);
+        let mut rent_sysvar = rent_sysvar();
+
+        // create mint
+        do_process_instruction(
+            initialize_mint(&program_id, &mint_key, &owner_key, None, 2).unwrap(),
+            vec![&mut mint_account, &mut rent_sysvar],
+        )
+        .unwrap();
+
+        // create account
+        do_process_instruction(
+            initialize_account(&program_id, &account_key, &mint_key, &owner_key).unwrap(),
+            vec![
+                &mut account_account,
+                &mut mint_account,
+                &mut owner_account,
+                &mut rent_sysvar,
+            ],
+        )
+        .unwrap();
+
+        // create another account
+        do_process_instruction(
+            initialize_account(&program_id, &account2_key, &mint_key, &owner_key).unwrap(),
+            vec![
+                &mut account2_account,
+                &mut mint_account,
+                &mut owner_account,
+                &mut rent_sysvar,
+            ],
+        )
+        .unwrap();
+
+        // mint to account
+        do_process_instruction(
+            mint_to(&program_id, &mint_key, &account_key, &owner_key, &[], 1000).unwrap(),
+            vec![&mut mint_account, &mut account_account, &mut owner_account],
+        )
+        .unwrap();
+
+        // missing signer
+        let mut instruction = approve(
+            &program_id,
+            &account_key,
+            &delegate_key,
+            &owner_key,
+            &[],
+            100,
+        )
+        .unwrap();
+        instruction.accounts[2].is_signer = false;
+        assert_eq!(
+            Err(ProgramError::MissingRequiredSignature),
+            do_process_instruction(
+                instruction,
+                vec![
+                    &mut account_account,
+                    &mut delegate_account,
+                    &mut owner_account,
+                ],
+            )
+        );
+
+        // no owner
+        assert_eq!(
+            Err(TokenError::OwnerMismatch.into()),
+            do_process_instruction(
+                approve(
+                    &program_id,
+                    &account_key,
+                    &delegate_key,
+                    &owner2_key,
+                    &[],
+                    100
+                )
+                .unwrap(),
+                vec![
+                    &mut account_account,
+                    &mut delegate_account,
+                    &mut owner2_account,
+                ],
+            )
+        );
+
+        // approve delegate
+        do_process_instruction(
+            approve(
+                &program_id,
+                &account_key,
+                &delegate_key,
+                &owner_key,
+                &[],
+                100,
+            )
+            .unwrap(),
+            vec![
+                &mut account_account,
+                &mut delegate_account,
+                &mut owner_account,
+            ],
+        )
+        .unwrap();
+
+        // approve delegate 2, with incorrect decimals
+        assert_eq!(
+            Err(TokenError::MintDecimalsMismatch.into()),
+            do_process_instruction(
+                approve_checked(
+                    &program_id,
+                    &account_key,
+                    &mint_key,
+                    &delegate_key,
+                    &owner_key,
+                    &[],
+                    100,
+                    0 // <-- incorrect decimals
+                )
+                .unwrap(),
+                vec![
+                    &mut account_account,
+                    &mut mint_account,
+                    &mut delegate_account,
+                    &mut owner_account,
+                ],
+            )
+        );
+
+        // approve delegate 2, with incorrect mint
+        assert_eq!(
+            Err(TokenError::MintMismatch.into()),
+            do_process_instruction(
+                approve_checked(
+                    &program_id,
+                    &account_key,
+                    &account2_key, // <-- bad mint
+                    &delegate_key,
+                    &owner_key,
+                    &[],
+                    100,
+                    0
+                )
+                .unwrap(),
+                vec![
+                    &mut account_account,
+                    &mut account2_account, // <-- bad mint
+                    &mut delegate_account,
+                    &mut owner_account,
+                ],
+            )
+        );
+
+        // approve delegate 2
+        do_process_instruction(
+            approve_checked(
+                &program_id,
+                &account_key,
+                &mint_key,
+                &delegate_key,
+                &owner_key,
+                &[],
+                100,
+                2,
+            )
+            .unwrap(),
+            vec![
+                &mut account_account,
+                &mut mint_account,
+                &mut delegate_account,
+                &mut owner_account,
+            ],
+        )
+        .unwrap();
+
+        // revoke delegate
+        do_process_instruction(
+            revoke(&program_id, &account_key, &owner_key, &[]).unwrap(),
+            vec![&mut account_account, &mut owner_account],
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn test_set_authority_dups() {
+        let program_id = crate::id();
+        let account1_key = Pubkey::new_unique();
+        let mut account1_account = SolanaAccount::new(
+            account_minimum_balance(),
+            Account::get_packed_len(),
+            &program_id,
+        );
+        let account1_info: AccountInfo = (&account1_key, true, &mut account1_account).into();
+        let owner_key = Pubkey::new_unique();
+        let mint_key = Pubkey::new_unique();
+        let mut mint_account =
+            SolanaAccount::new(mint_minimum_balance(), Mint::get_packed_len(), &program_id);
+        let mint_info: AccountInfo = (&mint_key, true, &mut mint_account).into();
+        let rent_key = rent::id();
+        let mut rent_sysvar = rent_sysvar();
+        let rent_info: AccountInfo = (&rent_key, false, &mut rent_sysvar).into();
+
+        // create mint
+        do_process_instruction_dups(
+            initialize_mint(&program_id, &mint_key, &mint_key, Some(&mint_key), 2).unwrap(),
+            vec![mint_info.clone(), rent_info.clone()],
+        )
+        .unwrap();
+
+        // create account
+        do_process_instruction_dups(
+            initialize_account(&program_id, &account1_key, &mint_key, &account1_key).unwrap(),
+            vec![
+                account1_info.clone(),
+                mint_info.clone(),
+                account1_info.clone(),
+                rent_info.clone(),
+            ],
+        )
+        .unwrap();
+
+        // set mint_authority when currently self
+        do_process_instruction_dups(
+            set_authority(
+                &program_id,
+                &mint_key,
+                Some(&owner_key),
+                AuthorityType::MintTokens,
+                &mint_key,
+                &[],
+            )
+            .unwrap(),
+            vec![mint_info.clone(), mint_info.clone()],
+        )
+        .unwrap();
+
+        // set freeze_authority when currently self
+        do_process_instruction_dups(
+            set_authority(
+                &program_id,
+                &mint_key,
+                Some(&owner_key),
+                AuthorityType::FreezeAccount,
+                &mint_key,
+                &[],
+            )
+            .unwrap(),
+            vec![mint_info.clone(), mint_info.clone()],
+        )
+        .unwrap();
+
+        // set account owner when currently self
+        do_process_instruction_dups(
+            set_authority(
+                &program_id,
+                &account1_key,
+                Some(&owner_key),
+                AuthorityType::AccountOwner,
+                &account1_key,
+                &[],
+            )
+            .unwrap(),
+            vec![account1_info.clone(), account1_info.clone()],
+        )
+        .unwrap();
+
+        // set close_authority when currently self
+        let mut account = Account::unpack_unchecked(&account1_info.data.borrow()).unwrap();
+        account.close_authority = COption::Some(account1_key);
+        Account::pack(account, &mut account1_info.data.borrow_mut()).unwrap();
+
+        do_process_instruction_dups(
+            set_authority(
+                &program_id,
+                &account1_key,
+                Some(&owner_key),
+                AuthorityType::CloseAccount,
+                &account1_key,
+                &[],
+            )
+            .unwrap(),
+            vec![account1_info.clone(), account1_info.clone()],
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn test_set_authority() {
+        let program_id = crate::id();
+        let account_key = Pubkey::new_unique();
+        let mut account_account = SolanaAccount::new(
+            account_minimum_balance(),
+            Account::get_packed_len(),
+            &program_id,
+        );
+        let account2_key = Pubkey::new_unique();
+        let mut account2_account = SolanaAccount::new(
+            account_minimum_balance(),
+            Account::get_packed_len(),
+            &program_id,
+        );
+        let owner_key = Pubkey::new_unique();
+        let mut owner_account = SolanaAccount::default();
+        let owner2_key = Pubkey::new_unique();
+        let mut owner2_account = SolanaAccount::default();
+        let owner3_key = Pubkey::new_unique();
+        let mut owner3_account = SolanaAccount::default();
+        let mint_key = Pubkey::new_unique();
+        let mut mint_account =
+            SolanaAccount::new(mint_minimum_balance(), Mint::get_packed_len(), &program_id);
+        let mint2_key = Pubkey::new_unique();
+        let mut mint2_account =
+            SolanaAccount::new(mint_minimum_balance(), Mint::get_packed_len(), &program_id);
+        let mut rent_sysvar = rent_sysvar();
+
+        // create new mint with owner
+        do_process_instruction(
+            initialize_mint(&program_id, &mint_key, &owner_key, None, 2).unwrap(),
+            vec![&mut mint_account, &mut rent_sysvar],
+        )
+        .unwrap();
+
+        // create mint with owner and freeze_authority
+        do_process_instruction(
+            initialize_mint(&program_id, &mint2_key, &owner_key, Some(&owner_key), 2).unwrap(),
+            vec![&mut mint2_account, &mut rent_sysvar],
+        )
+        .unwrap();
+
+        // invalid account
+        assert_eq!(
+            Err(ProgramError::UninitializedAccount),
+            do_process_instruction(
+                set_authority(
+                    &program_id,
+                    &account_key,
+                    Some(&owner2_key),
+                    AuthorityType::AccountOwner,
+                    &owner_key,
+                    &[]
+                )
+                .unwrap(),
+                vec![&mut account_account, &mut owner_account],
+            )
+        );
+
+        // create account
+        do_process_instruction(
+            initialize_account(&program_id, &account_key, &mint_key, &owner_key).unwrap(),
+            vec![
+                &mut account_account,
+                &mut mint_account,
+                &mut owner_account,
+                &mut rent_sysvar,
+            ],
+        )
+        .unwrap();
+
+        // create another account
+        do_process_instruction(
+            initialize_account(&program_id, &account2_key, &mint2_key, &owner_key).unwrap(),
+            vec![
+                &mut account2_account,
+                &mut mint2_account,
+                &mut owner_account,
+                &mut rent_sysvar,
+            ],
+        )
+        .unwrap();
+
+        // missing owner
+        assert_eq!(
+            Err(TokenError::OwnerMismatch.into()),
+            do_process_instruction(
+                set_authority(
+                    &program_id,
+                    &account_key,
+                    Some(&owner_key),
+                    AuthorityType::AccountOwner,
+                    &owner2_key,
+                    &[]
+                )
+                .unwrap(),
+                vec![&mut account_account, &mut owner2_account],
+            )
+        );
+
+        // owner did not sign
+        let mut instruction = set_authority(
+            &program_id,
+            &account_key,
+            Some(&owner2_key),
+            AuthorityType::AccountOwner,
+            &owner_key,
+            &[],
+        )
+        .unwrap();
+        instruction.accounts[1].is_signer = false;
+        assert_eq!(
+            Err(ProgramError::MissingRequiredSignature),
+            do_process_instruction(instruction, vec![&mut account_account, &mut owner_account,],)
+        );
+
+        // wrong authority type
+        assert_eq!(
+            Err(TokenError::AuthorityTypeNotSupported.into()),
+            do_process_instruction(
+                set_authority(
+                    &program_id,
+                    &account_key,
+                    Some(&owner2_key),
+                    AuthorityType::FreezeAccount,
+                    &owner_key,
+                    &[],
+                )
+                .unwrap(),
+                vec![&mut account_account, &mut owner_account],
+            )
+        );
+
+        // account owner may not be set to None
+        assert_eq!(
+            Err(TokenError::InvalidInstruction.into()),
+            do_process_instruction(
+                set_authority(
+                    &program_id,
+                    &account_key,
+                    None,
+                    AuthorityType::AccountOwner,
+                    &owner_key,
+                    &[],
+                )
+                .unwrap(),
+                vec![&mut account_account, &mut owner_account],
+            )
+        );
+
+        // set delegate
+        do_process_instruction(
+            approve(
+                &program_id,
+                &account_key,
+                &owner2_key,
+                &owner_key,
+                &[],
+                u64::MAX,
+            )
+            .unwrap(),
+            vec![
+                &mut account_account,
+                &mut owner2_account,
+                &mut owner_account,
+            ],
+        )
+        .unwrap();
+        let account = Account::unpack_unchecked(&account_account.data).unwrap();
+        assert_eq!(account.delegate, COption::Some(owner2_key));
+        assert_eq!(account.delegated_amount, u64::MAX);
+
+        // set owner
+        do_process_instruction(
+            set_authority(
+                &program_id,
+                &account_key,
+                Some(&owner3_key),
+                AuthorityType::AccountOwner,
+                &owner_key,
+                &[],
+            )
+            .unwrap(),
+            vec![&mut account_account, &mut owner_account],
+        )
+        .unwrap();
+
+        // check delegate cleared
+        let account = Account::unpack_unchecked(&account_account.data).unwrap();
+        assert_eq!(account.delegate, COption::None);
+        assert_eq!(account.delegated_amount, 0);
+
+        // set owner without existing delegate
+        do_process_instruction(
+            set_authority(
+                &program_id,
+                &account_key,
+                Some(&owner2_key),
+                AuthorityType::AccountOwner,
+                &owner3_key,
+                &[],
+            )
+            .unwrap(),
+            vec![&mut account_account, &mut owner3_account],
+        )
+        .unwrap();
+
+        // set close_authority
+        do_process_instruction(
+            set_authority(
+                &program_id,
+                &account_key,
+                Some(&owner2_key),
+                AuthorityType::CloseAccount,
+                &owner2_key,
+                &[],
+            )
+            .unwrap(),
+            vec![&mut account_account, &mut owner2_account],
+        )
+        .unwrap();
+
+        // close_authority may be set to None
+        do_process_instruction(
+            set_authority(
+                &program_id,
+                &account_key,
+                None,
+                AuthorityType::CloseAccount,
+                &owner2_key,
+                &[],
+            )
+            .unwrap(),
+            vec![&mut account_account, &mut owner2_account],
+        )
+        .unwrap();
+
+        // wrong owner
+        assert_eq!(
+            Err(TokenError::OwnerMismatch.into()),
+            do_process_instruction(
+                set_authority(
+                    &program_id,
+                    &mint_key,
+                    Some(&owner3_key),
+                    AuthorityType::MintTokens,
+                    &owner2_key,
+                    &[]
+                )
+                .unwrap(),
+                vec![&mut mint_account, &mut owner2_account],
+            )
+        );
+
+        // owner did not sign
+        let mut instruction = set_authority(
+            &program_id,
+            &mint_key,
+            Some(&owner2_key),
+            AuthorityType::MintTokens,
+            &owner_key,
+            &[],
+        )
+        .unwrap();
+        instruction.accounts[1].is_signer = false;
+        assert_eq!(
+            Err(ProgramError::MissingRequiredSignature),
+            do_process_instruction(instruction, vec![&mut mint_account, &mut owner_account],)
+        );
+
+        // cannot freeze
+        assert_eq!(
+            Err(TokenError::MintCannotFreeze.into()),
+            do_process_instruction(
+                set_authority(
+                    &program_id,
+                    &mint_key,
+                    Some(&owner2_key),
+                    AuthorityType::FreezeAccount,
+                    &owner_key,
+                    &[],
+                )
+                .unwrap(),
+                vec![&mut mint_account, &mut owner_account],
+            )
+        );
+
+        // set owner
+        do_process_instruction(
+            set_authority(
+                &program_id,
+                &mint_key,
+                Some(&owner2_key),
+                AuthorityType::MintTokens,
+                &owner_key,
+                &[],
+            )
+            .unwrap(),
+            vec![&mut mint_account, &mut owner_account],
+        )
+        .unwrap();
+
+        // set owner to None
+        do_process_instruction(
+            set_authority(
+                &program_id,
+                &mint_key,
+                None,
+                AuthorityType::MintTokens,
+                &owner2_key,
+                &[],
+            )
+            .unwrap(),
+            vec![&mut mint_account, &mut owner2_account],
+        )
+        .unwrap();
+
+        // test unsetting mint_authority is one-way operation
+        assert_eq!(
+            Err(TokenError::FixedSupply.into()),
+            do_process_instruction(
+                set_authority(
+                    &program_id,
+                    &mint2_key,
+                    Some(&owner2_key),
+                    AuthorityType::MintTokens,
+                    &owner_key,
+                    &[]
+                )
+                .unwrap(),
+                vec![&mut mint_account, &mut owner_account],
+            )
+        );
+
+        // set freeze_authority
+        do_process_instruction(
+            set_authority(
+                &program_id,
+                &mint2_key,
+                Some(&owner2_key),
+                AuthorityType::FreezeAccount,
+                &owner_key,
+                &[],
+            )
+            .unwrap(),
+            vec![&mut mint2_account, &mut owner_account],
+        )
+        .unwrap();
+
+        // test unsetting freeze_authority is one-way operation
+        do_process_instruction(
+            set_authority(
+                &program_id,
+                &mint2_key,
+                None,
+                AuthorityType::FreezeAccount,
+                &owner2_key,
+                &[],
+            )
+            .unwrap(),
+            vec![&mut mint2_account, &mut owner2_account],
+        )
+        .unwrap();
+
+        assert_eq!(
+            Err(TokenError::MintCannotFreeze.into()),
+            do_process_instruction(
+                set_authority(
+                    &program_id,
+                    &mint2_key,
+                    Some(&owner2_key),
+                    AuthorityType::FreezeAccount,
+                    &owner_key,
+                    &[],
+                )
+                .unwrap(),
+                vec![&mut mint2_account, &mut owner2_account],
+            )
+        );
+    }
+
+    #[test]
+    fn test_mint_to_dups() {
+        let program_id = crate::id();
+        let account1_key = Pubkey::new_unique();
+        let mut account1_account = SolanaAccount::new(
+            account_minimum_balance(),
+            Account::get_packed_len(),
+            &program_id,
+        );
+        let account1_info: AccountInfo = (&account1_key, true, &mut account1_account).into();
+        let owner_key = Pubkey::new_unique();
+        let mut owner_account = SolanaAccount::default();
+        let owner_info: AccountInfo = (&owner_key, true, &mut owner_account).into();
+        let mint_key = Pubkey::new_unique();
+        let mut mint_account =
+            SolanaAccount::new(mint_minimum_balance(), Mint::get_packed_len(), &program_id);
+        let mint_info: AccountInfo = (&mint_key, true, &mut mint_account).into();
+        let rent_key = rent::id();
+        let mut rent_sysvar = rent_sysvar();
+        let rent_info: AccountInfo = (&rent_key, false, &mut rent_sysvar).into();
+
+        // create mint
+        do_process_instruction_dups(
+            initialize_mint(&program_id, &mint_key, &mint_key, None, 2).unwrap(),
+            vec![mint_info.clone(), rent_info.clone()],
+        )
+        .unwrap();
+
+        // create account
+        do_process_instruction_dups(
+            initialize_account(&program_id, &account1_key, &mint_key, &owner_key).unwrap(),
+            vec![
+                account1_info.clone(),
+                mint_info.clone(),
+                owner_info.clone(),
+                rent_info.clone(),
+            ],
+        )
+        .unwrap();
+
+        // mint_to when mint_authority is self
+        do_process_instruction_dups(
+            mint_to(&program_id, &mint_key, &account1_key, &mint_key, &[], 42).unwrap(),
+            vec![mint_info.clone(), account1_info.clone(), mint_info.clone()],
+        )
+        .unwrap();
+
+        // mint_to_checked when mint_authority is self
+        do_process_instruction_dups(
+            mint_to_checked(&program_id, &mint_key, &account1_key, &mint_key, &[], 42, 2).unwrap(),
+            vec![mint_info.clone(), account1_info.clone(), mint_info.clone()],
+        )
+        .unwrap();
+
+        // mint_to when mint_authority is account owner
+        let mut mint = Mint::unpack_unchecked(&mint_info.data.borrow()).unwrap();
+        mint.mint_authority = COption::Some(account1_key);
+        Mint::pack(mint, &mut mint_info.data.borrow_mut()).unwrap();
+        do_process_instruction_dups(
+            mint_to(
+                &program_id,
+                &mint_key,
+                &account1_key,
+                &account1_key,
+                &[],
+                42,
+            )
+            .unwrap(),
+            vec![
+                mint_info.clone(),
+                account1_info.clone(),
+                account1_info.clone(),
+            ],
+        )
+        .unwrap();
+
+        // mint_to_checked when mint_authority is account owner
+        do_process_instruction_dups(
+            mint_to(
+                &program_id,
+                &mint_key,
+                &account1_key,
+                &account1_key,
+                &[],
+                42,
+            )
+            .unwrap(),
+            vec![
+                mint_info.clone(),
+                account1_info.clone(),
+                account1_info.clone(),
+            ],
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn test_mint_to() {
+        let program_id = crate::id();
+        let account_key = Pubkey::new_unique();
+        let mut account_account = SolanaAccount::new(
+            account_minimum_balance(),
+            Account::get_packed_len(),
+            &program_id,
+        );
+        let account2_key = Pubkey::new_unique();
+        let mut account2_account = SolanaAccount::new(
+            account_minimum_balance(),
+            Account::get_packed_len(),
+            &program_id,
+        );
+        let account3_key = Pubkey::new_unique();
+        let mut account3_account = SolanaAccount::new(
+            account_minimum_balance(),
+            Account::get_packed_len(),
+            &program_id,
+        );
+        let mismatch_key = Pubkey::new_unique();
+        let mut mismatch_account = SolanaAccount::new(
+            account_minimum_balance(),
+            Account::get_packed_len(),
+            &program_id,
+        );
+        let owner_key = Pubkey::new_unique();
+        let mut owner_account = SolanaAccount::default();
+        let owner2_key = Pubkey::new_unique();
+        let mut owner2_account = SolanaAccount::default();
+        let mint_key = Pubkey::new_unique();
+        let mut mint_account =
+            SolanaAccount::new(mint_minimum_balance(), Mint::get_packed_len(), &program_id);
+        let mint2_key = Pubkey::new_unique();
+        let uninitialized_key = Pubkey::new_unique();
+        let mut uninitialized_account = SolanaAccount::new(
+            account_minimum_balance(),
+            Account::get_packed_len(),
+            &program_id,
+        );
+        let mut rent_sysvar = rent_sysvar();
+
+        // create new mint with owner
+        do_process_instruction(
+            initialize_mint(&program_id, &mint_key, &owner_key, None, 2).unwrap(),
+            vec![&mut mint_account, &mut rent_sysvar],
+        )
+        .unwrap();
+
+        // create account
+        do_process_instruction(
+            initialize_account(&program_id, &account_key, &mint_key, &owner_key).unwrap(),
+            vec![
+                &mut account_account,
+                &mut mint_account,
+                &mut owner_account,
+                &mut rent_sysvar,
+            ],
+        )
+        .unwrap();
+
+        // create another account
+        do_process_instruction(
+            initialize_account(&program_id, &account2_key, &mint_key, &owner_key).unwrap(),
+            vec![
+                &mut account2_account,
+                &mut mint_account,
+                &mut owner_account,
+                &mut rent_sysvar,
+            ],
+        )
+        .unwrap();
+
+        // create another account
+        do_process_instruction(
+            initialize_account(&program_id, &account3_key, &mint_key, &owner_key).unwrap(),
+            vec![
+                &mut account3_account,
+                &mut mint_account,
+                &mut owner_account,
+                &mut rent_sysvar,
+            ],
+        )
+        .unwrap();
+
+        // create mismatch account
+        do_process_instruction(
+            initialize_account(&program_id, &mismatch_key, &mint_key, &owner_key).unwrap(),
+            vec![
+                &mut mismatch_account,
+                &mut mint_account,
+                &mut owner_account,
+                &mut rent_sysvar,
+            ],
+        )
+        .unwrap();
+        let mut account = Account::unpack_unchecked(&mismatch_account.data).unwrap();
+        account.mint = mint2_key;
+        Account::pack(account, &mut mismatch_account.data).unwrap();
+
+        // mint to
+        do_process_instruction(
+            mint_to(&program_id, &mint_key, &account_key, &owner_key, &[], 42).unwrap(),
+            vec![&mut mint_account, &mut account_account, &mut owner_account],
+        )
+        .unwrap();
+
+        let mint = Mint::unpack_unchecked(&mint_account.data).unwrap();
+        assert_eq!(mint.supply, 42);
+        let account = Account::unpack_unchecked(&account_account.data).unwrap();
+        assert_eq!(account.amount, 42);
+
+        // mint to another account to test supply accumulation
+        do_process_instruction(
+            mint_to(&program_id, &mint_key, &account2_key, &owner_key, &[], 42).unwrap(),
+            vec![&mut mint_account, &mut account2_account, &mut owner_account],
+        )
+        .unwrap();
+
+        let mint = Mint::unpack_unchecked(&mint_account.data).unwrap();
+        assert_eq!(mint.supply, 84);
+        let account = Account::unpack_unchecked(&account2_account.data).unwrap();
+        assert_eq!(account.amount, 42);
+
+        // missing signer
+        let mut instruction =
+            mint_to(&program_id, &mint_key, &account2_key, &owner_key, &[], 42).unwrap();
+        instruction.accounts[2].is_signer = false;
+        assert_eq!(
+            Err(ProgramError::MissingRequiredSignature),
+            do_process_instruction(
+                instruction,
+                vec![&mut mint_account, &mut account2_account, &mut owner_account],
+            )
+        );
+
+        // mismatch account
+        assert_eq!(
+            Err(TokenError::MintMismatch.into()),
+            do_process_instruction(
+                mint_to(&program_id, &mint_key, &mismatch_key, &owner_key, &[], 42).unwrap(),
+                vec![&mut mint_account, &mut mismatch_account, &mut owner_account],
+            )
+        );
+
+        // missing owner
+        assert_eq!(
+            Err(TokenError::OwnerMismatch.into()),
+            do_process_instruction(
+                mint_to(&program_id, &mint_key, &account2_key, &owner2_key, &[], 42).unwrap(),
+                vec![
+                    &mut mint_account,
+                    &mut account2_account,
+                    &mut owner2_account,
+                ],
+            )
+        );
+
+        // uninitialized destination account
+        assert_eq!(
+            Err(ProgramError::UninitializedAccount),
+            do_process_instruction(
+                mint_to(
+                    &program_id,
+                    &mint_key,
+                    &uninitialized_key,
+                    &owner_key,
+                    &[],
+                    42
+                )
+                .unwrap(),
+                vec![
+                    &mut mint_account,
+                    &mut uninitialized_account,
+                    &mut owner_account,
+                ],
+            )
+        );
+
+        // unset mint_authority and test minting fails
+        do_process_instruction(
+            set_authority(
+                &program_id,
+                &mint_key,
+                None,
+                AuthorityType::MintTokens,
+                &owner_key,
+                &[],
+            )
+            .unwrap(),
+            vec![&mut mint_account, &mut owner_account],
+        )
+        .unwrap();
+        assert_eq!(
+            Err(TokenError::FixedSupply.into()),
+            do_process_instruction(
+                mint_to(&program_id, &mint_key, &account2_key, &owner_key, &[], 42).unwrap(),
+                vec![&mut mint_account, &mut account2_account, &mut owner_account],
+            )
+        );
+    }
+
+    #[test]
+    fn test_burn_dups() {
+        let program_id = crate::id();
+        let account1_key = Pubkey::new_unique();
+        let mut account1_account = SolanaAccount::new(
+            account_minimum_balance(),
+            Account::get_packed_len(),
+            &program_id,
+        );
+        let account1_info: AccountInfo = (&account1_key, true, &mut account1_account).into();
+        let owner_key = Pubkey::new_unique();
+        let mut owner_account = SolanaAccount::default();
+        let owner_info: AccountInfo = (&owner_key, true, &mut owner_account).into();
+        let mint_key = Pubkey::new_unique();
+        let mut mint_account =
+            SolanaAccount::new(mint_minimum_balance(), Mint::get_packed_len(), &program_id);
+        let mint_info: AccountInfo = (&mint_key, true, &mut mint_account).into();
+        let rent_key = rent::id();
+        let mut rent_sysvar = rent_sysvar();
+        let rent_info: AccountInfo = (&rent_key, false, &mut rent_sysvar).into();
+
+        // create mint
+        do_process_instruction_dups(
+            initialize_mint(&program_id, &mint_key, &owner_key, None, 2).unwrap(),
+            vec![mint_info.clone(), rent_info.clone()],
+        )
+        .unwrap();
+
+        // create account
+        do_process_instruction_dups(
+            initialize_account(&program_id, &account1_key, &mint_key, &account1_key).unwrap(),
+            vec![
+                account1_info.clone(),
+                mint_info.clone(),
+                account1_info.clone(),
+                rent_info.clone(),
+            ],
+        )
+        .unwrap();
+
+        // mint to account
+        do_process_instruction_dups(
+            mint_to(&program_id, &mint_key, &account1_key, &owner_key, &[], 1000).unwrap(),
+            vec![mint_info.clone(), account1_info.clone(), owner_info.clone()],
+        )
+        .unwrap();
+
+        // source-owner burn
+        do_process_instruction_dups(
+            burn(
+                &program_id,
+                &mint_key,
+                &account1_key,
+                &account1_key,
+                &[],
+                500,
+            )
+            .unwrap(),
+            vec![
+                account1_info.clone(),
+                mint_info.clone(),
+                account1_info.clone(),
+            ],
+        )
+        .unwrap();
+
+        // source-owner burn_checked
+        do_process_instruction_dups(
+            burn_checked(
+                &program_id,
+                &account1_key,
+                &mint_key,
+                &account1_key,
+                &[],
+                500,
+                2,
+            )
+            .unwrap(),
+            vec![
+                account1_info.clone(),
+                mint_info.clone(),
+                account1_info.clone(),
+            ],
+        )
+        .unwrap();
+
+        // mint-owner burn
+        do_process_instruction_dups(
+            mint_to(&program_id, &mint_key, &account1_key, &owner_key, &[], 1000).unwrap(),
+            vec![mint_info.clone(), account1_info.clone(), owner_info.clone()],
+        )
+        .unwrap();
+        let mut account = Account::unpack_unchecked(&account1_info.data.borrow()).unwrap();
+        account.owner = mint_key;
+        Account::pack(account, &mut account1_info.data.borrow_mut()).unwrap();
+        do_process_instruction_dups(
+            burn(&program_id, &account1_key, &mint_key, &mint_key, &[], 500).unwrap(),
+            vec![account1_info.clone(), mint_info.clone(), mint_info.clone()],
+        )
+        .unwrap();
+
+        // mint-owner burn_checked
+        do_process_instruction_dups(
+            burn_checked(
+                &program_id,
+                &account1_key,
+                &mint_key,
+                &mint_key,
+                &[],
+                500,
+                2,
+            )
+            .unwrap(),
+            vec![account1_info.clone(), mint_info.clone(), mint_info.clone()],
+        )
+        .unwrap();
+
+        // source-delegate burn
+        do_process_instruction_dups(
+            mint_to(&program_id, &mint_key, &account1_key, &owner_key, &[], 1000).unwrap(),
+            vec![mint_info.clone(), account1_info.clone(), owner_info.clone()],
+        )
+        .unwrap();
+        let mut account = Account::unpack_unchecked(&account1_info.data.borrow()).unwrap();
+        account.delegated_amount = 1000;
+        account.delegate = COption::Some(account1_key);
+        account.owner = owner_key;
+        Account::pack(account, &mut account1_info.data.borrow_mut()).unwrap();
+        do_process_instruction_dups(
+            burn(
+                &program_id,
+                &account1_key,
+                &mint_key,
+                &account1_key,
+                &[],
+                500,
+            )
+            .unwrap(),
+            vec![
+                account1_info.clone(),
+                mint_info.clone(),
+                account1_info.clone(),
+            ],
+        )
+        .unwrap();
+
+        // source-delegate burn_checked
+        do_process_instruction_dups(
+            burn_checked(
+                &program_id,
+                &account1_key,
+                &mint_key,
+                &account1_key,
+                &[],
+                500,
+                2,
+            )
+            .unwrap(),
+            vec![
+                account1_info.clone(),
+                mint_info.clone(),
+                account1_info.clone(),
+            ],
+        )
+        .unwrap();
+
+        // mint-delegate burn
+        do_process_instruction_dups(
+            mint_to(&program_id, &mint_key, &account1_key, &owner_key, &[], 1000).unwrap(),
+            vec![mint_info.clone(), account1_info.clone(), owner_info.clone()],
+        )
+        .unwrap();
+        let mut account = Account::unpack_unchecked(&account1_info.data.borrow()).unwrap();
+        account.delegated_amount = 1000;
+        account.delegate = COption::Some(mint_key);
+        account.owner = owner_key;
+        Account::pack(account, &mut account1_info.data.borrow_mut()).unwrap();
+        do_process_instruction_dups(
+            burn(&program_id, &account1_key, &mint_key, &mint_key, &[], 500).unwrap(),
+            vec![account1_info.clone(), mint_info.clone(), mint_info.clone()],
+        )
+        .unwrap();
+
+        // mint-delegate burn_checked
+        do_process_instruction_dups(
+            burn_checked(
+                &program_id,
+                &account1_key,
+                &mint_key,
+                &mint_key,
+                &[],
+                500,
+                2,
+            )
+            .unwrap(),
+            vec![account1_info.clone(), mint_info.clone(), mint_info.clone()],
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn test_burn() {
+        let program_id = crate::id();
+        let account_key = Pubkey::new_unique();
+        let mut account_account = SolanaAccount::new(
+            account_minimum_balance(),
+            Account::get_packed_len(),
+            &program_id,
+        );
+        let account2_key = Pubkey::new_unique();
+        let mut account2_account = SolanaAccount::new(
+            account_minimum_balance(),
+            Account::get_packed_len(),
+            &program_id,
+        );
+        let account3_key = Pubkey::new_unique();
+        let mut account3_account = SolanaAccount::new(
+            account_minimum_balance(),
+            Account::get_packed_len(),
+            &program_id,
+        );
+        let delegate_key = Pubkey::new_unique();
+        let mut delegate_account = SolanaAccount::default();
+        let mismatch_key = Pubkey::new_unique();
+        let mut mismatch_account = SolanaAccount::new(
+            account_minimum_balance(),
+            Account::get_packed_len(),
+            &program_id,
+        );
+        let owner_key = Pubkey::new_unique();
+        let mut owner_account = SolanaAccount::default();
+        let owner2_key = Pubkey::new_unique();
+        let mut owner2_account = SolanaAccount::default();
+        let mint_key = Pubkey::new_unique();
+        let mut mint_account =
+            SolanaAccount::new(mint_minimum_balance(), Mint::get_packed_len(), &program_id);
+        let mint2_key = Pubkey::new_unique();
+        let mut rent_sysvar = rent_sysvar();
+
+        // create new mint
+        do_process_instruction(
+            initialize_mint(&program_id, &mint_key, &owner_key, None, 2).unwrap(),
+            vec![&mut mint_account, &mut rent_sysvar],
+        )
+        .unwrap();
+
+        // create account
+        do_process_instruction(
+            initialize_account(&program_id, &account_key, &mint_key, &owner_key).unwrap(),
+            vec![
+                &mut account_account,
+                &mut mint_account,
+                &mut owner_account,
+                &mut rent_sysvar,
+            ],
+        )
+        .unwrap();
+
+        // create another account
+        do_process_instruction(
+            initialize_account(&program_id, &account2_key, &mint_key, &owner_key).unwrap(),
+            vec![
+                &mut account2_account,
+                &mut mint_account,
+                &mut owner_account,
+                &mut rent_sysvar,
+            ],
+        )
+        .unwrap();
+
+        // create another account
+        do_process_instruction(
+            initialize_account(&program_id, &account3_key, &mint_key, &owner_key).unwrap(),
+            vec![
+                &mut account3_account,
+                &mut mint_account,
+                &mut owner_account,
+                &mut rent_sysvar,
+            ],
+        )
+        .unwrap();
+
+        // create mismatch account
+        do_process_instruction(
+            initialize_account(&program_id, &mismatch_key, &mint_key, &owner_key).unwrap(),
+            vec![
+                &mut mismatch_account,
+                &mut mint_account,
+                &mut owner_account,
+                &mut rent_sysvar,
+            ],
+        )
+        .unwrap();
+
+        // mint to account
+        do_process_instruction(
+            mint_to(&program_id, &mint_key, &account_key, &owner_key, &[], 1000).unwrap(),
+            vec![&mut mint_account, &mut account_account, &mut owner_account],
+        )
+        .unwrap();
+
+        // mint to mismatch account and change mint key
+        do_process_instruction(
+            mint_to(&program_id, &mint_key, &mismatch_key, &owner_key, &[], 1000).unwrap(),
+            vec![&mut mint_account, &mut mismatch_account, &mut owner_account],
+        )
+        .unwrap();
+        let mut account = Account::unpack_unchecked(&mismatch_account.data).unwrap();
+        account.mint = mint2_key;
+        Account::pack(account, &mut mismatch_account.data).unwrap();
+
+        // missing signer
+        let mut instruction =
+            burn(&program_id, &account_key, &mint_key, &delegate_key, &[], 42).unwrap();
+        instruction.accounts[1].is_signer = false;
+        assert_eq!(
+            Err(TokenError::OwnerMismatch.into()),
+            do_process_instruction(
+                instruction,
+                vec![
+                    &mut account_account,
+                    &mut mint_account,
+                    &mut delegate_account
+                ],
+            )
+        );
+
+        // missing owner
+        assert_eq!(
+            Err(TokenError::OwnerMismatch.into()),
+            do_process_instruction(
+                burn(&program_id, &account_key, &mint_key, &owner2_key, &[], 42).unwrap(),
+                vec![&mut account_account, &mut mint_account, &mut owner2_account],
+            )
+        );
+
+        // mint mismatch
+        assert_eq!(
+            Err(TokenError::MintMismatch.into()),
+            do_process_instruction(
+                burn(&program_id, &mismatch_key, &mint_key, &owner_key, &[], 42).unwrap(),
+                vec![&mut mismatch_account, &mut mint_account, &mut owner_account],
+            )
+        );
+
+        // burn
+        do_process_instruction(
+            burn(&program_id, &account_key, &mint_key, &owner_key, &[], 21).unwrap(),
+            vec![&mut account_account, &mut mint_account, &mut owner_account],
+        )
+        .unwrap();
+
+        // burn_checked, with incorrect decimals
+        assert_eq!(
+            Err(TokenError::MintDecimalsMismatch.into()),
+            do_process_instruction(
+                burn_checked(&program_id, &account_key, &mint_key, &owner_key, &[], 21, 3).unwrap(),
+                vec![&mut account_account, &mut mint_account, &mut owner_account],
+            )
+        );
+
+        // burn_checked
+        do_process_instruction(
+            burn_checked(&program_id, &account_key, &mint_key, &owner_key, &[], 21, 2).unwrap(),
+            vec![&mut account_account, &mut mint_account, &mut owner_account],
+        )
+        .unwrap();
+
+        let mint = Mint::unpack_unchecked(&mint_account.data).unwrap();
+        assert_eq!(mint.supply, 2000 - 42);
+        let account = Account::unpack_unchecked(&account_account.data).unwrap();
+        assert_eq!(account.amount, 1000 - 42);
+
+        // insufficient funds
+        assert_eq!(
+            Err(TokenError::InsufficientFunds.into()),
+            do_process_instruction(
+                burn(
+                    &program_id,
+                    &account_key,
+                    &mint_key,
+                    &owner_key,
+                    &[],
+                    100_000_000
+                )
+                .unwrap(),
+                vec![&mut account_account, &mut mint_account, &mut owner_account],
+            )
+        );
+
+        // approve delegate
+        do_process_instruction(
+            approve(
+                &program_id,
+                &account_key,
+                &delegate_key,
+                &owner_key,
+                &[],
+                84,
+            )
+            .unwrap(),
+            vec![
+                &mut account_account,
+                &mut delegate_account,
+                &mut owner_account,
+            ],
+        )
+        .unwrap();
+
+        // not a delegate of source account
+        assert_eq!(
+            Err(TokenError::InsufficientFunds.into()),
+            do_process_instruction(
+                burn(
+                    &program_id,
+                    &account_key,
+                    &mint_key,
+                    &owner_key,
+                    &[],
+                    100_000_000
+                )
+                .unwrap(),
+                vec![&mut account_account, &mut mint_account, &mut owner_account],
+            )
+        );
+
+        // burn via delegate
+        do_process_instruction(
+            burn(&program_id, &account_key, &mint_key, &delegate_key, &[], 84).unwrap(),
+            vec![
+                &mut account_account,
+                &mut mint_account,
+                &mut delegate_account,
+            ],
+        )
+        .unwrap();
+
+        // match
+        let mint = Mint::unpack_unchecked(&mint_account.data).unwrap();
+        assert_eq!(mint.supply, 2000 - 42 - 84);
+        let account = Account::unpack_unchecked(&account_account.data).unwrap();
+        assert_eq!(account.amount, 1000 - 42 - 84);
+
+        // insufficient funds approved via delegate
+        assert_eq!(
+            Err(TokenError::OwnerMismatch.into()),
+            do_process_instruction(
+                burn(
+                    &program_id,
+                    &account_key,
+                    &mint_key,
+                    &delegate_key,
+                    &[],
+                    100
+                )
+                .unwrap(),
+                vec![
+                    &mut account_account,
+                    &mut mint_account,
+                    &mut delegate_account
+                ],
+            )
+        );
+    }
+
+    #[test]
+    fn test_multisig() {
+        let program_id = crate::id();
+        let mint_key = Pubkey::new_unique();
+        let mut mint_account =
+            SolanaAccount::new(mint_minimum_balance(), Mint::get_packed_len(), &program_id);
+        let account_key = Pubkey::new_unique();
+        let mut account = SolanaAccount::new(
+            account_minimum_balance(),
+            Account::get_packed_len(),
+            &program_id,
+        );
+        let account2_key = Pubkey::new_unique();
+        let mut account2_account = SolanaAccount::new(
+            account_minimum_balance(),
+            Account::get_packed_len(),
+            &program_id,
+        );
+        let owner_key = Pubkey::new_unique();
+        let mut owner_account = SolanaAccount::default();
+        let multisig_key = Pubkey::new_unique();
+        let mut multisig_account = SolanaAccount::new(42, Multisig::get_packed_len(), &program_id);
+        let multisig_delegate_key = Pubkey::new_unique();
+        let mut multisig_delegate_account = SolanaAccount::new(
+            multisig_minimum_balance(),
+            Multisig::get_packed_len(),
+            &program_id,
+        );
+        let signer_keys: Vec<Pubkey> = (0..MAX_SIGNERS).map(|_| Pubkey::new_unique()).collect();
+        let signer_key_refs: Vec<&Pubkey> = signer_keys.iter().collect();
+        let mut signer_accounts = vec![SolanaAccount::new(0, 0, &program_id); MAX_SIGNERS];
+        let mut rent_sysvar = rent_sysvar();
+
+        // multisig is not rent exempt
+        let account_info_iter = &mut signer_accounts.iter_mut();
+        assert_eq!(
+            Err(TokenError::NotRentExempt.into()),
+            do_process_instruction(
+                initialize_multisig(&program_id, &multisig_key, &[&signer_keys[0]], 1).unwrap(),
+                vec![
+                    &mut multisig_account,
+                    &mut rent_sysvar,
+                    &mut account_info_iter.next().unwrap(),
+                ],
+            )
+        );
+
+        multisig_account.lamports = multisig_minimum_balance();
+
+        // single signer
+        let account_info_iter = &mut signer_accounts.iter_mut();
+        do_process_instruction(
+            initialize_multisig(&program_id, &multisig_key, &[&signer_keys[0]], 1).unwrap(),
+            vec![
+                &mut multisig_account,
+                &mut rent_sysvar,
+                &mut account_info_iter.next().unwrap(),
+            ],
+        )
+        .unwrap();
+
+        // multiple signer
+        let account_info_iter = &mut signer_accounts.iter_mut();
+        do_process_instruction(
+            initialize_multisig(
+                &program_id,
+                &multisig_delegate_key,
+                &signer_key_refs,
+                MAX_SIGNERS as u8,
+            )
+            .unwrap(),
+            vec![
+                &mut multisig_delegate_account,
+                &mut rent_sysvar,
+                &mut account_info_iter.next().unwrap(),
+                &mut account_info_iter.next().unwrap(),
+                &mut account_info_iter.next().unwrap(),
+                &mut account_info_iter.next().unwrap(),
+                &mut account_info_iter.next().unwrap(),
+                &mut account_info_iter.next().unwrap(),
+                &mut account_info_iter.next().unwrap(),
+                &mut account_info_iter.next().unwrap(),
+                &mut account_info_iter.next().unwrap(),
+                &mut account_info_iter.next().unwrap(),
+                &mut account_info_iter.next().unwrap(),
+            ],
+        )
+        .unwrap();
+
+        // create new mint with multisig owner
+        do_process_instruction(
+            initialize_mint(&program_id, &mint_key, &multisig_key, None, 2).unwrap(),
+            vec![&mut mint_account, &mut rent_sysvar],
+        )
+        .unwrap();
+
+        // create account with multisig owner
+        do_process_instruction(
+            initialize_account(&program_id, &account_key, &mint_key, &multisig_key).unwrap(),
+            vec![
+                &mut account,
+                &mut mint_account,
+                &mut multisig_account,
+                &mut rent_sysvar,
+            ],
+        )
+        .unwrap();
+
+        // create another account with multisig owner
+        do_process_instruction(
+            initialize_account(
+                &program_id,
+                &account2_key,
+                &mint_key,
+                &multisig_delegate_key,
+            )
+            .unwrap(),
+            vec![
+                &mut account2_account,
+                &mut mint_account,
+                &mut multisig_account,
+                &mut rent_sysvar,
+            ],
+        )
+        .unwrap();
+
+        // mint to account
+        let account_info_iter = &mut signer_accounts.iter_mut();
+        do_process_instruction(
+            mint_to(
+                &program_id,
+                &mint_key,
+                &account_key,
+                &multisig_key,
+                &[&signer_keys[0]],
+                1000,
+            )
+            .unwrap(),
+            vec![
+                &mut mint_account,
+                &mut account,
+                &mut multisig_account,
+                &mut account_info_iter.next().unwrap(),
+            ],
+        )
+        .unwrap();
+
+        // approve
+        let account_info_iter = &mut signer_accounts.iter_mut();
+        do_process_instruction(
+            approve(
+                &program_id,
+                &account_key,
+                &multisig_delegate_key,
+                &multisig_key,
+                &[&signer_keys[0]],
+                100,
+            )
+            .unwrap(),
+            vec![
+                &mut account,
+                &mut multisig_delegate_account,
+                &mut multisig_account,
+                &mut account_info_iter.next().unwrap(),
+            ],
+        )
+        .unwrap();
+
+        // transfer
+        let account_info_iter = &mut signer_accounts.iter_mut();
+        do_process_instruction(
+            transfer(
+                &program_id,
+                &account_key,
+                &account2_key,
+                &multisig_key,
+                &[&signer_keys[0]],
+                42,
+            )
+            .unwrap(),
+            vec![
+                &mut account,
+                &mut account2_account,
+                &mut multisig_account,
+                &mut account_info_iter.next().unwrap(),
+            ],
+        )
+        .unwrap();
+
+        // transfer via delegate
+        let account_info_iter = &mut signer_accounts.iter_mut();
+        do_process_instruction(
+            transfer(
+                &program_id,
+                &account_key,
+                &account2_key,
+                &multisig_delegate_key,
+                &signer_key_refs,
+                42,
+            )
+            .unwrap(),
+            vec![
+                &mut account,
+                &mut account2_account,
+                &mut multisig_delegate_account,
+                &mut account_info_iter.next().unwrap(),
+                &mut account_info_iter.next().unwrap(),
+                &mut account_info_iter.next().unwrap(),
+                &mut account_info_iter.next().unwrap(),
+                &mut account_info_iter.next().unwrap(),
+                &mut account_info_iter.next().unwrap(),
+                &mut account_info_iter.next().unwrap(),
+                &mut account_info_iter.next().unwrap(),
+                &mut account_info_iter.next().unwrap(),
+                &mut account_info_iter.next().unwrap(),
+                &mut account_info_iter.next().unwrap(),
+            ],
+        )
+        .unwrap();
+
+        // mint to
+        let account_info_iter = &mut signer_accounts.iter_mut();
+        do_process_instruction(
+            mint_to(
+                &program_id,
+                &mint_key,
+                &account2_key,
+                &multisig_key,
+                &[&signer_keys[0]],
+                42,
+            )
+            .unwrap(),
+            vec![
+                &mut mint_account,
+                &mut account2_account,
+                &mut multisig_account,
+                &mut account_info_iter.next().unwrap(),
+            ],
+        )
+        .unwrap();
+
+        // burn
+        let account_info_iter = &mut signer_accounts.iter_mut();
+        do_process_instruction(
+            burn(
+                &program_id,
+                &account_key,
+                &mint_key,
+                &multisig_key,
+                &[&signer_keys[0]],
+                42,
+            )
+            .unwrap(),
+            vec![
+                &mut account,
+                &mut mint_account,
+                &mut multisig_account,
+                &mut account_info_iter.next().unwrap(),
+            ],
+        )
+        .unwrap();
+
+        // burn via delegate
+        let account_info_iter = &mut signer_accounts.iter_mut();
+        do_process_instruction(
+            burn(
+                &program_id,
+                &account_key,
+                &mint_key,
+                &multisig_delegate_key,
+                &signer_key_refs,
+                42,
+            )
+            .unwrap(),
+            vec![
+                &mut account,
+                &mut mint_account,
+                &mut multisig_delegate_account,
+                &mut account_info_iter.next().unwrap(),
+                &mut account_info_iter.next().unwrap(),
+                &mut account_info_iter.next().unwrap(),
+                &mut account_info_iter.next().unwrap(),
+                &mut account_info_iter.next().unwrap(),
+                &mut account_info_iter.next().unwrap(),
+                &mut account_info_iter.next().unwrap(),
+                &mut account_info_iter.next().unwrap(),
+                &mut account_info_iter.next().unwrap(),
+                &mut account_info_iter.next().unwrap(),
+                &mut account_info_iter.next().unwrap(),
+            ],
+        )
+        .unwrap();
+
+        // freeze account
+        let account3_key = Pubkey::new_unique();
+        let mut account3_account = SolanaAccount::new(
+            account_minimum_balance(),
+            Account::get_packed_len(),
+            &program_id,
+        );
+        let mint2_key = Pubkey::new_unique();
+        let mut mint2_account =
+            SolanaAccount::new(mint_minimum_balance(), Mint::get_packed_len(), &program_id);
+        do_process_instruction(
+            initialize_mint(
+                &program_id,
+                &mint2_key,
+                &multisig_key,
+                Some(&multisig_key),
+                2,
+            )
+            .unwrap(),
+            vec![&mut mint2_account, &mut rent_sysvar],
+        )
+        .unwrap();
+        do_process_instruction(
+            initialize_account(&program_id, &account3_key, &mint2_key, &owner_key).unwrap(),
+            vec![
+                &mut account3_account,
+                &mut mint2_account,
+                &mut owner_account,
+                &mut rent_sysvar,
+            ],
+        )
+        .unwrap();
+        let account_info_iter = &mut signer_accounts.iter_mut();
+        do_process_instruction(
+            mint_to(
+                &program_id,
+                &mint2_key,
+                &account3_key,
+                &multisig_key,
+                &[&signer_keys[0]],
+                1000,
+            )
+            .unwrap(),
+            vec![
+                &mut mint2_account,
+                &mut account3_account,
+                &mut multisig_account,
+                &mut account_info_iter.next().unwrap(),
+            ],
+        )
+        .unwrap();
+        let account_info_iter = &mut signer_accounts.iter_mut();
+        do_process_instruction(
+            freeze_account(
+                &program_id,
+                &account3_key,
+                &mint2_key,
+                &multisig_key,
+                &[&signer_keys[0]],
+            )
+            .unwrap(),
+            vec![
+                &mut account3_account,
+                &mut mint2_account,
+                &mut multisig_account,
+                &mut account_info_iter.next().unwrap(),
+            ],
+        )
+        .unwrap();
+
+        // do SetAuthority on mint
+        let account_info_iter = &mut signer_accounts.iter_mut();
+        do_process_instruction(
+            set_authority(
+                &program_id,
+                &mint_key,
+                Some(&owner_key),
+                AuthorityType::MintTokens,
+                &multisig_key,
+                &[&signer_keys[0]],
+            )
+            .unwrap(),
+            vec![
+                &mut mint_account,
+                &mut multisig_account,
+                &mut account_info_iter.next().unwrap(),
+            ],
+        )
+        .unwrap();
+
+        // do SetAuthority on account
+        let account_info_iter = &mut signer_accounts.iter_mut();
+        do_process_instruction(
+            set_authority(
+                &program_id,
+                &account_key,
+                Some(&owner_key),
+                AuthorityType::AccountOwner,
+                &multisig_key,
+                &[&signer_keys[0]],
+            )
+            .unwrap(),
+            vec![
+                &mut account,
+                &mut multisig_account,
+                &mut account_info_iter.next().unwrap(),
+            ],
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn test_validate_owner() {
+        let program_id = crate::id();
+        let owner_key = Pubkey::new_unique();
+        let mut signer_keys = [Pubkey::default(); MAX_SIGNERS];
+        for signer_key in signer_keys.iter_mut().take(MAX_SIGNERS) {
+            *signer_key = Pubkey::new_unique();
+        }
+        let mut signer_lamports = 0;
+        let mut signer_data = vec![];
+        let mut signers = vec![
+            AccountInfo::new(
+                &owner_key,
+                true,
+                false,
+                &mut signer_lamports,
+                &mut signer_data,
+                &program_id,
+                false,
+                Epoch::default(),
+            );
+            MAX_SIGNERS + 1
+        ];
+        for (signer, key) in signers.iter_mut().zip(&signer_keys) {
+            signer.key = key;
+        }
+        let mut lamports = 0;
+        let mut data = vec![0; Multisig::get_packed_len()];
+        let mut multisig = Multisig::unpack_unchecked(&data).unwrap();
+        multisig.m = MAX_SIGNERS as u8;
+        multisig.n = MAX_SIGNERS as u8;
+        multisig.signers = signer_keys;
+        multisig.is_initialized = true;
+        Multisig::pack(multisig, &mut data).unwrap();
+        let owner_account_info = AccountInfo::new(
+            &owner_key,
+            false,
+            false,
+            &mut lamports,
+            &mut data,
+            &program_id,
+            false,
+            Epoch::default(),
+        );
+
+        // full 11 of 11
+        Processor::validate_owner(&program_id, &owner_key, &owner_account_info, &signers).unwrap();
+
+        // 1 of 11
+        {
+            let mut multisig =
+                Multisig::unpack_unchecked(&owner_account_info.data.borrow()).unwrap();
+            multisig.m = 1;
+            Multisig::pack(multisig, &mut owner_account_info.data.borrow_mut()).unwrap();
+        }
+        Processor::validate_owner(&program_id, &owner_key, &owner_account_info, &signers).unwrap();
+
+        // 2:1
+        {
+            let mut multisig =
+                Multisig::unpack_unchecked(&owner_account_info.data.borrow()).unwrap();
+            multisig.m = 2;
+            multisig.n = 1;
+            Multisig::pack(multisig, &mut owner_account_info.data.borrow_mut()).unwrap();
+        }
+        assert_eq!(
+            Err(ProgramError::MissingRequiredSignature),
+            Processor::validate_owner(&program_id, &owner_key, &owner_account_info, &signers)
+        );
+
+        // 0:11
+        {
+            let mut multisig =
+                Multisig::unpack_unchecked(&owner_account_info.data.borrow()).unwrap();
+            multisig.m = 0;
+            multisig.n = 11;
+            Multisig::pack(multisig, &mut owner_account_info.data.borrow_mut()).unwrap();
+        }
+        Processor::validate_owner(&program_id, &owner_key, &owner_account_info, &signers).unwrap();
+
+        // 2:11 but 0 provided
+        {
+            let mut multisig =
+                Multisig::unpack_unchecked(&owner_account_info.data.borrow()).unwrap();
+            multisig.m = 2;
+            multisig.n = 11;
+            Multisig::pack(multisig, &mut owner_account_info.data.borrow_mut()).unwrap();
+        }
+        assert_eq!(
+            Err(ProgramError::MissingRequiredSignature),
+            Processor::validate_owner(&program_id, &owner_key, &owner_account_info, &[])
+        );
+        // 2:11 but 1 provided
+        {
+            let mut multisig =
+                Multisig::unpack_unchecked(&owner_account_info.data.borrow()).unwrap();
+            multisig.m = 2;
+            multisig.n = 11;
+            Multisig::pack(multisig, &mut owner_account_info.data.borrow_mut()).unwrap();
+        }
+        assert_eq!(
+            Err(ProgramError::MissingRequiredSignature),
+            Processor::validate_owner(&program_id, &owner_key, &owner_account_info, &signers[0..1])
+        );
+
+        // 2:11, 2 from middle provided
+        {
+            let mut multisig =
+                Multisig::unpack_unchecked(&owner_account_info.data.borrow()).unwrap();
+            multisig.m = 2;
+            multisig.n = 11;
+            Multisig::pack(multisig, &mut owner_account_info.data.borrow_mut()).unwrap();
+        }
+        Processor::validate_owner(&program_id, &owner_key, &owner_account_info, &signers[5..7])
+            .unwrap();
+
+        // 11:11, one is not a signer
+        {
+            let mut multisig =
+                Multisig::unpack_unchecked(&owner_account_info.data.borrow()).unwrap();
+            multisig.m = 11;
+            multisig.n = 11;
+            Multisig::pack(multisig, &mut owner_account_info.data.borrow_mut()).unwrap();
+        }
+        signers[5].is_signer = false;
+        assert_eq!(
+            Err(ProgramError::MissingRequiredSignature),
+            Processor::validate_owner(&program_id, &owner_key, &owner_account_info, &signers)
+        );
+        signers[5].is_signer = true;
+
+        // 11:11, single signer signs multiple times
+        {
+            let mut signer_lamports = 0;
+            let mut signer_data = vec![];
+            let signers = vec![
+                AccountInfo::new(
+                    &signer_keys[5],
+                    true,
+                    false,
+                    &mut signer_lamports,
+                    &mut signer_data,
+                    &program_id,
+                    false,
+                    Epoch::default(),
+                );
+                MAX_SIGNERS + 1
+            ];
+            let mut multisig =
+                Multisig::unpack_unchecked(&owner_account_info.data.borrow()).unwrap();
+            multisig.m = 11;
+            multisig.n = 11;
+            Multisig::pack(multisig, &mut owner_account_info.data.borrow_mut()).unwrap();
+            assert_eq!(
+                Err(ProgramError::MissingRequiredSignature),
+                Processor::validate_owner(&program_id, &owner_key, &owner_account_info, &signers)
+            );
+        }
+    }
+
+    #[test]
+    fn test_close_account_dups() {
+        let program_id = crate::id();
+        let account1_key = Pubkey::new_unique();
+        let mut account1_account = SolanaAccount::new(
+            account_minimum_balance(),
+            Account::get_packed_len(),
+            &program_id,
+        );
+        let account1_info: AccountInfo = (&account1_key, true, &mut account1_account).into();
+        let account2_key = Pubkey::new_unique();
+        let mut account2_account = SolanaAccount::new(
+            account_minimum_balance(),
+            Account::get_packed_len(),
+            &program_id,
+        );
+        let account2_info: AccountInfo = (&account2_key, true, &mut account2_account).into();
+        let owner_key = Pubkey::new_unique();
+        let mint_key = Pubkey::new_unique();
+        let mut mint_account =
+            SolanaAccount::new(mint_minimum_balance(), Mint::get_packed_len(), &program_id);
+        let mint_info: AccountInfo = (&mint_key, false, &mut mint_account).into();
+        let rent_key = rent::id();
+        let mut rent_sysvar = rent_sysvar();
+        let rent_info: AccountInfo = (&rent_key, false, &mut rent_sysvar).into();
+
+        // create mint
+        do_process_instruction_dups(
+            initialize_mint(&program_id, &mint_key, &owner_key, None, 2).unwrap(),
+            vec![mint_info.clone(), rent_info.clone()],
+        )
+        .unwrap();
+
+        // create account
+        do_process_instruction_dups(
+            initialize_account(&program_id, &account1_key, &mint_key, &account1_key).unwrap(),
+            vec![
+                account1_info.clone(),
+                mint_info.clone(),
+                account1_info.clone(),
+                rent_info.clone(),
+            ],
+        )
+        .unwrap();
+
+        // source-owner close
+        do_process_instruction_dups(
+            close_account(
+                &program_id,
+                &account1_key,
+                &account2_key,
+                &account1_key,
+                &[],
+            )
+            .unwrap(),
+            vec![
+                account1_info.clone(),
+                account2_info.clone(),
+                account1_info.clone(),
+            ],
+        )
+        .unwrap();
+
+        // source-close-authority close
+        let mut account = Account::unpack_unchecked(&account1_info.data.borrow()).unwrap();
+        account.close_authority = COption::Some(account1_key);
+        account.owner = owner_key;
+        Account::pack(account, &mut account1_info.data.borrow_mut()).unwrap();
+        do_process_instruction_dups(
+            close_account(
+                &program_id,
+                &account1_key,
+                &account2_key,
+                &account1_key,
+                &[],
+            )
+            .unwrap(),
+            vec![
+                account1_info.clone(),
+                account2_info.clone(),
+                account1_info.clone(),
+            ],
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn test_close_account() {
+        let program_id = crate::id();
+        let mint_key = Pubkey::new_unique();
+        let mut mint_account =
+            SolanaAccount::new(mint_minimum_balance(), Mint::get_packed_len(), &program_id);
+        let account_key = Pubkey::new_unique();
+        let mut account_account = SolanaAccount::new(
+            account_minimum_balance(),
+            Account::get_packed_len(),
+            &program_id,
+        );
+        let account2_key = Pubkey::new_unique();
+        let mut account2_account = SolanaAccount::new(
+            account_minimum_balance() + 42,
+            Account::get_packed_len(),
+            &program_id,
+        );
+        let account3_key = Pubkey::new_unique();
+        let mut account3_account = SolanaAccount::new(
+            account_minimum_balance(),
+            Account::get_packed_len(),
+            &program_id,
+        );
+        let owner_key = Pubkey::new_unique();
+        let mut owner_account = SolanaAccount::default();
+        let owner2_key = Pubkey::new_unique();
+        let mut owner2_account = SolanaAccount::default();
+        let mut rent_sysvar = rent_sysvar();
+
+        // uninitialized
+        assert_eq!(
+            Err(ProgramError::UninitializedAccount),
+            do_process_instruction(
+                close_account(&program_id, &account_key, &account3_key, &owner2_key, &[]).unwrap(),
+                vec![
+                    &mut account_account,
+                    &mut account3_account,
+                    &mut owner2_account,
+                ],
+            )
+        );
+
+        // initialize and mint to non-native account
+        do_process_instruction(
+            initialize_mint(&program_id, &mint_key, &owner_key, None, 2).unwrap(),
+            vec![&mut mint_account, &mut rent_sysvar],
+        )
+        .unwrap();
+        do_process_instruction(
+            initialize_account(&program_id, &account_key, &mint_key, &owner_key).unwrap(),
+            vec![
+                &mut account_account,
+                &mut mint_account,
+                &mut owner_account,
+                &mut rent_sysvar,
+            ],
+        )
+        .unwrap();
+        do_process_instruction(
+            mint_to(&program_id, &mint_key, &account_key, &owner_key, &[], 42).unwrap(),
+            vec![
+                &mut mint_account,
+                &mut account_account,
+                &mut owner_account,
+                &mut rent_sysvar,
+            ],
+        )
+        .unwrap();
+        let account = Account::unpack_unchecked(&account_account.data).unwrap();
+        assert_eq!(account.amount, 42);
+
+        // initialize native account
+        do_process_instruction(
+            initialize_account(
+                &program_id,
+                &account2_key,
+                &crate::native_mint::id(),
+                &owner_key,
+            )
+            .unwrap(),
+            vec![
+                &mut account2_account,
+                &mut mint_account,
+                &mut owner_account,
+                &mut rent_sysvar,
+            ],
+        )
+        .unwrap();
+        let account = Account::unpack_unchecked(&account2_account.data).unwrap();
+        assert!(account.is_native());
+        assert_eq!(account.amount, 42);
+
+        // close non-native account with balance
+        assert_eq!(
+            Err(TokenError::NonNativeHasBalance.into()),
+            do_process_instruction(
+                close_account(&program_id, &account_key, &account3_key, &owner_key, &[]).unwrap(),
+                vec![
+                    &mut account_account,
+                    &mut account3_account,
+                    &mut owner_account,
+                ],
+            )
+        );
+        assert_eq!(account_account.lamports, account_minimum_balance());
+
+        // empty account
+        do_process_instruction(
+            burn(&program_id, &account_key, &mint_key, &owner_key, &[], 42).unwrap(),
+            vec![&mut account_account, &mut mint_account, &mut owner_account],
+        )
+        .unwrap();
+
+        // wrong owner
+        assert_eq!(
+            Err(TokenError::OwnerMismatch.into()),
+            do_process_instruction(
+                close_account(&program_id, &account_key, &account3_key, &owner2_key, &[]).unwrap(),
+                vec![
+                    &mut account_account,
+                    &mut account3_account,
+                    &mut owner2_account,
+                ],
+            )
+        );
+
+        // close account
+        do_process_instruction(
+            close_account(&program_id, &account_key, &account3_key, &owner_key, &[]).unwrap(),
+            vec![
+                &mut account_account,
+                &mut account3_account,
+                &mut owner_account,
+            ],
+        )
+        .unwrap();
+        assert_eq!(account_account.lamports, 0);
+        assert_eq!(account3_account.lamports, 2 * account_minimum_balance());
+        let account = Account::unpack_unchecked(&account_account.data).unwrap();
+        assert_eq!(account.amount, 0);
+
+        // fund and initialize new non-native account to test close authority
+        let account_key = Pubkey::new_unique();
+        let mut account_account = SolanaAccount::new(
+            account_minimum_balance(),
+            Account::get_packed_len(),
+            &program_id,
+        );
+        let owner2_key = Pubkey::new_unique();
+        let mut owner2_account = SolanaAccount::new(
+            account_minimum_balance(),
+            Account::get_packed_len(),
+            &program_id,
+        );
+        do_process_instruction(
+            initialize_account(&program_id, &account_key, &mint_key, &owner_key).unwrap(),
+            vec![
+                &mut account_account,
+                &mut mint_account,
+                &mut owner_account,
+                &mut rent_sysvar,
+            ],
+        )
+        .unwrap();
+        account_account.lamports = 2;
+
+        do_process_instruction(
+            set_authority(
+                &program_id,
+                &account_key,
+                Some(&owner2_key),
+                AuthorityType::CloseAccount,
+                &owner_key,
+                &[],
+            )
+            .unwrap(),
+            vec![&mut account_account, &mut owner_account],
+        )
+        .unwrap();
+
+        // account owner cannot authorize close if close_authority is set
+        assert_eq!(
+            Err(TokenError::OwnerMismatch.into()),
+            do_process_instruction(
+                close_account(&program_id, &account_key, &account3_key, &owner_key, &[]).unwrap(),
+                vec![
+                    &mut account_account,
+                    &mut account3_account,
+                    &mut owner_account,
+                ],
+            )
+        );
+
+        // close non-native account with close_authority
+        do_process_instruction(
+            close_account(&program_id, &account_key, &account3_key, &owner2_key, &[]).unwrap(),
+            vec![
+                &mut account_account,
+                &mut account3_account,
+                &mut owner2_account,
+            ],
+        )
+        .unwrap();
+        assert_eq!(account_account.lamports, 0);
+        assert_eq!(account3_account.lamports, 2 * account_minimum_balance() + 2);
+        let account = Account::unpack_unchecked(&account_account.data).unwrap();
+        assert_eq!(account.amount, 0);
+
+        // close native account
+        do_process_instruction(
+            close_account(&program_id, &account2_key, &account3_key, &owner_key, &[]).unwrap(),
+            vec![
+                &mut account2_account,
+                &mut account3_account,
+                &mut owner_account,
+            ],
+        )
+        .unwrap();
+        let account = Account::unpack_unchecked(&account2_account.data).unwrap();
+        assert!(account.is_native());
+        assert_eq!(account_account.lamports, 0);
+        assert_eq!(account.amount, 0);
+        assert_eq!(
+            account3_account.lamports,
+            3 * account_minimum_balance() + 2 + 42
+        );
+    }
+
+    #[test]
+    fn test_native_token() {
+        let program_id = crate::id();
+        let mut mint_account =
+            SolanaAccount::new(mint_minimum_balance(), Mint::get_packed_len(), &program_id);
+        let account_key = Pubkey::new_unique();
+        let mut account_account = SolanaAccount::new(
+            account_minimum_balance() + 40,
+            Account::get_packed_len(),
+            &program_id,
+        );
+        let account2_key = Pubkey::new_unique();
+        let mut account2_account = SolanaAccount::new(
+            account_minimum_balance(),
+            Account::get_packed_len(),
+            &program_id,
+        );
+        let account3_key = Pubkey::new_unique();
+        let mut account3_account = SolanaAccount::new(account_minimum_balance(), 0, &program_id);
+        let owner_key = Pubkey::new_unique();
+        let mut owner_account = SolanaAccount::default();
+        let mut rent_sysvar = rent_sysvar();
+
+        // initialize native account
+        do_process_instruction(
+            initialize_account(
+                &program_id,
+                &account_key,
+                &crate::native_mint::id(),
+                &owner_key,
+            )
+            .unwrap(),
+            vec![
+                &mut account_account,
+                &mut mint_account,
+                &mut owner_account,
+                &mut rent_sysvar,
+            ],
+        )
+        .unwrap();
+        let account = Account::unpack_unchecked(&account_account.data).unwrap();
+        assert!(account.is_native());
+        assert_eq!(account.amount, 40);
+
+        // initialize native account
+        do_process_instruction(
+            initialize_account(
+                &program_id,
+                &account2_key,
+                &crate::native_mint::id(),
+                &owner_key,
+            )
+            .unwrap(),
+            vec![
+                &mut account2_account,
+                &mut mint_account,
+                &mut owner_account,
+                &mut rent_sysvar,
+            ],
+        )
+        .unwrap();
+        let account = Account::unpack_unchecked(&account2_account.data).unwrap();
+        assert!(account.is_native());
+        assert_eq!(account.amount, 0);
+
+        // mint_to unsupported
+        assert_eq!(
+            Err(TokenError::NativeNotSupported.into()),
+            do_process_instruction(
+                mint_to(
+                    &program_id,
+                    &crate::native_mint::id(),
+                    &account_key,
+                    &owner_key,
+                    &[],
+                    42
+                )
+                .unwrap(),
+                vec![&mut mint_account, &mut account_account, &mut owner_account],
+            )
+        );
+
+        // burn unsupported
+        let bogus_mint_key = Pubkey::new_unique();
+        let mut bogus_mint_account =
+            SolanaAccount::new(mint_minimum_balance(), Mint::get_packed_len(), &program_id);
+        do_process_instruction(
+            initialize_mint(&program_id, &bogus_mint_key, &owner_key, None, 2).unwrap(),
+            vec![&mut bogus_mint_account, &mut rent_sysvar],
+        )
+        .unwrap();
+
+        assert_eq!(
+            Err(TokenError::NativeNotSupported.into()),
+            do_process_instruction(
+                burn(
+                    &program_id,
+                    &account_key,
+                    &bogus_mint_key,
+                    &owner_key,
+                    &[],
+                    42
+                )
+                .unwrap(),
+                vec![
+                    &mut account_account,
+                    &mut bogus_mint_account,
+                    &mut owner_account
+                ],
+            )
+        );
+
+        // ensure can't transfer below rent-exempt reserve
+        assert_eq!(
+            Err(TokenError::InsufficientFunds.into()),
+            do_process_instruction(
+                transfer(
+                    &program_id,
+                    &account_key,
+                    &account2_key,
+                    &owner_key,
+                    &[],
+                    50,
+                )
+                .unwrap(),
+                vec![
+                    &mut account_account,
+                    &mut account2_account,
+                    &mut owner_account,
+                ],
+            )
+        );
+
+        // transfer between native accounts
+        do_process_instruction(
+            transfer(
+                &program_id,
+                &account_key,
+                &account2_key,
+                &owner_key,
+                &[],
+                40,
+            )
+            .unwrap(),
+            vec![
+                &mut account_account,
+                &mut account2_account,
+                &mut owner_account,
+            ],
+        )
+        .unwrap();
+        assert_eq!(account_account.lamports, account_minimum_balance());
+        let account = Account::unpack_unchecked(&account_account.data).unwrap();
+        assert!(account.is_native());
+        assert_eq!(account.amount, 0);
+        assert_eq!(account2_account.lamports, account_minimum_balance() + 40);
+        let account = Account::unpack_unchecked(&account2_account.data).unwrap();
+        assert!(account.is_native());
+        assert_eq!(account.amount, 40);
+
+        // close native account
+        do_process_instruction(
+            close_account(&program_id, &account_key, &account3_key, &owner_key, &[]).unwrap(),
+            vec![
+                &mut account_account,
+                &mut account3_account,
+                &mut owner_account,
+            ],
+        )
+        .unwrap();
+        assert_eq!(account_account.lamports, 0);
+        assert_eq!(account3_account.lamports, 2 * account_minimum_balance());
+        let account = Account::unpack_unchecked(&account_account.data).unwrap();
+        assert!(account.is_native());
+        assert_eq!(account.amount, 0);
+    }
+
+    #[test]
+    fn test_overflow() {
+        let program_id = crate::id();
+        let account_key = Pubkey::new_unique();
+        let mut account_account = SolanaAccount::new(
+            account_minimum_balance(),
+            Account::get_packed_len(),
+            &program_id,
+        );
+        let account2_key = Pubkey::new_unique();
+        let mut account2_account = SolanaAccount::new(
+            account_minimum_balance(),
+            Account::get_packed_len(),
+            &program_id,
+        );
+        let owner_key = Pubkey::new_unique();
+        let mut owner_account = SolanaAccount::default();
+        let owner2_key = Pubkey::new_unique();
+        let mut owner2_account = SolanaAccount::default();
+        let mint_owner_key = Pubkey::new_unique();
+        let mut mint_owner_account = SolanaAccount::default();
+        let mint_key = Pubkey::new_unique();
+        let mut mint_account =
+            SolanaAccount::new(mint_minimum_balance(), Mint::get_packed_len(), &program_id);
+        let mut rent_sysvar = rent_sysvar();
+
+        // create new mint with owner
+        do_process_instruction(
+            initialize_mint(&program_id, &mint_key, &mint_owner_key, None, 2).unwrap(),
+            vec![&mut mint_account, &mut rent_sysvar],
+        )
+        .unwrap();
+
+        // create an account
+        do_process_instruction(
+            initialize_account(&program_id, &account_key, &mint_key, &owner_key).unwrap(),
+            vec![
+                &mut account_account,
+                &mut mint_account,
+                &mut owner_account,
+                &mut rent_sysvar,
+            ],
+        )
+        .unwrap();
+
+        // create another account
+        do_process_instruction(
+            initialize_account(&program_id, &account2_key, &mint_key, &owner2_key).unwrap(),
+            vec![
+                &mut account2_account,
+                &mut mint_account,
+                &mut owner2_account,
+                &mut rent_sysvar,
+            ],
+        )
+        .unwrap();
+
+        // mint the max to an account
+        do_process_instruction(
+            mint_to(
+                &program_id,
+                &mint_key,
+                &account_key,
+                &mint_owner_key,
+                &[],
+                u64::MAX,
+            )
+            .unwrap(),
+            vec![
+                &mut mint_account,
+                &mut account_account,
+                &mut mint_owner_account,
+            ],
+        )
+        .unwrap();
+        let account = Account::unpack_unchecked(&account_account.data).unwrap();
+        assert_eq!(account.amount, u64::MAX);
+
+        // attempt to mint one more to account
+        assert_eq!(
+            Err(TokenError::Overflow.into()),
+            do_process_instruction(
+                mint_to(
+                    &program_id,
+                    &mint_key,
+                    &account_key,
+                    &mint_owner_key,
+                    &[],
+                    1,
+                )
+                .unwrap(),
+                vec![
+                    &mut mint_account,
+                    &mut account_account,
+                    &mut mint_owner_account,
+                ],
+            )
+        );
+        let account = Account::unpack_unchecked(&account_account.data).unwrap();
+        assert_eq!(account.amount, u64::MAX);
+
+        // atttempt to mint one more to the other account
+        assert_eq!(
+            Err(TokenError::Overflow.into()),
+            do_process_instruction(
+                mint_to(
+                    &program_id,
+                    &mint_key,
+                    &account2_key,
+                    &mint_owner_key,
+                    &[],
+                    1,
+                )
+                .unwrap(),
+                vec![
+                    &mut mint_account,
+                    &mut account2_account,
+                    &mut mint_owner_account,
+                ],
+            )
+        );
+
+        // burn some of the supply
+        do_process_instruction(
+            burn(&program_id, &account_key, &mint_key, &owner_key, &[], 100).unwrap(),
+            vec![&mut account_account, &mut mint_account, &mut owner_account],
+        )
+        .unwrap();
+        let account = Account::unpack_unchecked(&account_account.data).unwrap();
+        assert_eq!(account.amount, u64::MAX - 100);
+
+        do_process_instruction(
+            mint_to(
+                &program_id,
+                &mint_key,
+                &account_key,
+                &mint_owner_key,
+                &[],
+                100,
+            )
+            .unwrap(),
+            vec![
+                &mut mint_account,
+                &mut account_account,
+                &mut mint_owner_account,
+            ],
+        )
+        .unwrap();
+        let account = Account::unpack_unchecked(&account_account.data).unwrap();
+        assert_eq!(account.amount, u64::MAX);
+
+        // manipulate account balance to attempt overflow transfer
+        let mut account = Account::unpack_unchecked(&account2_account.data).unwrap();
+        account.amount = 1;
+        Account::pack(account, &mut account2_account.data).unwrap();
+
+        assert_eq!(
+            Err(TokenError::Overflow.into()),
+            do_process_instruction(
+                transfer(
+                    &program_id,
+                    &account2_key,
+                    &account_key,
+                    &owner2_key,
+                    &[],
+                    1,
+                )
+                .unwrap(),
+                vec![
+                    &mut account2_account,
+                    &mut account_account,
+                    &mut owner2_account,
+                ],
+            )
+        );
+    }
+
+    #[test]
+    fn test_frozen() {
+        let program_id = crate::id();
+        let account_key = Pubkey::new_unique();
+        let mut account_account = SolanaAccount::new(
+            account_minimum_balance(),
+            Account::get_packed_len(),
+            &program_id,
+        );
+        let account2_key = Pubkey::new_unique();
+        let mut account2_account = SolanaAccount::new(
+            account_minimum_balance(),
+            Account::get_packed_len(),
+            &program_id,
+        );
+        let owner_key = Pubkey::new_unique();
+        let mut owner_account = SolanaAccount::default();
+        let mint_key = Pubkey::new_unique();
+        let mut mint_account =
+            SolanaAccount::new(mint_minimum_balance(), Mint::get_packed_len(), &program_id);
+        let mut rent_sysvar = rent_sysvar();
+
+        // create new mint and fund first account
+        do_process_instruction(
+            initialize_mint(&program_id, &mint_key, &owner_key, None, 2).unwrap(),
+            vec![&mut mint_account, &mut rent_sysvar],
+        )
+        .unwrap();
+
+        // create account
+        do_process_instruction(
+            initialize_account(&program_id, &account_key, &mint_key, &owner_key).unwrap(),
+            vec![
+                &mut account_account,
+                &mut mint_account,
+                &mut owner_account,
+                &mut rent_sysvar,
+            ],
+        )
+        .unwrap();
+
+        // create another account
+        do_process_instruction(
+            initialize_account(&program_id, &account2_key, &mint_key, &owner_key).unwrap(),
+            vec![
+                &mut account2_account,
+                &mut mint_account,
+                &mut owner_account,
+                &mut rent_sysvar,
+            ],
+        )
+        .unwrap();
+
+        // fund first account
+        do_process_instruction(
+            mint_to(&program_id, &mint_key, &account_key, &owner_key, &[], 1000).unwrap(),
+            vec![&mut mint_account, &mut account_account, &mut owner_account],
+        )
+        .unwrap();
+
+        // no transfer if either account is frozen
+        let mut account = Account::unpack_unchecked(&account2_account.data).unwrap();
+        account.state = AccountState::Frozen;
+        Account::pack(account, &mut account2_account.data).unwrap();
+        assert_eq!(
+            Err(TokenError::AccountFrozen.into()),
+            do_process_instruction(
+                transfer(
+                    &program_id,
+                    &account_key,
+                    &account2_key,
+                    &owner_key,
+                    &[],
+                    500,
+                )
+                .unwrap(),
+                vec![
+                    &mut account_account,
+                    &mut account2_account,
+                    &mut owner_account,
+                ],
+            )
+        );
+
+        let mut account = Account::unpack_unchecked(&account_account.data).unwrap();
+        account.state = AccountState::Initialized;
+        Account::pack(account, &mut account_account.data).unwrap();
+        let mut account = Account::unpack_unchecked(&account2_account.data).unwrap();
+        account.state = AccountState::Frozen;
+        Account::pack(account, &mut account2_account.data).unwrap();
+        assert_eq!(
+            Err(TokenError::AccountFrozen.into()),
+            do_process_instruction(
+                transfer(
+                    &program_id,
+                    &account_key,
+                    &account2_key,
+                    &owner_key,
+                    &[],
+                    500,
+                )
+                .unwrap(),
+                vec![
+                    &mut account_account,
+                    &mut account2_account,
+                    &mut owner_account,
+                ],
+            )
+        );
+
+        // no approve if account is frozen
+        let mut account = Account::unpack_unchecked(&account_account.data).unwrap();
+        account.state = AccountState::Frozen;
+        Account::pack(account, &mut account_account.data).unwrap();
+        let delegate_key = Pubkey::new_unique();
+        let mut delegate_account = SolanaAccount::default();
+        assert_eq!(
+            Err(TokenError::AccountFrozen.into()),
+            do_process_instruction(
+                approve(
+                    &program_id,
+                    &account_key,
+                    &delegate_key,
+                    &owner_key,
+                    &[],
+                    100
+                )
+                .unwrap(),
+                vec![
+                    &mut account_account,
+                    &mut delegate_account,
+                    &mut owner_account,
+                ],
+            )
+        );
+
+        // no revoke if account is frozen
+        let mut account = Account::unpack_unchecked(&account_account.data).unwrap();
+        account.delegate = COption::Some(delegate_key);
+        account.delegated_amount = 100;
+        Account::pack(account, &mut account_account.data).unwrap();
+        assert_eq!(
+            Err(TokenError::AccountFrozen.into()),
+            do_process_instruction(
+                revoke(&program_id, &account_key, &owner_key, &[]).unwrap(),
+                vec![&mut account_account, &mut owner_account],
+            )
+        );
+
+        // no set authority if account is frozen
+        let new_owner_key = Pubkey::new_unique();
+        assert_eq!(
+            Err(TokenError::AccountFrozen.into()),
+            do_process_instruction(
+                set_authority(
+                    &program_id,
+                    &account_key,
+                    Some(&new_owner_key),
+                    AuthorityType::AccountOwner,
+                    &owner_key,
+                    &[]
+                )
+                .unwrap(),
+                vec![&mut account_account, &mut owner_account,],
+            )
+        );
+
+        // no mint_to if destination account is frozen
+        assert_eq!(
+            Err(TokenError::AccountFrozen.into()),
+            do_process_instruction(
+                mint_to(&program_id, &mint_key, &account_key, &owner_key, &[], 100).unwrap(),
+                vec![&mut mint_account, &mut account_account, &mut owner_account,],
+            )
+        );
+
+        // no burn if account is frozen
+        assert_eq!(
+            Err(TokenError::AccountFrozen.into()),
+            do_process_instruction(
+                burn(&program_id, &account_key, &mint_key, &owner_key, &[], 100).unwrap(),
+                vec![&mut account_account, &mut mint_account, &mut owner_account],
+            )
+        );
+    }
+
+    #[test]
+    fn test_freeze_thaw_dups() {
+        let program_id = crate::id();
+        let account1_key = Pubkey::new_unique();
+        let mut account1_account = SolanaAccount::new(
+            account_minimum_balance(),
+            Account::get_packed_len(),
+            &program_id,
+        );
+        let account1_info: AccountInfo = (&account1_key, true, &mut account1_account).into();
+        let owner_key = Pubkey::new_unique();
+        let mint_key = Pubkey::new_unique();
+        let mut mint_account =
+            SolanaAccount::new(mint_minimum_balance(), Mint::get_packed_len(), &program_id);
+        let mint_info: AccountInfo = (&mint_key, true, &mut mint_account).into();
+        let rent_key = rent::id();
+        let mut rent_sysvar = rent_sysvar();
+        let rent_info: AccountInfo = (&rent_key, false, &mut rent_sysvar).into();
+
+        // create mint
+        do_process_instruction_dups(
+            initialize_mint(&program_id, &mint_key, &owner_key, Some(&account1_key), 2).unwrap(),
+            vec![mint_info.clone(), rent_info.clone()],
+        )
+        .unwrap();
+
+        // create account
+        do_process_instruction_dups(
+            initialize_account(&program_id, &account1_key, &mint_key, &account1_key).unwrap(),
+            vec![
+                account1_info.clone(),
+                mint_info.clone(),
+                account1_info.clone(),
+                rent_info.clone(),
+            ],
+        )
+        .unwrap();
+
+        // freeze where mint freeze_authority is account
+        do_process_instruction_dups(
+            freeze_account(&program_id, &account1_key, &mint_key, &account1_key, &[]).unwrap(),
+            vec![
+                account1_info.clone(),
+                mint_info.clone(),
+                account1_info.clone(),
+            ],
+        )
+        .unwrap();
+
+        // thaw where mint freeze_authority is account
+        let mut account = Account::unpack_unchecked(&account1_info.data.borrow()).unwrap();
+        account.state = AccountState::Frozen;
+        Account::pack(account, &mut account1_info.data.borrow_mut()).unwrap();
+        do_process_instruction_dups(
+            thaw_account(&program_id, &account1_key, &mint_key, &account1_key, &[]).unwrap(),
+            vec![
+                account1_info.clone(),
+                mint_info.clone(),
+                account1_info.clone(),
+            ],
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn test_freeze_account() {
+        let program_id = crate::id();
+        let account_key = Pubkey::new_unique();
+        let mut account_account = SolanaAccount::new(
+            account_minimum_balance(),
+            Account::get_packed_len(),
+            &program_id,
+        );
+        let account_owner_key = Pubkey::new_unique();
+        let mut account_owner_account = SolanaAccount::default();
+        let owner_key = Pubkey::new_unique();
+        let mut owner_account = SolanaAccount::default();
+        let owner2_key = Pubkey::new_unique();
+        let mut owner2_account = SolanaAccount::default();
+        let mint_key = Pubkey::new_unique();
+        let mut mint_account =
+            SolanaAccount::new(mint_minimum_balance(), Mint::get_packed_len(), &program_id);
+        let mut rent_sysvar = rent_sysvar();
+
+        // create new mint with owner different from account owner
+        do_process_instruction(
+            initialize_mint(&program_id, &mint_key, &owner_key, None, 2).unwrap(),
+            vec![&mut mint_account, &mut rent_sysvar],
+        )
+        .unwrap();
+
+        // create account
+        do_process_instruction(
+            initialize_account(&program_id, &account_key, &mint_key, &account_owner_key).unwrap(),
+            vec![
+                &mut account_account,
+                &mut mint_account,
+                &mut account_owner_account,
+                &mut rent_sysvar,
+            ],
+        )
+        .unwrap();
+
+        // mint to account
+        do_process_instruction(
+            mint_to(&program_id, &mint_key, &account_key, &owner_key, &[], 1000).unwrap(),
+            vec![&mut mint_account, &mut account_account, &mut owner_account],
+        )
+        .unwrap();
+
+        // mint cannot freeze
+        assert_eq!(
+            Err(TokenError::MintCannotFreeze.into()),
+            do_process_instruction(
+                freeze_account(&program_id, &account_key, &mint_key, &owner_key, &[]).unwrap(),
+                vec![&mut account_account, &mut mint_account, &mut owner_account],
+            )
+        );
+
+        // missing freeze_authority
+        let mut mint = Mint::unpack_unchecked(&mint_account.data).unwrap();
+        mint.freeze_authority = COption::Some(owner_key);
+        Mint::pack(mint, &mut mint_account.data).unwrap();
+        assert_eq!(
+            Err(TokenError::OwnerMismatch.into()),
+            do_process_instruction(
+                freeze_account(&program_id, &account_key, &mint_key, &owner2_key, &[]).unwrap(),
+                vec![&mut account_account, &mut mint_account, &mut owner2_account],
+            )
+        );
+
+        // check explicit thaw
+        assert_eq!(
+            Err(TokenError::InvalidState.into()),
+            do_process_instruction(
+                thaw_account(&program_id, &account_key, &mint_key, &owner2_key, &[]).unwrap(),
+                vec![&mut account_account, &mut mint_account, &mut owner2_account],
+            )
+        );
+
+        // freeze
+        do_process_instruction(
+            freeze_account(&program_id, &account_key, &mint_key, &owner_key, &[]).unwrap(),
+            vec![&mut account_account, &mut mint_account, &mut owner_account],
+        )
+        .unwrap();
+        let account = Account::unpack_unchecked(&account_account.data).unwrap();
+        assert_eq!(account.state, AccountState::Frozen);
+
+        // check explicit freeze
+        assert_eq!(
+            Err(TokenError::InvalidState.into()),
+            do_process_instruction(
+                freeze_account(&program_id, &account_key, &mint_key, &owner_key, &[]).unwrap(),
+                vec![&mut account_account, &mut mint_account, &mut owner_account],
+            )
+        );
+
+        // check thaw authority
+        assert_eq!(
+            Err(TokenError::OwnerMismatch.into()),
+            do_process_instruction(
+                thaw_account(&program_id, &account_key, &mint_key, &owner2_key, &[]).unwrap(),
+                vec![&mut account_account, &mut mint_account, &mut owner2_account],
+            )
+        );
+
+        // thaw
+        do_process_instruction(
+            thaw_account(&program_id, &account_key, &mint_key, &owner_key, &[]).unwrap(),
+            vec![&mut account_account, &mut mint_account, &mut owner_account],
+        )
+        .unwrap();
+        let account = Account::unpack_unchecked(&account_account.data).unwrap();
+        assert_eq!(account.state, AccountState::Initialized);
+    }
+
+    #[test]
+    fn test_initialize_account2() {
+        let program_id = crate::id();
+        let account_key = Pubkey::new_unique();
+        let mut account_account = SolanaAccount::new(
+            account_minimum_balance(),
+            Account::get_packed_len(),
+            &program_id,
+        );
+        let mut account2_account = SolanaAccount::new(
+            account_minimum_balance(),
+            Account::get_packed_len(),
+            &program_id,
+        );
+        let owner_key = Pubkey::new_unique();
+        let mut owner_account = SolanaAccount::default();
+        let mint_key = Pubkey::new_unique();
+        let mut mint_account =
+            SolanaAccount::new(mint_minimum_balance(), Mint::get_packed_len(), &program_id);
+        let mut rent_sysvar = rent_sysvar();
+
+        // create mint
+        do_process_instruction(
+            initialize_mint(&program_id, &mint_key, &owner_key, None, 2).unwrap(),
+            vec![&mut mint_account, &mut rent_sysvar],
+        )
+        .unwrap();
+
+        do_process_instruction(
+            initialize_account(&program_id, &account_key, &mint_key, &owner_key).unwrap(),
+            vec![
+                &mut account_account,
+                &mut mint_account,
+                &mut owner_account,
+                &mut rent_sysvar,
+            ],
+        )
+        .unwrap();
+
+        do_process_instruction(
+            initialize_account2(&program_id, &account_key, &mint_key, &owner_key).unwrap(),
+            vec![&mut account2_account, &mut mint_account, &mut rent_sysvar],
+        )
+        .unwrap();
+
+        assert_eq!(account_account, account2_account);
+    }
+
+    #[test]
+    fn test_minter() {
+        let program_id = crate::id();
+        let account_key = Pubkey::new_unique();
+        let mut account_account = SolanaAccount::new(
+            extended_account_minimum_balance(),
+            Account::EXTENDED_LEN,
+            &program_id,
+        );
+        let account_owner_key = Pubkey::new_unique();
+        let mut account_owner_account = SolanaAccount::default();
+        let owner_key = Pubkey::new_unique();
+        let mut owner_account = SolanaAccount::default();
+        let owner2_key = Pubkey::new_unique();
+        let mut owner2_account = SolanaAccount::default();
+        let minter_key = Pubkey::new_unique();
+        let mut minter_account = SolanaAccount::new(42, Minter::get_packed_len(), &program_id);
+        let minter_authority_key = Pubkey::new_unique();
+        let mut minter_authority_account = SolanaAccount::default();
+        let minter2_key = Pubkey::new_unique();
+        let mut minter2_account = SolanaAccount::new(
+            minter_minimum_balance(),
+            Minter::get_packed_len(),
+            &program_id,
+        );
+        let mint_key = Pubkey::new_unique();
+        let mut mint_account = SolanaAccount::new(
+            extended_mint_minimum_balance(),
+            Mint::EXTENDED_LEN,
+            &program_id,
+        );
+        let mint2_key = Pubkey::new_unique();
+        let mut mint2_account = SolanaAccount::new(
+            extended_mint_minimum_balance(),
+            Mint::EXTENDED_LEN,
+            &program_id,
+        );
+        let destination_key = Pubkey::new_unique();
+        let mut destination_account = SolanaAccount::default();
+        let mut rent_sysvar = rent_sysvar();
+
+        // create mints
+        do_process_instruction(
+            initialize_mint(&program_id, &mint_key, &owner_key, None, 2).unwrap(),
+            vec![&mut mint_account, &mut rent_sysvar],
+        )
+        .unwrap();
+        do_process_instruction(
+            initialize_mint(&program_id, &mint2_key, &owner_key, None, 2).unwrap(),
+            vec![&mut mint2_account, &mut rent_sysvar],
+        )
+        .unwrap();
+
+        // create account
+        do_process_instruction(
+            initialize_account(&program_id, &account_key, &mint_key, &account_owner_key).unwrap(),
+            vec![
+                &mut account_account,
+                &mut mint_account,
+                &mut account_owner_account,
+                &mut rent_sysvar,
+            ],
+        )
+        .unwrap();
+
+        // minter is not rent exempt
+        assert_eq!(
+            Err(TokenError::NotRentExempt.into()),
+            do_process_instruction(
+                initialize_minter(
+                    &program_id,
+                    &minter_key,
+                    &mint_key,
+                    &minter_authority_key,
+                    &owner_key,
+                    &[],
+                    100,
+                )
+                .unwrap(),
+                vec![
+                    &mut minter_account,
+                    &mut mint_account,
+                    &mut rent_sysvar,
+                    &mut owner_account,
+                ],
+            )
+        );
+
+        minter_account.lamports = minter_minimum_balance();
+
+        // missing mint authority
+        assert_eq!(
+            Err(TokenError::OwnerMismatch.into()),
+            do_process_instruction(
+                initialize_minter(
+                    &program_id,
+                    &minter_key,
+                    &mint_key,
+                    &minter_authority_key,
+                    &owner2_key,
+                    &[],
+                    100,
+                )
+                .unwrap(),
+                vec![
+                    &mut minter_account,
+                    &mut mint_account,
+                    &mut rent_sysvar,
+                    &mut owner2_account,
+                ],
+            )
+        );
+
+        // create minter
+        do_process_instruction(
+            initialize_minter(
+                &program_id,
+                &minter_key,
+                &mint_key,
+                &minter_authority_key,
+                &owner_key,
+                &[],
+                100,
+            )
+            .unwrap(),
+            vec![
+                &mut minter_account,
+                &mut mint_account,
+                &mut rent_sysvar,
+                &mut owner_account,
+            ],
+        )
+        .unwrap();
+        let minter = Minter::unpack(&minter_account.data).unwrap();
+        assert_eq!(minter.mint, mint_key);
+        assert_eq!(minter.authority, minter_authority_key);
+        assert_eq!(minter.allowance, 100);
+
+        // create twice
+        assert_eq!(
+            Err(TokenError::AlreadyInUse.into()),
+            do_process_instruction(
+                initialize_minter(
+                    &program_id,
+                    &minter_key,
+                    &mint_key,
+                    &minter_authority_key,
+                    &owner_key,
+                    &[],
+                    100,
+                )
+                .unwrap(),
+                vec![
+                    &mut minter_account,
+                    &mut mint_account,
+                    &mut rent_sysvar,
+                    &mut owner_account,
+                ],
+            )
+        );
+
+        // create minter of another mint
+        do_process_instruction(
+            initialize_minter(
+                &program_id,
+                &minter2_key,
+                &mint2_key,
+                &minter_authority_key,
+                &owner_key,
+                &[],
+                100,
+            )
+            .unwrap(),
+            vec![
+                &mut minter2_account,
+                &mut mint2_account,
+                &mut rent_sysvar,
+                &mut owner_account,
+            ],
+        )
+        .unwrap();
+
+        // mint with minter
+        do_process_instruction(
+            mint_to_with_minter(
+                &program_id,
+                &mint_key,
+                &account_key,
+                &minter_key,
+                &minter_authority_key,
+                &[],
+                60,
+            )
+            .unwrap(),
+            vec![
+                &mut mint_account,
+                &mut account_account,
+                &mut minter_account,
+                &mut minter_authority_account,
+            ],
+        )
+        .unwrap();
+        let account = Account::unpack(&account_account.data).unwrap();
+        assert_eq!(account.amount, 60);
+        let mint = Mint::unpack(&mint_account.data).unwrap();
+        assert_eq!(mint.supply, 60);
+        let minter = Minter::unpack(&minter_account.data).unwrap();
+        assert_eq!(minter.allowance, 40);
+
+        // allowance exceeded
+        assert_eq!(
+            Err(TokenError::MinterAllowanceExceeded.into()),
+            do_process_instruction(
+                mint_to_with_minter(
+                    &program_id,
+                    &mint_key,
+                    &account_key,
+                    &minter_key,
+                    &minter_authority_key,
+                    &[],
+                    41,
+                )
+                .unwrap(),
+                vec![
+                    &mut mint_account,
+                    &mut account_account,
+                    &mut minter_account,
+                    &mut minter_authority_account,
+                ],
+            )
+        );
+
+        // missing minter authority
+        assert_eq!(
+            Err(TokenError::OwnerMismatch.into()),
+            do_process_instruction(
+                mint_to_with_minter(
+                    &program_id,
+                    &mint_key,
+                    &account_key,
+                    &minter_key,
+                    &owner2_key,
+                    &[],
+                    1,
+                )
+                .unwrap(),
+                vec![
+                    &mut mint_account,
+                    &mut account_account,
+                    &mut minter_account,
+                    &mut owner2_account,
+                ],
+            )
+        );
+
+        // minter authority not a signer
+        let mut instruction = mint_to_with_minter(
+            &program_id,
+            &mint_key,
+            &account_key,
+            &minter_key,
+            &minter_authority_key,
+            &[],
+            1,
+        )
+        .unwrap();
+        instruction.accounts[3].is_signer = false;
+        assert_eq!(
+            Err(ProgramError::MissingRequiredSignature),
+            do_process_instruction(
+                instruction,
+                vec![
+                    &mut mint_account,
+                    &mut account_account,
+                    &mut minter_account,
+                    &mut minter_authority_account,
+                ],
+            )
+        );
+
+        // minter of another mint
+        assert_eq!(
+            Err(TokenError::MintMismatch.into()),
+            do_process_instruction(
+                mint_to_with_minter(
+                    &program_id,
+                    &mint_key,
+                    &account_key,
+                    &minter2_key,
+                    &minter_authority_key,
+                    &[],
+                    1,
+                )
+                .unwrap(),
+                vec![
+                    &mut mint_account,
+                    &mut account_account,
+                    &mut minter2_account,
+                    &mut minter_authority_account,
+                ],
+            )
+        );
+
+        // missing mint authority
+        assert_eq!(
+            Err(TokenError::OwnerMismatch.into()),
+            do_process_instruction(
+                set_minter_allowance(&program_id, &minter_key, &mint_key, &owner2_key, &[], 1000)
+                    .unwrap(),
+                vec![&mut minter_account, &mut mint_account, &mut owner2_account],
+            )
+        );
+
+        // minter of another mint
+        assert_eq!(
+            Err(TokenError::MintMismatch.into()),
+            do_process_instruction(
+                set_minter_allowance(&program_id, &minter2_key, &mint_key, &owner_key, &[], 1000)
+                    .unwrap(),
+                vec![&mut minter2_account, &mut mint_account, &mut owner_account],
+            )
+        );
+
+        // set allowance
+        do_process_instruction(
+            set_minter_allowance(&program_id, &minter_key, &mint_key, &owner_key, &[], 1000)
+                .unwrap(),
+            vec![&mut minter_account, &mut mint_account, &mut owner_account],
+        )
+        .unwrap();
+        do_process_instruction(
+            mint_to_with_minter(
+                &program_id,
+                &mint_key,
+                &account_key,
+                &minter_key,
+                &minter_authority_key,
+                &[],
+                1000,
+            )
+            .unwrap(),
+            vec![
+                &mut mint_account,
+                &mut account_account,
+                &mut minter_account,
+                &mut minter_authority_account,
+            ],
+        )
+        .unwrap();
+        let minter = Minter::unpack(&minter_account.data).unwrap();
+        assert_eq!(minter.allowance, 0);
+
+        // missing mint authority
+        assert_eq!(
+            Err(TokenError::OwnerMismatch.into()),
+            do_process_instruction(
+                revoke_minter(
+                    &program_id,
+                    &minter2_key,
+                    &mint2_key,
+                    &destination_key,
+                    &owner2_key,
+                    &[],
+                )
+                .unwrap(),
+                vec![
+                    &mut minter2_account,
+                    &mut mint2_account,
+                    &mut destination_account,
+                    &mut owner2_account,
+                ],
+            )
+        );
+
+        // revoke minter, leaving the other minter in place
+        do_process_instruction(
+            revoke_minter(
+                &program_id,
+                &minter2_key,
+                &mint2_key,
+                &destination_key,
+                &owner_key,
+                &[],
+            )
+            .unwrap(),
+            vec![
+                &mut minter2_account,
+                &mut mint2_account,
+                &mut destination_account,
+                &mut owner_account,
+            ],
+        )
+        .unwrap();
+        assert_eq!(minter2_account.lamports, 0);
+        assert_eq!(destination_account.lamports, minter_minimum_balance());
+        let minter = Minter::unpack(&minter_account.data).unwrap();
+        assert!(minter.is_initialized);
+
+        // mint authority still mints without a minter
+        do_process_instruction(
+            mint_to(&program_id, &mint_key, &account_key, &owner_key, &[], 1).unwrap(),
+            vec![&mut mint_account, &mut account_account, &mut owner_account],
+        )
+        .unwrap();
+        let account = Account::unpack(&account_account.data).unwrap();
+        assert_eq!(account.amount, 1061);
+    }
+
+    #[test]
+    fn test_mint_roles() {
+        let program_id = crate::id();
+        let account_key = Pubkey::new_unique();
+        let mut account_account = SolanaAccount::new(
+            extended_account_minimum_balance(),
+            Account::EXTENDED_LEN,
+            &program_id,
+        );
+        let treasury_key = Pubkey::new_unique();
+        let mut treasury_account = SolanaAccount::new(
+            extended_account_minimum_balance(),
+            Account::EXTENDED_LEN,
+            &program_id,
+        );
+        let account_owner_key = Pubkey::new_unique();
+        let mut account_owner_account = SolanaAccount::default();
+        let owner_key = Pubkey::new_unique();
+        let mut owner_account = SolanaAccount::default();
+        let role_key = Pubkey::new_unique();
+        let mut role_account = SolanaAccount::default();
+        let mint_key = Pubkey::new_unique();
+        let mut mint_account = SolanaAccount::new(
+            extended_mint_minimum_balance(),
+            Mint::EXTENDED_LEN,
+            &program_id,
+        );
+        let mut rent_sysvar = rent_sysvar();
+
+        // create new mint with freeze authority
+        do_process_instruction(
+            initialize_mint(&program_id, &mint_key, &owner_key, Some(&owner_key), 2).unwrap(),
+            vec![&mut mint_account, &mut rent_sysvar],
+        )
+        .unwrap();
+
+        // create account
+        do_process_instruction(
+            initialize_account(&program_id, &account_key, &mint_key, &account_owner_key).unwrap(),
+            vec![
+                &mut account_account,
+                &mut mint_account,
+                &mut account_owner_account,
+                &mut rent_sysvar,
+            ],
+        )
+        .unwrap();
+
+        // mint to account
+        do_process_instruction(
+            mint_to(&program_id, &mint_key, &account_key, &owner_key, &[], 1000).unwrap(),
+            vec![&mut mint_account, &mut account_account, &mut owner_account],
+        )
+        .unwrap();
+
+        // missing mint authority
+        assert_eq!(
+            Err(TokenError::OwnerMismatch.into()),
+            do_process_instruction(
+                set_mint_role(
+                    &program_id,
+                    &mint_key,
+                    Some(&role_key),
+                    MintRole::Pauser,
+                    &role_key,
+                    &[],
+                )
+                .unwrap(),
+                vec![&mut mint_account, &mut role_account],
+            )
+        );
+
+        // mint cannot pause
+        assert_eq!(
+            Err(TokenError::MintCannotPause.into()),
+            do_process_instruction(
+                pause_minting(&program_id, &mint_key, &role_key, &[]).unwrap(),
+                vec![&mut mint_account, &mut role_account],
+            )
+        );
+
+        // assign roles
+        for role in [MintRole::Burner, MintRole::Pauser, MintRole::Freezer].iter() {
+            do_process_instruction(
+                set_mint_role(
+                    &program_id,
+                    &mint_key,
+                    Some(&role_key),
+                    role.clone(),
+                    &owner_key,
+                    &[],
+                )
+                .unwrap(),
+                vec![&mut mint_account, &mut owner_account],
+            )
+            .unwrap();
+        }
+        let mint = Mint::unpack(&mint_account.data).unwrap();
+        assert_eq!(mint.burner, COption::Some(role_key));
+        assert_eq!(mint.pauser, COption::Some(role_key));
+        assert_eq!(mint.freezer, COption::Some(role_key));
+
+        // check explicit resume
+        assert_eq!(
+            Err(TokenError::InvalidState.into()),
+            do_process_instruction(
+                resume_minting(&program_id, &mint_key, &role_key, &[]).unwrap(),
+                vec![&mut mint_account, &mut role_account],
+            )
+        );
+
+        // mint authority is not the pauser
+        assert_eq!(
+            Err(TokenError::OwnerMismatch.into()),
+            do_process_instruction(
+                pause_minting(&program_id, &mint_key, &owner_key, &[]).unwrap(),
+                vec![&mut mint_account, &mut owner_account],
+            )
+        );
+
+        // pause
+        do_process_instruction(
+            pause_minting(&program_id, &mint_key, &role_key, &[]).unwrap(),
+            vec![&mut mint_account, &mut role_account],
+        )
+        .unwrap();
+        let mint = Mint::unpack(&mint_account.data).unwrap();
+        assert!(mint.minting_paused);
+        assert_eq!(
+            Err(TokenError::MintingPaused.into()),
+            do_process_instruction(
+                mint_to(&program_id, &mint_key, &account_key, &owner_key, &[], 1).unwrap(),
+                vec![&mut mint_account, &mut account_account, &mut owner_account],
+            )
+        );
+
+        // resume
+        do_process_instruction(
+            resume_minting(&program_id, &mint_key, &role_key, &[]).unwrap(),
+            vec![&mut mint_account, &mut role_account],
+        )
+        .unwrap();
+        do_process_instruction(
+            mint_to(&program_id, &mint_key, &account_key, &owner_key, &[], 1).unwrap(),
+            vec![&mut mint_account, &mut account_account, &mut owner_account],
+        )
+        .unwrap();
+
+        // freezer freezes and thaws
+        do_process_instruction(
+            freeze_account(&program_id, &account_key, &mint_key, &role_key, &[]).unwrap(),
+            vec![&mut account_account, &mut mint_account, &mut role_account],
+        )
+        .unwrap();
+        let account = Account::unpack_unchecked(&account_account.data).unwrap();
+        assert_eq!(account.state, AccountState::Frozen);
+        do_process_instruction(
+            thaw_account(&program_id, &account_key, &mint_key, &role_key, &[]).unwrap(),
+            vec![&mut account_account, &mut mint_account, &mut role_account],
+        )
+        .unwrap();
+        let account = Account::unpack_unchecked(&account_account.data).unwrap();
+        assert_eq!(account.state, AccountState::Initialized);
+
+        // burner can't burn from accounts it has no authority over
+        assert_eq!(
+            Err(TokenError::OwnerMismatch.into()),
+            do_process_instruction(
+                burn(&program_id, &account_key, &mint_key, &role_key, &[], 100).unwrap(),
+                vec![&mut account_account, &mut mint_account, &mut role_account],
+            )
+        );
+
+        // holder still burns
+        do_process_instruction(
+            burn(
+                &program_id,
+                &account_key,
+                &mint_key,
+                &account_owner_key,
+                &[],
+                100,
+            )
+            .unwrap(),
+            vec![
+                &mut account_account,
+                &mut mint_account,
+                &mut account_owner_account,
+            ],
+        )
+        .unwrap();
+
+        // burner burns as a delegate
+        do_process_instruction(
+            approve(
+                &program_id,
+                &account_key,
+                &role_key,
+                &account_owner_key,
+                &[],
+                100,
+            )
+            .unwrap(),
+            vec![
+                &mut account_account,
+                &mut role_account,
+                &mut account_owner_account,
+            ],
+        )
+        .unwrap();
+        do_process_instruction(
+            burn(&program_id, &account_key, &mint_key, &role_key, &[], 100).unwrap(),
+            vec![&mut account_account, &mut mint_account, &mut role_account],
+        )
+        .unwrap();
+        let account = Account::unpack(&account_account.data).unwrap();
+        assert_eq!(account.amount, 801);
+
+        // burner burns from the mint authority's accounts
+        do_process_instruction(
+            initialize_account(&program_id, &treasury_key, &mint_key, &owner_key).unwrap(),
+            vec![
+                &mut treasury_account,
+                &mut mint_account,
+                &mut owner_account,
+                &mut rent_sysvar,
+            ],
+        )
+        .unwrap();
+        do_process_instruction(
+            mint_to(&program_id, &mint_key, &treasury_key, &owner_key, &[], 100).unwrap(),
+            vec![&mut mint_account, &mut treasury_account, &mut owner_account],
+        )
+        .unwrap();
+        do_process_instruction(
+            burn(&program_id, &treasury_key, &mint_key, &role_key, &[], 60).unwrap(),
+            vec![&mut treasury_account, &mut mint_account, &mut role_account],
+        )
+        .unwrap();
+        let treasury = Account::unpack(&treasury_account.data).unwrap();
+        assert_eq!(treasury.amount, 40);
+
+        // clear burner
+        do_process_instruction(
+            set_mint_role(
+                &program_id,
+                &mint_key,
+                None,
+                MintRole::Burner,
+                &owner_key,
+                &[],
+            )
+            .unwrap(),
+            vec![&mut mint_account, &mut owner_account],
+        )
+        .unwrap();
+        assert_eq!(
+            Err(TokenError::OwnerMismatch.into()),
+            do_process_instruction(
+                burn(&program_id, &account_key, &mint_key, &role_key, &[], 100).unwrap(),
+                vec![&mut account_account, &mut mint_account, &mut role_account],
+            )
+        );
+        assert_eq!(
+            Err(TokenError::OwnerMismatch.into()),
+            do_process_instruction(
+                burn(&program_id, &treasury_key, &mint_key, &role_key, &[], 40).unwrap(),
+                vec![&mut treasury_account, &mut mint_account, &mut role_account],
+            )
+        );
+        do_process_instruction(
+            burn(
+                &program_id,
+                &account_key,
+                &mint_key,
+                &account_owner_key,
+                &[],
+                100,
+            )
+            .unwrap(),
+            vec![
+                &mut account_account,
+                &mut mint_account,
+                &mut account_owner_account,
+            ],
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn test_propose_accept_authority() {
+        let program_id = crate::id();
+        let account_key = Pubkey::new_unique();
+        let mut account_account = SolanaAccount::new(
+            extended_account_minimum_balance(),
+            Account::EXTENDED_LEN,
+            &program_id,
+        );
+        let owner_key = Pubkey::new_unique();
+        let mut owner_account = SolanaAccount::default();
+        let owner2_key = Pubkey::new_unique();
+        let mut owner2_account = SolanaAccount::default();
+        let owner3_key = Pubkey::new_unique();
+        let mut owner3_account = SolanaAccount::default();
+        let mint_key = Pubkey::new_unique();
+        let mut mint_account = SolanaAccount::new(
+            extended_mint_minimum_balance(),
+            Mint::EXTENDED_LEN,
+            &program_id,
+        );
+        let mut rent_sysvar = rent_sysvar();
+
+        // create new mint with owner
+        do_process_instruction(
+            initialize_mint(&program_id, &mint_key, &owner_key, None, 2).unwrap(),
+            vec![&mut mint_account, &mut rent_sysvar],
+        )
+        .unwrap();
+
+        // create account
+        do_process_instruction(
+            initialize_account(&program_id, &account_key, &mint_key, &owner_key).unwrap(),
+            vec![
+                &mut account_account,
+                &mut mint_account,
+                &mut owner_account,
+                &mut rent_sysvar,
+            ],
+        )
+        .unwrap();
+
+        // wrong owner
+        assert_eq!(
+            Err(TokenError::OwnerMismatch.into()),
+            do_process_instruction(
+                propose_authority(
+                    &program_id,
+                    &account_key,
+                    &owner2_key,
+                    AuthorityType::AccountOwner,
+                    &owner3_key,
+                    &[],
+                )
+                .unwrap(),
+                vec![&mut account_account, &mut owner3_account],
+            )
+        );
+
+        // nothing to accept
+        assert_eq!(
+            Err(TokenError::NoPendingAuthority.into()),
+            do_process_instruction(
+                accept_authority(
+                    &program_id,
+                    &account_key,
+                    AuthorityType::AccountOwner,
+                    &owner2_key,
+                    &[],
+                )
+                .unwrap(),
+                vec![&mut account_account, &mut owner2_account],
+            )
+        );
+
+        // propose new owner
+        do_process_instruction(
+            propose_authority(
+                &program_id,
+                &account_key,
+                &owner2_key,
+                AuthorityType::AccountOwner,
+                &owner_key,
+                &[],
+            )
+            .unwrap(),
+            vec![&mut account_account, &mut owner_account],
+        )
+        .unwrap();
+        let account = Account::unpack_unchecked(&account_account.data).unwrap();
+        assert_eq!(account.owner, owner_key);
+        assert_eq!(account.pending_owner, COption::Some(owner2_key));
+
+        // only the proposed owner may accept
+        assert_eq!(
+            Err(TokenError::OwnerMismatch.into()),
+            do_process_instruction(
+                accept_authority(
+                    &program_id,
+                    &account_key,
+                    AuthorityType::AccountOwner,
+                    &owner3_key,
+                    &[],
+                )
+                .unwrap(),
+                vec![&mut account_account, &mut owner3_account],
+            )
+        );
+
+        // accept new owner
+        do_process_instruction(
+            accept_authority(
+                &program_id,
+                &account_key,
+                AuthorityType::AccountOwner,
+                &owner2_key,
+                &[],
+            )
+            .unwrap(),
+            vec![&mut account_account, &mut owner2_account],
+        )
+        .unwrap();
+        let account = Account::unpack_unchecked(&account_account.data).unwrap();
+        assert_eq!(account.owner, owner2_key);
+        assert_eq!(account.pending_owner, COption::None);
+
+        // propose and cancel close authority
+        do_process_instruction(
+            propose_authority(
+                &program_id,
+                &account_key,
+                &owner3_key,
+                AuthorityType::CloseAccount,
+                &owner2_key,
+                &[],
+            )
+            .unwrap(),
+            vec![&mut account_account, &mut owner2_account],
+        )
+        .unwrap();
+        do_process_instruction(
+            cancel_authority_proposal(
+                &program_id,
+                &account_key,
+                AuthorityType::CloseAccount,
+                &owner2_key,
+                &[],
+            )
+            .unwrap(),
+            vec![&mut account_account, &mut owner2_account],
+        )
+        .unwrap();
+        let account = Account::unpack_unchecked(&account_account.data).unwrap();
+        assert_eq!(account.close_authority, COption::None);
+        assert_eq!(account.pending_close_authority, COption::None);
+
+        // nothing left to cancel
+        assert_eq!(
+            Err(TokenError::NoPendingAuthority.into()),
+            do_process_instruction(
+                cancel_authority_proposal(
+                    &program_id,
+                    &account_key,
+                    AuthorityType::CloseAccount,
+                    &owner2_key,
+                    &[],
+                )
+                .unwrap(),
+                vec![&mut account_account, &mut owner2_account],
+            )
+        );
+
+        // cancelled proposal can't be accepted
+        assert_eq!(
+            Err(TokenError::NoPendingAuthority.into()),
+            do_process_instruction(
+                accept_authority(
+                    &program_id,
+                    &account_key,
+                    AuthorityType::CloseAccount,
+                    &owner3_key,
+                    &[],
+                )
+                .unwrap(),
+                vec![&mut account_account, &mut owner3_account],
+            )
+        );
+
+        // mint doesn't support account authority types
+        assert_eq!(
+            Err(TokenError::AuthorityTypeNotSupported.into()),
+            do_process_instruction(
+                propose_authority(
+                    &program_id,
+                    &mint_key,
+                    &owner2_key,
+                    AuthorityType::AccountOwner,
+                    &owner_key,
+                    &[],
+                )
+                .unwrap(),
+                vec![&mut mint_account, &mut owner_account],
+            )
+        );
+
+        // propose and accept mint authority
+        do_process_instruction(
+            propose_authority(
+                &program_id,
+                &mint_key,
+                &owner2_key,
+                AuthorityType::MintTokens,
+                &owner_key,
+                &[],
+            )
+            .unwrap(),
+            vec![&mut mint_account, &mut owner_account],
+        )
+        .unwrap();
+        do_process_instruction(
+            accept_authority(
+                &program_id,
+                &mint_key,
+                AuthorityType::MintTokens,
+                &owner2_key,
+                &[],
+            )
+            .unwrap(),
+            vec![&mut mint_account, &mut owner2_account],
+        )
+        .unwrap();
+        let mint = Mint::unpack_unchecked(&mint_account.data).unwrap();
+        assert_eq!(mint.mint_authority, COption::Some(owner2_key));
+        assert_eq!(mint.pending_mint_authority, COption::None);
+
+        // no freeze authority to hand over
+        assert_eq!(
+            Err(TokenError::MintCannotFreeze.into()),
+            do_process_instruction(
+                propose_authority(
+                    &program_id,
+                    &mint_key,
+                    &owner3_key,
+                    AuthorityType::FreezeAccount,
+                    &owner2_key,
+                    &[],
+                )
+                .unwrap(),
+                vec![&mut mint_account, &mut owner2_account],
+            )
+        );
+
+        // set_authority discards a pending proposal
+        do_process_instruction(
+            propose_authority(
+                &program_id,
+                &mint_key,
+                &owner3_key,
+                AuthorityType::MintTokens,
+                &owner2_key,
+                &[],
+            )
+            .unwrap(),
+            vec![&mut mint_account, &mut owner2_account],
+        )
+        .unwrap();
+        do_process_instruction(
+            set_authority(
+                &program_id,
+                &mint_key,
+                Some(&owner_key),
+                AuthorityType::MintTokens,
+                &owner2_key,
+                &[],
+            )
+            .unwrap(),
+            vec![&mut mint_account, &mut owner2_account],
+        )
+        .unwrap();
+        let mint = Mint::unpack_unchecked(&mint_account.data).unwrap();
+        assert_eq!(mint.mint_authority, COption::Some(owner_key));
+        assert_eq!(mint.pending_mint_authority, COption::None);
+    }
+
+    #[test]
+    fn test_authority_timelock() {
+        let program_id = crate::id();
+        let owner_key = Pubkey::new_unique();
+        let mut owner_account = SolanaAccount::default();
+        let owner2_key = Pubkey::new_unique();
+        let mut owner2_account = SolanaAccount::default();
+        let freeze_key = Pubkey::new_unique();
+        let mut freeze_account = SolanaAccount::default();
+        let pauser_key = Pubkey::new_unique();
+        let mint_key = Pubkey::new_unique();
+        let mut mint_account = SolanaAccount::new(
+            extended_mint_minimum_balance(),
+            Mint::EXTENDED_LEN,
+            &program_id,
+        );
+        let mut rent_sysvar = rent_sysvar();
+
+        // create new mint with freeze authority
+        do_process_instruction(
+            initialize_mint(&program_id, &mint_key, &owner_key, Some(&freeze_key), 2).unwrap(),
+            vec![&mut mint_account, &mut rent_sysvar],
+        )
+        .unwrap();
+
+        // wrong authority
+        assert_eq!(
+            Err(TokenError::OwnerMismatch.into()),
+            do_process_instruction(
+                set_authority_timelock(&program_id, &mint_key, &owner2_key, &[], 100).unwrap(),
+                vec![&mut mint_account, &mut owner2_account],
+            )
+        );
+
+        // set timelock
+        do_process_instruction(
+            set_authority_timelock(&program_id, &mint_key, &owner_key, &[], 100).unwrap(),
+            vec![&mut mint_account, &mut owner_account],
+        )
+        .unwrap();
+
+        // timelock can't be decreased
+        assert_eq!(
+            Err(TokenError::InvalidAuthorityTimelock.into()),
+            do_process_instruction(
+                set_authority_timelock(&program_id, &mint_key, &owner_key, &[], 99).unwrap(),
+                vec![&mut mint_account, &mut owner_account],
+            )
+        );
+
+        // immediate changes are rejected
+        assert_eq!(
+            Err(TokenError::AuthorityChangeTimelocked.into()),
+            do_process_instruction(
+                set_authority(
+                    &program_id,
+                    &mint_key,
+                    Some(&owner2_key),
+                    AuthorityType::MintTokens,
+                    &owner_key,
+                    &[],
+                )
+                .unwrap(),
+                vec![&mut mint_account, &mut owner_account],
+            )
+        );
+        assert_eq!(
+            Err(TokenError::AuthorityChangeTimelocked.into()),
+            do_process_instruction(
+                set_mint_role(
+                    &program_id,
+                    &mint_key,
+                    Some(&pauser_key),
+                    MintRole::Pauser,
+                    &owner_key,
+                    &[],
+                )
+                .unwrap(),
+                vec![&mut mint_account, &mut owner_account],
+            )
+        );
+
+        // nothing queued
+        assert_eq!(
+            Err(TokenError::NoPendingAuthority.into()),
+            do_process_instruction(
+                execute_authority_change(&program_id, &mint_key).unwrap(),
+                vec![&mut mint_account, &mut clock_sysvar(1000)],
+            )
+        );
+
+        // freeze authority changes are queued by the freeze authority
+        assert_eq!(
+            Err(TokenError::OwnerMismatch.into()),
+            do_process_instruction(
+                queue_authority_change(
+                    &program_id,
+                    &mint_key,
+                    None,
+                    TimelockedAuthority::FreezeAccount,
+                    &owner_key,
+                    &[],
+                )
+                .unwrap(),
+                vec![
+                    &mut mint_account,
+                    &mut clock_sysvar(1000),
+                    &mut owner_account,
+                ],
+            )
+        );
+
+        // queue mint authority change
+        do_process_instruction(
+            queue_authority_change(
+                &program_id,
+                &mint_key,
+                Some(&owner2_key),
+                TimelockedAuthority::MintTokens,
+                &owner_key,
+                &[],
+            )
+            .unwrap(),
+            vec![
+                &mut mint_account,
+                &mut clock_sysvar(1000),
+                &mut owner_account,
+            ],
+        )
+        .unwrap();
+        let mint = Mint::unpack_unchecked(&mint_account.data).unwrap();
+        assert_eq!(mint.queued_authority, TimelockedAuthority::MintTokens);
+        assert_eq!(mint.queued_new_authority, COption::Some(owner2_key));
+        assert_eq!(mint.queued_unlock_timestamp, 1100);
+
+        // only one change at a time
+        assert_eq!(
+            Err(TokenError::AuthorityChangeQueued.into()),
+            do_process_instruction(
+                queue_authority_change(
+                    &program_id,
+                    &mint_key,
+                    None,
+                    TimelockedAuthority::FreezeAccount,
+                    &freeze_key,
+                    &[],
+                )
+                .unwrap(),
+                vec![
+                    &mut mint_account,
+                    &mut clock_sysvar(1000),
+                    &mut freeze_account,
+                ],
+            )
+        );
+
+        // too early
+        assert_eq!(
+            Err(TokenError::AuthorityChangeTimelocked.into()),
+            do_process_instruction(
+                execute_authority_change(&program_id, &mint_key).unwrap(),
+                vec![&mut mint_account, &mut clock_sysvar(1099)],
+            )
+        );
+
+        // execute
+        do_process_instruction(
+            execute_authority_change(&program_id, &mint_key).unwrap(),
+            vec![&mut mint_account, &mut clock_sysvar(1100)],
+        )
+        .unwrap();
+        let mint = Mint::unpack_unchecked(&mint_account.data).unwrap();
+        assert_eq!(mint.mint_authority, COption::Some(owner2_key));
+        assert_eq!(mint.queued_authority, TimelockedAuthority::None);
+        assert_eq!(mint.queued_new_authority, COption::None);
+
+        // queue role change
+        do_process_instruction(
+            queue_authority_change(
+                &program_id,
+                &mint_key,
+                Some(&pauser_key),
+                TimelockedAuthority::Pauser,
+                &owner2_key,
+                &[],
+            )
+            .unwrap(),
+            vec![
+                &mut mint_account,
+                &mut clock_sysvar(2000),
+                &mut owner2_account,
+            ],
+        )
+        .unwrap();
+
+        // only the current authority may cancel
+        assert_eq!(
+            Err(TokenError::OwnerMismatch.into()),
+            do_process_instruction(
+                cancel_authority_change(&program_id, &mint_key, &owner_key, &[]).unwrap(),
+                vec![&mut mint_account, &mut owner_account],
+            )
+        );
+
+        // cancel
+        do_process_instruction(
+            cancel_authority_change(&program_id, &mint_key, &owner2_key, &[]).unwrap(),
+            vec![&mut mint_account, &mut owner2_account],
+        )
+        .unwrap();
+        assert_eq!(
+            Err(TokenError::NoPendingAuthority.into()),
+            do_process_instruction(
+                execute_authority_change(&program_id, &mint_key).unwrap(),
+                vec![&mut mint_account, &mut clock_sysvar(3000)],
+            )
+        );
+        let mint = Mint::unpack_unchecked(&mint_account.data).unwrap();
+        assert_eq!(mint.pauser, COption::None);
+
+        // queue disabling the freeze authority
+        do_process_instruction(
+            queue_authority_change(
+                &program_id,
+                &mint_key,
+                None,
+                TimelockedAuthority::FreezeAccount,
+                &freeze_key,
+                &[],
+            )
+            .unwrap(),
+            vec![
+                &mut mint_account,
+                &mut clock_sysvar(3000),
+                &mut freeze_account,
+            ],
+        )
+        .unwrap();
+        do_process_instruction(
+            execute_authority_change(&program_id, &mint_key).unwrap(),
+            vec![&mut mint_account, &mut clock_sysvar(3100)],
+        )
+        .unwrap();
+        let mint = Mint::unpack_unchecked(&mint_account.data).unwrap();
+        assert_eq!(mint.freeze_authority, COption::None);
+    }
+
+    #[test]
+    fn test_guardian_recovery() {
+        let program_id = crate::id();
+        let account_key = Pubkey::new_unique();
+        let mut account_account = SolanaAccount::new(
+            extended_account_minimum_balance(),
+            Account::EXTENDED_LEN,
+            &program_id,
+        );
+        let owner_key = Pubkey::new_unique();
+        let mut owner_account = SolanaAccount::default();
+        let new_owner_key = Pubkey::new_unique();
+        let mut new_owner_account = SolanaAccount::default();
+        let mint_key = Pubkey::new_unique();
+        let mut mint_account = SolanaAccount::new(
+            extended_mint_minimum_balance(),
+            Mint::EXTENDED_LEN,
+            &program_id,
+        );
+        let guardians_key = Pubkey::new_unique();
+        let mut guardians_account = SolanaAccount::new(
+            multisig_minimum_balance(),
+            Multisig::get_packed_len(),
+            &program_id,
+        );
+        let guardian_keys: Vec<Pubkey> = (0..3).map(|_| Pubkey::new_unique()).collect();
+        let guardian_key_refs: Vec<&Pubkey> = guardian_keys.iter().collect();
+        let mut guardian_accounts = vec![SolanaAccount::new(0, 0, &program_id); 3];
+        let mut rent_sysvar = rent_sysvar();
+
+        // create 2 of 3 guardian set
+        let guardian_iter = &mut guardian_accounts.iter_mut();
+        do_process_instruction(
+            initialize_multisig(&program_id, &guardians_key, &guardian_key_refs, 2).unwrap(),
+            vec![
+                &mut guardians_account,
+                &mut rent_sysvar,
+                guardian_iter.next().unwrap(),
+                guardian_iter.next().unwrap(),
+                guardian_iter.next().unwrap(),
+            ],
+        )
+        .unwrap();
+
+        // create new mint
+        do_process_instruction(
+            initialize_mint(&program_id, &mint_key, &owner_key, None, 2).unwrap(),
+            vec![&mut mint_account, &mut rent_sysvar],
+        )
+        .unwrap();
+
+        // create account
+        do_process_instruction(
+            initialize_account(&program_id, &account_key, &mint_key, &owner_key).unwrap(),
+            vec![
+                &mut account_account,
+                &mut mint_account,
+                &mut owner_account,
+                &mut rent_sysvar,
+            ],
+        )
+        .unwrap();
+
+        // no guardians registered
+        let guardian_iter = &mut guardian_accounts.iter_mut();
+        assert_eq!(
+            Err(TokenError::NoGuardians.into()),
+            do_process_instruction(
+                initiate_recovery(
+                    &program_id,
+                    &account_key,
+                    &new_owner_key,
+                    &guardians_key,
+                    &[&guardian_keys[0], &guardian_keys[1]],
+                )
+                .unwrap(),
+                vec![
+                    &mut account_account,
+                    &mut clock_sysvar(1000),
+                    &mut guardians_account,
+                    guardian_iter.next().unwrap(),
+                    guardian_iter.next().unwrap(),
+                ],
+            )
+        );
+
+        // only the owner may register guardians
+        assert_eq!(
+            Err(TokenError::OwnerMismatch.into()),
+            do_process_instruction(
+                set_guardians(
+                    &program_id,
+                    &account_key,
+                    Some(&guardians_key),
+                    100,
+                    &new_owner_key,
+                    &[],
+                )
+                .unwrap(),
+                vec![&mut account_account, &mut new_owner_account],
+            )
+        );
+
+        // register guardians
+        do_process_instruction(
+            set_guardians(
+                &program_id,
+                &account_key,
+                Some(&guardians_key),
+                100,
+                &owner_key,
+                &[],
+            )
+            .unwrap(),
+            vec![&mut account_account, &mut owner_account],
+        )
+        .unwrap();
+
+        // guardian quorum not met
+        let guardian_iter = &mut guardian_accounts.iter_mut();
+        assert_eq!(
+            Err(ProgramError::MissingRequiredSignature),
+            do_process_instruction(
+                initiate_recovery(
+                    &program_id,
+                    &account_key,
+                    &new_owner_key,
+                    &guardians_key,
+                    &[&guardian_keys[0]],
+                )
+                .unwrap(),
+                vec![
+                    &mut account_account,
+                    &mut clock_sysvar(1000),
+                    &mut guardians_account,
+                    guardian_iter.next().unwrap(),
+                ],
+            )
+        );
+
+        // initiate recovery
+        let guardian_iter = &mut guardian_accounts.iter_mut();
+        do_process_instruction(
+            initiate_recovery(
+                &program_id,
+                &account_key,
+                &new_owner_key,
+                &guardians_key,
+                &[&guardian_keys[0], &guardian_keys[2]],
+            )
+            .unwrap(),
+            vec![
+                &mut account_account,
+                &mut clock_sysvar(1000),
+                &mut guardians_account,
+                guardian_iter.next().unwrap(),
+                guardian_iter.nth(1).unwrap(),
+            ],
+        )
+        .unwrap();
+        let account = Account::unpack_unchecked(&account_account.data).unwrap();
+        assert_eq!(account.recovery_owner, COption::Some(new_owner_key));
+        assert_eq!(account.recovery_unlock_timestamp, 1100);
+
+        // owner vetoes
+        assert_eq!(
+            Err(TokenError::OwnerMismatch.into()),
+            do_process_instruction(
+                veto_recovery(&program_id, &account_key, &new_owner_key, &[]).unwrap(),
+                vec![&mut account_account, &mut new_owner_account],
+            )
+        );
+        do_process_instruction(
+            veto_recovery(&program_id, &account_key, &owner_key, &[]).unwrap(),
+            vec![&mut account_account, &mut owner_account],
+        )
+        .unwrap();
+        assert_eq!(
+            Err(TokenError::NoPendingAuthority.into()),
+            do_process_instruction(
+                complete_recovery(&program_id, &account_key).unwrap(),
+                vec![&mut account_account, &mut clock_sysvar(2000)],
+            )
+        );
+
+        // initiate recovery again
+        let guardian_iter = &mut guardian_accounts.iter_mut();
+        do_process_instruction(
+            initiate_recovery(
+                &program_id,
+                &account_key,
+                &new_owner_key,
+                &guardians_key,
+                &[&guardian_keys[0], &guardian_keys[1]],
+            )
+            .unwrap(),
+            vec![
+                &mut account_account,
+                &mut clock_sysvar(2000),
+                &mut guardians_account,
+                guardian_iter.next().unwrap(),
+                guardian_iter.next().unwrap(),
+            ],
+        )
+        .unwrap();
+
+        // veto delay hasn't elapsed
+        assert_eq!(
+            Err(TokenError::AuthorityChangeTimelocked.into()),
+            do_process_instruction(
+                complete_recovery(&program_id, &account_key).unwrap(),
+                vec![&mut account_account, &mut clock_sysvar(2099)],
+            )
+        );
+
+        // complete recovery
+        do_process_instruction(
+            complete_recovery(&program_id, &account_key).unwrap(),
+            vec![&mut account_account, &mut clock_sysvar(2100)],
+        )
+        .unwrap();
+        let account = Account::unpack_unchecked(&account_account.data).unwrap();
+        assert_eq!(account.owner, new_owner_key);
+        assert_eq!(account.guardians, COption::Some(guardians_key));
+        assert_eq!(account.recovery_owner, COption::None);
+
+        // owner change drops the guardians
+        do_process_instruction(
+            set_authority(
+                &program_id,
+                &account_key,
+                Some(&owner_key),
+                AuthorityType::AccountOwner,
+                &new_owner_key,
+                &[],
+            )
+            .unwrap(),
+            vec![&mut account_account, &mut new_owner_account],
+        )
+        .unwrap();
+        let account = Account::unpack_unchecked(&account_account.data).unwrap();
+        assert_eq!(account.guardians, COption::None);
+    }
+
+    #[test]
+    fn test_delegate_limits() {
+        let program_id = crate::id();
+        let account_key = Pubkey::new_unique();
+        let mut account_account = SolanaAccount::new(
+            extended_account_minimum_balance(),
+            Account::EXTENDED_LEN,
+            &program_id,
+        );
+        let account2_key = Pubkey::new_unique();
+        let mut account2_account = SolanaAccount::new(
+            extended_account_minimum_balance(),
+            Account::EXTENDED_LEN,
+            &program_id,
+        );
+        let owner_key = Pubkey::new_unique();
+        let mut owner_account = SolanaAccount::default();
+        let delegate_key = Pubkey::new_unique();
+        let mut delegate_account = SolanaAccount::default();
+        let mint_key = Pubkey::new_unique();
+        let mut mint_account = SolanaAccount::new(
+            extended_mint_minimum_balance(),
+            Mint::EXTENDED_LEN,
+            &program_id,
+        );
+        let mut rent_sysvar = rent_sysvar();
+
+        // create new mint
+        do_process_instruction(
+            initialize_mint(&program_id, &mint_key, &owner_key, None, 2).unwrap(),
+            vec![&mut mint_account, &mut rent_sysvar],
+        )
+        .unwrap();
+
+        // create accounts
+        do_process_instruction(
+            initialize_account(&program_id, &account_key, &mint_key, &owner_key).unwrap(),
+            vec![
+                &mut account_account,
+                &mut mint_account,
+                &mut owner_account,
+                &mut rent_sysvar,
+            ],
+        )
+        .unwrap();
+        do_process_instruction(
+            initialize_account(&program_id, &account2_key, &mint_key, &owner_key).unwrap(),
+            vec![
+                &mut account2_account,
+                &mut mint_account,
+                &mut owner_account,
+                &mut rent_sysvar,
+            ],
+        )
+        .unwrap();
+
+        // mint to account
+        do_process_instruction(
+            mint_to(&program_id, &mint_key, &account_key, &owner_key, &[], 1000).unwrap(),
+            vec![&mut mint_account, &mut account_account, &mut owner_account],
+        )
+        .unwrap();
+
+        // approve delegate until 1000 with a cap of 100 per transfer
+        do_process_instruction(
+            approve_with_limits(
+                &program_id,
+                &account_key,
+                &delegate_key,
+                &owner_key,
+                &[],
+                500,
+                1000,
+                Some(100),
+            )
+            .unwrap(),
+            vec![
+                &mut account_account,
+                &mut delegate_account,
+                &mut owner_account,
+            ],
+        )
+        .unwrap();
+        let account = Account::unpack_unchecked(&account_account.data).unwrap();
+        assert_eq!(account.delegate, COption::Some(delegate_key));
+        assert_eq!(account.delegate_expiry, 1000);
+        assert_eq!(account.delegate_transfer_cap, COption::Some(100));
+
+        // without the clock account, expired by the runtime's clock
+        set_syscall_clock(1000);
+        assert_eq!(
+            Err(TokenError::DelegateExpired.into()),
+            do_process_instruction(
+                with_mint(
+                    transfer(
+                        &program_id,
+                        &account_key,
+                        &account2_key,
+                        &delegate_key,
+                        &[],
+                        100,
+                    )
+                    .unwrap(),
+                    &mint_key,
+                ),
+                vec![
+                    &mut account_account,
+                    &mut account2_account,
+                    &mut delegate_account,
+                    &mut mint_account,
+                ],
+            )
+        );
+
+        // over the per-transfer cap
+        let mut instruction = with_mint(
+            transfer(
+                &program_id,
+                &account_key,
+                &account2_key,
+                &delegate_key,
+                &[],
+                101,
+            )
+            .unwrap(),
+            &mint_key,
+        );
+        instruction
+            .accounts
+            .push(AccountMeta::new_readonly(sysvar::clock::id(), false));
+        assert_eq!(
+            Err(TokenError::DelegateTransferCapExceeded.into()),
+            do_process_instruction(
+                instruction,
+                vec![
+                    &mut account_account,
+                    &mut account2_account,
+                    &mut delegate_account,
+                    &mut mint_account,
+                    &mut clock_sysvar(999),
+                ],
+            )
+        );
+
+        // delegate transfer within limits
+        let mut instruction = with_mint(
+            transfer(
+                &program_id,
+                &account_key,
+                &account2_key,
+                &delegate_key,
+                &[],
+                100,
+            )
+            .unwrap(),
+            &mint_key,
+        );
+        instruction
+            .accounts
+            .push(AccountMeta::new_readonly(sysvar::clock::id(), false));
+        do_process_instruction(
+            instruction,
+            vec![
+                &mut account_account,
+                &mut account2_account,
+                &mut delegate_account,
+                &mut mint_account,
+                &mut clock_sysvar(999),
+            ],
+        )
+        .unwrap();
+
+        // delegate burn over the cap
+        let mut instruction = burn(
+            &program_id,
+            &account_key,
+            &mint_key,
+            &delegate_key,
+            &[],
+            200,
+        )
+        .unwrap();
+        instruction
+            .accounts
+            .push(AccountMeta::new_readonly(sysvar::clock::id(), false));
+        assert_eq!(
+            Err(TokenError::DelegateTransferCapExceeded.into()),
+            do_process_instruction(
+                instruction,
+                vec![
+                    &mut account_account,
+                    &mut mint_account,
+                    &mut delegate_account,
+                    &mut clock_sysvar(999),
+                ],
+            )
+        );
+
+        // expired
+        let mut instruction = with_mint(
+            transfer(
+                &program_id,
+                &account_key,
+                &account2_key,
+                &delegate_key,
+                &[],
+                100,
+            )
+            .unwrap(),
+            &mint_key,
+        );
+        instruction
+            .accounts
+            .push(AccountMeta::new_readonly(sysvar::clock::id(), false));
+        assert_eq!(
+            Err(TokenError::DelegateExpired.into()),
+            do_process_instruction(
+                instruction,
+                vec![
+                    &mut account_account,
+                    &mut account2_account,
+                    &mut delegate_account,
+                    &mut mint_account,
+                    &mut clock_sysvar(1000),
+                ],
+            )
+        );
+        let mut instruction =
+            burn(&program_id, &account_key, &mint_key, &delegate_key, &[], 10).unwrap();
+        instruction
+            .accounts
+            .push(AccountMeta::new_readonly(sysvar::clock::id(), false));
+        assert_eq!(
+            Err(TokenError::DelegateExpired.into()),
+            do_process_instruction(
+                instruction,
+                vec![
+                    &mut account_account,
+                    &mut mint_account,
+                    &mut delegate_account,
+                    &mut clock_sysvar(1000),
+                ],
+            )
+        );
+
+        // owner transfer before expiry by the runtime's clock leaves the delegation in place
+        set_syscall_clock(999);
+        do_process_instruction(
+            with_mint(
+                transfer(&program_id, &account_key, &account2_key, &owner_key, &[], 1).unwrap(),
+                &mint_key,
+            ),
+            vec![
+                &mut account_account,
+                &mut account2_account,
+                &mut owner_account,
+                &mut mint_account,
+            ],
+        )
+        .unwrap();
+        let account = Account::unpack_unchecked(&account_account.data).unwrap();
+        assert_eq!(account.delegate, COption::Some(delegate_key));
+
+        // owner transfer with the clock clears the expired delegation
+        let mut instruction = with_mint(
+            transfer(&program_id, &account_key, &account2_key, &owner_key, &[], 1).unwrap(),
+            &mint_key,
+        );
+        instruction
+            .accounts
+            .push(AccountMeta::new_readonly(sysvar::clock::id(), false));
+        do_process_instruction(
+            instruction,
+            vec![
+                &mut account_account,
+                &mut account2_account,
+                &mut owner_account,
+                &mut mint_account,
+                &mut clock_sysvar(1000),
+            ],
+        )
+        .unwrap();
+        let account = Account::unpack_unchecked(&account_account.data).unwrap();
+        assert_eq!(account.amount, 898);
+        assert_eq!(account.delegate, COption::None);
+        assert_eq!(account.delegated_amount, 0);
+        assert_eq!(account.delegate_expiry, 0);
+        assert_eq!(account.delegate_transfer_cap, COption::None);
+
+        // plain approve has no limits
+        do_process_instruction(
+            approve(
+                &program_id,
+                &account_key,
+                &delegate_key,
+                &owner_key,
+                &[],
+                300,
+            )
+            .unwrap(),
+            vec![
+                &mut account_account,
+                &mut delegate_account,
+                &mut owner_account,
+            ],
+        )
+        .unwrap();
+        do_process_instruction(
+            with_mint(
+                transfer(
+                    &program_id,
+                    &account_key,
+                    &account2_key,
+                    &delegate_key,
+                    &[],
+                    300,
+                )
+                .unwrap(),
+                &mint_key,
+            ),
+            vec![
+                &mut account_account,
+                &mut account2_account,
+                &mut delegate_account,
+                &mut mint_account,
+            ],
+        )
+        .unwrap();
+
+        // spending the whole delegation clears its limits
+        do_process_instruction(
+            approve_with_limits(
+                &program_id,
+                &account_key,
+                &delegate_key,
+                &owner_key,
+                &[],
+                100,
+                2000,
+                Some(100),
+            )
+            .unwrap(),
+            vec![
+                &mut account_account,
+                &mut delegate_account,
+                &mut owner_account,
+            ],
+        )
+        .unwrap();
+        let mut instruction = with_mint(
+            transfer(
+                &program_id,
+                &account_key,
+                &account2_key,
+                &delegate_key,
+                &[],
+                100,
+            )
+            .unwrap(),
+            &mint_key,
+        );
+        instruction
+            .accounts
+            .push(AccountMeta::new_readonly(sysvar::clock::id(), false));
+        do_process_instruction(
+            instruction,
+            vec![
+                &mut account_account,
+                &mut account2_account,
+                &mut delegate_account,
+                &mut mint_account,
+                &mut clock_sysvar(1500),
+            ],
+        )
+        .unwrap();
+        let account = Account::unpack_unchecked(&account_account.data).unwrap();
+        assert_eq!(account.delegate, COption::None);
+        assert_eq!(account.delegate_expiry, 0);
+        assert_eq!(account.delegate_transfer_cap, COption::None);
+    }
+
+    #[test]
+    fn test_allowances() {
+        let program_id = crate::id();
+        let account_key = Pubkey::new_unique();
+        let mut account_account = SolanaAccount::new(
+            extended_account_minimum_balance(),
+            Account::EXTENDED_LEN,
+            &program_id,
+        );
+        let account2_key = Pubkey::new_unique();
+        let mut account2_account = SolanaAccount::new(
+            extended_account_minimum_balance(),
+            Account::EXTENDED_LEN,
+            &program_id,
+        );
+        let owner_key = Pubkey::new_unique();
+        let mut owner_account = SolanaAccount::default();
+        let spender_key = Pubkey::new_unique();
+        let mut spender_account = SolanaAccount::default();
+        let spender2_key = Pubkey::new_unique();
+        let mut spender2_account = SolanaAccount::default();
+        let payer_key = Pubkey::new_unique();
+        let mut payer_account = SolanaAccount::default();
+        let mut system_program_account = SolanaAccount::default();
+        let mint_key = Pubkey::new_unique();
+        let mut mint_account = SolanaAccount::new(
+            extended_mint_minimum_balance(),
+            Mint::EXTENDED_LEN,
+            &program_id,
+        );
+        let mut allowance_account = SolanaAccount::new(
+            allowance_minimum_balance(),
+            Allowance::get_packed_len(),
+            &program_id,
+        );
+        let mut allowance2_account = SolanaAccount::new(
+            allowance_minimum_balance(),
+            Allowance::get_packed_len(),
+            &program_id,
+        );
+        let mut rent_sysvar = rent_sysvar();
+
+        // create new mint
+        do_process_instruction(
+            initialize_mint(&program_id, &mint_key, &owner_key, None, 2).unwrap(),
+            vec![&mut mint_account, &mut rent_sysvar],
+        )
+        .unwrap();
+
+        // create accounts
+        do_process_instruction(
+            initialize_account(&program_id, &account_key, &mint_key, &owner_key).unwrap(),
+            vec![
+                &mut account_account,
+                &mut mint_account,
+                &mut owner_account,
+                &mut rent_sysvar,
+            ],
+        )
+        .unwrap();
+        do_process_instruction(
+            initialize_account(&program_id, &account2_key, &mint_key, &owner_key).unwrap(),
+            vec![
+                &mut account2_account,
+                &mut mint_account,
+                &mut owner_account,
+                &mut rent_sysvar,
+            ],
+        )
+        .unwrap();
+
+        // mint to account
+        do_process_instruction(
+            mint_to(&program_id, &mint_key, &account_key, &owner_key, &[], 1000).unwrap(),
+            vec![&mut mint_account, &mut account_account, &mut owner_account],
+        )
+        .unwrap();
+
+        // wrong owner
+        assert_eq!(
+            Err(TokenError::OwnerMismatch.into()),
+            do_process_instruction(
+                set_allowance(
+                    &program_id,
+                    &account_key,
+                    &spender_key,
+                    &payer_key,
+                    &spender_key,
+                    &[],
+                    100,
+                )
+                .unwrap(),
+                vec![
+                    &mut allowance_account,
+                    &mut account_account,
+                    &mut spender_account,
+                    &mut payer_account,
+                    &mut system_program_account,
+                    &mut rent_sysvar,
+                    &mut SolanaAccount::default(),
+                ],
+            )
+        );
+
+        // allowance not at the derived address
+        let mut instruction = set_allowance(
+            &program_id,
+            &account_key,
+            &spender_key,
+            &payer_key,
+            &owner_key,
+            &[],
+            100,
+        )
+        .unwrap();
+        instruction.accounts[0].pubkey = Pubkey::new_unique();
+        assert_eq!(
+            Err(ProgramError::InvalidSeeds),
+            do_process_instruction(
+                instruction,
+                vec![
+                    &mut allowance_account,
+                    &mut account_account,
+                    &mut spender_account,
+                    &mut payer_account,
+                    &mut system_program_account,
+                    &mut rent_sysvar,
+                    &mut owner_account,
+                ],
+            )
+        );
+
+        // approve two spenders
+        do_process_instruction(
+            set_allowance(
+                &program_id,
+                &account_key,
+                &spender_key,
+                &payer_key,
+                &owner_key,
+                &[],
+                100,
+            )
+            .unwrap(),
+            vec![
+                &mut allowance_account,
+                &mut account_account,
+                &mut spender_account,
+                &mut payer_account,
+                &mut system_program_account,
+                &mut rent_sysvar,
+                &mut owner_account,
+            ],
+        )
+        .unwrap();
+        do_process_instruction(
+            set_allowance(
+                &program_id,
+                &account_key,
+                &spender2_key,
+                &payer_key,
+                &owner_key,
+                &[],
+                200,
+            )
+            .unwrap(),
+            vec![
+                &mut allowance2_account,
+                &mut account_account,
+                &mut spender2_account,
+                &mut payer_account,
+                &mut system_program_account,
+                &mut rent_sysvar,
+                &mut owner_account,
+            ],
+        )
+        .unwrap();
+        let allowance = Allowance::unpack(&allowance_account.data).unwrap();
+        assert_eq!(allowance.account, account_key);
+        assert_eq!(allowance.spender, spender_key);
+        assert_eq!(allowance.amount, 100);
+        assert_eq!(
+            crate::get_allowance_address(&account_key, &spender_key),
+            Pubkey::create_program_address(
+                &[
+                    crate::ALLOWANCE_SEED,
+                    account_key.as_ref(),
+                    spender_key.as_ref(),
+                    &[allowance.bump_seed],
+                ],
+                &program_id,
+            )
+            .unwrap()
+        );
+
+        // wrong spender
+        assert_eq!(
+            Err(TokenError::OwnerMismatch.into()),
+            do_process_instruction(
+                with_mint(
+                    transfer_with_allowance(
+                        &program_id,
+                        &account_key,
+                        &account2_key,
+                        &spender2_key,
+                        &[],
+                        60,
+                    )
+                    .unwrap(),
+                    &mint_key,
+                ),
+                vec![
+                    &mut account_account,
+                    &mut account2_account,
+                    &mut allowance_account,
+                    &mut spender2_account,
+                    &mut mint_account,
+                ],
+            )
+        );
+
+        // spend allowance
+        do_process_instruction(
+            with_mint(
+                transfer_with_allowance(
+                    &program_id,
+                    &account_key,
+                    &account2_key,
+                    &spender_key,
+                    &[],
+                    60,
+                )
+                .unwrap(),
+                &mint_key,
+            ),
+            vec![
+                &mut account_account,
+                &mut account2_account,
+                &mut allowance_account,
+                &mut spender_account,
+                &mut mint_account,
+            ],
+        )
+        .unwrap();
+        let allowance = Allowance::unpack(&allowance_account.data).unwrap();
+        assert_eq!(allowance.amount, 40);
+
+        // insufficient allowance
+        assert_eq!(
+            Err(TokenError::InsufficientFunds.into()),
+            do_process_instruction(
+                with_mint(
+                    transfer_with_allowance(
+                        &program_id,
+                        &account_key,
+                        &account2_key,
+                        &spender_key,
+                        &[],
+                        41,
+                    )
+                    .unwrap(),
+                    &mint_key,
+                ),
+                vec![
+                    &mut account_account,
+                    &mut account2_account,
+                    &mut allowance_account,
+                    &mut spender_account,
+                    &mut mint_account,
+                ],
+            )
+        );
+
+        // second spender is unaffected
+        do_process_instruction(
+            with_mint(
+                transfer_with_allowance(
+                    &program_id,
+                    &account_key,
+                    &account2_key,
+                    &spender2_key,
+                    &[],
+                    200,
+                )
+                .unwrap(),
+                &mint_key,
+            ),
+            vec![
+                &mut account_account,
+                &mut account2_account,
+                &mut allowance2_account,
+                &mut spender2_account,
+                &mut mint_account,
+            ],
+        )
+        .unwrap();
+
+        // allowance granted on another account
+        assert_eq!(
+            Err(TokenError::AllowanceMismatch.into()),
+            do_process_instruction(
+                with_mint(
+                    transfer_with_allowance(
+                        &program_id,
+                        &account2_key,
+                        &account_key,
+                        &spender_key,
+                        &[],
+                        10,
+                    )
+                    .unwrap(),
+                    &mint_key,
+                ),
+                vec![
+                    &mut account2_account,
+                    &mut account_account,
+                    &mut allowance_account,
+                    &mut spender_account,
+                    &mut mint_account,
+                ],
+            )
+        );
+
+        // burn against allowance
+        do_process_instruction(
+            burn_with_allowance(&program_id, &account_key, &mint_key, &spender_key, &[], 40)
+                .unwrap(),
+            vec![
+                &mut account_account,
+                &mut mint_account,
+                &mut allowance_account,
+                &mut spender_account,
+            ],
+        )
+        .unwrap();
+        let account = Account::unpack_unchecked(&account_account.data).unwrap();
+        assert_eq!(account.amount, 700);
+        assert_eq!(account.delegate, COption::None);
+        let allowance = Allowance::unpack(&allowance_account.data).unwrap();
+        assert_eq!(allowance.amount, 0);
+
+        // close allowance
+        let mut dest_account = SolanaAccount::default();
+        do_process_instruction(
+            close_allowance(
+                &program_id,
+                &account_key,
+                &spender_key,
+                &payer_key,
+                &owner_key,
+                &[],
+            )
+            .unwrap(),
+            vec![
+                &mut allowance_account,
+                &mut account_account,
+                &mut dest_account,
+                &mut owner_account,
+            ],
+        )
+        .unwrap();
+        assert_eq!(allowance_account.lamports, 0);
+        assert_eq!(dest_account.lamports, allowance_minimum_balance());
+        assert_eq!(
+            Err(ProgramError::UninitializedAccount),
+            Allowance::unpack(&allowance_account.data)
+        );
+    }
+
+    #[test]
+    fn test_prefunded_program_addresses() {
+        let program_id = crate::id();
+        let owner_key = Pubkey::new_unique();
+        let mut owner_account = SolanaAccount::default();
+        let payer_key = Pubkey::new_unique();
+        let mut payer_account = SolanaAccount::default();
+        let mut system_program_account = SolanaAccount::default();
+        let mint_key = Pubkey::new_unique();
+        let mut mint_account = SolanaAccount::new(
+            extended_mint_minimum_balance(),
+            Mint::EXTENDED_LEN,
+            &program_id,
+        );
+        let account_key = Pubkey::new_unique();
+        let mut account_account = SolanaAccount::new(
+            extended_account_minimum_balance(),
+            Account::EXTENDED_LEN,
+            &program_id,
+        );
+        let spender_key = Pubkey::new_unique();
+        let mut spender_account = SolanaAccount::default();
+        let spender2_key = Pubkey::new_unique();
+        let mut spender2_account = SolanaAccount::default();
+        let allowance_key = crate::get_allowance_address(&account_key, &spender_key);
+        let allowance2_key = crate::get_allowance_address(&account_key, &spender2_key);
+        let mut rent_sysvar = rent_sysvar();
+        do_process_instruction(
+            initialize_mint(&program_id, &mint_key, &owner_key, None, 2).unwrap(),
+            vec![&mut mint_account, &mut rent_sysvar],
+        )
+        .unwrap();
+        do_process_instruction(
+            initialize_account(&program_id, &account_key, &mint_key, &owner_key).unwrap(),
+            vec![
+                &mut account_account,
+                &mut mint_account,
+                &mut owner_account,
+                &mut rent_sysvar,
+            ],
+        )
+        .unwrap();
+
+        // the system program doesn't run under the stubs, so the accounts stay unassigned and
+        // only the invoked instructions are checked
+
+        // an unfunded allowance is created outright
+        take_invoked_instructions();
+        let mut allowance_account = SolanaAccount::default();
+        assert_eq!(
+            Err(ProgramError::IncorrectProgramId),
+            do_process_instruction(
+                set_allowance(
+                    &program_id,
+                    &account_key,
+                    &spender_key,
+                    &payer_key,
+                    &owner_key,
+                    &[],
+                    100,
+                )
+                .unwrap(),
+                vec![
+                    &mut allowance_account,
+                    &mut account_account,
+                    &mut spender_account,
+                    &mut payer_account,
+                    &mut system_program_account,
+                    &mut rent_sysvar,
+                    &mut owner_account,
+                ],
+            )
+        );
+        assert_eq!(
+            take_invoked_instructions(),
+            vec![system_instruction::create_account(
+                &payer_key,
+                &allowance_key,
+                allowance_minimum_balance(),
+                Allowance::LEN as u64,
+                &program_id,
+            )]
+        );
+
+        // a prefunded allowance is topped up, allocated and assigned
+        let mut allowance2_account = SolanaAccount::new(1, 0, &system_program::id());
+        assert_eq!(
+            Err(ProgramError::IncorrectProgramId),
+            do_process_instruction(
+                set_allowance(
+                    &program_id,
+                    &account_key,
+                    &spender2_key,
+                    &payer_key,
+                    &owner_key,
+                    &[],
+                    100,
+                )
+                .unwrap(),
+                vec![
+                    &mut allowance2_account,
+                    &mut account_account,
+                    &mut spender2_account,
+                    &mut payer_account,
+                    &mut system_program_account,
+                    &mut rent_sysvar,
+                    &mut owner_account,
+                ],
+            )
+        );
+        assert_eq!(
+            take_invoked_instructions(),
+            vec![
+                system_instruction::transfer(
+                    &payer_key,
+                    &allowance2_key,
+                    allowance_minimum_balance() - 1
+                ),
+                system_instruction::allocate(&allowance2_key, Allowance::LEN as u64),
+                system_instruction::assign(&allowance2_key, &program_id),
+            ]
+        );
+
+        // as is a prefunded nonce
+        let nonce_key = crate::get_nonce_address(&owner_key);
+        let mut nonce_account = SolanaAccount::new(1, 0, &system_program::id());
+        assert_eq!(
+            Err(ProgramError::IncorrectProgramId),
+            do_process_instruction(
+                initialize_nonce(&program_id, &owner_key, &payer_key).unwrap(),
+                vec![
+                    &mut nonce_account,
+                    &mut owner_account,
+                    &mut payer_account,
+                    &mut system_program_account,
+                    &mut rent_sysvar,
+                ],
+            )
+        );
+        assert_eq!(
+            take_invoked_instructions(),
+            vec![
+                system_instruction::transfer(&payer_key, &nonce_key, nonce_minimum_balance() - 1),
+                system_instruction::allocate(&nonce_key, AuthorizationNonce::LEN as u64),
+                system_instruction::assign(&nonce_key, &program_id),
+            ]
+        );
+
+        // as is a prefunded Ethereum owner
+        let address = [7; 20];
+        let ethereum_owner_key = crate::get_ethereum_owner_address(&address);
+        let mut ethereum_owner_account = SolanaAccount::new(1, 0, &system_program::id());
+        assert_eq!(
+            Err(ProgramError::IncorrectProgramId),
+            do_process_instruction(
+                initialize_ethereum_owner(&program_id, &address, &payer_key).unwrap(),
+                vec![
+                    &mut ethereum_owner_account,
+                    &mut payer_account,
+                    &mut system_program_account,
+                    &mut rent_sysvar,
+                ],
+            )
+        );
+        assert_eq!(
+            take_invoked_instructions(),
+            vec![
+                system_instruction::transfer(
+                    &payer_key,
+                    &ethereum_owner_key,
+                    Rent::default().minimum_balance(EthereumOwner::LEN) - 1
+                ),
+                system_instruction::allocate(&ethereum_owner_key, EthereumOwner::LEN as u64),
+                system_instruction::assign(&ethereum_owner_key, &program_id),
+            ]
+        );
+
+        // as is a prefunded credential
+        let holder_key = Pubkey::new_unique();
+        let mut holder_account = SolanaAccount::default();
+        let credential_key = crate::get_credential_address(&mint_key, &holder_key);
+        let mut credential_account = SolanaAccount::new(1, 0, &system_program::id());
+        do_process_instruction(
+            set_mint_role(
+                &program_id,
+                &mint_key,
+                Some(&owner_key),
+                MintRole::ComplianceAuthority,
+                &owner_key,
+                &[],
+            )
+            .unwrap(),
+            vec![&mut mint_account, &mut owner_account],
+        )
+        .unwrap();
+        assert_eq!(
+            Err(ProgramError::IncorrectProgramId),
+            do_process_instruction(
+                issue_credential(
+                    &program_id,
+                    &mint_key,
+                    &holder_key,
+                    &payer_key,
+                    &owner_key,
+                    &[],
+                    2000,
+                    840,
+                )
+                .unwrap(),
+                vec![
+                    &mut credential_account,
+                    &mut mint_account,
+                    &mut holder_account,
+                    &mut payer_account,
+                    &mut system_program_account,
+                    &mut rent_sysvar,
+                    &mut owner_account,
+                ],
+            )
+        );
+        assert_eq!(
+            take_invoked_instructions(),
+            vec![
+                system_instruction::transfer(
+                    &payer_key,
+                    &credential_key,
+                    Rent::default().minimum_balance(Credential::LEN) - 1
+                ),
+                system_instruction::allocate(&credential_key, Credential::LEN as u64),
+                system_instruction::assign(&credential_key, &program_id),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_allowance_owner_change() {
+        let program_id = crate::id();
+        let account_key = Pubkey::new_unique();
+        let mut account_account = SolanaAccount::new(
+            extended_account_minimum_balance(),
+            Account::EXTENDED_LEN,
+            &program_id,
+        );
+        let account2_key = Pubkey::new_unique();
+        let mut account2_account = SolanaAccount::new(
+            extended_account_minimum_balance(),
+            Account::EXTENDED_LEN,
+            &program_id,
+        );
+        let owner_key = Pubkey::new_unique();
+        let mut owner_account = SolanaAccount::default();
+        let owner2_key = Pubkey::new_unique();
+        let mut owner2_account = SolanaAccount::default();
+        let owner3_key = Pubkey::new_unique();
+        let mut owner3_account = SolanaAccount::default();
+        let spender_key = Pubkey::new_unique();
+        let mut spender_account = SolanaAccount::default();
+        let payer_key = Pubkey::new_unique();
+        let mut payer_account = SolanaAccount::default();
+        let mut system_program_account = SolanaAccount::default();
+        let mint_key = Pubkey::new_unique();
+        let mut mint_account = SolanaAccount::new(
+            extended_mint_minimum_balance(),
+            Mint::EXTENDED_LEN,
+            &program_id,
+        );
+        let mut allowance_account = SolanaAccount::new(
+            allowance_minimum_balance(),
+            Allowance::get_packed_len(),
+            &program_id,
+        );
+        let guardians_key = Pubkey::new_unique();
+        let mut guardians_account = SolanaAccount::new(
+            multisig_minimum_balance(),
+            Multisig::get_packed_len(),
+            &program_id,
+        );
+        let guardian_key = Pubkey::new_unique();
+        let mut guardian_account = SolanaAccount::default();
+        let mut rent_sysvar = rent_sysvar();
+
+        // create 1 of 1 guardian set
+        do_process_instruction(
+            initialize_multisig(&program_id, &guardians_key, &[&guardian_key], 1).unwrap(),
+            vec![
+                &mut guardians_account,
+                &mut rent_sysvar,
+                &mut guardian_account,
+            ],
+        )
+        .unwrap();
+
+        // create new mint
+        do_process_instruction(
+            initialize_mint(&program_id, &mint_key, &owner_key, None, 2).unwrap(),
+            vec![&mut mint_account, &mut rent_sysvar],
+        )
+        .unwrap();
+
+        // create accounts
+        do_process_instruction(
+            initialize_account(&program_id, &account_key, &mint_key, &owner_key).unwrap(),
+            vec![
+                &mut account_account,
+                &mut mint_account,
+                &mut owner_account,
+                &mut rent_sysvar,
+            ],
+        )
+        .unwrap();
+        do_process_instruction(
+            initialize_account(&program_id, &account2_key, &mint_key, &owner_key).unwrap(),
+            vec![
+                &mut account2_account,
+                &mut mint_account,
+                &mut owner_account,
+                &mut rent_sysvar,
+            ],
+        )
+        .unwrap();
+
+        // mint to account
+        do_process_instruction(
+            mint_to(&program_id, &mint_key, &account_key, &owner_key, &[], 1000).unwrap(),
+            vec![&mut mint_account, &mut account_account, &mut owner_account],
+        )
+        .unwrap();
+
+        // owner grants an allowance
+        do_process_instruction(
+            set_allowance(
+                &program_id,
+                &account_key,
+                &spender_key,
+                &payer_key,
+                &owner_key,
+                &[],
+                100,
+            )
+            .unwrap(),
+            vec![
+                &mut allowance_account,
+                &mut account_account,
+                &mut spender_account,
+                &mut payer_account,
+                &mut system_program_account,
+                &mut rent_sysvar,
+                &mut owner_account,
+            ],
+        )
+        .unwrap();
+        let allowance = Allowance::unpack(&allowance_account.data).unwrap();
+        assert_eq!(allowance.owner, owner_key);
+
+        // spend part of it
+        do_process_instruction(
+            with_mint(
+                transfer_with_allowance(
+                    &program_id,
+                    &account_key,
+                    &account2_key,
+                    &spender_key,
+                    &[],
+                    10,
+                )
+                .unwrap(),
+                &mint_key,
+            ),
+            vec![
+                &mut account_account,
+                &mut account2_account,
+                &mut allowance_account,
+                &mut spender_account,
+                &mut mint_account,
+            ],
+        )
+        .unwrap();
+
+        // owner hands the account over
+        do_process_instruction(
+            set_authority(
+                &program_id,
+                &account_key,
+                Some(&owner2_key),
+                AuthorityType::AccountOwner,
+                &owner_key,
+                &[],
+            )
+            .unwrap(),
+            vec![&mut account_account, &mut owner_account],
+        )
+        .unwrap();
+
+        // allowance lapses with the previous owner
+        assert_eq!(
+            Err(TokenError::AllowanceOwnerChanged.into()),
+            do_process_instruction(
+                with_mint(
+                    transfer_with_allowance(
+                        &program_id,
+                        &account_key,
+                        &account2_key,
+                        &spender_key,
+                        &[],
+                        10,
+                    )
+                    .unwrap(),
+                    &mint_key,
+                ),
+                vec![
+                    &mut account_account,
+                    &mut account2_account,
+                    &mut allowance_account,
+                    &mut spender_account,
+                    &mut mint_account,
+                ],
+            )
+        );
+
+        // new owner grants it again
+        do_process_instruction(
+            set_allowance(
+                &program_id,
+                &account_key,
+                &spender_key,
+                &payer_key,
+                &owner2_key,
+                &[],
+                100,
+            )
+            .unwrap(),
+            vec![
+                &mut allowance_account,
+                &mut account_account,
+                &mut spender_account,
+                &mut payer_account,
+                &mut system_program_account,
+                &mut rent_sysvar,
+                &mut owner2_account,
+            ],
+        )
+        .unwrap();
+        do_process_instruction(
+            with_mint(
+                transfer_with_allowance(
+                    &program_id,
+                    &account_key,
+                    &account2_key,
+                    &spender_key,
+                    &[],
+                    10,
+                )
+                .unwrap(),
+                &mint_key,
+            ),
+            vec![
+                &mut account_account,
+                &mut account2_account,
+                &mut allowance_account,
+                &mut spender_account,
+                &mut mint_account,
+            ],
+        )
+        .unwrap();
+
+        // ownership handed over through propose and accept
+        do_process_instruction(
+            propose_authority(
+                &program_id,
+                &account_key,
+                &owner3_key,
+                AuthorityType::AccountOwner,
+                &owner2_key,
+                &[],
+            )
+            .unwrap(),
+            vec![&mut account_account, &mut owner2_account],
+        )
+        .unwrap();
+        do_process_instruction(
+            accept_authority(
+                &program_id,
+                &account_key,
+                AuthorityType::AccountOwner,
+                &owner3_key,
+                &[],
+            )
+            .unwrap(),
+            vec![&mut account_account, &mut owner3_account],
+        )
+        .unwrap();
+        assert_eq!(
+            Err(TokenError::AllowanceOwnerChanged.into()),
+            do_process_instruction(
+                with_mint(
+                    transfer_with_allowance(
+                        &program_id,
+                        &account_key,
+                        &account2_key,
+                        &spender_key,
+                        &[],
+                        10,
+                    )
+                    .unwrap(),
+                    &mint_key,
+                ),
+                vec![
+                    &mut account_account,
+                    &mut account2_account,
+                    &mut allowance_account,
+                    &mut spender_account,
+                    &mut mint_account,
+                ],
+            )
+        );
+
+        // new owner grants it again and registers guardians
+        do_process_instruction(
+            set_allowance(
+                &program_id,
+                &account_key,
+                &spender_key,
+                &payer_key,
+                &owner3_key,
+                &[],
+                100,
+            )
+            .unwrap(),
+            vec![
+                &mut allowance_account,
+                &mut account_account,
+                &mut spender_account,
+                &mut payer_account,
+                &mut system_program_account,
+                &mut rent_sysvar,
+                &mut owner3_account,
+            ],
+        )
+        .unwrap();
+        do_process_instruction(
+            set_guardians(
+                &program_id,
+                &account_key,
+                Some(&guardians_key),
+                100,
+                &owner3_key,
+                &[],
+            )
+            .unwrap(),
+            vec![&mut account_account, &mut owner3_account],
+        )
+        .unwrap();
+
+        // guardians recover the account
+        do_process_instruction(
+            initiate_recovery(
+                &program_id,
+                &account_key,
+                &owner_key,
+                &guardians_key,
+                &[&guardian_key],
+            )
+            .unwrap(),
+            vec![
+                &mut account_account,
+                &mut clock_sysvar(1000),
+                &mut guardians_account,
+                &mut guardian_account,
+            ],
+        )
+        .unwrap();
+        do_process_instruction(
+            complete_recovery(&program_id, &account_key).unwrap(),
+            vec![&mut account_account, &mut clock_sysvar(1100)],
+        )
+        .unwrap();
+        let account = Account::unpack(&account_account.data).unwrap();
+        assert_eq!(account.owner, owner_key);
+        assert_eq!(
+            Err(TokenError::AllowanceOwnerChanged.into()),
+            do_process_instruction(
+                with_mint(
+                    transfer_with_allowance(
+                        &program_id,
+                        &account_key,
+                        &account2_key,
+                        &spender_key,
+                        &[],
+                        10,
+                    )
+                    .unwrap(),
+                    &mint_key,
+                ),
+                vec![
+                    &mut account_account,
+                    &mut account2_account,
+                    &mut allowance_account,
+                    &mut spender_account,
+                    &mut mint_account,
+                ],
+            )
+        );
+    }
+
+    #[test]
+    fn test_approve_if_current_and_adjust_allowance() {
+        let program_id = crate::id();
+        let account_key = Pubkey::new_unique();
+        let mut account_account = SolanaAccount::new(
+            extended_account_minimum_balance(),
+            Account::EXTENDED_LEN,
+            &program_id,
+        );
+        let owner_key = Pubkey::new_unique();
+        let mut owner_account = SolanaAccount::default();
+        let delegate_key = Pubkey::new_unique();
+        let mut delegate_account = SolanaAccount::default();
+        let delegate2_key = Pubkey::new_unique();
+        let mut delegate2_account = SolanaAccount::default();
+        let mint_key = Pubkey::new_unique();
+        let mut mint_account = SolanaAccount::new(
+            extended_mint_minimum_balance(),
+            Mint::EXTENDED_LEN,
+            &program_id,
+        );
+        let mut rent_sysvar = rent_sysvar();
+
+        // create new mint
+        do_process_instruction(
+            initialize_mint(&program_id, &mint_key, &owner_key, None, 2).unwrap(),
+            vec![&mut mint_account, &mut rent_sysvar],
+        )
+        .unwrap();
+
+        // create account
+        do_process_instruction(
+            initialize_account(&program_id, &account_key, &mint_key, &owner_key).unwrap(),
+            vec![
+                &mut account_account,
+                &mut mint_account,
+                &mut owner_account,
+                &mut rent_sysvar,
+            ],
+        )
+        .unwrap();
+
+        // expected delegation does not match
+        assert_eq!(
+            Err(TokenError::UnexpectedDelegation.into()),
+            do_process_instruction(
+                approve_if_current(
+                    &program_id,
+                    &account_key,
+                    &delegate_key,
+                    &owner_key,
+                    &[],
+                    Some(&delegate2_key),
+                    0,
+                    100,
+                )
+                .unwrap(),
+                vec![
+                    &mut account_account,
+                    &mut delegate_account,
+                    &mut owner_account,
+                ],
+            )
+        );
+
+        // approve from no delegation
+        do_process_instruction(
+            approve_if_current(
+                &program_id,
+                &account_key,
+                &delegate_key,
+                &owner_key,
+                &[],
+                None,
+                0,
+                100,
+            )
+            .unwrap(),
+            vec![
+                &mut account_account,
+                &mut delegate_account,
+                &mut owner_account,
+            ],
+        )
+        .unwrap();
+
+        // stale expected amount
+        assert_eq!(
+            Err(TokenError::UnexpectedDelegation.into()),
+            do_process_instruction(
+                approve_if_current(
+                    &program_id,
+                    &account_key,
+                    &delegate2_key,
+                    &owner_key,
+                    &[],
+                    Some(&delegate_key),
+                    50,
+                    10,
+                )
+                .unwrap(),
+                vec![
+                    &mut account_account,
+                    &mut delegate2_account,
+                    &mut owner_account,
+                ],
+            )
+        );
+
+        // increase
+        do_process_instruction(
+            increase_allowance(
+                &program_id,
+                &account_key,
+                &delegate_key,
+                &owner_key,
+                &[],
+                50,
+            )
+            .unwrap(),
+            vec![
+                &mut account_account,
+                &mut delegate_account,
+                &mut owner_account,
+            ],
+        )
+        .unwrap();
+        let account = Account::unpack_unchecked(&account_account.data).unwrap();
+        assert_eq!(account.delegate, COption::Some(delegate_key));
+        assert_eq!(account.delegated_amount, 150);
+
+        // increase overflows
+        assert_eq!(
+            Err(TokenError::Overflow.into()),
+            do_process_instruction(
+                increase_allowance(
+                    &program_id,
+                    &account_key,
+                    &delegate_key,
+                    &owner_key,
+                    &[],
+                    u64::MAX
+                )
+                .unwrap(),
+                vec![
+                    &mut account_account,
+                    &mut delegate_account,
+                    &mut owner_account,
+                ],
+            )
+        );
+
+        // adjust for a different delegate
+        assert_eq!(
+            Err(TokenError::UnexpectedDelegation.into()),
+            do_process_instruction(
+                increase_allowance(
+                    &program_id,
+                    &account_key,
+                    &delegate2_key,
+                    &owner_key,
+                    &[],
+                    1
+                )
+                .unwrap(),
+                vec![
+                    &mut account_account,
+                    &mut delegate2_account,
+                    &mut owner_account,
+                ],
+            )
+        );
+
+        // decrease underflows
+        assert_eq!(
+            Err(TokenError::Overflow.into()),
+            do_process_instruction(
+                decrease_allowance(
+                    &program_id,
+                    &account_key,
+                    &delegate_key,
+                    &owner_key,
+                    &[],
+                    151
+                )
+                .unwrap(),
+                vec![
+                    &mut account_account,
+                    &mut delegate_account,
+                    &mut owner_account,
+                ],
+            )
+        );
+
+        // decrease
+        do_process_instruction(
+            decrease_allowance(
+                &program_id,
+                &account_key,
+                &delegate_key,
+                &owner_key,
+                &[],
+                100,
+            )
+            .unwrap(),
+            vec![
+                &mut account_account,
+                &mut delegate_account,
+                &mut owner_account,
+            ],
+        )
+        .unwrap();
+        let account = Account::unpack_unchecked(&account_account.data).unwrap();
+        assert_eq!(account.delegated_amount, 50);
+
+        // decrease to zero removes the delegate
+        do_process_instruction(
+            decrease_allowance(
+                &program_id,
+                &account_key,
+                &delegate_key,
+                &owner_key,
+                &[],
+                50,
+            )
+            .unwrap(),
+            vec![
+                &mut account_account,
+                &mut delegate_account,
+                &mut owner_account,
+            ],
+        )
+        .unwrap();
+        let account = Account::unpack_unchecked(&account_account.data).unwrap();
+        assert_eq!(account.delegate, COption::None);
+        assert_eq!(account.delegated_amount, 0);
+
+        // nothing left to decrease
+        assert_eq!(
+            Err(TokenError::UnexpectedDelegation.into()),
+            do_process_instruction(
+                decrease_allowance(&program_id, &account_key, &delegate_key, &owner_key, &[], 1)
+                    .unwrap(),
+                vec![
+                    &mut account_account,
+                    &mut delegate_account,
+                    &mut owner_account,
+                ],
+            )
+        );
+
+        // increase with no delegate approves
+        do_process_instruction(
+            increase_allowance(
+                &program_id,
+                &account_key,
+                &delegate2_key,
+                &owner_key,
+                &[],
+                7,
+            )
+            .unwrap(),
+            vec![
+                &mut account_account,
+                &mut delegate2_account,
+                &mut owner_account,
+            ],
+        )
+        .unwrap();
+        let account = Account::unpack_unchecked(&account_account.data).unwrap();
+        assert_eq!(account.delegate, COption::Some(delegate2_key));
+        assert_eq!(account.delegated_amount, 7);
+    }
+
+    #[test]
+    fn test_signed_authorizations() {
+        let program_id = crate::id();
+        let account_key = Pubkey::new_unique();
+        let mut account_account = SolanaAccount::new(
+            extended_account_minimum_balance(),
+            Account::EXTENDED_LEN,
+            &program_id,
+        );
+        let account2_key = Pubkey::new_unique();
+        let mut account2_account = SolanaAccount::new(
+            extended_account_minimum_balance(),
+            Account::EXTENDED_LEN,
+            &program_id,
+        );
+        let owner_key = Pubkey::new_unique();
+        let mut owner_account = SolanaAccount::default();
+        let delegate_key = Pubkey::new_unique();
+        let mut delegate_account = SolanaAccount::default();
+        let payer_key = Pubkey::new_unique();
+        let mut payer_account = SolanaAccount::default();
+        let mut system_program_account = SolanaAccount::default();
+        let mint_key = Pubkey::new_unique();
+        let mut mint_account = SolanaAccount::new(
+            extended_mint_minimum_balance(),
+            Mint::EXTENDED_LEN,
+            &program_id,
+        );
+        let mut nonce_account = SolanaAccount::new(
+            nonce_minimum_balance(),
+            AuthorizationNonce::get_packed_len(),
+            &program_id,
+        );
+        let mut rent_sysvar = rent_sysvar();
+        let mut clock_sysvar = clock_sysvar(100);
+
+        // create new mint
+        do_process_instruction(
+            initialize_mint(&program_id, &mint_key, &owner_key, None, 2).unwrap(),
+            vec![&mut mint_account, &mut rent_sysvar],
+        )
+        .unwrap();
+
+        // create accounts
+        do_process_instruction(
+            initialize_account(&program_id, &account_key, &mint_key, &owner_key).unwrap(),
+            vec![
+                &mut account_account,
+                &mut mint_account,
+                &mut owner_account,
+                &mut rent_sysvar,
+            ],
+        )
+        .unwrap();
+        do_process_instruction(
+            initialize_account(&program_id, &account2_key, &mint_key, &owner_key).unwrap(),
+            vec![
+                &mut account2_account,
+                &mut mint_account,
+                &mut owner_account,
+                &mut rent_sysvar,
+            ],
+        )
+        .unwrap();
+
+        // mint to account
+        do_process_instruction(
+            mint_to(&program_id, &mint_key, &account_key, &owner_key, &[], 1000).unwrap(),
+            vec![&mut mint_account, &mut account_account, &mut owner_account],
+        )
+        .unwrap();
+
+        // nonce not at the derived address
+        let mut instruction = initialize_nonce(&program_id, &owner_key, &payer_key).unwrap();
+        instruction.accounts[0].pubkey = Pubkey::new_unique();
+        assert_eq!(
+            Err(ProgramError::InvalidSeeds),
+            do_process_instruction(
+                instruction,
+                vec![
+                    &mut nonce_account,
+                    &mut owner_account,
+                    &mut payer_account,
+                    &mut system_program_account,
+                    &mut rent_sysvar,
+                ],
+            )
+        );
+
+        // create nonce
+        do_process_instruction(
+            initialize_nonce(&program_id, &owner_key, &payer_key).unwrap(),
+            vec![
+                &mut nonce_account,
+                &mut owner_account,
+                &mut payer_account,
+                &mut system_program_account,
+                &mut rent_sysvar,
+            ],
+        )
+        .unwrap();
+        let nonce = AuthorizationNonce::unpack(&nonce_account.data).unwrap();
+        assert_eq!(nonce.owner, owner_key);
+        assert_eq!(nonce.nonce, 0);
+
+        // nonce already initialized
+        assert_eq!(
+            Err(TokenError::AlreadyInUse.into()),
+            do_process_instruction(
+                initialize_nonce(&program_id, &owner_key, &payer_key).unwrap(),
+                vec![
+                    &mut nonce_account,
+                    &mut owner_account,
+                    &mut payer_account,
+                    &mut system_program_account,
+                    &mut rent_sysvar,
+                ],
+            )
+        );
+
+        let permit_instruction = |nonce, valid_after, valid_until| {
+            permit(
+                &program_id,
+                &account_key,
+                &delegate_key,
+                &owner_key,
+                100,
+                nonce,
+                valid_after,
+                valid_until,
+            )
+            .unwrap()
+        };
+        let permit_message = |nonce, valid_after, valid_until| {
+            authorization_message(
+                &program_id,
+                &TokenInstruction::Permit {
+                    amount: 100,
+                    nonce,
+                    valid_after,
+                    valid_until,
+                },
+                &account_key,
+                &delegate_key,
+            )
+        };
+
+        // no ed25519 instruction
+        let instruction = permit_instruction(0, 0, 200);
+        let mut instructions_account = instructions_sysvar(std::slice::from_ref(&instruction), 0);
+        assert_eq!(
+            Err(TokenError::MissingAuthorizationSignature.into()),
+            do_process_instruction(
+                instruction,
+                vec![
+                    &mut account_account,
+                    &mut delegate_account,
+                    &mut nonce_account,
+                    &mut instructions_account,
+                    &mut clock_sysvar,
+                ],
+            )
+        );
+
+        // signed by someone other than the owner
+        let instruction = permit_instruction(0, 0, 200);
+        let mut instructions_account = instructions_sysvar(
+            &[
+                ed25519_instruction(&delegate_key, &permit_message(0, 0, 200)),
+                instruction.clone(),
+            ],
+            1,
+        );
+        assert_eq!(
+            Err(TokenError::MissingAuthorizationSignature.into()),
+            do_process_instruction(
+                instruction,
+                vec![
+                    &mut account_account,
+                    &mut delegate_account,
+                    &mut nonce_account,
+                    &mut instructions_account,
+                    &mut clock_sysvar,
+                ],
+            )
+        );
+
+        // signed a different message
+        let instruction = permit_instruction(0, 0, 200);
+        let mut instructions_account = instructions_sysvar(
+            &[
+                ed25519_instruction(&owner_key, &permit_message(0, 0, 300)),
+                instruction.clone(),
+            ],
+            1,
+        );
+        assert_eq!(
+            Err(TokenError::MissingAuthorizationSignature.into()),
+            do_process_instruction(
+                instruction,
+                vec![
+                    &mut account_account,
+                    &mut delegate_account,
+                    &mut nonce_account,
+                    &mut instructions_account,
+                    &mut clock_sysvar,
+                ],
+            )
+        );
+
+        // signature verified by a later instruction
+        let instruction = permit_instruction(0, 0, 200);
+        let mut instructions_account = instructions_sysvar(
+            &[
+                instruction.clone(),
+                ed25519_instruction(&owner_key, &permit_message(0, 0, 200)),
+            ],
+            0,
+        );
+        assert_eq!(
+            Err(TokenError::MissingAuthorizationSignature.into()),
+            do_process_instruction(
+                instruction,
+                vec![
+                    &mut account_account,
+                    &mut delegate_account,
+                    &mut nonce_account,
+                    &mut instructions_account,
+                    &mut clock_sysvar,
+                ],
+            )
+        );
+
+        // message held in another instruction
+        let instruction = permit_instruction(0, 0, 200);
+        let mut ed25519 = ed25519_instruction(&owner_key, &permit_message(0, 0, 200));
+        ed25519.data[14..16].copy_from_slice(&1u16.to_le_bytes());
+        let mut instructions_account = instructions_sysvar(&[ed25519, instruction.clone()], 1);
+        assert_eq!(
+            Err(TokenError::MissingAuthorizationSignature.into()),
+            do_process_instruction(
+                instruction,
+                vec![
+                    &mut account_account,
+                    &mut delegate_account,
+                    &mut nonce_account,
+                    &mut instructions_account,
+                    &mut clock_sysvar,
+                ],
+            )
+        );
+
+        // not yet valid
+        let instruction = permit_instruction(0, 150, 200);
+        let mut instructions_account = instructions_sysvar(
+            &[
+                ed25519_instruction(&owner_key, &permit_message(0, 150, 200)),
+                instruction.clone(),
+            ],
+            1,
+        );
+        assert_eq!(
+            Err(TokenError::AuthorizationNotValid.into()),
+            do_process_instruction(
+                instruction,
+                vec![
+                    &mut account_account,
+                    &mut delegate_account,
+                    &mut nonce_account,
+                    &mut instructions_account,
+                    &mut clock_sysvar,
+                ],
+            )
+        );
+
+        // expired
+        let instruction = permit_instruction(0, 0, 50);
+        let mut instructions_account = instructions_sysvar(
+            &[
+                ed25519_instruction(&owner_key, &permit_message(0, 0, 50)),
+                instruction.clone(),
+            ],
+            1,
+        );
+        assert_eq!(
+            Err(TokenError::AuthorizationNotValid.into()),
+            do_process_instruction(
+                instruction,
+                vec![
+                    &mut account_account,
+                    &mut delegate_account,
+                    &mut nonce_account,
+                    &mut instructions_account,
+                    &mut clock_sysvar,
+                ],
+            )
+        );
+
+        // wrong nonce
+        let instruction = permit_instruction(1, 0, 200);
+        let mut instructions_account = instructions_sysvar(
+            &[
+                ed25519_instruction(&owner_key, &permit_message(1, 0, 200)),
+                instruction.clone(),
+            ],
+            1,
+        );
+        assert_eq!(
+            Err(TokenError::InvalidNonce.into()),
+            do_process_instruction(
+                instruction,
+                vec![
+                    &mut account_account,
+                    &mut delegate_account,
+                    &mut nonce_account,
+                    &mut instructions_account,
+                    &mut clock_sysvar,
+                ],
+            )
+        );
+
+        // permit
+        let instruction = permit_instruction(0, 0, 200);
+        let mut instructions_account = instructions_sysvar(
+            &[
+                ed25519_instruction(&owner_key, &permit_message(0, 0, 200)),
+                instruction.clone(),
+            ],
+            1,
+        );
+        do_process_instruction(
+            instruction.clone(),
+            vec![
+                &mut account_account,
+                &mut delegate_account,
+                &mut nonce_account,
+                &mut instructions_account,
+                &mut clock_sysvar,
+            ],
+        )
+        .unwrap();
+        let account = Account::unpack_unchecked(&account_account.data).unwrap();
+        assert_eq!(account.delegate, COption::Some(delegate_key));
+        assert_eq!(account.delegated_amount, 100);
+        let nonce = AuthorizationNonce::unpack(&nonce_account.data).unwrap();
+        assert_eq!(nonce.nonce, 1);
+
+        // replay
+        assert_eq!(
+            Err(TokenError::InvalidNonce.into()),
+            do_process_instruction(
+                instruction,
+                vec![
+                    &mut account_account,
+                    &mut delegate_account,
+                    &mut nonce_account,
+                    &mut instructions_account,
+                    &mut clock_sysvar,
+                ],
+            )
+        );
+
+        // transfer with authorization
+        let instruction = with_mint(
+            transfer_with_authorization(
+                &program_id,
+                &account_key,
+                &account2_key,
+                &owner_key,
+                400,
+                1,
+                0,
+                200,
+            )
+            .unwrap(),
+            &mint_key,
+        );
+        let message = authorization_message(
+            &program_id,
+            &TokenInstruction::TransferWithAuthorization {
+                amount: 400,
+                nonce: 1,
+                valid_after: 0,
+                valid_until: 200,
+            },
+            &account_key,
+            &account2_key,
+        );
+        let mut instructions_account = instructions_sysvar(
+            &[
+                ed25519_instruction(&owner_key, &message),
+                instruction.clone(),
+            ],
+            1,
+        );
+        do_process_instruction(
+            instruction,
+            vec![
+                &mut account_account,
+                &mut account2_account,
+                &mut nonce_account,
+                &mut instructions_account,
+                &mut clock_sysvar,
+                &mut mint_account,
+            ],
+        )
+        .unwrap();
+        let account = Account::unpack_unchecked(&account_account.data).unwrap();
+        assert_eq!(account.amount, 600);
+        let account = Account::unpack_unchecked(&account2_account.data).unwrap();
+        assert_eq!(account.amount, 400);
+        let nonce = AuthorizationNonce::unpack(&nonce_account.data).unwrap();
+        assert_eq!(nonce.nonce, 2);
+
+        // insufficient funds
+        let instruction = with_mint(
+            transfer_with_authorization(
+                &program_id,
+                &account_key,
+                &account2_key,
+                &owner_key,
+                601,
+                2,
+                0,
+                200,
+            )
+            .unwrap(),
+            &mint_key,
+        );
+        let mut instructions_account = instructions_sysvar(std::slice::from_ref(&instruction), 0);
+        assert_eq!(
+            Err(TokenError::InsufficientFunds.into()),
+            do_process_instruction(
+                instruction,
+                vec![
+                    &mut account_account,
+                    &mut account2_account,
+                    &mut nonce_account,
+                    &mut instructions_account,
+                    &mut clock_sysvar,
+                    &mut mint_account,
+                ],
+            )
+        );
+    }
+
+    #[test]
+    fn test_session_keys() {
+        let program_id = crate::id();
+        let account_key = Pubkey::new_unique();
+        let mut account_account = SolanaAccount::new(
+            extended_account_minimum_balance(),
+            Account::EXTENDED_LEN,
+            &program_id,
+        );
+        let account2_key = Pubkey::new_unique();
+        let mut account2_account = SolanaAccount::new(
+            extended_account_minimum_balance(),
+            Account::EXTENDED_LEN,
+            &program_id,
+        );
+        let account3_key = Pubkey::new_unique();
+        let mut account3_account = SolanaAccount::new(
+            extended_account_minimum_balance(),
+            Account::EXTENDED_LEN,
+            &program_id,
+        );
+        let account4_key = Pubkey::new_unique();
+        let mut account4_account = SolanaAccount::new(
+            extended_account_minimum_balance(),
+            Account::EXTENDED_LEN,
+            &program_id,
+        );
+        let owner_key = Pubkey::new_unique();
+        let mut owner_account = SolanaAccount::default();
+        let session_key = Pubkey::new_unique();
+        let mut session_account = SolanaAccount::default();
+        let mint_key = Pubkey::new_unique();
+        let mut mint_account = SolanaAccount::new(
+            extended_mint_minimum_balance(),
+            Mint::EXTENDED_LEN,
+            &program_id,
+        );
+        let mut rent_sysvar = rent_sysvar();
+
+        // create new mint
+        do_process_instruction(
+            initialize_mint(&program_id, &mint_key, &owner_key, None, 2).unwrap(),
+            vec![&mut mint_account, &mut rent_sysvar],
+        )
+        .unwrap();
+
+        // create accounts
+        do_process_instruction(
+            initialize_account(&program_id, &account_key, &mint_key, &owner_key).unwrap(),
+            vec![
+                &mut account_account,
+                &mut mint_account,
+                &mut owner_account,
+                &mut rent_sysvar,
+            ],
+        )
+        .unwrap();
+        do_process_instruction(
+            initialize_account(&program_id, &account2_key, &mint_key, &owner_key).unwrap(),
+            vec![
+                &mut account2_account,
+                &mut mint_account,
+                &mut owner_account,
+                &mut rent_sysvar,
+            ],
+        )
+        .unwrap();
+        do_process_instruction(
+            initialize_account(&program_id, &account3_key, &mint_key, &owner_key).unwrap(),
+            vec![
+                &mut account3_account,
+                &mut mint_account,
+                &mut owner_account,
+                &mut rent_sysvar,
+            ],
+        )
+        .unwrap();
+        do_process_instruction(
+            initialize_account(&program_id, &account4_key, &mint_key, &owner_key).unwrap(),
+            vec![
+                &mut account4_account,
+                &mut mint_account,
+                &mut owner_account,
+                &mut rent_sysvar,
+            ],
+        )
+        .unwrap();
+
+        // mint to account
+        do_process_instruction(
+            mint_to(&program_id, &mint_key, &account_key, &owner_key, &[], 1000).unwrap(),
+            vec![&mut mint_account, &mut account_account, &mut owner_account],
+        )
+        .unwrap();
+
+        // unknown scope bits
+        assert_eq!(
+            Err(ProgramError::InvalidArgument),
+            do_process_instruction(
+                create_session(
+                    &program_id,
+                    &account_key,
+                    &owner_key,
+                    &[],
+                    &session_key,
+                    200,
+                    1 << 2,
+                    &[],
+                    300,
+                )
+                .unwrap(),
+                vec![&mut account_account, &mut owner_account],
+            )
+        );
+
+        // too many destinations
+        let destinations = [&account2_key; MAX_SESSION_DESTINATIONS + 1];
+        assert_eq!(
+            Err(ProgramError::InvalidArgument),
+            create_session(
+                &program_id,
+                &account_key,
+                &owner_key,
+                &[],
+                &session_key,
+                200,
+                SESSION_SCOPE_TRANSFER,
+                &destinations,
+                300,
+            )
+        );
+
+        // not the owner
+        assert_eq!(
+            Err(TokenError::OwnerMismatch.into()),
+            do_process_instruction(
+                create_session(
+                    &program_id,
+                    &account_key,
+                    &session_key,
+                    &[],
+                    &session_key,
+                    200,
+                    SESSION_SCOPE_TRANSFER,
+                    &[&account2_key],
+                    300,
+                )
+                .unwrap(),
+                vec![&mut account_account, &mut session_account],
+            )
+        );
+
+        // create a transfer-only session to account2 and account4, for up to 300 tokens
+        do_process_instruction(
+            create_session(
+                &program_id,
+                &account_key,
+                &owner_key,
+                &[],
+                &session_key,
+                200,
+                SESSION_SCOPE_TRANSFER,
+                &[&account2_key, &account4_key],
+                300,
+            )
+            .unwrap(),
+            vec![&mut account_account, &mut owner_account],
+        )
+        .unwrap();
+        let account = Account::unpack_unchecked(&account_account.data).unwrap();
+        assert_eq!(account.session_key, COption::Some(session_key));
+        assert_eq!(account.session_remaining_amount, 300);
+        assert_eq!(
+            account.session_destinations(),
+            &[account2_key, account4_key]
+        );
+
+        let session_transfer = |destination: &Pubkey, amount| {
+            let mut instruction = transfer(
+                &program_id,
+                &account_key,
+                destination,
+                &session_key,
+                &[],
+                amount,
+            )
+            .unwrap();
+            instruction
+                .accounts
+                .push(AccountMeta::new_readonly(mint_key, false));
+            instruction
+                .accounts
+                .push(AccountMeta::new_readonly(sysvar::clock::id(), false));
+            instruction
+        };
+
+        // missing clock
+        assert_eq!(
+            Err(ProgramError::NotEnoughAccountKeys),
+            do_process_instruction(
+                with_mint(
+                    transfer(
+                        &program_id,
+                        &account_key,
+                        &account2_key,
+                        &session_key,
+                        &[],
+                        100,
+                    )
+                    .unwrap(),
+                    &mint_key,
+                ),
+                vec![
+                    &mut account_account,
+                    &mut account2_account,
+                    &mut session_account,
+                    &mut mint_account,
+                ],
+            )
+        );
+
+        // destination not allowed
+        assert_eq!(
+            Err(TokenError::SessionScopeExceeded.into()),
+            do_process_instruction(
+                session_transfer(&account3_key, 100),
+                vec![
+                    &mut account_account,
+                    &mut account3_account,
+                    &mut session_account,
+                    &mut mint_account,
+                    &mut clock_sysvar(100),
+                ],
+            )
+        );
+
+        // over the session amount
+        assert_eq!(
+            Err(TokenError::SessionScopeExceeded.into()),
+            do_process_instruction(
+                session_transfer(&account2_key, 301),
+                vec![
+                    &mut account_account,
+                    &mut account2_account,
+                    &mut session_account,
+                    &mut mint_account,
+                    &mut clock_sysvar(100),
+                ],
+            )
+        );
+
+        // burn not in scope
+        let mut instruction =
+            burn(&program_id, &account_key, &mint_key, &session_key, &[], 100).unwrap();
+        instruction
+            .accounts
+            .push(AccountMeta::new_readonly(sysvar::clock::id(), false));
+        assert_eq!(
+            Err(TokenError::SessionScopeExceeded.into()),
+            do_process_instruction(
+                instruction,
+                vec![
+                    &mut account_account,
+                    &mut mint_account,
+                    &mut session_account,
+                    &mut clock_sysvar(100),
+                ],
+            )
+        );
+
+        // transfer with the session key
+        do_process_instruction(
+            session_transfer(&account2_key, 200),
+            vec![
+                &mut account_account,
+                &mut account2_account,
+                &mut session_account,
+                &mut mint_account,
+                &mut clock_sysvar(100),
+            ],
+        )
+        .unwrap();
+        let account = Account::unpack_unchecked(&account_account.data).unwrap();
+        assert_eq!(account.amount, 800);
+        assert_eq!(account.session_remaining_amount, 100);
+        let account = Account::unpack_unchecked(&account2_account.data).unwrap();
+        assert_eq!(account.amount, 200);
+
+        // transfer to the second destination
+        do_process_instruction(
+            session_transfer(&account4_key, 50),
+            vec![
+                &mut account_account,
+                &mut account4_account,
+                &mut session_account,
+                &mut mint_account,
+                &mut clock_sysvar(100),
+            ],
+        )
+        .unwrap();
+        let account = Account::unpack_unchecked(&account_account.data).unwrap();
+        assert_eq!(account.amount, 750);
+        assert_eq!(account.session_remaining_amount, 50);
+        let account = Account::unpack_unchecked(&account4_account.data).unwrap();
+        assert_eq!(account.amount, 50);
+
+        // session expired
+        assert_eq!(
+            Err(TokenError::SessionKeyExpired.into()),
+            do_process_instruction(
+                session_transfer(&account2_key, 50),
+                vec![
+                    &mut account_account,
+                    &mut account2_account,
+                    &mut session_account,
+                    &mut mint_account,
+                    &mut clock_sysvar(200),
+                ],
+            )
+        );
+
+        // revoke the session
+        do_process_instruction(
+            revoke_session(&program_id, &account_key, &owner_key, &[]).unwrap(),
+            vec![&mut account_account, &mut owner_account],
+        )
+        .unwrap();
+        let account = Account::unpack_unchecked(&account_account.data).unwrap();
+        assert_eq!(account.session_key, COption::None);
+        assert_eq!(account.session_remaining_amount, 0);
+        assert!(account.session_destinations().is_empty());
+
+        // session key no longer accepted
+        assert_eq!(
+            Err(TokenError::OwnerMismatch.into()),
+            do_process_instruction(
+                session_transfer(&account2_key, 100),
+                vec![
+                    &mut account_account,
+                    &mut account2_account,
+                    &mut session_account,
+                    &mut mint_account,
+                    &mut clock_sysvar(100),
+                ],
+            )
+        );
+    }
+
+    #[test]
+    fn test_ethereum_owner() {
+        let program_id = crate::id();
+        let address = [7u8; 20];
+        let owner_key = crate::get_ethereum_owner_address(&address);
+        let mut owner_account = SolanaAccount::new(
+            Rent::default().minimum_balance(EthereumOwner::get_packed_len()),
+            EthereumOwner::get_packed_len(),
+            &program_id,
+        );
+        let account_key = Pubkey::new_unique();
+        let mut account_account = SolanaAccount::new(
+            extended_account_minimum_balance(),
+            Account::EXTENDED_LEN,
+            &program_id,
+        );
+        let account2_key = Pubkey::new_unique();
+        let mut account2_account = SolanaAccount::new(
+            extended_account_minimum_balance(),
+            Account::EXTENDED_LEN,
+            &program_id,
+        );
+        let mint_authority_key = Pubkey::new_unique();
+        let mut mint_authority_account = SolanaAccount::default();
+        let payer_key = Pubkey::new_unique();
+        let mut payer_account = SolanaAccount::default();
+        let mut system_program_account = SolanaAccount::default();
+        let mint_key = Pubkey::new_unique();
+        let mut mint_account = SolanaAccount::new(
+            extended_mint_minimum_balance(),
+            Mint::EXTENDED_LEN,
+            &program_id,
+        );
+        let mut rent_sysvar = rent_sysvar();
+
+        // create Ethereum owner
+        do_process_instruction(
+            initialize_ethereum_owner(&program_id, &address, &payer_key).unwrap(),
+            vec![
+                &mut owner_account,
+                &mut payer_account,
+                &mut system_program_account,
+                &mut rent_sysvar,
+            ],
+        )
+        .unwrap();
+        let ethereum_owner = EthereumOwner::unpack(&owner_account.data).unwrap();
+        assert_eq!(ethereum_owner.address, address);
+        assert_eq!(ethereum_owner.nonce, 0);
+
+        // owner already initialized
+        assert_eq!(
+            Err(TokenError::AlreadyInUse.into()),
+            do_process_instruction(
+                initialize_ethereum_owner(&program_id, &address, &payer_key).unwrap(),
+                vec![
+                    &mut owner_account,
+                    &mut payer_account,
+                    &mut system_program_account,
+                    &mut rent_sysvar,
+                ],
+            )
+        );
+
+        // create new mint
+        do_process_instruction(
+            initialize_mint(&program_id, &mint_key, &mint_authority_key, None, 2).unwrap(),
+            vec![&mut mint_account, &mut rent_sysvar],
+        )
+        .unwrap();
+
+        // create accounts, the first owned by the Ethereum address
+        do_process_instruction(
+            initialize_account(&program_id, &account_key, &mint_key, &owner_key).unwrap(),
+            vec![
+                &mut account_account,
+                &mut mint_account,
+                &mut owner_account,
+                &mut rent_sysvar,
+            ],
+        )
+        .unwrap();
+        do_process_instruction(
+            initialize_account(&program_id, &account2_key, &mint_key, &mint_authority_key).unwrap(),
+            vec![
+                &mut account2_account,
+                &mut mint_account,
+                &mut mint_authority_account,
+                &mut rent_sysvar,
+            ],
+        )
+        .unwrap();
+
+        // mint to account
+        do_process_instruction(
+            mint_to(
+                &program_id,
+                &mint_key,
+                &account_key,
+                &mint_authority_key,
+                &[],
+                1000,
+            )
+            .unwrap(),
+            vec![
+                &mut mint_account,
+                &mut account_account,
+                &mut mint_authority_account,
+            ],
+        )
+        .unwrap();
+
+        // missing instructions sysvar
+        let mut instruction = with_mint(
+            transfer(
+                &program_id,
+                &account_key,
+                &account2_key,
+                &owner_key,
+                &[],
+                100,
+            )
+            .unwrap(),
+            &mint_key,
+        );
+        instruction.accounts[2] = AccountMeta::new(owner_key, false);
+        assert_eq!(
+            Err(ProgramError::NotEnoughAccountKeys),
+            do_process_instruction(
+                instruction.clone(),
+                vec![
+                    &mut account_account,
+                    &mut account2_account,
+                    &mut owner_account,
+                    &mut mint_account,
+                ],
+            )
+        );
+
+        instruction
+            .accounts
+            .push(AccountMeta::new_readonly(sysvar::instructions::id(), false));
+
+        // signed by another address
+        set_syscall_clock(1000);
+        let message = ethereum_authorization_message(&instruction, 0, 1000);
+        assert!(message.starts_with(b"\x19Ethereum Signed Message:\n"));
+        let mut instructions_account = instructions_sysvar(
+            &[
+                secp256k1_instruction(&[8u8; 20], &message, 0),
+                instruction.clone(),
+            ],
+            1,
+        );
+        assert_eq!(
+            Err(TokenError::MissingAuthorizationSignature.into()),
+            do_process_instruction(
+                instruction.clone(),
+                vec![
+                    &mut account_account,
+                    &mut account2_account,
+                    &mut owner_account,
+                    &mut mint_account,
+                    &mut instructions_account,
+                ],
+            )
+        );
+
+        // signed with a stale nonce
+        let message = ethereum_authorization_message(&instruction, 1, 1000);
+        let mut instructions_account = instructions_sysvar(
+            &[
+                secp256k1_instruction(&address, &message, 0),
+                instruction.clone(),
+            ],
+            1,
+        );
+        assert_eq!(
+            Err(TokenError::MissingAuthorizationSignature.into()),
+            do_process_instruction(
+                instruction.clone(),
+                vec![
+                    &mut account_account,
+                    &mut account2_account,
+                    &mut owner_account,
+                    &mut mint_account,
+                    &mut instructions_account,
+                ],
+            )
+        );
+
+        // signed without the signed message prefix
+        let message = ethereum_authorization_message(&instruction, 0, 1000);
+        let body_len = 32 + 8 + instruction.data.len() + 32 * instruction.accounts.len() + 8;
+        let mut instructions_account = instructions_sysvar(
+            &[
+                secp256k1_instruction(&address, &message[message.len() - body_len..], 0),
+                instruction.clone(),
+            ],
+            1,
+        );
+        assert_eq!(
+            Err(TokenError::MissingAuthorizationSignature.into()),
+            do_process_instruction(
+                instruction.clone(),
+                vec![
+                    &mut account_account,
+                    &mut account2_account,
+                    &mut owner_account,
+                    &mut mint_account,
+                    &mut instructions_account,
+                ],
+            )
+        );
+
+        // authorization expired
+        let message = ethereum_authorization_message(&instruction, 0, 999);
+        let mut instructions_account = instructions_sysvar(
+            &[
+                secp256k1_instruction(&address, &message, 0),
+                instruction.clone(),
+            ],
+            1,
+        );
+        assert_eq!(
+            Err(TokenError::AuthorizationNotValid.into()),
+            do_process_instruction(
+                instruction.clone(),
+                vec![
+                    &mut account_account,
+                    &mut account2_account,
+                    &mut owner_account,
+                    &mut mint_account,
+                    &mut instructions_account,
+                ],
+            )
+        );
+
+        // message held in another instruction
+        let message = ethereum_authorization_message(&instruction, 0, 1000);
+        let mut secp256k1 = secp256k1_instruction(&address, &message, 0);
+        secp256k1.data[11] = 1;
+        let mut instructions_account = instructions_sysvar(&[secp256k1, instruction.clone()], 1);
+        assert_eq!(
+            Err(TokenError::MissingAuthorizationSignature.into()),
+            do_process_instruction(
+                instruction.clone(),
+                vec![
+                    &mut account_account,
+                    &mut account2_account,
+                    &mut owner_account,
+                    &mut mint_account,
+                    &mut instructions_account,
+                ],
+            )
+        );
+
+        // authorized transfer that fails leaves the nonce unused
+        let mut failing_instruction = instruction.clone();
+        failing_instruction.data = TokenInstruction::Transfer { amount: 1001 }.pack();
+        let failing_message = ethereum_authorization_message(&failing_instruction, 0, 1000);
+        let mut instructions_account = instructions_sysvar(
+            &[
+                secp256k1_instruction(&address, &failing_message, 0),
+                failing_instruction.clone(),
+            ],
+            1,
+        );
+        assert_eq!(
+            Err(TokenError::InsufficientFunds.into()),
+            do_process_instruction(
+                failing_instruction,
+                vec![
+                    &mut account_account,
+                    &mut account2_account,
+                    &mut owner_account,
+                    &mut mint_account,
+                    &mut instructions_account,
+                ],
+            )
+        );
+        let ethereum_owner = EthereumOwner::unpack(&owner_account.data).unwrap();
+        assert_eq!(ethereum_owner.nonce, 0);
+
+        // transfer authorized by the Ethereum address
+        let mut instructions_account = instructions_sysvar(
+            &[
+                secp256k1_instruction(&address, &message, 0),
+                instruction.clone(),
+            ],
+            1,
+        );
+        do_process_instruction(
+            instruction.clone(),
+            vec![
+                &mut account_account,
+                &mut account2_account,
+                &mut owner_account,
+                &mut mint_account,
+                &mut instructions_account,
+            ],
+        )
+        .unwrap();
+        let account = Account::unpack_unchecked(&account_account.data).unwrap();
+        assert_eq!(account.amount, 900);
+        let account = Account::unpack_unchecked(&account2_account.data).unwrap();
+        assert_eq!(account.amount, 100);
+        let ethereum_owner = EthereumOwner::unpack(&owner_account.data).unwrap();
+        assert_eq!(ethereum_owner.nonce, 1);
+
+        // replay
+        assert_eq!(
+            Err(TokenError::MissingAuthorizationSignature.into()),
+            do_process_instruction(
+                instruction,
+                vec![
+                    &mut account_account,
+                    &mut account2_account,
+                    &mut owner_account,
+                    &mut mint_account,
+                    &mut instructions_account,
+                ],
+            )
+        );
+    }
+
+    #[test]
+    fn test_authority_matrix() {
+        let program_id = crate::id();
+        let address = [7u8; 20];
+        let authorities = [
+            TestAuthority::Signer(Pubkey::new_unique()),
+            TestAuthority::Multisig {
+                multisig: Pubkey::new_unique(),
+                signer: Pubkey::new_unique(),
+            },
+            TestAuthority::WeightedMultisig {
+                multisig: Pubkey::new_unique(),
+                signer: Pubkey::new_unique(),
+            },
+            TestAuthority::EthereumOwner {
+                owner: crate::get_ethereum_owner_address(&address),
+                address,
+            },
+        ];
+
+        for authority in authorities.iter() {
+            let authority_key = authority.key();
+            let signers = authority.signer_pubkeys();
+            let mint_key = Pubkey::new_unique();
+            let account_key = Pubkey::new_unique();
+            let account2_key = Pubkey::new_unique();
+            let mut store = HashMap::new();
+            store.insert(rent::id(), rent_sysvar());
+            store.insert(
+                mint_key,
+                SolanaAccount::new(
+                    extended_mint_minimum_balance(),
+                    Mint::EXTENDED_LEN,
+                    &program_id,
+                ),
+            );
+            store.insert(
+                account_key,
+                SolanaAccount::new(
+                    extended_account_minimum_balance(),
+                    Account::EXTENDED_LEN,
+                    &program_id,
+                ),
+            );
+            store.insert(
+                account2_key,
+                SolanaAccount::new(
+                    extended_account_minimum_balance(),
+                    Account::EXTENDED_LEN,
+                    &program_id,
+                ),
+            );
+
+            let delegate_key = Pubkey::new_unique();
+            let minter_key = Pubkey::new_unique();
+            let allowlist_key = Pubkey::new_unique();
+            let holder_key = Pubkey::new_unique();
+            let allowance_key = crate::get_allowance_address(&account_key, &authority_key);
+            let credential_key = crate::get_credential_address(&mint_key, &holder_key);
+            store.insert(sysvar::clock::id(), clock_sysvar(1000));
+            store.insert(
+                minter_key,
+                SolanaAccount::new(
+                    Rent::default().minimum_balance(Minter::get_packed_len()),
+                    Minter::get_packed_len(),
+                    &program_id,
+                ),
+            );
+            store.insert(
+                allowlist_key,
+                SolanaAccount::new(
+                    Rent::default().minimum_balance(DestinationAllowlist::get_packed_len()),
+                    DestinationAllowlist::get_packed_len(),
+                    &program_id,
+                ),
+            );
+            store.insert(
+                allowance_key,
+                SolanaAccount::new(
+                    allowance_minimum_balance(),
+                    Allowance::get_packed_len(),
+                    &program_id,
+                ),
+            );
+            store.insert(
+                credential_key,
+                SolanaAccount::new(
+                    Rent::default().minimum_balance(Credential::get_packed_len()),
+                    Credential::get_packed_len(),
+                    &program_id,
+                ),
+            );
+
+            // create the authority account
+            match authority {
+                TestAuthority::Signer(_) => {}
+                TestAuthority::Multisig { multisig, signer } => {
+                    store.insert(
+                        *multisig,
+                        SolanaAccount::new(
+                            multisig_minimum_balance(),
+                            Multisig::get_packed_len(),
+                            &program_id,
+                        ),
+                    );
+                    do_process_instruction_with_store(
+                        initialize_multisig(&program_id, multisig, &[signer], 1).unwrap(),
+                        &mut store,
+                    )
+                    .unwrap();
+                }
+                TestAuthority::WeightedMultisig { multisig, signer } => {
+                    store.insert(
+                        *multisig,
+                        SolanaAccount::new(
+                            Rent::default().minimum_balance(WeightedMultisig::get_packed_len()),
+                            WeightedMultisig::get_packed_len(),
+                            &program_id,
+                        ),
+                    );
+                    do_process_instruction_with_store(
+                        initialize_weighted_multisig(&program_id, multisig, &[signer], &[2], 2)
+                            .unwrap(),
+                        &mut store,
+                    )
+                    .unwrap();
+                }
+                TestAuthority::EthereumOwner { owner, address } => {
+                    store.insert(
+                        *owner,
+                        SolanaAccount::new(
+                            Rent::default().minimum_balance(EthereumOwner::get_packed_len()),
+                            EthereumOwner::get_packed_len(),
+                            &program_id,
+                        ),
+                    );
+                    do_process_instruction_with_store(
+                        initialize_ethereum_owner(&program_id, address, &Pubkey::new_unique())
+                            .unwrap(),
+                        &mut store,
+                    )
+                    .unwrap();
+                }
+            }
+            assert_eq!(
+                AuthorityKind::resolve(
+                    &program_id,
+                    &(&authority_key, store.entry(authority_key).or_default()).into_account_info(),
+                ),
+                match authority {
+                    TestAuthority::Signer(_) => AuthorityKind::Signer,
+                    TestAuthority::Multisig { .. } => AuthorityKind::Multisig,
+                    TestAuthority::WeightedMultisig { .. } => AuthorityKind::WeightedMultisig,
+                    TestAuthority::EthereumOwner { .. } => AuthorityKind::EthereumOwner,
+                }
+            );
+
+            // create mint and accounts
+            do_process_instruction_with_store(
+                initialize_mint(
+                    &program_id,
+                    &mint_key,
+                    &authority_key,
+                    Some(&authority_key),
+                    2,
+                )
+                .unwrap(),
+                &mut store,
+            )
+            .unwrap();
+            do_process_instruction_with_store(
+                initialize_account(&program_id, &account_key, &mint_key, &authority_key).unwrap(),
+                &mut store,
+            )
+            .unwrap();
+            do_process_instruction_with_store(
+                initialize_account(&program_id, &account2_key, &mint_key, &Pubkey::new_unique())
+                    .unwrap(),
+                &mut store,
+            )
+            .unwrap();
+
+            // mint authority over transfer restrictions
+            assert_authority_required(
+                authority,
+                set_max_balance(&program_id, &mint_key, &authority_key, &signers, Some(1000))
+                    .unwrap(),
+                &mut store,
+            );
+            assert_authority_required(
+                authority,
+                add_max_balance_exemption(
+                    &program_id,
+                    &account_key,
+                    &mint_key,
+                    &authority_key,
+                    &signers,
+                )
+                .unwrap(),
+                &mut store,
+            );
+            assert_authority_required(
+                authority,
+                remove_max_balance_exemption(
+                    &program_id,
+                    &account_key,
+                    &mint_key,
+                    &authority_key,
+                    &signers,
+                )
+                .unwrap(),
+                &mut store,
+            );
+            assert_authority_required(
+                authority,
+                set_max_balance(&program_id, &mint_key, &authority_key, &signers, None).unwrap(),
+                &mut store,
+            );
+            assert_authority_required(
+                authority,
+                set_launch_controls(&program_id, &mint_key, &authority_key, &signers, 0, 0)
+                    .unwrap(),
+                &mut store,
+            );
+            assert_authority_required(
+                authority,
+                set_min_transfer_amount(&program_id, &mint_key, &authority_key, &signers, 0)
+                    .unwrap(),
+                &mut store,
+            );
+            assert_authority_required(
+                authority,
+                set_mint_role(
+                    &program_id,
+                    &mint_key,
+                    Some(&authority_key),
+                    MintRole::ComplianceAuthority,
+                    &authority_key,
+                    &signers,
+                )
+                .unwrap(),
+                &mut store,
+            );
+
+            // compliance authority
+            assert_authority_required(
+                authority,
+                issue_credential(
+                    &program_id,
+                    &mint_key,
+                    &holder_key,
+                    &Pubkey::new_unique(),
+                    &authority_key,
+                    &signers,
+                    2000,
+                    1,
+                )
+                .unwrap(),
+                &mut store,
+            );
+            assert_authority_required(
+                authority,
+                revoke_credential(
+                    &program_id,
+                    &mint_key,
+                    &holder_key,
+                    &Pubkey::new_unique(),
+                    &authority_key,
+                    &signers,
+                )
+                .unwrap(),
+                &mut store,
+            );
+            assert_authority_required(
+                authority,
+                set_mint_role(
+                    &program_id,
+                    &mint_key,
+                    None,
+                    MintRole::ComplianceAuthority,
+                    &authority_key,
+                    &signers,
+                )
+                .unwrap(),
+                &mut store,
+            );
+
+            // mint authority
+            assert_authority_required(
+                authority,
+                mint_to(
+                    &program_id,
+                    &mint_key,
+                    &account_key,
+                    &authority_key,
+                    &signers,
+                    1000,
+                )
+                .unwrap(),
+                &mut store,
+            );
+            assert_authority_required(
+                authority,
+                mint_to_checked(
+                    &program_id,
+                    &mint_key,
+                    &account_key,
+                    &authority_key,
+                    &signers,
+                    10,
+                    2,
+                )
+                .unwrap(),
+                &mut store,
+            );
+            assert_authority_required(
+                authority,
+                set_mint_role(
+                    &program_id,
+                    &mint_key,
+                    Some(&authority_key),
+                    MintRole::Pauser,
+                    &authority_key,
+                    &signers,
+                )
+                .unwrap(),
+                &mut store,
+            );
+            assert_authority_required(
+                authority,
+                pause_minting(&program_id, &mint_key, &authority_key, &signers).unwrap(),
+                &mut store,
+            );
+            assert_authority_required(
+                authority,
+                resume_minting(&program_id, &mint_key, &authority_key, &signers).unwrap(),
+                &mut store,
+            );
+            assert_authority_required(
+                authority,
+                initialize_minter(
+                    &program_id,
+                    &minter_key,
+                    &mint_key,
+                    &authority_key,
+                    &authority_key,
+                    &signers,
+                    100,
+                )
+                .unwrap(),
+                &mut store,
+            );
+            assert_authority_required(
+                authority,
+                set_minter_allowance(
+                    &program_id,
+                    &minter_key,
+                    &mint_key,
+                    &authority_key,
+                    &signers,
+                    50,
+                )
+                .unwrap(),
+                &mut store,
+            );
+
+            // minter authority
+            assert_authority_required(
+                authority,
+                mint_to_with_minter(
+                    &program_id,
+                    &mint_key,
+                    &account_key,
+                    &minter_key,
+                    &authority_key,
+                    &signers,
+                    10,
+                )
+                .unwrap(),
+                &mut store,
+            );
+            assert_authority_required(
+                authority,
+                revoke_minter(
+                    &program_id,
+                    &minter_key,
+                    &mint_key,
+                    &Pubkey::new_unique(),
+                    &authority_key,
+                    &signers,
+                )
+                .unwrap(),
+                &mut store,
+            );
+
+            // account owner
+            assert_authority_required(
+                authority,
+                approve(
+                    &program_id,
+                    &account_key,
+                    &delegate_key,
+                    &authority_key,
+                    &signers,
+                    100,
+                )
+                .unwrap(),
+                &mut store,
+            );
+            assert_authority_required(
+                authority,
+                approve_checked(
+                    &program_id,
+                    &account_key,
+                    &mint_key,
+                    &delegate_key,
+                    &authority_key,
+                    &signers,
+                    100,
+                    2,
+                )
+                .unwrap(),
+                &mut store,
+            );
+            assert_authority_required(
+                authority,
+                approve_with_limits(
+                    &program_id,
+                    &account_key,
+                    &delegate_key,
+                    &authority_key,
+                    &signers,
+                    100,
+                    2000,
+                    Some(10),
+                )
+                .unwrap(),
+                &mut store,
+            );
+            assert_authority_required(
+                authority,
+                approve_if_current(
+                    &program_id,
+                    &account_key,
+                    &delegate_key,
+                    &authority_key,
+                    &signers,
+                    Some(&delegate_key),
+                    100,
+                    50,
+                )
+                .unwrap(),
+                &mut store,
+            );
+            assert_authority_required(
+                authority,
+                increase_allowance(
+                    &program_id,
+                    &account_key,
+                    &delegate_key,
+                    &authority_key,
+                    &signers,
+                    10,
+                )
+                .unwrap(),
+                &mut store,
+            );
+            assert_authority_required(
+                authority,
+                decrease_allowance(
+                    &program_id,
+                    &account_key,
+                    &delegate_key,
+                    &authority_key,
+                    &signers,
+                    10,
+                )
+                .unwrap(),
+                &mut store,
+            );
+            assert_authority_required(
+                authority,
+                revoke(&program_id, &account_key, &authority_key, &signers).unwrap(),
+                &mut store,
+            );
+            assert_authority_required(
+                authority,
+                set_allowance(
+                    &program_id,
+                    &account_key,
+                    &authority_key,
+                    &Pubkey::new_unique(),
+                    &authority_key,
+                    &signers,
+                    100,
+                )
+                .unwrap(),
+                &mut store,
+            );
+            assert_authority_required(
+                authority,
+                place_hold(
+                    &program_id,
+                    &account_key,
+                    &authority_key,
+                    &signers,
+                    &authority_key,
+                    10,
+                    2000,
+                )
+                .unwrap(),
+                &mut store,
+            );
+
+            // hold capturer
+            assert_authority_required(
+                authority,
+                with_mint(
+                    capture_hold(
+                        &program_id,
+                        &account_key,
+                        &account2_key,
+                        &authority_key,
+                        &signers,
+                        10,
+                    )
+                    .unwrap(),
+                    &mint_key,
+                ),
+                &mut store,
+            );
+            assert_authority_required(
+                authority,
+                place_hold(
+                    &program_id,
+                    &account_key,
+                    &authority_key,
+                    &signers,
+                    &authority_key,
+                    10,
+                    2000,
+                )
+                .unwrap(),
+                &mut store,
+            );
+            assert_authority_required(
+                authority,
+                release_hold(&program_id, &account_key, Some(&authority_key), &signers).unwrap(),
+                &mut store,
+            );
+
+            // account owner
+            assert_authority_required(
+                authority,
+                with_mint(
+                    transfer(
+                        &program_id,
+                        &account_key,
+                        &account2_key,
+                        &authority_key,
+                        &signers,
+                        870,
+                    )
+                    .unwrap(),
+                    &mint_key,
+                ),
+                &mut store,
+            );
+            assert_authority_required(
+                authority,
+                transfer_checked(
+                    &program_id,
+                    &account_key,
+                    &mint_key,
+                    &account2_key,
+                    &authority_key,
+                    &signers,
+                    10,
+                    2,
+                )
+                .unwrap(),
+                &mut store,
+            );
+            assert_authority_required(
+                authority,
+                burn(
+                    &program_id,
+                    &account_key,
+                    &mint_key,
+                    &authority_key,
+                    &signers,
+                    90,
+                )
+                .unwrap(),
+                &mut store,
+            );
+            assert_authority_required(
+                authority,
+                burn_checked(
+                    &program_id,
+                    &account_key,
+                    &mint_key,
+                    &authority_key,
+                    &signers,
+                    20,
+                    2,
+                )
+                .unwrap(),
+                &mut store,
+            );
+
+            // allowance spender
+            assert_authority_required(
+                authority,
+                with_mint(
+                    transfer_with_allowance(
+                        &program_id,
+                        &account_key,
+                        &account2_key,
+                        &authority_key,
+                        &signers,
+                        10,
+                    )
+                    .unwrap(),
+                    &mint_key,
+                ),
+                &mut store,
+            );
+            assert_authority_required(
+                authority,
+                burn_with_allowance(
+                    &program_id,
+                    &account_key,
+                    &mint_key,
+                    &authority_key,
+                    &signers,
+                    10,
+                )
+                .unwrap(),
+                &mut store,
+            );
+
+            // account owner
+            assert_authority_required(
+                authority,
+                close_allowance(
+                    &program_id,
+                    &account_key,
+                    &authority_key,
+                    &Pubkey::new_unique(),
+                    &authority_key,
+                    &signers,
+                )
+                .unwrap(),
+                &mut store,
+            );
+            assert_authority_required(
+                authority,
+                create_session(
+                    &program_id,
+                    &account_key,
+                    &authority_key,
+                    &signers,
+                    &Pubkey::new_unique(),
+                    1000,
+                    SESSION_SCOPE_TRANSFER,
+                    &[],
+                    10,
+                )
+                .unwrap(),
+                &mut store,
+            );
+            assert_authority_required(
+                authority,
+                revoke_session(&program_id, &account_key, &authority_key, &signers).unwrap(),
+                &mut store,
+            );
+            assert_authority_required(
+                authority,
+                set_spending_limit(&program_id, &account_key, &authority_key, &signers, 100, 10)
+                    .unwrap(),
+                &mut store,
+            );
+            assert_authority_required(
+                authority,
+                set_min_incoming_amount(&program_id, &account_key, &authority_key, &signers, 0)
+                    .unwrap(),
+                &mut store,
+            );
+            assert_authority_required(
+                authority,
+                initialize_destination_allowlist(
+                    &program_id,
+                    &allowlist_key,
+                    &account_key,
+                    &authority_key,
+                    &signers,
+                )
+                .unwrap(),
+                &mut store,
+            );
+            assert_authority_required(
+                authority,
+                add_allowed_destination(
+                    &program_id,
+                    &allowlist_key,
+                    &account_key,
+                    &authority_key,
+                    &signers,
+                    &account2_key,
+                )
+                .unwrap(),
+                &mut store,
+            );
+            assert_authority_required(
+                authority,
+                remove_allowed_destination(
+                    &program_id,
+                    &allowlist_key,
+                    &account_key,
+                    &authority_key,
+                    &signers,
+                    &account2_key,
+                )
+                .unwrap(),
+                &mut store,
+            );
+            assert_authority_required(
+                authority,
+                set_guardians(
+                    &program_id,
+                    &account_key,
+                    Some(&authority_key),
+                    10,
+                    &authority_key,
+                    &signers,
+                )
+                .unwrap(),
+                &mut store,
+            );
+
+            // guardians
+            assert_authority_required(
+                authority,
+                initiate_recovery(
+                    &program_id,
+                    &account_key,
+                    &Pubkey::new_unique(),
+                    &authority_key,
+                    &signers,
+                )
+                .unwrap(),
+                &mut store,
+            );
+
+            // account owner
+            assert_authority_required(
+                authority,
+                veto_recovery(&program_id, &account_key, &authority_key, &signers).unwrap(),
+                &mut store,
+            );
+
+            // freeze authority
+            assert_authority_required(
+                authority,
+                freeze_account(
+                    &program_id,
+                    &account_key,
+                    &mint_key,
+                    &authority_key,
+                    &signers,
+                )
+                .unwrap(),
+                &mut store,
+            );
+            assert_authority_required(
+                authority,
+                thaw_account(
+                    &program_id,
+                    &account_key,
+                    &mint_key,
+                    &authority_key,
+                    &signers,
+                )
+                .unwrap(),
+                &mut store,
+            );
+            assert_authority_required(
+                authority,
+                freeze_account_with_reason(
+                    &program_id,
+                    &account_key,
+                    &mint_key,
+                    &authority_key,
+                    &signers,
+                    1,
+                    0,
+                )
+                .unwrap(),
+                &mut store,
+            );
+            assert_authority_required(
+                authority,
+                thaw_accounts(
+                    &program_id,
+                    &[&account_key],
+                    &mint_key,
+                    &authority_key,
+                    &signers,
+                )
+                .unwrap(),
+                &mut store,
+            );
+            assert_authority_required(
+                authority,
+                freeze_accounts(
+                    &program_id,
+                    &[&account_key],
+                    &mint_key,
+                    &authority_key,
+                    &signers,
+                )
+                .unwrap(),
+                &mut store,
+            );
+            assert_authority_required(
+                authority,
+                set_receive_only(
                     &program_id,
                     &account_key,
-                    &delegate_key,
-                    &owner2_key,
-                    &[],
-                    100
+                    &mint_key,
+                    &authority_key,
+                    &signers,
                 )
                 .unwrap(),
-                vec![
-                    &mut account_account,
-                    &mut delegate_account,
-                    &mut owner2_account,
-                ],
-            )
-        );
-
-        // approve delegate
-        do_process_instruction(
-            approve(
-                &program_id,
-                &account_key,
-                &delegate_key,
-                &owner_key,
-                &[],
-                100,
-            )
-            .unwrap(),
-            vec![
-                &mut account_account,
-                &mut delegate_account,
-                &mut owner_account,
-            ],
-        )
-        .unwrap();
-
-        // approve delegate 2, with incorrect decimals
-        assert_eq!(
-            Err(TokenError::MintDecimalsMismatch.into()),
-            do_process_instruction(
-                approve_checked(
+                &mut store,
+            );
+            assert_authority_required(
+                authority,
+                set_send_only(
                     &program_id,
                     &account_key,
                     &mint_key,
-                    &delegate_key,
-                    &owner_key,
-                    &[],
-                    100,
-                    0 // <-- incorrect decimals
+                    &authority_key,
+                    &signers,
                 )
                 .unwrap(),
-                vec![
-                    &mut account_account,
-                    &mut mint_account,
-                    &mut delegate_account,
-                    &mut owner_account,
-                ],
-            )
-        );
-
-        // approve delegate 2, with incorrect mint
-        assert_eq!(
-            Err(TokenError::MintMismatch.into()),
-            do_process_instruction(
-                approve_checked(
+                &mut store,
+            );
+            assert_authority_required(
+                authority,
+                thaw_account(
                     &program_id,
                     &account_key,
-                    &account2_key, // <-- bad mint
-                    &delegate_key,
-                    &owner_key,
-                    &[],
-                    100,
-                    0
+                    &mint_key,
+                    &authority_key,
+                    &signers,
                 )
                 .unwrap(),
-                vec![
-                    &mut account_account,
-                    &mut account2_account, // <-- bad mint
-                    &mut delegate_account,
-                    &mut owner_account,
-                ],
-            )
-        );
-
-        // approve delegate 2
-        do_process_instruction(
-            approve_checked(
-                &program_id,
-                &account_key,
-                &mint_key,
-                &delegate_key,
-                &owner_key,
-                &[],
-                100,
-                2,
-            )
-            .unwrap(),
-            vec![
-                &mut account_account,
-                &mut mint_account,
-                &mut delegate_account,
-                &mut owner_account,
-            ],
-        )
-        .unwrap();
-
-        // revoke delegate
-        do_process_instruction(
-            revoke(&program_id, &account_key, &owner_key, &[]).unwrap(),
-            vec![&mut account_account, &mut owner_account],
-        )
-        .unwrap();
-    }
-
-    #[test]
-    fn test_set_authority_dups() {
-        let program_id = crate::id();
-        let account1_key = Pubkey::new_unique();
-        let mut account1_account = SolanaAccount::new(
-            account_minimum_balance(),
-            Account::get_packed_len(),
-            &program_id,
-        );
-        let account1_info: AccountInfo = (&account1_key, true, &mut account1_account).into();
-        let owner_key = Pubkey::new_unique();
-        let mint_key = Pubkey::new_unique();
-        let mut mint_account =
-            SolanaAccount::new(mint_minimum_balance(), Mint::get_packed_len(), &program_id);
-        let mint_info: AccountInfo = (&mint_key, true, &mut mint_account).into();
-        let rent_key = rent::id();
-        let mut rent_sysvar = rent_sysvar();
-        let rent_info: AccountInfo = (&rent_key, false, &mut rent_sysvar).into();
-
-        // create mint
-        do_process_instruction_dups(
-            initialize_mint(&program_id, &mint_key, &mint_key, Some(&mint_key), 2).unwrap(),
-            vec![mint_info.clone(), rent_info.clone()],
-        )
-        .unwrap();
-
-        // create account
-        do_process_instruction_dups(
-            initialize_account(&program_id, &account1_key, &mint_key, &account1_key).unwrap(),
-            vec![
-                account1_info.clone(),
-                mint_info.clone(),
-                account1_info.clone(),
-                rent_info.clone(),
-            ],
-        )
-        .unwrap();
-
-        // set mint_authority when currently self
-        do_process_instruction_dups(
-            set_authority(
-                &program_id,
-                &mint_key,
-                Some(&owner_key),
-                AuthorityType::MintTokens,
-                &mint_key,
-                &[],
-            )
-            .unwrap(),
-            vec![mint_info.clone(), mint_info.clone()],
-        )
-        .unwrap();
-
-        // set freeze_authority when currently self
-        do_process_instruction_dups(
-            set_authority(
-                &program_id,
-                &mint_key,
-                Some(&owner_key),
-                AuthorityType::FreezeAccount,
-                &mint_key,
-                &[],
-            )
-            .unwrap(),
-            vec![mint_info.clone(), mint_info.clone()],
-        )
-        .unwrap();
-
-        // set account owner when currently self
-        do_process_instruction_dups(
-            set_authority(
-                &program_id,
-                &account1_key,
-                Some(&owner_key),
-                AuthorityType::AccountOwner,
-                &account1_key,
-                &[],
-            )
-            .unwrap(),
-            vec![account1_info.clone(), account1_info.clone()],
-        )
-        .unwrap();
-
-        // set close_authority when currently self
-        let mut account = Account::unpack_unchecked(&account1_info.data.borrow()).unwrap();
-        account.close_authority = COption::Some(account1_key);
-        Account::pack(account, &mut account1_info.data.borrow_mut()).unwrap();
+                &mut store,
+            );
+            assert_authority_required(
+                authority,
+                set_authority(
+                    &program_id,
+                    &mint_key,
+                    Some(&authority_key),
+                    AuthorityType::FreezeAccount,
+                    &authority_key,
+                    &signers,
+                )
+                .unwrap(),
+                &mut store,
+            );
 
-        do_process_instruction_dups(
-            set_authority(
-                &program_id,
-                &account1_key,
-                Some(&owner_key),
-                AuthorityType::CloseAccount,
-                &account1_key,
-                &[],
-            )
-            .unwrap(),
-            vec![account1_info.clone(), account1_info.clone()],
-        )
-        .unwrap();
+            // mint authority handover
+            assert_authority_required(
+                authority,
+                propose_authority(
+                    &program_id,
+                    &mint_key,
+                    &authority_key,
+                    AuthorityType::MintTokens,
+                    &authority_key,
+                    &signers,
+                )
+                .unwrap(),
+                &mut store,
+            );
+            assert_authority_required(
+                authority,
+                accept_authority(
+                    &program_id,
+                    &mint_key,
+                    AuthorityType::MintTokens,
+                    &authority_key,
+                    &signers,
+                )
+                .unwrap(),
+                &mut store,
+            );
+            assert_authority_required(
+                authority,
+                propose_authority(
+                    &program_id,
+                    &mint_key,
+                    &Pubkey::new_unique(),
+                    AuthorityType::MintTokens,
+                    &authority_key,
+                    &signers,
+                )
+                .unwrap(),
+                &mut store,
+            );
+            assert_authority_required(
+                authority,
+                cancel_authority_proposal(
+                    &program_id,
+                    &mint_key,
+                    AuthorityType::MintTokens,
+                    &authority_key,
+                    &signers,
+                )
+                .unwrap(),
+                &mut store,
+            );
+            assert_authority_required(
+                authority,
+                set_authority_timelock(&program_id, &mint_key, &authority_key, &signers, 10)
+                    .unwrap(),
+                &mut store,
+            );
+            assert_authority_required(
+                authority,
+                queue_authority_change(
+                    &program_id,
+                    &mint_key,
+                    Some(&Pubkey::new_unique()),
+                    TimelockedAuthority::MintTokens,
+                    &authority_key,
+                    &signers,
+                )
+                .unwrap(),
+                &mut store,
+            );
+            assert_authority_required(
+                authority,
+                cancel_authority_change(&program_id, &mint_key, &authority_key, &signers).unwrap(),
+                &mut store,
+            );
+
+            // close the emptied account
+            assert_authority_required(
+                authority,
+                close_account(
+                    &program_id,
+                    &account_key,
+                    &Pubkey::new_unique(),
+                    &authority_key,
+                    &signers,
+                )
+                .unwrap(),
+                &mut store,
+            );
+            assert_eq!(store[&account_key].lamports, 0);
+            let account = Account::unpack_unchecked(&store[&account2_key].data).unwrap();
+            assert_eq!(account.amount, 900);
+        }
     }
 
     #[test]
-    fn test_set_authority() {
+    fn test_weighted_multisig() {
         let program_id = crate::id();
+        let multisig_key = Pubkey::new_unique();
+        let cfo_key = Pubkey::new_unique();
+        let operator_key = Pubkey::new_unique();
+        let operator2_key = Pubkey::new_unique();
+        let signer_keys = [&cfo_key, &operator_key, &operator2_key];
+        let mint_key = Pubkey::new_unique();
         let account_key = Pubkey::new_unique();
-        let mut account_account = SolanaAccount::new(
-            account_minimum_balance(),
-            Account::get_packed_len(),
-            &program_id,
-        );
         let account2_key = Pubkey::new_unique();
-        let mut account2_account = SolanaAccount::new(
-            account_minimum_balance(),
-            Account::get_packed_len(),
-            &program_id,
+        let mut store = HashMap::new();
+        store.insert(rent::id(), rent_sysvar());
+        store.insert(
+            multisig_key,
+            SolanaAccount::new(42, WeightedMultisig::get_packed_len(), &program_id),
+        );
+        store.insert(
+            mint_key,
+            SolanaAccount::new(
+                extended_mint_minimum_balance(),
+                Mint::EXTENDED_LEN,
+                &program_id,
+            ),
+        );
+        store.insert(
+            account_key,
+            SolanaAccount::new(
+                extended_account_minimum_balance(),
+                Account::EXTENDED_LEN,
+                &program_id,
+            ),
+        );
+        store.insert(
+            account2_key,
+            SolanaAccount::new(
+                extended_account_minimum_balance(),
+                Account::EXTENDED_LEN,
+                &program_id,
+            ),
         );
-        let owner_key = Pubkey::new_unique();
-        let mut owner_account = SolanaAccount::default();
-        let owner2_key = Pubkey::new_unique();
-        let mut owner2_account = SolanaAccount::default();
-        let owner3_key = Pubkey::new_unique();
-        let mut owner3_account = SolanaAccount::default();
-        let mint_key = Pubkey::new_unique();
-        let mut mint_account =
-            SolanaAccount::new(mint_minimum_balance(), Mint::get_packed_len(), &program_id);
-        let mint2_key = Pubkey::new_unique();
-        let mut mint2_account =
-            SolanaAccount::new(mint_minimum_balance(), Mint::get_packed_len(), &program_id);
-        let mut rent_sysvar = rent_sysvar();
-
-        // create new mint with owner
-        do_process_instruction(
-            initialize_mint(&program_id, &mint_key, &owner_key, None, 2).unwrap(),
-            vec![&mut mint_account, &mut rent_sysvar],
-        )
-        .unwrap();
-
-        // create mint with owner and freeze_authority
-        do_process_instruction(
-            initialize_mint(&program_id, &mint2_key, &owner_key, Some(&owner_key), 2).unwrap(),
-            vec![&mut mint2_account, &mut rent_sysvar],
-        )
-        .unwrap();
 
-        // invalid account
+        // builder rejects invalid weights and thresholds
         assert_eq!(
-            Err(ProgramError::UninitializedAccount),
-            do_process_instruction(
-                set_authority(
-                    &program_id,
-                    &account_key,
-                    Some(&owner2_key),
-                    AuthorityType::AccountOwner,
-                    &owner_key,
-                    &[]
-                )
-                .unwrap(),
-                vec![&mut account_account, &mut owner_account],
-            )
+            Err(ProgramError::MissingRequiredSignature),
+            initialize_weighted_multisig(&program_id, &multisig_key, &signer_keys, &[2, 1, 1], 0)
+        );
+        assert_eq!(
+            Err(ProgramError::MissingRequiredSignature),
+            initialize_weighted_multisig(&program_id, &multisig_key, &signer_keys, &[2, 1, 1], 5)
+        );
+        assert_eq!(
+            Err(ProgramError::MissingRequiredSignature),
+            initialize_weighted_multisig(&program_id, &multisig_key, &signer_keys, &[2, 1], 3)
         );
 
-        // create account
-        do_process_instruction(
-            initialize_account(&program_id, &account_key, &mint_key, &owner_key).unwrap(),
-            vec![
-                &mut account_account,
-                &mut mint_account,
-                &mut owner_account,
-                &mut rent_sysvar,
-            ],
-        )
-        .unwrap();
-
-        // create another account
-        do_process_instruction(
-            initialize_account(&program_id, &account2_key, &mint2_key, &owner_key).unwrap(),
-            vec![
-                &mut account2_account,
-                &mut mint2_account,
-                &mut owner_account,
-                &mut rent_sysvar,
-            ],
-        )
-        .unwrap();
-
-        // missing owner
+        // multisig is not rent exempt
         assert_eq!(
-            Err(TokenError::OwnerMismatch.into()),
-            do_process_instruction(
-                set_authority(
+            Err(TokenError::NotRentExempt.into()),
+            do_process_instruction_with_store(
+                initialize_weighted_multisig(
                     &program_id,
-                    &account_key,
-                    Some(&owner_key),
-                    AuthorityType::AccountOwner,
-                    &owner2_key,
-                    &[]
+                    &multisig_key,
+                    &signer_keys,
+                    &[2, 1, 1],
+                    3
                 )
                 .unwrap(),
-                vec![&mut account_account, &mut owner2_account],
+                &mut store,
             )
         );
+        store.get_mut(&multisig_key).unwrap().lamports =
+            Rent::default().minimum_balance(WeightedMultisig::get_packed_len());
 
-        // owner did not sign
-        let mut instruction = set_authority(
-            &program_id,
-            &account_key,
-            Some(&owner2_key),
-            AuthorityType::AccountOwner,
-            &owner_key,
-            &[],
-        )
-        .unwrap();
-        instruction.accounts[1].is_signer = false;
+        // weights do not match the signers
+        let mut instruction =
+            initialize_weighted_multisig(&program_id, &multisig_key, &signer_keys, &[2, 1, 1], 3)
+                .unwrap();
+        instruction.data = TokenInstruction::InitializeWeightedMultisig {
+            threshold: 3,
+            weights: vec![2, 1],
+        }
+        .pack();
         assert_eq!(
-            Err(ProgramError::MissingRequiredSignature),
-            do_process_instruction(instruction, vec![&mut account_account, &mut owner_account,],)
+            Err(TokenError::InvalidNumberOfProvidedSigners.into()),
+            do_process_instruction_with_store(instruction.clone(), &mut store)
         );
 
-        // wrong authority type
+        // threshold exceeds the total weight
+        instruction.data = TokenInstruction::InitializeWeightedMultisig {
+            threshold: 5,
+            weights: vec![2, 1, 1],
+        }
+        .pack();
         assert_eq!(
-            Err(TokenError::AuthorityTypeNotSupported.into()),
-            do_process_instruction(
-                set_authority(
-                    &program_id,
-                    &account_key,
-                    Some(&owner2_key),
-                    AuthorityType::FreezeAccount,
-                    &owner_key,
-                    &[],
-                )
+            Err(TokenError::InvalidWeightThreshold.into()),
+            do_process_instruction_with_store(instruction, &mut store)
+        );
+
+        // create weighted multisig
+        do_process_instruction_with_store(
+            initialize_weighted_multisig(&program_id, &multisig_key, &signer_keys, &[2, 1, 1], 3)
                 .unwrap(),
-                vec![&mut account_account, &mut owner_account],
-            )
+            &mut store,
+        )
+        .unwrap();
+        let multisig = WeightedMultisig::unpack(&store[&multisig_key].data).unwrap();
+        assert_eq!(multisig.threshold, 3);
+        assert_eq!(multisig.n, 3);
+        assert_eq!(
+            multisig.signers[..3],
+            [cfo_key, operator_key, operator2_key]
         );
+        assert_eq!(multisig.weights[..3], [2, 1, 1]);
 
-        // account owner may not be set to None
+        // multisig already initialized
         assert_eq!(
-            Err(TokenError::InvalidInstruction.into()),
-            do_process_instruction(
-                set_authority(
+            Err(TokenError::AlreadyInUse.into()),
+            do_process_instruction_with_store(
+                initialize_weighted_multisig(
                     &program_id,
-                    &account_key,
-                    None,
-                    AuthorityType::AccountOwner,
-                    &owner_key,
-                    &[],
+                    &multisig_key,
+                    &signer_keys,
+                    &[2, 1, 1],
+                    3
                 )
                 .unwrap(),
-                vec![&mut account_account, &mut owner_account],
+                &mut store,
             )
         );
 
-        // set delegate
-        do_process_instruction(
-            approve(
-                &program_id,
-                &account_key,
-                &owner2_key,
-                &owner_key,
-                &[],
-                u64::MAX,
-            )
-            .unwrap(),
-            vec![
-                &mut account_account,
-                &mut owner2_account,
-                &mut owner_account,
-            ],
-        )
-        .unwrap();
-        let account = Account::unpack_unchecked(&account_account.data).unwrap();
-        assert_eq!(account.delegate, COption::Some(owner2_key));
-        assert_eq!(account.delegated_amount, u64::MAX);
-
-        // set owner
-        do_process_instruction(
-            set_authority(
-                &program_id,
-                &account_key,
-                Some(&owner3_key),
-                AuthorityType::AccountOwner,
-                &owner_key,
-                &[],
-            )
-            .unwrap(),
-            vec![&mut account_account, &mut owner_account],
+        // create mint and accounts owned by the weighted multisig
+        do_process_instruction_with_store(
+            initialize_mint(&program_id, &mint_key, &multisig_key, None, 2).unwrap(),
+            &mut store,
         )
         .unwrap();
-
-        // check delegate cleared
-        let account = Account::unpack_unchecked(&account_account.data).unwrap();
-        assert_eq!(account.delegate, COption::None);
-        assert_eq!(account.delegated_amount, 0);
-
-        // set owner without existing delegate
-        do_process_instruction(
-            set_authority(
-                &program_id,
-                &account_key,
-                Some(&owner2_key),
-                AuthorityType::AccountOwner,
-                &owner3_key,
-                &[],
-            )
-            .unwrap(),
-            vec![&mut account_account, &mut owner3_account],
+        do_process_instruction_with_store(
+            initialize_account(&program_id, &account_key, &mint_key, &multisig_key).unwrap(),
+            &mut store,
         )
         .unwrap();
-
-        // set close_authority
-        do_process_instruction(
-            set_authority(
-                &program_id,
-                &account_key,
-                Some(&owner2_key),
-                AuthorityType::CloseAccount,
-                &owner2_key,
-                &[],
-            )
-            .unwrap(),
-            vec![&mut account_account, &mut owner2_account],
+        do_process_instruction_with_store(
+            initialize_account(&program_id, &account2_key, &mint_key, &cfo_key).unwrap(),
+            &mut store,
         )
         .unwrap();
 
-        // close_authority may be set to None
-        do_process_instruction(
-            set_authority(
-                &program_id,
-                &account_key,
-                None,
-                AuthorityType::CloseAccount,
-                &owner2_key,
-                &[],
+        // the CFO alone falls short of the threshold
+        assert_eq!(
+            Err(ProgramError::MissingRequiredSignature),
+            do_process_instruction_with_store(
+                mint_to(
+                    &program_id,
+                    &mint_key,
+                    &account_key,
+                    &multisig_key,
+                    &[&cfo_key],
+                    1000,
+                )
+                .unwrap(),
+                &mut store,
             )
-            .unwrap(),
-            vec![&mut account_account, &mut owner2_account],
-        )
-        .unwrap();
+        );
 
-        // wrong owner
+        // so do both operators
         assert_eq!(
-            Err(TokenError::OwnerMismatch.into()),
-            do_process_instruction(
-                set_authority(
+            Err(ProgramError::MissingRequiredSignature),
+            do_process_instruction_with_store(
+                mint_to(
                     &program_id,
                     &mint_key,
-                    Some(&owner3_key),
-                    AuthorityType::MintTokens,
-                    &owner2_key,
-                    &[]
+                    &account_key,
+                    &multisig_key,
+                    &[&operator_key, &operator2_key],
+                    1000,
                 )
                 .unwrap(),
-                vec![&mut mint_account, &mut owner2_account],
+                &mut store,
             )
         );
 
-        // owner did not sign
-        let mut instruction = set_authority(
-            &program_id,
-            &mint_key,
-            Some(&owner2_key),
-            AuthorityType::MintTokens,
-            &owner_key,
-            &[],
-        )
-        .unwrap();
-        instruction.accounts[1].is_signer = false;
+        // a signer counts once however often it is passed
         assert_eq!(
             Err(ProgramError::MissingRequiredSignature),
-            do_process_instruction(instruction, vec![&mut mint_account, &mut owner_account],)
-        );
-
-        // cannot freeze
-        assert_eq!(
-            Err(TokenError::MintCannotFreeze.into()),
-            do_process_instruction(
-                set_authority(
+            do_process_instruction_with_store(
+                mint_to(
                     &program_id,
                     &mint_key,
-                    Some(&owner2_key),
-                    AuthorityType::FreezeAccount,
-                    &owner_key,
-                    &[],
+                    &account_key,
+                    &multisig_key,
+                    &[&operator_key, &operator_key, &operator_key],
+                    1000,
                 )
                 .unwrap(),
-                vec![&mut mint_account, &mut owner_account],
+                &mut store,
             )
         );
 
-        // set owner
-        do_process_instruction(
-            set_authority(
-                &program_id,
-                &mint_key,
-                Some(&owner2_key),
-                AuthorityType::MintTokens,
-                &owner_key,
-                &[],
-            )
-            .unwrap(),
-            vec![&mut mint_account, &mut owner_account],
+        // a listed signer that did not sign
+        let mut instruction = mint_to(
+            &program_id,
+            &mint_key,
+            &account_key,
+            &multisig_key,
+            &[&cfo_key, &operator_key],
+            1000,
         )
         .unwrap();
+        instruction.accounts[4].is_signer = false;
+        assert_eq!(
+            Err(ProgramError::MissingRequiredSignature),
+            do_process_instruction_with_store(instruction, &mut store)
+        );
 
-        // set owner to None
-        do_process_instruction(
-            set_authority(
+        // the CFO and an operator meet the threshold
+        do_process_instruction_with_store(
+            mint_to(
                 &program_id,
                 &mint_key,
-                None,
-                AuthorityType::MintTokens,
-                &owner2_key,
-                &[],
+                &account_key,
+                &multisig_key,
+                &[&cfo_key, &operator_key],
+                1000,
             )
             .unwrap(),
-            vec![&mut mint_account, &mut owner2_account],
+            &mut store,
         )
         .unwrap();
 
-        // test unsetting mint_authority is one-way operation
-        assert_eq!(
-            Err(TokenError::FixedSupply.into()),
-            do_process_instruction(
-                set_authority(
+        // transfer approved by all signers
+        do_process_instruction_with_store(
+            with_mint(
+                transfer(
                     &program_id,
-                    &mint2_key,
-                    Some(&owner2_key),
-                    AuthorityType::MintTokens,
-                    &owner_key,
-                    &[]
+                    &account_key,
+                    &account2_key,
+                    &multisig_key,
+                    &[&operator2_key, &operator_key, &cfo_key],
+                    400,
                 )
                 .unwrap(),
-                vec![&mut mint_account, &mut owner_account],
-            )
+                &mint_key,
+            ),
+            &mut store,
+        )
+        .unwrap();
+        let account = Account::unpack_unchecked(&store[&account_key].data).unwrap();
+        assert_eq!(account.amount, 600);
+        let account = Account::unpack_unchecked(&store[&account2_key].data).unwrap();
+        assert_eq!(account.amount, 400);
+    }
+
+    #[test]
+    fn test_multisig_signer_updates() {
+        let program_id = crate::id();
+        let multisig_key = Pubkey::new_unique();
+        let multisig2_key = Pubkey::new_unique();
+        let signer_key = Pubkey::new_unique();
+        let signer2_key = Pubkey::new_unique();
+        let signer3_key = Pubkey::new_unique();
+        let new_signer_key = Pubkey::new_unique();
+        let mut store = HashMap::new();
+        store.insert(rent::id(), rent_sysvar());
+        store.insert(
+            multisig_key,
+            SolanaAccount::new(
+                multisig_minimum_balance(),
+                Multisig::get_packed_len(),
+                &program_id,
+            ),
+        );
+        store.insert(
+            multisig2_key,
+            SolanaAccount::new(
+                multisig_minimum_balance(),
+                Multisig::get_packed_len(),
+                &program_id,
+            ),
         );
 
-        // set freeze_authority
-        do_process_instruction(
-            set_authority(
+        // create multisig
+        do_process_instruction_with_store(
+            initialize_multisig(
                 &program_id,
-                &mint2_key,
-                Some(&owner2_key),
-                AuthorityType::FreezeAccount,
-                &owner_key,
-                &[],
+                &multisig_key,
+                &[&signer_key, &signer2_key, &signer3_key],
+                2,
             )
             .unwrap(),
-            vec![&mut mint2_account, &mut owner_account],
+            &mut store,
         )
         .unwrap();
 
-        // test unsetting freeze_authority is one-way operation
-        do_process_instruction(
-            set_authority(
+        // add signer without enough signatures
+        assert_eq!(
+            Err(ProgramError::MissingRequiredSignature),
+            do_process_instruction_with_store(
+                add_signer(&program_id, &multisig_key, &new_signer_key, &[&signer_key]).unwrap(),
+                &mut store,
+            )
+        );
+
+        // add signer
+        do_process_instruction_with_store(
+            add_signer(
                 &program_id,
-                &mint2_key,
-                None,
-                AuthorityType::FreezeAccount,
-                &owner2_key,
-                &[],
+                &multisig_key,
+                &new_signer_key,
+                &[&signer_key, &signer2_key],
             )
             .unwrap(),
-            vec![&mut mint2_account, &mut owner2_account],
+            &mut store,
         )
         .unwrap();
+        let multisig = Multisig::unpack(&store[&multisig_key].data).unwrap();
+        assert_eq!(multisig.n, 4);
+        assert_eq!(multisig.signers[3], new_signer_key);
 
+        // add an existing signer
         assert_eq!(
-            Err(TokenError::MintCannotFreeze.into()),
-            do_process_instruction(
-                set_authority(
+            Err(TokenError::DuplicateSigner.into()),
+            do_process_instruction_with_store(
+                add_signer(
                     &program_id,
-                    &mint2_key,
-                    Some(&owner2_key),
-                    AuthorityType::FreezeAccount,
-                    &owner_key,
-                    &[],
+                    &multisig_key,
+                    &signer3_key,
+                    &[&signer_key, &signer2_key],
                 )
                 .unwrap(),
-                vec![&mut mint2_account, &mut owner2_account],
+                &mut store,
             )
         );
-    }
 
-    #[test]
-    fn test_mint_to_dups() {
-        let program_id = crate::id();
-        let account1_key = Pubkey::new_unique();
-        let mut account1_account = SolanaAccount::new(
-            account_minimum_balance(),
-            Account::get_packed_len(),
-            &program_id,
+        // fill the multisig, then exceed the maximum number of signers
+        for _ in 4..MAX_SIGNERS {
+            do_process_instruction_with_store(
+                add_signer(
+                    &program_id,
+                    &multisig_key,
+                    &Pubkey::new_unique(),
+                    &[&signer_key, &signer2_key],
+                )
+                .unwrap(),
+                &mut store,
+            )
+            .unwrap();
+        }
+        assert_eq!(
+            Err(TokenError::InvalidNumberOfProvidedSigners.into()),
+            do_process_instruction_with_store(
+                add_signer(
+                    &program_id,
+                    &multisig_key,
+                    &Pubkey::new_unique(),
+                    &[&signer_key, &signer2_key],
+                )
+                .unwrap(),
+                &mut store,
+            )
         );
-        let account1_info: AccountInfo = (&account1_key, true, &mut account1_account).into();
-        let owner_key = Pubkey::new_unique();
-        let mut owner_account = SolanaAccount::default();
-        let owner_info: AccountInfo = (&owner_key, true, &mut owner_account).into();
-        let mint_key = Pubkey::new_unique();
-        let mut mint_account =
-            SolanaAccount::new(mint_minimum_balance(), Mint::get_packed_len(), &program_id);
-        let mint_info: AccountInfo = (&mint_key, true, &mut mint_account).into();
-        let rent_key = rent::id();
-        let mut rent_sysvar = rent_sysvar();
-        let rent_info: AccountInfo = (&rent_key, false, &mut rent_sysvar).into();
 
-        // create mint
-        do_process_instruction_dups(
-            initialize_mint(&program_id, &mint_key, &mint_key, None, 2).unwrap(),
-            vec![mint_info.clone(), rent_info.clone()],
-        )
-        .unwrap();
+        // threshold outside the signer bounds
+        assert_eq!(
+            Err(ProgramError::InvalidArgument),
+            set_threshold(&program_id, &multisig_key, 0, &[&signer_key, &signer2_key])
+        );
+        let mut instruction =
+            set_threshold(&program_id, &multisig_key, 1, &[&signer_key, &signer2_key]).unwrap();
+        instruction.data = TokenInstruction::SetThreshold { m: 0 }.pack();
+        assert_eq!(
+            Err(TokenError::InvalidNumberOfRequiredSigners.into()),
+            do_process_instruction_with_store(instruction.clone(), &mut store)
+        );
+        instruction.data = TokenInstruction::SetThreshold {
+            m: MAX_SIGNERS as u8 + 1,
+        }
+        .pack();
+        assert_eq!(
+            Err(TokenError::InvalidNumberOfRequiredSigners.into()),
+            do_process_instruction_with_store(instruction, &mut store)
+        );
 
-        // create account
-        do_process_instruction_dups(
-            initialize_account(&program_id, &account1_key, &mint_key, &owner_key).unwrap(),
-            vec![
-                account1_info.clone(),
-                mint_info.clone(),
-                owner_info.clone(),
-                rent_info.clone(),
-            ],
+        // raise the threshold
+        assert_eq!(
+            Err(ProgramError::MissingRequiredSignature),
+            do_process_instruction_with_store(
+                set_threshold(&program_id, &multisig_key, 3, &[&signer_key]).unwrap(),
+                &mut store,
+            )
+        );
+        do_process_instruction_with_store(
+            set_threshold(&program_id, &multisig_key, 3, &[&signer_key, &signer2_key]).unwrap(),
+            &mut store,
         )
         .unwrap();
+        let multisig = Multisig::unpack(&store[&multisig_key].data).unwrap();
+        assert_eq!(multisig.m, 3);
 
-        // mint_to when mint_authority is self
-        do_process_instruction_dups(
-            mint_to(&program_id, &mint_key, &account1_key, &mint_key, &[], 42).unwrap(),
-            vec![mint_info.clone(), account1_info.clone(), mint_info.clone()],
-        )
-        .unwrap();
+        // the raised threshold applies to further updates
+        assert_eq!(
+            Err(ProgramError::MissingRequiredSignature),
+            do_process_instruction_with_store(
+                remove_signer(
+                    &program_id,
+                    &multisig_key,
+                    &signer2_key,
+                    &[&signer_key, &signer2_key],
+                )
+                .unwrap(),
+                &mut store,
+            )
+        );
 
-        // mint_to_checked when mint_authority is self
-        do_process_instruction_dups(
-            mint_to_checked(&program_id, &mint_key, &account1_key, &mint_key, &[], 42, 2).unwrap(),
-            vec![mint_info.clone(), account1_info.clone(), mint_info.clone()],
-        )
-        .unwrap();
+        // remove a key that is not a signer
+        assert_eq!(
+            Err(TokenError::SignerNotFound.into()),
+            do_process_instruction_with_store(
+                remove_signer(
+                    &program_id,
+                    &multisig_key,
+                    &Pubkey::new_unique(),
+                    &[&signer_key, &signer2_key, &signer3_key],
+                )
+                .unwrap(),
+                &mut store,
+            )
+        );
 
-        // mint_to when mint_authority is account owner
-        let mut mint = Mint::unpack_unchecked(&mint_info.data.borrow()).unwrap();
-        mint.mint_authority = COption::Some(account1_key);
-        Mint::pack(mint, &mut mint_info.data.borrow_mut()).unwrap();
-        do_process_instruction_dups(
-            mint_to(
+        // remove signer, keeping the others in order
+        do_process_instruction_with_store(
+            remove_signer(
                 &program_id,
-                &mint_key,
-                &account1_key,
-                &account1_key,
-                &[],
-                42,
+                &multisig_key,
+                &signer2_key,
+                &[&signer_key, &signer2_key, &signer3_key],
             )
             .unwrap(),
-            vec![
-                mint_info.clone(),
-                account1_info.clone(),
-                account1_info.clone(),
-            ],
+            &mut store,
         )
         .unwrap();
+        let multisig = Multisig::unpack(&store[&multisig_key].data).unwrap();
+        assert_eq!(multisig.n as usize, MAX_SIGNERS - 1);
+        assert_eq!(
+            multisig.signers[..3],
+            [signer_key, signer3_key, new_signer_key]
+        );
+        assert_eq!(multisig.signers[MAX_SIGNERS - 1], Pubkey::default());
 
-        // mint_to_checked when mint_authority is account owner
-        do_process_instruction_dups(
-            mint_to(
-                &program_id,
-                &mint_key,
-                &account1_key,
-                &account1_key,
-                &[],
-                42,
+        // the removed signer no longer counts
+        assert_eq!(
+            Err(ProgramError::MissingRequiredSignature),
+            do_process_instruction_with_store(
+                set_threshold(
+                    &program_id,
+                    &multisig_key,
+                    2,
+                    &[&signer_key, &signer2_key, &signer3_key],
+                )
+                .unwrap(),
+                &mut store,
             )
-            .unwrap(),
-            vec![
-                mint_info.clone(),
-                account1_info.clone(),
-                account1_info.clone(),
-            ],
+        );
+
+        // create a 2 of 2 multisig
+        do_process_instruction_with_store(
+            initialize_multisig(&program_id, &multisig2_key, &[&signer_key, &signer2_key], 2)
+                .unwrap(),
+            &mut store,
+        )
+        .unwrap();
+
+        // removing a signer would leave fewer signers than required
+        assert_eq!(
+            Err(TokenError::InvalidNumberOfRequiredSigners.into()),
+            do_process_instruction_with_store(
+                remove_signer(
+                    &program_id,
+                    &multisig2_key,
+                    &signer2_key,
+                    &[&signer_key, &signer2_key],
+                )
+                .unwrap(),
+                &mut store,
+            )
+        );
+
+        // lower the threshold first
+        do_process_instruction_with_store(
+            set_threshold(&program_id, &multisig2_key, 1, &[&signer_key, &signer2_key]).unwrap(),
+            &mut store,
+        )
+        .unwrap();
+        do_process_instruction_with_store(
+            remove_signer(&program_id, &multisig2_key, &signer2_key, &[&signer_key]).unwrap(),
+            &mut store,
         )
         .unwrap();
+        let multisig = Multisig::unpack(&store[&multisig2_key].data).unwrap();
+        assert_eq!(multisig.m, 1);
+        assert_eq!(multisig.n, 1);
+        assert_eq!(multisig.signers[0], signer_key);
+
+        // the last signer cannot be removed
+        assert_eq!(
+            Err(TokenError::InvalidNumberOfProvidedSigners.into()),
+            do_process_instruction_with_store(
+                remove_signer(&program_id, &multisig2_key, &signer_key, &[&signer_key]).unwrap(),
+                &mut store,
+            )
+        );
     }
 
     #[test]
-    fn test_mint_to() {
+    fn test_multisig_proposals() {
         let program_id = crate::id();
+        let multisig_key = Pubkey::new_unique();
+        let signer_key = Pubkey::new_unique();
+        let signer2_key = Pubkey::new_unique();
+        let signer3_key = Pubkey::new_unique();
+        let proposal_key = Pubkey::new_unique();
+        let proposal2_key = Pubkey::new_unique();
+        let proposal3_key = Pubkey::new_unique();
+        let mint_key = Pubkey::new_unique();
         let account_key = Pubkey::new_unique();
-        let mut account_account = SolanaAccount::new(
-            account_minimum_balance(),
-            Account::get_packed_len(),
-            &program_id,
-        );
-        let account2_key = Pubkey::new_unique();
-        let mut account2_account = SolanaAccount::new(
-            account_minimum_balance(),
-            Account::get_packed_len(),
-            &program_id,
-        );
-        let account3_key = Pubkey::new_unique();
-        let mut account3_account = SolanaAccount::new(
-            account_minimum_balance(),
-            Account::get_packed_len(),
-            &program_id,
+        let mut store = HashMap::new();
+        store.insert(rent::id(), rent_sysvar());
+        store.insert(sysvar::clock::id(), clock_sysvar(100));
+        store.insert(
+            multisig_key,
+            SolanaAccount::new(
+                multisig_minimum_balance(),
+                Multisig::get_packed_len(),
+                &program_id,
+            ),
         );
-        let mismatch_key = Pubkey::new_unique();
-        let mut mismatch_account = SolanaAccount::new(
-            account_minimum_balance(),
-            Account::get_packed_len(),
-            &program_id,
+        for key in &[proposal_key, proposal2_key, proposal3_key] {
+            store.insert(
+                *key,
+                SolanaAccount::new(
+                    Rent::default().minimum_balance(MultisigProposal::get_packed_len()),
+                    MultisigProposal::get_packed_len(),
+                    &program_id,
+                ),
+            );
+        }
+        store.insert(
+            mint_key,
+            SolanaAccount::new(
+                extended_mint_minimum_balance(),
+                Mint::EXTENDED_LEN,
+                &program_id,
+            ),
         );
-        let owner_key = Pubkey::new_unique();
-        let mut owner_account = SolanaAccount::default();
-        let owner2_key = Pubkey::new_unique();
-        let mut owner2_account = SolanaAccount::default();
-        let mint_key = Pubkey::new_unique();
-        let mut mint_account =
-            SolanaAccount::new(mint_minimum_balance(), Mint::get_packed_len(), &program_id);
-        let mint2_key = Pubkey::new_unique();
-        let uninitialized_key = Pubkey::new_unique();
-        let mut uninitialized_account = SolanaAccount::new(
-            account_minimum_balance(),
-            Account::get_packed_len(),
-            &program_id,
+        store.insert(
+            account_key,
+            SolanaAccount::new(
+                extended_account_minimum_balance(),
+                Account::EXTENDED_LEN,
+                &program_id,
+            ),
         );
-        let mut rent_sysvar = rent_sysvar();
 
-        // create new mint with owner
-        do_process_instruction(
-            initialize_mint(&program_id, &mint_key, &owner_key, None, 2).unwrap(),
-            vec![&mut mint_account, &mut rent_sysvar],
+        // create a 2 of 3 multisig holding the mint authority
+        do_process_instruction_with_store(
+            initialize_multisig(
+                &program_id,
+                &multisig_key,
+                &[&signer_key, &signer2_key, &signer3_key],
+                2,
+            )
+            .unwrap(),
+            &mut store,
         )
         .unwrap();
-
-        // create account
-        do_process_instruction(
-            initialize_account(&program_id, &account_key, &mint_key, &owner_key).unwrap(),
-            vec![
-                &mut account_account,
-                &mut mint_account,
-                &mut owner_account,
-                &mut rent_sysvar,
-            ],
+        do_process_instruction_with_store(
+            initialize_mint(&program_id, &mint_key, &multisig_key, None, 2).unwrap(),
+            &mut store,
         )
         .unwrap();
-
-        // create another account
-        do_process_instruction(
-            initialize_account(&program_id, &account2_key, &mint_key, &owner_key).unwrap(),
-            vec![
-                &mut account2_account,
-                &mut mint_account,
-                &mut owner_account,
-                &mut rent_sysvar,
-            ],
+        do_process_instruction_with_store(
+            initialize_account(&program_id, &account_key, &mint_key, &signer_key).unwrap(),
+            &mut store,
         )
         .unwrap();
-
-        // create another account
-        do_process_instruction(
-            initialize_account(&program_id, &account3_key, &mint_key, &owner_key).unwrap(),
-            vec![
-                &mut account3_account,
-                &mut mint_account,
-                &mut owner_account,
-                &mut rent_sysvar,
-            ],
+        let proposed = mint_to(
+            &program_id,
+            &mint_key,
+            &account_key,
+            &multisig_key,
+            &[],
+            1000,
         )
         .unwrap();
 
-        // create mismatch account
-        do_process_instruction(
-            initialize_account(&program_id, &mismatch_key, &mint_key, &owner_key).unwrap(),
-            vec![
-                &mut mismatch_account,
-                &mut mint_account,
-                &mut owner_account,
-                &mut rent_sysvar,
-            ],
+        // proposer is not a signer of the multisig
+        assert_eq!(
+            Err(TokenError::SignerNotFound.into()),
+            do_process_instruction_with_store(
+                create_proposal(
+                    &program_id,
+                    &proposal_key,
+                    &multisig_key,
+                    &Pubkey::new_unique(),
+                    &proposed,
+                    1000,
+                )
+                .unwrap(),
+                &mut store,
+            )
+        );
+
+        // proposer did not sign
+        let mut instruction = create_proposal(
+            &program_id,
+            &proposal_key,
+            &multisig_key,
+            &signer_key,
+            &proposed,
+            1000,
         )
         .unwrap();
-        let mut account = Account::unpack_unchecked(&mismatch_account.data).unwrap();
-        account.mint = mint2_key;
-        Account::pack(account, &mut mismatch_account.data).unwrap();
+        instruction.accounts[2].is_signer = false;
+        assert_eq!(
+            Err(ProgramError::MissingRequiredSignature),
+            do_process_instruction_with_store(instruction, &mut store)
+        );
 
-        // mint to
-        do_process_instruction(
-            mint_to(&program_id, &mint_key, &account_key, &owner_key, &[], 42).unwrap(),
-            vec![&mut mint_account, &mut account_account, &mut owner_account],
+        // proposals cannot propose proposal instructions
+        let mut instruction = create_proposal(
+            &program_id,
+            &proposal_key,
+            &multisig_key,
+            &signer_key,
+            &proposed,
+            1000,
         )
         .unwrap();
+        instruction.data = TokenInstruction::CreateProposal {
+            expiry: 1000,
+            instruction_data: TokenInstruction::ExecuteProposal.pack(),
+        }
+        .pack();
+        assert_eq!(
+            Err(TokenError::InvalidInstruction.into()),
+            do_process_instruction_with_store(instruction, &mut store)
+        );
 
-        let mint = Mint::unpack_unchecked(&mint_account.data).unwrap();
-        assert_eq!(mint.supply, 42);
-        let account = Account::unpack_unchecked(&account_account.data).unwrap();
-        assert_eq!(account.amount, 42);
-
-        // mint to another account to test supply accumulation
-        do_process_instruction(
-            mint_to(&program_id, &mint_key, &account2_key, &owner_key, &[], 42).unwrap(),
-            vec![&mut mint_account, &mut account2_account, &mut owner_account],
+        // create proposal
+        do_process_instruction_with_store(
+            create_proposal(
+                &program_id,
+                &proposal_key,
+                &multisig_key,
+                &signer_key,
+                &proposed,
+                1000,
+            )
+            .unwrap(),
+            &mut store,
         )
         .unwrap();
+        let proposal = MultisigProposal::unpack(&store[&proposal_key].data).unwrap();
+        assert_eq!(proposal.state, ProposalState::Pending);
+        assert_eq!(proposal.multisig, multisig_key);
+        assert_eq!(proposal.approvals(), [signer_key]);
+        assert_eq!(proposal.instruction_data(), &proposed.data[..]);
+        assert_eq!(
+            proposal.instruction_accounts(),
+            [mint_key, account_key, multisig_key]
+        );
 
-        let mint = Mint::unpack_unchecked(&mint_account.data).unwrap();
-        assert_eq!(mint.supply, 84);
-        let account = Account::unpack_unchecked(&account2_account.data).unwrap();
-        assert_eq!(account.amount, 42);
-
-        // missing signer
-        let mut instruction =
-            mint_to(&program_id, &mint_key, &account2_key, &owner_key, &[], 42).unwrap();
-        instruction.accounts[2].is_signer = false;
+        // proposal already initialized
         assert_eq!(
-            Err(ProgramError::MissingRequiredSignature),
-            do_process_instruction(
-                instruction,
-                vec![&mut mint_account, &mut account2_account, &mut owner_account],
+            Err(TokenError::AlreadyInUse.into()),
+            do_process_instruction_with_store(
+                create_proposal(
+                    &program_id,
+                    &proposal_key,
+                    &multisig_key,
+                    &signer_key,
+                    &proposed,
+                    1000,
+                )
+                .unwrap(),
+                &mut store,
             )
         );
 
-        // mismatch account
+        // one approval falls short of the threshold
         assert_eq!(
-            Err(TokenError::MintMismatch.into()),
-            do_process_instruction(
-                mint_to(&program_id, &mint_key, &mismatch_key, &owner_key, &[], 42).unwrap(),
-                vec![&mut mint_account, &mut mismatch_account, &mut owner_account],
+            Err(ProgramError::MissingRequiredSignature),
+            do_process_instruction_with_store(
+                execute_proposal(&program_id, &proposal_key, &multisig_key, &proposed).unwrap(),
+                &mut store,
             )
         );
 
-        // missing owner
+        // approvals by the proposer again or by a non-signer
         assert_eq!(
-            Err(TokenError::OwnerMismatch.into()),
-            do_process_instruction(
-                mint_to(&program_id, &mint_key, &account2_key, &owner2_key, &[], 42).unwrap(),
-                vec![
-                    &mut mint_account,
-                    &mut account2_account,
-                    &mut owner2_account,
-                ],
+            Err(TokenError::AlreadyApproved.into()),
+            do_process_instruction_with_store(
+                approve_proposal(&program_id, &proposal_key, &multisig_key, &signer_key).unwrap(),
+                &mut store,
             )
         );
-
-        // uninitialized destination account
         assert_eq!(
-            Err(ProgramError::UninitializedAccount),
-            do_process_instruction(
-                mint_to(
+            Err(TokenError::SignerNotFound.into()),
+            do_process_instruction_with_store(
+                approve_proposal(
                     &program_id,
-                    &mint_key,
-                    &uninitialized_key,
-                    &owner_key,
-                    &[],
-                    42
+                    &proposal_key,
+                    &multisig_key,
+                    &Pubkey::new_unique(),
                 )
                 .unwrap(),
-                vec![
-                    &mut mint_account,
-                    &mut uninitialized_account,
-                    &mut owner_account,
-                ],
+                &mut store,
             )
         );
 
-        // unset mint_authority and test minting fails
-        do_process_instruction(
-            set_authority(
-                &program_id,
-                &mint_key,
-                None,
-                AuthorityType::MintTokens,
-                &owner_key,
-                &[],
-            )
-            .unwrap(),
-            vec![&mut mint_account, &mut owner_account],
+        // a pending proposal does not stand in for the signers
+        let mut instruction = proposed.clone();
+        instruction
+            .accounts
+            .push(AccountMeta::new_readonly(proposal_key, false));
+        assert_eq!(
+            Err(ProgramError::MissingRequiredSignature),
+            do_process_instruction_with_store(instruction, &mut store)
+        );
+
+        // second signer approves in another transaction
+        do_process_instruction_with_store(
+            approve_proposal(&program_id, &proposal_key, &multisig_key, &signer2_key).unwrap(),
+            &mut store,
         )
         .unwrap();
+        let proposal = MultisigProposal::unpack(&store[&proposal_key].data).unwrap();
+        assert_eq!(proposal.approvals(), [signer_key, signer2_key]);
+
+        // execute with other accounts than proposed
+        let mut instruction =
+            execute_proposal(&program_id, &proposal_key, &multisig_key, &proposed).unwrap();
+        instruction.accounts[4].pubkey = Pubkey::new_unique();
         assert_eq!(
-            Err(TokenError::FixedSupply.into()),
-            do_process_instruction(
-                mint_to(&program_id, &mint_key, &account2_key, &owner_key, &[], 42).unwrap(),
-                vec![&mut mint_account, &mut account2_account, &mut owner_account],
+            Err(TokenError::ProposalAccountMismatch.into()),
+            do_process_instruction_with_store(instruction, &mut store)
+        );
+
+        // execute after expiry
+        store.insert(sysvar::clock::id(), clock_sysvar(1001));
+        assert_eq!(
+            Err(TokenError::ProposalExpired.into()),
+            do_process_instruction_with_store(
+                execute_proposal(&program_id, &proposal_key, &multisig_key, &proposed).unwrap(),
+                &mut store,
             )
         );
-    }
+        store.insert(sysvar::clock::id(), clock_sysvar(1000));
 
-    #[test]
-    fn test_burn_dups() {
-        let program_id = crate::id();
-        let account1_key = Pubkey::new_unique();
-        let mut account1_account = SolanaAccount::new(
-            account_minimum_balance(),
-            Account::get_packed_len(),
-            &program_id,
+        // execute
+        do_process_instruction_with_store(
+            execute_proposal(&program_id, &proposal_key, &multisig_key, &proposed).unwrap(),
+            &mut store,
+        )
+        .unwrap();
+        let account = Account::unpack_unchecked(&store[&account_key].data).unwrap();
+        assert_eq!(account.amount, 1000);
+        let proposal = MultisigProposal::unpack(&store[&proposal_key].data).unwrap();
+        assert_eq!(proposal.state, ProposalState::Executed);
+
+        // an executed proposal neither runs again nor stands in for the signers
+        assert_eq!(
+            Err(TokenError::ProposalNotPending.into()),
+            do_process_instruction_with_store(
+                execute_proposal(&program_id, &proposal_key, &multisig_key, &proposed).unwrap(),
+                &mut store,
+            )
+        );
+        let mut instruction = proposed.clone();
+        instruction
+            .accounts
+            .push(AccountMeta::new_readonly(proposal_key, false));
+        assert_eq!(
+            Err(ProgramError::MissingRequiredSignature),
+            do_process_instruction_with_store(instruction.clone(), &mut store)
         );
-        let account1_info: AccountInfo = (&account1_key, true, &mut account1_account).into();
-        let owner_key = Pubkey::new_unique();
-        let mut owner_account = SolanaAccount::default();
-        let owner_info: AccountInfo = (&owner_key, true, &mut owner_account).into();
-        let mint_key = Pubkey::new_unique();
-        let mut mint_account =
-            SolanaAccount::new(mint_minimum_balance(), Mint::get_packed_len(), &program_id);
-        let mint_info: AccountInfo = (&mint_key, true, &mut mint_account).into();
-        let rent_key = rent::id();
-        let mut rent_sysvar = rent_sysvar();
-        let rent_info: AccountInfo = (&rent_key, false, &mut rent_sysvar).into();
 
-        // create mint
-        do_process_instruction_dups(
-            initialize_mint(&program_id, &mint_key, &owner_key, None, 2).unwrap(),
-            vec![mint_info.clone(), rent_info.clone()],
-        )
-        .unwrap();
+        // nor does a proposal marked as executing outside of its execution
+        let mut proposal = MultisigProposal::unpack(&store[&proposal_key].data).unwrap();
+        proposal.state = ProposalState::Executing;
+        MultisigProposal::pack(proposal, &mut store.get_mut(&proposal_key).unwrap().data).unwrap();
+        assert_eq!(
+            Err(ProgramError::MissingRequiredSignature),
+            do_process_instruction_with_store(instruction, &mut store)
+        );
 
-        // create account
-        do_process_instruction_dups(
-            initialize_account(&program_id, &account1_key, &mint_key, &account1_key).unwrap(),
-            vec![
-                account1_info.clone(),
-                mint_info.clone(),
-                account1_info.clone(),
-                rent_info.clone(),
-            ],
+        // create a second proposal
+        do_process_instruction_with_store(
+            create_proposal(
+                &program_id,
+                &proposal2_key,
+                &multisig_key,
+                &signer2_key,
+                &proposed,
+                2000,
+            )
+            .unwrap(),
+            &mut store,
         )
         .unwrap();
 
-        // mint to account
-        do_process_instruction_dups(
-            mint_to(&program_id, &mint_key, &account1_key, &owner_key, &[], 1000).unwrap(),
-            vec![mint_info.clone(), account1_info.clone(), owner_info.clone()],
+        // only the proposer or the multisig may cancel
+        assert_eq!(
+            Err(TokenError::OwnerMismatch.into()),
+            do_process_instruction_with_store(
+                cancel_proposal(&program_id, &proposal2_key, &signer3_key, &[]).unwrap(),
+                &mut store,
+            )
+        );
+        let mut instruction =
+            cancel_proposal(&program_id, &proposal2_key, &signer2_key, &[]).unwrap();
+        instruction.accounts[1].is_signer = false;
+        assert_eq!(
+            Err(ProgramError::MissingRequiredSignature),
+            do_process_instruction_with_store(instruction, &mut store)
+        );
+
+        // cancel by the proposer
+        do_process_instruction_with_store(
+            cancel_proposal(&program_id, &proposal2_key, &signer2_key, &[]).unwrap(),
+            &mut store,
         )
         .unwrap();
+        let proposal = MultisigProposal::unpack(&store[&proposal2_key].data).unwrap();
+        assert_eq!(proposal.state, ProposalState::Cancelled);
+        assert_eq!(
+            Err(TokenError::ProposalNotPending.into()),
+            do_process_instruction_with_store(
+                approve_proposal(&program_id, &proposal2_key, &multisig_key, &signer_key).unwrap(),
+                &mut store,
+            )
+        );
 
-        // source-owner burn
-        do_process_instruction_dups(
-            burn(
+        // create a third proposal, approved by a signer that is then removed
+        let proposed = add_signer(&program_id, &multisig_key, &Pubkey::new_unique(), &[]).unwrap();
+        do_process_instruction_with_store(
+            create_proposal(
                 &program_id,
-                &mint_key,
-                &account1_key,
-                &account1_key,
-                &[],
-                500,
+                &proposal3_key,
+                &multisig_key,
+                &signer3_key,
+                &proposed,
+                2000,
             )
             .unwrap(),
-            vec![
-                account1_info.clone(),
-                mint_info.clone(),
-                account1_info.clone(),
-            ],
+            &mut store,
         )
         .unwrap();
-
-        // source-owner burn_checked
-        do_process_instruction_dups(
-            burn_checked(
+        do_process_instruction_with_store(
+            remove_signer(
                 &program_id,
-                &account1_key,
-                &mint_key,
-                &account1_key,
-                &[],
-                500,
-                2,
+                &multisig_key,
+                &signer3_key,
+                &[&signer_key, &signer2_key],
             )
             .unwrap(),
-            vec![
-                account1_info.clone(),
-                mint_info.clone(),
-                account1_info.clone(),
-            ],
-        )
-        .unwrap();
-
-        // mint-owner burn
-        do_process_instruction_dups(
-            mint_to(&program_id, &mint_key, &account1_key, &owner_key, &[], 1000).unwrap(),
-            vec![mint_info.clone(), account1_info.clone(), owner_info.clone()],
+            &mut store,
         )
         .unwrap();
-        let mut account = Account::unpack_unchecked(&account1_info.data.borrow()).unwrap();
-        account.owner = mint_key;
-        Account::pack(account, &mut account1_info.data.borrow_mut()).unwrap();
-        do_process_instruction_dups(
-            burn(&program_id, &account1_key, &mint_key, &mint_key, &[], 500).unwrap(),
-            vec![account1_info.clone(), mint_info.clone(), mint_info.clone()],
+        do_process_instruction_with_store(
+            approve_proposal(&program_id, &proposal3_key, &multisig_key, &signer_key).unwrap(),
+            &mut store,
         )
         .unwrap();
+        let proposal = MultisigProposal::unpack(&store[&proposal3_key].data).unwrap();
+        assert_eq!(proposal.approvals(), [signer_key]);
+        assert_eq!(
+            Err(ProgramError::MissingRequiredSignature),
+            do_process_instruction_with_store(
+                execute_proposal(&program_id, &proposal3_key, &multisig_key, &proposed).unwrap(),
+                &mut store,
+            )
+        );
 
-        // mint-owner burn_checked
-        do_process_instruction_dups(
-            burn_checked(
+        // cancel by the multisig
+        do_process_instruction_with_store(
+            cancel_proposal(
                 &program_id,
-                &account1_key,
-                &mint_key,
-                &mint_key,
-                &[],
-                500,
-                2,
+                &proposal3_key,
+                &multisig_key,
+                &[&signer_key, &signer2_key],
             )
             .unwrap(),
-            vec![account1_info.clone(), mint_info.clone(), mint_info.clone()],
+            &mut store,
         )
         .unwrap();
+        let proposal = MultisigProposal::unpack(&store[&proposal3_key].data).unwrap();
+        assert_eq!(proposal.state, ProposalState::Cancelled);
+    }
 
-        // source-delegate burn
-        do_process_instruction_dups(
-            mint_to(&program_id, &mint_key, &account1_key, &owner_key, &[], 1000).unwrap(),
-            vec![mint_info.clone(), account1_info.clone(), owner_info.clone()],
-        )
-        .unwrap();
-        let mut account = Account::unpack_unchecked(&account1_info.data.borrow()).unwrap();
-        account.delegated_amount = 1000;
-        account.delegate = COption::Some(account1_key);
-        account.owner = owner_key;
-        Account::pack(account, &mut account1_info.data.borrow_mut()).unwrap();
-        do_process_instruction_dups(
-            burn(
+    #[test]
+    fn test_multisig_integrity() {
+        let program_id = crate::id();
+        let multisig_key = Pubkey::new_unique();
+        let weighted_multisig_key = Pubkey::new_unique();
+        let signer_key = Pubkey::new_unique();
+        let signer2_key = Pubkey::new_unique();
+        let mut store = HashMap::new();
+        store.insert(rent::id(), rent_sysvar());
+        store.insert(
+            multisig_key,
+            SolanaAccount::new(
+                multisig_minimum_balance(),
+                Multisig::get_packed_len(),
                 &program_id,
-                &account1_key,
-                &mint_key,
-                &account1_key,
-                &[],
-                500,
+            ),
+        );
+        store.insert(
+            weighted_multisig_key,
+            SolanaAccount::new(
+                Rent::default().minimum_balance(WeightedMultisig::get_packed_len()),
+                WeightedMultisig::get_packed_len(),
+                &program_id,
+            ),
+        );
+
+        // duplicated signer
+        assert_eq!(
+            Err(TokenError::DuplicateSigner.into()),
+            do_process_instruction_with_store(
+                initialize_multisig(
+                    &program_id,
+                    &multisig_key,
+                    &[&signer_key, &signer2_key, &signer_key],
+                    2,
+                )
+                .unwrap(),
+                &mut store,
             )
-            .unwrap(),
-            vec![
-                account1_info.clone(),
-                mint_info.clone(),
-                account1_info.clone(),
-            ],
+        );
+        assert_eq!(
+            Err(TokenError::DuplicateSigner.into()),
+            do_process_instruction_with_store(
+                initialize_weighted_multisig(
+                    &program_id,
+                    &weighted_multisig_key,
+                    &[&signer_key, &signer_key],
+                    &[1, 1],
+                    2,
+                )
+                .unwrap(),
+                &mut store,
+            )
+        );
+
+        // multisig among its own signers
+        assert_eq!(
+            Err(TokenError::MultisigSelfSigner.into()),
+            do_process_instruction_with_store(
+                initialize_multisig(&program_id, &multisig_key, &[&signer_key, &multisig_key], 1)
+                    .unwrap(),
+                &mut store,
+            )
+        );
+        assert_eq!(
+            Err(TokenError::MultisigSelfSigner.into()),
+            do_process_instruction_with_store(
+                initialize_weighted_multisig(
+                    &program_id,
+                    &weighted_multisig_key,
+                    &[&weighted_multisig_key],
+                    &[1],
+                    1,
+                )
+                .unwrap(),
+                &mut store,
+            )
+        );
+
+        // threshold above the number of signers
+        let mut instruction =
+            initialize_multisig(&program_id, &multisig_key, &[&signer_key, &signer2_key], 2)
+                .unwrap();
+        instruction.data = TokenInstruction::InitializeMultisig { m: 3 }.pack();
+        assert_eq!(
+            Err(TokenError::ThresholdExceedsSigners.into()),
+            do_process_instruction_with_store(instruction, &mut store)
+        );
+
+        // valid multisig
+        do_process_instruction_with_store(
+            initialize_multisig(&program_id, &multisig_key, &[&signer_key, &signer2_key], 2)
+                .unwrap(),
+            &mut store,
         )
         .unwrap();
+        let multisig = Multisig::unpack(&store[&multisig_key].data).unwrap();
+        assert_eq!(multisig.verify_integrity(&multisig_key), Ok(()));
 
-        // source-delegate burn_checked
-        do_process_instruction_dups(
-            burn_checked(
+        // the multisig cannot be added as its own signer
+        assert_eq!(
+            Err(TokenError::MultisigSelfSigner.into()),
+            do_process_instruction_with_store(
+                add_signer(
+                    &program_id,
+                    &multisig_key,
+                    &multisig_key,
+                    &[&signer_key, &signer2_key],
+                )
+                .unwrap(),
+                &mut store,
+            )
+        );
+
+        // unsafe configurations written before initialization was hardened
+        let mut unsafe_multisig = multisig;
+        unsafe_multisig.signers[1] = signer_key;
+        assert_eq!(
+            Err(TokenError::DuplicateSigner.into()),
+            unsafe_multisig.verify_integrity(&multisig_key)
+        );
+        let mut unsafe_multisig = multisig;
+        unsafe_multisig.signers[0] = multisig_key;
+        assert_eq!(
+            Err(TokenError::MultisigSelfSigner.into()),
+            unsafe_multisig.verify_integrity(&multisig_key)
+        );
+        let mut unsafe_multisig = multisig;
+        unsafe_multisig.m = 3;
+        assert_eq!(
+            Err(TokenError::ThresholdExceedsSigners.into()),
+            unsafe_multisig.verify_integrity(&multisig_key)
+        );
+        let mut unsafe_multisig = multisig;
+        unsafe_multisig.m = 0;
+        assert_eq!(
+            Err(TokenError::InvalidNumberOfRequiredSigners.into()),
+            unsafe_multisig.verify_integrity(&multisig_key)
+        );
+        let mut unsafe_multisig = multisig;
+        unsafe_multisig.n = 0;
+        assert_eq!(
+            Err(TokenError::InvalidNumberOfProvidedSigners.into()),
+            unsafe_multisig.verify_integrity(&multisig_key)
+        );
+
+        // valid weighted multisig
+        do_process_instruction_with_store(
+            initialize_weighted_multisig(
                 &program_id,
-                &account1_key,
-                &mint_key,
-                &account1_key,
-                &[],
-                500,
+                &weighted_multisig_key,
+                &[&signer_key, &signer2_key],
+                &[2, 1],
                 2,
             )
             .unwrap(),
-            vec![
-                account1_info.clone(),
-                mint_info.clone(),
-                account1_info.clone(),
-            ],
+            &mut store,
         )
         .unwrap();
+        let weighted_multisig =
+            WeightedMultisig::unpack(&store[&weighted_multisig_key].data).unwrap();
+        assert_eq!(
+            weighted_multisig.verify_integrity(&weighted_multisig_key),
+            Ok(())
+        );
+        let mut unsafe_multisig = weighted_multisig;
+        unsafe_multisig.threshold = 4;
+        assert_eq!(
+            Err(TokenError::InvalidWeightThreshold.into()),
+            unsafe_multisig.verify_integrity(&weighted_multisig_key)
+        );
+        let mut unsafe_multisig = weighted_multisig;
+        unsafe_multisig.signers[1] = signer_key;
+        assert_eq!(
+            Err(TokenError::DuplicateSigner.into()),
+            unsafe_multisig.verify_integrity(&weighted_multisig_key)
+        );
+    }
 
-        // mint-delegate burn
-        do_process_instruction_dups(
-            mint_to(&program_id, &mint_key, &account1_key, &owner_key, &[], 1000).unwrap(),
-            vec![mint_info.clone(), account1_info.clone(), owner_info.clone()],
+    #[test]
+    fn test_balance_holds() {
+        let program_id = crate::id();
+        let owner_key = Pubkey::new_unique();
+        let capturer_key = Pubkey::new_unique();
+        let mint_key = Pubkey::new_unique();
+        let account_key = Pubkey::new_unique();
+        let account2_key = Pubkey::new_unique();
+        let account3_key = Pubkey::new_unique();
+        let mut store = HashMap::new();
+        store.insert(rent::id(), rent_sysvar());
+        store.insert(sysvar::clock::id(), clock_sysvar(100));
+        store.insert(
+            mint_key,
+            SolanaAccount::new(
+                extended_mint_minimum_balance(),
+                Mint::EXTENDED_LEN,
+                &program_id,
+            ),
+        );
+        for key in &[account_key, account2_key, account3_key] {
+            store.insert(
+                *key,
+                SolanaAccount::new(
+                    extended_account_minimum_balance(),
+                    Account::EXTENDED_LEN,
+                    &program_id,
+                ),
+            );
+        }
+
+        // create mint and accounts
+        do_process_instruction_with_store(
+            initialize_mint(&program_id, &mint_key, &owner_key, None, 2).unwrap(),
+            &mut store,
         )
         .unwrap();
-        let mut account = Account::unpack_unchecked(&account1_info.data.borrow()).unwrap();
-        account.delegated_amount = 1000;
-        account.delegate = COption::Some(mint_key);
-        account.owner = owner_key;
-        Account::pack(account, &mut account1_info.data.borrow_mut()).unwrap();
-        do_process_instruction_dups(
-            burn(&program_id, &account1_key, &mint_key, &mint_key, &[], 500).unwrap(),
-            vec![account1_info.clone(), mint_info.clone(), mint_info.clone()],
+        for key in &[account_key, account2_key, account3_key] {
+            do_process_instruction_with_store(
+                initialize_account(&program_id, key, &mint_key, &owner_key).unwrap(),
+                &mut store,
+            )
+            .unwrap();
+        }
+        do_process_instruction_with_store(
+            mint_to(&program_id, &mint_key, &account_key, &owner_key, &[], 1000).unwrap(),
+            &mut store,
         )
         .unwrap();
 
-        // mint-delegate burn_checked
-        do_process_instruction_dups(
-            burn_checked(
-                &program_id,
-                &account1_key,
-                &mint_key,
-                &mint_key,
+        // not the owner
+        assert_eq!(
+            Err(TokenError::OwnerMismatch.into()),
+            do_process_instruction_with_store(
+                place_hold(
+                    &program_id,
+                    &account_key,
+                    &capturer_key,
+                    &[],
+                    &capturer_key,
+                    600,
+                    200,
+                )
+                .unwrap(),
+                &mut store,
+            )
+        );
+
+        // more than the balance
+        assert_eq!(
+            Err(TokenError::InsufficientFunds.into()),
+            do_process_instruction_with_store(
+                place_hold(
+                    &program_id,
+                    &account_key,
+                    &owner_key,
+                    &[],
+                    &capturer_key,
+                    1001,
+                    200,
+                )
+                .unwrap(),
+                &mut store,
+            )
+        );
+
+        // hold 600 tokens until 200
+        do_process_instruction_with_store(
+            place_hold(
+                &program_id,
+                &account_key,
+                &owner_key,
                 &[],
-                500,
-                2,
+                &capturer_key,
+                600,
+                200,
             )
             .unwrap(),
-            vec![account1_info.clone(), mint_info.clone(), mint_info.clone()],
+            &mut store,
         )
         .unwrap();
-    }
+        let account = Account::unpack(&store[&account_key].data).unwrap();
+        assert_eq!(account.hold_capturer, COption::Some(capturer_key));
+        assert_eq!(account.held_amount, 600);
+        assert_eq!(account.hold_expiry, 200);
+        assert_eq!(account.available_amount(), 400);
 
-    #[test]
-    fn test_burn() {
-        let program_id = crate::id();
-        let account_key = Pubkey::new_unique();
-        let mut account_account = SolanaAccount::new(
-            account_minimum_balance(),
-            Account::get_packed_len(),
-            &program_id,
-        );
-        let account2_key = Pubkey::new_unique();
-        let mut account2_account = SolanaAccount::new(
-            account_minimum_balance(),
-            Account::get_packed_len(),
-            &program_id,
+        // one hold at a time
+        assert_eq!(
+            Err(TokenError::HoldExists.into()),
+            do_process_instruction_with_store(
+                place_hold(
+                    &program_id,
+                    &account_key,
+                    &owner_key,
+                    &[],
+                    &capturer_key,
+                    100,
+                    200,
+                )
+                .unwrap(),
+                &mut store,
+            )
         );
-        let account3_key = Pubkey::new_unique();
-        let mut account3_account = SolanaAccount::new(
-            account_minimum_balance(),
-            Account::get_packed_len(),
-            &program_id,
+
+        // held funds cannot be transferred or burned
+        assert_eq!(
+            Err(TokenError::InsufficientFunds.into()),
+            do_process_instruction_with_store(
+                with_mint(
+                    transfer(
+                        &program_id,
+                        &account_key,
+                        &account2_key,
+                        &owner_key,
+                        &[],
+                        401,
+                    )
+                    .unwrap(),
+                    &mint_key,
+                ),
+                &mut store,
+            )
         );
-        let delegate_key = Pubkey::new_unique();
-        let mut delegate_account = SolanaAccount::default();
-        let mismatch_key = Pubkey::new_unique();
-        let mut mismatch_account = SolanaAccount::new(
-            account_minimum_balance(),
-            Account::get_packed_len(),
-            &program_id,
+        assert_eq!(
+            Err(TokenError::InsufficientFunds.into()),
+            do_process_instruction_with_store(
+                burn(&program_id, &account_key, &mint_key, &owner_key, &[], 401).unwrap(),
+                &mut store,
+            )
         );
-        let owner_key = Pubkey::new_unique();
-        let mut owner_account = SolanaAccount::default();
-        let owner2_key = Pubkey::new_unique();
-        let mut owner2_account = SolanaAccount::default();
-        let mint_key = Pubkey::new_unique();
-        let mut mint_account =
-            SolanaAccount::new(mint_minimum_balance(), Mint::get_packed_len(), &program_id);
-        let mint2_key = Pubkey::new_unique();
-        let mut rent_sysvar = rent_sysvar();
-
-        // create new mint
-        do_process_instruction(
-            initialize_mint(&program_id, &mint_key, &owner_key, None, 2).unwrap(),
-            vec![&mut mint_account, &mut rent_sysvar],
+        do_process_instruction_with_store(
+            with_mint(
+                transfer(
+                    &program_id,
+                    &account_key,
+                    &account2_key,
+                    &owner_key,
+                    &[],
+                    300,
+                )
+                .unwrap(),
+                &mint_key,
+            ),
+            &mut store,
         )
         .unwrap();
 
-        // create account
-        do_process_instruction(
-            initialize_account(&program_id, &account_key, &mint_key, &owner_key).unwrap(),
-            vec![
-                &mut account_account,
-                &mut mint_account,
-                &mut owner_account,
-                &mut rent_sysvar,
-            ],
-        )
-        .unwrap();
+        // not the capturer
+        assert_eq!(
+            Err(TokenError::OwnerMismatch.into()),
+            do_process_instruction_with_store(
+                with_mint(
+                    capture_hold(
+                        &program_id,
+                        &account_key,
+                        &account2_key,
+                        &owner_key,
+                        &[],
+                        500
+                    )
+                    .unwrap(),
+                    &mint_key,
+                ),
+                &mut store,
+            )
+        );
 
-        // create another account
-        do_process_instruction(
-            initialize_account(&program_id, &account2_key, &mint_key, &owner_key).unwrap(),
-            vec![
-                &mut account2_account,
-                &mut mint_account,
-                &mut owner_account,
-                &mut rent_sysvar,
-            ],
-        )
-        .unwrap();
+        // more than the held amount
+        assert_eq!(
+            Err(TokenError::InsufficientFunds.into()),
+            do_process_instruction_with_store(
+                with_mint(
+                    capture_hold(
+                        &program_id,
+                        &account_key,
+                        &account2_key,
+                        &capturer_key,
+                        &[],
+                        601,
+                    )
+                    .unwrap(),
+                    &mint_key,
+                ),
+                &mut store,
+            )
+        );
 
-        // create another account
-        do_process_instruction(
-            initialize_account(&program_id, &account3_key, &mint_key, &owner_key).unwrap(),
-            vec![
-                &mut account3_account,
-                &mut mint_account,
-                &mut owner_account,
-                &mut rent_sysvar,
-            ],
-        )
-        .unwrap();
+        // expired
+        store.insert(sysvar::clock::id(), clock_sysvar(200));
+        assert_eq!(
+            Err(TokenError::HoldExpired.into()),
+            do_process_instruction_with_store(
+                with_mint(
+                    capture_hold(
+                        &program_id,
+                        &account_key,
+                        &account2_key,
+                        &capturer_key,
+                        &[],
+                        500,
+                    )
+                    .unwrap(),
+                    &mint_key,
+                ),
+                &mut store,
+            )
+        );
+        store.insert(sysvar::clock::id(), clock_sysvar(100));
 
-        // create mismatch account
-        do_process_instruction(
-            initialize_account(&program_id, &mismatch_key, &mint_key, &owner_key).unwrap(),
-            vec![
-                &mut mismatch_account,
-                &mut mint_account,
-                &mut owner_account,
-                &mut rent_sysvar,
-            ],
+        // capture 500, keeping the remaining 100 held
+        do_process_instruction_with_store(
+            with_mint(
+                capture_hold(
+                    &program_id,
+                    &account_key,
+                    &account2_key,
+                    &capturer_key,
+                    &[],
+                    500,
+                )
+                .unwrap(),
+                &mint_key,
+            ),
+            &mut store,
         )
         .unwrap();
+        let account = Account::unpack(&store[&account_key].data).unwrap();
+        assert_eq!(account.amount, 200);
+        assert_eq!(account.hold_capturer, COption::Some(capturer_key));
+        assert_eq!(account.held_amount, 100);
+        assert_eq!(account.hold_expiry, 200);
+        assert_eq!(account.available_amount(), 100);
+        let account2 = Account::unpack(&store[&account2_key].data).unwrap();
+        assert_eq!(account2.amount, 800);
+
+        // the remainder cannot be transferred before the hold expires
+        assert_eq!(
+            Err(TokenError::InsufficientFunds.into()),
+            do_process_instruction_with_store(
+                with_mint(
+                    transfer(
+                        &program_id,
+                        &account_key,
+                        &account2_key,
+                        &owner_key,
+                        &[],
+                        101,
+                    )
+                    .unwrap(),
+                    &mint_key,
+                ),
+                &mut store,
+            )
+        );
 
-        // mint to account
-        do_process_instruction(
-            mint_to(&program_id, &mint_key, &account_key, &owner_key, &[], 1000).unwrap(),
-            vec![&mut mint_account, &mut account_account, &mut owner_account],
-        )
-        .unwrap();
+        // nor captured beyond it
+        assert_eq!(
+            Err(TokenError::InsufficientFunds.into()),
+            do_process_instruction_with_store(
+                with_mint(
+                    capture_hold(
+                        &program_id,
+                        &account_key,
+                        &account2_key,
+                        &capturer_key,
+                        &[],
+                        101,
+                    )
+                    .unwrap(),
+                    &mint_key,
+                ),
+                &mut store,
+            )
+        );
 
-        // mint to mismatch account and change mint key
-        do_process_instruction(
-            mint_to(&program_id, &mint_key, &mismatch_key, &owner_key, &[], 1000).unwrap(),
-            vec![&mut mint_account, &mut mismatch_account, &mut owner_account],
+        // release the remainder once the hold expires
+        store.insert(sysvar::clock::id(), clock_sysvar(200));
+        do_process_instruction_with_store(
+            release_hold(&program_id, &account_key, None, &[]).unwrap(),
+            &mut store,
         )
         .unwrap();
-        let mut account = Account::unpack_unchecked(&mismatch_account.data).unwrap();
-        account.mint = mint2_key;
-        Account::pack(account, &mut mismatch_account.data).unwrap();
+        store.insert(sysvar::clock::id(), clock_sysvar(100));
+        let account = Account::unpack(&store[&account_key].data).unwrap();
+        assert_eq!(account.hold_capturer, COption::None);
+        assert_eq!(account.held_amount, 0);
+        assert_eq!(account.available_amount(), 200);
 
-        // missing signer
-        let mut instruction =
-            burn(&program_id, &account_key, &mint_key, &delegate_key, &[], 42).unwrap();
-        instruction.accounts[1].is_signer = false;
+        // no hold
         assert_eq!(
-            Err(TokenError::OwnerMismatch.into()),
-            do_process_instruction(
-                instruction,
-                vec![
-                    &mut account_account,
-                    &mut mint_account,
-                    &mut delegate_account
-                ],
+            Err(TokenError::HoldNotFound.into()),
+            do_process_instruction_with_store(
+                with_mint(
+                    capture_hold(
+                        &program_id,
+                        &account_key,
+                        &account2_key,
+                        &capturer_key,
+                        &[],
+                        1,
+                    )
+                    .unwrap(),
+                    &mint_key,
+                ),
+                &mut store,
             )
         );
-
-        // missing owner
         assert_eq!(
-            Err(TokenError::OwnerMismatch.into()),
-            do_process_instruction(
-                burn(&program_id, &account_key, &mint_key, &owner2_key, &[], 42).unwrap(),
-                vec![&mut account_account, &mut mint_account, &mut owner2_account],
+            Err(TokenError::HoldNotFound.into()),
+            do_process_instruction_with_store(
+                release_hold(&program_id, &account_key, None, &[]).unwrap(),
+                &mut store,
             )
         );
 
-        // mint mismatch
+        // hold 100 tokens until 200
+        do_process_instruction_with_store(
+            place_hold(
+                &program_id,
+                &account_key,
+                &owner_key,
+                &[],
+                &capturer_key,
+                100,
+                200,
+            )
+            .unwrap(),
+            &mut store,
+        )
+        .unwrap();
+
+        // only the capturer may release an unexpired hold
         assert_eq!(
-            Err(TokenError::MintMismatch.into()),
-            do_process_instruction(
-                burn(&program_id, &mismatch_key, &mint_key, &owner_key, &[], 42).unwrap(),
-                vec![&mut mismatch_account, &mut mint_account, &mut owner_account],
+            Err(ProgramError::NotEnoughAccountKeys),
+            do_process_instruction_with_store(
+                release_hold(&program_id, &account_key, None, &[]).unwrap(),
+                &mut store,
+            )
+        );
+        assert_eq!(
+            Err(TokenError::OwnerMismatch.into()),
+            do_process_instruction_with_store(
+                release_hold(&program_id, &account_key, Some(&owner_key), &[]).unwrap(),
+                &mut store,
             )
         );
 
-        // burn
-        do_process_instruction(
-            burn(&program_id, &account_key, &mint_key, &owner_key, &[], 21).unwrap(),
-            vec![&mut account_account, &mut mint_account, &mut owner_account],
+        // anyone may release an expired hold
+        store.insert(sysvar::clock::id(), clock_sysvar(200));
+        do_process_instruction_with_store(
+            release_hold(&program_id, &account_key, None, &[]).unwrap(),
+            &mut store,
+        )
+        .unwrap();
+        let account = Account::unpack(&store[&account_key].data).unwrap();
+        assert_eq!(account.hold_capturer, COption::None);
+        assert_eq!(account.available_amount(), 200);
+        store.insert(sysvar::clock::id(), clock_sysvar(100));
+
+        // the capturer releases before expiry
+        do_process_instruction_with_store(
+            place_hold(
+                &program_id,
+                &account_key,
+                &owner_key,
+                &[],
+                &capturer_key,
+                50,
+                200,
+            )
+            .unwrap(),
+            &mut store,
         )
         .unwrap();
+        do_process_instruction_with_store(
+            release_hold(&program_id, &account_key, Some(&capturer_key), &[]).unwrap(),
+            &mut store,
+        )
+        .unwrap();
+        let account = Account::unpack(&store[&account_key].data).unwrap();
+        assert_eq!(account.hold_capturer, COption::None);
 
-        // burn_checked, with incorrect decimals
+        // an account with an outstanding hold cannot be closed
+        do_process_instruction_with_store(
+            place_hold(
+                &program_id,
+                &account3_key,
+                &owner_key,
+                &[],
+                &capturer_key,
+                0,
+                200,
+            )
+            .unwrap(),
+            &mut store,
+        )
+        .unwrap();
         assert_eq!(
-            Err(TokenError::MintDecimalsMismatch.into()),
-            do_process_instruction(
-                burn_checked(&program_id, &account_key, &mint_key, &owner_key, &[], 21, 3).unwrap(),
-                vec![&mut account_account, &mut mint_account, &mut owner_account],
+            Err(TokenError::HoldExists.into()),
+            do_process_instruction_with_store(
+                close_account(&program_id, &account3_key, &owner_key, &owner_key, &[]).unwrap(),
+                &mut store,
             )
         );
+    }
 
-        // burn_checked
-        do_process_instruction(
-            burn_checked(&program_id, &account_key, &mint_key, &owner_key, &[], 21, 2).unwrap(),
-            vec![&mut account_account, &mut mint_account, &mut owner_account],
+    #[test]
+    fn test_partial_freeze_states() {
+        let program_id = crate::id();
+        let owner_key = Pubkey::new_unique();
+        let freeze_key = Pubkey::new_unique();
+        let mint_key = Pubkey::new_unique();
+        let account_key = Pubkey::new_unique();
+        let account2_key = Pubkey::new_unique();
+        let mut store = HashMap::new();
+        store.insert(rent::id(), rent_sysvar());
+        store.insert(
+            mint_key,
+            SolanaAccount::new(
+                extended_mint_minimum_balance(),
+                Mint::EXTENDED_LEN,
+                &program_id,
+            ),
+        );
+        for key in &[account_key, account2_key] {
+            store.insert(
+                *key,
+                SolanaAccount::new(
+                    extended_account_minimum_balance(),
+                    Account::EXTENDED_LEN,
+                    &program_id,
+                ),
+            );
+        }
+
+        // create mint and accounts
+        do_process_instruction_with_store(
+            initialize_mint(&program_id, &mint_key, &owner_key, Some(&freeze_key), 2).unwrap(),
+            &mut store,
         )
         .unwrap();
+        for key in &[account_key, account2_key] {
+            do_process_instruction_with_store(
+                initialize_account(&program_id, key, &mint_key, &owner_key).unwrap(),
+                &mut store,
+            )
+            .unwrap();
+            do_process_instruction_with_store(
+                mint_to(&program_id, &mint_key, key, &owner_key, &[], 1000).unwrap(),
+                &mut store,
+            )
+            .unwrap();
+        }
 
-        let mint = Mint::unpack_unchecked(&mint_account.data).unwrap();
-        assert_eq!(mint.supply, 2000 - 42);
-        let account = Account::unpack_unchecked(&account_account.data).unwrap();
-        assert_eq!(account.amount, 1000 - 42);
-
-        // insufficient funds
+        // not the freeze authority
         assert_eq!(
-            Err(TokenError::InsufficientFunds.into()),
-            do_process_instruction(
-                burn(
-                    &program_id,
-                    &account_key,
-                    &mint_key,
-                    &owner_key,
-                    &[],
-                    100_000_000
-                )
-                .unwrap(),
-                vec![&mut account_account, &mut mint_account, &mut owner_account],
+            Err(TokenError::OwnerMismatch.into()),
+            do_process_instruction_with_store(
+                set_receive_only(&program_id, &account_key, &mint_key, &owner_key, &[]).unwrap(),
+                &mut store,
             )
         );
 
-        // approve delegate
-        do_process_instruction(
-            approve(
-                &program_id,
-                &account_key,
-                &delegate_key,
-                &owner_key,
-                &[],
-                84,
-            )
-            .unwrap(),
-            vec![
-                &mut account_account,
-                &mut delegate_account,
-                &mut owner_account,
-            ],
+        // restrict account to receiving
+        do_process_instruction_with_store(
+            set_receive_only(&program_id, &account_key, &mint_key, &freeze_key, &[]).unwrap(),
+            &mut store,
         )
         .unwrap();
+        let account = Account::unpack(&store[&account_key].data).unwrap();
+        assert_eq!(account.state, AccountState::ReceiveOnly);
+        assert_eq!(store[&account_key].data[108], 3);
+        assert_eq!(
+            Err(TokenError::InvalidState.into()),
+            do_process_instruction_with_store(
+                set_receive_only(&program_id, &account_key, &mint_key, &freeze_key, &[]).unwrap(),
+                &mut store,
+            )
+        );
 
-        // not a delegate of source account
+        // a receive-only account cannot send or burn
         assert_eq!(
-            Err(TokenError::InsufficientFunds.into()),
-            do_process_instruction(
-                burn(
+            Err(TokenError::AccountFrozen.into()),
+            do_process_instruction_with_store(
+                with_mint(
+                    transfer(
+                        &program_id,
+                        &account_key,
+                        &account2_key,
+                        &owner_key,
+                        &[],
+                        100,
+                    )
+                    .unwrap(),
+                    &mint_key,
+                ),
+                &mut store,
+            )
+        );
+        assert_eq!(
+            Err(TokenError::AccountFrozen.into()),
+            do_process_instruction_with_store(
+                burn(&program_id, &account_key, &mint_key, &owner_key, &[], 100).unwrap(),
+                &mut store,
+            )
+        );
+
+        // but it can receive
+        do_process_instruction_with_store(
+            with_mint(
+                transfer(
                     &program_id,
+                    &account2_key,
                     &account_key,
-                    &mint_key,
                     &owner_key,
                     &[],
-                    100_000_000
+                    100,
                 )
                 .unwrap(),
-                vec![&mut account_account, &mut mint_account, &mut owner_account],
-            )
-        );
-
-        // burn via delegate
-        do_process_instruction(
-            burn(&program_id, &account_key, &mint_key, &delegate_key, &[], 84).unwrap(),
-            vec![
-                &mut account_account,
-                &mut mint_account,
-                &mut delegate_account,
-            ],
+                &mint_key,
+            ),
+            &mut store,
+        )
+        .unwrap();
+        do_process_instruction_with_store(
+            mint_to(&program_id, &mint_key, &account_key, &owner_key, &[], 100).unwrap(),
+            &mut store,
         )
         .unwrap();
+        let account = Account::unpack(&store[&account_key].data).unwrap();
+        assert_eq!(account.amount, 1200);
 
-        // match
-        let mint = Mint::unpack_unchecked(&mint_account.data).unwrap();
-        assert_eq!(mint.supply, 2000 - 42 - 84);
-        let account = Account::unpack_unchecked(&account_account.data).unwrap();
-        assert_eq!(account.amount, 1000 - 42 - 84);
+        // restrict account to sending
+        do_process_instruction_with_store(
+            set_send_only(&program_id, &account_key, &mint_key, &freeze_key, &[]).unwrap(),
+            &mut store,
+        )
+        .unwrap();
+        let account = Account::unpack(&store[&account_key].data).unwrap();
+        assert_eq!(account.state, AccountState::SendOnly);
+        assert_eq!(store[&account_key].data[108], 4);
 
-        // insufficient funds approved via delegate
+        // a send-only account cannot receive
         assert_eq!(
-            Err(TokenError::OwnerMismatch.into()),
-            do_process_instruction(
-                burn(
-                    &program_id,
-                    &account_key,
+            Err(TokenError::AccountFrozen.into()),
+            do_process_instruction_with_store(
+                with_mint(
+                    transfer(
+                        &program_id,
+                        &account2_key,
+                        &account_key,
+                        &owner_key,
+                        &[],
+                        100,
+                    )
+                    .unwrap(),
                     &mint_key,
-                    &delegate_key,
-                    &[],
-                    100
-                )
-                .unwrap(),
-                vec![
-                    &mut account_account,
-                    &mut mint_account,
-                    &mut delegate_account
-                ],
+                ),
+                &mut store,
             )
         );
-    }
-
-    #[test]
-    fn test_multisig() {
-        let program_id = crate::id();
-        let mint_key = Pubkey::new_unique();
-        let mut mint_account =
-            SolanaAccount::new(mint_minimum_balance(), Mint::get_packed_len(), &program_id);
-        let account_key = Pubkey::new_unique();
-        let mut account = SolanaAccount::new(
-            account_minimum_balance(),
-            Account::get_packed_len(),
-            &program_id,
-        );
-        let account2_key = Pubkey::new_unique();
-        let mut account2_account = SolanaAccount::new(
-            account_minimum_balance(),
-            Account::get_packed_len(),
-            &program_id,
-        );
-        let owner_key = Pubkey::new_unique();
-        let mut owner_account = SolanaAccount::default();
-        let multisig_key = Pubkey::new_unique();
-        let mut multisig_account = SolanaAccount::new(42, Multisig::get_packed_len(), &program_id);
-        let multisig_delegate_key = Pubkey::new_unique();
-        let mut multisig_delegate_account = SolanaAccount::new(
-            multisig_minimum_balance(),
-            Multisig::get_packed_len(),
-            &program_id,
-        );
-        let signer_keys = vec![Pubkey::new_unique(); MAX_SIGNERS];
-        let signer_key_refs: Vec<&Pubkey> = signer_keys.iter().collect();
-        let mut signer_accounts = vec![SolanaAccount::new(0, 0, &program_id); MAX_SIGNERS];
-        let mut rent_sysvar = rent_sysvar();
-
-        // multisig is not rent exempt
-        let account_info_iter = &mut signer_accounts.iter_mut();
         assert_eq!(
-            Err(TokenError::NotRentExempt.into()),
-            do_process_instruction(
-                initialize_multisig(&program_id, &multisig_key, &[&signer_keys[0]], 1).unwrap(),
-                vec![
-                    &mut multisig_account,
-                    &mut rent_sysvar,
-                    &mut account_info_iter.next().unwrap(),
-                ],
+            Err(TokenError::AccountFrozen.into()),
+            do_process_instruction_with_store(
+                mint_to(&program_id, &mint_key, &account_key, &owner_key, &[], 100).unwrap(),
+                &mut store,
             )
         );
 
-        multisig_account.lamports = multisig_minimum_balance();
-
-        // single signer
-        let account_info_iter = &mut signer_accounts.iter_mut();
-        do_process_instruction(
-            initialize_multisig(&program_id, &multisig_key, &[&signer_keys[0]], 1).unwrap(),
-            vec![
-                &mut multisig_account,
-                &mut rent_sysvar,
-                &mut account_info_iter.next().unwrap(),
-            ],
+        // but it can send and burn
+        do_process_instruction_with_store(
+            with_mint(
+                transfer(
+                    &program_id,
+                    &account_key,
+                    &account2_key,
+                    &owner_key,
+                    &[],
+                    100,
+                )
+                .unwrap(),
+                &mint_key,
+            ),
+            &mut store,
         )
         .unwrap();
-
-        // multiple signer
-        let account_info_iter = &mut signer_accounts.iter_mut();
-        do_process_instruction(
-            initialize_multisig(
-                &program_id,
-                &multisig_delegate_key,
-                &signer_key_refs,
-                MAX_SIGNERS as u8,
-            )
-            .unwrap(),
-            vec![
-                &mut multisig_delegate_account,
-                &mut rent_sysvar,
-                &mut account_info_iter.next().unwrap(),
-                &mut account_info_iter.next().unwrap(),
-                &mut account_info_iter.next().unwrap(),
-                &mut account_info_iter.next().unwrap(),
-                &mut account_info_iter.next().unwrap(),
-                &mut account_info_iter.next().unwrap(),
-                &mut account_info_iter.next().unwrap(),
-                &mut account_info_iter.next().unwrap(),
-                &mut account_info_iter.next().unwrap(),
-                &mut account_info_iter.next().unwrap(),
-                &mut account_info_iter.next().unwrap(),
-            ],
+        do_process_instruction_with_store(
+            burn(&program_id, &account_key, &mint_key, &owner_key, &[], 100).unwrap(),
+            &mut store,
         )
         .unwrap();
+        let account = Account::unpack(&store[&account_key].data).unwrap();
+        assert_eq!(account.amount, 1000);
 
-        // create new mint with multisig owner
-        do_process_instruction(
-            initialize_mint(&program_id, &mint_key, &multisig_key, None, 2).unwrap(),
-            vec![&mut mint_account, &mut rent_sysvar],
+        // freezing a restricted account blocks sending too
+        do_process_instruction_with_store(
+            freeze_account(&program_id, &account_key, &mint_key, &freeze_key, &[]).unwrap(),
+            &mut store,
         )
         .unwrap();
+        assert_eq!(store[&account_key].data[108], 2);
+        assert_eq!(
+            Err(TokenError::AccountFrozen.into()),
+            do_process_instruction_with_store(
+                with_mint(
+                    transfer(
+                        &program_id,
+                        &account_key,
+                        &account2_key,
+                        &owner_key,
+                        &[],
+                        100,
+                    )
+                    .unwrap(),
+                    &mint_key,
+                ),
+                &mut store,
+            )
+        );
 
-        // create account with multisig owner
-        do_process_instruction(
-            initialize_account(&program_id, &account_key, &mint_key, &multisig_key).unwrap(),
-            vec![
-                &mut account,
-                &mut mint_account,
-                &mut multisig_account,
-                &mut rent_sysvar,
-            ],
+        // thaw lifts any restriction
+        do_process_instruction_with_store(
+            set_send_only(&program_id, &account_key, &mint_key, &freeze_key, &[]).unwrap(),
+            &mut store,
         )
         .unwrap();
-
-        // create another account with multisig owner
-        do_process_instruction(
-            initialize_account(
-                &program_id,
-                &account2_key,
-                &mint_key,
-                &multisig_delegate_key,
+        do_process_instruction_with_store(
+            thaw_account(&program_id, &account_key, &mint_key, &freeze_key, &[]).unwrap(),
+            &mut store,
+        )
+        .unwrap();
+        let account = Account::unpack(&store[&account_key].data).unwrap();
+        assert_eq!(account.state, AccountState::Initialized);
+        assert_eq!(
+            Err(TokenError::InvalidState.into()),
+            do_process_instruction_with_store(
+                thaw_account(&program_id, &account_key, &mint_key, &freeze_key, &[]).unwrap(),
+                &mut store,
             )
-            .unwrap(),
-            vec![
-                &mut account2_account,
-                &mut mint_account,
-                &mut multisig_account,
-                &mut rent_sysvar,
-            ],
+        );
+        do_process_instruction_with_store(
+            mint_to(&program_id, &mint_key, &account_key, &owner_key, &[], 100).unwrap(),
+            &mut store,
         )
         .unwrap();
+    }
 
-        // mint to account
-        let account_info_iter = &mut signer_accounts.iter_mut();
-        do_process_instruction(
-            mint_to(
+    #[test]
+    fn test_freeze_with_reason() {
+        let program_id = crate::id();
+        let owner_key = Pubkey::new_unique();
+        let freeze_key = Pubkey::new_unique();
+        let delegate_key = Pubkey::new_unique();
+        let mint_key = Pubkey::new_unique();
+        let account_key = Pubkey::new_unique();
+        let account2_key = Pubkey::new_unique();
+        let mut store = HashMap::new();
+        store.insert(rent::id(), rent_sysvar());
+        store.insert(
+            mint_key,
+            SolanaAccount::new(
+                extended_mint_minimum_balance(),
+                Mint::EXTENDED_LEN,
                 &program_id,
-                &mint_key,
-                &account_key,
-                &multisig_key,
-                &[&signer_keys[0]],
-                1000,
-            )
-            .unwrap(),
-            vec![
-                &mut mint_account,
-                &mut account,
-                &mut multisig_account,
-                &mut account_info_iter.next().unwrap(),
-            ],
+            ),
+        );
+        for key in &[account_key, account2_key] {
+            store.insert(
+                *key,
+                SolanaAccount::new(
+                    extended_account_minimum_balance(),
+                    Account::EXTENDED_LEN,
+                    &program_id,
+                ),
+            );
+        }
+
+        // create mint and accounts
+        do_process_instruction_with_store(
+            initialize_mint(&program_id, &mint_key, &owner_key, Some(&freeze_key), 2).unwrap(),
+            &mut store,
         )
         .unwrap();
-
-        // approve
-        let account_info_iter = &mut signer_accounts.iter_mut();
-        do_process_instruction(
-            approve(
-                &program_id,
-                &account_key,
-                &multisig_delegate_key,
-                &multisig_key,
-                &[&signer_keys[0]],
-                100,
+        for key in &[account_key, account2_key] {
+            do_process_instruction_with_store(
+                initialize_account(&program_id, key, &mint_key, &owner_key).unwrap(),
+                &mut store,
             )
-            .unwrap(),
-            vec![
-                &mut account,
-                &mut multisig_delegate_account,
-                &mut multisig_account,
-                &mut account_info_iter.next().unwrap(),
-            ],
+            .unwrap();
+        }
+        do_process_instruction_with_store(
+            mint_to(&program_id, &mint_key, &account_key, &owner_key, &[], 1000).unwrap(),
+            &mut store,
         )
         .unwrap();
 
-        // transfer
-        let account_info_iter = &mut signer_accounts.iter_mut();
-        do_process_instruction(
-            transfer(
+        let with_clock = |mut instruction: Instruction| {
+            instruction
+                .accounts
+                .push(AccountMeta::new_readonly(sysvar::clock::id(), false));
+            instruction
+        };
+
+        // freeze until 200 for reason 7
+        do_process_instruction_with_store(
+            freeze_account_with_reason(
                 &program_id,
                 &account_key,
-                &account2_key,
-                &multisig_key,
-                &[&signer_keys[0]],
-                42,
+                &mint_key,
+                &freeze_key,
+                &[],
+                7,
+                200,
             )
             .unwrap(),
-            vec![
-                &mut account,
-                &mut account2_account,
-                &mut multisig_account,
-                &mut account_info_iter.next().unwrap(),
-            ],
+            &mut store,
         )
         .unwrap();
+        let account = Account::unpack(&store[&account_key].data).unwrap();
+        assert_eq!(account.state, AccountState::Frozen);
+        assert_eq!(account.freeze_reason, 7);
+        assert_eq!(account.auto_thaw_timestamp, 200);
 
-        // transfer via delegate
-        let account_info_iter = &mut signer_accounts.iter_mut();
-        do_process_instruction(
-            transfer(
-                &program_id,
-                &account_key,
-                &account2_key,
-                &multisig_delegate_key,
-                &signer_key_refs,
-                42,
+        // frozen before the auto-thaw timestamp, with or without the Clock sysvar account
+        set_syscall_clock(199);
+        assert_eq!(
+            Err(TokenError::AccountFrozen.into()),
+            do_process_instruction_with_store(
+                with_mint(
+                    transfer(
+                        &program_id,
+                        &account_key,
+                        &account2_key,
+                        &owner_key,
+                        &[],
+                        100,
+                    )
+                    .unwrap(),
+                    &mint_key,
+                ),
+                &mut store,
             )
-            .unwrap(),
-            vec![
-                &mut account,
-                &mut account2_account,
-                &mut multisig_delegate_account,
-                &mut account_info_iter.next().unwrap(),
-                &mut account_info_iter.next().unwrap(),
-                &mut account_info_iter.next().unwrap(),
-                &mut account_info_iter.next().unwrap(),
-                &mut account_info_iter.next().unwrap(),
-                &mut account_info_iter.next().unwrap(),
-                &mut account_info_iter.next().unwrap(),
-                &mut account_info_iter.next().unwrap(),
-                &mut account_info_iter.next().unwrap(),
-                &mut account_info_iter.next().unwrap(),
-                &mut account_info_iter.next().unwrap(),
-            ],
+        );
+        store.insert(sysvar::clock::id(), clock_sysvar(199));
+        assert_eq!(
+            Err(TokenError::AccountFrozen.into()),
+            do_process_instruction_with_store(
+                with_clock(with_mint(
+                    transfer(
+                        &program_id,
+                        &account_key,
+                        &account2_key,
+                        &owner_key,
+                        &[],
+                        100,
+                    )
+                    .unwrap(),
+                    &mint_key,
+                )),
+                &mut store,
+            )
+        );
+
+        // thawed once the timestamp passes
+        store.insert(sysvar::clock::id(), clock_sysvar(200));
+        do_process_instruction_with_store(
+            with_clock(with_mint(
+                transfer(
+                    &program_id,
+                    &account_key,
+                    &account2_key,
+                    &owner_key,
+                    &[],
+                    100,
+                )
+                .unwrap(),
+                &mint_key,
+            )),
+            &mut store,
         )
         .unwrap();
+        let account = Account::unpack(&store[&account_key].data).unwrap();
+        assert_eq!(account.state, AccountState::Initialized);
+        assert_eq!(account.freeze_reason, 0);
+        assert_eq!(account.auto_thaw_timestamp, 0);
+        assert_eq!(account.amount, 900);
 
-        // mint to
-        let account_info_iter = &mut signer_accounts.iter_mut();
-        do_process_instruction(
-            mint_to(
+        // an expired freeze applies to other instructions as well
+        do_process_instruction_with_store(
+            freeze_account_with_reason(
                 &program_id,
+                &account_key,
                 &mint_key,
-                &account2_key,
-                &multisig_key,
-                &[&signer_keys[0]],
-                42,
+                &freeze_key,
+                &[],
+                5,
+                300,
             )
             .unwrap(),
-            vec![
-                &mut mint_account,
-                &mut account2_account,
-                &mut multisig_account,
-                &mut account_info_iter.next().unwrap(),
-            ],
+            &mut store,
+        )
+        .unwrap();
+        assert_eq!(
+            Err(TokenError::AccountFrozen.into()),
+            do_process_instruction_with_store(
+                with_clock(
+                    approve(
+                        &program_id,
+                        &account_key,
+                        &delegate_key,
+                        &owner_key,
+                        &[],
+                        100,
+                    )
+                    .unwrap()
+                ),
+                &mut store,
+            )
+        );
+        store.insert(sysvar::clock::id(), clock_sysvar(300));
+        do_process_instruction_with_store(
+            with_clock(
+                approve(
+                    &program_id,
+                    &account_key,
+                    &delegate_key,
+                    &owner_key,
+                    &[],
+                    100,
+                )
+                .unwrap(),
+            ),
+            &mut store,
         )
         .unwrap();
+        let account = Account::unpack(&store[&account_key].data).unwrap();
+        assert_eq!(account.state, AccountState::Initialized);
+        assert_eq!(account.delegate, COption::Some(delegate_key));
 
-        // burn
-        let account_info_iter = &mut signer_accounts.iter_mut();
-        do_process_instruction(
-            burn(
+        // without an auto-thaw timestamp the freeze lasts until thawed
+        do_process_instruction_with_store(
+            freeze_account_with_reason(
                 &program_id,
                 &account_key,
                 &mint_key,
-                &multisig_key,
-                &[&signer_keys[0]],
-                42,
+                &freeze_key,
+                &[],
+                9,
+                0,
             )
             .unwrap(),
-            vec![
-                &mut account,
-                &mut mint_account,
-                &mut multisig_account,
-                &mut account_info_iter.next().unwrap(),
-            ],
+            &mut store,
+        )
+        .unwrap();
+        store.insert(sysvar::clock::id(), clock_sysvar(i64::MAX));
+        assert_eq!(
+            Err(TokenError::AccountFrozen.into()),
+            do_process_instruction_with_store(
+                with_clock(with_mint(
+                    transfer(
+                        &program_id,
+                        &account_key,
+                        &account2_key,
+                        &owner_key,
+                        &[],
+                        100,
+                    )
+                    .unwrap(),
+                    &mint_key,
+                )),
+                &mut store,
+            )
+        );
+        let account = Account::unpack(&store[&account_key].data).unwrap();
+        assert_eq!(account.freeze_reason, 9);
+        do_process_instruction_with_store(
+            thaw_account(&program_id, &account_key, &mint_key, &freeze_key, &[]).unwrap(),
+            &mut store,
         )
         .unwrap();
+        let account = Account::unpack(&store[&account_key].data).unwrap();
+        assert_eq!(account.state, AccountState::Initialized);
+        assert_eq!(account.freeze_reason, 0);
 
-        // burn via delegate
-        let account_info_iter = &mut signer_accounts.iter_mut();
-        do_process_instruction(
-            burn(
+        // an expired freeze thaws without the Clock sysvar account
+        do_process_instruction_with_store(
+            freeze_account_with_reason(
                 &program_id,
                 &account_key,
                 &mint_key,
-                &multisig_delegate_key,
-                &signer_key_refs,
-                42,
+                &freeze_key,
+                &[],
+                3,
+                400,
             )
             .unwrap(),
-            vec![
-                &mut account,
-                &mut mint_account,
-                &mut multisig_delegate_account,
-                &mut account_info_iter.next().unwrap(),
-                &mut account_info_iter.next().unwrap(),
-                &mut account_info_iter.next().unwrap(),
-                &mut account_info_iter.next().unwrap(),
-                &mut account_info_iter.next().unwrap(),
-                &mut account_info_iter.next().unwrap(),
-                &mut account_info_iter.next().unwrap(),
-                &mut account_info_iter.next().unwrap(),
-                &mut account_info_iter.next().unwrap(),
-                &mut account_info_iter.next().unwrap(),
-                &mut account_info_iter.next().unwrap(),
-            ],
+            &mut store,
         )
         .unwrap();
-
-        // freeze account
-        let account3_key = Pubkey::new_unique();
-        let mut account3_account = SolanaAccount::new(
-            account_minimum_balance(),
-            Account::get_packed_len(),
-            &program_id,
-        );
-        let mint2_key = Pubkey::new_unique();
-        let mut mint2_account =
-            SolanaAccount::new(mint_minimum_balance(), Mint::get_packed_len(), &program_id);
-        do_process_instruction(
-            initialize_mint(
-                &program_id,
-                &mint2_key,
-                &multisig_key,
-                Some(&multisig_key),
-                2,
+        set_syscall_clock(399);
+        assert_eq!(
+            Err(TokenError::AccountFrozen.into()),
+            do_process_instruction_with_store(
+                burn(&program_id, &account_key, &mint_key, &owner_key, &[], 100).unwrap(),
+                &mut store,
             )
-            .unwrap(),
-            vec![&mut mint2_account, &mut rent_sysvar],
+        );
+        set_syscall_clock(400);
+        do_process_instruction_with_store(
+            with_mint(
+                transfer(
+                    &program_id,
+                    &account_key,
+                    &account2_key,
+                    &owner_key,
+                    &[],
+                    100,
+                )
+                .unwrap(),
+                &mint_key,
+            ),
+            &mut store,
         )
         .unwrap();
-        do_process_instruction(
-            initialize_account(&program_id, &account3_key, &mint2_key, &owner_key).unwrap(),
-            vec![
-                &mut account3_account,
-                &mut mint2_account,
-                &mut owner_account,
-                &mut rent_sysvar,
-            ],
+        let account = Account::unpack(&store[&account_key].data).unwrap();
+        assert_eq!(account.state, AccountState::Initialized);
+        assert_eq!(account.auto_thaw_timestamp, 0);
+        assert_eq!(account.amount, 800);
+    }
+
+    #[test]
+    fn test_bulk_freeze_thaw() {
+        let program_id = crate::id();
+        let owner_key = Pubkey::new_unique();
+        let multisig_key = Pubkey::new_unique();
+        let signer_key = Pubkey::new_unique();
+        let signer2_key = Pubkey::new_unique();
+        let mint_key = Pubkey::new_unique();
+        let mint2_key = Pubkey::new_unique();
+        let account_keys: Vec<_> = (0..3).map(|_| Pubkey::new_unique()).collect();
+        let other_account_key = Pubkey::new_unique();
+        let mut store = HashMap::new();
+        store.insert(rent::id(), rent_sysvar());
+        store.insert(
+            multisig_key,
+            SolanaAccount::new(
+                multisig_minimum_balance(),
+                Multisig::get_packed_len(),
+                &program_id,
+            ),
+        );
+        for key in &[mint_key, mint2_key] {
+            store.insert(
+                *key,
+                SolanaAccount::new(
+                    extended_mint_minimum_balance(),
+                    Mint::EXTENDED_LEN,
+                    &program_id,
+                ),
+            );
+        }
+        for key in account_keys.iter().chain(Some(&other_account_key)) {
+            store.insert(
+                *key,
+                SolanaAccount::new(
+                    extended_account_minimum_balance(),
+                    Account::EXTENDED_LEN,
+                    &program_id,
+                ),
+            );
+        }
+
+        // create a 2 of 2 multisig holding the freeze authority, mints and accounts
+        do_process_instruction_with_store(
+            initialize_multisig(&program_id, &multisig_key, &[&signer_key, &signer2_key], 2)
+                .unwrap(),
+            &mut store,
         )
         .unwrap();
-        let account_info_iter = &mut signer_accounts.iter_mut();
-        do_process_instruction(
-            mint_to(
-                &program_id,
-                &mint2_key,
-                &account3_key,
-                &multisig_key,
-                &[&signer_keys[0]],
-                1000,
+        for key in &[mint_key, mint2_key] {
+            do_process_instruction_with_store(
+                initialize_mint(&program_id, key, &owner_key, Some(&multisig_key), 2).unwrap(),
+                &mut store,
             )
-            .unwrap(),
-            vec![
-                &mut mint2_account,
-                &mut account3_account,
-                &mut multisig_account,
-                &mut account_info_iter.next().unwrap(),
-            ],
+            .unwrap();
+        }
+        for key in account_keys.iter() {
+            do_process_instruction_with_store(
+                initialize_account(&program_id, key, &mint_key, &owner_key).unwrap(),
+                &mut store,
+            )
+            .unwrap();
+        }
+        do_process_instruction_with_store(
+            initialize_account(&program_id, &other_account_key, &mint2_key, &owner_key).unwrap(),
+            &mut store,
         )
-        .unwrap();
-        let account_info_iter = &mut signer_accounts.iter_mut();
-        do_process_instruction(
+        .unwrap();
+        let accounts: Vec<_> = account_keys.iter().collect();
+
+        // the second account is already frozen
+        do_process_instruction_with_store(
             freeze_account(
                 &program_id,
-                &account3_key,
-                &mint2_key,
+                &account_keys[1],
+                &mint_key,
                 &multisig_key,
-                &[&signer_keys[0]],
+                &[&signer_key, &signer2_key],
             )
             .unwrap(),
-            vec![
-                &mut account3_account,
-                &mut mint2_account,
-                &mut multisig_account,
-                &mut account_info_iter.next().unwrap(),
-            ],
+            &mut store,
         )
         .unwrap();
 
-        // do SetAuthority on mint
-        let account_info_iter = &mut signer_accounts.iter_mut();
-        do_process_instruction(
-            set_authority(
+        // missing a signer
+        assert_eq!(
+            Err(ProgramError::MissingRequiredSignature),
+            do_process_instruction_with_store(
+                freeze_accounts(
+                    &program_id,
+                    &accounts,
+                    &mint_key,
+                    &multisig_key,
+                    &[&signer_key],
+                )
+                .unwrap(),
+                &mut store,
+            )
+        );
+
+        // an account of another mint
+        assert_eq!(
+            Err(TokenError::MintMismatch.into()),
+            do_process_instruction_with_store(
+                freeze_accounts(
+                    &program_id,
+                    &[&account_keys[0], &other_account_key],
+                    &mint_key,
+                    &multisig_key,
+                    &[&signer_key, &signer2_key],
+                )
+                .unwrap(),
+                &mut store,
+            )
+        );
+
+        // more accounts than provided
+        let mut instruction = freeze_accounts(
+            &program_id,
+            &accounts,
+            &mint_key,
+            &multisig_key,
+            &[&signer_key, &signer2_key],
+        )
+        .unwrap();
+        instruction.data[1] = 6;
+        assert_eq!(
+            Err(ProgramError::NotEnoughAccountKeys),
+            do_process_instruction_with_store(instruction, &mut store)
+        );
+
+        // freeze all accounts, leaving the frozen one unchanged and returning its index
+        install_syscall_stubs();
+        do_process_instruction_with_store(
+            freeze_accounts(
                 &program_id,
+                &accounts,
                 &mint_key,
-                Some(&owner_key),
-                AuthorityType::MintTokens,
                 &multisig_key,
-                &[&signer_keys[0]],
+                &[&signer_key, &signer2_key],
             )
             .unwrap(),
-            vec![
-                &mut mint_account,
-                &mut multisig_account,
-                &mut account_info_iter.next().unwrap(),
-            ],
+            &mut store,
         )
         .unwrap();
+        for key in account_keys.iter() {
+            let account = Account::unpack(&store[key].data).unwrap();
+            assert_eq!(account.state, AccountState::Frozen);
+        }
+        assert_eq!(
+            solana_program::program::get_return_data(),
+            Some((program_id, vec![1]))
+        );
 
-        // do SetAuthority on account
-        let account_info_iter = &mut signer_accounts.iter_mut();
-        do_process_instruction(
-            set_authority(
+        // thaw all accounts
+        do_process_instruction_with_store(
+            thaw_accounts(
                 &program_id,
-                &account_key,
-                Some(&owner_key),
-                AuthorityType::AccountOwner,
+                &accounts,
+                &mint_key,
                 &multisig_key,
-                &[&signer_keys[0]],
+                &[&signer_key, &signer2_key],
             )
             .unwrap(),
-            vec![
-                &mut account,
-                &mut multisig_account,
-                &mut account_info_iter.next().unwrap(),
-            ],
+            &mut store,
         )
         .unwrap();
+        for key in account_keys.iter() {
+            let account = Account::unpack(&store[key].data).unwrap();
+            assert_eq!(account.state, AccountState::Initialized);
+        }
+        assert_eq!(solana_program::program::get_return_data(), None);
     }
 
     #[test]
-    fn test_validate_owner() {
+    fn test_spending_limits() {
         let program_id = crate::id();
         let owner_key = Pubkey::new_unique();
-        let mut signer_keys = [Pubkey::default(); MAX_SIGNERS];
-        for signer_key in signer_keys.iter_mut().take(MAX_SIGNERS) {
-            *signer_key = Pubkey::new_unique();
-        }
-        let mut signer_lamports = 0;
-        let mut signer_data = vec![];
-        let mut signers = vec![
-            AccountInfo::new(
-                &owner_key,
-                true,
-                false,
-                &mut signer_lamports,
-                &mut signer_data,
+        let mint_key = Pubkey::new_unique();
+        let account_key = Pubkey::new_unique();
+        let account2_key = Pubkey::new_unique();
+        let mut store = HashMap::new();
+        store.insert(rent::id(), rent_sysvar());
+        store.insert(sysvar::clock::id(), clock_sysvar(1000));
+        store.insert(
+            mint_key,
+            SolanaAccount::new(
+                extended_mint_minimum_balance(),
+                Mint::EXTENDED_LEN,
                 &program_id,
-                false,
-                Epoch::default(),
-            );
-            MAX_SIGNERS + 1
-        ];
-        for (signer, key) in signers.iter_mut().zip(&signer_keys) {
-            signer.key = key;
-        }
-        let mut lamports = 0;
-        let mut data = vec![0; Multisig::get_packed_len()];
-        let mut multisig = Multisig::unpack_unchecked(&data).unwrap();
-        multisig.m = MAX_SIGNERS as u8;
-        multisig.n = MAX_SIGNERS as u8;
-        multisig.signers = signer_keys;
-        multisig.is_initialized = true;
-        Multisig::pack(multisig, &mut data).unwrap();
-        let owner_account_info = AccountInfo::new(
-            &owner_key,
-            false,
-            false,
-            &mut lamports,
-            &mut data,
-            &program_id,
-            false,
-            Epoch::default(),
-        );
-
-        // full 11 of 11
-        Processor::validate_owner(&program_id, &owner_key, &owner_account_info, &signers).unwrap();
-
-        // 1 of 11
-        {
-            let mut multisig =
-                Multisig::unpack_unchecked(&owner_account_info.data.borrow()).unwrap();
-            multisig.m = 1;
-            Multisig::pack(multisig, &mut owner_account_info.data.borrow_mut()).unwrap();
-        }
-        Processor::validate_owner(&program_id, &owner_key, &owner_account_info, &signers).unwrap();
-
-        // 2:1
-        {
-            let mut multisig =
-                Multisig::unpack_unchecked(&owner_account_info.data.borrow()).unwrap();
-            multisig.m = 2;
-            multisig.n = 1;
-            Multisig::pack(multisig, &mut owner_account_info.data.borrow_mut()).unwrap();
-        }
-        assert_eq!(
-            Err(ProgramError::MissingRequiredSignature),
-            Processor::validate_owner(&program_id, &owner_key, &owner_account_info, &signers)
-        );
-
-        // 0:11
-        {
-            let mut multisig =
-                Multisig::unpack_unchecked(&owner_account_info.data.borrow()).unwrap();
-            multisig.m = 0;
-            multisig.n = 11;
-            Multisig::pack(multisig, &mut owner_account_info.data.borrow_mut()).unwrap();
-        }
-        Processor::validate_owner(&program_id, &owner_key, &owner_account_info, &signers).unwrap();
-
-        // 2:11 but 0 provided
-        {
-            let mut multisig =
-                Multisig::unpack_unchecked(&owner_account_info.data.borrow()).unwrap();
-            multisig.m = 2;
-            multisig.n = 11;
-            Multisig::pack(multisig, &mut owner_account_info.data.borrow_mut()).unwrap();
-        }
-        assert_eq!(
-            Err(ProgramError::MissingRequiredSignature),
-            Processor::validate_owner(&program_id, &owner_key, &owner_account_info, &[])
+            ),
         );
-        // 2:11 but 1 provided
-        {
-            let mut multisig =
-                Multisig::unpack_unchecked(&owner_account_info.data.borrow()).unwrap();
-            multisig.m = 2;
-            multisig.n = 11;
-            Multisig::pack(multisig, &mut owner_account_info.data.borrow_mut()).unwrap();
+        for key in &[account_key, account2_key] {
+            store.insert(
+                *key,
+                SolanaAccount::new(
+                    extended_account_minimum_balance(),
+                    Account::EXTENDED_LEN,
+                    &program_id,
+                ),
+            );
         }
-        assert_eq!(
-            Err(ProgramError::MissingRequiredSignature),
-            Processor::validate_owner(&program_id, &owner_key, &owner_account_info, &signers[0..1])
-        );
 
-        // 2:11, 2 from middle provided
-        {
-            let mut multisig =
-                Multisig::unpack_unchecked(&owner_account_info.data.borrow()).unwrap();
-            multisig.m = 2;
-            multisig.n = 11;
-            Multisig::pack(multisig, &mut owner_account_info.data.borrow_mut()).unwrap();
-        }
-        Processor::validate_owner(&program_id, &owner_key, &owner_account_info, &signers[5..7])
+        // create mint and accounts
+        do_process_instruction_with_store(
+            initialize_mint(&program_id, &mint_key, &owner_key, None, 2).unwrap(),
+            &mut store,
+        )
+        .unwrap();
+        for key in &[account_key, account2_key] {
+            do_process_instruction_with_store(
+                initialize_account(&program_id, key, &mint_key, &owner_key).unwrap(),
+                &mut store,
+            )
             .unwrap();
-
-        // 11:11, one is not a signer
-        {
-            let mut multisig =
-                Multisig::unpack_unchecked(&owner_account_info.data.borrow()).unwrap();
-            multisig.m = 11;
-            multisig.n = 11;
-            Multisig::pack(multisig, &mut owner_account_info.data.borrow_mut()).unwrap();
         }
-        signers[5].is_signer = false;
-        assert_eq!(
-            Err(ProgramError::MissingRequiredSignature),
-            Processor::validate_owner(&program_id, &owner_key, &owner_account_info, &signers)
-        );
-        signers[5].is_signer = true;
+        do_process_instruction_with_store(
+            mint_to(&program_id, &mint_key, &account_key, &owner_key, &[], 5000).unwrap(),
+            &mut store,
+        )
+        .unwrap();
 
-        // 11:11, single signer signs multiple times
-        {
-            let mut signer_lamports = 0;
-            let mut signer_data = vec![];
-            let signers = vec![
-                AccountInfo::new(
-                    &signer_keys[5],
-                    true,
-                    false,
-                    &mut signer_lamports,
-                    &mut signer_data,
+        let with_clock = |mut instruction: Instruction| {
+            instruction
+                .accounts
+                .push(AccountMeta::new_readonly(sysvar::clock::id(), false));
+            instruction
+        };
+        let transfer_out = |amount| {
+            with_clock(with_mint(
+                transfer(
                     &program_id,
-                    false,
-                    Epoch::default(),
-                );
-                MAX_SIGNERS + 1
-            ];
-            let mut multisig =
-                Multisig::unpack_unchecked(&owner_account_info.data.borrow()).unwrap();
-            multisig.m = 11;
-            multisig.n = 11;
-            Multisig::pack(multisig, &mut owner_account_info.data.borrow_mut()).unwrap();
-            assert_eq!(
-                Err(ProgramError::MissingRequiredSignature),
-                Processor::validate_owner(&program_id, &owner_key, &owner_account_info, &signers)
-            );
-        }
-    }
-
-    #[test]
-    fn test_close_account_dups() {
-        let program_id = crate::id();
-        let account1_key = Pubkey::new_unique();
-        let mut account1_account = SolanaAccount::new(
-            account_minimum_balance(),
-            Account::get_packed_len(),
-            &program_id,
+                    &account_key,
+                    &account2_key,
+                    &owner_key,
+                    &[],
+                    amount,
+                )
+                .unwrap(),
+                &mint_key,
+            ))
+        };
+        let burn_out = |amount| {
+            with_clock(
+                burn(
+                    &program_id,
+                    &account_key,
+                    &mint_key,
+                    &owner_key,
+                    &[],
+                    amount,
+                )
+                .unwrap(),
+            )
+        };
+
+        // empty window
+        assert_eq!(
+            Err(ProgramError::InvalidArgument),
+            do_process_instruction_with_store(
+                set_spending_limit(&program_id, &account_key, &owner_key, &[], 300, 0).unwrap(),
+                &mut store,
+            )
         );
-        let account1_info: AccountInfo = (&account1_key, true, &mut account1_account).into();
-        let account2_key = Pubkey::new_unique();
-        let mut account2_account = SolanaAccount::new(
-            account_minimum_balance(),
-            Account::get_packed_len(),
-            &program_id,
+
+        // not the owner
+        assert_eq!(
+            Err(TokenError::OwnerMismatch.into()),
+            do_process_instruction_with_store(
+                set_spending_limit(&program_id, &account_key, &account2_key, &[], 300, 100)
+                    .unwrap(),
+                &mut store,
+            )
         );
-        let account2_info: AccountInfo = (&account2_key, true, &mut account2_account).into();
-        let owner_key = Pubkey::new_unique();
-        let mint_key = Pubkey::new_unique();
-        let mut mint_account =
-            SolanaAccount::new(mint_minimum_balance(), Mint::get_packed_len(), &program_id);
-        let mint_info: AccountInfo = (&mint_key, false, &mut mint_account).into();
-        let rent_key = rent::id();
-        let mut rent_sysvar = rent_sysvar();
-        let rent_info: AccountInfo = (&rent_key, false, &mut rent_sysvar).into();
 
-        // create mint
-        do_process_instruction_dups(
-            initialize_mint(&program_id, &mint_key, &owner_key, None, 2).unwrap(),
-            vec![mint_info.clone(), rent_info.clone()],
+        // limit outflows to 300 per 100 seconds
+        do_process_instruction_with_store(
+            set_spending_limit(&program_id, &account_key, &owner_key, &[], 300, 100).unwrap(),
+            &mut store,
         )
         .unwrap();
+        let account = Account::unpack(&store[&account_key].data).unwrap();
+        assert_eq!(account.spending_limit, COption::Some(300));
+        assert_eq!(account.spending_window, 100);
 
-        // create account
-        do_process_instruction_dups(
-            initialize_account(&program_id, &account1_key, &mint_key, &account1_key).unwrap(),
-            vec![
-                account1_info.clone(),
-                mint_info.clone(),
-                account1_info.clone(),
-                rent_info.clone(),
-            ],
+        // the clock is required
+        assert_eq!(
+            Err(ProgramError::NotEnoughAccountKeys),
+            do_process_instruction_with_store(
+                with_mint(
+                    transfer(
+                        &program_id,
+                        &account_key,
+                        &account2_key,
+                        &owner_key,
+                        &[],
+                        100,
+                    )
+                    .unwrap(),
+                    &mint_key,
+                ),
+                &mut store,
+            )
+        );
+
+        // transfers and burns share the limit, even when the owner signs
+        do_process_instruction_with_store(transfer_out(200), &mut store).unwrap();
+        assert_eq!(
+            Err(TokenError::SpendingLimitExceeded.into()),
+            do_process_instruction_with_store(burn_out(101), &mut store)
+        );
+        do_process_instruction_with_store(burn_out(100), &mut store).unwrap();
+        assert_eq!(
+            Err(TokenError::SpendingLimitExceeded.into()),
+            do_process_instruction_with_store(transfer_out(1), &mut store)
+        );
+        let account = Account::unpack(&store[&account_key].data).unwrap();
+        assert_eq!(account.spending_window_start, 1000);
+        assert_eq!(account.spending_window_spent, 300);
+
+        // a new window
+        store.insert(sysvar::clock::id(), clock_sysvar(1100));
+        do_process_instruction_with_store(transfer_out(300), &mut store).unwrap();
+        let account = Account::unpack(&store[&account_key].data).unwrap();
+        assert_eq!(account.spending_window_start, 1100);
+        assert_eq!(account.spending_window_spent, 300);
+
+        // raising the limit is delayed
+        do_process_instruction_with_store(
+            set_spending_limit(&program_id, &account_key, &owner_key, &[], 500, 100).unwrap(),
+            &mut store,
         )
         .unwrap();
+        let account = Account::unpack(&store[&account_key].data).unwrap();
+        assert_eq!(account.spending_limit, COption::Some(300));
+        assert_eq!(account.pending_spending_limit, COption::Some(500));
+        assert_eq!(
+            account.spending_limit_unlock_timestamp,
+            1100 + SPENDING_LIMIT_INCREASE_DELAY
+        );
+        store.insert(sysvar::clock::id(), clock_sysvar(1200));
+        assert_eq!(
+            Err(TokenError::SpendingLimitExceeded.into()),
+            do_process_instruction_with_store(transfer_out(301), &mut store)
+        );
 
-        // source-owner close
-        do_process_instruction_dups(
-            close_account(
-                &program_id,
-                &account1_key,
-                &account2_key,
-                &account1_key,
-                &[],
-            )
-            .unwrap(),
-            vec![
-                account1_info.clone(),
-                account2_info.clone(),
-                account1_info.clone(),
-            ],
+        // and takes effect once the delay elapses
+        store.insert(
+            sysvar::clock::id(),
+            clock_sysvar(1100 + SPENDING_LIMIT_INCREASE_DELAY),
+        );
+        do_process_instruction_with_store(transfer_out(500), &mut store).unwrap();
+        let account = Account::unpack(&store[&account_key].data).unwrap();
+        assert_eq!(account.spending_limit, COption::Some(500));
+        assert_eq!(account.pending_spending_limit, COption::None);
+
+        // shortening the window is delayed too
+        do_process_instruction_with_store(
+            set_spending_limit(&program_id, &account_key, &owner_key, &[], 500, 50).unwrap(),
+            &mut store,
         )
         .unwrap();
-
-        // source-close-authority close
-        let mut account = Account::unpack_unchecked(&account1_info.data.borrow()).unwrap();
-        account.close_authority = COption::Some(account1_key);
-        account.owner = owner_key;
-        Account::pack(account, &mut account1_info.data.borrow_mut()).unwrap();
-        do_process_instruction_dups(
-            close_account(
-                &program_id,
-                &account1_key,
-                &account2_key,
-                &account1_key,
-                &[],
-            )
-            .unwrap(),
-            vec![
-                account1_info.clone(),
-                account2_info.clone(),
-                account1_info.clone(),
-            ],
+        let account = Account::unpack(&store[&account_key].data).unwrap();
+        assert_eq!(account.spending_window, 100);
+        assert_eq!(account.pending_spending_window, 50);
+
+        // lowering the limit takes effect at once, dropping the pending change
+        do_process_instruction_with_store(
+            set_spending_limit(&program_id, &account_key, &owner_key, &[], 100, 100).unwrap(),
+            &mut store,
         )
         .unwrap();
+        let account = Account::unpack(&store[&account_key].data).unwrap();
+        assert_eq!(account.spending_limit, COption::Some(100));
+        assert_eq!(account.pending_spending_limit, COption::None);
+        store.insert(
+            sysvar::clock::id(),
+            clock_sysvar(1200 + SPENDING_LIMIT_INCREASE_DELAY),
+        );
+        assert_eq!(
+            Err(TokenError::SpendingLimitExceeded.into()),
+            do_process_instruction_with_store(transfer_out(101), &mut store)
+        );
+        do_process_instruction_with_store(transfer_out(100), &mut store).unwrap();
     }
 
     #[test]
-    fn test_close_account() {
+    fn test_destination_allowlists() {
         let program_id = crate::id();
+        let owner_key = Pubkey::new_unique();
+        let owner2_key = Pubkey::new_unique();
         let mint_key = Pubkey::new_unique();
-        let mut mint_account =
-            SolanaAccount::new(mint_minimum_balance(), Mint::get_packed_len(), &program_id);
         let account_key = Pubkey::new_unique();
-        let mut account_account = SolanaAccount::new(
-            account_minimum_balance(),
-            Account::get_packed_len(),
-            &program_id,
-        );
         let account2_key = Pubkey::new_unique();
-        let mut account2_account = SolanaAccount::new(
-            account_minimum_balance() + 42,
-            Account::get_packed_len(),
-            &program_id,
-        );
         let account3_key = Pubkey::new_unique();
-        let mut account3_account = SolanaAccount::new(
-            account_minimum_balance(),
-            Account::get_packed_len(),
-            &program_id,
-        );
-        let owner_key = Pubkey::new_unique();
-        let mut owner_account = SolanaAccount::default();
-        let owner2_key = Pubkey::new_unique();
-        let mut owner2_account = SolanaAccount::default();
-        let mut rent_sysvar = rent_sysvar();
-
-        // uninitialized
-        assert_eq!(
-            Err(ProgramError::UninitializedAccount),
-            do_process_instruction(
-                close_account(&program_id, &account_key, &account3_key, &owner2_key, &[]).unwrap(),
-                vec![
-                    &mut account_account,
-                    &mut account3_account,
-                    &mut owner2_account,
-                ],
-            )
+        let account4_key = Pubkey::new_unique();
+        let allowlist_key = Pubkey::new_unique();
+        let allowlist2_key = Pubkey::new_unique();
+        let mut store = HashMap::new();
+        store.insert(rent::id(), rent_sysvar());
+        store.insert(sysvar::clock::id(), clock_sysvar(1000));
+        store.insert(
+            mint_key,
+            SolanaAccount::new(
+                extended_mint_minimum_balance(),
+                Mint::EXTENDED_LEN,
+                &program_id,
+            ),
         );
+        for key in &[account_key, account2_key, account3_key, account4_key] {
+            store.insert(
+                *key,
+                SolanaAccount::new(
+                    extended_account_minimum_balance(),
+                    Account::EXTENDED_LEN,
+                    &program_id,
+                ),
+            );
+        }
+        for key in &[allowlist_key, allowlist2_key] {
+            store.insert(
+                *key,
+                SolanaAccount::new(
+                    Rent::default().minimum_balance(DestinationAllowlist::get_packed_len()),
+                    DestinationAllowlist::get_packed_len(),
+                    &program_id,
+                ),
+            );
+        }
 
-        // initialize and mint to non-native account
-        do_process_instruction(
+        // create mint and accounts
+        do_process_instruction_with_store(
             initialize_mint(&program_id, &mint_key, &owner_key, None, 2).unwrap(),
-            vec![&mut mint_account, &mut rent_sysvar],
-        )
-        .unwrap();
-        do_process_instruction(
-            initialize_account(&program_id, &account_key, &mint_key, &owner_key).unwrap(),
-            vec![
-                &mut account_account,
-                &mut mint_account,
-                &mut owner_account,
-                &mut rent_sysvar,
-            ],
+            &mut store,
         )
         .unwrap();
-        do_process_instruction(
-            mint_to(&program_id, &mint_key, &account_key, &owner_key, &[], 42).unwrap(),
-            vec![
-                &mut mint_account,
-                &mut account_account,
-                &mut owner_account,
-                &mut rent_sysvar,
-            ],
+        for (key, owner) in &[
+            (account_key, owner_key),
+            (account2_key, owner_key),
+            (account3_key, owner2_key),
+            (account4_key, owner2_key),
+        ] {
+            do_process_instruction_with_store(
+                initialize_account(&program_id, key, &mint_key, owner).unwrap(),
+                &mut store,
+            )
+            .unwrap();
+        }
+        do_process_instruction_with_store(
+            mint_to(&program_id, &mint_key, &account_key, &owner_key, &[], 1000).unwrap(),
+            &mut store,
         )
         .unwrap();
-        let account = Account::unpack_unchecked(&account_account.data).unwrap();
-        assert_eq!(account.amount, 42);
 
-        // initialize native account
-        do_process_instruction(
-            initialize_account(
+        let transfer_to = |destination: &Pubkey, amount| {
+            let mut instruction = transfer(
                 &program_id,
-                &account2_key,
-                &crate::native_mint::id(),
+                &account_key,
+                destination,
                 &owner_key,
+                &[],
+                amount,
             )
-            .unwrap(),
-            vec![
-                &mut account2_account,
-                &mut mint_account,
-                &mut owner_account,
-                &mut rent_sysvar,
-            ],
-        )
-        .unwrap();
-        let account = Account::unpack_unchecked(&account2_account.data).unwrap();
-        assert!(account.is_native());
-        assert_eq!(account.amount, 42);
+            .unwrap();
+            instruction
+                .accounts
+                .push(AccountMeta::new_readonly(mint_key, false));
+            instruction
+                .accounts
+                .push(AccountMeta::new_readonly(allowlist_key, false));
+            instruction
+                .accounts
+                .push(AccountMeta::new_readonly(sysvar::clock::id(), false));
+            instruction
+        };
 
-        // close non-native account with balance
+        // not the owner
         assert_eq!(
-            Err(TokenError::NonNativeHasBalance.into()),
-            do_process_instruction(
-                close_account(&program_id, &account_key, &account3_key, &owner_key, &[]).unwrap(),
-                vec![
-                    &mut account_account,
-                    &mut account3_account,
-                    &mut owner_account,
-                ],
+            Err(TokenError::OwnerMismatch.into()),
+            do_process_instruction_with_store(
+                initialize_destination_allowlist(
+                    &program_id,
+                    &allowlist_key,
+                    &account_key,
+                    &owner2_key,
+                    &[],
+                )
+                .unwrap(),
+                &mut store,
             )
         );
-        assert_eq!(account_account.lamports, account_minimum_balance());
 
-        // empty account
-        do_process_instruction(
-            burn(&program_id, &account_key, &mint_key, &owner_key, &[], 42).unwrap(),
-            vec![&mut account_account, &mut mint_account, &mut owner_account],
+        // restrict the account's destinations
+        do_process_instruction_with_store(
+            initialize_destination_allowlist(
+                &program_id,
+                &allowlist_key,
+                &account_key,
+                &owner_key,
+                &[],
+            )
+            .unwrap(),
+            &mut store,
         )
         .unwrap();
+        let account = Account::unpack(&store[&account_key].data).unwrap();
+        assert_eq!(account.destination_allowlist, COption::Some(allowlist_key));
+        let allowlist = DestinationAllowlist::unpack(&store[&allowlist_key].data).unwrap();
+        assert_eq!(allowlist.account, account_key);
+        assert_eq!(allowlist.n, 0);
 
-        // wrong owner
+        // the account already has an allowlist
         assert_eq!(
-            Err(TokenError::OwnerMismatch.into()),
-            do_process_instruction(
-                close_account(&program_id, &account_key, &account3_key, &owner2_key, &[]).unwrap(),
-                vec![
-                    &mut account_account,
-                    &mut account3_account,
-                    &mut owner2_account,
-                ],
+            Err(TokenError::AlreadyInUse.into()),
+            do_process_instruction_with_store(
+                initialize_destination_allowlist(
+                    &program_id,
+                    &allowlist2_key,
+                    &account_key,
+                    &owner_key,
+                    &[],
+                )
+                .unwrap(),
+                &mut store,
             )
         );
 
-        // close account
-        do_process_instruction(
-            close_account(&program_id, &account_key, &account3_key, &owner_key, &[]).unwrap(),
-            vec![
-                &mut account_account,
-                &mut account3_account,
-                &mut owner_account,
-            ],
+        // the allowlist and clock are required
+        assert_eq!(
+            Err(ProgramError::NotEnoughAccountKeys),
+            do_process_instruction_with_store(
+                with_mint(
+                    transfer(
+                        &program_id,
+                        &account_key,
+                        &account2_key,
+                        &owner_key,
+                        &[],
+                        100,
+                    )
+                    .unwrap(),
+                    &mint_key,
+                ),
+                &mut store,
+            )
+        );
+
+        // an empty allowlist allows nothing
+        assert_eq!(
+            Err(TokenError::DestinationNotAllowed.into()),
+            do_process_instruction_with_store(transfer_to(&account2_key, 100), &mut store)
+        );
+
+        // allow an account
+        do_process_instruction_with_store(
+            add_allowed_destination(
+                &program_id,
+                &allowlist_key,
+                &account_key,
+                &owner_key,
+                &[],
+                &account2_key,
+            )
+            .unwrap(),
+            &mut store,
         )
         .unwrap();
-        assert_eq!(account_account.lamports, 0);
-        assert_eq!(account3_account.lamports, 2 * account_minimum_balance());
-        let account = Account::unpack_unchecked(&account_account.data).unwrap();
-        assert_eq!(account.amount, 0);
+        let allowlist = DestinationAllowlist::unpack(&store[&allowlist_key].data).unwrap();
+        assert_eq!(allowlist.n, 1);
+        assert_eq!(allowlist.destinations[0], account2_key);
+        assert_eq!(
+            allowlist.active_timestamps[0],
+            1000 + DESTINATION_ALLOWLIST_DELAY
+        );
+        assert_eq!(
+            Err(TokenError::DestinationAlreadyAllowed.into()),
+            do_process_instruction_with_store(
+                add_allowed_destination(
+                    &program_id,
+                    &allowlist_key,
+                    &account_key,
+                    &owner_key,
+                    &[],
+                    &account2_key,
+                )
+                .unwrap(),
+                &mut store,
+            )
+        );
 
-        // fund and initialize new non-native account to test close authority
-        let account_key = Pubkey::new_unique();
-        let mut account_account = SolanaAccount::new(
-            account_minimum_balance(),
-            Account::get_packed_len(),
-            &program_id,
+        // not until the delay elapses
+        assert_eq!(
+            Err(TokenError::DestinationNotAllowed.into()),
+            do_process_instruction_with_store(transfer_to(&account2_key, 100), &mut store)
         );
-        let owner2_key = Pubkey::new_unique();
-        let mut owner2_account = SolanaAccount::new(
-            account_minimum_balance(),
-            Account::get_packed_len(),
-            &program_id,
+        store.insert(
+            sysvar::clock::id(),
+            clock_sysvar(1000 + DESTINATION_ALLOWLIST_DELAY),
         );
-        do_process_instruction(
-            initialize_account(&program_id, &account_key, &mint_key, &owner_key).unwrap(),
-            vec![
-                &mut account_account,
-                &mut mint_account,
-                &mut owner_account,
-                &mut rent_sysvar,
-            ],
+        do_process_instruction_with_store(transfer_to(&account2_key, 100), &mut store).unwrap();
+        assert_eq!(
+            Err(TokenError::DestinationNotAllowed.into()),
+            do_process_instruction_with_store(transfer_to(&account3_key, 100), &mut store)
+        );
+
+        // allow all of an owner's accounts
+        do_process_instruction_with_store(
+            add_allowed_destination(
+                &program_id,
+                &allowlist_key,
+                &account_key,
+                &owner_key,
+                &[],
+                &owner2_key,
+            )
+            .unwrap(),
+            &mut store,
         )
         .unwrap();
-        account_account.lamports = 2;
+        store.insert(
+            sysvar::clock::id(),
+            clock_sysvar(1000 + 2 * DESTINATION_ALLOWLIST_DELAY),
+        );
+        do_process_instruction_with_store(transfer_to(&account3_key, 100), &mut store).unwrap();
+        do_process_instruction_with_store(transfer_to(&account4_key, 100), &mut store).unwrap();
 
-        do_process_instruction(
-            set_authority(
+        // removal takes effect at once
+        do_process_instruction_with_store(
+            remove_allowed_destination(
                 &program_id,
+                &allowlist_key,
                 &account_key,
-                Some(&owner2_key),
-                AuthorityType::CloseAccount,
                 &owner_key,
                 &[],
+                &account2_key,
             )
             .unwrap(),
-            vec![&mut account_account, &mut owner_account],
+            &mut store,
         )
         .unwrap();
-
-        // account owner cannot authorize close if close_authority is set
+        let allowlist = DestinationAllowlist::unpack(&store[&allowlist_key].data).unwrap();
+        assert_eq!(allowlist.n, 1);
+        assert_eq!(allowlist.destinations[0], owner2_key);
+        assert_eq!(allowlist.destinations[1], Pubkey::default());
         assert_eq!(
-            Err(TokenError::OwnerMismatch.into()),
-            do_process_instruction(
-                close_account(&program_id, &account_key, &account3_key, &owner_key, &[]).unwrap(),
-                vec![
-                    &mut account_account,
-                    &mut account3_account,
-                    &mut owner_account,
-                ],
+            Err(TokenError::DestinationNotAllowed.into()),
+            do_process_instruction_with_store(transfer_to(&account2_key, 100), &mut store)
+        );
+        do_process_instruction_with_store(transfer_to(&account3_key, 100), &mut store).unwrap();
+        assert_eq!(
+            Err(TokenError::DestinationNotFound.into()),
+            do_process_instruction_with_store(
+                remove_allowed_destination(
+                    &program_id,
+                    &allowlist_key,
+                    &account_key,
+                    &owner_key,
+                    &[],
+                    &account2_key,
+                )
+                .unwrap(),
+                &mut store,
             )
         );
 
-        // close non-native account with close_authority
-        do_process_instruction(
-            close_account(&program_id, &account_key, &account3_key, &owner2_key, &[]).unwrap(),
-            vec![
-                &mut account_account,
-                &mut account3_account,
-                &mut owner2_account,
-            ],
+        // another account's allowlist
+        do_process_instruction_with_store(
+            initialize_destination_allowlist(
+                &program_id,
+                &allowlist2_key,
+                &account2_key,
+                &owner_key,
+                &[],
+            )
+            .unwrap(),
+            &mut store,
         )
         .unwrap();
-        assert_eq!(account_account.lamports, 0);
-        assert_eq!(account3_account.lamports, 2 * account_minimum_balance() + 2);
-        let account = Account::unpack_unchecked(&account_account.data).unwrap();
-        assert_eq!(account.amount, 0);
+        assert_eq!(
+            Err(TokenError::AllowlistMismatch.into()),
+            do_process_instruction_with_store(
+                add_allowed_destination(
+                    &program_id,
+                    &allowlist2_key,
+                    &account_key,
+                    &owner_key,
+                    &[],
+                    &account2_key,
+                )
+                .unwrap(),
+                &mut store,
+            )
+        );
 
-        // close native account
-        do_process_instruction(
-            close_account(&program_id, &account2_key, &account3_key, &owner_key, &[]).unwrap(),
-            vec![
-                &mut account2_account,
-                &mut account3_account,
-                &mut owner_account,
-            ],
-        )
-        .unwrap();
-        let account = Account::unpack_unchecked(&account2_account.data).unwrap();
-        assert!(account.is_native());
-        assert_eq!(account_account.lamports, 0);
-        assert_eq!(account.amount, 0);
+        // not the owner
         assert_eq!(
-            account3_account.lamports,
-            3 * account_minimum_balance() + 2 + 42
+            Err(TokenError::OwnerMismatch.into()),
+            do_process_instruction_with_store(
+                add_allowed_destination(
+                    &program_id,
+                    &allowlist_key,
+                    &account_key,
+                    &owner2_key,
+                    &[],
+                    &account2_key,
+                )
+                .unwrap(),
+                &mut store,
+            )
+        );
+
+        // the allowlist is full
+        for _ in 1..MAX_ALLOWED_DESTINATIONS {
+            do_process_instruction_with_store(
+                add_allowed_destination(
+                    &program_id,
+                    &allowlist_key,
+                    &account_key,
+                    &owner_key,
+                    &[],
+                    &Pubkey::new_unique(),
+                )
+                .unwrap(),
+                &mut store,
+            )
+            .unwrap();
+        }
+        assert_eq!(
+            Err(TokenError::AllowlistFull.into()),
+            do_process_instruction_with_store(
+                add_allowed_destination(
+                    &program_id,
+                    &allowlist_key,
+                    &account_key,
+                    &owner_key,
+                    &[],
+                    &account2_key,
+                )
+                .unwrap(),
+                &mut store,
+            )
         );
     }
 
     #[test]
-    fn test_native_token() {
+    fn test_max_balance() {
         let program_id = crate::id();
-        let mut mint_account =
-            SolanaAccount::new(mint_minimum_balance(), Mint::get_packed_len(), &program_id);
+        let owner_key = Pubkey::new_unique();
+        let owner2_key = Pubkey::new_unique();
+        let mint_key = Pubkey::new_unique();
+        let treasury_key = Pubkey::new_unique();
         let account_key = Pubkey::new_unique();
-        let mut account_account = SolanaAccount::new(
-            account_minimum_balance() + 40,
-            Account::get_packed_len(),
-            &program_id,
-        );
         let account2_key = Pubkey::new_unique();
-        let mut account2_account = SolanaAccount::new(
-            account_minimum_balance(),
-            Account::get_packed_len(),
-            &program_id,
+        let mut store = HashMap::new();
+        store.insert(rent::id(), rent_sysvar());
+        store.insert(
+            mint_key,
+            SolanaAccount::new(
+                extended_mint_minimum_balance(),
+                Mint::EXTENDED_LEN,
+                &program_id,
+            ),
         );
-        let account3_key = Pubkey::new_unique();
-        let mut account3_account = SolanaAccount::new(account_minimum_balance(), 0, &program_id);
-        let owner_key = Pubkey::new_unique();
-        let mut owner_account = SolanaAccount::default();
-        let mut rent_sysvar = rent_sysvar();
+        for key in &[treasury_key, account_key, account2_key] {
+            store.insert(
+                *key,
+                SolanaAccount::new(
+                    extended_account_minimum_balance(),
+                    Account::EXTENDED_LEN,
+                    &program_id,
+                ),
+            );
+        }
 
-        // initialize native account
-        do_process_instruction(
-            initialize_account(
-                &program_id,
-                &account_key,
-                &crate::native_mint::id(),
-                &owner_key,
+        // create mint and accounts
+        do_process_instruction_with_store(
+            initialize_mint(&program_id, &mint_key, &owner_key, None, 2).unwrap(),
+            &mut store,
+        )
+        .unwrap();
+        for (key, owner) in &[
+            (treasury_key, owner_key),
+            (account_key, owner2_key),
+            (account2_key, owner2_key),
+        ] {
+            do_process_instruction_with_store(
+                initialize_account(&program_id, key, &mint_key, owner).unwrap(),
+                &mut store,
             )
-            .unwrap(),
-            vec![
-                &mut account_account,
-                &mut mint_account,
-                &mut owner_account,
-                &mut rent_sysvar,
-            ],
+            .unwrap();
+        }
+
+        // not the mint authority
+        assert_eq!(
+            Err(TokenError::OwnerMismatch.into()),
+            do_process_instruction_with_store(
+                set_max_balance(&program_id, &mint_key, &owner2_key, &[], Some(100)).unwrap(),
+                &mut store,
+            )
+        );
+
+        // cap balances at 100
+        do_process_instruction_with_store(
+            set_max_balance(&program_id, &mint_key, &owner_key, &[], Some(100)).unwrap(),
+            &mut store,
         )
         .unwrap();
-        let account = Account::unpack_unchecked(&account_account.data).unwrap();
-        assert!(account.is_native());
-        assert_eq!(account.amount, 40);
+        let mint = Mint::unpack(&store[&mint_key].data).unwrap();
+        assert_eq!(mint.max_balance, COption::Some(100));
 
-        // initialize native account
-        do_process_instruction(
-            initialize_account(
-                &program_id,
-                &account2_key,
-                &crate::native_mint::id(),
-                &owner_key,
+        // minting is capped
+        assert_eq!(
+            Err(TokenError::MaxBalanceExceeded.into()),
+            do_process_instruction_with_store(
+                mint_to(&program_id, &mint_key, &account_key, &owner_key, &[], 101).unwrap(),
+                &mut store,
             )
-            .unwrap(),
-            vec![
-                &mut account2_account,
-                &mut mint_account,
-                &mut owner_account,
-                &mut rent_sysvar,
-            ],
+        );
+        do_process_instruction_with_store(
+            mint_to(&program_id, &mint_key, &account_key, &owner_key, &[], 100).unwrap(),
+            &mut store,
         )
         .unwrap();
-        let account = Account::unpack_unchecked(&account2_account.data).unwrap();
-        assert!(account.is_native());
-        assert_eq!(account.amount, 0);
 
-        // mint_to unsupported
+        // exempt the treasury
         assert_eq!(
-            Err(TokenError::NativeNotSupported.into()),
-            do_process_instruction(
-                mint_to(
+            Err(TokenError::OwnerMismatch.into()),
+            do_process_instruction_with_store(
+                add_max_balance_exemption(&program_id, &treasury_key, &mint_key, &owner2_key, &[])
+                    .unwrap(),
+                &mut store,
+            )
+        );
+        do_process_instruction_with_store(
+            add_max_balance_exemption(&program_id, &treasury_key, &mint_key, &owner_key, &[])
+                .unwrap(),
+            &mut store,
+        )
+        .unwrap();
+        let treasury = Account::unpack(&store[&treasury_key].data).unwrap();
+        assert!(treasury.max_balance_exempt);
+        do_process_instruction_with_store(
+            mint_to(&program_id, &mint_key, &treasury_key, &owner_key, &[], 1000).unwrap(),
+            &mut store,
+        )
+        .unwrap();
+
+        // the mint is required
+        assert_eq!(
+            Err(ProgramError::NotEnoughAccountKeys),
+            do_process_instruction_with_store(
+                transfer(
                     &program_id,
-                    &crate::native_mint::id(),
-                    &account_key,
+                    &treasury_key,
+                    &account2_key,
                     &owner_key,
                     &[],
-                    42
+                    50,
                 )
                 .unwrap(),
-                vec![&mut mint_account, &mut account_account, &mut owner_account],
+                &mut store,
             )
         );
 
-        // burn unsupported
-        let bogus_mint_key = Pubkey::new_unique();
-        let mut bogus_mint_account =
-            SolanaAccount::new(mint_minimum_balance(), Mint::get_packed_len(), &program_id);
-        do_process_instruction(
-            initialize_mint(&program_id, &bogus_mint_key, &owner_key, None, 2).unwrap(),
-            vec![&mut bogus_mint_account, &mut rent_sysvar],
+        // transfers are capped
+        assert_eq!(
+            Err(TokenError::MaxBalanceExceeded.into()),
+            do_process_instruction_with_store(
+                with_mint(
+                    transfer(
+                        &program_id,
+                        &treasury_key,
+                        &account2_key,
+                        &owner_key,
+                        &[],
+                        101,
+                    )
+                    .unwrap(),
+                    &mint_key,
+                ),
+                &mut store,
+            )
+        );
+        do_process_instruction_with_store(
+            with_mint(
+                transfer(
+                    &program_id,
+                    &treasury_key,
+                    &account2_key,
+                    &owner_key,
+                    &[],
+                    100,
+                )
+                .unwrap(),
+                &mint_key,
+            ),
+            &mut store,
         )
         .unwrap();
-
         assert_eq!(
-            Err(TokenError::NativeNotSupported.into()),
-            do_process_instruction(
-                burn(
+            Err(TokenError::MaxBalanceExceeded.into()),
+            do_process_instruction_with_store(
+                transfer_checked(
                     &program_id,
                     &account_key,
-                    &bogus_mint_key,
-                    &owner_key,
+                    &mint_key,
+                    &account2_key,
+                    &owner2_key,
                     &[],
-                    42
+                    1,
+                    2,
                 )
                 .unwrap(),
-                vec![
-                    &mut account_account,
-                    &mut bogus_mint_account,
-                    &mut owner_account
-                ],
+                &mut store,
             )
         );
 
-        // ensure can't transfer below rent-exempt reserve
+        // the exemption may be removed
+        do_process_instruction_with_store(
+            remove_max_balance_exemption(&program_id, &treasury_key, &mint_key, &owner_key, &[])
+                .unwrap(),
+            &mut store,
+        )
+        .unwrap();
         assert_eq!(
-            Err(TokenError::InsufficientFunds.into()),
-            do_process_instruction(
+            Err(TokenError::MaxBalanceExceeded.into()),
+            do_process_instruction_with_store(
+                with_mint(
+                    transfer(
+                        &program_id,
+                        &account_key,
+                        &treasury_key,
+                        &owner2_key,
+                        &[],
+                        1,
+                    )
+                    .unwrap(),
+                    &mint_key,
+                ),
+                &mut store,
+            )
+        );
+
+        // the maximum balance may be raised while tokens exist
+        do_process_instruction_with_store(
+            set_max_balance(&program_id, &mint_key, &owner_key, &[], Some(1000)).unwrap(),
+            &mut store,
+        )
+        .unwrap();
+        do_process_instruction_with_store(
+            with_mint(
                 transfer(
                     &program_id,
                     &account_key,
                     &account2_key,
-                    &owner_key,
+                    &owner2_key,
                     &[],
-                    50,
+                    100,
                 )
                 .unwrap(),
-                vec![
-                    &mut account_account,
-                    &mut account2_account,
-                    &mut owner_account,
-                ],
-            )
-        );
+                &mint_key,
+            ),
+            &mut store,
+        )
+        .unwrap();
 
-        // transfer between native accounts
-        do_process_instruction(
+        // or removed, and reintroduced while tokens are outstanding
+        do_process_instruction_with_store(
+            set_max_balance(&program_id, &mint_key, &owner_key, &[], None).unwrap(),
+            &mut store,
+        )
+        .unwrap();
+        do_process_instruction_with_store(
+            set_max_balance(&program_id, &mint_key, &owner_key, &[], Some(100)).unwrap(),
+            &mut store,
+        )
+        .unwrap();
+        let big_transfer = with_mint(
             transfer(
                 &program_id,
+                &treasury_key,
                 &account_key,
-                &account2_key,
                 &owner_key,
                 &[],
-                40,
+                500,
             )
             .unwrap(),
-            vec![
-                &mut account_account,
-                &mut account2_account,
-                &mut owner_account,
-            ],
-        )
-        .unwrap();
-        assert_eq!(account_account.lamports, account_minimum_balance());
-        let account = Account::unpack_unchecked(&account_account.data).unwrap();
-        assert!(account.is_native());
-        assert_eq!(account.amount, 0);
-        assert_eq!(account2_account.lamports, account_minimum_balance() + 40);
-        let account = Account::unpack_unchecked(&account2_account.data).unwrap();
-        assert!(account.is_native());
-        assert_eq!(account.amount, 40);
-
-        // close native account
-        do_process_instruction(
-            close_account(&program_id, &account_key, &account3_key, &owner_key, &[]).unwrap(),
-            vec![
-                &mut account_account,
-                &mut account3_account,
-                &mut owner_account,
-            ],
+            &mint_key,
+        );
+        assert_eq!(
+            Err(TokenError::MaxBalanceExceeded.into()),
+            do_process_instruction_with_store(big_transfer.clone(), &mut store)
+        );
+        do_process_instruction_with_store(
+            set_max_balance(&program_id, &mint_key, &owner_key, &[], None).unwrap(),
+            &mut store,
         )
         .unwrap();
-        assert_eq!(account_account.lamports, 0);
-        assert_eq!(account3_account.lamports, 2 * account_minimum_balance());
-        let account = Account::unpack_unchecked(&account_account.data).unwrap();
-        assert!(account.is_native());
-        assert_eq!(account.amount, 0);
+        do_process_instruction_with_store(big_transfer, &mut store).unwrap();
+        let account = Account::unpack(&store[&account_key].data).unwrap();
+        assert_eq!(account.amount, 500);
     }
 
     #[test]
-    fn test_overflow() {
+    fn test_launch_controls() {
         let program_id = crate::id();
-        let account_key = Pubkey::new_unique();
-        let mut account_account = SolanaAccount::new(
-            account_minimum_balance(),
-            Account::get_packed_len(),
-            &program_id,
-        );
-        let account2_key = Pubkey::new_unique();
-        let mut account2_account = SolanaAccount::new(
-            account_minimum_balance(),
-            Account::get_packed_len(),
-            &program_id,
-        );
         let owner_key = Pubkey::new_unique();
-        let mut owner_account = SolanaAccount::default();
         let owner2_key = Pubkey::new_unique();
-        let mut owner2_account = SolanaAccount::default();
-        let mint_owner_key = Pubkey::new_unique();
-        let mut mint_owner_account = SolanaAccount::default();
         let mint_key = Pubkey::new_unique();
-        let mut mint_account =
-            SolanaAccount::new(mint_minimum_balance(), Mint::get_packed_len(), &program_id);
-        let mut rent_sysvar = rent_sysvar();
+        let issuer_key = Pubkey::new_unique();
+        let account_key = Pubkey::new_unique();
+        let account2_key = Pubkey::new_unique();
+        let mut store = HashMap::new();
+        store.insert(rent::id(), rent_sysvar());
+        store.insert(sysvar::clock::id(), clock_sysvar(1000));
+        store.insert(
+            mint_key,
+            SolanaAccount::new(
+                extended_mint_minimum_balance(),
+                Mint::EXTENDED_LEN,
+                &program_id,
+            ),
+        );
+        for key in &[issuer_key, account_key, account2_key] {
+            store.insert(
+                *key,
+                SolanaAccount::new(
+                    extended_account_minimum_balance(),
+                    Account::EXTENDED_LEN,
+                    &program_id,
+                ),
+            );
+        }
 
-        // create new mint with owner
-        do_process_instruction(
-            initialize_mint(&program_id, &mint_key, &mint_owner_key, None, 2).unwrap(),
-            vec![&mut mint_account, &mut rent_sysvar],
+        // create mint and accounts
+        do_process_instruction_with_store(
+            initialize_mint(&program_id, &mint_key, &owner_key, None, 2).unwrap(),
+            &mut store,
         )
         .unwrap();
+        for (key, owner) in &[
+            (issuer_key, owner_key),
+            (account_key, owner2_key),
+            (account2_key, owner2_key),
+        ] {
+            do_process_instruction_with_store(
+                initialize_account(&program_id, key, &mint_key, owner).unwrap(),
+                &mut store,
+            )
+            .unwrap();
+        }
 
-        // create an account
-        do_process_instruction(
-            initialize_account(&program_id, &account_key, &mint_key, &owner_key).unwrap(),
-            vec![
-                &mut account_account,
-                &mut mint_account,
-                &mut owner_account,
-                &mut rent_sysvar,
-            ],
+        let transfer_out = |source: &Pubkey, destination: &Pubkey, owner: &Pubkey| {
+            let mut instruction =
+                transfer(&program_id, source, destination, owner, &[], 10).unwrap();
+            instruction
+                .accounts
+                .push(AccountMeta::new_readonly(mint_key, false));
+            instruction
+                .accounts
+                .push(AccountMeta::new_readonly(sysvar::clock::id(), false));
+            instruction
+        };
+
+        // not the mint authority
+        assert_eq!(
+            Err(TokenError::OwnerMismatch.into()),
+            do_process_instruction_with_store(
+                set_launch_controls(&program_id, &mint_key, &owner2_key, &[], 2000, 100).unwrap(),
+                &mut store,
+            )
+        );
+
+        // trading starts at 2000, with a 100 second cooldown
+        do_process_instruction_with_store(
+            set_launch_controls(&program_id, &mint_key, &owner_key, &[], 2000, 100).unwrap(),
+            &mut store,
         )
         .unwrap();
-
-        // create another account
-        do_process_instruction(
-            initialize_account(&program_id, &account2_key, &mint_key, &owner2_key).unwrap(),
-            vec![
-                &mut account2_account,
-                &mut mint_account,
-                &mut owner2_account,
-                &mut rent_sysvar,
-            ],
+        let mint = Mint::unpack(&store[&mint_key].data).unwrap();
+        assert_eq!(mint.trading_start_timestamp, 2000);
+        assert_eq!(mint.transfer_cooldown, 100);
+        do_process_instruction_with_store(
+            mint_to(&program_id, &mint_key, &issuer_key, &owner_key, &[], 1000).unwrap(),
+            &mut store,
         )
         .unwrap();
 
-        // mint the max to an account
-        do_process_instruction(
-            mint_to(
-                &program_id,
-                &mint_key,
-                &account_key,
-                &mint_owner_key,
-                &[],
-                u64::MAX,
-            )
-            .unwrap(),
-            vec![
-                &mut mint_account,
-                &mut account_account,
-                &mut mint_owner_account,
-            ],
+        // the issuer distributes before trading starts
+        do_process_instruction_with_store(
+            transfer_out(&issuer_key, &account_key, &owner_key),
+            &mut store,
         )
         .unwrap();
-        let account = Account::unpack_unchecked(&account_account.data).unwrap();
-        assert_eq!(account.amount, u64::MAX);
+        do_process_instruction_with_store(
+            transfer_out(&issuer_key, &account_key, &owner_key),
+            &mut store,
+        )
+        .unwrap();
+        let account = Account::unpack(&store[&account_key].data).unwrap();
+        assert_eq!(account.amount, 20);
 
-        // attempt to mint one more to account
+        // holders wait for trading to start
         assert_eq!(
-            Err(TokenError::Overflow.into()),
-            do_process_instruction(
-                mint_to(
-                    &program_id,
-                    &mint_key,
-                    &account_key,
-                    &mint_owner_key,
-                    &[],
-                    1,
-                )
-                .unwrap(),
-                vec![
-                    &mut mint_account,
-                    &mut account_account,
-                    &mut mint_owner_account,
-                ],
+            Err(TokenError::TradingNotStarted.into()),
+            do_process_instruction_with_store(
+                transfer_out(&account_key, &account2_key, &owner2_key),
+                &mut store,
             )
         );
-        let account = Account::unpack_unchecked(&account_account.data).unwrap();
-        assert_eq!(account.amount, u64::MAX);
 
-        // atttempt to mint one more to the other account
+        // the mint and clock are required
+        store.insert(sysvar::clock::id(), clock_sysvar(2000));
         assert_eq!(
-            Err(TokenError::Overflow.into()),
-            do_process_instruction(
-                mint_to(
+            Err(ProgramError::NotEnoughAccountKeys),
+            do_process_instruction_with_store(
+                transfer(
                     &program_id,
-                    &mint_key,
+                    &account_key,
                     &account2_key,
-                    &mint_owner_key,
+                    &owner2_key,
                     &[],
-                    1,
+                    10,
                 )
                 .unwrap(),
-                vec![
-                    &mut mint_account,
-                    &mut account2_account,
-                    &mut mint_owner_account,
-                ],
+                &mut store,
             )
         );
-
-        // burn some of the supply
-        do_process_instruction(
-            burn(&program_id, &account_key, &mint_key, &owner_key, &[], 100).unwrap(),
-            vec![&mut account_account, &mut mint_account, &mut owner_account],
+        let mut instruction = transfer(
+            &program_id,
+            &account_key,
+            &account2_key,
+            &owner2_key,
+            &[],
+            10,
         )
         .unwrap();
-        let account = Account::unpack_unchecked(&account_account.data).unwrap();
-        assert_eq!(account.amount, u64::MAX - 100);
+        instruction
+            .accounts
+            .push(AccountMeta::new_readonly(mint_key, false));
+        assert_eq!(
+            Err(ProgramError::NotEnoughAccountKeys),
+            do_process_instruction_with_store(instruction, &mut store)
+        );
 
-        do_process_instruction(
-            mint_to(
-                &program_id,
-                &mint_key,
-                &account_key,
-                &mint_owner_key,
-                &[],
-                100,
+        // one transfer per cooldown
+        do_process_instruction_with_store(
+            transfer_out(&account_key, &account2_key, &owner2_key),
+            &mut store,
+        )
+        .unwrap();
+        let account = Account::unpack(&store[&account_key].data).unwrap();
+        assert_eq!(account.last_transfer_timestamp, 2000);
+        store.insert(sysvar::clock::id(), clock_sysvar(2099));
+        assert_eq!(
+            Err(TokenError::TransferCooldown.into()),
+            do_process_instruction_with_store(
+                transfer_out(&account_key, &account2_key, &owner2_key),
+                &mut store,
             )
-            .unwrap(),
-            vec![
-                &mut mint_account,
-                &mut account_account,
-                &mut mint_owner_account,
-            ],
+        );
+        store.insert(sysvar::clock::id(), clock_sysvar(2100));
+        do_process_instruction_with_store(
+            transfer_out(&account_key, &account2_key, &owner2_key),
+            &mut store,
         )
         .unwrap();
-        let account = Account::unpack_unchecked(&account_account.data).unwrap();
-        assert_eq!(account.amount, u64::MAX);
 
-        // manipulate account balance to attempt overflow transfer
-        let mut account = Account::unpack_unchecked(&account2_account.data).unwrap();
-        account.amount = 1;
-        Account::pack(account, &mut account2_account.data).unwrap();
-
-        assert_eq!(
-            Err(TokenError::Overflow.into()),
-            do_process_instruction(
-                transfer(
-                    &program_id,
-                    &account2_key,
-                    &account_key,
-                    &owner2_key,
-                    &[],
-                    1,
-                )
-                .unwrap(),
-                vec![
-                    &mut account2_account,
-                    &mut account_account,
-                    &mut owner2_account,
-                ],
+        // launch controls may be lifted and reintroduced while tokens are outstanding
+        do_process_instruction_with_store(
+            set_launch_controls(&program_id, &mint_key, &owner_key, &[], 0, 0).unwrap(),
+            &mut store,
+        )
+        .unwrap();
+        do_process_instruction_with_store(
+            transfer_out(&account2_key, &account_key, &owner2_key),
+            &mut store,
+        )
+        .unwrap();
+        do_process_instruction_with_store(
+            transfer_out(&account2_key, &account_key, &owner2_key),
+            &mut store,
+        )
+        .unwrap();
+        do_process_instruction_with_store(
+            set_launch_controls(&program_id, &mint_key, &owner_key, &[], 0, 100).unwrap(),
+            &mut store,
+        )
+        .unwrap();
+        store.insert(sysvar::clock::id(), clock_sysvar(2200));
+        do_process_instruction_with_store(
+            transfer_out(&account_key, &account2_key, &owner2_key),
+            &mut store,
+        )
+        .unwrap();
+        assert_eq!(
+            Err(TokenError::TransferCooldown.into()),
+            do_process_instruction_with_store(
+                transfer_out(&account_key, &account2_key, &owner2_key),
+                &mut store,
             )
         );
     }
 
     #[test]
-    fn test_frozen() {
+    fn test_credentials() {
         let program_id = crate::id();
-        let account_key = Pubkey::new_unique();
-        let mut account_account = SolanaAccount::new(
-            account_minimum_balance(),
-            Account::get_packed_len(),
-            &program_id,
-        );
-        let account2_key = Pubkey::new_unique();
-        let mut account2_account = SolanaAccount::new(
-            account_minimum_balance(),
-            Account::get_packed_len(),
-            &program_id,
-        );
         let owner_key = Pubkey::new_unique();
-        let mut owner_account = SolanaAccount::default();
+        let owner2_key = Pubkey::new_unique();
+        let owner3_key = Pubkey::new_unique();
+        let compliance_key = Pubkey::new_unique();
+        let payer_key = Pubkey::new_unique();
         let mint_key = Pubkey::new_unique();
-        let mut mint_account =
-            SolanaAccount::new(mint_minimum_balance(), Mint::get_packed_len(), &program_id);
-        let mut rent_sysvar = rent_sysvar();
+        let issuer_key = Pubkey::new_unique();
+        let account2_key = Pubkey::new_unique();
+        let account3_key = Pubkey::new_unique();
+        let credential_key = crate::get_credential_address(&mint_key, &owner_key);
+        let credential2_key = crate::get_credential_address(&mint_key, &owner2_key);
+        let mut store = HashMap::new();
+        store.insert(rent::id(), rent_sysvar());
+        store.insert(sysvar::clock::id(), clock_sysvar(1000));
+        store.insert(
+            mint_key,
+            SolanaAccount::new(
+                extended_mint_minimum_balance(),
+                Mint::EXTENDED_LEN,
+                &program_id,
+            ),
+        );
+        for key in &[issuer_key, account2_key, account3_key] {
+            store.insert(
+                *key,
+                SolanaAccount::new(
+                    extended_account_minimum_balance(),
+                    Account::EXTENDED_LEN,
+                    &program_id,
+                ),
+            );
+        }
+        for key in &[credential_key, credential2_key] {
+            store.insert(
+                *key,
+                SolanaAccount::new(
+                    Rent::default().minimum_balance(Credential::get_packed_len()),
+                    Credential::get_packed_len(),
+                    &program_id,
+                ),
+            );
+        }
 
-        // create new mint and fund first account
-        do_process_instruction(
+        // create mint and accounts
+        do_process_instruction_with_store(
             initialize_mint(&program_id, &mint_key, &owner_key, None, 2).unwrap(),
-            vec![&mut mint_account, &mut rent_sysvar],
+            &mut store,
         )
         .unwrap();
+        for (key, owner) in &[
+            (issuer_key, owner_key),
+            (account2_key, owner2_key),
+            (account3_key, owner3_key),
+        ] {
+            do_process_instruction_with_store(
+                initialize_account(&program_id, key, &mint_key, owner).unwrap(),
+                &mut store,
+            )
+            .unwrap();
+        }
 
-        // create account
-        do_process_instruction(
-            initialize_account(&program_id, &account_key, &mint_key, &owner_key).unwrap(),
-            vec![
-                &mut account_account,
-                &mut mint_account,
-                &mut owner_account,
-                &mut rent_sysvar,
-            ],
-        )
-        .unwrap();
+        let issue = |holder: &Pubkey, authority: &Pubkey, expiry| {
+            issue_credential(
+                &program_id,
+                &mint_key,
+                holder,
+                &payer_key,
+                authority,
+                &[],
+                expiry,
+                840,
+            )
+            .unwrap()
+        };
+        let with_credentials = |mut instruction: Instruction, credentials: &[&Pubkey]| {
+            for credential in credentials {
+                instruction
+                    .accounts
+                    .push(AccountMeta::new_readonly(**credential, false));
+            }
+            instruction
+                .accounts
+                .push(AccountMeta::new_readonly(sysvar::clock::id(), false));
+            instruction
+        };
+        let transfer_to = |destination: &Pubkey, credentials: &[&Pubkey]| {
+            let mut instruction =
+                transfer(&program_id, &issuer_key, destination, &owner_key, &[], 10).unwrap();
+            instruction
+                .accounts
+                .push(AccountMeta::new_readonly(mint_key, false));
+            with_credentials(instruction, credentials)
+        };
 
-        // create another account
-        do_process_instruction(
-            initialize_account(&program_id, &account2_key, &mint_key, &owner_key).unwrap(),
-            vec![
-                &mut account2_account,
-                &mut mint_account,
-                &mut owner_account,
-                &mut rent_sysvar,
-            ],
-        )
-        .unwrap();
+        // the mint has no compliance authority
+        assert_eq!(
+            Err(TokenError::MintCannotIssueCredentials.into()),
+            do_process_instruction_with_store(issue(&owner_key, &owner_key, 2000), &mut store)
+        );
 
-        // fund first account
-        do_process_instruction(
-            mint_to(&program_id, &mint_key, &account_key, &owner_key, &[], 1000).unwrap(),
-            vec![&mut mint_account, &mut account_account, &mut owner_account],
+        // assign the compliance authority
+        do_process_instruction_with_store(
+            set_mint_role(
+                &program_id,
+                &mint_key,
+                Some(&compliance_key),
+                MintRole::ComplianceAuthority,
+                &owner_key,
+                &[],
+            )
+            .unwrap(),
+            &mut store,
         )
         .unwrap();
 
-        // no transfer if either account is frozen
-        let mut account = Account::unpack_unchecked(&account2_account.data).unwrap();
-        account.state = AccountState::Frozen;
-        Account::pack(account, &mut account2_account.data).unwrap();
+        // not the compliance authority
         assert_eq!(
-            Err(TokenError::AccountFrozen.into()),
-            do_process_instruction(
-                transfer(
-                    &program_id,
-                    &account_key,
-                    &account2_key,
-                    &owner_key,
-                    &[],
-                    500,
-                )
-                .unwrap(),
-                vec![
-                    &mut account_account,
-                    &mut account2_account,
-                    &mut owner_account,
-                ],
-            )
+            Err(TokenError::OwnerMismatch.into()),
+            do_process_instruction_with_store(issue(&owner_key, &owner_key, 2000), &mut store)
         );
 
-        let mut account = Account::unpack_unchecked(&account_account.data).unwrap();
-        account.state = AccountState::Initialized;
-        Account::pack(account, &mut account_account.data).unwrap();
-        let mut account = Account::unpack_unchecked(&account2_account.data).unwrap();
-        account.state = AccountState::Frozen;
-        Account::pack(account, &mut account2_account.data).unwrap();
+        // issue credentials
+        do_process_instruction_with_store(issue(&owner_key, &compliance_key, 2000), &mut store)
+            .unwrap();
+        do_process_instruction_with_store(issue(&owner2_key, &compliance_key, 2000), &mut store)
+            .unwrap();
+        let credential = Credential::unpack(&store[&credential2_key].data).unwrap();
+        assert_eq!(credential.mint, mint_key);
+        assert_eq!(credential.owner, owner2_key);
+        assert_eq!(credential.expiry, 2000);
+        assert_eq!(credential.jurisdiction, 840);
+
+        // minting requires the destination owner's credential
         assert_eq!(
-            Err(TokenError::AccountFrozen.into()),
-            do_process_instruction(
-                transfer(
-                    &program_id,
-                    &account_key,
-                    &account2_key,
-                    &owner_key,
-                    &[],
-                    500,
-                )
-                .unwrap(),
-                vec![
-                    &mut account_account,
-                    &mut account2_account,
-                    &mut owner_account,
-                ],
+            Err(ProgramError::NotEnoughAccountKeys),
+            do_process_instruction_with_store(
+                mint_to(&program_id, &mint_key, &issuer_key, &owner_key, &[], 1000).unwrap(),
+                &mut store,
             )
         );
-
-        // no approve if account is frozen
-        let mut account = Account::unpack_unchecked(&account_account.data).unwrap();
-        account.state = AccountState::Frozen;
-        Account::pack(account, &mut account_account.data).unwrap();
-        let delegate_key = Pubkey::new_unique();
-        let mut delegate_account = SolanaAccount::default();
         assert_eq!(
-            Err(TokenError::AccountFrozen.into()),
-            do_process_instruction(
-                approve(
-                    &program_id,
-                    &account_key,
-                    &delegate_key,
-                    &owner_key,
-                    &[],
-                    100
-                )
-                .unwrap(),
-                vec![
-                    &mut account_account,
-                    &mut delegate_account,
-                    &mut owner_account,
-                ],
+            Err(TokenError::CredentialRequired.into()),
+            do_process_instruction_with_store(
+                with_credentials(
+                    mint_to(&program_id, &mint_key, &account3_key, &owner_key, &[], 1000).unwrap(),
+                    &[&credential_key],
+                ),
+                &mut store,
             )
         );
+        do_process_instruction_with_store(
+            with_credentials(
+                mint_to(&program_id, &mint_key, &issuer_key, &owner_key, &[], 1000).unwrap(),
+                &[&credential_key],
+            ),
+            &mut store,
+        )
+        .unwrap();
 
-        // no revoke if account is frozen
-        let mut account = Account::unpack_unchecked(&account_account.data).unwrap();
-        account.delegate = COption::Some(delegate_key);
-        account.delegated_amount = 100;
-        Account::pack(account, &mut account_account.data).unwrap();
+        // transfers require both owners' credentials
         assert_eq!(
-            Err(TokenError::AccountFrozen.into()),
-            do_process_instruction(
-                revoke(&program_id, &account_key, &owner_key, &[]).unwrap(),
-                vec![&mut account_account, &mut owner_account],
+            Err(TokenError::CredentialRequired.into()),
+            do_process_instruction_with_store(
+                transfer_to(&account2_key, &[&credential_key]),
+                &mut store,
+            )
+        );
+        do_process_instruction_with_store(
+            transfer_to(&account2_key, &[&credential_key, &credential2_key]),
+            &mut store,
+        )
+        .unwrap();
+        assert_eq!(
+            Err(TokenError::CredentialRequired.into()),
+            do_process_instruction_with_store(
+                transfer_to(&account3_key, &[&credential_key, &credential2_key]),
+                &mut store,
             )
         );
 
-        // no set authority if account is frozen
-        let new_owner_key = Pubkey::new_unique();
+        // revocation takes effect at once
         assert_eq!(
-            Err(TokenError::AccountFrozen.into()),
-            do_process_instruction(
-                set_authority(
+            Err(TokenError::OwnerMismatch.into()),
+            do_process_instruction_with_store(
+                revoke_credential(
                     &program_id,
-                    &account_key,
-                    Some(&new_owner_key),
-                    AuthorityType::AccountOwner,
+                    &mint_key,
+                    &owner2_key,
+                    &payer_key,
                     &owner_key,
-                    &[]
+                    &[],
                 )
                 .unwrap(),
-                vec![&mut account_account, &mut owner_account,],
+                &mut store,
             )
         );
-
-        // no mint_to if destination account is frozen
+        do_process_instruction_with_store(
+            revoke_credential(
+                &program_id,
+                &mint_key,
+                &owner2_key,
+                &payer_key,
+                &compliance_key,
+                &[],
+            )
+            .unwrap(),
+            &mut store,
+        )
+        .unwrap();
+        assert_eq!(store[&credential2_key].lamports, 0);
         assert_eq!(
-            Err(TokenError::AccountFrozen.into()),
-            do_process_instruction(
-                mint_to(&program_id, &mint_key, &account_key, &owner_key, &[], 100).unwrap(),
-                vec![&mut mint_account, &mut account_account, &mut owner_account,],
+            Err(ProgramError::UninitializedAccount),
+            Credential::unpack(&store[&credential2_key].data)
+        );
+        assert_eq!(
+            Err(TokenError::CredentialRequired.into()),
+            do_process_instruction_with_store(
+                transfer_to(&account2_key, &[&credential_key, &credential2_key]),
+                &mut store,
             )
         );
 
-        // no burn if account is frozen
+        // credentials expire
+        do_process_instruction_with_store(issue(&owner2_key, &compliance_key, 3000), &mut store)
+            .unwrap();
+        store.insert(sysvar::clock::id(), clock_sysvar(2000));
         assert_eq!(
-            Err(TokenError::AccountFrozen.into()),
-            do_process_instruction(
-                burn(&program_id, &account_key, &mint_key, &owner_key, &[], 100).unwrap(),
-                vec![&mut account_account, &mut mint_account, &mut owner_account],
+            Err(TokenError::CredentialRequired.into()),
+            do_process_instruction_with_store(
+                transfer_to(&account2_key, &[&credential_key, &credential2_key]),
+                &mut store,
             )
         );
-    }
+        do_process_instruction_with_store(issue(&owner_key, &compliance_key, 3000), &mut store)
+            .unwrap();
+        do_process_instruction_with_store(
+            transfer_to(&account2_key, &[&credential_key, &credential2_key]),
+            &mut store,
+        )
+        .unwrap();
 
-    #[test]
-    fn test_freeze_thaw_dups() {
-        let program_id = crate::id();
-        let account1_key = Pubkey::new_unique();
-        let mut account1_account = SolanaAccount::new(
-            account_minimum_balance(),
-            Account::get_packed_len(),
-            &program_id,
-        );
-        let account1_info: AccountInfo = (&account1_key, true, &mut account1_account).into();
-        let owner_key = Pubkey::new_unique();
-        let mint_key = Pubkey::new_unique();
-        let mut mint_account =
-            SolanaAccount::new(mint_minimum_balance(), Mint::get_packed_len(), &program_id);
-        let mint_info: AccountInfo = (&mint_key, true, &mut mint_account).into();
-        let rent_key = rent::id();
-        let mut rent_sysvar = rent_sysvar();
-        let rent_info: AccountInfo = (&rent_key, false, &mut rent_sysvar).into();
+        // the compliance authority may be removed and reintroduced while tokens are outstanding
+        let set_compliance_authority = |new_authority| {
+            set_mint_role(
+                &program_id,
+                &mint_key,
+                new_authority,
+                MintRole::ComplianceAuthority,
+                &owner_key,
+                &[],
+            )
+            .unwrap()
+        };
+        do_process_instruction_with_store(set_compliance_authority(None), &mut store).unwrap();
+        do_process_instruction_with_store(
+            set_compliance_authority(Some(&compliance_key)),
+            &mut store,
+        )
+        .unwrap();
+        let mint = Mint::unpack(&store[&mint_key].data).unwrap();
+        assert_eq!(mint.compliance_authority, COption::Some(compliance_key));
 
-        // create mint
-        do_process_instruction_dups(
-            initialize_mint(&program_id, &mint_key, &owner_key, Some(&account1_key), 2).unwrap(),
-            vec![mint_info.clone(), rent_info.clone()],
+        // under a timelock, the compliance authority changes through the queue
+        let queue_compliance_change = |mint: &Pubkey, new_authority| {
+            queue_authority_change(
+                &program_id,
+                mint,
+                new_authority,
+                TimelockedAuthority::ComplianceAuthority,
+                &owner_key,
+                &[],
+            )
+            .unwrap()
+        };
+        do_process_instruction_with_store(
+            set_authority_timelock(&program_id, &mint_key, &owner_key, &[], 100).unwrap(),
+            &mut store,
+        )
+        .unwrap();
+        assert_eq!(
+            Err(TokenError::AuthorityChangeTimelocked.into()),
+            do_process_instruction_with_store(set_compliance_authority(None), &mut store)
+        );
+        do_process_instruction_with_store(queue_compliance_change(&mint_key, None), &mut store)
+            .unwrap();
+        store.insert(sysvar::clock::id(), clock_sysvar(2100));
+        do_process_instruction_with_store(
+            execute_authority_change(&program_id, &mint_key).unwrap(),
+            &mut store,
         )
         .unwrap();
+        let mint = Mint::unpack(&store[&mint_key].data).unwrap();
+        assert_eq!(mint.compliance_authority, COption::None);
 
-        // create account
-        do_process_instruction_dups(
-            initialize_account(&program_id, &account1_key, &mint_key, &account1_key).unwrap(),
-            vec![
-                account1_info.clone(),
-                mint_info.clone(),
-                account1_info.clone(),
-                rent_info.clone(),
-            ],
+        // rotate the compliance authority of a timelocked mint
+        let mint2_key = Pubkey::new_unique();
+        let compliance2_key = Pubkey::new_unique();
+        store.insert(
+            mint2_key,
+            SolanaAccount::new(
+                extended_mint_minimum_balance(),
+                Mint::EXTENDED_LEN,
+                &program_id,
+            ),
+        );
+        do_process_instruction_with_store(
+            initialize_mint(&program_id, &mint2_key, &owner_key, None, 2).unwrap(),
+            &mut store,
         )
         .unwrap();
-
-        // freeze where mint freeze_authority is account
-        do_process_instruction_dups(
-            freeze_account(&program_id, &account1_key, &mint_key, &account1_key, &[]).unwrap(),
-            vec![
-                account1_info.clone(),
-                mint_info.clone(),
-                account1_info.clone(),
-            ],
+        do_process_instruction_with_store(
+            set_mint_role(
+                &program_id,
+                &mint2_key,
+                Some(&compliance_key),
+                MintRole::ComplianceAuthority,
+                &owner_key,
+                &[],
+            )
+            .unwrap(),
+            &mut store,
         )
         .unwrap();
-
-        // thaw where mint freeze_authority is account
-        let mut account = Account::unpack_unchecked(&account1_info.data.borrow()).unwrap();
-        account.state = AccountState::Frozen;
-        Account::pack(account, &mut account1_info.data.borrow_mut()).unwrap();
-        do_process_instruction_dups(
-            thaw_account(&program_id, &account1_key, &mint_key, &account1_key, &[]).unwrap(),
-            vec![
-                account1_info.clone(),
-                mint_info.clone(),
-                account1_info.clone(),
-            ],
+        do_process_instruction_with_store(
+            set_authority_timelock(&program_id, &mint2_key, &owner_key, &[], 100).unwrap(),
+            &mut store,
+        )
+        .unwrap();
+        do_process_instruction_with_store(
+            queue_compliance_change(&mint2_key, Some(&compliance2_key)),
+            &mut store,
+        )
+        .unwrap();
+        assert_eq!(
+            Err(TokenError::AuthorityChangeTimelocked.into()),
+            do_process_instruction_with_store(
+                execute_authority_change(&program_id, &mint2_key).unwrap(),
+                &mut store,
+            )
+        );
+        store.insert(sysvar::clock::id(), clock_sysvar(2200));
+        do_process_instruction_with_store(
+            execute_authority_change(&program_id, &mint2_key).unwrap(),
+            &mut store,
         )
         .unwrap();
+        let mint = Mint::unpack(&store[&mint2_key].data).unwrap();
+        assert_eq!(mint.compliance_authority, COption::Some(compliance2_key));
     }
 
     #[test]
-    fn test_freeze_account() {
+    fn test_dust_protection() {
         let program_id = crate::id();
-        let account_key = Pubkey::new_unique();
-        let mut account_account = SolanaAccount::new(
-            account_minimum_balance(),
-            Account::get_packed_len(),
-            &program_id,
-        );
-        let account_owner_key = Pubkey::new_unique();
-        let mut account_owner_account = SolanaAccount::default();
         let owner_key = Pubkey::new_unique();
-        let mut owner_account = SolanaAccount::default();
         let owner2_key = Pubkey::new_unique();
-        let mut owner2_account = SolanaAccount::default();
         let mint_key = Pubkey::new_unique();
-        let mut mint_account =
-            SolanaAccount::new(mint_minimum_balance(), Mint::get_packed_len(), &program_id);
-        let mut rent_sysvar = rent_sysvar();
+        let account_key = Pubkey::new_unique();
+        let account2_key = Pubkey::new_unique();
+        let account3_key = Pubkey::new_unique();
+        let legacy_account_key = Pubkey::new_unique();
+        let mut store = HashMap::new();
+        store.insert(rent::id(), rent_sysvar());
+        store.insert(
+            mint_key,
+            SolanaAccount::new(
+                extended_mint_minimum_balance(),
+                Mint::EXTENDED_LEN,
+                &program_id,
+            ),
+        );
+        for key in &[account_key, account2_key, account3_key] {
+            store.insert(
+                *key,
+                SolanaAccount::new(
+                    extended_account_minimum_balance(),
+                    Account::EXTENDED_LEN,
+                    &program_id,
+                ),
+            );
+        }
 
-        // create new mint with owner different from account owner
-        do_process_instruction(
+        // create mint and accounts
+        do_process_instruction_with_store(
             initialize_mint(&program_id, &mint_key, &owner_key, None, 2).unwrap(),
-            vec![&mut mint_account, &mut rent_sysvar],
+            &mut store,
         )
         .unwrap();
+        for (key, owner) in &[
+            (account_key, owner_key),
+            (account2_key, owner2_key),
+            (account3_key, owner2_key),
+        ] {
+            do_process_instruction_with_store(
+                initialize_account(&program_id, key, &mint_key, owner).unwrap(),
+                &mut store,
+            )
+            .unwrap();
+        }
 
-        // create account
-        do_process_instruction(
-            initialize_account(&program_id, &account_key, &mint_key, &account_owner_key).unwrap(),
-            vec![
-                &mut account_account,
-                &mut mint_account,
-                &mut account_owner_account,
-                &mut rent_sysvar,
-            ],
+        // accounts of an extended mint must be extended too
+        store.insert(
+            legacy_account_key,
+            SolanaAccount::new(
+                account_minimum_balance(),
+                Account::get_packed_len(),
+                &program_id,
+            ),
+        );
+        assert_eq!(
+            Err(TokenError::ExtensionRequired.into()),
+            do_process_instruction_with_store(
+                initialize_account(&program_id, &legacy_account_key, &mint_key, &owner_key)
+                    .unwrap(),
+                &mut store,
+            )
+        );
+
+        // not the mint authority
+        assert_eq!(
+            Err(TokenError::OwnerMismatch.into()),
+            do_process_instruction_with_store(
+                set_min_transfer_amount(&program_id, &mint_key, &owner2_key, &[], 10).unwrap(),
+                &mut store,
+            )
+        );
+
+        // require transfers of at least 10
+        do_process_instruction_with_store(
+            set_min_transfer_amount(&program_id, &mint_key, &owner_key, &[], 10).unwrap(),
+            &mut store,
         )
         .unwrap();
-
-        // mint to account
-        do_process_instruction(
-            mint_to(&program_id, &mint_key, &account_key, &owner_key, &[], 1000).unwrap(),
-            vec![&mut mint_account, &mut account_account, &mut owner_account],
+        let mint = Mint::unpack(&store[&mint_key].data).unwrap();
+        assert_eq!(mint.min_transfer_amount, 10);
+        do_process_instruction_with_store(
+            mint_to(&program_id, &mint_key, &account_key, &owner_key, &[], 100).unwrap(),
+            &mut store,
         )
         .unwrap();
 
-        // mint cannot freeze
+        // the mint is required
         assert_eq!(
-            Err(TokenError::MintCannotFreeze.into()),
-            do_process_instruction(
-                freeze_account(&program_id, &account_key, &mint_key, &owner_key, &[]).unwrap(),
-                vec![&mut account_account, &mut mint_account, &mut owner_account],
+            Err(ProgramError::NotEnoughAccountKeys),
+            do_process_instruction_with_store(
+                transfer(
+                    &program_id,
+                    &account_key,
+                    &account2_key,
+                    &owner_key,
+                    &[],
+                    15
+                )
+                .unwrap(),
+                &mut store,
             )
         );
 
-        // missing freeze_authority
-        let mut mint = Mint::unpack_unchecked(&mint_account.data).unwrap();
-        mint.freeze_authority = COption::Some(owner_key);
-        Mint::pack(mint, &mut mint_account.data).unwrap();
+        // transfers below the minimum fail
         assert_eq!(
-            Err(TokenError::OwnerMismatch.into()),
-            do_process_instruction(
-                freeze_account(&program_id, &account_key, &mint_key, &owner2_key, &[]).unwrap(),
-                vec![&mut account_account, &mut mint_account, &mut owner2_account],
+            Err(TokenError::BelowMinimumTransfer.into()),
+            do_process_instruction_with_store(
+                with_mint(
+                    transfer(&program_id, &account_key, &account2_key, &owner_key, &[], 9).unwrap(),
+                    &mint_key,
+                ),
+                &mut store,
             )
         );
+        do_process_instruction_with_store(
+            with_mint(
+                transfer(
+                    &program_id,
+                    &account_key,
+                    &account2_key,
+                    &owner_key,
+                    &[],
+                    15,
+                )
+                .unwrap(),
+                &mint_key,
+            ),
+            &mut store,
+        )
+        .unwrap();
+        do_process_instruction_with_store(
+            with_mint(
+                transfer(
+                    &program_id,
+                    &account2_key,
+                    &account3_key,
+                    &owner2_key,
+                    &[],
+                    10,
+                )
+                .unwrap(),
+                &mint_key,
+            ),
+            &mut store,
+        )
+        .unwrap();
 
-        // check explicit thaw
+        // a smaller transfer may empty the source
         assert_eq!(
-            Err(TokenError::InvalidState.into()),
-            do_process_instruction(
-                thaw_account(&program_id, &account_key, &mint_key, &owner2_key, &[]).unwrap(),
-                vec![&mut account_account, &mut mint_account, &mut owner2_account],
+            Err(TokenError::BelowMinimumTransfer.into()),
+            do_process_instruction_with_store(
+                with_mint(
+                    transfer(
+                        &program_id,
+                        &account2_key,
+                        &account3_key,
+                        &owner2_key,
+                        &[],
+                        4
+                    )
+                    .unwrap(),
+                    &mint_key,
+                ),
+                &mut store,
             )
         );
-
-        // freeze
-        do_process_instruction(
-            freeze_account(&program_id, &account_key, &mint_key, &owner_key, &[]).unwrap(),
-            vec![&mut account_account, &mut mint_account, &mut owner_account],
+        do_process_instruction_with_store(
+            with_mint(
+                transfer(
+                    &program_id,
+                    &account2_key,
+                    &account3_key,
+                    &owner2_key,
+                    &[],
+                    5,
+                )
+                .unwrap(),
+                &mint_key,
+            ),
+            &mut store,
         )
         .unwrap();
-        let account = Account::unpack_unchecked(&account_account.data).unwrap();
-        assert_eq!(account.state, AccountState::Frozen);
+        let account = Account::unpack(&store[&account2_key].data).unwrap();
+        assert_eq!(account.amount, 0);
 
-        // check explicit freeze
+        // the minimum may be removed, and applies to existing holders when reintroduced
+        do_process_instruction_with_store(
+            set_min_transfer_amount(&program_id, &mint_key, &owner_key, &[], 0).unwrap(),
+            &mut store,
+        )
+        .unwrap();
+        do_process_instruction_with_store(
+            with_mint(
+                transfer(&program_id, &account_key, &account3_key, &owner_key, &[], 1).unwrap(),
+                &mint_key,
+            ),
+            &mut store,
+        )
+        .unwrap();
+        do_process_instruction_with_store(
+            set_min_transfer_amount(&program_id, &mint_key, &owner_key, &[], 10).unwrap(),
+            &mut store,
+        )
+        .unwrap();
         assert_eq!(
-            Err(TokenError::InvalidState.into()),
-            do_process_instruction(
-                freeze_account(&program_id, &account_key, &mint_key, &owner_key, &[]).unwrap(),
-                vec![&mut account_account, &mut mint_account, &mut owner_account],
+            Err(TokenError::BelowMinimumTransfer.into()),
+            do_process_instruction_with_store(
+                with_mint(
+                    transfer(&program_id, &account_key, &account3_key, &owner_key, &[], 1).unwrap(),
+                    &mint_key,
+                ),
+                &mut store,
             )
         );
 
-        // check thaw authority
+        // only the owner sets the account's incoming minimum
         assert_eq!(
             Err(TokenError::OwnerMismatch.into()),
-            do_process_instruction(
-                thaw_account(&program_id, &account_key, &mint_key, &owner2_key, &[]).unwrap(),
-                vec![&mut account_account, &mut mint_account, &mut owner2_account],
+            do_process_instruction_with_store(
+                set_min_incoming_amount(&program_id, &account3_key, &owner_key, &[], 20).unwrap(),
+                &mut store,
             )
         );
-
-        // thaw
-        do_process_instruction(
-            thaw_account(&program_id, &account_key, &mint_key, &owner_key, &[]).unwrap(),
-            vec![&mut account_account, &mut mint_account, &mut owner_account],
+        do_process_instruction_with_store(
+            set_min_incoming_amount(&program_id, &account3_key, &owner2_key, &[], 20).unwrap(),
+            &mut store,
         )
         .unwrap();
-        let account = Account::unpack_unchecked(&account_account.data).unwrap();
-        assert_eq!(account.state, AccountState::Initialized);
-    }
+        let account = Account::unpack(&store[&account3_key].data).unwrap();
+        assert_eq!(account.min_incoming_amount, 20);
 
-    #[test]
-    fn test_initialize_account2() {
-        let program_id = crate::id();
-        let account_key = Pubkey::new_unique();
-        let mut account_account = SolanaAccount::new(
-            account_minimum_balance(),
-            Account::get_packed_len(),
-            &program_id,
-        );
-        let mut account2_account = SolanaAccount::new(
-            account_minimum_balance(),
-            Account::get_packed_len(),
-            &program_id,
+        // smaller incoming transfers are rejected
+        assert_eq!(
+            Err(TokenError::BelowMinimumIncoming.into()),
+            do_process_instruction_with_store(
+                with_mint(
+                    transfer(
+                        &program_id,
+                        &account_key,
+                        &account3_key,
+                        &owner_key,
+                        &[],
+                        19
+                    )
+                    .unwrap(),
+                    &mint_key,
+                ),
+                &mut store,
+            )
         );
-        let owner_key = Pubkey::new_unique();
-        let mut owner_account = SolanaAccount::default();
-        let mint_key = Pubkey::new_unique();
-        let mut mint_account =
-            SolanaAccount::new(mint_minimum_balance(), Mint::get_packed_len(), &program_id);
-        let mut rent_sysvar = rent_sysvar();
-
-        // create mint
-        do_process_instruction(
-            initialize_mint(&program_id, &mint_key, &owner_key, None, 2).unwrap(),
-            vec![&mut mint_account, &mut rent_sysvar],
+        do_process_instruction_with_store(
+            with_mint(
+                transfer(
+                    &program_id,
+                    &account_key,
+                    &account3_key,
+                    &owner_key,
+                    &[],
+                    20,
+                )
+                .unwrap(),
+                &mint_key,
+            ),
+            &mut store,
         )
         .unwrap();
+        let account = Account::unpack(&store[&account3_key].data).unwrap();
+        assert_eq!(account.amount, 36);
 
-        do_process_instruction(
-            initialize_account(&program_id, &account_key, &mint_key, &owner_key).unwrap(),
-            vec![
-                &mut account_account,
-                &mut mint_account,
-                &mut owner_account,
-                &mut rent_sysvar,
-            ],
+        // held tokens are left out when emptying the source
+        do_process_instruction_with_store(
+            place_hold(
+                &program_id,
+                &account_key,
+                &owner_key,
+                &[],
+                &owner2_key,
+                60,
+                UnixTimestamp::MAX,
+            )
+            .unwrap(),
+            &mut store,
         )
         .unwrap();
-
-        do_process_instruction(
-            initialize_account2(&program_id, &account_key, &mint_key, &owner_key).unwrap(),
-            vec![&mut account2_account, &mut mint_account, &mut rent_sysvar],
+        do_process_instruction_with_store(
+            with_mint(
+                transfer(&program_id, &account_key, &account2_key, &owner_key, &[], 4).unwrap(),
+                &mint_key,
+            ),
+            &mut store,
         )
         .unwrap();
-
-        assert_eq!(account_account, account2_account);
+        let account = Account::unpack(&store[&account_key].data).unwrap();
+        assert_eq!(account.amount, 60);
+        assert_eq!(account.available_amount(), 0);
     }
 }
@@ -1,9 +1,13 @@
 //! State transition types
 
-use crate::instruction::MAX_SIGNERS;
+use crate::{
+    error::TokenError,
+    instruction::{is_valid_signer_index, MAX_SIGNERS},
+};
 use arrayref::{array_mut_ref, array_ref, array_refs, mut_array_refs};
 use num_enum::TryFromPrimitive;
 use solana_program::{
+    clock::UnixTimestamp,
     program_error::ProgramError,
     program_option::COption,
     program_pack::{IsInitialized, Pack, Sealed},
@@ -11,6 +15,9 @@ use solana_program::{
 };
 
 /// Mint data.
+///
+/// The fields following `freeze_authority` are stored in the extension, which only Mints allocated
+/// with `Mint::EXTENDED_LEN` bytes hold; other Mints read them as their defaults.
 #[repr(C)]
 #[derive(Clone, Copy, Debug, Default, PartialEq)]
 pub struct Mint {
@@ -26,6 +33,64 @@ pub struct Mint {
     pub is_initialized: bool,
     /// Optional authority to freeze token accounts.
     pub freeze_authority: COption<Pubkey>,
+    /// Optional authority permitted to burn this Mint's tokens from accounts owned by the mint
+    /// authority.
+    pub burner: COption<Pubkey>,
+    /// Optional authority permitted to pause and resume minting.
+    pub pauser: COption<Pubkey>,
+    /// Optional authority permitted to freeze and thaw accounts alongside the freeze authority.
+    pub freezer: COption<Pubkey>,
+    /// Is `true` if minting has been paused by the pauser
+    pub minting_paused: bool,
+    /// Mint authority proposed by the current mint authority, pending its acceptance.
+    pub pending_mint_authority: COption<Pubkey>,
+    /// Freeze authority proposed by the current freeze authority, pending its acceptance.
+    pub pending_freeze_authority: COption<Pubkey>,
+    /// Minimum delay, in seconds, between queueing a mint-level authority change and executing
+    /// it. If zero, mint-level authorities may also be changed immediately.
+    pub authority_timelock: u64,
+    /// The mint-level authority targeted by the queued change, if any.
+    pub queued_authority: TimelockedAuthority,
+    /// The authority that will be installed once the queued change executes.
+    pub queued_new_authority: COption<Pubkey>,
+    /// Earliest unix timestamp at which the queued change may execute.
+    pub queued_unlock_timestamp: UnixTimestamp,
+    /// If `max_balance` is `Some`, the most any account not exempted by the mint authority may
+    /// hold.
+    pub max_balance: COption<u64>,
+    /// Unix timestamp before which only accounts owned by the mint authority may transfer, or
+    /// zero if trading is open.
+    pub trading_start_timestamp: UnixTimestamp,
+    /// Minimum delay, in seconds, between outgoing transfers of accounts not owned by the mint
+    /// authority, or zero for none.
+    pub transfer_cooldown: u64,
+    /// Optional authority issuing the credentials holders need to send and receive tokens.
+    pub compliance_authority: COption<Pubkey>,
+    /// Smallest amount a transfer may move, unless it empties the source account, or zero for
+    /// none.
+    pub min_transfer_amount: u64,
+}
+impl Mint {
+    /// Length of a Mint carrying the extension: the base layout, a version byte and the
+    /// extension fields
+    pub const EXTENDED_LEN: usize = Self::LEN + 1 + 306;
+
+    /// Checks if `data_len` is the length of a Mint, with or without the extension
+    pub fn is_packed_len(data_len: usize) -> bool {
+        data_len == Self::LEN || data_len == Self::EXTENDED_LEN
+    }
+
+    fn has_extension_data(&self) -> bool {
+        *self
+            != Mint {
+                mint_authority: self.mint_authority,
+                supply: self.supply,
+                decimals: self.decimals,
+                is_initialized: self.is_initialized,
+                freeze_authority: self.freeze_authority,
+                ..Mint::default()
+            }
+    }
 }
 impl Sealed for Mint {}
 impl IsInitialized for Mint {
@@ -36,6 +101,7 @@ impl IsInitialized for Mint {
 impl Pack for Mint {
     const LEN: usize = 82;
     fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        let (src, extension) = src.split_at(Self::LEN);
         let src = array_ref![src, 0, 82];
         let (mint_authority, supply, decimals, is_initialized, freeze_authority) =
             array_refs![src, 36, 8, 1, 1, 36];
@@ -48,15 +114,58 @@ impl Pack for Mint {
             _ => return Err(ProgramError::InvalidAccountData),
         };
         let freeze_authority = unpack_coption_key(freeze_authority)?;
-        Ok(Mint {
+        let mut mint = Mint {
             mint_authority,
             supply,
             decimals,
             is_initialized,
             freeze_authority,
-        })
+            ..Mint::default()
+        };
+        if let Some(extension) = unpack_extension_version(extension)? {
+            let extension = array_ref![extension, 0, 306];
+            let (
+                burner,
+                pauser,
+                freezer,
+                minting_paused,
+                pending_mint_authority,
+                pending_freeze_authority,
+                authority_timelock,
+                queued_authority,
+                queued_new_authority,
+                queued_unlock_timestamp,
+                max_balance,
+                trading_start_timestamp,
+                transfer_cooldown,
+                compliance_authority,
+                min_transfer_amount,
+            ) = array_refs![extension, 36, 36, 36, 1, 36, 36, 8, 1, 36, 8, 12, 8, 8, 36, 8];
+            mint.burner = unpack_coption_key(burner)?;
+            mint.pauser = unpack_coption_key(pauser)?;
+            mint.freezer = unpack_coption_key(freezer)?;
+            mint.minting_paused = match minting_paused {
+                [0] => false,
+                [1] => true,
+                _ => return Err(ProgramError::InvalidAccountData),
+            };
+            mint.pending_mint_authority = unpack_coption_key(pending_mint_authority)?;
+            mint.pending_freeze_authority = unpack_coption_key(pending_freeze_authority)?;
+            mint.authority_timelock = u64::from_le_bytes(*authority_timelock);
+            mint.queued_authority = TimelockedAuthority::try_from_primitive(queued_authority[0])
+                .or(Err(ProgramError::InvalidAccountData))?;
+            mint.queued_new_authority = unpack_coption_key(queued_new_authority)?;
+            mint.queued_unlock_timestamp = UnixTimestamp::from_le_bytes(*queued_unlock_timestamp);
+            mint.max_balance = unpack_coption_u64(max_balance)?;
+            mint.trading_start_timestamp = UnixTimestamp::from_le_bytes(*trading_start_timestamp);
+            mint.transfer_cooldown = u64::from_le_bytes(*transfer_cooldown);
+            mint.compliance_authority = unpack_coption_key(compliance_authority)?;
+            mint.min_transfer_amount = u64::from_le_bytes(*min_transfer_amount);
+        }
+        Ok(mint)
     }
     fn pack_into_slice(&self, dst: &mut [u8]) {
+        let (dst, extension_dst) = dst.split_at_mut(Self::LEN);
         let dst = array_mut_ref![dst, 0, 82];
         let (
             mint_authority_dst,
@@ -71,16 +180,102 @@ impl Pack for Mint {
             decimals,
             is_initialized,
             ref freeze_authority,
+            ref burner,
+            ref pauser,
+            ref freezer,
+            minting_paused,
+            ref pending_mint_authority,
+            ref pending_freeze_authority,
+            authority_timelock,
+            queued_authority,
+            ref queued_new_authority,
+            queued_unlock_timestamp,
+            ref max_balance,
+            trading_start_timestamp,
+            transfer_cooldown,
+            ref compliance_authority,
+            min_transfer_amount,
         } = self;
         pack_coption_key(mint_authority, mint_authority_dst);
         *supply_dst = supply.to_le_bytes();
         decimals_dst[0] = decimals;
         is_initialized_dst[0] = is_initialized as u8;
         pack_coption_key(freeze_authority, freeze_authority_dst);
+        if let Some(extension_dst) = pack_extension_version(extension_dst) {
+            let extension_dst = array_mut_ref![extension_dst, 0, 306];
+            let (
+                burner_dst,
+                pauser_dst,
+                freezer_dst,
+                minting_paused_dst,
+                pending_mint_authority_dst,
+                pending_freeze_authority_dst,
+                authority_timelock_dst,
+                queued_authority_dst,
+                queued_new_authority_dst,
+                queued_unlock_timestamp_dst,
+                max_balance_dst,
+                trading_start_timestamp_dst,
+                transfer_cooldown_dst,
+                compliance_authority_dst,
+                min_transfer_amount_dst,
+            ) = mut_array_refs![
+                extension_dst,
+                36,
+                36,
+                36,
+                1,
+                36,
+                36,
+                8,
+                1,
+                36,
+                8,
+                12,
+                8,
+                8,
+                36,
+                8
+            ];
+            pack_coption_key(burner, burner_dst);
+            pack_coption_key(pauser, pauser_dst);
+            pack_coption_key(freezer, freezer_dst);
+            minting_paused_dst[0] = minting_paused as u8;
+            pack_coption_key(pending_mint_authority, pending_mint_authority_dst);
+            pack_coption_key(pending_freeze_authority, pending_freeze_authority_dst);
+            *authority_timelock_dst = authority_timelock.to_le_bytes();
+            queued_authority_dst[0] = queued_authority as u8;
+            pack_coption_key(queued_new_authority, queued_new_authority_dst);
+            *queued_unlock_timestamp_dst = queued_unlock_timestamp.to_le_bytes();
+            pack_coption_u64(max_balance, max_balance_dst);
+            *trading_start_timestamp_dst = trading_start_timestamp.to_le_bytes();
+            *transfer_cooldown_dst = transfer_cooldown.to_le_bytes();
+            pack_coption_key(compliance_authority, compliance_authority_dst);
+            *min_transfer_amount_dst = min_transfer_amount.to_le_bytes();
+        }
+    }
+    fn unpack_unchecked(input: &[u8]) -> Result<Self, ProgramError> {
+        if !Self::is_packed_len(input.len()) {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        Self::unpack_from_slice(input)
+    }
+    fn pack(src: Self, dst: &mut [u8]) -> Result<(), ProgramError> {
+        if !Self::is_packed_len(dst.len()) {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        if dst.len() == Self::LEN && src.has_extension_data() {
+            return Err(TokenError::ExtensionRequired.into());
+        }
+        src.pack_into_slice(dst);
+        Ok(())
     }
 }
 
 /// Account data.
+///
+/// The fields following `close_authority` are stored in the extension, which only Accounts allocated
+/// with `Account::EXTENDED_LEN` bytes hold; other Accounts read them as their defaults.
 #[repr(C)]
 #[derive(Clone, Copy, Debug, Default, PartialEq)]
 pub struct Account {
@@ -103,17 +298,131 @@ pub struct Account {
     pub delegated_amount: u64,
     /// Optional authority to close the account.
     pub close_authority: COption<Pubkey>,
+    /// Owner proposed by the current owner, pending its acceptance.
+    pub pending_owner: COption<Pubkey>,
+    /// Close authority proposed by the current close authority, pending its acceptance.
+    pub pending_close_authority: COption<Pubkey>,
+    /// Optional guardian authority, usually a multisignature account holding the guardian set
+    /// and threshold, permitted to initiate a recovery of the account's ownership.
+    pub guardians: COption<Pubkey>,
+    /// Delay, in seconds, during which the owner may veto a recovery initiated by the guardians.
+    pub recovery_delay: u64,
+    /// Owner proposed by the guardians, installed once the recovery delay elapses.
+    pub recovery_owner: COption<Pubkey>,
+    /// Earliest unix timestamp at which the pending recovery may complete.
+    pub recovery_unlock_timestamp: UnixTimestamp,
+    /// Unix timestamp at which the delegate's approval expires, or zero if it never expires.
+    pub delegate_expiry: UnixTimestamp,
+    /// Optional maximum amount the delegate may move in a single transfer or burn.
+    pub delegate_transfer_cap: COption<u64>,
+    /// Optional temporary key permitted to act for the owner within the session's scope.
+    pub session_key: COption<Pubkey>,
+    /// Unix timestamp at which the session key expires.
+    pub session_expiry: UnixTimestamp,
+    /// Bitmap of `SESSION_SCOPE_*` operations the session key may perform.
+    pub session_scope: u8,
+    /// Number of destinations the session key is limited to
+    pub num_session_destinations: u8,
+    /// If `num_session_destinations` is nonzero, the session key may only transfer to the first
+    /// `num_session_destinations` of these destinations.
+    pub session_destinations: [Pubkey; MAX_SESSION_DESTINATIONS],
+    /// The amount the session key may still transfer or burn.
+    pub session_remaining_amount: u64,
+    /// If `hold_capturer` is `Some`, `held_amount` is reserved for it to capture until
+    /// `hold_expiry`.
+    pub hold_capturer: COption<Pubkey>,
+    /// The amount reserved by the hold, unavailable to transfers and burns.
+    pub held_amount: u64,
+    /// Unix timestamp at which the hold expires.
+    pub hold_expiry: UnixTimestamp,
+    /// Reason code recorded by the freeze authority when freezing the account.
+    pub freeze_reason: u8,
+    /// Unix timestamp from which a frozen account behaves as thawed, or zero if the freeze
+    /// lasts until a `ThawAccount`.
+    pub auto_thaw_timestamp: UnixTimestamp,
+    /// If `spending_limit` is `Some`, the most the account may send or burn per spending window.
+    pub spending_limit: COption<u64>,
+    /// Length, in seconds, of the spending window.
+    pub spending_window: u64,
+    /// Unix timestamp at which the current spending window started.
+    pub spending_window_start: UnixTimestamp,
+    /// The amount sent or burned within the current spending window.
+    pub spending_window_spent: u64,
+    /// Raised spending limit, installed once the limit's unlock timestamp passes.
+    pub pending_spending_limit: COption<u64>,
+    /// Spending window accompanying the pending spending limit.
+    pub pending_spending_window: u64,
+    /// Earliest unix timestamp at which the pending spending limit takes effect.
+    pub spending_limit_unlock_timestamp: UnixTimestamp,
+    /// If `destination_allowlist` is `Some`, transfers may only go to the destinations it allows.
+    pub destination_allowlist: COption<Pubkey>,
+    /// Is `true` if the mint authority exempted the account from the mint's maximum balance.
+    pub max_balance_exempt: bool,
+    /// Unix timestamp of the account's last outgoing transfer under a transfer cooldown.
+    pub last_transfer_timestamp: UnixTimestamp,
+    /// Smallest amount the owner accepts in an incoming transfer, or zero for any.
+    pub min_incoming_amount: u64,
 }
 impl Account {
+    /// Length of an Account carrying the extension: the base layout, a version byte and the
+    /// extension fields
+    pub const EXTENDED_LEN: usize = Self::LEN + 1 + 540;
+
+    /// Checks if `data_len` is the length of a Account, with or without the extension
+    pub fn is_packed_len(data_len: usize) -> bool {
+        data_len == Self::LEN || data_len == Self::EXTENDED_LEN
+    }
+
     /// Checks if account is frozen
     pub fn is_frozen(&self) -> bool {
         self.state == AccountState::Frozen
     }
+    /// Checks if account may send or burn tokens
+    pub fn can_send(&self) -> bool {
+        self.state == AccountState::Initialized || self.state == AccountState::SendOnly
+    }
+    /// Checks if account may receive tokens
+    pub fn can_receive(&self) -> bool {
+        self.state == AccountState::Initialized || self.state == AccountState::ReceiveOnly
+    }
     /// Checks if account is native
     pub fn is_native(&self) -> bool {
         self.is_native.is_some()
     }
+    /// Checks if account has an outstanding hold
+    pub fn has_hold(&self) -> bool {
+        self.hold_capturer.is_some()
+    }
+    /// Checks if account is frozen with an auto-thaw timestamp that has passed
+    pub fn freeze_expired(&self, unix_timestamp: UnixTimestamp) -> bool {
+        self.is_frozen()
+            && self.auto_thaw_timestamp != 0
+            && unix_timestamp >= self.auto_thaw_timestamp
+    }
+    /// The amount not reserved by a hold
+    pub fn available_amount(&self) -> u64 {
+        self.amount.saturating_sub(self.held_amount)
+    }
+    /// The destinations the session key is limited to
+    pub fn session_destinations(&self) -> &[Pubkey] {
+        &self.session_destinations[..self.num_session_destinations as usize]
+    }
+    fn has_extension_data(&self) -> bool {
+        *self
+            != Account {
+                mint: self.mint,
+                owner: self.owner,
+                amount: self.amount,
+                delegate: self.delegate,
+                state: self.state,
+                is_native: self.is_native,
+                delegated_amount: self.delegated_amount,
+                close_authority: self.close_authority,
+                ..Account::default()
+            }
+    }
 }
+
 impl Sealed for Account {}
 impl IsInitialized for Account {
     fn is_initialized(&self) -> bool {
@@ -123,10 +432,11 @@ impl IsInitialized for Account {
 impl Pack for Account {
     const LEN: usize = 165;
     fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        let (src, extension) = src.split_at(Self::LEN);
         let src = array_ref![src, 0, 165];
         let (mint, owner, amount, delegate, state, is_native, delegated_amount, close_authority) =
             array_refs![src, 32, 32, 8, 36, 1, 12, 8, 36];
-        Ok(Account {
+        let mut account = Account {
             mint: Pubkey::new_from_array(*mint),
             owner: Pubkey::new_from_array(*owner),
             amount: u64::from_le_bytes(*amount),
@@ -136,9 +446,124 @@ impl Pack for Account {
             is_native: unpack_coption_u64(is_native)?,
             delegated_amount: u64::from_le_bytes(*delegated_amount),
             close_authority: unpack_coption_key(close_authority)?,
-        })
+            ..Account::default()
+        };
+        if let Some(extension) = unpack_extension_version(extension)? {
+            let extension = array_ref![extension, 0, 540];
+            let (
+                pending_owner,
+                pending_close_authority,
+                guardians,
+                recovery_delay,
+                recovery_owner,
+                recovery_unlock_timestamp,
+                delegate_expiry,
+                delegate_transfer_cap,
+                session_key,
+                session_expiry,
+                session_scope,
+                num_session_destinations,
+                session_destinations_flat,
+                session_remaining_amount,
+                hold_capturer,
+                held_amount,
+                hold_expiry,
+                freeze_reason,
+                auto_thaw_timestamp,
+                spending_limit,
+                spending_window,
+                spending_window_start,
+                spending_window_spent,
+                pending_spending_limit,
+                pending_spending_window,
+                spending_limit_unlock_timestamp,
+                destination_allowlist,
+                max_balance_exempt,
+                last_transfer_timestamp,
+                min_incoming_amount,
+            ) = array_refs![
+                extension,
+                36,
+                36,
+                36,
+                8,
+                36,
+                8,
+                8,
+                12,
+                36,
+                8,
+                1,
+                1,
+                32 * MAX_SESSION_DESTINATIONS,
+                8,
+                36,
+                8,
+                8,
+                1,
+                8,
+                12,
+                8,
+                8,
+                8,
+                12,
+                8,
+                8,
+                36,
+                1,
+                8,
+                8
+            ];
+            account.pending_owner = unpack_coption_key(pending_owner)?;
+            account.pending_close_authority = unpack_coption_key(pending_close_authority)?;
+            account.guardians = unpack_coption_key(guardians)?;
+            account.recovery_delay = u64::from_le_bytes(*recovery_delay);
+            account.recovery_owner = unpack_coption_key(recovery_owner)?;
+            account.recovery_unlock_timestamp =
+                UnixTimestamp::from_le_bytes(*recovery_unlock_timestamp);
+            account.delegate_expiry = UnixTimestamp::from_le_bytes(*delegate_expiry);
+            account.delegate_transfer_cap = unpack_coption_u64(delegate_transfer_cap)?;
+            account.session_key = unpack_coption_key(session_key)?;
+            account.session_expiry = UnixTimestamp::from_le_bytes(*session_expiry);
+            account.session_scope = session_scope[0];
+            account.num_session_destinations = num_session_destinations[0];
+            if account.num_session_destinations as usize > MAX_SESSION_DESTINATIONS {
+                return Err(ProgramError::InvalidAccountData);
+            }
+            for (src, dst) in session_destinations_flat
+                .chunks(32)
+                .zip(account.session_destinations.iter_mut())
+            {
+                *dst = Pubkey::new(src);
+            }
+            account.session_remaining_amount = u64::from_le_bytes(*session_remaining_amount);
+            account.hold_capturer = unpack_coption_key(hold_capturer)?;
+            account.held_amount = u64::from_le_bytes(*held_amount);
+            account.hold_expiry = UnixTimestamp::from_le_bytes(*hold_expiry);
+            account.freeze_reason = freeze_reason[0];
+            account.auto_thaw_timestamp = UnixTimestamp::from_le_bytes(*auto_thaw_timestamp);
+            account.spending_limit = unpack_coption_u64(spending_limit)?;
+            account.spending_window = u64::from_le_bytes(*spending_window);
+            account.spending_window_start = UnixTimestamp::from_le_bytes(*spending_window_start);
+            account.spending_window_spent = u64::from_le_bytes(*spending_window_spent);
+            account.pending_spending_limit = unpack_coption_u64(pending_spending_limit)?;
+            account.pending_spending_window = u64::from_le_bytes(*pending_spending_window);
+            account.spending_limit_unlock_timestamp =
+                UnixTimestamp::from_le_bytes(*spending_limit_unlock_timestamp);
+            account.destination_allowlist = unpack_coption_key(destination_allowlist)?;
+            account.max_balance_exempt = match max_balance_exempt {
+                [0] => false,
+                [1] => true,
+                _ => return Err(ProgramError::InvalidAccountData),
+            };
+            account.last_transfer_timestamp =
+                UnixTimestamp::from_le_bytes(*last_transfer_timestamp);
+            account.min_incoming_amount = u64::from_le_bytes(*min_incoming_amount);
+        }
+        Ok(account)
     }
     fn pack_into_slice(&self, dst: &mut [u8]) {
+        let (dst, extension_dst) = dst.split_at_mut(Self::LEN);
         let dst = array_mut_ref![dst, 0, 165];
         let (
             mint_dst,
@@ -159,6 +584,36 @@ impl Pack for Account {
             ref is_native,
             delegated_amount,
             ref close_authority,
+            ref pending_owner,
+            ref pending_close_authority,
+            ref guardians,
+            recovery_delay,
+            ref recovery_owner,
+            recovery_unlock_timestamp,
+            delegate_expiry,
+            ref delegate_transfer_cap,
+            ref session_key,
+            session_expiry,
+            session_scope,
+            num_session_destinations,
+            ref session_destinations,
+            session_remaining_amount,
+            ref hold_capturer,
+            held_amount,
+            hold_expiry,
+            freeze_reason,
+            auto_thaw_timestamp,
+            ref spending_limit,
+            spending_window,
+            spending_window_start,
+            spending_window_spent,
+            ref pending_spending_limit,
+            pending_spending_window,
+            spending_limit_unlock_timestamp,
+            ref destination_allowlist,
+            max_balance_exempt,
+            last_transfer_timestamp,
+            min_incoming_amount,
         } = self;
         mint_dst.copy_from_slice(mint.as_ref());
         owner_dst.copy_from_slice(owner.as_ref());
@@ -168,9 +623,138 @@ impl Pack for Account {
         pack_coption_u64(is_native, is_native_dst);
         *delegated_amount_dst = delegated_amount.to_le_bytes();
         pack_coption_key(close_authority, close_authority_dst);
+        if let Some(extension_dst) = pack_extension_version(extension_dst) {
+            let extension_dst = array_mut_ref![extension_dst, 0, 540];
+            let (
+                pending_owner_dst,
+                pending_close_authority_dst,
+                guardians_dst,
+                recovery_delay_dst,
+                recovery_owner_dst,
+                recovery_unlock_timestamp_dst,
+                delegate_expiry_dst,
+                delegate_transfer_cap_dst,
+                session_key_dst,
+                session_expiry_dst,
+                session_scope_dst,
+                num_session_destinations_dst,
+                session_destinations_flat,
+                session_remaining_amount_dst,
+                hold_capturer_dst,
+                held_amount_dst,
+                hold_expiry_dst,
+                freeze_reason_dst,
+                auto_thaw_timestamp_dst,
+                spending_limit_dst,
+                spending_window_dst,
+                spending_window_start_dst,
+                spending_window_spent_dst,
+                pending_spending_limit_dst,
+                pending_spending_window_dst,
+                spending_limit_unlock_timestamp_dst,
+                destination_allowlist_dst,
+                max_balance_exempt_dst,
+                last_transfer_timestamp_dst,
+                min_incoming_amount_dst,
+            ) = mut_array_refs![
+                extension_dst,
+                36,
+                36,
+                36,
+                8,
+                36,
+                8,
+                8,
+                12,
+                36,
+                8,
+                1,
+                1,
+                32 * MAX_SESSION_DESTINATIONS,
+                8,
+                36,
+                8,
+                8,
+                1,
+                8,
+                12,
+                8,
+                8,
+                8,
+                12,
+                8,
+                8,
+                36,
+                1,
+                8,
+                8
+            ];
+            pack_coption_key(pending_owner, pending_owner_dst);
+            pack_coption_key(pending_close_authority, pending_close_authority_dst);
+            pack_coption_key(guardians, guardians_dst);
+            *recovery_delay_dst = recovery_delay.to_le_bytes();
+            pack_coption_key(recovery_owner, recovery_owner_dst);
+            *recovery_unlock_timestamp_dst = recovery_unlock_timestamp.to_le_bytes();
+            *delegate_expiry_dst = delegate_expiry.to_le_bytes();
+            pack_coption_u64(delegate_transfer_cap, delegate_transfer_cap_dst);
+            pack_coption_key(session_key, session_key_dst);
+            *session_expiry_dst = session_expiry.to_le_bytes();
+            session_scope_dst[0] = session_scope;
+            num_session_destinations_dst[0] = num_session_destinations;
+            for (i, src) in session_destinations.iter().enumerate() {
+                let dst_array = array_mut_ref![session_destinations_flat, 32 * i, 32];
+                dst_array.copy_from_slice(src.as_ref());
+            }
+            *session_remaining_amount_dst = session_remaining_amount.to_le_bytes();
+            pack_coption_key(hold_capturer, hold_capturer_dst);
+            *held_amount_dst = held_amount.to_le_bytes();
+            *hold_expiry_dst = hold_expiry.to_le_bytes();
+            freeze_reason_dst[0] = freeze_reason;
+            *auto_thaw_timestamp_dst = auto_thaw_timestamp.to_le_bytes();
+            pack_coption_u64(spending_limit, spending_limit_dst);
+            *spending_window_dst = spending_window.to_le_bytes();
+            *spending_window_start_dst = spending_window_start.to_le_bytes();
+            *spending_window_spent_dst = spending_window_spent.to_le_bytes();
+            pack_coption_u64(pending_spending_limit, pending_spending_limit_dst);
+            *pending_spending_window_dst = pending_spending_window.to_le_bytes();
+            *spending_limit_unlock_timestamp_dst = spending_limit_unlock_timestamp.to_le_bytes();
+            pack_coption_key(destination_allowlist, destination_allowlist_dst);
+            max_balance_exempt_dst[0] = max_balance_exempt as u8;
+            *last_transfer_timestamp_dst = last_transfer_timestamp.to_le_bytes();
+            *min_incoming_amount_dst = min_incoming_amount.to_le_bytes();
+        }
+    }
+    fn unpack_unchecked(input: &[u8]) -> Result<Self, ProgramError> {
+        if !Self::is_packed_len(input.len()) {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        Self::unpack_from_slice(input)
+    }
+    fn pack(src: Self, dst: &mut [u8]) -> Result<(), ProgramError> {
+        if !Self::is_packed_len(dst.len()) {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        if dst.len() == Self::LEN && src.has_extension_data() {
+            return Err(TokenError::ExtensionRequired.into());
+        }
+        src.pack_into_slice(dst);
+        Ok(())
     }
 }
 
+/// Session key scope bit permitting transfers
+pub const SESSION_SCOPE_TRANSFER: u8 = 1;
+/// Session key scope bit permitting burns
+pub const SESSION_SCOPE_BURN: u8 = 1 << 1;
+/// Maximum number of destinations a session key may be limited to
+pub const MAX_SESSION_DESTINATIONS: usize = 4;
+
+/// Delay, in seconds, before a raised spending limit takes effect
+pub const SPENDING_LIMIT_INCREASE_DELAY: UnixTimestamp = 24 * 60 * 60;
+
+/// Delay, in seconds, before a destination added to an allowlist may receive transfers
+pub const DESTINATION_ALLOWLIST_DELAY: UnixTimestamp = 24 * 60 * 60;
+
 /// Account state.
 #[repr(u8)]
 #[derive(Clone, Copy, Debug, PartialEq, TryFromPrimitive)]
@@ -183,6 +767,12 @@ pub enum AccountState {
     /// Account has been frozen by the mint freeze authority. Neither the account owner nor
     /// the delegate are able to perform operations on this account.
     Frozen,
+    /// Account has been restricted by the mint freeze authority to receiving tokens; it cannot
+    /// send or burn them.
+    ReceiveOnly,
+    /// Account has been restricted by the mint freeze authority to sending and burning tokens;
+    /// it cannot receive them.
+    SendOnly,
 }
 
 impl Default for AccountState {
@@ -191,6 +781,32 @@ impl Default for AccountState {
     }
 }
 
+/// Mint-level authorities whose changes are subject to the Mint's authority timelock.
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, PartialEq, TryFromPrimitive)]
+pub enum TimelockedAuthority {
+    /// No authority change is queued
+    None,
+    /// The Mint's mint authority
+    MintTokens,
+    /// The Mint's freeze authority
+    FreezeAccount,
+    /// The Mint's burner
+    Burner,
+    /// The Mint's pauser
+    Pauser,
+    /// The Mint's freezer
+    Freezer,
+    /// The Mint's compliance authority
+    ComplianceAuthority,
+}
+
+impl Default for TimelockedAuthority {
+    fn default() -> Self {
+        TimelockedAuthority::None
+    }
+}
+
 /// Multisignature data.
 #[repr(C)]
 #[derive(Clone, Copy, Debug, Default, PartialEq)]
@@ -204,6 +820,23 @@ pub struct Multisig {
     /// Signer public keys
     pub signers: [Pubkey; MAX_SIGNERS],
 }
+impl Multisig {
+    /// Checks the multisignature for an unsafe configuration: signer counts out of bounds, a
+    /// threshold above the number of signers, a duplicated signer, whose signature counts more
+    /// than once toward the threshold, or the multisignature listed among its own signers.
+    pub fn verify_integrity(&self, multisig_key: &Pubkey) -> Result<(), ProgramError> {
+        if !is_valid_signer_index(self.n as usize) {
+            return Err(TokenError::InvalidNumberOfProvidedSigners.into());
+        }
+        if !is_valid_signer_index(self.m as usize) {
+            return Err(TokenError::InvalidNumberOfRequiredSigners.into());
+        }
+        if self.m > self.n {
+            return Err(TokenError::ThresholdExceedsSigners.into());
+        }
+        verify_signers(&self.signers[..self.n as usize], multisig_key)
+    }
+}
 impl Sealed for Multisig {}
 impl IsInitialized for Multisig {
     fn is_initialized(&self) -> bool {
@@ -245,7 +878,697 @@ impl Pack for Multisig {
     }
 }
 
+/// Weighted multisignature data.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct WeightedMultisig {
+    /// Total weight of the signatures required
+    pub threshold: u16,
+    /// Number of valid signers
+    pub n: u8,
+    /// Is `true` if this structure has been initialized
+    pub is_initialized: bool,
+    /// Signer public keys
+    pub signers: [Pubkey; MAX_SIGNERS],
+    /// Weight of each signer's signature
+    pub weights: [u8; MAX_SIGNERS],
+}
+impl WeightedMultisig {
+    /// Checks the weighted multisignature for an unsafe configuration: a signer count out of
+    /// bounds, a threshold of zero or above the signers' total weight, a duplicated signer, or the
+    /// multisignature listed among its own signers.
+    pub fn verify_integrity(&self, multisig_key: &Pubkey) -> Result<(), ProgramError> {
+        if !is_valid_signer_index(self.n as usize) {
+            return Err(TokenError::InvalidNumberOfProvidedSigners.into());
+        }
+        let total_weight: u16 = self.weights[..self.n as usize]
+            .iter()
+            .map(|weight| *weight as u16)
+            .sum();
+        if self.threshold == 0 || self.threshold > total_weight {
+            return Err(TokenError::InvalidWeightThreshold.into());
+        }
+        verify_signers(&self.signers[..self.n as usize], multisig_key)
+    }
+}
+impl Sealed for WeightedMultisig {}
+impl IsInitialized for WeightedMultisig {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+impl Pack for WeightedMultisig {
+    const LEN: usize = 2 + 1 + 1 + 32 * MAX_SIGNERS + MAX_SIGNERS;
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        let src = array_ref![src, 0, WeightedMultisig::LEN];
+        #[allow(clippy::ptr_offset_with_cast)]
+        let (threshold, n, is_initialized, signers_flat, weights) =
+            array_refs![src, 2, 1, 1, 32 * MAX_SIGNERS, MAX_SIGNERS];
+        let mut result = WeightedMultisig {
+            threshold: u16::from_le_bytes(*threshold),
+            n: n[0],
+            is_initialized: match is_initialized {
+                [0] => false,
+                [1] => true,
+                _ => return Err(ProgramError::InvalidAccountData),
+            },
+            signers: [Pubkey::new_from_array([0u8; 32]); MAX_SIGNERS],
+            weights: *weights,
+        };
+        for (src, dst) in signers_flat.chunks(32).zip(result.signers.iter_mut()) {
+            *dst = Pubkey::new(src);
+        }
+        Ok(result)
+    }
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        let dst = array_mut_ref![dst, 0, WeightedMultisig::LEN];
+        #[allow(clippy::ptr_offset_with_cast)]
+        let (threshold, n, is_initialized, signers_flat, weights) =
+            mut_array_refs![dst, 2, 1, 1, 32 * MAX_SIGNERS, MAX_SIGNERS];
+        *threshold = self.threshold.to_le_bytes();
+        *n = [self.n];
+        *is_initialized = [self.is_initialized as u8];
+        for (i, src) in self.signers.iter().enumerate() {
+            let dst_array = array_mut_ref![signers_flat, 32 * i, 32];
+            dst_array.copy_from_slice(src.as_ref());
+        }
+        *weights = self.weights;
+    }
+}
+
+/// Maximum length of the instruction data a multisignature proposal may hold
+pub const MAX_PROPOSAL_DATA_LEN: usize = 128;
+/// Maximum number of accounts a multisignature proposal's instruction may take
+pub const MAX_PROPOSAL_ACCOUNTS: usize = 8;
+
+/// Multisignature proposal state.
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, PartialEq, TryFromPrimitive)]
+pub enum ProposalState {
+    /// Proposal is not yet initialized
+    Uninitialized,
+    /// Proposal is collecting approvals
+    Pending,
+    /// Proposal's instruction is being executed
+    Executing,
+    /// Proposal's instruction has been executed
+    Executed,
+    /// Proposal has been cancelled
+    Cancelled,
+}
+
+impl Default for ProposalState {
+    fn default() -> Self {
+        ProposalState::Uninitialized
+    }
+}
+
+/// Multisignature proposal data: a token instruction with a multisignature as its authority,
+/// approved by the multisignature's signers across transactions.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct MultisigProposal {
+    /// The multisignature whose signers approve the proposal
+    pub multisig: Pubkey,
+    /// The signer that created the proposal, who may cancel it
+    pub proposer: Pubkey,
+    /// Unix timestamp after which the proposal can no longer be approved or executed
+    pub expiry: UnixTimestamp,
+    /// The proposal's state
+    pub state: ProposalState,
+    /// Number of approvals
+    pub num_approvals: u8,
+    /// Signers that approved the proposal
+    pub approvals: [Pubkey; MAX_SIGNERS],
+    /// Length of the instruction data
+    pub data_len: u16,
+    /// The serialized `TokenInstruction` to execute
+    pub data: [u8; MAX_PROPOSAL_DATA_LEN],
+    /// Number of accounts the instruction takes
+    pub num_accounts: u8,
+    /// The accounts the instruction takes, in order
+    pub accounts: [Pubkey; MAX_PROPOSAL_ACCOUNTS],
+}
+impl MultisigProposal {
+    /// The signers that approved the proposal
+    pub fn approvals(&self) -> &[Pubkey] {
+        &self.approvals[..self.num_approvals as usize]
+    }
+    /// The serialized `TokenInstruction` to execute
+    pub fn instruction_data(&self) -> &[u8] {
+        &self.data[..self.data_len as usize]
+    }
+    /// The accounts the instruction takes, in order
+    pub fn instruction_accounts(&self) -> &[Pubkey] {
+        &self.accounts[..self.num_accounts as usize]
+    }
+}
+impl Sealed for MultisigProposal {}
+impl IsInitialized for MultisigProposal {
+    fn is_initialized(&self) -> bool {
+        self.state != ProposalState::Uninitialized
+    }
+}
+impl Pack for MultisigProposal {
+    const LEN: usize = 32
+        + 32
+        + 8
+        + 1
+        + 1
+        + 32 * MAX_SIGNERS
+        + 2
+        + MAX_PROPOSAL_DATA_LEN
+        + 1
+        + 32 * MAX_PROPOSAL_ACCOUNTS;
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        let src = array_ref![src, 0, MultisigProposal::LEN];
+        #[allow(clippy::ptr_offset_with_cast)]
+        let (
+            multisig,
+            proposer,
+            expiry,
+            state,
+            num_approvals,
+            approvals_flat,
+            data_len,
+            data,
+            num_accounts,
+            accounts_flat,
+        ) = array_refs![
+            src,
+            32,
+            32,
+            8,
+            1,
+            1,
+            32 * MAX_SIGNERS,
+            2,
+            MAX_PROPOSAL_DATA_LEN,
+            1,
+            32 * MAX_PROPOSAL_ACCOUNTS
+        ];
+        let mut result = MultisigProposal {
+            multisig: Pubkey::new_from_array(*multisig),
+            proposer: Pubkey::new_from_array(*proposer),
+            expiry: UnixTimestamp::from_le_bytes(*expiry),
+            state: ProposalState::try_from_primitive(state[0])
+                .or(Err(ProgramError::InvalidAccountData))?,
+            num_approvals: num_approvals[0],
+            approvals: [Pubkey::new_from_array([0u8; 32]); MAX_SIGNERS],
+            data_len: u16::from_le_bytes(*data_len),
+            data: *data,
+            num_accounts: num_accounts[0],
+            accounts: [Pubkey::new_from_array([0u8; 32]); MAX_PROPOSAL_ACCOUNTS],
+        };
+        if result.num_approvals as usize > MAX_SIGNERS
+            || result.data_len as usize > MAX_PROPOSAL_DATA_LEN
+            || result.num_accounts as usize > MAX_PROPOSAL_ACCOUNTS
+        {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        for (src, dst) in approvals_flat.chunks(32).zip(result.approvals.iter_mut()) {
+            *dst = Pubkey::new(src);
+        }
+        for (src, dst) in accounts_flat.chunks(32).zip(result.accounts.iter_mut()) {
+            *dst = Pubkey::new(src);
+        }
+        Ok(result)
+    }
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        let dst = array_mut_ref![dst, 0, MultisigProposal::LEN];
+        #[allow(clippy::ptr_offset_with_cast)]
+        let (
+            multisig_dst,
+            proposer_dst,
+            expiry_dst,
+            state_dst,
+            num_approvals_dst,
+            approvals_flat,
+            data_len_dst,
+            data_dst,
+            num_accounts_dst,
+            accounts_flat,
+        ) = mut_array_refs![
+            dst,
+            32,
+            32,
+            8,
+            1,
+            1,
+            32 * MAX_SIGNERS,
+            2,
+            MAX_PROPOSAL_DATA_LEN,
+            1,
+            32 * MAX_PROPOSAL_ACCOUNTS
+        ];
+        multisig_dst.copy_from_slice(self.multisig.as_ref());
+        proposer_dst.copy_from_slice(self.proposer.as_ref());
+        *expiry_dst = self.expiry.to_le_bytes();
+        state_dst[0] = self.state as u8;
+        num_approvals_dst[0] = self.num_approvals;
+        for (i, src) in self.approvals.iter().enumerate() {
+            let dst_array = array_mut_ref![approvals_flat, 32 * i, 32];
+            dst_array.copy_from_slice(src.as_ref());
+        }
+        *data_len_dst = self.data_len.to_le_bytes();
+        *data_dst = self.data;
+        num_accounts_dst[0] = self.num_accounts;
+        for (i, src) in self.accounts.iter().enumerate() {
+            let dst_array = array_mut_ref![accounts_flat, 32 * i, 32];
+            dst_array.copy_from_slice(src.as_ref());
+        }
+    }
+}
+
+/// Minter data.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Minter {
+    /// The mint this minter may mint tokens of
+    pub mint: Pubkey,
+    /// The authority/multisignature permitted to mint against this minter's allowance
+    pub authority: Pubkey,
+    /// The amount of tokens this minter may still mint
+    pub allowance: u64,
+    /// Is `true` if this structure has been initialized
+    pub is_initialized: bool,
+}
+impl Sealed for Minter {}
+impl IsInitialized for Minter {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+impl Pack for Minter {
+    const LEN: usize = 73;
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        let src = array_ref![src, 0, 73];
+        let (mint, authority, allowance, is_initialized) = array_refs![src, 32, 32, 8, 1];
+        Ok(Minter {
+            mint: Pubkey::new_from_array(*mint),
+            authority: Pubkey::new_from_array(*authority),
+            allowance: u64::from_le_bytes(*allowance),
+            is_initialized: match is_initialized {
+                [0] => false,
+                [1] => true,
+                _ => return Err(ProgramError::InvalidAccountData),
+            },
+        })
+    }
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        let dst = array_mut_ref![dst, 0, 73];
+        let (mint_dst, authority_dst, allowance_dst, is_initialized_dst) =
+            mut_array_refs![dst, 32, 32, 8, 1];
+        let &Minter {
+            ref mint,
+            ref authority,
+            allowance,
+            is_initialized,
+        } = self;
+        mint_dst.copy_from_slice(mint.as_ref());
+        authority_dst.copy_from_slice(authority.as_ref());
+        *allowance_dst = allowance.to_le_bytes();
+        is_initialized_dst[0] = is_initialized as u8;
+    }
+}
+
+/// Maximum number of destinations an allowlist may hold
+pub const MAX_ALLOWED_DESTINATIONS: usize = 10;
+
+/// Destination allowlist data, restricting the destinations of a token account's transfers.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct DestinationAllowlist {
+    /// The token account whose transfers the allowlist restricts
+    pub account: Pubkey,
+    /// Is `true` if this structure has been initialized
+    pub is_initialized: bool,
+    /// Number of allowed destinations
+    pub n: u8,
+    /// Allowed destination token accounts or owners
+    pub destinations: [Pubkey; MAX_ALLOWED_DESTINATIONS],
+    /// Unix timestamp from which each destination may receive transfers
+    pub active_timestamps: [UnixTimestamp; MAX_ALLOWED_DESTINATIONS],
+}
+impl DestinationAllowlist {
+    /// Checks if the allowlist allows transfers to the destination account, or to accounts of
+    /// the destination owner, at `unix_timestamp`
+    pub fn allows(&self, account: &Pubkey, owner: &Pubkey, unix_timestamp: UnixTimestamp) -> bool {
+        self.destinations[..self.n as usize]
+            .iter()
+            .zip(self.active_timestamps.iter())
+            .any(|(destination, active_timestamp)| {
+                (destination == account || destination == owner)
+                    && unix_timestamp >= *active_timestamp
+            })
+    }
+}
+impl Sealed for DestinationAllowlist {}
+impl IsInitialized for DestinationAllowlist {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+impl Pack for DestinationAllowlist {
+    const LEN: usize = 434;
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        let src = array_ref![src, 0, 434];
+        #[allow(clippy::ptr_offset_with_cast)]
+        let (account, is_initialized, n, destinations_flat, active_timestamps_flat) = array_refs![
+            src,
+            32,
+            1,
+            1,
+            32 * MAX_ALLOWED_DESTINATIONS,
+            8 * MAX_ALLOWED_DESTINATIONS
+        ];
+        let mut result = DestinationAllowlist {
+            account: Pubkey::new_from_array(*account),
+            is_initialized: match is_initialized {
+                [0] => false,
+                [1] => true,
+                _ => return Err(ProgramError::InvalidAccountData),
+            },
+            n: n[0],
+            destinations: [Pubkey::new_from_array([0u8; 32]); MAX_ALLOWED_DESTINATIONS],
+            active_timestamps: [0; MAX_ALLOWED_DESTINATIONS],
+        };
+        if result.n as usize > MAX_ALLOWED_DESTINATIONS {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        for (src, dst) in destinations_flat
+            .chunks(32)
+            .zip(result.destinations.iter_mut())
+        {
+            *dst = Pubkey::new(src);
+        }
+        for (src, dst) in active_timestamps_flat
+            .chunks(8)
+            .zip(result.active_timestamps.iter_mut())
+        {
+            *dst = UnixTimestamp::from_le_bytes(*array_ref![src, 0, 8]);
+        }
+        Ok(result)
+    }
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        let dst = array_mut_ref![dst, 0, 434];
+        #[allow(clippy::ptr_offset_with_cast)]
+        let (account, is_initialized, n, destinations_flat, active_timestamps_flat) = mut_array_refs![
+            dst,
+            32,
+            1,
+            1,
+            32 * MAX_ALLOWED_DESTINATIONS,
+            8 * MAX_ALLOWED_DESTINATIONS
+        ];
+        account.copy_from_slice(self.account.as_ref());
+        *is_initialized = [self.is_initialized as u8];
+        *n = [self.n];
+        for (i, src) in self.destinations.iter().enumerate() {
+            let dst_array = array_mut_ref![destinations_flat, 32 * i, 32];
+            dst_array.copy_from_slice(src.as_ref());
+        }
+        for (i, src) in self.active_timestamps.iter().enumerate() {
+            let dst_array = array_mut_ref![active_timestamps_flat, 8 * i, 8];
+            *dst_array = src.to_le_bytes();
+        }
+    }
+}
+
+/// Allowance data, held at a program derived address keyed by token account and spender.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Allowance {
+    /// The token account the spender may transfer or burn from
+    pub account: Pubkey,
+    /// The authority/multisignature permitted to spend against this allowance
+    pub spender: Pubkey,
+    /// The token account owner that granted the allowance; it lapses once the account changes
+    /// owner
+    pub owner: Pubkey,
+    /// The amount of tokens the spender may still transfer or burn
+    pub amount: u64,
+    /// Is `true` if this structure has been initialized
+    pub is_initialized: bool,
+    /// Bump seed of the allowance's program derived address
+    pub bump_seed: u8,
+}
+impl Sealed for Allowance {}
+impl IsInitialized for Allowance {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+impl Pack for Allowance {
+    const LEN: usize = 106;
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        let src = array_ref![src, 0, 106];
+        let (account, spender, owner, amount, is_initialized, bump_seed) =
+            array_refs![src, 32, 32, 32, 8, 1, 1];
+        Ok(Allowance {
+            account: Pubkey::new_from_array(*account),
+            spender: Pubkey::new_from_array(*spender),
+            owner: Pubkey::new_from_array(*owner),
+            amount: u64::from_le_bytes(*amount),
+            is_initialized: match is_initialized {
+                [0] => false,
+                [1] => true,
+                _ => return Err(ProgramError::InvalidAccountData),
+            },
+            bump_seed: bump_seed[0],
+        })
+    }
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        let dst = array_mut_ref![dst, 0, 106];
+        let (account_dst, spender_dst, owner_dst, amount_dst, is_initialized_dst, bump_seed_dst) =
+            mut_array_refs![dst, 32, 32, 32, 8, 1, 1];
+        let &Allowance {
+            ref account,
+            ref spender,
+            ref owner,
+            amount,
+            is_initialized,
+            bump_seed,
+        } = self;
+        account_dst.copy_from_slice(account.as_ref());
+        spender_dst.copy_from_slice(spender.as_ref());
+        owner_dst.copy_from_slice(owner.as_ref());
+        *amount_dst = amount.to_le_bytes();
+        is_initialized_dst[0] = is_initialized as u8;
+        bump_seed_dst[0] = bump_seed;
+    }
+}
+
+/// Authorization nonce data, held at a program derived address keyed by owner. Off-chain signed
+/// authorizations must carry the current nonce, which is incremented each time one is used.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct AuthorizationNonce {
+    /// The owner whose signed authorizations this nonce protects
+    pub owner: Pubkey,
+    /// The nonce the owner's next signed authorization must carry
+    pub nonce: u64,
+    /// Is `true` if this structure has been initialized
+    pub is_initialized: bool,
+    /// Bump seed of the nonce's program derived address
+    pub bump_seed: u8,
+}
+impl Sealed for AuthorizationNonce {}
+impl IsInitialized for AuthorizationNonce {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+impl Pack for AuthorizationNonce {
+    const LEN: usize = 42;
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        let src = array_ref![src, 0, 42];
+        let (owner, nonce, is_initialized, bump_seed) = array_refs![src, 32, 8, 1, 1];
+        Ok(AuthorizationNonce {
+            owner: Pubkey::new_from_array(*owner),
+            nonce: u64::from_le_bytes(*nonce),
+            is_initialized: match is_initialized {
+                [0] => false,
+                [1] => true,
+                _ => return Err(ProgramError::InvalidAccountData),
+            },
+            bump_seed: bump_seed[0],
+        })
+    }
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        let dst = array_mut_ref![dst, 0, 42];
+        let (owner_dst, nonce_dst, is_initialized_dst, bump_seed_dst) =
+            mut_array_refs![dst, 32, 8, 1, 1];
+        let &AuthorizationNonce {
+            ref owner,
+            nonce,
+            is_initialized,
+            bump_seed,
+        } = self;
+        owner_dst.copy_from_slice(owner.as_ref());
+        *nonce_dst = nonce.to_le_bytes();
+        is_initialized_dst[0] = is_initialized as u8;
+        bump_seed_dst[0] = bump_seed;
+    }
+}
+
+/// Credential data, held at a program derived address keyed by mint and owner. Owners of a mint
+/// with a compliance authority need a valid credential to send and receive its tokens.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Credential {
+    /// The mint whose compliance authority issued the credential
+    pub mint: Pubkey,
+    /// The verified owner
+    pub owner: Pubkey,
+    /// Unix timestamp at which the credential expires
+    pub expiry: UnixTimestamp,
+    /// Jurisdiction code recorded by the compliance authority
+    pub jurisdiction: u16,
+    /// Is `true` if this structure has been initialized
+    pub is_initialized: bool,
+    /// Bump seed of the credential's program derived address
+    pub bump_seed: u8,
+}
+impl Credential {
+    /// Checks if the credential is valid at `unix_timestamp`
+    pub fn is_valid(&self, unix_timestamp: UnixTimestamp) -> bool {
+        self.is_initialized && unix_timestamp < self.expiry
+    }
+}
+impl Sealed for Credential {}
+impl IsInitialized for Credential {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+impl Pack for Credential {
+    const LEN: usize = 76;
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        let src = array_ref![src, 0, 76];
+        let (mint, owner, expiry, jurisdiction, is_initialized, bump_seed) =
+            array_refs![src, 32, 32, 8, 2, 1, 1];
+        Ok(Credential {
+            mint: Pubkey::new_from_array(*mint),
+            owner: Pubkey::new_from_array(*owner),
+            expiry: UnixTimestamp::from_le_bytes(*expiry),
+            jurisdiction: u16::from_le_bytes(*jurisdiction),
+            is_initialized: match is_initialized {
+                [0] => false,
+                [1] => true,
+                _ => return Err(ProgramError::InvalidAccountData),
+            },
+            bump_seed: bump_seed[0],
+        })
+    }
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        let dst = array_mut_ref![dst, 0, 76];
+        let (mint_dst, owner_dst, expiry_dst, jurisdiction_dst, is_initialized_dst, bump_seed_dst) =
+            mut_array_refs![dst, 32, 32, 8, 2, 1, 1];
+        let &Credential {
+            ref mint,
+            ref owner,
+            expiry,
+            jurisdiction,
+            is_initialized,
+            bump_seed,
+        } = self;
+        mint_dst.copy_from_slice(mint.as_ref());
+        owner_dst.copy_from_slice(owner.as_ref());
+        *expiry_dst = expiry.to_le_bytes();
+        *jurisdiction_dst = jurisdiction.to_le_bytes();
+        is_initialized_dst[0] = is_initialized as u8;
+        bump_seed_dst[0] = bump_seed;
+    }
+}
+
+/// A 20-byte Ethereum address.
+pub type EthereumAddress = [u8; 20];
+
+/// Ethereum owner data, held at a program derived address keyed by Ethereum address. Token
+/// accounts and multisignatures may name it as their owner, whose authority is then proven by a
+/// secp256k1 signature from the Ethereum address rather than a Solana signer.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct EthereumOwner {
+    /// The Ethereum address whose signatures carry this owner's authority
+    pub address: EthereumAddress,
+    /// The nonce the address's next signed authorization must carry
+    pub nonce: u64,
+    /// Is `true` if this structure has been initialized
+    pub is_initialized: bool,
+    /// Bump seed of the owner's program derived address
+    pub bump_seed: u8,
+}
+impl Sealed for EthereumOwner {}
+impl IsInitialized for EthereumOwner {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+impl Pack for EthereumOwner {
+    const LEN: usize = 30;
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        let src = array_ref![src, 0, 30];
+        let (address, nonce, is_initialized, bump_seed) = array_refs![src, 20, 8, 1, 1];
+        Ok(EthereumOwner {
+            address: *address,
+            nonce: u64::from_le_bytes(*nonce),
+            is_initialized: match is_initialized {
+                [0] => false,
+                [1] => true,
+                _ => return Err(ProgramError::InvalidAccountData),
+            },
+            bump_seed: bump_seed[0],
+        })
+    }
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        let dst = array_mut_ref![dst, 0, 30];
+        let (address_dst, nonce_dst, is_initialized_dst, bump_seed_dst) =
+            mut_array_refs![dst, 20, 8, 1, 1];
+        let &EthereumOwner {
+            ref address,
+            nonce,
+            is_initialized,
+            bump_seed,
+        } = self;
+        address_dst.copy_from_slice(address);
+        *nonce_dst = nonce.to_le_bytes();
+        is_initialized_dst[0] = is_initialized as u8;
+        bump_seed_dst[0] = bump_seed;
+    }
+}
+
 // Helpers
+fn verify_signers(signers: &[Pubkey], multisig_key: &Pubkey) -> Result<(), ProgramError> {
+    for (i, signer) in signers.iter().enumerate() {
+        if signer == multisig_key {
+            return Err(TokenError::MultisigSelfSigner.into());
+        }
+        if signers[..i].contains(signer) {
+            return Err(TokenError::DuplicateSigner.into());
+        }
+    }
+    Ok(())
+}
+
+/// Version of the extension layout following the base layout of extended Mints and Accounts
+pub const EXTENSION_VERSION: u8 = 1;
+
+// Splits off the version byte of an extension, returning the extension fields if they have been
+// written; a zeroed version byte marks an extension not yet written, read as all defaults
+fn unpack_extension_version(src: &[u8]) -> Result<Option<&[u8]>, ProgramError> {
+    match src.split_first() {
+        None | Some((0, _)) => Ok(None),
+        Some((&EXTENSION_VERSION, extension)) => Ok(Some(extension)),
+        Some(_) => Err(ProgramError::InvalidAccountData),
+    }
+}
+fn pack_extension_version(dst: &mut [u8]) -> Option<&mut [u8]> {
+    let (version_dst, extension_dst) = dst.split_first_mut()?;
+    *version_dst = EXTENSION_VERSION;
+    Some(extension_dst)
+}
 fn pack_coption_key(src: &COption<Pubkey>, dst: &mut [u8; 36]) {
     let (tag, body) = mut_array_refs![dst, 4, 32];
     match src {